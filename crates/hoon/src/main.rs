@@ -2,6 +2,7 @@
 use clap::Parser;
 use tracing::info;
 use zkvm_jetpack::hot::produce_prover_hot_state;
+use zkvm_jetpack::instrument;
 
 #[tokio::main]
 pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -12,5 +13,9 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     info!("NOTE: save is set to: {:?}", cli.out_dir);
     let prover_hot_state = produce_prover_hot_state();
-    hoon::run(cli, &prover_hot_state).await
+    let result = hoon::run(cli, &prover_hot_state).await;
+    if instrument::enabled() {
+        instrument::log_report();
+    }
+    result
 }
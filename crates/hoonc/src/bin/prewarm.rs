@@ -1,6 +1,5 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
 
 use clap::Parser;
 use hoonc::Error;
@@ -9,7 +8,7 @@ use nockapp::kernel::boot::{self, default_boot_cli};
 use nockapp::kernel::form::LoadState;
 use nockapp::noun::slab::{NockJammer, NounSlab};
 use nockapp::one_punch::OnePunchWire;
-use nockapp::save::JammedCheckpoint;
+use nockapp::save::{SaveableCheckpoint, Saver};
 use nockapp::wire::Wire;
 use nockapp::{exit_driver, file_driver, AtomExt};
 use nockvm::noun::{Atom, D, T};
@@ -55,8 +54,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     nockapp.save_blocking().await?;
 
     let checkpoints_dir = base_data_dir.join("hoonc").join("checkpoints");
-    let latest_checkpoint = find_latest_checkpoint(&checkpoints_dir)?;
-    let exported = checkpoint_to_exported_state(&latest_checkpoint)?;
+    let exported = checkpoint_to_exported_state(&checkpoints_dir).await?;
 
     if let Some(parent) = args.output.parent() {
         fs::create_dir_all(parent)?;
@@ -65,7 +63,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!(
         "Wrote prewarmed kernel state from {} to {}",
-        latest_checkpoint.display(),
+        checkpoints_dir.display(),
         args.output.display()
     );
 
@@ -106,43 +104,19 @@ async fn run_boot_poke(nockapp: &mut nockapp::NockApp<NockJammer>) -> Result<(),
         .map_err(|e| -> Error { Box::new(e) })
 }
 
-fn find_latest_checkpoint(dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let mut latest: Option<(SystemTime, PathBuf)> = None;
-
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        if !entry.file_type()?.is_file() {
-            continue;
-        }
-        let metadata = entry.metadata()?;
-        let modified = metadata.modified()?;
-        if latest
-            .as_ref()
-            .map(|(time, _)| modified > *time)
-            .unwrap_or(true)
-        {
-            latest = Some((modified, entry.path()));
-        }
-    }
-
-    latest
-        .map(|(_, path)| path)
-        .ok_or_else(|| format!("No checkpoint found in {}", dir.display()).into())
-}
-
-fn checkpoint_to_exported_state(path: &Path) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let bytes = fs::read(path)?;
-    let checkpoint = JammedCheckpoint::decode_from_bytes(&bytes)
-        .map_err(|e| -> Box<dyn std::error::Error> { Box::new(e) })?;
-
-    let mut kernel_slab = NounSlab::<NockJammer>::new();
-    let kernel_root = kernel_slab.cue_into(checkpoint.state_jam.0.clone())?;
-    kernel_slab.set_root(kernel_root);
+async fn checkpoint_to_exported_state(
+    checkpoints_dir: &Path,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (_, checkpoint) =
+        Saver::<NockJammer>::try_load::<SaveableCheckpoint>(&checkpoints_dir.to_path_buf(), None)
+            .await?;
+    let checkpoint = checkpoint
+        .ok_or_else(|| format!("No checkpoint found in {}", checkpoints_dir.display()))?;
 
     let load_state = LoadState {
         ker_hash: checkpoint.ker_hash,
         event_num: checkpoint.event_num,
-        kernel_state: kernel_slab,
+        kernel_state: checkpoint.state,
     };
     let exported = ExportedState::from_loadstate(load_state);
     Ok(exported.encode()?)
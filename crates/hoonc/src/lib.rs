@@ -4,6 +4,7 @@ use std::io::Write;
 use std::path::PathBuf;
 
 use clap::{ColorChoice, Parser};
+use nockapp::data_dir::DataDir;
 use nockapp::driver::Operation;
 use nockapp::kernel::boot::{self, default_boot_cli, Cli as BootCli};
 use nockapp::noun::slab::{Jammer, NockJammer, NounSlab};
@@ -225,11 +226,10 @@ async fn initialize_hoonc_inner<J: Jammer + Send + 'static>(
 ) -> Result<(nockapp::NockApp<J>, PathBuf), Error> {
     debug!("Dependencies directory: {:?}", deps_dir);
     debug!("Entry file: {:?}", entry);
-    let data_dir = system_data_dir();
+    let data_dir = boot_cli.data_dir.clone().unwrap_or_else(system_data_dir);
     let mut boot_cli = boot_cli;
     let disable_prewarm = std::env::var("HOONC_DISABLE_PREWARM").is_ok();
-    let hoonc_data_dir = data_dir.join("hoonc");
-    let checkpoints_dir = hoonc_data_dir.join("checkpoints");
+    let checkpoints_dir = DataDir::new(&data_dir).checkpoints("hoonc");
     let has_existing_checkpoint = checkpoints_dir.exists()
         && std::fs::read_dir(&checkpoints_dir)
             .map(|entries| {
@@ -0,0 +1,110 @@
+//! Kafka backend. Publishes wait for every in-sync replica, and messages are consumed in order,
+//! their offsets stored for the next commit only once they have been handled or rejected.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::Message;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error, warn};
+
+use super::{BusError, Delivery, InboundMessage, MessageBus};
+
+/// How long a publish may wait in the producer's queue
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub(super) struct KafkaBus {
+    producer: FutureProducer,
+    consumer: Arc<StreamConsumer>,
+}
+
+impl KafkaBus {
+    pub(super) fn connect(brokers: &str, group_id: &str) -> Result<Self, BusError> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("acks", "all")
+            .set("enable.idempotence", "true")
+            .create()
+            .map_err(|e| BusError::Connect(e.to_string()))?;
+        let consumer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("group.id", group_id)
+            .set("enable.auto.commit", "true")
+            // Offsets are stored by hand once a message is dealt with, and committed from there
+            .set("enable.auto.offset.store", "false")
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .map_err(|e| BusError::Connect(e.to_string()))?;
+        Ok(Self {
+            producer,
+            consumer: Arc::new(consumer),
+        })
+    }
+}
+
+#[async_trait]
+impl MessageBus for KafkaBus {
+    async fn publish(&self, subject: &str, payload: Bytes) -> Result<(), BusError> {
+        let record: FutureRecord<'_, (), [u8]> = FutureRecord::to(subject).payload(&payload[..]);
+        self.producer
+            .send(record, QUEUE_TIMEOUT)
+            .await
+            .map_err(|(e, _)| BusError::Publish {
+                subject: subject.to_string(),
+                reason: e.to_string(),
+            })?;
+        Ok(())
+    }
+
+    async fn subscribe(
+        &self,
+        subjects: &[String],
+        inbound: mpsc::Sender<InboundMessage>,
+    ) -> Result<(), BusError> {
+        let topics: Vec<&str> = subjects.iter().map(String::as_str).collect();
+        self.consumer
+            .subscribe(&topics)
+            .map_err(|e| BusError::Subscribe(e.to_string()))?;
+
+        let consumer = self.consumer.clone();
+        tokio::spawn(async move {
+            loop {
+                let message = match consumer.recv().await {
+                    Ok(message) => message,
+                    Err(e) => {
+                        warn!("bus: error receiving from Kafka: {}", e);
+                        continue;
+                    }
+                };
+                let (done, delivery) = oneshot::channel();
+                let sent = inbound
+                    .send(InboundMessage {
+                        subject: message.topic().to_string(),
+                        payload: Bytes::copy_from_slice(message.payload().unwrap_or_default()),
+                        done,
+                    })
+                    .await;
+                if sent.is_err() {
+                    break;
+                }
+                // Without a stored offset the message is consumed again after a restart. Later
+                // messages are held back until this one is answered, so nothing gets skipped.
+                match delivery.await {
+                    Ok(Delivery::Handled | Delivery::Rejected) => {
+                        if let Err(e) = consumer.store_offset_from_message(&message) {
+                            error!("bus: failed to store Kafka offset: {}", e);
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+            debug!("bus: Kafka consumer stopped");
+        });
+        Ok(())
+    }
+}
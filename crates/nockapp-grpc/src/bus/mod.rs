@@ -0,0 +1,547 @@
+//! Bridge between a NockApp and an external message bus (NATS JetStream or Kafka).
+//!
+//! The kernel publishes with `[%bus %publish subject=@t data=*]` effects, and every message on a
+//! subscribed subject is poked in as `[%bus %message subject=@t data=*]`. Inbound messages are
+//! only acknowledged to the bus once the poke has been acked or nacked by the kernel, so a
+//! message that was in flight when the app went down is delivered again: handling is
+//! at-least-once, and kernels should tolerate seeing a message twice. A message the kernel
+//! nacks is not redelivered.
+//!
+//! ## Formats
+//! With `format = "jam"` a message is the jam of `data`. With `format = "json"` it is an
+//! envelope, `{"subject": .., "payload": <base64 jam>}` going out, and
+//! `{"wire": {"source": .., "version": .., "tags": [..]}, "payload": <base64 jam>}` coming in,
+//! the optional `wire` choosing the wire the message is poked on, as for the gRPC `Poke` call.
+//!
+//! ## Configuration
+//! ```toml
+//! format = "jam"
+//! subscribe = ["orders.>"]
+//!
+//! [backend]
+//! kind = "nats"
+//! url = "nats://localhost:4222"
+//! stream = "ORDERS"      # JetStream stream holding the subscribed subjects
+//! consumer = "nockapp"   # durable consumer name
+//! ```
+//! or, for Kafka, where subjects are topics:
+//! ```toml
+//! [backend]
+//! kind = "kafka"
+//! brokers = "localhost:9092"
+//! group_id = "nockapp"
+//! ```
+//! Each backend needs its cargo feature, `nats` or `kafka`.
+
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "nats")]
+mod nats;
+
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bytes::Bytes;
+use nockapp::driver::{make_driver, IODriverFn, NockAppHandle, PokeResult};
+use nockapp::noun::slab::NounSlab;
+use nockapp::wire::{Wire as _, WireRepr};
+use nockapp::{AtomExt, NockAppError};
+use nockvm::noun::{Atom, Noun, D, T};
+use nockvm_macros::tas;
+use noun_serde::{NounDecode, NounDecodeError};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error, info, warn};
+
+use crate::pb::common::v1::{wire_tag, Wire, WireTag};
+use crate::wire_conversion::grpc_wire_to_nockapp;
+
+/// Messages a backend may have handed over before it waits for the driver to catch up
+const INBOUND_QUEUE: usize = 64;
+
+/// Attempts at publishing an effect before it is dropped
+const PUBLISH_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry of a failed publish, doubling with each attempt
+const PUBLISH_BACKOFF: Duration = Duration::from_millis(200);
+
+#[derive(Debug, thiserror::Error)]
+pub enum BusError {
+    #[error("Failed to read bus config {path}: {source}")]
+    ReadConfig {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Invalid bus config: {0}")]
+    Config(String),
+    #[error("{0} support is not compiled in, enable the `{1}` feature")]
+    Unsupported(&'static str, &'static str),
+    #[error("Failed to connect to the message bus: {0}")]
+    Connect(String),
+    #[error("Failed to publish to {subject}: {reason}")]
+    Publish { subject: String, reason: String },
+    #[error("Failed to subscribe: {0}")]
+    Subscribe(String),
+    #[error("Invalid message on {subject}: {reason}")]
+    Decode { subject: String, reason: String },
+}
+
+impl From<BusError> for NockAppError {
+    fn from(err: BusError) -> Self {
+        NockAppError::OtherError(err.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BusFormat {
+    #[default]
+    Jam,
+    Json,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum BackendConfig {
+    Nats {
+        url: String,
+        stream: String,
+        consumer: String,
+    },
+    Kafka {
+        brokers: String,
+        group_id: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BusConfig {
+    pub backend: BackendConfig,
+    #[serde(default)]
+    pub format: BusFormat,
+    /// Subjects (Kafka topics) whose messages are poked into the kernel
+    #[serde(default)]
+    pub subscribe: Vec<String>,
+}
+
+impl BusConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BusError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| BusError::ReadConfig {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, BusError> {
+        toml::from_str(text).map_err(|e| BusError::Config(e.to_string()))
+    }
+}
+
+/// What the driver made of an inbound message, which the backend acknowledges accordingly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// The kernel acked the poke
+    Handled,
+    /// The message was malformed or the kernel nacked it; it won't be redelivered
+    Rejected,
+}
+
+/// A message received from the bus, waiting on `done` to be acknowledged.
+pub struct InboundMessage {
+    pub subject: String,
+    pub payload: Bytes,
+    pub done: oneshot::Sender<Delivery>,
+}
+
+#[async_trait]
+pub trait MessageBus: Send + Sync {
+    /// Publish `payload` to `subject`, returning once the bus has accepted it.
+    async fn publish(&self, subject: &str, payload: Bytes) -> Result<(), BusError>;
+
+    /// Start delivering messages on `subjects` to `inbound`. A message whose `done` is dropped
+    /// unanswered must be delivered again later.
+    async fn subscribe(
+        &self,
+        subjects: &[String],
+        inbound: mpsc::Sender<InboundMessage>,
+    ) -> Result<(), BusError>;
+}
+
+async fn connect(backend: &BackendConfig) -> Result<Box<dyn MessageBus>, BusError> {
+    match backend {
+        #[cfg(feature = "nats")]
+        BackendConfig::Nats {
+            url,
+            stream,
+            consumer,
+        } => Ok(Box::new(
+            nats::NatsBus::connect(url, stream, consumer).await?,
+        )),
+        #[cfg(not(feature = "nats"))]
+        BackendConfig::Nats { .. } => Err(BusError::Unsupported("NATS", "nats")),
+        #[cfg(feature = "kafka")]
+        BackendConfig::Kafka { brokers, group_id } => {
+            Ok(Box::new(kafka::KafkaBus::connect(brokers, group_id)?))
+        }
+        #[cfg(not(feature = "kafka"))]
+        BackendConfig::Kafka { .. } => Err(BusError::Unsupported("Kafka", "kafka")),
+    }
+}
+
+pub enum BusWire {
+    Message(String),
+}
+
+impl nockapp::wire::Wire for BusWire {
+    const VERSION: u64 = 1;
+    const SOURCE: &'static str = "bus";
+
+    fn to_wire(&self) -> WireRepr {
+        let tags = match self {
+            BusWire::Message(subject) => vec!["message".into(), subject.as_str().into()],
+        };
+        WireRepr::new(BusWire::SOURCE, BusWire::VERSION, tags)
+    }
+}
+
+/// Pull the subject and data out of the tail of a `[%bus %publish subject data]` effect.
+fn decode_publish(effect: Noun) -> Result<(String, NounSlab), NounDecodeError> {
+    let effect = effect.as_cell()?;
+    if !unsafe { effect.head().raw_equals(&D(tas!(b"publish"))) } {
+        return Err(NounDecodeError::InvalidTag);
+    }
+    let body = effect.tail().as_cell()?;
+    let subject = String::from_noun(&body.head())?;
+    let mut data = NounSlab::new();
+    data.copy_into(body.tail());
+    Ok((subject, data))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OutboundEnvelope {
+    subject: String,
+    payload: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InboundEnvelope {
+    #[serde(default)]
+    wire: Option<JsonWire>,
+    payload: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonWire {
+    source: String,
+    #[serde(default = "default_wire_version")]
+    version: u64,
+    #[serde(default)]
+    tags: Vec<JsonWireTag>,
+}
+
+fn default_wire_version() -> u64 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonWireTag {
+    Number(u64),
+    Text(String),
+}
+
+impl From<JsonWire> for Wire {
+    fn from(wire: JsonWire) -> Self {
+        Wire {
+            source: wire.source,
+            version: wire.version,
+            tags: wire
+                .tags
+                .into_iter()
+                .map(|tag| WireTag {
+                    value: Some(match tag {
+                        JsonWireTag::Number(n) => wire_tag::Value::Number(n),
+                        JsonWireTag::Text(s) => wire_tag::Value::Text(s),
+                    }),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Serialize the data of a publish effect for the bus.
+fn encode_message(format: BusFormat, subject: &str, data: &NounSlab) -> Bytes {
+    let jam = data.jam();
+    match format {
+        BusFormat::Jam => jam,
+        BusFormat::Json => {
+            let envelope = OutboundEnvelope {
+                subject: subject.to_string(),
+                payload: BASE64.encode(&jam),
+            };
+            Bytes::from(serde_json::to_vec(&envelope).expect("envelope serializes"))
+        }
+    }
+}
+
+/// Turn a message from the bus into the wire and cause to poke with.
+fn decode_message(
+    format: BusFormat,
+    subject: &str,
+    payload: Bytes,
+) -> Result<(WireRepr, NounSlab), BusError> {
+    let invalid = |reason: String| BusError::Decode {
+        subject: subject.to_string(),
+        reason,
+    };
+    let (wire, jam) = match format {
+        BusFormat::Jam => (None, payload),
+        BusFormat::Json => {
+            let envelope: InboundEnvelope =
+                serde_json::from_slice(&payload).map_err(|e| invalid(e.to_string()))?;
+            let jam = BASE64
+                .decode(envelope.payload)
+                .map_err(|e| invalid(format!("bad payload: {}", e)))?;
+            let wire = envelope
+                .wire
+                .map(|wire| grpc_wire_to_nockapp(&wire.into()))
+                .transpose()
+                .map_err(|e| invalid(e.to_string()))?;
+            (wire, Bytes::from(jam))
+        }
+    };
+
+    let mut slab = NounSlab::new();
+    let data = slab
+        .cue_into(jam)
+        .map_err(|e| invalid(format!("bad jam: {:?}", e)))?;
+    let subject_atom =
+        Atom::from_value(&mut slab, subject).map_err(|e| invalid(format!("{:?}", e)))?;
+    let cause = T(
+        &mut slab,
+        &[D(tas!(b"bus")), D(tas!(b"message")), subject_atom.as_noun(), data],
+    );
+    slab.set_root(cause);
+    let wire = wire.unwrap_or_else(|| BusWire::Message(subject.to_string()).to_wire());
+    Ok((wire, slab))
+}
+
+async fn publish_with_retry(bus: &dyn MessageBus, subject: &str, payload: Bytes) {
+    let mut backoff = PUBLISH_BACKOFF;
+    for attempt in 1..=PUBLISH_ATTEMPTS {
+        match bus.publish(subject, payload.clone()).await {
+            Ok(()) => return,
+            Err(e) if attempt < PUBLISH_ATTEMPTS => {
+                warn!("bus: {} (attempt {}), retrying", e, attempt);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(e) => {
+                error!(
+                    "bus: giving up on message to {} after {} attempts: {}",
+                    subject, attempt, e
+                );
+            }
+        }
+    }
+}
+
+async fn deliver(handle: &NockAppHandle, format: BusFormat, message: InboundMessage) {
+    let InboundMessage {
+        subject,
+        payload,
+        done,
+    } = message;
+    let (wire, cause) = match decode_message(format, &subject, payload) {
+        Ok(poke) => poke,
+        Err(e) => {
+            warn!("bus: rejecting message: {}", e);
+            let _ = done.send(Delivery::Rejected);
+            return;
+        }
+    };
+    match handle.poke(wire, cause).await {
+        Ok(PokeResult::Ack) => {
+            let _ = done.send(Delivery::Handled);
+        }
+        Ok(PokeResult::Nack) => {
+            warn!("bus: kernel nacked message on {}", subject);
+            let _ = done.send(Delivery::Rejected);
+        }
+        // Leave the message unacknowledged so it is delivered again
+        Err(e) => error!("bus: failed to poke message on {}: {}", subject, e),
+    }
+}
+
+/// Message bus IO driver, connecting the kernel to NATS JetStream or Kafka as set out in
+/// `config`. See the [module docs](self) for the effects, pokes and message formats.
+///
+/// ## Effects
+/// `[%bus %publish subject=@t data=*]`
+/// publishes `data` to `subject`, retrying for a while if the bus doesn't accept it
+///
+/// ## Pokes
+/// `[%bus %message subject=@t data=*]`
+/// for each message on a subscribed subject, on `/bus/1/message/<subject>` unless a JSON
+/// envelope names another wire
+pub fn message_bus_driver(config: BusConfig) -> IODriverFn {
+    make_driver(move |handle| async move {
+        let bus = connect(&config.backend).await?;
+        let (inbound_tx, mut inbound_rx) = mpsc::channel(INBOUND_QUEUE);
+        if !config.subscribe.is_empty() {
+            bus.subscribe(&config.subscribe, inbound_tx).await?;
+            info!("bus: subscribed to {}", config.subscribe.join(", "));
+        } else {
+            drop(inbound_tx);
+        }
+
+        loop {
+            tokio::select! {
+                Some(message) = inbound_rx.recv() => {
+                    deliver(&handle, config.format, message).await;
+                }
+                effect = handle.next_effect() => {
+                    let slab = match effect {
+                        Ok(slab) => slab,
+                        Err(e) => {
+                            error!("Error receiving effect: {:?}", e);
+                            continue;
+                        }
+                    };
+                    let Ok(effect_cell) = unsafe { slab.root() }.as_cell() else {
+                        continue;
+                    };
+                    if !unsafe { effect_cell.head().raw_equals(&D(tas!(b"bus"))) } {
+                        continue;
+                    }
+                    match decode_publish(effect_cell.tail()) {
+                        Ok((subject, data)) => {
+                            debug!("bus: publishing to {}", subject);
+                            let payload = encode_message(config.format, &subject, &data);
+                            publish_with_retry(bus.as_ref(), &subject, payload).await;
+                        }
+                        Err(e) => error!("bus: invalid bus effect: {:?}", e),
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jammed_pair() -> NounSlab {
+        let mut slab = NounSlab::new();
+        let data = T(&mut slab, &[D(1), D(2)]);
+        slab.set_root(data);
+        slab
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = BusConfig::parse(
+            r#"
+            format = "json"
+            subscribe = ["orders.>"]
+
+            [backend]
+            kind = "kafka"
+            brokers = "localhost:9092"
+            group_id = "nockapp"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.format, BusFormat::Json);
+        assert_eq!(config.subscribe, vec!["orders.>".to_string()]);
+        assert_eq!(
+            config.backend,
+            BackendConfig::Kafka {
+                brokers: "localhost:9092".to_string(),
+                group_id: "nockapp".to_string(),
+            }
+        );
+
+        assert!(BusConfig::parse("[backend]\nkind = \"amqp\"").is_err());
+        assert!(BusConfig::parse(
+            "[backend]\nkind = \"nats\"\nurl = \"nats://localhost\"\nstream = \"S\"\nconsumer = \"c\"\nretries = 3"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_decode_publish() {
+        let mut slab: NounSlab = NounSlab::new();
+        let subject = Atom::from_value(&mut slab, "orders.new").unwrap();
+        let effect = T(
+            &mut slab,
+            &[D(tas!(b"publish")), subject.as_noun(), D(1), D(2)],
+        );
+        let (subject, data) = decode_publish(effect).unwrap();
+        assert_eq!(subject, "orders.new");
+        let data = unsafe { data.root() }.as_cell().unwrap();
+        assert!(unsafe { data.tail().raw_equals(&D(2)) });
+
+        let effect = T(&mut slab, &[D(tas!(b"send")), D(0), D(0)]);
+        assert!(decode_publish(effect).is_err());
+    }
+
+    #[test]
+    fn test_jam_roundtrip() {
+        let data = jammed_pair();
+        let payload = encode_message(BusFormat::Jam, "orders.new", &data);
+        let (wire, cause) = decode_message(BusFormat::Jam, "orders.new", payload).unwrap();
+        assert_eq!(wire, BusWire::Message("orders.new".to_string()).to_wire());
+
+        let cause = unsafe { cause.root() }.as_cell().unwrap();
+        assert!(unsafe { cause.head().raw_equals(&D(tas!(b"bus"))) });
+        let message = cause.tail().as_cell().unwrap();
+        assert!(unsafe { message.head().raw_equals(&D(tas!(b"message"))) });
+        let data = message.tail().as_cell().unwrap().tail().as_cell().unwrap();
+        assert!(unsafe { data.head().raw_equals(&D(1)) });
+        assert!(unsafe { data.tail().raw_equals(&D(2)) });
+    }
+
+    #[test]
+    fn test_json_envelope() {
+        let data = jammed_pair();
+        let payload = encode_message(BusFormat::Json, "orders.new", &data);
+        let envelope: OutboundEnvelope = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(envelope.subject, "orders.new");
+
+        let inbound = serde_json::json!({
+            "wire": {"source": "orders", "tags": ["new", 7]},
+            "payload": envelope.payload,
+        });
+        let (wire, _) = decode_message(
+            BusFormat::Json,
+            "orders.new",
+            Bytes::from(inbound.to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            wire,
+            WireRepr::new("orders", 1, vec!["new".into(), 7u64.into()])
+        );
+
+        let unwired = serde_json::json!({ "payload": envelope.payload });
+        let (wire, _) = decode_message(
+            BusFormat::Json,
+            "orders.new",
+            Bytes::from(unwired.to_string()),
+        )
+        .unwrap();
+        assert_eq!(wire, BusWire::Message("orders.new".to_string()).to_wire());
+
+        let bad = serde_json::json!({ "payload": "not base64!" });
+        assert!(decode_message(BusFormat::Json, "x", Bytes::from(bad.to_string())).is_err());
+        assert!(decode_message(BusFormat::Json, "x", Bytes::from_static(b"{}")).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! NATS JetStream backend. Publishes wait for the stream to store the message, and messages are
+//! consumed from a durable pull consumer, acked once handled and terminated once rejected.
+
+use async_nats::jetstream::{self, consumer, AckKind};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, error, warn};
+
+use super::{BusError, Delivery, InboundMessage, MessageBus};
+
+pub(super) struct NatsBus {
+    context: jetstream::Context,
+    stream: String,
+    consumer: String,
+}
+
+impl NatsBus {
+    pub(super) async fn connect(url: &str, stream: &str, consumer: &str) -> Result<Self, BusError> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| BusError::Connect(e.to_string()))?;
+        Ok(Self {
+            context: jetstream::new(client),
+            stream: stream.to_string(),
+            consumer: consumer.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl MessageBus for NatsBus {
+    async fn publish(&self, subject: &str, payload: Bytes) -> Result<(), BusError> {
+        let to_error = |reason: String| BusError::Publish {
+            subject: subject.to_string(),
+            reason,
+        };
+        self.context
+            .publish(subject.to_string(), payload)
+            .await
+            .map_err(|e| to_error(e.to_string()))?
+            .await
+            .map_err(|e| to_error(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn subscribe(
+        &self,
+        subjects: &[String],
+        inbound: mpsc::Sender<InboundMessage>,
+    ) -> Result<(), BusError> {
+        let stream = self
+            .context
+            .get_stream(&self.stream)
+            .await
+            .map_err(|e| BusError::Subscribe(format!("stream {}: {}", self.stream, e)))?;
+        let consumer: consumer::PullConsumer = stream
+            .get_or_create_consumer(
+                &self.consumer,
+                consumer::pull::Config {
+                    durable_name: Some(self.consumer.clone()),
+                    filter_subjects: subjects.to_vec(),
+                    ack_policy: consumer::AckPolicy::Explicit,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| BusError::Subscribe(format!("consumer {}: {}", self.consumer, e)))?;
+        let mut messages = consumer
+            .messages()
+            .await
+            .map_err(|e| BusError::Subscribe(e.to_string()))?;
+
+        tokio::spawn(async move {
+            while let Some(message) = messages.next().await {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(e) => {
+                        warn!("bus: error receiving from NATS: {}", e);
+                        continue;
+                    }
+                };
+                let (done, delivery) = oneshot::channel();
+                let sent = inbound
+                    .send(InboundMessage {
+                        subject: message.subject.to_string(),
+                        payload: message.payload.clone(),
+                        done,
+                    })
+                    .await;
+                if sent.is_err() {
+                    break;
+                }
+                // Unanswered messages are redelivered by the server once their ack wait is up
+                let ack = match delivery.await {
+                    Ok(Delivery::Handled) => message.ack().await,
+                    Ok(Delivery::Rejected) => message.ack_with(AckKind::Term).await,
+                    Err(_) => continue,
+                };
+                if let Err(e) = ack {
+                    error!("bus: failed to acknowledge NATS message: {}", e);
+                }
+            }
+            debug!("bus: NATS consumer stopped");
+        });
+        Ok(())
+    }
+}
@@ -43,6 +43,9 @@ pub enum NockAppGrpcError {
     #[error("Transaction pending")]
     TxPending,
 
+    #[error("Transaction pruned")]
+    TxPruned,
+
     #[error("Transaction not found")]
     NotFound,
 
@@ -127,6 +130,11 @@ impl From<NockAppGrpcError> for tonic::Status {
                 "Transaction pending".to_string(),
                 ErrorCode::PeekReturnedNoData,
             ),
+            TxPruned => (
+                tonic::Code::FailedPrecondition,
+                "Transaction pruned".to_string(),
+                ErrorCode::PeekReturnedNoData,
+            ),
             NotFound => (
                 tonic::Code::NotFound,
                 "Transaction not found".to_string(),
@@ -23,6 +23,7 @@
 
 // Include the generated protobuf code
 
+pub mod bus;
 pub mod error;
 pub mod services;
 #[cfg(test)]
@@ -34,7 +35,7 @@ pub mod wire_conversion;
 pub use error::{NockAppGrpcError, Result};
 pub use nockapp_grpc_proto::pb;
 pub use nockapp_grpc_proto::v1::convert;
-pub use services::{private_nockapp, public_nockchain};
+pub use services::{legacy_npc, private_nockapp, public_nockchain};
 
 // Backcompat re-export: allow imports like `nockapp_grpc::driver::...`
 pub mod driver {
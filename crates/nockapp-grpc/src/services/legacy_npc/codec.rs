@@ -0,0 +1,256 @@
+//! Framing and messages of the legacy NPC socket protocol.
+//!
+//! Every message is a little-endian `u64` byte length followed by that many bytes of jammed noun.
+//! Clients send `[pid %peek path]` and `[pid %poke cause]`, and are answered on the same `pid`
+//! with `[pid %bind (unit result)]` for a peek and `[pid %ack ~]` or `[pid %nack ~]` for a poke.
+
+use bytes::Bytes;
+use nockapp::noun::slab::NounSlab;
+use nockvm::noun::{D, SIG, T};
+use nockvm_macros::tas;
+use noun_serde::prelude::*;
+use noun_serde::NounDecodeError;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::{NockAppGrpcError, Result};
+
+/// Largest frame accepted from a client
+pub const MAX_FRAME_LEN: u64 = 256 << 20;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NpcRequest {
+    /// Jammed path to peek at
+    Peek { pid: u64, path: Vec<u8> },
+    /// Jammed cause to poke with
+    Poke { pid: u64, cause: Vec<u8> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NpcResponse {
+    /// Jammed peek result, `None` if the peek failed
+    Bind {
+        pid: u64,
+        result: Option<Vec<u8>>,
+    },
+    Ack {
+        pid: u64,
+    },
+    Nack {
+        pid: u64,
+    },
+}
+
+/// Jam `noun` on its own, copying it out of the slab it lives in.
+fn jam_noun(noun: nockapp::Noun) -> Vec<u8> {
+    let mut slab: NounSlab = NounSlab::new();
+    slab.copy_into(noun);
+    slab.jam().to_vec()
+}
+
+impl NpcRequest {
+    pub fn pid(&self) -> u64 {
+        match self {
+            NpcRequest::Peek { pid, .. } | NpcRequest::Poke { pid, .. } => *pid,
+        }
+    }
+
+    pub fn decode(frame: Bytes) -> Result<Self> {
+        let mut slab: NounSlab = NounSlab::new();
+        let noun = slab
+            .cue_into(frame)
+            .map_err(|e| NockAppGrpcError::Serialization(format!("Bad NPC frame: {:?}", e)))?;
+        let cell = noun.as_cell().map_err(NounDecodeError::from)?;
+        let pid = u64::from_noun(&cell.head())?;
+        let body = cell.tail().as_cell().map_err(NounDecodeError::from)?;
+        let tag = body.head();
+        if unsafe { tag.raw_equals(&D(tas!(b"peek"))) } {
+            Ok(NpcRequest::Peek {
+                pid,
+                path: jam_noun(body.tail()),
+            })
+        } else if unsafe { tag.raw_equals(&D(tas!(b"poke"))) } {
+            Ok(NpcRequest::Poke {
+                pid,
+                cause: jam_noun(body.tail()),
+            })
+        } else {
+            Err(NounDecodeError::InvalidTag.into())
+        }
+    }
+
+    pub fn encode(&self) -> Result<Bytes> {
+        let mut slab: NounSlab = NounSlab::new();
+        let (pid, tag, jammed) = match self {
+            NpcRequest::Peek { pid, path } => (*pid, tas!(b"peek"), path),
+            NpcRequest::Poke { pid, cause } => (*pid, tas!(b"poke"), cause),
+        };
+        let body = cue_payload(&mut slab, jammed)?;
+        let pid = pid.to_noun(&mut slab);
+        let message = T(&mut slab, &[pid, D(tag), body]);
+        slab.set_root(message);
+        Ok(slab.jam())
+    }
+}
+
+impl NpcResponse {
+    pub fn encode(&self) -> Result<Bytes> {
+        let mut slab: NounSlab = NounSlab::new();
+        let (pid, body) = match self {
+            NpcResponse::Bind { pid, result } => {
+                let unit = match result {
+                    Some(jammed) => {
+                        let result = cue_payload(&mut slab, jammed)?;
+                        T(&mut slab, &[SIG, result])
+                    }
+                    None => SIG,
+                };
+                (*pid, T(&mut slab, &[D(tas!(b"bind")), unit]))
+            }
+            NpcResponse::Ack { pid } => (*pid, T(&mut slab, &[D(tas!(b"ack")), SIG])),
+            NpcResponse::Nack { pid } => (*pid, T(&mut slab, &[D(tas!(b"nack")), SIG])),
+        };
+        let pid = pid.to_noun(&mut slab);
+        let message = T(&mut slab, &[pid, body]);
+        slab.set_root(message);
+        Ok(slab.jam())
+    }
+
+    pub fn decode(frame: Bytes) -> Result<Self> {
+        let mut slab: NounSlab = NounSlab::new();
+        let noun = slab
+            .cue_into(frame)
+            .map_err(|e| NockAppGrpcError::Serialization(format!("Bad NPC frame: {:?}", e)))?;
+        let cell = noun.as_cell().map_err(NounDecodeError::from)?;
+        let pid = u64::from_noun(&cell.head())?;
+        let body = cell.tail().as_cell().map_err(NounDecodeError::from)?;
+        let tag = body.head();
+        if unsafe { tag.raw_equals(&D(tas!(b"bind"))) } {
+            let result = match body.tail().as_cell() {
+                Ok(unit) => Some(jam_noun(unit.tail())),
+                Err(_) => None,
+            };
+            Ok(NpcResponse::Bind { pid, result })
+        } else if unsafe { tag.raw_equals(&D(tas!(b"ack"))) } {
+            Ok(NpcResponse::Ack { pid })
+        } else if unsafe { tag.raw_equals(&D(tas!(b"nack"))) } {
+            Ok(NpcResponse::Nack { pid })
+        } else {
+            Err(NounDecodeError::InvalidTag.into())
+        }
+    }
+}
+
+fn cue_payload(slab: &mut NounSlab, jammed: &[u8]) -> Result<nockapp::Noun> {
+    slab.cue_into(Bytes::copy_from_slice(jammed))
+        .map_err(|e| NockAppGrpcError::Serialization(format!("Bad NPC payload: {:?}", e)))
+}
+
+/// Read one frame, `None` if the peer closed the connection between frames.
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Bytes>> {
+    let mut len_bytes = [0u8; 8];
+    match reader.read_exact(&mut len_bytes).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(NockAppGrpcError::Internal(e.to_string())),
+    }
+    let len = u64::from_le_bytes(len_bytes);
+    if len > MAX_FRAME_LEN {
+        return Err(NockAppGrpcError::InvalidRequest(format!(
+            "NPC frame of {} bytes is over the {} byte limit",
+            len, MAX_FRAME_LEN
+        )));
+    }
+    let mut frame = vec![0u8; len as usize];
+    reader
+        .read_exact(&mut frame)
+        .await
+        .map_err(|e| NockAppGrpcError::Internal(e.to_string()))?;
+    Ok(Some(Bytes::from(frame)))
+}
+
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &[u8]) -> Result<()> {
+    let mut bytes = Vec::with_capacity(8 + frame.len());
+    bytes.extend_from_slice(&(frame.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(frame);
+    writer
+        .write_all(&bytes)
+        .await
+        .map_err(|e| NockAppGrpcError::Internal(e.to_string()))?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| NockAppGrpcError::Internal(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jammed(noun: impl FnOnce(&mut NounSlab) -> nockapp::Noun) -> Vec<u8> {
+        let mut slab: NounSlab = NounSlab::new();
+        let noun = noun(&mut slab);
+        slab.set_root(noun);
+        slab.jam().to_vec()
+    }
+
+    #[test]
+    fn test_request_roundtrip() {
+        let path = jammed(|slab| T(slab, &[D(tas!(b"state")), SIG]));
+        let peek = NpcRequest::Peek { pid: 7, path };
+        assert_eq!(NpcRequest::decode(peek.encode().unwrap()).unwrap(), peek);
+
+        let cause = jammed(|slab| T(slab, &[D(tas!(b"command")), D(42)]));
+        let poke = NpcRequest::Poke {
+            pid: u64::MAX,
+            cause,
+        };
+        assert_eq!(NpcRequest::decode(poke.encode().unwrap()).unwrap(), poke);
+
+        let bad = jammed(|slab| T(slab, &[D(1), D(tas!(b"scry")), SIG]));
+        assert!(NpcRequest::decode(Bytes::from(bad)).is_err());
+    }
+
+    #[test]
+    fn test_response_roundtrip() {
+        let result = jammed(|slab| T(slab, &[D(1), D(2)]));
+        for response in [
+            NpcResponse::Bind {
+                pid: 1,
+                result: Some(result),
+            },
+            NpcResponse::Bind {
+                pid: 2,
+                result: None,
+            },
+            NpcResponse::Ack { pid: 3 },
+            NpcResponse::Nack { pid: 4 },
+        ] {
+            assert_eq!(
+                NpcResponse::decode(response.encode().unwrap()).unwrap(),
+                response
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frames() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        write_frame(&mut client, b"hello").await.unwrap();
+        write_frame(&mut client, b"").await.unwrap();
+        drop(client);
+
+        assert_eq!(
+            read_frame(&mut server).await.unwrap(),
+            Some(Bytes::from_static(b"hello"))
+        );
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some(Bytes::new()));
+        assert_eq!(read_frame(&mut server).await.unwrap(), None);
+
+        let (mut client, mut server) = tokio::io::duplex(64);
+        client
+            .write_all(&(MAX_FRAME_LEN + 1).to_le_bytes())
+            .await
+            .unwrap();
+        assert!(read_frame(&mut server).await.is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use nockapp::driver::{make_driver, IODriverFn, NockAppHandle};
+use nockapp::NockAppError;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tonic::Request;
+use tracing::{debug, error, info, warn};
+
+use super::codec::{read_frame, write_frame, NpcRequest, NpcResponse};
+use crate::pb::common::v1::{wire_tag, Wire, WireTag};
+use crate::pb::private::v1::nock_app_service_server::NockAppService;
+use crate::pb::private::v1::{peek_response, poke_response, PeekRequest, PokeRequest};
+use crate::services::private_nockapp::PrivateNockAppGrpcServer;
+
+/// Responses a connection may have queued before its requests wait on the socket
+const RESPONSE_QUEUE: usize = 64;
+
+/// Wire legacy pokes are delivered on, `/npc/1/<pid>`
+pub fn npc_wire(pid: u64) -> Wire {
+    Wire {
+        source: "npc".to_string(),
+        version: 1,
+        tags: vec![WireTag {
+            value: Some(wire_tag::Value::Number(pid)),
+        }],
+    }
+}
+
+/// Answer one legacy request by translating it into a private gRPC call.
+async fn translate(service: &PrivateNockAppGrpcServer, request: NpcRequest) -> NpcResponse {
+    match request {
+        NpcRequest::Peek { pid, path } => {
+            let result = service
+                .peek(Request::new(PeekRequest {
+                    pid: pid as i32,
+                    path,
+                }))
+                .await
+                .ok()
+                .and_then(|response| response.into_inner().result);
+            let result = match result {
+                Some(peek_response::Result::Data(data)) => Some(data),
+                Some(peek_response::Result::Error(error)) => {
+                    debug!("NPC peek {} failed: {}", pid, error.message);
+                    None
+                }
+                None => None,
+            };
+            NpcResponse::Bind { pid, result }
+        }
+        NpcRequest::Poke { pid, cause } => {
+            let result = service
+                .poke(Request::new(PokeRequest {
+                    pid: pid as i32,
+                    wire: Some(npc_wire(pid)),
+                    payload: cause,
+                }))
+                .await
+                .ok()
+                .and_then(|response| response.into_inner().result);
+            match result {
+                Some(poke_response::Result::Acknowledged(true)) => NpcResponse::Ack { pid },
+                Some(poke_response::Result::Error(error)) => {
+                    debug!("NPC poke {} failed: {}", pid, error.message);
+                    NpcResponse::Nack { pid }
+                }
+                _ => NpcResponse::Nack { pid },
+            }
+        }
+    }
+}
+
+async fn serve_connection(service: Arc<PrivateNockAppGrpcServer>, stream: UnixStream) {
+    let (mut reader, mut writer) = stream.into_split();
+    let (response_tx, mut response_rx) = mpsc::channel::<NpcResponse>(RESPONSE_QUEUE);
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(response) = response_rx.recv().await {
+            let frame = match response.encode() {
+                Ok(frame) => frame,
+                Err(e) => {
+                    error!("Failed to encode NPC response: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = write_frame(&mut writer, &frame).await {
+                debug!("NPC client went away: {}", e);
+                break;
+            }
+        }
+    });
+
+    loop {
+        let frame = match read_frame(&mut reader).await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Dropping NPC connection: {}", e);
+                break;
+            }
+        };
+        let request = match NpcRequest::decode(frame) {
+            Ok(request) => request,
+            Err(e) => {
+                // Without a pid there's no one to nack
+                warn!("Ignoring malformed NPC request: {}", e);
+                continue;
+            }
+        };
+        debug!("NPC request {}", request.pid());
+        // Requests are answered as they complete, so a slow peek doesn't hold up the rest
+        let service = service.clone();
+        let response_tx = response_tx.clone();
+        tokio::spawn(async move {
+            let response = translate(&service, request).await;
+            let _ = response_tx.send(response).await;
+        });
+    }
+
+    drop(response_tx);
+    let _ = writer_task.await;
+}
+
+/// Serve the legacy socket-based NockApp protocol on a Unix socket.
+///
+/// Peeks and pokes from old clients are translated into calls on the private gRPC service, so
+/// they behave exactly as they would over gRPC. Pokes arrive on the `/npc/1/<pid>` wire. This
+/// driver is for keeping existing integrations running while they move to gRPC; new clients
+/// should use [`grpc_server_driver`](super::super::private_nockapp::grpc_server_driver).
+///
+/// # Example
+/// ```rust,ignore
+/// use nockapp_grpc::legacy_npc::npc_listener_driver;
+/// // in an async context with a NockApp instance:
+/// // app.add_io_driver(npc_listener_driver("./app.sock".into())).await;
+/// ```
+pub fn npc_listener_driver(socket_path: PathBuf) -> IODriverFn {
+    make_driver(move |handle: NockAppHandle| async move {
+        // A socket left over from an unclean shutdown would stop us binding
+        if socket_path.exists() {
+            std::fs::remove_file(&socket_path).map_err(NockAppError::IoError)?;
+        }
+        let listener = UnixListener::bind(&socket_path).map_err(NockAppError::IoError)?;
+        info!("Serving legacy NPC protocol on {}", socket_path.display());
+
+        let service = Arc::new(PrivateNockAppGrpcServer::new(handle));
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    debug!("NPC client connected");
+                    tokio::spawn(serve_connection(service.clone(), stream));
+                }
+                Err(e) => {
+                    error!("Failed to accept NPC connection: {}", e);
+                }
+            }
+        }
+    })
+}
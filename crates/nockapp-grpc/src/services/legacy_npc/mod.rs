@@ -0,0 +1,5 @@
+pub mod codec;
+pub mod driver;
+
+pub use codec::{NpcRequest, NpcResponse};
+pub use driver::{npc_listener_driver, npc_wire};
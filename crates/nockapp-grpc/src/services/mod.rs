@@ -1,2 +1,3 @@
+pub mod legacy_npc;
 pub mod private_nockapp;
 pub mod public_nockchain;
@@ -66,4 +66,36 @@ impl PrivateNockAppGrpcClient {
             None => Err(NockAppGrpcError::Internal("Empty response".to_string())),
         }
     }
+
+    /// Read the node's per-jet counters, optionally zeroing them afterwards.
+    pub async fn jet_stats(&mut self, reset: bool) -> Result<JetStatsResponse> {
+        let response = self.client.jet_stats(JetStatsRequest { reset }).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Switch the node to a new jammed kernel without restarting it, optionally running a
+    /// jammed upgrade poke on `wire` once the state is loaded into it.
+    pub async fn upgrade_kernel(
+        &mut self,
+        kernel: Vec<u8>,
+        poke: Option<(Wire, Vec<u8>)>,
+    ) -> Result<bool> {
+        let (wire, payload) = poke.unzip();
+        let request = UpgradeKernelRequest {
+            kernel,
+            wire,
+            payload,
+        };
+
+        let response = self.client.upgrade_kernel(request).await?;
+        let response = response.into_inner();
+
+        match response.result {
+            Some(upgrade_kernel_response::Result::Upgraded(upgraded)) => Ok(upgraded),
+            Some(upgrade_kernel_response::Result::Error(error)) => {
+                Err(NockAppGrpcError::Internal(error.message))
+            }
+            None => Err(NockAppGrpcError::Internal("Empty response".to_string())),
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
 
 use nockapp::driver::{make_driver, IODriverFn, NockAppHandle};
 use nockapp::noun::slab::NounSlab;
@@ -11,6 +12,7 @@ use noun_serde::NounDecodeError;
 use tracing::{error, info};
 
 use super::client::PrivateNockAppGrpcClient;
+use super::jet_stats::JetStatsSource;
 use super::server::PrivateNockAppGrpcServer;
 use crate::wire_conversion::create_grpc_wire;
 
@@ -31,11 +33,26 @@ use crate::wire_conversion::create_grpc_wire;
 /// // app.add_io_driver(grpc_server_driver()).await;
 /// ```
 pub fn grpc_server_driver(port: u16) -> IODriverFn {
+    server_driver(port, None)
+}
+
+/// [`grpc_server_driver`], serving the `JetStats` call from `jet_stats`.
+pub fn grpc_server_driver_with_jet_stats(
+    port: u16,
+    jet_stats: Arc<dyn JetStatsSource>,
+) -> IODriverFn {
+    server_driver(port, Some(jet_stats))
+}
+
+fn server_driver(port: u16, jet_stats: Option<Arc<dyn JetStatsSource>>) -> IODriverFn {
     let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
     make_driver(move |handle: NockAppHandle| async move {
         info!("Starting private gRPC server on {}", addr);
 
-        let server = PrivateNockAppGrpcServer::new(handle);
+        let mut server = PrivateNockAppGrpcServer::new(handle);
+        if let Some(jet_stats) = jet_stats {
+            server = server.with_jet_stats(jet_stats);
+        }
 
         match server.serve(addr).await {
             Ok(_) => {
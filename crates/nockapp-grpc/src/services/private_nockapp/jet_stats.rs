@@ -0,0 +1,14 @@
+use crate::pb::private::v1::JetStat;
+
+/// Per-jet call counters served by the `JetStats` call. nockapp-grpc registers no jets itself,
+/// so the binary that builds the hot state supplies them.
+pub trait JetStatsSource: Send + Sync {
+    /// Whether jets are being counted at all.
+    fn enabled(&self) -> bool;
+
+    /// Counters for every jet called so far.
+    fn snapshot(&self) -> Vec<JetStat>;
+
+    /// Zero every counter.
+    fn reset(&self);
+}
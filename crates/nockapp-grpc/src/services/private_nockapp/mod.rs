@@ -1,7 +1,9 @@
 pub mod client;
 pub mod driver;
+pub mod jet_stats;
 pub mod server;
 
 pub use client::PrivateNockAppGrpcClient;
-pub use driver::{grpc_listener_driver, grpc_server_driver};
+pub use driver::{grpc_listener_driver, grpc_server_driver, grpc_server_driver_with_jet_stats};
+pub use jet_stats::JetStatsSource;
 pub use server::PrivateNockAppGrpcServer;
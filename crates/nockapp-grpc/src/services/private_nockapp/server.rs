@@ -1,11 +1,14 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use nockapp::driver::{NockAppHandle, PokeResult};
+use nockapp::kernel::form::KernelUpgrade;
 use nockapp::noun::slab::NounSlab;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info, warn};
 
+use super::jet_stats::JetStatsSource;
 use crate::error::{NockAppGrpcError, Result};
 use crate::pb::common::v1::{ErrorCode, ErrorStatus};
 use crate::pb::private::v1::nock_app_service_server::{
@@ -16,11 +19,21 @@ use crate::wire_conversion::grpc_wire_to_nockapp;
 
 pub struct PrivateNockAppGrpcServer {
     handle: NockAppHandle,
+    jet_stats: Option<Arc<dyn JetStatsSource>>,
 }
 
 impl PrivateNockAppGrpcServer {
     pub fn new(handle: NockAppHandle) -> Self {
-        Self { handle }
+        Self {
+            handle,
+            jet_stats: None,
+        }
+    }
+
+    /// Serve `JetStats` from `jet_stats`. Without it the call reports counting as disabled.
+    pub fn with_jet_stats(mut self, jet_stats: Arc<dyn JetStatsSource>) -> Self {
+        self.jet_stats = Some(jet_stats);
+        self
     }
 
     pub async fn serve(self, addr: SocketAddr) -> Result<()> {
@@ -185,4 +198,86 @@ impl PrivateNockApp for PrivateNockAppGrpcServer {
             }
         }
     }
+
+    async fn jet_stats(
+        &self,
+        request: Request<JetStatsRequest>,
+    ) -> std::result::Result<Response<JetStatsResponse>, Status> {
+        let req = request.into_inner();
+        debug!("JetStats request: reset={}", req.reset);
+        let Some(source) = &self.jet_stats else {
+            return Ok(Response::new(JetStatsResponse {
+                enabled: false,
+                jets: Vec::new(),
+            }));
+        };
+        let jets = source.snapshot();
+        if req.reset {
+            source.reset();
+        }
+        Ok(Response::new(JetStatsResponse {
+            enabled: source.enabled(),
+            jets,
+        }))
+    }
+
+    async fn upgrade_kernel(
+        &self,
+        request: Request<UpgradeKernelRequest>,
+    ) -> std::result::Result<Response<UpgradeKernelResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "UpgradeKernel request: {} byte kernel, upgrade poke: {}",
+            req.kernel.len(),
+            req.payload.is_some()
+        );
+        let error_response = |error| {
+            Response::new(UpgradeKernelResponse {
+                result: Some(upgrade_kernel_response::Result::Error(
+                    self.build_error_response(error),
+                )),
+            })
+        };
+
+        let poke = match req.payload {
+            Some(payload) => {
+                let wire = match req.wire.as_ref().map(grpc_wire_to_nockapp) {
+                    Some(Ok(wire)) => wire,
+                    Some(Err(e)) => {
+                        warn!("Invalid wire in UpgradeKernel: {}", e);
+                        return Ok(error_response(e));
+                    }
+                    None => {
+                        return Ok(error_response(NockAppGrpcError::InvalidRequest(
+                            "Wire is required with an upgrade poke".to_string(),
+                        )));
+                    }
+                };
+                let mut payload_slab = NounSlab::new();
+                if let Err(e) = payload_slab.cue_into(bytes::Bytes::from(payload)) {
+                    warn!("Failed to decode JAM payload: {:?}", e);
+                    return Ok(error_response(NockAppGrpcError::Serialization(format!(
+                        "JAM decoding failed: {:?}",
+                        e
+                    ))));
+                }
+                Some((wire, payload_slab))
+            }
+            None => None,
+        };
+
+        let upgrade = KernelUpgrade {
+            kernel: req.kernel,
+            poke,
+        };
+        match self.handle.upgrade_kernel(upgrade).await {
+            Ok(()) => Ok(Response::new(UpgradeKernelResponse {
+                result: Some(upgrade_kernel_response::Result::Upgraded(true)),
+            })),
+            Err(e) => {
+                error!("Kernel upgrade failed: {}", e);
+                Ok(error_response(NockAppGrpcError::NockApp(e)))
+            }
+        }
+    }
 }
@@ -2,5 +2,9 @@ pub mod v1;
 pub mod v2;
 
 pub use v2::client::PublicNockchainGrpcClient;
-pub use v2::driver::{grpc_listener_driver, grpc_server_driver};
+pub use v2::driver::{grpc_listener_driver, grpc_server_driver, grpc_server_driver_with_admin};
+pub use v2::indexer::ExplorerIndex;
+pub use v2::mempool::MempoolManager;
+pub use v2::mining::MiningMonitor;
+pub use v2::peers::PeerManager;
 pub use v2::server::PublicNockchainGrpcServer;
@@ -6,10 +6,11 @@ use std::time::{Duration, Instant};
 
 use nockapp::noun::slab::NounSlab;
 use nockapp_grpc_proto::pb::public::v2::{
-    transaction_details, transaction_output, BigNum as ProtoBigNum, BlockDetails,
-    CoinbaseSplit as ProtoCoinbaseSplit, CoinbaseSplitV0 as ProtoCoinbaseSplitV0,
-    CoinbaseSplitV1 as ProtoCoinbaseSplitV1, CoinbaseSplitV1Entry, PageMsg as ProtoPageMsg,
-    ProofOfWork, TransactionDetails, TransactionInput, TransactionOutput,
+    transaction_details, transaction_output, BigNum as ProtoBigNum, BlockDetails, BlockEntry,
+    BlockTransaction, BlockUpdate, CoinbaseSplit as ProtoCoinbaseSplit,
+    CoinbaseSplitV0 as ProtoCoinbaseSplitV0, CoinbaseSplitV1 as ProtoCoinbaseSplitV1,
+    CoinbaseSplitV1Entry, NoteRef, PageMsg as ProtoPageMsg, ProofOfWork, TransactionBlockData,
+    TransactionDetails, TransactionInput, TransactionOutput,
 };
 use nockchain_math::noun_ext::NounMathExt;
 use nockchain_math::structs::HoonMapIter;
@@ -17,7 +18,7 @@ use nockchain_types::tx_engine::common::{BlockHeight, Hash, Name, Page};
 use nockchain_types::tx_engine::v0::{Lock, NoteV0, RawTx};
 use nockvm::noun::{Noun, SIG};
 use noun_serde::{NounDecode, NounDecodeError, NounEncode};
-use tokio::sync::{RwLock, Semaphore};
+use tokio::sync::{Notify, RwLock, Semaphore};
 use tracing::{debug, error, info, warn};
 
 use crate::error::{NockAppGrpcError, Result as GrpcResult};
@@ -35,6 +36,17 @@ pub struct BlockMetadata {
     pub tx_ids: Vec<Hash>,
 }
 
+impl BlockMetadata {
+    pub fn to_transaction_block_data(&self) -> TransactionBlockData {
+        TransactionBlockData {
+            block_id: Some(hash_to_proto(&self.block_id)),
+            height: self.height,
+            parent: Some(hash_to_proto(&self.parent_id)),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExplorerMetricsSnapshot {
     pub cache_height: u64,
@@ -131,6 +143,9 @@ pub struct FullPageDetails {
     pub tx_ids: Vec<Hash>,
     pub coinbase: CoinbaseSplitValue,
     pub msg: PageMsgValue,
+
+    /// Whether the node has pruned the block's transactions
+    pub pruned: bool,
 }
 
 /// BigNum representation for display
@@ -209,6 +224,9 @@ pub struct BlockExplorerCache {
     last_backfill: Arc<RwLock<Option<Instant>>>,
     get_blocks_latency: Arc<LatencyTracker>,
     get_block_details_latency: Arc<LatencyTracker>,
+
+    /// Woken whenever blocks are inserted, for block subscribers waiting on the tip
+    new_blocks: Notify,
 }
 
 impl BlockExplorerCache {
@@ -239,6 +257,7 @@ impl BlockExplorerCache {
             last_backfill: Arc::new(RwLock::new(None)),
             get_blocks_latency: Arc::new(LatencyTracker::new(512)),
             get_block_details_latency: Arc::new(LatencyTracker::new(512)),
+            new_blocks: Notify::new(),
         }
     }
 
@@ -539,6 +558,20 @@ impl BlockExplorerCache {
         self.max_height.load(Ordering::Acquire)
     }
 
+    /// Wait until the cache has seen the heaviest chain reach `height`
+    pub async fn wait_for_height(&self, height: u64) {
+        loop {
+            let notified = self.new_blocks.notified();
+            tokio::pin!(notified);
+            // Register before checking, so an insert in between is not missed
+            notified.as_mut().enable();
+            if self.max_height.load(Ordering::Acquire) >= height {
+                return;
+            }
+            notified.await;
+        }
+    }
+
     pub fn record_get_blocks_latency(&self, duration: Duration) {
         self.get_blocks_latency.record(duration);
     }
@@ -830,6 +863,10 @@ impl BlockExplorerCache {
             .load_block_with_transactions(handle, meta.height)
             .await?;
 
+        if block.pruned {
+            return Err(NockAppGrpcError::TxPruned);
+        }
+
         let metadata = block.metadata.clone();
         let (hash, tx) = block
             .txs
@@ -840,6 +877,28 @@ impl BlockExplorerCache {
         Ok(build_transaction_details(&metadata, &hash, tx))
     }
 
+    /// The block at `height` on the heaviest chain, with the notes its transactions spent and
+    /// created
+    pub async fn load_block_update(
+        &self,
+        handle: &Arc<dyn BalanceHandle>,
+        height: u64,
+    ) -> GrpcResult<BlockUpdate> {
+        let block = self.load_block_with_transactions(handle, height).await?;
+        // Sending a pruned block would pass it off as one without transactions
+        if block.pruned {
+            return Err(NockAppGrpcError::TxPruned);
+        }
+        Ok(BlockUpdate {
+            block: Some(block_entry_to_proto(&block.metadata)),
+            transactions: block
+                .txs
+                .iter()
+                .map(|(tx_id, tx)| block_transaction(tx_id, tx))
+                .collect(),
+        })
+    }
+
     /// Peek /heaviest-chain ~ to get current tip
     #[tracing::instrument(name = "block_explorer_cache.peek_heaviest_chain", skip(self, handle))]
     async fn peek_heaviest_chain(
@@ -1221,6 +1280,7 @@ impl BlockExplorerCache {
                 .block_explorer_backfill_age_seconds
                 .swap(last.elapsed().as_secs_f64());
         }
+        self.new_blocks.notify_waiters();
         debug!(
             batch_size,
             highest_seen = max_in_batch,
@@ -1390,46 +1450,36 @@ impl TryFrom<BlockRangeEntryNoun> for BlockRangeEntry {
     fn try_from(raw: BlockRangeEntryNoun) -> std::result::Result<Self, Self::Error> {
         let BlockRangeEntryNoun { height, tail } = raw;
         let BlockRangeEntryTail { block_id, tail } = tail;
-        let PageAndTxs { page, txs } = tail;
-
-        let parent_id = page.parent;
-        let timestamp = page.timestamp;
-        let tx_ids = extract_tx_ids_from_map(&txs)?;
+        let PageAndTxs { page, txs: _ } = tail;
 
+        // The page lists every tx-id even if the node has pruned the block's transactions
         Ok(Self {
             height: height.0 .0,
             block_id,
-            parent_id,
-            timestamp,
-            tx_ids,
+            parent_id: page.parent,
+            timestamp: page.timestamp,
+            tx_ids: page.tx_ids,
         })
     }
 }
 
-/// Extract transaction IDs from transactions map (z-map tx-id tx)
-fn extract_tx_ids_from_map(
-    txs_noun: &Noun,
+/// Extract transaction IDs from a page's tx-ids (z-set tx-id)
+fn extract_tx_ids_from_set(
+    tx_ids_noun: &Noun,
 ) -> std::result::Result<Vec<Hash>, noun_serde::NounDecodeError> {
-    // Check if it's an empty map (atom 0)
-    if let Ok(atom) = txs_noun.as_atom() {
+    // Check if it's an empty set (atom 0)
+    if let Ok(atom) = tx_ids_noun.as_atom() {
         if atom.as_u64()? == 0 {
             return Ok(Vec::new());
         }
     }
 
-    // Iterate over the z-map and collect keys (tx-ids)
+    // HoonMapIter returns each node's value, which for a z-set is the tx-id itself
     let mut tx_ids = Vec::new();
-    for (idx, entry) in HoonMapIter::from(*txs_noun).enumerate() {
-        if !entry.is_cell() {
-            return Err(NounDecodeError::Custom(format!(
-                "extract_tx_ids_from_map: entry {} is not a cell (expected z-map [key value] pair)",
-                idx
-            )));
-        }
-        let [key, _value] = entry.uncell().map_err(|_| NounDecodeError::ExpectedCell)?;
-        let hash = Hash::from_noun(&key).map_err(|e| {
+    for (idx, entry) in HoonMapIter::from(*tx_ids_noun).enumerate() {
+        let hash = Hash::from_noun(&entry).map_err(|e| {
             NounDecodeError::Custom(format!(
-                "extract_tx_ids_from_map: failed to decode tx_id at entry {}: {}",
+                "extract_tx_ids_from_set: failed to decode tx_id at entry {}: {}",
                 idx, e
             ))
         })?;
@@ -1439,9 +1489,15 @@ fn extract_tx_ids_from_map(
     Ok(tx_ids)
 }
 
+/// A block has been pruned if its page lists transactions but the kernel has no body for it.
+fn body_pruned(tx_ids: &[Hash], txs_noun: &Noun) -> bool {
+    !tx_ids.is_empty() && txs_noun.is_atom()
+}
+
 struct BlockEntryWithTxs {
     metadata: BlockMetadata,
     txs: Vec<(Hash, DecodedTx)>,
+    pruned: bool,
 }
 
 impl TryFrom<BlockRangeEntryNoun> for BlockEntryWithTxs {
@@ -1455,7 +1511,7 @@ impl TryFrom<BlockRangeEntryNoun> for BlockEntryWithTxs {
         let parent_id = page.parent;
         let timestamp = page.timestamp;
         let txs_full = extract_transactions_from_map(&txs)?;
-        let tx_ids = txs_full.iter().map(|(hash, _)| hash.clone()).collect();
+        let pruned = body_pruned(&page.tx_ids, &txs);
 
         Ok(Self {
             metadata: BlockMetadata {
@@ -1463,9 +1519,10 @@ impl TryFrom<BlockRangeEntryNoun> for BlockEntryWithTxs {
                 block_id,
                 parent_id,
                 timestamp,
-                tx_ids,
+                tx_ids: page.tx_ids,
             },
             txs: txs_full,
+            pruned,
         })
     }
 }
@@ -1534,14 +1591,15 @@ struct TxV1Data {
 
 #[derive(Debug, Clone)]
 struct TxV1Input {
-    name_first: Hash,
+    name: Name,
     // We don't have access to input amounts in v1 spends directly
     // The amount comes from the note being spent, which we don't have here
 }
 
 #[derive(Debug, Clone)]
 struct TxV1Output {
-    lock_root: Hash,
+    /// For v1, name.first is the lock root hash
+    name: Name,
     assets: u64,
 }
 
@@ -1661,9 +1719,7 @@ fn decode_v1_spends(noun: &Noun) -> Result<(Vec<TxV1Input>, u64), NounDecodeErro
                 idx, e
             ))
         })?;
-        inputs.push(TxV1Input {
-            name_first: name.first,
-        });
+        inputs.push(TxV1Input { name });
 
         // Extract fee from spend: spend is [tag [sig/witness [seeds fee]]]
         // Navigate: value.tail().tail().tail() to get fee
@@ -1806,10 +1862,7 @@ fn decode_outputs_v1(noun: &Noun) -> Result<Vec<TxV1Output>, NounDecodeError> {
             ))
         })?;
 
-        outputs.push(TxV1Output {
-            lock_root: name.first, // For v1, name.first is the lock root hash
-            assets,
-        });
+        outputs.push(TxV1Output { name, assets });
     }
 
     Ok(outputs)
@@ -1940,7 +1993,7 @@ fn build_transaction_details_v1(
     let mut inputs = Vec::new();
     for input in tx_inputs {
         inputs.push(TransactionInput {
-            note_name_b58: input.name_first.to_base58(),
+            note_name_b58: input.name.first.to_base58(),
             amount: None,                // Amount not available in v1 spend data
             source_tx_id: String::new(), // Not directly available
             coinbase: false,             // Would need to check the note being spent
@@ -1952,13 +2005,13 @@ fn build_transaction_details_v1(
     for output in tx_outputs {
         total_output += output.assets;
         outputs_proto.push(TransactionOutput {
-            note_name_b58: output.lock_root.to_base58(),
+            note_name_b58: output.name.first.to_base58(),
             amount_required: Some(transaction_output::AmountRequired::Amount(
                 pb_common::Nicks {
                     value: output.assets,
                 },
             )),
-            lock_summary: format!("lock:{}", &output.lock_root.to_base58()[..8]),
+            lock_summary: format!("lock:{}", &output.name.first.to_base58()[..8]),
         });
     }
 
@@ -1998,6 +2051,67 @@ fn note_name_to_b58(name: &Name) -> String {
     name.first.to_base58()
 }
 
+fn block_entry_to_proto(metadata: &BlockMetadata) -> BlockEntry {
+    BlockEntry {
+        block_id: Some(hash_to_proto(&metadata.block_id)),
+        height: metadata.height,
+        parent: Some(hash_to_proto(&metadata.parent_id)),
+        timestamp: metadata.timestamp,
+        tx_ids: metadata
+            .tx_ids
+            .iter()
+            .map(|tx_id| pb_common::Base58Hash {
+                hash: tx_id.to_base58(),
+            })
+            .collect(),
+    }
+}
+
+/// The notes a transaction spent and created, by full name
+fn block_transaction(tx_id: &Hash, tx: &DecodedTx) -> BlockTransaction {
+    let note_ref = |name: &Name, amount: Option<u64>| NoteRef {
+        first_name_b58: name.first.to_base58(),
+        last_name_b58: name.last.to_base58(),
+        amount: amount.map(|value| pb_common::Nicks { value }),
+    };
+    let (spent, created) = match tx {
+        DecodedTx::V0(tx) => (
+            tx.raw_tx
+                .inputs
+                .0
+                .iter()
+                .map(|(name, _)| note_ref(name, None))
+                .collect(),
+            tx.outputs
+                .iter()
+                .map(|output| {
+                    note_ref(
+                        &output.note.tail.name,
+                        Some(output.note.tail.assets.0 as u64),
+                    )
+                })
+                .collect(),
+        ),
+        DecodedTx::V1(tx) => (
+            tx.inputs
+                .iter()
+                .map(|input| note_ref(&input.name, None))
+                .collect(),
+            tx.outputs
+                .iter()
+                .map(|output| note_ref(&output.name, Some(output.assets)))
+                .collect(),
+        ),
+    };
+    BlockTransaction {
+        tx_id: Some(pb_common::Base58Hash {
+            hash: tx_id.to_base58(),
+        }),
+        spent,
+        created,
+    }
+}
+
 fn lock_summary(lock: &Lock) -> String {
     let keys: Vec<String> = lock
         .pubkeys
@@ -2144,7 +2258,8 @@ fn decode_v0_page(
         .tail()
         .as_cell()
         .map_err(|_| NounDecodeError::Custom("v0 page: expected cell after parent".into()))?;
-    let _page_tx_ids = &cell.head();
+    let tx_ids = extract_tx_ids_from_set(&cell.head())
+        .map_err(|e| NounDecodeError::Custom(format!("v0 page tx_ids: {}", e)))?;
 
     let cell = cell
         .tail()
@@ -2200,8 +2315,7 @@ fn decode_v0_page(
 
     let msg = decode_page_msg(&cell.tail())
         .map_err(|e| NounDecodeError::Custom(format!("v0 page msg: {}", e)))?;
-    let tx_ids = extract_tx_ids_from_map(&txs_noun)
-        .map_err(|e| NounDecodeError::Custom(format!("v0 page tx_ids: {}", e)))?;
+    let pruned = body_pruned(&tx_ids, &txs_noun);
 
     Ok(FullPageDetails {
         height,
@@ -2217,6 +2331,7 @@ fn decode_v0_page(
         tx_ids,
         coinbase,
         msg,
+        pruned,
     })
 }
 
@@ -2251,7 +2366,8 @@ fn decode_v1_page(
         .tail()
         .as_cell()
         .map_err(|_| NounDecodeError::Custom("v1 page: expected cell after parent".into()))?;
-    let _page_tx_ids = &cell.head();
+    let tx_ids = extract_tx_ids_from_set(&cell.head())
+        .map_err(|e| NounDecodeError::Custom(format!("v1 page tx_ids: {}", e)))?;
 
     let cell = cell
         .tail()
@@ -2307,8 +2423,7 @@ fn decode_v1_page(
 
     let msg = decode_page_msg(&cell.tail())
         .map_err(|e| NounDecodeError::Custom(format!("v1 page msg: {}", e)))?;
-    let tx_ids = extract_tx_ids_from_map(&txs_noun)
-        .map_err(|e| NounDecodeError::Custom(format!("v1 page tx_ids: {}", e)))?;
+    let pruned = body_pruned(&tx_ids, &txs_noun);
 
     Ok(FullPageDetails {
         height,
@@ -2324,6 +2439,7 @@ fn decode_v1_page(
         tx_ids,
         coinbase,
         msg,
+        pruned,
     })
 }
 
@@ -2508,6 +2624,7 @@ impl FullPageDetails {
             tx_count: self.tx_ids.len() as u32,
             has_pow: self.pow_present,
             version: self.version,
+            pruned: self.pruned,
         }
     }
 
@@ -2604,25 +2721,19 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_decode_block_range_entry_minimal() {
-        let mut slab: NounSlab = NounSlab::new();
-
-        // Create a minimal BlockRangeEntry structure
-        // [page-number block-id page (z-map tx-id tx)]
-
+    /// Build a heaviest-chain-blocks-range entry: [height [block-id [page txs-map]]]
+    fn block_range_entry_noun(slab: &mut NounSlab, tx_ids_set: Noun, txs_map_noun: Noun) -> Noun {
         // page-number as Belt (atom)
         let height = BlockHeight(Belt(42));
-        let height_noun = height.to_noun(&mut slab);
+        let height_noun = height.to_noun(slab);
 
         // block-id as Hash [Belt; 5]
         let block_id = Hash([Belt(1), Belt(2), Belt(3), Belt(4), Belt(5)]);
-        let block_id_noun = block_id.to_noun(&mut slab);
+        let block_id_noun = block_id.to_noun(slab);
 
         // page structure: [digest pow parent tx-ids coinbase timestamp epoch-counter target accumulated-work height msg]
         let digest = Hash([Belt(10), Belt(11), Belt(12), Belt(13), Belt(14)]);
         let pow = nockvm::noun::D(0); // empty unit
-        let tx_ids_set = nockvm::noun::D(0); // empty z-set
         let coinbase = nockvm::noun::D(0);
         let timestamp = Belt(1234567890);
         let epoch_counter = Belt(0);
@@ -2633,40 +2744,76 @@ mod tests {
         let msg = nockvm::noun::D(0);
 
         // Create all nouns first to avoid borrow checker issues
-        let digest_noun = digest.to_noun(&mut slab);
-        let parent_noun = parent.to_noun(&mut slab);
-        let timestamp_noun = timestamp.to_noun(&mut slab);
-        let epoch_counter_noun = epoch_counter.to_noun(&mut slab);
-        let target_noun = target.to_noun(&mut slab);
-        let accumulated_work_noun = accumulated_work.to_noun(&mut slab);
-        let page_height_noun = page_height.to_noun(&mut slab);
+        let digest_noun = digest.to_noun(slab);
+        let parent_noun = parent.to_noun(slab);
+        let timestamp_noun = timestamp.to_noun(slab);
+        let epoch_counter_noun = epoch_counter.to_noun(slab);
+        let target_noun = target.to_noun(slab);
+        let accumulated_work_noun = accumulated_work.to_noun(slab);
+        let page_height_noun = page_height.to_noun(slab);
 
         let page_noun = nockvm::noun::T(
-            &mut slab,
+            slab,
             &[
                 digest_noun, pow, parent_noun, tx_ids_set, coinbase, timestamp_noun,
                 epoch_counter_noun, target_noun, accumulated_work_noun, page_height_noun, msg,
             ],
         );
 
-        // Empty z-map (atom 0)
-        let txs_map_noun = nockvm::noun::D(0);
-
         // Build inner cells first
-        let page_txs_cell = nockvm::noun::T(&mut slab, &[page_noun, txs_map_noun]);
-        let block_page_cell = nockvm::noun::T(&mut slab, &[block_id_noun, page_txs_cell]);
+        let page_txs_cell = nockvm::noun::T(slab, &[page_noun, txs_map_noun]);
+        let block_page_cell = nockvm::noun::T(slab, &[block_id_noun, page_txs_cell]);
 
         // Build the entry: [height [block-id [page txs-map]]]
-        let entry_noun = nockvm::noun::T(&mut slab, &[height_noun, block_page_cell]);
+        nockvm::noun::T(slab, &[height_noun, block_page_cell])
+    }
+
+    #[test]
+    fn test_decode_block_range_entry_minimal() {
+        let mut slab: NounSlab = NounSlab::new();
+
+        // Create a minimal BlockRangeEntry structure with an empty z-set of tx-ids and an
+        // empty z-map (atom 0) of txs
+        let entry_noun = block_range_entry_noun(&mut slab, nockvm::noun::D(0), nockvm::noun::D(0));
 
         // Try to decode it
         let raw = BlockRangeEntryNoun::from_noun(&entry_noun).expect("decode raw entry");
         let entry = BlockRangeEntry::try_from(raw).expect("convert raw entry");
 
         assert_eq!(entry.height, 42);
-        assert_eq!(entry.block_id, block_id);
-        assert_eq!(entry.parent_id, parent);
+        assert_eq!(
+            entry.block_id,
+            Hash([Belt(1), Belt(2), Belt(3), Belt(4), Belt(5)])
+        );
+        assert_eq!(
+            entry.parent_id,
+            Hash([Belt(20), Belt(21), Belt(22), Belt(23), Belt(24)])
+        );
         assert_eq!(entry.timestamp, 1234567890);
         assert_eq!(entry.tx_ids.len(), 0);
     }
+
+    #[test]
+    fn test_decode_block_range_entry_pruned() {
+        let mut slab: NounSlab = NounSlab::new();
+
+        // The page lists one tx-id, a z-set node [n l r], but the kernel has no txs for it
+        let tx_id = Hash([Belt(30), Belt(31), Belt(32), Belt(33), Belt(34)]);
+        let tx_id_noun = tx_id.to_noun(&mut slab);
+        let tx_ids_set = nockvm::noun::T(
+            &mut slab,
+            &[tx_id_noun, nockvm::noun::D(0), nockvm::noun::D(0)],
+        );
+        let entry_noun = block_range_entry_noun(&mut slab, tx_ids_set, nockvm::noun::D(0));
+
+        let raw = BlockRangeEntryNoun::from_noun(&entry_noun).expect("decode raw entry");
+        let entry = BlockRangeEntry::try_from(raw).expect("convert raw entry");
+        assert_eq!(entry.tx_ids, vec![tx_id.clone()]);
+
+        let raw = BlockRangeEntryNoun::from_noun(&entry_noun).expect("decode raw entry");
+        let block = BlockEntryWithTxs::try_from(raw).expect("convert raw entry");
+        assert!(block.pruned);
+        assert!(block.txs.is_empty());
+        assert_eq!(block.metadata.tx_ids, vec![tx_id]);
+    }
 }
@@ -1,16 +1,18 @@
 use nockapp_grpc_proto::pb::common::v1::{Base58Hash, Base58Pubkey};
 use nockchain_types::tx_engine::v1;
-use tonic::transport::Channel;
+use tonic::transport::{Channel, Endpoint};
 
 use crate::error::{NockAppGrpcError, Result};
 use crate::pb::common::v1::PageRequest;
 use crate::pb::common::{v1 as pb_common_v1, v2 as pb_common_v2};
+use crate::pb::public::v2::nockchain_reorg_service_client::NockchainReorgServiceClient;
 use crate::pb::public::v2::nockchain_service_client::NockchainServiceClient as PublicNockchainClient;
 use crate::pb::public::v2::*;
 
 #[derive(Clone)]
 pub struct PublicNockchainGrpcClient {
     client: PublicNockchainClient<Channel>,
+    reorgs: NockchainReorgServiceClient<Channel>,
 }
 
 pub enum BalanceRequest {
@@ -20,8 +22,13 @@ pub enum BalanceRequest {
 
 impl PublicNockchainGrpcClient {
     pub async fn connect<T: AsRef<str>>(address: T) -> Result<Self> {
-        let client = PublicNockchainClient::connect(address.as_ref().to_string()).await?;
-        Ok(Self { client })
+        let channel = Endpoint::new(address.as_ref().to_string())?
+            .connect()
+            .await?;
+        Ok(Self {
+            client: PublicNockchainClient::new(channel.clone()),
+            reorgs: NockchainReorgServiceClient::new(channel),
+        })
     }
 
     // Simple autopager: fetches all pages and aggregates notes client-side.
@@ -150,6 +157,20 @@ impl PublicNockchainGrpcClient {
         }
     }
 
+    /// The reorgs the node remembers, oldest first.
+    pub async fn recent_reorgs(&mut self) -> Result<Vec<ReorgEvent>> {
+        let mut stream = self
+            .reorgs
+            .subscribe_reorgs(SubscribeReorgsRequest { follow: false })
+            .await?
+            .into_inner();
+        let mut reorgs = Vec::new();
+        while let Some(reorg) = stream.message().await? {
+            reorgs.push(reorg);
+        }
+        Ok(reorgs)
+    }
+
     // pub async fn transaction_confirmation(
     //     &mut self,
     //     tx_id: pb_common::Base58Hash,
@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use nockapp::driver::{make_driver, IODriverFn, NockAppHandle};
 use nockchain_types::tx_engine::v1;
@@ -8,6 +9,11 @@ use noun_serde::{NounDecode, NounDecodeError};
 use tracing::{error, info, warn};
 
 use super::client::PublicNockchainGrpcClient;
+use super::indexer::ExplorerIndex;
+use super::mempool::MempoolManager;
+use super::mining::MiningMonitor;
+use super::peers::PeerManager;
+use super::reorgs::ReorgFeed;
 use super::server::PublicNockchainGrpcServer;
 use crate::pb::public::v2::wallet_send_transaction_response;
 
@@ -41,8 +47,36 @@ impl NounDecode for PublicNockchainEffect {
 
 /// Create a public gRPC server driver for NockApp (read-only/public API)
 pub fn grpc_server_driver(addr: SocketAddr) -> IODriverFn {
+    grpc_server_driver_with_admin(addr, None, None, None, None)
+}
+
+/// Create a public gRPC server driver that also serves peer management when `peers` is given,
+/// mempool policy management when `mempool` is given and mining stats when `mining` is given,
+/// and keeps `index` up to date and serves it to explorers when it is given
+pub fn grpc_server_driver_with_admin(
+    addr: SocketAddr,
+    peers: Option<Arc<dyn PeerManager>>,
+    mempool: Option<Arc<dyn MempoolManager>>,
+    mining: Option<Arc<dyn MiningMonitor>>,
+    index: Option<Arc<ExplorerIndex>>,
+) -> IODriverFn {
     make_driver(move |handle: NockAppHandle| async move {
-        let server = PublicNockchainGrpcServer::new(handle);
+        let (handle, effects) = handle.dup();
+        let reorgs = Arc::new(ReorgFeed::default());
+        tokio::spawn(reorgs.clone().follow(effects));
+        let mut server = PublicNockchainGrpcServer::new(handle).with_reorg_feed(reorgs);
+        if let Some(peers) = peers {
+            server = server.with_peer_manager(peers);
+        }
+        if let Some(mempool) = mempool {
+            server = server.with_mempool_manager(mempool);
+        }
+        if let Some(mining) = mining {
+            server = server.with_mining_monitor(mining);
+        }
+        if let Some(index) = index {
+            server = server.with_index(index);
+        }
         match server.serve(addr).await {
             Ok(_) => {
                 info!("Public gRPC server shutting down gracefully");
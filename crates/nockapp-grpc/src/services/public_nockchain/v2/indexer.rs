@@ -0,0 +1,528 @@
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use redb::{Database, ReadableTable, TableDefinition};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use super::cache::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::error::{NockAppGrpcError, Result};
+use crate::pb::common::v1 as pb_common;
+use crate::pb::public::v2::nockchain_block_service_client::NockchainBlockServiceClient;
+use crate::pb::public::v2::nockchain_index_service_server::NockchainIndexService;
+use crate::pb::public::v2::*;
+
+/// Indexed blocks: height → (block id, parent id, timestamp)
+const BLOCKS: TableDefinition<u64, ([u64; 5], [u64; 5], u64)> = TableDefinition::new("blocks");
+
+/// Height of the block each transaction is in
+const TXS: TableDefinition<&str, u64> = TableDefinition::new("txs");
+
+/// (address, height, position in block) → (tx id, received, spent)
+const ADDRESS_TXS: TableDefinition<(&str, u64, u32), (&str, u64, u64)> =
+    TableDefinition::new("address_txs");
+
+/// Unspent notes by (first name, last name) → amount
+const NOTES: TableDefinition<(&str, &str), u64> = TableDefinition::new("notes");
+
+/// Balance of each address holding unspent notes
+const BALANCES: TableDefinition<&str, u64> = TableDefinition::new("balances");
+
+/// (balance, address), so the rich list is a reverse scan
+const RICH: TableDefinition<(u64, &str), ()> = TableDefinition::new("rich");
+
+/// Most addresses GetRichList returns
+const MAX_RICH_LIST: usize = 1_000;
+
+/// How long the indexer waits before subscribing again after the stream ends or fails
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+fn db_error(err: impl Into<redb::Error>) -> NockAppGrpcError {
+    NockAppGrpcError::Internal(format!("Explorer index: {}", err.into()))
+}
+
+fn belts(hash: Option<&pb_common::Hash>) -> [u64; 5] {
+    let Some(hash) = hash else {
+        return [0; 5];
+    };
+    [&hash.belt_1, &hash.belt_2, &hash.belt_3, &hash.belt_4, &hash.belt_5]
+        .map(|belt| belt.as_ref().map_or(0, |belt| belt.value))
+}
+
+fn hash_from_belts(belts: [u64; 5]) -> pb_common::Hash {
+    let [b1, b2, b3, b4, b5] = belts.map(|value| Some(pb_common::Belt { value }));
+    pb_common::Hash {
+        belt_1: b1,
+        belt_2: b2,
+        belt_3: b3,
+        belt_4: b4,
+        belt_5: b5,
+    }
+}
+
+/// Indexes over the heaviest chain, kept in an embedded database: the transactions of each
+/// address, the block of each transaction, and address balances for a rich list.
+///
+/// Addresses are note first names, which commit to the note's lock. Balances only count notes
+/// created by transactions the index has seen, so coinbase rewards are not included until they
+/// are moved by a transaction.
+pub struct ExplorerIndex {
+    db: Database,
+}
+
+impl ExplorerIndex {
+    pub fn open(path: &Path) -> Result<Self> {
+        let db = Database::create(path).map_err(db_error)?;
+        let txn = db.begin_write().map_err(db_error)?;
+        {
+            // Create the tables, so readers never find them missing
+            txn.open_table(BLOCKS).map_err(db_error)?;
+            txn.open_table(TXS).map_err(db_error)?;
+            txn.open_table(ADDRESS_TXS).map_err(db_error)?;
+            txn.open_table(NOTES).map_err(db_error)?;
+            txn.open_table(BALANCES).map_err(db_error)?;
+            txn.open_table(RICH).map_err(db_error)?;
+        }
+        txn.commit().map_err(db_error)?;
+        Ok(Self { db })
+    }
+
+    /// Height of the newest indexed block
+    pub fn indexed_height(&self) -> Result<Option<u64>> {
+        let txn = self.db.begin_read().map_err(db_error)?;
+        let blocks = txn.open_table(BLOCKS).map_err(db_error)?;
+        let last = blocks.last().map_err(db_error)?;
+        Ok(last.map(|(height, _)| height.value()))
+    }
+
+    /// Index the block after the newest indexed one
+    pub fn apply(&self, update: &BlockUpdate) -> Result<()> {
+        let block = update.block.as_ref().ok_or_else(|| {
+            NockAppGrpcError::InvalidRequest("block update without a block".into())
+        })?;
+        let block_id = belts(block.block_id.as_ref());
+        let parent = belts(block.parent.as_ref());
+
+        let txn = self.db.begin_write().map_err(db_error)?;
+        {
+            let mut blocks = txn.open_table(BLOCKS).map_err(db_error)?;
+            let tip = blocks
+                .last()
+                .map_err(db_error)?
+                .map(|(height, entry)| (height.value(), entry.value().0));
+            if let Some((tip_height, tip_id)) = tip {
+                if block.height != tip_height + 1 {
+                    return Err(NockAppGrpcError::InvalidRequest(format!(
+                        "expected block {}, got block {}",
+                        tip_height + 1,
+                        block.height
+                    )));
+                }
+                if parent != tip_id {
+                    return Err(NockAppGrpcError::Internal(format!(
+                        "block {} does not extend the indexed chain; remove the index to rebuild it",
+                        block.height
+                    )));
+                }
+            }
+            blocks
+                .insert(block.height, (block_id, parent, block.timestamp))
+                .map_err(db_error)?;
+
+            let mut txs = txn.open_table(TXS).map_err(db_error)?;
+            let mut address_txs = txn.open_table(ADDRESS_TXS).map_err(db_error)?;
+            let mut notes = txn.open_table(NOTES).map_err(db_error)?;
+            let mut balances = txn.open_table(BALANCES).map_err(db_error)?;
+            let mut rich = txn.open_table(RICH).map_err(db_error)?;
+            for (position, tx) in update.transactions.iter().enumerate() {
+                let tx_id = tx.tx_id.as_ref().map_or("", |id| id.hash.as_str());
+                txs.insert(tx_id, block.height).map_err(db_error)?;
+
+                // address → (received, spent)
+                let mut moved: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+                for note in &tx.spent {
+                    let name = (note.first_name_b58.as_str(), note.last_name_b58.as_str());
+                    let amount = notes
+                        .remove(name)
+                        .map_err(db_error)?
+                        .map_or(0, |amount| amount.value());
+                    moved.entry(name.0).or_default().1 += amount;
+                }
+                for note in &tx.created {
+                    let name = (note.first_name_b58.as_str(), note.last_name_b58.as_str());
+                    let amount = note.amount.as_ref().map_or(0, |nicks| nicks.value);
+                    notes.insert(name, amount).map_err(db_error)?;
+                    moved.entry(name.0).or_default().0 += amount;
+                }
+
+                for (address, (received, spent)) in moved {
+                    address_txs
+                        .insert(
+                            (address, block.height, position as u32),
+                            (tx_id, received, spent),
+                        )
+                        .map_err(db_error)?;
+                    if received == spent {
+                        continue;
+                    }
+                    let old = balances
+                        .get(address)
+                        .map_err(db_error)?
+                        .map_or(0, |balance| balance.value());
+                    let new = (old + received).saturating_sub(spent);
+                    if old > 0 {
+                        rich.remove((old, address)).map_err(db_error)?;
+                    }
+                    if new > 0 {
+                        balances.insert(address, new).map_err(db_error)?;
+                        rich.insert((new, address), ()).map_err(db_error)?;
+                    } else {
+                        balances.remove(address).map_err(db_error)?;
+                    }
+                }
+            }
+        }
+        txn.commit().map_err(db_error)?;
+        Ok(())
+    }
+
+    /// The block a transaction is in
+    pub fn transaction_block(&self, tx_id: &str) -> Result<Option<TransactionBlockData>> {
+        let txn = self.db.begin_read().map_err(db_error)?;
+        let txs = txn.open_table(TXS).map_err(db_error)?;
+        let Some(height) = txs
+            .get(tx_id)
+            .map_err(db_error)?
+            .map(|height| height.value())
+        else {
+            return Ok(None);
+        };
+        let blocks = txn.open_table(BLOCKS).map_err(db_error)?;
+        let entry = blocks.get(height).map_err(db_error)?;
+        Ok(entry.map(|entry| {
+            let (block_id, parent, timestamp) = entry.value();
+            TransactionBlockData {
+                block_id: Some(hash_from_belts(block_id)),
+                height,
+                parent: Some(hash_from_belts(parent)),
+                timestamp,
+            }
+        }))
+    }
+
+    /// Up to `limit` of the address's transactions, newest first, from before `cursor`, and the
+    /// cursor for the next page if there is one
+    pub fn address_transactions(
+        &self,
+        address: &str,
+        cursor: Option<(u64, u32)>,
+        limit: usize,
+    ) -> Result<(Vec<AddressTransaction>, Option<(u64, u32)>)> {
+        let txn = self.db.begin_read().map_err(db_error)?;
+        let address_txs = txn.open_table(ADDRESS_TXS).map_err(db_error)?;
+        let blocks = txn.open_table(BLOCKS).map_err(db_error)?;
+        let (before_height, before_position) = cursor.unwrap_or((u64::MAX, u32::MAX));
+
+        let mut page = Vec::new();
+        let range = address_txs
+            .range((address, 0u64, 0u32)..(address, before_height, before_position))
+            .map_err(db_error)?;
+        for entry in range.rev() {
+            let (key, value) = entry.map_err(db_error)?;
+            let (_, height, position) = key.value();
+            if page.len() == limit {
+                return Ok((page, Some((height, position + 1))));
+            }
+            let (tx_id, received, spent) = value.value();
+            let block_id = blocks
+                .get(height)
+                .map_err(db_error)?
+                .map(|entry| hash_from_belts(entry.value().0));
+            page.push(AddressTransaction {
+                tx_id: Some(pb_common::Base58Hash {
+                    hash: tx_id.to_string(),
+                }),
+                height,
+                block_id,
+                received: Some(pb_common::Nicks { value: received }),
+                spent: Some(pb_common::Nicks { value: spent }),
+            });
+        }
+        Ok((page, None))
+    }
+
+    /// The `limit` addresses with the highest balances, richest first
+    pub fn rich_list(&self, limit: usize) -> Result<Vec<RichListEntry>> {
+        let txn = self.db.begin_read().map_err(db_error)?;
+        let rich = txn.open_table(RICH).map_err(db_error)?;
+        let mut entries = Vec::new();
+        for entry in rich.iter().map_err(db_error)?.rev().take(limit) {
+            let (key, _) = entry.map_err(db_error)?;
+            let (balance, address) = key.value();
+            entries.push(RichListEntry {
+                address: Some(pb_common::Base58Hash {
+                    hash: address.to_string(),
+                }),
+                balance: Some(pb_common::Nicks { value: balance }),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+/// Index the blocks of the public gRPC server at `addr`, from the last indexed one, for as long
+/// as the node runs
+pub async fn run_indexer(index: Arc<ExplorerIndex>, mut addr: SocketAddr) {
+    if addr.ip().is_unspecified() {
+        addr.set_ip(Ipv4Addr::LOCALHOST.into());
+    }
+    loop {
+        match follow_blocks(&index, addr).await {
+            Ok(()) => info!("Block subscription ended; resubscribing"),
+            Err(err) => warn!("Explorer indexer failed: {}; resubscribing", err),
+        }
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+async fn follow_blocks(index: &Arc<ExplorerIndex>, addr: SocketAddr) -> Result<()> {
+    let from_height = index.indexed_height()?.map_or(0, |height| height + 1);
+    let mut client = NockchainBlockServiceClient::connect(format!("http://{}", addr)).await?;
+    let mut stream = client
+        .subscribe_blocks(SubscribeBlocksRequest { from_height })
+        .await?
+        .into_inner();
+    info!(from_height, "Explorer indexer following blocks");
+
+    while let Some(update) = stream.message().await? {
+        let index = index.clone();
+        tokio::task::spawn_blocking(move || index.apply(&update))
+            .await
+            .map_err(|e| {
+                NockAppGrpcError::Internal(format!("Explorer indexer panicked: {}", e))
+            })??;
+    }
+    Ok(())
+}
+
+/// Serves the explorer index
+#[derive(Clone)]
+pub struct NockchainIndexServer {
+    index: Arc<ExplorerIndex>,
+}
+
+impl NockchainIndexServer {
+    pub fn new(index: Arc<ExplorerIndex>) -> Self {
+        Self { index }
+    }
+
+    fn indexed_height(&self) -> std::result::Result<u64, Status> {
+        Ok(self.index.indexed_height()?.unwrap_or(0))
+    }
+}
+
+fn decode_cursor(token: &str) -> Option<(u64, u32)> {
+    let (height, position) = token.split_once('.')?;
+    Some((
+        u64::from_str_radix(height, 16).ok()?,
+        u32::from_str_radix(position, 16).ok()?,
+    ))
+}
+
+fn encode_cursor((height, position): (u64, u32)) -> String {
+    format!("{:x}.{:x}", height, position)
+}
+
+#[tonic::async_trait]
+impl NockchainIndexService for NockchainIndexServer {
+    #[tracing::instrument(name = "grpc.index.get_address_transactions", skip(self, request))]
+    async fn get_address_transactions(
+        &self,
+        request: Request<GetAddressTransactionsRequest>,
+    ) -> std::result::Result<Response<GetAddressTransactionsResponse>, Status> {
+        let req = request.into_inner();
+        let address = req
+            .address
+            .map(|address| address.hash)
+            .ok_or_else(|| Status::invalid_argument("address is required"))?;
+        let page = req.page.unwrap_or_default();
+        let limit = match page.client_page_items_limit {
+            0 => DEFAULT_PAGE_SIZE,
+            limit => (limit as usize).min(MAX_PAGE_SIZE),
+        };
+        let cursor = match page.page_token.as_str() {
+            "" => None,
+            token => Some(
+                decode_cursor(token)
+                    .ok_or_else(|| Status::invalid_argument("invalid page token"))?,
+            ),
+        };
+
+        let (transactions, next) = self.index.address_transactions(&address, cursor, limit)?;
+        Ok(Response::new(GetAddressTransactionsResponse {
+            result: Some(get_address_transactions_response::Result::Transactions(
+                AddressTransactions {
+                    transactions,
+                    indexed_height: self.indexed_height()?,
+                    page: Some(pb_common::PageResponse {
+                        next_page_token: next.map(encode_cursor).unwrap_or_default(),
+                    }),
+                },
+            )),
+        }))
+    }
+
+    #[tracing::instrument(name = "grpc.index.get_indexed_transaction", skip(self, request))]
+    async fn get_indexed_transaction(
+        &self,
+        request: Request<GetIndexedTransactionRequest>,
+    ) -> std::result::Result<Response<GetIndexedTransactionResponse>, Status> {
+        let tx_id = request
+            .into_inner()
+            .tx_id
+            .map(|tx_id| tx_id.hash)
+            .ok_or_else(|| Status::invalid_argument("tx_id is required"))?;
+        match self.index.transaction_block(&tx_id)? {
+            Some(block) => Ok(Response::new(GetIndexedTransactionResponse {
+                result: Some(get_indexed_transaction_response::Result::Block(block)),
+            })),
+            None => Err(Status::not_found("Transaction not indexed")),
+        }
+    }
+
+    #[tracing::instrument(name = "grpc.index.get_rich_list", skip(self, request))]
+    async fn get_rich_list(
+        &self,
+        request: Request<GetRichListRequest>,
+    ) -> std::result::Result<Response<GetRichListResponse>, Status> {
+        let limit = match request.into_inner().limit {
+            0 => MAX_RICH_LIST,
+            limit => (limit as usize).min(MAX_RICH_LIST),
+        };
+        Ok(Response::new(GetRichListResponse {
+            result: Some(get_rich_list_response::Result::RichList(RichList {
+                entries: self.index.rich_list(limit)?,
+                indexed_height: self.indexed_height()?,
+            })),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(seed: u64) -> pb_common::Hash {
+        hash_from_belts([seed; 5])
+    }
+
+    fn note(first: &str, last: &str, amount: Option<u64>) -> NoteRef {
+        NoteRef {
+            first_name_b58: first.to_string(),
+            last_name_b58: last.to_string(),
+            amount: amount.map(|value| pb_common::Nicks { value }),
+        }
+    }
+
+    fn block(height: u64, transactions: Vec<BlockTransaction>) -> BlockUpdate {
+        BlockUpdate {
+            block: Some(BlockEntry {
+                block_id: Some(hash(height + 1)),
+                height,
+                parent: Some(hash(height)),
+                timestamp: 1_000 + height,
+                tx_ids: Vec::new(),
+            }),
+            transactions,
+        }
+    }
+
+    fn tx(id: &str, spent: Vec<NoteRef>, created: Vec<NoteRef>) -> BlockTransaction {
+        BlockTransaction {
+            tx_id: Some(pb_common::Base58Hash {
+                hash: id.to_string(),
+            }),
+            spent,
+            created,
+        }
+    }
+
+    fn balances(index: &ExplorerIndex) -> Vec<(String, u64)> {
+        index
+            .rich_list(10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.address.unwrap().hash, entry.balance.unwrap().value))
+            .collect()
+    }
+
+    #[test]
+    fn test_index_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = ExplorerIndex::open(&dir.path().join("index.redb")).unwrap();
+        assert_eq!(index.indexed_height().unwrap(), None);
+
+        index
+            .apply(&block(
+                0,
+                vec![tx(
+                    "tx-a",
+                    vec![note("coinbase", "c1", None)],
+                    vec![note("alice", "a1", Some(70)), note("bob", "b1", Some(30))],
+                )],
+            ))
+            .unwrap();
+        index
+            .apply(&block(
+                1,
+                vec![tx(
+                    "tx-b",
+                    vec![note("alice", "a1", None)],
+                    vec![note("bob", "b2", Some(50)), note("alice", "a2", Some(15))],
+                )],
+            ))
+            .unwrap();
+        assert_eq!(index.indexed_height().unwrap(), Some(1));
+        assert_eq!(
+            balances(&index),
+            vec![("bob".to_string(), 80), ("alice".to_string(), 15)]
+        );
+
+        let found = index.transaction_block("tx-b").unwrap().unwrap();
+        assert_eq!(found.height, 1);
+        assert_eq!(found.block_id, Some(hash(2)));
+        assert!(index.transaction_block("tx-c").unwrap().is_none());
+
+        // Newest first, one per page
+        let (page, next) = index.address_transactions("alice", None, 1).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].tx_id.as_ref().unwrap().hash, "tx-b");
+        assert_eq!(page[0].received.as_ref().unwrap().value, 15);
+        assert_eq!(page[0].spent.as_ref().unwrap().value, 70);
+        let next = decode_cursor(&encode_cursor(next.unwrap()));
+        let (page, next) = index.address_transactions("alice", next, 1).unwrap();
+        assert_eq!(page[0].tx_id.as_ref().unwrap().hash, "tx-a");
+        assert_eq!(page[0].received.as_ref().unwrap().value, 70);
+        assert_eq!(next, None);
+        // The coinbase note was never indexed, but the spend still shows in its history
+        let (page, _) = index.address_transactions("coinbase", None, 10).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].spent.as_ref().unwrap().value, 0);
+
+        // Blocks must follow on from the indexed tip
+        assert!(index.apply(&block(3, Vec::new())).is_err());
+        let mut orphan = block(2, Vec::new());
+        orphan.block.as_mut().unwrap().parent = Some(hash(9));
+        assert!(index.apply(&orphan).is_err());
+        assert_eq!(index.indexed_height().unwrap(), Some(1));
+
+        // The index survives reopening
+        drop(index);
+        let index = ExplorerIndex::open(&dir.path().join("index.redb")).unwrap();
+        assert_eq!(index.indexed_height().unwrap(), Some(1));
+        assert_eq!(balances(&index).len(), 2);
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+use super::peers::error_status;
+use crate::error::{NockAppGrpcError, Result};
+use crate::pb::public::v2::nockchain_mempool_service_server::NockchainMempoolService;
+use crate::pb::public::v2::*;
+
+/// The node's mempool admission policy, as enforced by its networking driver.
+#[async_trait]
+pub trait MempoolManager: Send + Sync {
+    async fn get_policy(&self) -> Result<MempoolPolicy>;
+
+    /// Replace the policy, returning how many transactions were evicted to fit it.
+    async fn set_policy(&self, policy: MempoolPolicy) -> Result<u64>;
+}
+
+#[derive(Clone)]
+pub struct NockchainMempoolServer {
+    mempool: Arc<dyn MempoolManager>,
+}
+
+impl NockchainMempoolServer {
+    pub fn new(mempool: Arc<dyn MempoolManager>) -> Self {
+        Self { mempool }
+    }
+}
+
+#[tonic::async_trait]
+impl NockchainMempoolService for NockchainMempoolServer {
+    async fn get_mempool_policy(
+        &self,
+        _request: Request<GetMempoolPolicyRequest>,
+    ) -> std::result::Result<Response<GetMempoolPolicyResponse>, Status> {
+        let result = match self.mempool.get_policy().await {
+            Ok(policy) => get_mempool_policy_response::Result::Policy(policy),
+            Err(e) => get_mempool_policy_response::Result::Error(error_status(e)),
+        };
+        Ok(Response::new(GetMempoolPolicyResponse {
+            result: Some(result),
+        }))
+    }
+
+    async fn set_mempool_policy(
+        &self,
+        request: Request<SetMempoolPolicyRequest>,
+    ) -> std::result::Result<Response<SetMempoolPolicyResponse>, Status> {
+        let remote_addr = request.remote_addr();
+        let policy = request.into_inner().policy;
+        info!(
+            "SetMempoolPolicy policy={:?} client_ip={:?}",
+            policy, remote_addr
+        );
+        let set = match policy {
+            Some(policy) => self.mempool.set_policy(policy).await,
+            None => Err(NockAppGrpcError::InvalidRequest(
+                "policy is required".to_string(),
+            )),
+        };
+        let result = match set {
+            Ok(evicted) => set_mempool_policy_response::Result::Evicted(evicted),
+            Err(e) => set_mempool_policy_response::Result::Error(error_status(e)),
+        };
+        Ok(Response::new(SetMempoolPolicyResponse {
+            result: Some(result),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::pb::common::v1::ErrorCode;
+
+    fn policy(max_txs: u64) -> MempoolPolicy {
+        MempoolPolicy {
+            max_txs,
+            max_tx_bytes: 1024,
+            min_fee_rate: 100,
+            max_txs_per_peer: 1,
+            replacement_fee_bump_percent: Some(10),
+        }
+    }
+
+    struct FakeMempool {
+        max_txs: Mutex<u64>,
+    }
+
+    #[async_trait]
+    impl MempoolManager for FakeMempool {
+        async fn get_policy(&self) -> Result<MempoolPolicy> {
+            Ok(policy(*self.max_txs.lock().unwrap()))
+        }
+
+        async fn set_policy(&self, policy: MempoolPolicy) -> Result<u64> {
+            let mut max_txs = self.max_txs.lock().unwrap();
+            let evicted = max_txs.saturating_sub(policy.max_txs);
+            *max_txs = policy.max_txs;
+            Ok(evicted)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mempool_service() {
+        let server = NockchainMempoolServer::new(Arc::new(FakeMempool {
+            max_txs: Mutex::new(5),
+        }));
+
+        let set = server
+            .set_mempool_policy(Request::new(SetMempoolPolicyRequest {
+                policy: Some(policy(2)),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            set.result,
+            Some(set_mempool_policy_response::Result::Evicted(3))
+        );
+
+        let got = server
+            .get_mempool_policy(Request::new(GetMempoolPolicyRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            got.result,
+            Some(get_mempool_policy_response::Result::Policy(policy(2)))
+        );
+
+        let missing = server
+            .set_mempool_policy(Request::new(SetMempoolPolicyRequest { policy: None }))
+            .await
+            .unwrap()
+            .into_inner();
+        match missing.result {
+            Some(set_mempool_policy_response::Result::Error(e)) => {
+                assert_eq!(e.code, ErrorCode::InvalidRequest as i32)
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+    }
+}
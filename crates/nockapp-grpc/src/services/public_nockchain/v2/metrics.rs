@@ -181,6 +181,10 @@ metrics_struct![
         block_explorer_get_transaction_details_pending,
         "nockchain_public_grpc.block_explorer.get_transaction_details.pending", Count
     ),
+    (
+        block_explorer_get_transaction_details_pruned,
+        "nockchain_public_grpc.block_explorer.get_transaction_details.pruned", Count
+    ),
     (
         block_explorer_get_transaction_details_invalid_request,
         "nockchain_public_grpc.block_explorer.get_transaction_details.invalid_request", Count
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tonic::{Request, Response, Status};
+
+use super::peers::error_status;
+use crate::error::Result;
+use crate::pb::public::v2::nockchain_mining_service_server::NockchainMiningService;
+use crate::pb::public::v2::*;
+
+/// Performance of the node's mining driver.
+#[async_trait]
+pub trait MiningMonitor: Send + Sync {
+    async fn mining_stats(&self) -> Result<MiningStats>;
+}
+
+#[derive(Clone)]
+pub struct NockchainMiningServer {
+    mining: Arc<dyn MiningMonitor>,
+}
+
+impl NockchainMiningServer {
+    pub fn new(mining: Arc<dyn MiningMonitor>) -> Self {
+        Self { mining }
+    }
+}
+
+#[tonic::async_trait]
+impl NockchainMiningService for NockchainMiningServer {
+    async fn get_mining_stats(
+        &self,
+        _request: Request<GetMiningStatsRequest>,
+    ) -> std::result::Result<Response<GetMiningStatsResponse>, Status> {
+        let result = match self.mining.mining_stats().await {
+            Ok(stats) => get_mining_stats_response::Result::Stats(stats),
+            Err(e) => get_mining_stats_response::Result::Error(error_status(e)),
+        };
+        Ok(Response::new(GetMiningStatsResponse {
+            result: Some(result),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeMining;
+
+    #[async_trait]
+    impl MiningMonitor for FakeMining {
+        async fn mining_stats(&self) -> Result<MiningStats> {
+            Ok(MiningStats {
+                proof_rate: 0.5,
+                proofs: 30,
+                blocks: 1,
+                uptime_secs: 60,
+                candidates: vec![CandidateStats {
+                    header: "header".to_string(),
+                    attempts: 32,
+                    proofs: 30,
+                    started_at: 1,
+                }],
+                threads: vec![],
+                recent_proofs: vec![],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mining_service() {
+        let server = NockchainMiningServer::new(Arc::new(FakeMining));
+        let response = server
+            .get_mining_stats(Request::new(GetMiningStatsRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        match response.result {
+            Some(get_mining_stats_response::Result::Stats(stats)) => {
+                assert_eq!(stats.proofs, 30);
+                assert_eq!(stats.candidates[0].attempts, 32);
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+    }
+}
@@ -2,7 +2,12 @@ pub mod block_explorer;
 mod cache;
 pub mod client;
 pub mod driver;
+pub mod indexer;
+pub mod mempool;
 pub mod metrics;
+pub mod mining;
+pub mod peers;
+pub mod reorgs;
 pub mod server;
 
 #[cfg(test)]
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+use crate::error::{NockAppGrpcError, Result};
+use crate::pb::common::v1::{Acknowledged, ErrorCode, ErrorStatus};
+use crate::pb::public::v2::nockchain_peer_service_server::NockchainPeerService;
+use crate::pb::public::v2::*;
+
+/// The node's peer set, as managed by its networking driver.
+#[async_trait]
+pub trait PeerManager: Send + Sync {
+    async fn list_peers(&self) -> Result<Vec<PeerEntry>>;
+
+    /// Disconnect the peer with this base58 peer ID and refuse it from now on.
+    async fn ban_peer(&self, peer_id: &str) -> Result<()>;
+
+    async fn unban_peer(&self, peer_id: &str) -> Result<()>;
+
+    /// Dial the peer at this multiaddr and keep it connected.
+    async fn add_peer(&self, address: &str) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct NockchainPeerServer {
+    peers: Arc<dyn PeerManager>,
+}
+
+impl NockchainPeerServer {
+    pub fn new(peers: Arc<dyn PeerManager>) -> Self {
+        Self { peers }
+    }
+}
+
+pub(super) fn error_status(error: NockAppGrpcError) -> ErrorStatus {
+    ErrorStatus {
+        code: match &error {
+            NockAppGrpcError::InvalidRequest(_) => ErrorCode::InvalidRequest as i32,
+            _ => ErrorCode::InternalError as i32,
+        },
+        message: error.to_string(),
+        details: None,
+    }
+}
+
+fn required(field: &str, value: String) -> Result<String> {
+    if value.is_empty() {
+        return Err(NockAppGrpcError::InvalidRequest(format!(
+            "{} is required",
+            field
+        )));
+    }
+    Ok(value)
+}
+
+#[tonic::async_trait]
+impl NockchainPeerService for NockchainPeerServer {
+    async fn list_peers(
+        &self,
+        _request: Request<ListPeersRequest>,
+    ) -> std::result::Result<Response<ListPeersResponse>, Status> {
+        let result = match self.peers.list_peers().await {
+            Ok(peers) => list_peers_response::Result::Peers(PeerList { peers }),
+            Err(e) => list_peers_response::Result::Error(error_status(e)),
+        };
+        Ok(Response::new(ListPeersResponse {
+            result: Some(result),
+        }))
+    }
+
+    async fn ban_peer(
+        &self,
+        request: Request<BanPeerRequest>,
+    ) -> std::result::Result<Response<BanPeerResponse>, Status> {
+        let remote_addr = request.remote_addr();
+        let peer_id = request.into_inner().peer_id;
+        info!("BanPeer peer_id={} client_ip={:?}", peer_id, remote_addr);
+        let banned = match required("peer_id", peer_id) {
+            Ok(peer_id) => self.peers.ban_peer(&peer_id).await,
+            Err(e) => Err(e),
+        };
+        let result = match banned {
+            Ok(()) => ban_peer_response::Result::Ack(Acknowledged {}),
+            Err(e) => ban_peer_response::Result::Error(error_status(e)),
+        };
+        Ok(Response::new(BanPeerResponse {
+            result: Some(result),
+        }))
+    }
+
+    async fn unban_peer(
+        &self,
+        request: Request<UnbanPeerRequest>,
+    ) -> std::result::Result<Response<UnbanPeerResponse>, Status> {
+        let remote_addr = request.remote_addr();
+        let peer_id = request.into_inner().peer_id;
+        info!("UnbanPeer peer_id={} client_ip={:?}", peer_id, remote_addr);
+        let unbanned = match required("peer_id", peer_id) {
+            Ok(peer_id) => self.peers.unban_peer(&peer_id).await,
+            Err(e) => Err(e),
+        };
+        let result = match unbanned {
+            Ok(()) => unban_peer_response::Result::Ack(Acknowledged {}),
+            Err(e) => unban_peer_response::Result::Error(error_status(e)),
+        };
+        Ok(Response::new(UnbanPeerResponse {
+            result: Some(result),
+        }))
+    }
+
+    async fn add_peer(
+        &self,
+        request: Request<AddPeerRequest>,
+    ) -> std::result::Result<Response<AddPeerResponse>, Status> {
+        let remote_addr = request.remote_addr();
+        let address = request.into_inner().address;
+        info!("AddPeer address={} client_ip={:?}", address, remote_addr);
+        let added = match required("address", address) {
+            Ok(address) => self.peers.add_peer(&address).await,
+            Err(e) => Err(e),
+        };
+        let result = match added {
+            Ok(()) => add_peer_response::Result::Ack(Acknowledged {}),
+            Err(e) => add_peer_response::Result::Error(error_status(e)),
+        };
+        Ok(Response::new(AddPeerResponse {
+            result: Some(result),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakePeers {
+        banned: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl PeerManager for FakePeers {
+        async fn list_peers(&self) -> Result<Vec<PeerEntry>> {
+            Ok(vec![PeerEntry {
+                peer_id: "12D3KooWPeer".to_string(),
+                address: "/ip4/10.0.0.1/udp/4001/quic-v1".to_string(),
+                direction: PeerDirection::Inbound as i32,
+                agent_version: String::new(),
+                protocol_version: String::new(),
+                last_seen: 1,
+            }])
+        }
+
+        async fn ban_peer(&self, peer_id: &str) -> Result<()> {
+            self.banned.lock().unwrap().push(peer_id.to_string());
+            Ok(())
+        }
+
+        async fn unban_peer(&self, _peer_id: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn add_peer(&self, address: &str) -> Result<()> {
+            Err(NockAppGrpcError::InvalidRequest(format!(
+                "invalid multiaddr {}",
+                address
+            )))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_peer_service() {
+        let peers = Arc::new(FakePeers::default());
+        let server = NockchainPeerServer::new(peers.clone());
+
+        let listed = server
+            .list_peers(Request::new(ListPeersRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        match listed.result {
+            Some(list_peers_response::Result::Peers(list)) => assert_eq!(list.peers.len(), 1),
+            other => panic!("unexpected response {:?}", other),
+        }
+
+        let banned = server
+            .ban_peer(Request::new(BanPeerRequest {
+                peer_id: "12D3KooWPeer".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(matches!(
+            banned.result,
+            Some(ban_peer_response::Result::Ack(_))
+        ));
+        assert_eq!(*peers.banned.lock().unwrap(), vec!["12D3KooWPeer"]);
+
+        let missing = server
+            .ban_peer(Request::new(BanPeerRequest {
+                peer_id: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        match missing.result {
+            Some(ban_peer_response::Result::Error(e)) => {
+                assert_eq!(e.code, ErrorCode::InvalidRequest as i32)
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+
+        let added = server
+            .add_peer(Request::new(AddPeerRequest {
+                address: "nonsense".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(matches!(
+            added.result,
+            Some(add_peer_response::Result::Error(_))
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use nockapp::driver::NockAppHandle;
+use nockapp::Noun;
+use nockchain_math::noun_ext::NounMathExt;
+use nockchain_types::tx_engine::common::Hash;
+use nockvm::ext::NounExt;
+use noun_serde::{NounDecode, NounDecodeError};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::{debug, info, warn};
+
+use crate::pb::common::v1 as pb_common;
+use crate::pb::public::v2::nockchain_reorg_service_server::NockchainReorgService;
+use crate::pb::public::v2::*;
+
+/// Reorgs replayed to new subscribers
+const RECENT_REORGS: usize = 64;
+
+/// Reorgs a subscriber may fall behind by before it is dropped
+const SUBSCRIBER_QUEUE: usize = 64;
+
+/// Decode a `[%reorg ancestor orphaned heaviest]` effect.
+pub fn decode_reorg_effect(effect: &Noun) -> Result<ReorgEvent, NounDecodeError> {
+    let effect_cell = effect.as_cell()?;
+    if !effect_cell.head().eq_bytes(b"reorg") {
+        return Err(NounDecodeError::InvalidTag);
+    }
+    let [ancestor, orphaned, heaviest] = effect_cell
+        .tail()
+        .uncell()
+        .map_err(|_| NounDecodeError::ExpectedCell)?;
+    Ok(ReorgEvent {
+        ancestor_height: u64::from_noun(&ancestor)?,
+        orphaned: Vec::<Hash>::from_noun(&orphaned)?
+            .into_iter()
+            .map(pb_common::Hash::from)
+            .collect(),
+        heaviest: Some(Hash::from_noun(&heaviest)?.into()),
+    })
+}
+
+/// Reorgs of the node's heaviest chain: the most recent ones, and a channel for new ones.
+pub struct ReorgFeed {
+    recent: Mutex<VecDeque<ReorgEvent>>,
+    events: broadcast::Sender<ReorgEvent>,
+}
+
+impl Default for ReorgFeed {
+    fn default() -> Self {
+        Self {
+            recent: Mutex::new(VecDeque::new()),
+            events: broadcast::channel(SUBSCRIBER_QUEUE).0,
+        }
+    }
+}
+
+impl ReorgFeed {
+    pub fn publish(&self, event: ReorgEvent) {
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.push_back(event.clone());
+        if recent.len() > RECENT_REORGS {
+            recent.pop_front();
+        }
+        // No subscribers is fine
+        let _ = self.events.send(event);
+    }
+
+    /// The remembered reorgs, oldest first, and a receiver for every reorg published after them.
+    pub fn subscribe(&self) -> (Vec<ReorgEvent>, broadcast::Receiver<ReorgEvent>) {
+        let recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        (recent.iter().cloned().collect(), self.events.subscribe())
+    }
+
+    /// Publish the reorgs in the kernel's `%reorg` effects until the app exits.
+    pub async fn follow(self: Arc<Self>, handle: NockAppHandle) {
+        loop {
+            let effect = match handle.next_effect().await {
+                Ok(effect) => effect,
+                Err(_) => continue,
+            };
+            match decode_reorg_effect(unsafe { effect.root() }) {
+                Ok(event) => {
+                    info!(
+                        "Heaviest chain reorganized to ancestor at height {}, {} blocks orphaned",
+                        event.ancestor_height,
+                        event.orphaned.len()
+                    );
+                    self.publish(event);
+                }
+                Err(NounDecodeError::InvalidTag) => {}
+                Err(err) => warn!("Failed to decode reorg effect: {}", err),
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NockchainReorgServer {
+    feed: Arc<ReorgFeed>,
+}
+
+impl NockchainReorgServer {
+    pub fn new(feed: Arc<ReorgFeed>) -> Self {
+        Self { feed }
+    }
+}
+
+#[tonic::async_trait]
+impl NockchainReorgService for NockchainReorgServer {
+    type SubscribeReorgsStream = ReceiverStream<std::result::Result<ReorgEvent, Status>>;
+
+    async fn subscribe_reorgs(
+        &self,
+        request: Request<SubscribeReorgsRequest>,
+    ) -> std::result::Result<Response<Self::SubscribeReorgsStream>, Status> {
+        let follow = request.into_inner().follow;
+        let (recent, mut events) = self.feed.subscribe();
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_QUEUE);
+        tokio::spawn(async move {
+            for event in recent {
+                if tx.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+            if !follow {
+                return;
+            }
+            loop {
+                let item = match events.recv().await {
+                    Ok(event) => Ok(event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        debug!("Reorg subscriber lagged by {missed} events, closing stream");
+                        Err(Status::data_loss(format!(
+                            "missed {missed} reorgs, resubscribe to catch up"
+                        )))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let lagged = item.is_err();
+                if tx.send(item).await.is_err() || lagged {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nockapp::noun::slab::NounSlab;
+    use nockchain_math::belt::Belt;
+    use nockvm::noun::{D, T};
+    use nockvm_macros::tas;
+    use noun_serde::NounEncode;
+    use tokio_stream::StreamExt;
+
+    use super::*;
+
+    fn hash(n: u64) -> Hash {
+        Hash([Belt(n), Belt(0), Belt(0), Belt(0), Belt(0)])
+    }
+
+    fn event(ancestor_height: u64) -> ReorgEvent {
+        ReorgEvent {
+            ancestor_height,
+            orphaned: vec![hash(ancestor_height + 1).into()],
+            heaviest: Some(hash(100).into()),
+        }
+    }
+
+    #[test]
+    fn reorg_effect_is_decoded() {
+        let mut slab: NounSlab = NounSlab::new();
+        let orphaned = vec![hash(12), hash(11)].to_noun(&mut slab);
+        let heaviest = hash(20).to_noun(&mut slab);
+        let effect = T(&mut slab, &[D(tas!(b"reorg")), D(10), orphaned, heaviest]);
+
+        let event = decode_reorg_effect(&effect).unwrap();
+        assert_eq!(event.ancestor_height, 10);
+        assert_eq!(
+            event.orphaned,
+            vec![pb_common::Hash::from(hash(12)), hash(11).into()]
+        );
+        assert_eq!(event.heaviest, Some(hash(20).into()));
+
+        let other = T(&mut slab, &[D(tas!(b"seen")), D(0)]);
+        assert!(matches!(
+            decode_reorg_effect(&other),
+            Err(NounDecodeError::InvalidTag)
+        ));
+    }
+
+    #[tokio::test]
+    async fn subscribers_get_recent_reorgs_then_new_ones() {
+        let feed = Arc::new(ReorgFeed::default());
+        feed.publish(event(1));
+        let server = NockchainReorgServer::new(feed.clone());
+
+        let replay: Vec<_> = server
+            .subscribe_reorgs(Request::new(SubscribeReorgsRequest { follow: false }))
+            .await
+            .unwrap()
+            .into_inner()
+            .collect()
+            .await;
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].as_ref().unwrap().ancestor_height, 1);
+
+        let mut stream = server
+            .subscribe_reorgs(Request::new(SubscribeReorgsRequest { follow: true }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(stream.next().await.unwrap().unwrap().ancestor_height, 1);
+        feed.publish(event(2));
+        assert_eq!(stream.next().await.unwrap().unwrap().ancestor_height, 2);
+    }
+}
@@ -11,8 +11,9 @@ use nockapp::wire::WireRepr;
 use nockchain_types::tx_engine::{v0, v1};
 use nockvm::noun::SIG;
 use noun_serde::{NounDecode, NounEncode};
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tokio::time::{self, Duration};
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 use tonic_reflection::server::Builder as ReflectionBuilder;
@@ -22,15 +23,25 @@ use super::block_explorer::BlockExplorerCache;
 use super::cache::{
     AddressBalanceCache, DEFAULT_PAGE_BYTES, DEFAULT_PAGE_SIZE, MAX_PAGE_BYTES, MAX_PAGE_SIZE,
 };
+use super::indexer::{run_indexer, ExplorerIndex, NockchainIndexServer};
+use super::mempool::{MempoolManager, NockchainMempoolServer};
 use super::metrics::{init_metrics, NockchainGrpcApiMetrics};
+use super::mining::{MiningMonitor, NockchainMiningServer};
+use super::peers::{NockchainPeerServer, PeerManager};
+use super::reorgs::{NockchainReorgServer, ReorgFeed};
 use crate::error::{NockAppGrpcError, Result};
 use crate::pb::common::v1::{Acknowledged, ErrorCode, ErrorStatus};
 use crate::pb::public::v2::nockchain_block_service_server::{
     NockchainBlockService, NockchainBlockServiceServer,
 };
+use crate::pb::public::v2::nockchain_index_service_server::NockchainIndexServiceServer;
+use crate::pb::public::v2::nockchain_mempool_service_server::NockchainMempoolServiceServer;
 use crate::pb::public::v2::nockchain_metrics_service_server::{
     NockchainMetricsService, NockchainMetricsServiceServer,
 };
+use crate::pb::public::v2::nockchain_mining_service_server::NockchainMiningServiceServer;
+use crate::pb::public::v2::nockchain_peer_service_server::NockchainPeerServiceServer;
+use crate::pb::public::v2::nockchain_reorg_service_server::NockchainReorgServiceServer;
 use crate::pb::public::v2::nockchain_service_server::{NockchainService, NockchainServiceServer};
 use crate::pb::public::v2::*;
 use crate::public_nockchain::v2::cache::{
@@ -45,6 +56,12 @@ use crate::wire_conversion::{create_grpc_wire, grpc_wire_to_nockapp};
 
 const DEFAULT_HEAVIEST_CHAIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
 
+/// Blocks a SubscribeBlocks stream may buffer ahead of its client
+const BLOCK_SUBSCRIBER_QUEUE: usize = 16;
+
+/// How long SubscribeBlocks waits to retry a block the kernel cannot serve yet
+const BLOCK_RETRY_DELAY: Duration = Duration::from_secs(2);
+
 #[async_trait]
 pub trait BalanceHandle: Send + Sync {
     async fn peek(
@@ -88,6 +105,11 @@ pub struct PublicNockchainGrpcServer {
     block_explorer_cache: Arc<BlockExplorerCache>,
     metrics: Arc<NockchainGrpcApiMetrics>,
     heaviest_chain: Arc<RwLock<Option<HeaviestChainSnapshot>>>,
+    index: Option<Arc<ExplorerIndex>>,
+    peer_manager: Option<Arc<dyn PeerManager>>,
+    mempool_manager: Option<Arc<dyn MempoolManager>>,
+    mining_monitor: Option<Arc<dyn MiningMonitor>>,
+    reorg_feed: Option<Arc<ReorgFeed>>,
 }
 
 #[derive(Clone)]
@@ -110,9 +132,46 @@ impl PublicNockchainGrpcServer {
             block_explorer_cache,
             metrics,
             heaviest_chain: Arc::new(RwLock::new(None)),
+            index: None,
+            peer_manager: None,
+            mempool_manager: None,
+            mining_monitor: None,
+            reorg_feed: None,
         }
     }
 
+    /// Also keep `index` up to date from the server's own block stream, and serve it
+    pub fn with_index(mut self, index: Arc<ExplorerIndex>) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Also serve `NockchainPeerService`, backed by `peers`. Peer management is for operators,
+    /// so only enable it where the public address is not exposed to untrusted clients.
+    pub fn with_peer_manager(mut self, peers: Arc<dyn PeerManager>) -> Self {
+        self.peer_manager = Some(peers);
+        self
+    }
+
+    /// Also serve `NockchainMempoolService`, backed by `mempool`. Like peer management, this is
+    /// for operators only.
+    pub fn with_mempool_manager(mut self, mempool: Arc<dyn MempoolManager>) -> Self {
+        self.mempool_manager = Some(mempool);
+        self
+    }
+
+    /// Also serve `NockchainMiningService`, backed by `mining`.
+    pub fn with_mining_monitor(mut self, mining: Arc<dyn MiningMonitor>) -> Self {
+        self.mining_monitor = Some(mining);
+        self
+    }
+
+    /// Also serve `NockchainReorgService`, streaming the reorgs published to `reorgs`.
+    pub fn with_reorg_feed(mut self, reorgs: Arc<ReorgFeed>) -> Self {
+        self.reorg_feed = Some(reorgs);
+        self
+    }
+
     #[cfg(test)]
     pub(crate) fn with_handle(handle: Arc<dyn BalanceHandle>) -> Self {
         let metrics = init_metrics();
@@ -125,6 +184,11 @@ impl PublicNockchainGrpcServer {
             block_explorer_cache,
             metrics,
             heaviest_chain: Arc::new(RwLock::new(None)),
+            index: None,
+            peer_manager: None,
+            mempool_manager: None,
+            mining_monitor: None,
+            reorg_feed: None,
         }
     }
 
@@ -174,6 +238,49 @@ impl PublicNockchainGrpcServer {
             self.block_explorer_cache.clone(),
             self.metrics.clone(),
         ));
+        let index_api = self.index.clone().map(|index| {
+            tokio::spawn(run_indexer(index.clone(), addr));
+            NockchainIndexServiceServer::new(NockchainIndexServer::new(index))
+        });
+
+        let peer_api = self
+            .peer_manager
+            .clone()
+            .map(|peers| NockchainPeerServiceServer::new(NockchainPeerServer::new(peers)));
+        if peer_api.is_some() {
+            info!("Serving peer management on {}", addr);
+            health_reporter
+                .set_serving::<NockchainPeerServiceServer<NockchainPeerServer>>()
+                .await;
+        }
+        let mempool_api = self.mempool_manager.clone().map(|mempool| {
+            NockchainMempoolServiceServer::new(NockchainMempoolServer::new(mempool))
+        });
+        if mempool_api.is_some() {
+            info!("Serving mempool policy management on {}", addr);
+            health_reporter
+                .set_serving::<NockchainMempoolServiceServer<NockchainMempoolServer>>()
+                .await;
+        }
+        let mining_api = self
+            .mining_monitor
+            .clone()
+            .map(|mining| NockchainMiningServiceServer::new(NockchainMiningServer::new(mining)));
+        if mining_api.is_some() {
+            info!("Serving mining stats on {}", addr);
+            health_reporter
+                .set_serving::<NockchainMiningServiceServer<NockchainMiningServer>>()
+                .await;
+        }
+        let reorg_api = self
+            .reorg_feed
+            .clone()
+            .map(|reorgs| NockchainReorgServiceServer::new(NockchainReorgServer::new(reorgs)));
+        if reorg_api.is_some() {
+            health_reporter
+                .set_serving::<NockchainReorgServiceServer<NockchainReorgServer>>()
+                .await;
+        }
 
         Server::builder()
             .add_service(health_service)
@@ -181,6 +288,11 @@ impl PublicNockchainGrpcServer {
             .add_service(nockchain_api)
             .add_service(block_explorer_api)
             .add_service(metrics_api)
+            .add_optional_service(index_api)
+            .add_optional_service(peer_api)
+            .add_optional_service(mempool_api)
+            .add_optional_service(mining_api)
+            .add_optional_service(reorg_api)
             .serve(addr)
             .await
             .map_err(NockAppGrpcError::Transport)?;
@@ -1820,6 +1932,25 @@ impl NockchainBlockService for NockchainBlockServer {
                     })),
                 )
             }
+            Err(NockAppGrpcError::TxPruned) => {
+                metrics
+                    .block_explorer_get_transaction_details_pruned
+                    .increment();
+                let block = self
+                    .block_explorer_cache
+                    .get_block_for_tx(&tx_hash)
+                    .await
+                    .map(|meta| meta.to_transaction_block_data());
+                timed_return(
+                    &metrics.block_explorer_get_transaction_details_success,
+                    request_start,
+                    Ok(Response::new(GetTransactionDetailsResponse {
+                        result: Some(get_transaction_details_response::Result::Pruned(
+                            TransactionPruned { block },
+                        )),
+                    })),
+                )
+            }
             Err(NockAppGrpcError::NotFound) => {
                 metrics
                     .block_explorer_get_transaction_details_not_found
@@ -1837,6 +1968,44 @@ impl NockchainBlockService for NockchainBlockServer {
             }
         }
     }
+
+    type SubscribeBlocksStream = ReceiverStream<std::result::Result<BlockUpdate, Status>>;
+
+    #[tracing::instrument(name = "grpc.block_explorer.subscribe_blocks", skip(self, request))]
+    async fn subscribe_blocks(
+        &self,
+        request: Request<SubscribeBlocksRequest>,
+    ) -> std::result::Result<Response<Self::SubscribeBlocksStream>, Status> {
+        let mut height = request.into_inner().from_height;
+        info!(from_height = height, "Serving SubscribeBlocks request");
+
+        let handle = self.handle.clone();
+        let cache = self.block_explorer_cache.clone();
+        let (tx, rx) = mpsc::channel(BLOCK_SUBSCRIBER_QUEUE);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = cache.wait_for_height(height) => {}
+                    _ = tx.closed() => return,
+                }
+                let item = match cache.load_block_update(&handle, height).await {
+                    Ok(update) => Ok(update),
+                    Err(NockAppGrpcError::PeekReturnedNoData) => {
+                        debug!(height, "Block not available yet; retrying");
+                        time::sleep(BLOCK_RETRY_DELAY).await;
+                        continue;
+                    }
+                    Err(err) => Err(Status::from(err)),
+                };
+                let failed = item.is_err();
+                if tx.send(item).await.is_err() || failed {
+                    return;
+                }
+                height += 1;
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,214 @@
+//! The on-disk layout of NockApps started with `--data-dir`, which keeps every app's state under
+//! one root instead of wherever each app used to put it:
+//!
+//! ```text
+//! <root>/<app>/checkpoints   kernel checkpoints and event log
+//! <root>/<app>/pma           scratch space, cleared at boot
+//! <root>/<app>/...           the app's other files, e.g. the node's libp2p identity
+//! <root>/logs/<binary>.log   a copy of the log output
+//! ```
+//!
+//! `--migrate-data-dir` moves an app's state from its old location into the layout. The old
+//! location is left with a [`MOVED_FILE`] naming the new one, so starting the app there again
+//! without `--data-dir` fails instead of booting with empty state.
+
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use thiserror::Error;
+use tracing::info;
+
+/// Left at an app's old location once its state has been migrated
+pub const MOVED_FILE: &str = "MOVED";
+
+#[derive(Debug, Error)]
+pub enum DataDirError {
+    #[error("{0} already exists, refusing to migrate over it")]
+    DestinationExists(PathBuf),
+    #[error("State in {from} was moved to {to}, start with --data-dir to use it")]
+    Moved { from: PathBuf, to: PathBuf },
+    #[error("Failed to migrate {from} to {to}: {source}")]
+    Io {
+        from: PathBuf,
+        to: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Root of the `--data-dir` layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataDir {
+    root: PathBuf,
+}
+
+impl DataDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Everything the app `name` keeps on disk.
+    pub fn app(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    pub fn checkpoints(&self, name: &str) -> PathBuf {
+        self.app(name).join("checkpoints")
+    }
+
+    pub fn pma(&self, name: &str) -> PathBuf {
+        self.app(name).join("pma")
+    }
+
+    pub fn logs(&self) -> PathBuf {
+        self.root.join("logs")
+    }
+
+    /// Log file for the binary `name`.
+    pub fn log_file(&self, name: &str) -> PathBuf {
+        self.logs().join(format!("{name}.log"))
+    }
+}
+
+/// Move a file or directory from its old location `from` to `to`, returning whether there was
+/// anything to move. A directory is left behind at `from` holding a [`MOVED_FILE`].
+///
+/// `to` may already exist as an empty directory, or contain `from` itself, as when the old layout
+/// nested an app's state one level deeper than the new one.
+pub fn migrate(from: &Path, to: &Path) -> Result<bool, DataDirError> {
+    let io_err = |source| DataDirError::Io {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+        source,
+    };
+    if !from.exists() || from.join(MOVED_FILE).exists() {
+        return Ok(false);
+    }
+    let nested = from.starts_with(to);
+    let source = if nested {
+        let staging = to.with_extension("migrating");
+        fs::rename(from, &staging).map_err(io_err)?;
+        staging
+    } else {
+        from.to_path_buf()
+    };
+    if to.exists() {
+        let empty = to.is_dir() && fs::read_dir(to).map_err(io_err)?.next().is_none();
+        if !empty {
+            if nested {
+                fs::rename(&source, from).map_err(io_err)?;
+            }
+            return Err(DataDirError::DestinationExists(to.to_path_buf()));
+        }
+        fs::remove_dir(to).map_err(io_err)?;
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(io_err)?;
+    }
+    if fs::rename(&source, to).is_err() {
+        // Probably a different filesystem
+        copy_all(&source, to).map_err(io_err)?;
+        remove_all(&source).map_err(io_err)?;
+    }
+    if to.is_dir() && !nested {
+        fs::create_dir_all(from).map_err(io_err)?;
+        fs::write(from.join(MOVED_FILE), to.display().to_string()).map_err(io_err)?;
+    }
+    info!("Migrated {} to {}", from.display(), to.display());
+    Ok(true)
+}
+
+/// Fail if the state that was at `dir` has been migrated.
+pub fn check_not_moved(dir: &Path) -> Result<(), DataDirError> {
+    match fs::read_to_string(dir.join(MOVED_FILE)) {
+        Ok(to) => Err(DataDirError::Moved {
+            from: dir.to_path_buf(),
+            to: PathBuf::from(to.trim()),
+        }),
+        Err(_) => Ok(()),
+    }
+}
+
+fn copy_all(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+fn remove_all(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_moves_state_and_leaves_a_note() {
+        let tmp = tempfile::tempdir().unwrap();
+        let old = tmp.path().join(".data.nockchain");
+        fs::create_dir_all(old.join("checkpoints")).unwrap();
+        fs::write(old.join("checkpoints").join("0.chkjam"), b"state").unwrap();
+        let data_dir = DataDir::new(tmp.path().join("root"));
+
+        assert!(migrate(&old, &data_dir.app("nockchain")).unwrap());
+        let moved = fs::read(data_dir.checkpoints("nockchain").join("0.chkjam")).unwrap();
+        assert_eq!(moved, b"state");
+        assert!(matches!(
+            check_not_moved(&old),
+            Err(DataDirError::Moved { to, .. }) if to == data_dir.app("nockchain")
+        ));
+
+        // Running the migration again finds nothing to move
+        assert!(!migrate(&old, &data_dir.app("nockchain")).unwrap());
+    }
+
+    #[test]
+    fn migrate_handles_nested_and_occupied_destinations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let data_dir = DataDir::new(tmp.path());
+        let old = data_dir.app("wallet").join("wallet");
+        fs::create_dir_all(old.join("checkpoints")).unwrap();
+
+        assert!(migrate(&old, &data_dir.app("wallet")).unwrap());
+        assert!(data_dir.checkpoints("wallet").is_dir());
+        assert!(!data_dir.app("wallet").join("wallet").exists());
+
+        let other = tmp.path().join("other");
+        fs::create_dir_all(&other).unwrap();
+        assert!(matches!(
+            migrate(&other, &data_dir.app("wallet")),
+            Err(DataDirError::DestinationExists(_))
+        ));
+        assert!(other.exists());
+    }
+
+    #[test]
+    fn migrate_moves_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let old = tmp.path().join(".nockchain_identity");
+        fs::write(&old, b"key").unwrap();
+        let new = DataDir::new(tmp.path().join("root"))
+            .app("nockchain")
+            .join("identity");
+
+        assert!(migrate(&old, &new).unwrap());
+        assert_eq!(fs::read(&new).unwrap(), b"key");
+        assert!(!old.exists());
+    }
+}
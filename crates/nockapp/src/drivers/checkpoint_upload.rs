@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::checkpoint_store::CheckpointStore;
+use crate::nockapp::driver::{make_driver, IODriverFn};
+
+/// Creates an IO driver that uploads the newest checkpoint in `checkpoint_dir` to `store` every
+/// `interval`, skipping ticks where nothing new has been saved. A failed upload is logged and
+/// tried again on the next tick.
+pub fn checkpoint_upload(
+    mut store: CheckpointStore,
+    checkpoint_dir: PathBuf,
+    interval: Duration,
+) -> IODriverFn {
+    make_driver(move |_handle| async move {
+        let mut upload_interval = time::interval(interval);
+        upload_interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        loop {
+            upload_interval.tick().await;
+            match store.upload_latest(&checkpoint_dir).await {
+                Ok(Some(event_num)) => info!(
+                    "Uploaded checkpoint at event {} to {}",
+                    event_num,
+                    store.location()
+                ),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to upload checkpoint to {}: {}", store.location(), e),
+            }
+        }
+    })
+}
@@ -5,8 +5,8 @@ use std::time::Duration;
 
 use anyhow::Result;
 use instant_acme::{
-    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, LetsEncrypt,
-    NewAccount, NewOrder, Order, OrderStatus,
+    Account, AccountCredentials, AuthorizationStatus, Challenge, ChallengeType, Identifier,
+    LetsEncrypt, NewAccount, NewOrder, Order, OrderStatus,
 };
 use rustls::pki_types::pem::PemObject;
 use rustls::pki_types::{CertificateDer, PrivateKeyDer};
@@ -16,11 +16,15 @@ use tokio::fs;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+use crate::drivers::http::dns::{challenge_record_name, DnsProvider, TxtRecord};
+
 pub struct AcmeManager {
     account: Account,
     domain: String,
     cache_dir: PathBuf,
     http_challenges: Arc<RwLock<HashMap<String, String>>>,
+    /// Answers DNS-01 challenges instead of HTTP-01 when set
+    dns_provider: Option<Arc<dyn DnsProvider>>,
 }
 
 impl AcmeManager {
@@ -63,9 +67,24 @@ impl AcmeManager {
             domain,
             cache_dir,
             http_challenges: Arc::new(RwLock::new(HashMap::new())),
+            dns_provider: None,
         })
     }
 
+    /// Answer challenges with DNS-01 through `provider` rather than HTTP-01. Required for
+    /// wildcard domains, and for servers the CA can't reach on port 80.
+    pub fn with_dns_provider(mut self, provider: Arc<dyn DnsProvider>) -> Self {
+        self.dns_provider = Some(provider);
+        self
+    }
+
+    /// How long DNS-01 challenges wait for their records to propagate, zero for HTTP-01.
+    pub fn dns_propagation_delay(&self) -> Duration {
+        self.dns_provider
+            .as_ref()
+            .map_or(Duration::ZERO, |dns| dns.propagation_delay())
+    }
+
     pub async fn get_certificate(&self) -> Result<ServerConfig> {
         let cert_path = self.cache_dir.join("cert.pem");
         let key_path = self.cache_dir.join("key.pem");
@@ -137,31 +156,20 @@ impl AcmeManager {
 
         debug!("Created order");
 
-        // Process challenges
-        self.process_challenges(&mut order).await?;
-
-        // Wait for order to be ready
-        loop {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-            order.refresh().await?;
-
-            match order.state().status {
-                OrderStatus::Ready => {
-                    info!("Order ready, finalizing certificate");
-                    break;
-                }
-                OrderStatus::Invalid => {
-                    return Err(anyhow::anyhow!("Order became invalid"));
-                }
-                OrderStatus::Pending => {
-                    debug!("Order still pending...");
-                    continue;
-                }
-                _ => {
-                    debug!("Order status: {:?}", order.state().status);
+        // Process challenges, then wait for the order to be ready before removing any DNS records
+        let mut dns_records = Vec::new();
+        let mut ready = self.process_challenges(&mut order, &mut dns_records).await;
+        if ready.is_ok() {
+            ready = Self::wait_for_ready(&mut order).await;
+        }
+        if let Some(dns) = &self.dns_provider {
+            for record in &dns_records {
+                if let Err(e) = dns.delete_txt_record(record).await {
+                    warn!("Failed to remove DNS record {}: {}", record.name, e);
                 }
             }
         }
+        ready?;
 
         // Generate key pair and CSR
         let key_pair = rcgen::KeyPair::generate()?;
@@ -258,17 +266,61 @@ impl AcmeManager {
         Ok(config)
     }
 
-    async fn process_challenges(&self, order: &mut Order) -> Result<()> {
+    async fn wait_for_ready(order: &mut Order) -> Result<()> {
+        loop {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            order.refresh().await?;
+
+            match order.state().status {
+                OrderStatus::Ready => {
+                    info!("Order ready, finalizing certificate");
+                    break;
+                }
+                OrderStatus::Invalid => {
+                    return Err(anyhow::anyhow!("Order became invalid"));
+                }
+                OrderStatus::Pending => {
+                    debug!("Order still pending...");
+                    continue;
+                }
+                _ => {
+                    debug!("Order status: {:?}", order.state().status);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer the order's pending challenges, adding any DNS records made for them to
+    /// `dns_records` to be removed once the order is ready.
+    async fn process_challenges(
+        &self,
+        order: &mut Order,
+        dns_records: &mut Vec<TxtRecord>,
+    ) -> Result<()> {
         let authorizations = order.authorizations().await?;
 
         for authz in authorizations {
             match authz.status {
+                AuthorizationStatus::Pending if self.dns_provider.is_some() => {
+                    let challenge = authz
+                        .challenges
+                        .iter()
+                        .find(|c| c.r#type == ChallengeType::Dns01)
+                        .ok_or_else(|| anyhow::anyhow!("No DNS-01 challenge found"))?;
+                    self.process_dns_challenge(order, challenge, dns_records)
+                        .await?;
+                }
                 AuthorizationStatus::Pending => {
                     let challenge = authz
                         .challenges
                         .iter()
                         .find(|c| c.r#type == ChallengeType::Http01)
-                        .ok_or_else(|| anyhow::anyhow!("No HTTP-01 challenge found"))?;
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "No HTTP-01 challenge found, wildcard domains need DNS-01 (ACME_CHALLENGE=dns-01)"
+                            )
+                        })?;
 
                     let key_authorization = order.key_authorization(challenge);
 
@@ -310,6 +362,37 @@ impl AcmeManager {
         Ok(())
     }
 
+    async fn process_dns_challenge(
+        &self,
+        order: &mut Order,
+        challenge: &Challenge,
+        dns_records: &mut Vec<TxtRecord>,
+    ) -> Result<()> {
+        let dns = self
+            .dns_provider
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No DNS provider configured"))?;
+        let key_authorization = order.key_authorization(challenge);
+        let name = challenge_record_name(&self.domain);
+
+        info!("Starting DNS-01 challenge for {}", self.domain);
+        let record = dns
+            .create_txt_record(&name, &key_authorization.dns_value())
+            .await?;
+        dns_records.push(record);
+
+        let delay = dns.propagation_delay();
+        info!(
+            "Waiting {}s for the DNS record {} to propagate",
+            delay.as_secs(),
+            name
+        );
+        tokio::time::sleep(delay).await;
+
+        order.set_challenge_ready(&challenge.url).await?;
+        Ok(())
+    }
+
     pub fn get_challenge_handler(&self) -> Arc<RwLock<HashMap<String, String>>> {
         self.http_challenges.clone()
     }
@@ -0,0 +1,335 @@
+//! DNS providers for answering ACME DNS-01 challenges.
+//!
+//! A DNS-01 challenge is answered by publishing a TXT record at `_acme-challenge.<domain>`, so
+//! certificates can be issued without the CA reaching this server over HTTP, and for wildcard
+//! domains. The provider is picked with `ACME_DNS_PROVIDER`:
+//!
+//! - `cloudflare`: `CLOUDFLARE_API_TOKEN` and `CLOUDFLARE_ZONE_ID`
+//! - `route53`: `ROUTE53_HOSTED_ZONE_ID`, `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+//!   optionally `AWS_SESSION_TOKEN`
+//! - `manual`: logs the record for the operator to create by hand
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::utils::sigv4::sigv4_authorization;
+
+/// How long to wait after publishing a record before asking the CA to check it, by default.
+const DEFAULT_PROPAGATION_DELAY: Duration = Duration::from_secs(60);
+
+/// How long the operator has to create a record by hand, by default.
+const DEFAULT_MANUAL_PROPAGATION_DELAY: Duration = Duration::from_secs(300);
+
+/// TTL of the challenge records we create, in seconds
+const RECORD_TTL: u32 = 60;
+
+/// A TXT record published for a challenge.
+#[derive(Debug, Clone)]
+pub struct TxtRecord {
+    pub name: String,
+    pub value: String,
+    /// The provider's id for the record, if it has one
+    pub id: Option<String>,
+}
+
+#[async_trait]
+pub trait DnsProvider: Send + Sync {
+    /// Publish a TXT record with `value` at `name`.
+    async fn create_txt_record(&self, name: &str, value: &str) -> Result<TxtRecord>;
+
+    /// Remove a record made by [`DnsProvider::create_txt_record`].
+    async fn delete_txt_record(&self, record: &TxtRecord) -> Result<()>;
+
+    /// How long a new record takes to be visible to the CA.
+    fn propagation_delay(&self) -> Duration {
+        DEFAULT_PROPAGATION_DELAY
+    }
+}
+
+/// The name of the challenge record for `domain`. Wildcard domains share their base domain's.
+pub fn challenge_record_name(domain: &str) -> String {
+    format!("_acme-challenge.{}", domain.trim_start_matches("*."))
+}
+
+/// Build the provider named by `ACME_DNS_PROVIDER`. `ACME_DNS_PROPAGATION_SECS` overrides how
+/// long to wait for records to propagate.
+pub fn dns_provider_from_env() -> Result<Arc<dyn DnsProvider>> {
+    let provider = env::var("ACME_DNS_PROVIDER").context("ACME_DNS_PROVIDER is not set")?;
+    let propagation_delay = match env::var("ACME_DNS_PROPAGATION_SECS") {
+        Ok(secs) => Some(Duration::from_secs(
+            secs.parse()
+                .context("ACME_DNS_PROPAGATION_SECS is not a number")?,
+        )),
+        Err(_) => None,
+    };
+
+    let provider: Arc<dyn DnsProvider> = match provider.to_lowercase().as_str() {
+        "cloudflare" => Arc::new(CloudflareDns {
+            client: Client::new(),
+            api_token: env::var("CLOUDFLARE_API_TOKEN")
+                .context("CLOUDFLARE_API_TOKEN is not set")?,
+            zone_id: env::var("CLOUDFLARE_ZONE_ID").context("CLOUDFLARE_ZONE_ID is not set")?,
+            propagation_delay: propagation_delay.unwrap_or(DEFAULT_PROPAGATION_DELAY),
+        }),
+        "route53" => Arc::new(Route53Dns {
+            client: Client::new(),
+            hosted_zone_id: env::var("ROUTE53_HOSTED_ZONE_ID")
+                .context("ROUTE53_HOSTED_ZONE_ID is not set")?,
+            access_key_id: env::var("AWS_ACCESS_KEY_ID").context("AWS_ACCESS_KEY_ID is not set")?,
+            secret_access_key: env::var("AWS_SECRET_ACCESS_KEY")
+                .context("AWS_SECRET_ACCESS_KEY is not set")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+            propagation_delay: propagation_delay.unwrap_or(DEFAULT_PROPAGATION_DELAY),
+        }),
+        "manual" => Arc::new(ManualDns {
+            propagation_delay: propagation_delay.unwrap_or(DEFAULT_MANUAL_PROPAGATION_DELAY),
+        }),
+        other => return Err(anyhow!("Unknown ACME_DNS_PROVIDER: {}", other)),
+    };
+    Ok(provider)
+}
+
+/// Records managed through the Cloudflare API with a token allowed to edit the zone's DNS.
+pub struct CloudflareDns {
+    client: Client,
+    api_token: String,
+    zone_id: String,
+    propagation_delay: Duration,
+}
+
+impl CloudflareDns {
+    pub fn new(api_token: String, zone_id: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_token,
+            zone_id,
+            propagation_delay: DEFAULT_PROPAGATION_DELAY,
+        }
+    }
+
+    fn records_url(&self) -> String {
+        format!(
+            "https://api.cloudflare.com/client/v4/zones/{}/dns_records",
+            self.zone_id
+        )
+    }
+}
+
+#[async_trait]
+impl DnsProvider for CloudflareDns {
+    async fn create_txt_record(&self, name: &str, value: &str) -> Result<TxtRecord> {
+        let response: serde_json::Value = self
+            .client
+            .post(self.records_url())
+            .bearer_auth(&self.api_token)
+            .json(&json!({
+                "type": "TXT",
+                "name": name,
+                "content": value,
+                "ttl": RECORD_TTL,
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if response["success"] != json!(true) {
+            return Err(anyhow!(
+                "Cloudflare refused to create {}: {}", name, response["errors"]
+            ));
+        }
+        let id = response["result"]["id"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Cloudflare response for {} has no record id", name))?;
+        debug!("Created Cloudflare TXT record {} ({})", name, id);
+        Ok(TxtRecord {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: Some(id.to_string()),
+        })
+    }
+
+    async fn delete_txt_record(&self, record: &TxtRecord) -> Result<()> {
+        let id = record
+            .id
+            .as_ref()
+            .ok_or_else(|| anyhow!("Cloudflare record {} has no id", record.name))?;
+        self.client
+            .delete(format!("{}/{}", self.records_url(), id))
+            .bearer_auth(&self.api_token)
+            .send()
+            .await?
+            .error_for_status()?;
+        debug!("Deleted Cloudflare TXT record {} ({})", record.name, id);
+        Ok(())
+    }
+
+    fn propagation_delay(&self) -> Duration {
+        self.propagation_delay
+    }
+}
+
+/// Records managed through the Route 53 API, with requests signed using AWS Signature Version 4.
+pub struct Route53Dns {
+    client: Client,
+    hosted_zone_id: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    propagation_delay: Duration,
+}
+
+const ROUTE53_HOST: &str = "route53.amazonaws.com";
+/// Route 53 is a global service, signed for this region.
+const ROUTE53_REGION: &str = "us-east-1";
+
+impl Route53Dns {
+    pub fn new(
+        hosted_zone_id: String,
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            hosted_zone_id,
+            access_key_id,
+            secret_access_key,
+            session_token,
+            propagation_delay: DEFAULT_PROPAGATION_DELAY,
+        }
+    }
+
+    async fn change_record(&self, action: &str, name: &str, value: &str) -> Result<()> {
+        let zone_id = self.hosted_zone_id.trim_start_matches("/hostedzone/");
+        let path = format!("/2013-04-01/hostedzone/{}/rrset/", zone_id);
+        let body = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ChangeResourceRecordSetsRequest xmlns="https://route53.amazonaws.com/doc/2013-04-01/">
+<ChangeBatch><Changes><Change><Action>{action}</Action><ResourceRecordSet><Name>{name}</Name><Type>TXT</Type><TTL>{RECORD_TTL}</TTL><ResourceRecords><ResourceRecord><Value>"{value}"</Value></ResourceRecord></ResourceRecords></ResourceRecordSet></Change></Changes></ChangeBatch>
+</ChangeResourceRecordSetsRequest>"#
+        );
+
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(body.as_bytes()));
+        let mut headers = vec![
+            ("content-type", "text/xml".to_string()),
+            ("host", ROUTE53_HOST.to_string()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization = sigv4_authorization(
+            &self.access_key_id, &self.secret_access_key, "POST", &path, &headers, &payload_hash,
+            &amz_date, ROUTE53_REGION, "route53",
+        );
+
+        let mut request = self
+            .client
+            .post(format!("https://{}{}", ROUTE53_HOST, path))
+            .header("authorization", authorization)
+            .body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, value);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Route 53 {} of {} failed with {}: {}", action, name, status, text
+            ));
+        }
+        debug!("Route 53 {} of TXT record {} accepted", action, name);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DnsProvider for Route53Dns {
+    async fn create_txt_record(&self, name: &str, value: &str) -> Result<TxtRecord> {
+        self.change_record("UPSERT", name, value).await?;
+        Ok(TxtRecord {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: None,
+        })
+    }
+
+    async fn delete_txt_record(&self, record: &TxtRecord) -> Result<()> {
+        self.change_record("DELETE", &record.name, &record.value)
+            .await
+    }
+
+    fn propagation_delay(&self) -> Duration {
+        self.propagation_delay
+    }
+}
+
+/// Records created by hand: the record is logged, and the operator has the propagation delay to
+/// publish it before the CA checks.
+pub struct ManualDns {
+    propagation_delay: Duration,
+}
+
+impl ManualDns {
+    pub fn new(propagation_delay: Duration) -> Self {
+        Self { propagation_delay }
+    }
+}
+
+#[async_trait]
+impl DnsProvider for ManualDns {
+    async fn create_txt_record(&self, name: &str, value: &str) -> Result<TxtRecord> {
+        warn!(
+            "Create a DNS TXT record at {} with the value {} within {} seconds",
+            name,
+            value,
+            self.propagation_delay.as_secs()
+        );
+        Ok(TxtRecord {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: None,
+        })
+    }
+
+    async fn delete_txt_record(&self, record: &TxtRecord) -> Result<()> {
+        info!(
+            "The DNS TXT record at {} is no longer needed and can be removed",
+            record.name
+        );
+        Ok(())
+    }
+
+    fn propagation_delay(&self) -> Duration {
+        self.propagation_delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_record_name() {
+        assert_eq!(
+            challenge_record_name("example.com"),
+            "_acme-challenge.example.com"
+        );
+        assert_eq!(
+            challenge_record_name("*.example.com"),
+            "_acme-challenge.example.com"
+        );
+    }
+}
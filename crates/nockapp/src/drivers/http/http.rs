@@ -19,6 +19,8 @@ use tower_http::services::ServeDir;
 use tracing::{debug, error, info, warn};
 
 use crate::drivers::http::acme::AcmeManager;
+use crate::drivers::http::dns::dns_provider_from_env;
+use crate::drivers::http::middleware::{HttpConfig, HttpConfigError};
 use crate::nockapp::driver::{make_driver, IODriverFn, PokeResult};
 use crate::nockapp::wire::{Wire, WireRepr};
 use crate::nockapp::NockAppError;
@@ -59,6 +61,8 @@ pub enum HttpError {
     EnvError(#[from] env::VarError),
     #[error("Noun processing error: {0}")]
     NounError(#[from] nockvm::noun::Error),
+    #[error("HTTP config error: {0}")]
+    ConfigError(#[from] HttpConfigError),
 }
 
 impl From<HttpError> for NockAppError {
@@ -67,6 +71,7 @@ impl From<HttpError> for NockAppError {
             HttpError::BindError(io_err) => NockAppError::IoError(io_err),
             HttpError::EffectError(nock_err) => nock_err,
             HttpError::Utf8Error(utf8_err) => NockAppError::FromUtf8Error(utf8_err),
+            HttpError::ConfigError(config_err) => NockAppError::OtherError(config_err.to_string()),
             _ => NockAppError::OtherError(String::from("HttpError: Unknown error")),
         }
     }
@@ -179,6 +184,8 @@ pub fn http() -> IODriverFn {
         let domain = env::var("HTTPS_DOMAIN").unwrap_or_else(|_| "localhost".to_string());
         // Directory to serve static files from
         let web_dir = env::var("WEB_DIR").ok();
+        // CORS, body limits, timeouts and auth, from the file named by `HTTP_CONFIG`
+        let http_config = HttpConfig::from_env().map_err(HttpError::ConfigError)?;
 
         // Check if we're running locally
         let is_local = domain == "localhost"
@@ -209,9 +216,26 @@ pub fn http() -> IODriverFn {
                 .unwrap_or_else(|_| crate::system_data_dir().join("acme"));
             info!("HTTPS enabled with domain: {}, email: {}", domain, email);
             info!("Setting up Let's Encrypt for domain: {}", domain);
-            let acme_manager = AcmeManager::new(domain.clone(), email.clone(), cache_dir.clone())
-                .await
-                .map_err(HttpError::AcmeError)?;
+            let mut acme_manager =
+                AcmeManager::new(domain.clone(), email.clone(), cache_dir.clone())
+                    .await
+                    .map_err(HttpError::AcmeError)?;
+            // Challenge type to answer, `http-01` unless set
+            let challenge = env::var("ACME_CHALLENGE").unwrap_or_else(|_| "http-01".to_string());
+            match challenge.to_lowercase().as_str() {
+                "http-01" => {}
+                "dns-01" => {
+                    let provider = dns_provider_from_env().map_err(HttpError::AcmeError)?;
+                    info!("Answering ACME challenges with DNS-01");
+                    acme_manager = acme_manager.with_dns_provider(provider);
+                }
+                other => {
+                    return Err(HttpError::AcmeError(anyhow::anyhow!(
+                        "Unknown ACME_CHALLENGE: {}", other
+                    ))
+                    .into());
+                }
+            }
 
             let challenges = acme_manager.get_challenge_handler();
 
@@ -264,6 +288,7 @@ pub fn http() -> IODriverFn {
             }
             router.with_state(app_state.clone())
         };
+        let app = http_config.apply(app).map_err(HttpError::ConfigError)?;
 
         if is_local {
             // Local development: just run HTTP on port 8080
@@ -304,12 +329,12 @@ pub fn http() -> IODriverFn {
             let acme_manager =
                 acme_manager_opt.expect("acme_manager should be set when https is enabled");
             let app_for_https = app.clone();
+            // 5 minute timeout, plus however long DNS-01 records take to propagate
+            let certificate_timeout =
+                tokio::time::Duration::from_secs(300) + acme_manager.dns_propagation_delay();
             tokio::spawn(async move {
-                match tokio::time::timeout(
-                    tokio::time::Duration::from_secs(300), // 5 minute timeout
-                    acme_manager.get_certificate(),
-                )
-                .await
+                match tokio::time::timeout(certificate_timeout, acme_manager.get_certificate())
+                    .await
                 {
                     Ok(Ok(tls_config)) => {
                         info!("Successfully got certificate, starting HTTPS server");
@@ -0,0 +1,465 @@
+//! Configurable middleware for the HTTP driver.
+//!
+//! Layers are read from the TOML file named by the `HTTP_CONFIG` environment variable. Every
+//! section is optional, and a missing file leaves the driver as it was:
+//!
+//! ```toml
+//! [cors]
+//! allow_origins = ["https://example.com"]   # or ["*"]
+//! allow_methods = ["GET", "POST"]
+//! allow_headers = ["content-type", "authorization"]
+//! allow_credentials = false
+//! max_age_secs = 3600
+//!
+//! [limits]
+//! max_body_bytes = 1048576
+//!
+//! [timeouts]
+//! default_secs = 30
+//! routes = [{ prefix = "/api/slow", secs = 120 }]
+//!
+//! [auth]
+//! bearer_tokens = ["secret-token"]
+//! basic = [{ username = "admin", password = "hunter2" }]
+//! exempt_paths = ["/health"]
+//! ```
+//!
+//! CORS sits outermost so preflight requests are answered without credentials, then auth, then
+//! timeouts, then the body limit. ACME HTTP-01 challenges are always exempt from auth.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{DefaultBodyLimit, Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use axum::middleware::{from_fn_with_state, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tracing::{debug, info};
+
+/// Environment variable naming the middleware config file
+pub const HTTP_CONFIG_ENV: &str = "HTTP_CONFIG";
+
+/// Path prefix ACME HTTP-01 challenges are served under, never put behind auth
+const ACME_CHALLENGE_PREFIX: &str = "/.well-known/acme-challenge/";
+
+#[derive(Debug, thiserror::Error)]
+pub enum HttpConfigError {
+    #[error("Failed to read HTTP config {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Failed to parse HTTP config: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid CORS origin: {0}")]
+    InvalidOrigin(String),
+    #[error("Invalid CORS method: {0}")]
+    InvalidMethod(String),
+    #[error("Invalid CORS header: {0}")]
+    InvalidHeader(String),
+    #[error("CORS credentials can't be allowed with a wildcard {0}")]
+    CredentialsWithWildcard(&'static str),
+    #[error("Auth is configured without any bearer tokens or basic credentials")]
+    NoCredentials,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HttpConfig {
+    pub cors: Option<CorsConfig>,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub timeouts: TimeoutsConfig,
+    pub auth: Option<AuthConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Origins allowed to make requests, `"*"` for any
+    #[serde(default)]
+    pub allow_origins: Vec<String>,
+    /// Methods allowed on requests, `"*"` for any
+    #[serde(default = "default_cors_methods")]
+    pub allow_methods: Vec<String>,
+    /// Request headers allowed, `"*"` for any
+    #[serde(default)]
+    pub allow_headers: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+    /// How long browsers may cache preflight responses
+    pub max_age_secs: Option<u64>,
+}
+
+fn default_cors_methods() -> Vec<String> {
+    vec!["GET".to_string(), "POST".to_string()]
+}
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Largest request body accepted, axum's 2MB default if unset
+    pub max_body_bytes: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TimeoutsConfig {
+    /// Timeout for requests not matching any route, none if unset
+    pub default_secs: Option<u64>,
+    /// Timeouts for paths under a prefix, the longest matching prefix winning
+    #[serde(default)]
+    pub routes: Vec<RouteTimeout>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RouteTimeout {
+    pub prefix: String,
+    pub secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Tokens accepted as `Authorization: Bearer <token>`
+    #[serde(default)]
+    pub bearer_tokens: Vec<String>,
+    /// Credentials accepted as `Authorization: Basic <base64(username:password)>`
+    #[serde(default)]
+    pub basic: Vec<BasicCredentials>,
+    /// Path prefixes served without credentials
+    #[serde(default)]
+    pub exempt_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BasicCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl HttpConfig {
+    /// Load the config from the file named by [`HTTP_CONFIG_ENV`], or the default (no
+    /// middleware) if it isn't set.
+    pub fn from_env() -> Result<Self, HttpConfigError> {
+        match std::env::var(HTTP_CONFIG_ENV) {
+            Ok(path) => Self::load(Path::new(&path)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, HttpConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| HttpConfigError::Read(path.to_path_buf(), e))?;
+        let config: Self = toml::from_str(&contents)?;
+        info!("Loaded HTTP middleware config from {}", path.display());
+        Ok(config)
+    }
+
+    /// Wrap `router` in the configured middleware.
+    pub fn apply(&self, mut router: Router) -> Result<Router, HttpConfigError> {
+        if let Some(max_body_bytes) = self.limits.max_body_bytes {
+            debug!("Limiting request bodies to {} bytes", max_body_bytes);
+            router = router.layer(DefaultBodyLimit::max(max_body_bytes));
+        }
+        if self.timeouts.default_secs.is_some() || !self.timeouts.routes.is_empty() {
+            let timeouts = Arc::new(self.timeouts.clone());
+            router = router.layer(from_fn_with_state(timeouts, timeout_middleware));
+        }
+        if let Some(auth) = &self.auth {
+            if auth.bearer_tokens.is_empty() && auth.basic.is_empty() {
+                return Err(HttpConfigError::NoCredentials);
+            }
+            router = router.layer(from_fn_with_state(Arc::new(auth.clone()), auth_middleware));
+        }
+        if let Some(cors) = &self.cors {
+            router = router.layer(cors.layer()?);
+        }
+        Ok(router)
+    }
+}
+
+impl CorsConfig {
+    fn layer(&self) -> Result<CorsLayer, HttpConfigError> {
+        let origins = if is_wildcard(&self.allow_origins) {
+            if self.allow_credentials {
+                return Err(HttpConfigError::CredentialsWithWildcard("origin"));
+            }
+            AllowOrigin::any()
+        } else {
+            let origins = self
+                .allow_origins
+                .iter()
+                .map(|origin| {
+                    HeaderValue::from_str(origin)
+                        .map_err(|_| HttpConfigError::InvalidOrigin(origin.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowOrigin::list(origins)
+        };
+        let methods = if is_wildcard(&self.allow_methods) {
+            if self.allow_credentials {
+                return Err(HttpConfigError::CredentialsWithWildcard("method"));
+            }
+            AllowMethods::any()
+        } else {
+            let methods = self
+                .allow_methods
+                .iter()
+                .map(|method| {
+                    Method::from_bytes(method.to_uppercase().as_bytes())
+                        .map_err(|_| HttpConfigError::InvalidMethod(method.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowMethods::list(methods)
+        };
+        let headers = if is_wildcard(&self.allow_headers) {
+            if self.allow_credentials {
+                return Err(HttpConfigError::CredentialsWithWildcard("header"));
+            }
+            AllowHeaders::any()
+        } else {
+            let headers = self
+                .allow_headers
+                .iter()
+                .map(|header| {
+                    HeaderName::from_bytes(header.as_bytes())
+                        .map_err(|_| HttpConfigError::InvalidHeader(header.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            AllowHeaders::list(headers)
+        };
+
+        let mut layer = CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(self.allow_credentials);
+        if let Some(max_age_secs) = self.max_age_secs {
+            layer = layer.max_age(Duration::from_secs(max_age_secs));
+        }
+        Ok(layer)
+    }
+}
+
+fn is_wildcard(values: &[String]) -> bool {
+    values.iter().any(|value| value == "*")
+}
+
+impl TimeoutsConfig {
+    /// Timeout for a request to `path`, if it has one.
+    fn for_path(&self, path: &str) -> Option<Duration> {
+        self.routes
+            .iter()
+            .filter(|route| path.starts_with(&route.prefix))
+            .max_by_key(|route| route.prefix.len())
+            .map(|route| route.secs)
+            .or(self.default_secs)
+            .map(Duration::from_secs)
+    }
+}
+
+impl AuthConfig {
+    fn is_exempt(&self, path: &str) -> bool {
+        path.starts_with(ACME_CHALLENGE_PREFIX)
+            || self
+                .exempt_paths
+                .iter()
+                .any(|prefix| path.starts_with(prefix))
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(value) = headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok()) else {
+            return false;
+        };
+        if let Some(token) = value.strip_prefix("Bearer ") {
+            return self
+                .bearer_tokens
+                .iter()
+                .any(|expected| constant_time_eq(expected.as_bytes(), token.trim().as_bytes()));
+        }
+        if let Some(encoded) = value.strip_prefix("Basic ") {
+            let Ok(decoded) = STANDARD.decode(encoded.trim()) else {
+                return false;
+            };
+            let Some((username, password)) = std::str::from_utf8(&decoded)
+                .ok()
+                .and_then(|creds| creds.split_once(':'))
+            else {
+                return false;
+            };
+            return self.basic.iter().any(|creds| {
+                constant_time_eq(creds.username.as_bytes(), username.as_bytes())
+                    & constant_time_eq(creds.password.as_bytes(), password.as_bytes())
+            });
+        }
+        false
+    }
+
+    /// The challenge sent with a 401, preferring basic so browsers prompt for it.
+    fn challenge(&self) -> &'static str {
+        if self.basic.is_empty() {
+            "Bearer"
+        } else {
+            "Basic realm=\"nockapp\""
+        }
+    }
+}
+
+/// Compare secrets without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn timeout_middleware(
+    State(timeouts): State<Arc<TimeoutsConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(timeout) = timeouts.for_path(request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let uri = request.uri().clone();
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            debug!("Request for {} timed out after {:?}", uri, timeout);
+            StatusCode::GATEWAY_TIMEOUT.into_response()
+        }
+    }
+}
+
+async fn auth_middleware(
+    State(auth): State<Arc<AuthConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if auth.is_exempt(request.uri().path()) || auth.is_authorized(request.headers()) {
+        return next.run(request).await;
+    }
+    debug!("Rejecting unauthorized request for {}", request.uri());
+    (
+        StatusCode::UNAUTHORIZED,
+        [(WWW_AUTHENTICATE, auth.challenge())],
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [cors]
+        allow_origins = ["https://example.com"]
+        allow_headers = ["content-type"]
+        max_age_secs = 60
+
+        [limits]
+        max_body_bytes = 1024
+
+        [timeouts]
+        default_secs = 30
+        routes = [
+            { prefix = "/api", secs = 10 },
+            { prefix = "/api/slow", secs = 120 },
+        ]
+
+        [auth]
+        bearer_tokens = ["token"]
+        basic = [{ username = "admin", password = "hunter2" }]
+        exempt_paths = ["/health"]
+    "#;
+
+    fn config() -> HttpConfig {
+        toml::from_str(CONFIG).expect("config should parse")
+    }
+
+    fn authorization(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = config();
+        let cors = config.cors.as_ref().unwrap();
+        assert_eq!(cors.allow_methods, vec!["GET", "POST"]);
+        assert_eq!(cors.max_age_secs, Some(60));
+        assert_eq!(config.limits.max_body_bytes, Some(1024));
+        assert_eq!(config.timeouts.routes.len(), 2);
+        assert_eq!(config.auth.as_ref().unwrap().basic[0].username, "admin");
+
+        assert_eq!(
+            toml::from_str::<HttpConfig>("").unwrap(),
+            HttpConfig::default()
+        );
+        assert!(toml::from_str::<HttpConfig>("[limits]\nmax_body = 1").is_err());
+    }
+
+    #[test]
+    fn test_timeout_for_path() {
+        let timeouts = config().timeouts;
+        assert_eq!(timeouts.for_path("/"), Some(Duration::from_secs(30)));
+        assert_eq!(
+            timeouts.for_path("/api/poke"),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            timeouts.for_path("/api/slow/job"),
+            Some(Duration::from_secs(120))
+        );
+
+        let routes_only = TimeoutsConfig {
+            default_secs: None,
+            routes: vec![RouteTimeout {
+                prefix: "/api".to_string(),
+                secs: 5,
+            }],
+        };
+        assert_eq!(routes_only.for_path("/index.html"), None);
+    }
+
+    #[test]
+    fn test_auth() {
+        let auth = config().auth.unwrap();
+        assert!(auth.is_authorized(&authorization("Bearer token")));
+        assert!(!auth.is_authorized(&authorization("Bearer tokens")));
+        let basic = STANDARD.encode("admin:hunter2");
+        assert!(auth.is_authorized(&authorization(&format!("Basic {basic}"))));
+        let wrong = STANDARD.encode("admin:hunter3");
+        assert!(!auth.is_authorized(&authorization(&format!("Basic {wrong}"))));
+        assert!(!auth.is_authorized(&authorization("Basic not base64!")));
+        assert!(!auth.is_authorized(&HeaderMap::new()));
+
+        assert!(auth.is_exempt("/health/live"));
+        assert!(auth.is_exempt("/.well-known/acme-challenge/abc"));
+        assert!(!auth.is_exempt("/api"));
+    }
+
+    #[test]
+    fn test_cors_config() {
+        assert!(config().cors.unwrap().layer().is_ok());
+
+        let wildcard: CorsConfig =
+            toml::from_str("allow_origins = [\"*\"]\nallow_credentials = true").unwrap();
+        assert!(matches!(
+            wildcard.layer(),
+            Err(HttpConfigError::CredentialsWithWildcard("origin"))
+        ));
+
+        let bad_method: CorsConfig = toml::from_str("allow_methods = [\"GE T\"]").unwrap();
+        assert!(matches!(
+            bad_method.layer(),
+            Err(HttpConfigError::InvalidMethod(_))
+        ));
+    }
+}
@@ -1,6 +1,10 @@
 pub mod acme;
+pub mod dns;
 #[allow(clippy::module_inception)]
 pub mod http;
+pub mod middleware;
 
 pub use acme::AcmeManager;
+pub use dns::DnsProvider;
 pub use http::http;
+pub use middleware::HttpConfig;
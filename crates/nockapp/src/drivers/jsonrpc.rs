@@ -0,0 +1,462 @@
+//! JSON-RPC 2.0 over stdin and stdout, for running a NockApp as the subprocess of an editor,
+//! language server client or script.
+//!
+//! Messages are framed one per line, or with `Content-Length` headers as in the Language Server
+//! Protocol. JSON values travel to and from the kernel as cords of JSON text, which the kernel
+//! can parse with `de:json:html` or pass along untouched. Request ids are JSON text too, so a
+//! response goes back with the id exactly as the client sent it. Batches are not supported.
+//!
+//! Since stdout carries the protocol, apps using this driver should log to stderr, e.g. with
+//! `--log-stderr`.
+
+use nockvm::noun::{Atom, Noun, D, T};
+use nockvm_macros::tas;
+use noun_serde::{NounDecode, NounDecodeError};
+use serde_json::{json, Value};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+use crate::nockapp::driver::{make_driver, IODriverFn, PokeResult};
+use crate::nockapp::error::NockAppError;
+use crate::nockapp::wire::{Wire, WireRepr};
+use crate::noun::slab::NounSlab;
+use crate::AtomExt;
+
+/// Largest `Content-Length` accepted from the client
+const MAX_MESSAGE_LEN: usize = 64 << 20;
+
+/// Messages read ahead of the kernel
+const MESSAGE_QUEUE: usize = 64;
+
+// Error codes from the JSON-RPC 2.0 spec
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// How messages are delimited on the stream
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// One message per line
+    #[default]
+    Lines,
+    /// `Content-Length: <n>` and a blank line before each message, as in LSP
+    Headers,
+}
+
+pub enum JsonRpcWire {
+    Request,
+    Notify,
+}
+
+impl Wire for JsonRpcWire {
+    const VERSION: u64 = 1;
+    const SOURCE: &'static str = "rpc";
+
+    fn to_wire(&self) -> WireRepr {
+        let tags = match self {
+            JsonRpcWire::Request => vec!["request".into()],
+            JsonRpcWire::Notify => vec!["notify".into()],
+        };
+        WireRepr::new(JsonRpcWire::SOURCE, JsonRpcWire::VERSION, tags)
+    }
+}
+
+/// A message from the client, as it will be poked into the kernel.
+#[derive(Clone, Debug, PartialEq)]
+enum Incoming {
+    Request {
+        id: Value,
+        method: String,
+        params: Value,
+    },
+    Notification {
+        method: String,
+        params: Value,
+    },
+}
+
+impl Incoming {
+    /// Parse a message, or produce the error response to send back instead.
+    fn parse(text: &str) -> Result<Self, Value> {
+        let message: Value = serde_json::from_str(text)
+            .map_err(|e| error_response(Value::Null, PARSE_ERROR, &e.to_string()))?;
+        let invalid = |id: Value, reason: &str| error_response(id, INVALID_REQUEST, reason);
+        let Value::Object(mut message) = message else {
+            let reason = if message.is_array() {
+                "Batch requests are not supported"
+            } else {
+                "Expected a request object"
+            };
+            return Err(invalid(Value::Null, reason));
+        };
+        let id = message.remove("id");
+        if message.get("jsonrpc") != Some(&json!("2.0")) {
+            return Err(invalid(id.unwrap_or(Value::Null), "Expected jsonrpc 2.0"));
+        }
+        let Some(Value::String(method)) = message.remove("method") else {
+            return Err(invalid(id.unwrap_or(Value::Null), "Expected a method"));
+        };
+        let params = message.remove("params").unwrap_or(Value::Null);
+        match id {
+            None => Ok(Incoming::Notification { method, params }),
+            Some(id @ (Value::String(_) | Value::Number(_) | Value::Null)) => {
+                Ok(Incoming::Request { id, method, params })
+            }
+            Some(_) => Err(invalid(Value::Null, "Invalid id")),
+        }
+    }
+
+    fn id(&self) -> Option<&Value> {
+        match self {
+            Incoming::Request { id, .. } => Some(id),
+            Incoming::Notification { .. } => None,
+        }
+    }
+
+    /// The wire and cause to poke with:
+    /// `[%rpc %request id=@t method=@t params=@t]` or `[%rpc %notify method=@t params=@t]`
+    fn to_poke(&self) -> Result<(WireRepr, NounSlab), NockAppError> {
+        let mut slab = NounSlab::new();
+        let (wire, cause) = match self {
+            Incoming::Request { id, method, params } => {
+                let id = cord(&mut slab, &id.to_string())?;
+                let method = cord(&mut slab, method)?;
+                let params = cord(&mut slab, &params.to_string())?;
+                let cause = T(
+                    &mut slab,
+                    &[D(tas!(b"rpc")), D(tas!(b"request")), id, method, params],
+                );
+                (JsonRpcWire::Request.to_wire(), cause)
+            }
+            Incoming::Notification { method, params } => {
+                let method = cord(&mut slab, method)?;
+                let params = cord(&mut slab, &params.to_string())?;
+                let cause = T(
+                    &mut slab,
+                    &[D(tas!(b"rpc")), D(tas!(b"notify")), method, params],
+                );
+                (JsonRpcWire::Notify.to_wire(), cause)
+            }
+        };
+        slab.set_root(cause);
+        Ok((wire, slab))
+    }
+}
+
+fn cord(slab: &mut NounSlab, text: &str) -> Result<Noun, NockAppError> {
+    Ok(Atom::from_value(slab, text)?.as_noun())
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Parse a cord of JSON text from the kernel.
+fn json_cord(noun: &Noun) -> Result<Value, NounDecodeError> {
+    let text = String::from_noun(noun)?;
+    serde_json::from_str(&text).map_err(|e| NounDecodeError::Custom(e.to_string()))
+}
+
+/// Turn the tail of an `%rpc` effect into the message to send the client:
+/// `[%result id=@t result=@t]`, `[%error id=@t error=@t]` or `[%notify method=@t params=@t]`
+fn effect_message(effect: Noun) -> Result<Value, NounDecodeError> {
+    let effect = effect.as_cell()?;
+    let tag = effect.head();
+    let body = effect.tail().as_cell()?;
+    if unsafe { tag.raw_equals(&D(tas!(b"result"))) } {
+        Ok(json!({
+            "jsonrpc": "2.0",
+            "id": json_cord(&body.head())?,
+            "result": json_cord(&body.tail())?,
+        }))
+    } else if unsafe { tag.raw_equals(&D(tas!(b"error"))) } {
+        Ok(json!({
+            "jsonrpc": "2.0",
+            "id": json_cord(&body.head())?,
+            "error": json_cord(&body.tail())?,
+        }))
+    } else if unsafe { tag.raw_equals(&D(tas!(b"notify"))) } {
+        Ok(json!({
+            "jsonrpc": "2.0",
+            "method": String::from_noun(&body.head())?,
+            "params": json_cord(&body.tail())?,
+        }))
+    } else {
+        Err(NounDecodeError::InvalidTag)
+    }
+}
+
+/// Read one message, `None` at the end of the stream.
+async fn read_message<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    framing: Framing,
+) -> std::io::Result<Option<String>> {
+    match framing {
+        Framing::Lines => loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Ok(None);
+            }
+            if !line.trim().is_empty() {
+                return Ok(Some(line));
+            }
+        },
+        Framing::Headers => {
+            let mut content_length = None;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await? == 0 {
+                    return Ok(None);
+                }
+                let line = line.trim_end();
+                if line.is_empty() {
+                    if content_length.is_some() {
+                        break;
+                    }
+                    continue;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.trim().eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse::<usize>().ok();
+                    }
+                }
+            }
+            let len = content_length.unwrap_or_default();
+            if len > MAX_MESSAGE_LEN {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "{} byte message is over the {} byte limit",
+                        len, MAX_MESSAGE_LEN
+                    ),
+                ));
+            }
+            let mut body = vec![0u8; len];
+            reader.read_exact(&mut body).await?;
+            String::from_utf8(body)
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        }
+    }
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    framing: Framing,
+    message: &Value,
+) -> std::io::Result<()> {
+    let body = message.to_string();
+    match framing {
+        Framing::Lines => {
+            writer.write_all(body.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+        Framing::Headers => {
+            writer
+                .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+                .await?;
+            writer.write_all(body.as_bytes()).await?;
+        }
+    }
+    writer.flush().await
+}
+
+/// JSON-RPC 2.0 IO driver on stdin and stdout. See the [module docs](self) for framing and
+/// encoding. The app exits when stdin is closed.
+///
+/// ## Pokes
+/// `[%rpc %request id=@t method=@t params=@t]`
+/// for each request, on `/rpc/1/request`. If the kernel nacks it the client gets an internal
+/// error; otherwise the kernel answers with a `%result` or `%error` effect.
+///
+/// `[%rpc %notify method=@t params=@t]`
+/// for each notification, on `/rpc/1/notify`
+///
+/// ## Effects
+/// `[%rpc %result id=@t result=@t]`
+/// `[%rpc %error id=@t error=@t]`
+/// answers the request with `id`, `error` being a JSON-RPC error object
+///
+/// `[%rpc %notify method=@t params=@t]`
+/// sends the client a notification
+pub fn jsonrpc_stdio(framing: Framing) -> IODriverFn {
+    make_driver(move |handle| async move {
+        let (incoming_tx, mut incoming_rx) = mpsc::channel::<String>(MESSAGE_QUEUE);
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<Value>(MESSAGE_QUEUE);
+
+        // Reads aren't cancel-safe, so they get a task of their own rather than a select arm.
+        tokio::spawn(async move {
+            let mut stdin = BufReader::new(tokio::io::stdin());
+            loop {
+                match read_message(&mut stdin, framing).await {
+                    Ok(Some(text)) => {
+                        if incoming_tx.send(text).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        error!("jsonrpc: failed to read from stdin: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+        tokio::spawn(async move {
+            let mut stdout = tokio::io::stdout();
+            while let Some(message) = outgoing_rx.recv().await {
+                if let Err(e) = write_message(&mut stdout, framing, &message).await {
+                    error!("jsonrpc: failed to write to stdout: {}", e);
+                    break;
+                }
+            }
+        });
+
+        loop {
+            tokio::select! {
+                text = incoming_rx.recv() => {
+                    let Some(text) = text else {
+                        info!("jsonrpc: stdin closed, exiting");
+                        handle.exit.exit(0).await?;
+                        return Ok(());
+                    };
+                    let incoming = match Incoming::parse(&text) {
+                        Ok(incoming) => incoming,
+                        Err(response) => {
+                            let _ = outgoing_tx.send(response).await;
+                            continue;
+                        }
+                    };
+                    let (wire, cause) = incoming.to_poke()?;
+                    match handle.poke(wire, cause).await? {
+                        PokeResult::Ack => {}
+                        PokeResult::Nack => {
+                            warn!("jsonrpc: kernel nacked {:?}", incoming);
+                            if let Some(id) = incoming.id() {
+                                let response =
+                                    error_response(id.clone(), INTERNAL_ERROR, "Poke failed");
+                                let _ = outgoing_tx.send(response).await;
+                            }
+                        }
+                    }
+                }
+                effect = handle.next_effect() => {
+                    let slab = match effect {
+                        Ok(slab) => slab,
+                        Err(e) => {
+                            error!("Error receiving effect: {:?}", e);
+                            continue;
+                        }
+                    };
+                    let Ok(effect_cell) = unsafe { slab.root() }.as_cell() else {
+                        continue;
+                    };
+                    if !unsafe { effect_cell.head().raw_equals(&D(tas!(b"rpc"))) } {
+                        continue;
+                    }
+                    match effect_message(effect_cell.tail()) {
+                        Ok(message) => {
+                            debug!("jsonrpc: sending {}", message);
+                            let _ = outgoing_tx.send(message).await;
+                        }
+                        Err(e) => error!("jsonrpc: invalid rpc effect: {:?}", e),
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_incoming() {
+        assert_eq!(
+            Incoming::parse(r#"{"jsonrpc":"2.0","id":1,"method":"hover","params":[1,2]}"#),
+            Ok(Incoming::Request {
+                id: json!(1),
+                method: "hover".to_string(),
+                params: json!([1, 2]),
+            })
+        );
+        assert_eq!(
+            Incoming::parse(r#"{"jsonrpc":"2.0","method":"exit"}"#),
+            Ok(Incoming::Notification {
+                method: "exit".to_string(),
+                params: Value::Null,
+            })
+        );
+
+        let error_code = |text: &str| Incoming::parse(text).unwrap_err()["error"]["code"].clone();
+        assert_eq!(error_code("{"), json!(PARSE_ERROR));
+        assert_eq!(error_code("[]"), json!(INVALID_REQUEST));
+        assert_eq!(
+            error_code(r#"{"id":1,"method":"m"}"#),
+            json!(INVALID_REQUEST)
+        );
+        assert_eq!(
+            Incoming::parse(r#"{"jsonrpc":"2.0","id":"a"}"#).unwrap_err()["id"],
+            json!("a")
+        );
+    }
+
+    #[test]
+    fn test_poke_and_effect() {
+        let request = Incoming::Request {
+            id: json!("req-1"),
+            method: "add".to_string(),
+            params: json!({"a": 1}),
+        };
+        let (wire, cause) = request.to_poke().unwrap();
+        assert_eq!(wire, JsonRpcWire::Request.to_wire());
+        let cause = unsafe { cause.root() }.as_cell().unwrap();
+        let request = cause.tail().as_cell().unwrap().tail().as_cell().unwrap();
+        assert_eq!(String::from_noun(&request.head()).unwrap(), "\"req-1\"");
+
+        let mut slab: NounSlab = NounSlab::new();
+        let id = cord(&mut slab, "\"req-1\"").unwrap();
+        let result = cord(&mut slab, "{\"sum\":3}").unwrap();
+        let effect = T(&mut slab, &[D(tas!(b"result")), id, result]);
+        assert_eq!(
+            effect_message(effect).unwrap(),
+            json!({"jsonrpc": "2.0", "id": "req-1", "result": {"sum": 3}})
+        );
+
+        let method = cord(&mut slab, "progress").unwrap();
+        let params = cord(&mut slab, "[50]").unwrap();
+        let effect = T(&mut slab, &[D(tas!(b"notify")), method, params]);
+        assert_eq!(
+            effect_message(effect).unwrap(),
+            json!({"jsonrpc": "2.0", "method": "progress", "params": [50]})
+        );
+
+        let bad = cord(&mut slab, "{").unwrap();
+        let effect = T(&mut slab, &[D(tas!(b"result")), id, bad]);
+        assert!(effect_message(effect).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_framing() {
+        let message = json!({"jsonrpc": "2.0", "method": "ping"});
+        for framing in [Framing::Lines, Framing::Headers] {
+            let mut buf = Vec::new();
+            write_message(&mut buf, framing, &message).await.unwrap();
+            write_message(&mut buf, framing, &message).await.unwrap();
+            let mut reader = BufReader::new(&buf[..]);
+            for _ in 0..2 {
+                let text = read_message(&mut reader, framing).await.unwrap().unwrap();
+                assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), message);
+            }
+            assert_eq!(read_message(&mut reader, framing).await.unwrap(), None);
+        }
+    }
+}
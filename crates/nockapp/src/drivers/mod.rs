@@ -1,13 +1,23 @@
+pub mod checkpoint_upload;
 pub mod exit;
 pub mod file;
 pub mod http;
+pub mod jsonrpc;
 pub mod markdown;
 pub mod one_punch;
+pub mod reload;
+pub mod scheduler;
 pub mod timer;
+pub mod watcher;
 
+pub use checkpoint_upload::checkpoint_upload as checkpoint_upload_driver;
 pub use exit::exit as exit_driver;
 pub use file::file as file_driver;
 pub use http::http::http as http_driver;
+pub use jsonrpc::jsonrpc_stdio as jsonrpc_driver;
 pub use markdown::markdown as markdown_driver;
 pub use one_punch::one_punch_man as one_punch_driver;
+pub use reload::kernel_reload as kernel_reload_driver;
+pub use scheduler::scheduler as scheduler_driver;
 pub use timer::make_timer_driver as timer_driver;
+pub use watcher::file_watcher as watcher_driver;
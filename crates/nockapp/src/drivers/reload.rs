@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use tracing::{error, info};
+
+use super::watcher::{FileEvent, Watcher, WatcherConfig};
+use crate::kernel::form::KernelUpgrade;
+use crate::nockapp::driver::{make_driver, IODriverFn};
+
+/// Kernel hot-reload IO driver
+///
+/// Watches the kernel jam at `jam` and, each time it is written with new contents, switches the
+/// app to it in place with [`NockAppHandle::upgrade_kernel`], carrying the state over. Drivers
+/// stay attached, so their connections survive the reload. A jam that fails to load is logged
+/// and the running kernel kept.
+///
+/// [`NockAppHandle::upgrade_kernel`]: crate::nockapp::driver::NockAppHandle::upgrade_kernel
+pub fn kernel_reload(jam: PathBuf) -> IODriverFn {
+    make_driver(move |handle| async move {
+        let config = WatcherConfig::new(vec![jam.clone()]).recursive(false);
+        let poll_interval = config.poll_interval;
+        let mut watcher = tokio::task::spawn_blocking(move || Watcher::new(config)).await?;
+        let mut interval = tokio::time::interval(poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        info!("reload: watching {} for new kernels", jam.display());
+
+        loop {
+            interval.tick().await;
+            let (returned, events) = tokio::task::spawn_blocking(move || {
+                let events = watcher.poll(Instant::now());
+                (watcher, events)
+            })
+            .await?;
+            watcher = returned;
+
+            // Only the latest contents matter if the jam changed more than once
+            let Some(path) = events.into_iter().rev().find_map(|event| match event {
+                FileEvent::Create { path, .. } | FileEvent::Modify { path, .. } => Some(path),
+                FileEvent::Delete { .. } => None,
+            }) else {
+                continue;
+            };
+            let kernel = match tokio::fs::read(&path).await {
+                Ok(kernel) => kernel,
+                Err(e) => {
+                    error!("reload: failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let upgrade = KernelUpgrade { kernel, poke: None };
+            match handle.upgrade_kernel(upgrade).await {
+                Ok(()) => info!("reload: switched to the kernel in {}", path.display()),
+                Err(e) => error!(
+                    "reload: failed to switch to the kernel in {}, keeping the running one: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+    })
+}
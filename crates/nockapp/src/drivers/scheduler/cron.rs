@@ -0,0 +1,194 @@
+//! Five-field cron expressions, evaluated in UTC.
+//!
+//! `minute hour day-of-month month day-of-week`, each field being `*`, a value, a range `a-b`,
+//! any of those stepped with `/n`, or a comma-separated list of them. Days of the week run from
+//! 0 (Sunday) to 7 (also Sunday). As in Vixie cron, when both day fields are restricted a day
+//! matching either one fires, and a day field starting with `*` (such as `*/2`) is unrestricted.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+
+/// How far ahead to look for a matching time before giving up on an expression, such as
+/// `0 0 31 2 *`, that never fires.
+const MAX_SEARCH_YEARS: i32 = 5;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CronError {
+    #[error("Expected 5 fields in cron expression, got {0}")]
+    FieldCount(usize),
+    #[error("Invalid cron field {field}: {value}")]
+    InvalidField { field: &'static str, value: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day-of-month field started with `*`
+    any_day_of_month: bool,
+    /// Whether the day-of-week field started with `*`
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, CronError> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(CronError::FieldCount(fields.len()));
+        };
+        let mut days_of_week = parse_field(day_of_week, "day-of-week", 0, 7)?;
+        // 7 is Sunday too
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59)?,
+            hours: parse_field(hour, "hour", 0, 23)?,
+            days_of_month: parse_field(day_of_month, "day-of-month", 1, 31)?,
+            months: parse_field(month, "month", 1, 12)?,
+            days_of_week,
+            any_day_of_month: day_of_month.starts_with('*'),
+            any_day_of_week: day_of_week.starts_with('*'),
+        })
+    }
+
+    /// The first minute strictly after `after` that the schedule fires on.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start.year() + MAX_SEARCH_YEARS;
+        let mut t = start;
+        while t.year() <= limit {
+            if !has(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.day_matches(&t) {
+                t = Utc
+                    .with_ymd_and_hms(t.year(), t.month(), t.day(), 0, 0, 0)
+                    .single()?
+                    + Duration::days(1);
+            } else if !has(self.hours, t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if !has(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, t: &DateTime<Utc>) -> bool {
+        let day_of_month = has(self.days_of_month, t.day());
+        let day_of_week = has(self.days_of_week, t.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (false, true) => day_of_month,
+            (true, false) => day_of_week,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parse one field into a bitset of the values it matches.
+fn parse_field(field: &str, name: &'static str, min: u32, max: u32) -> Result<u64, CronError> {
+    let invalid = || CronError::InvalidField {
+        field: name,
+        value: field.to_string(),
+    };
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                start.parse().map_err(|_| invalid())?,
+                end.parse().map_err(|_| invalid())?,
+            )
+        } else {
+            let value = range.parse().map_err(|_| invalid())?;
+            // `a/n` means every n from a onwards
+            let end = if part.contains('/') { max } else { value };
+            (value, end)
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn next(expr: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        CronSchedule::parse(expr).unwrap().next_after(after)
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            CronSchedule::parse("* * * *"),
+            Err(CronError::FieldCount(4))
+        );
+        for expr in [
+            "60 * * * *", "* 24 * * *", "* * 0 * *", "* * * 13 *", "* * * * 8", "*/0 * * * *",
+            "5-1 * * * *", "x * * * *",
+        ] {
+            assert!(
+                CronSchedule::parse(expr).is_err(),
+                "{expr} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn test_next_after() {
+        let t = at(2025, 1, 1, 12, 30);
+        assert_eq!(next("* * * * *", t), Some(at(2025, 1, 1, 12, 31)));
+        assert_eq!(
+            next("* * * * *", t + Duration::seconds(59)),
+            Some(at(2025, 1, 1, 12, 31))
+        );
+        assert_eq!(next("*/15 * * * *", t), Some(at(2025, 1, 1, 12, 45)));
+        assert_eq!(next("0 9-17/4 * * *", t), Some(at(2025, 1, 1, 13, 0)));
+        assert_eq!(next("0 0 * * *", t), Some(at(2025, 1, 2, 0, 0)));
+        assert_eq!(next("0 0 1 * *", t), Some(at(2025, 2, 1, 0, 0)));
+        assert_eq!(next("5,10 3 * 6 *", t), Some(at(2025, 6, 1, 3, 5)));
+        // 2025-01-05 is a Sunday
+        assert_eq!(next("0 0 * * 0", t), Some(at(2025, 1, 5, 0, 0)));
+        assert_eq!(next("0 0 * * 7", t), Some(at(2025, 1, 5, 0, 0)));
+        // Either day field may match when both are restricted
+        assert_eq!(next("0 0 15 * 0", t), Some(at(2025, 1, 5, 0, 0)));
+        // A stepped `*` still leaves its day field unrestricted
+        assert_eq!(next("0 0 */2 * 1", t), Some(at(2025, 1, 6, 0, 0)));
+        assert_eq!(next("0 0 10 * */2", t), Some(at(2025, 1, 10, 0, 0)));
+        assert_eq!(next("0 0 29 2 *", t), Some(at(2028, 2, 29, 0, 0)));
+        assert_eq!(next("0 0 31 2 *", t), None);
+    }
+}
@@ -0,0 +1,338 @@
+pub mod cron;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use nockvm::noun::{Atom, D, T};
+use nockvm_macros::tas;
+use noun_serde::NounDecode;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, warn};
+
+pub use self::cron::{CronError, CronSchedule};
+use crate::nockapp::driver::{make_driver, IODriverFn};
+use crate::nockapp::error::NockAppError;
+use crate::nockapp::wire::{Wire, WireRepr};
+use crate::noun::slab::NounSlab;
+use crate::AtomExt;
+
+pub enum SchedulerWire {
+    Fire,
+}
+
+impl Wire for SchedulerWire {
+    const VERSION: u64 = 1;
+    const SOURCE: &'static str = "scheduler";
+
+    fn to_wire(&self) -> WireRepr {
+        let tags = match self {
+            SchedulerWire::Fire => vec!["fire".into()],
+        };
+        WireRepr::new(SchedulerWire::SOURCE, SchedulerWire::VERSION, tags)
+    }
+}
+
+/// When a timer fires
+#[derive(Clone, Debug, PartialEq, Eq, NounDecode, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Schedule {
+    /// Every so many seconds
+    #[noun(tag = "every")]
+    Every(u64),
+    /// On a five-field cron expression, in UTC
+    #[noun(tag = "cron")]
+    Cron(String),
+}
+
+impl Schedule {
+    fn validate(&self) -> Result<(), NockAppError> {
+        match self {
+            Schedule::Every(0) => Err(NockAppError::OtherError(
+                "timer interval must be at least one second".to_string(),
+            )),
+            Schedule::Every(_) => Ok(()),
+            Schedule::Cron(expr) => CronSchedule::parse(expr)
+                .map(|_| ())
+                .map_err(|e| NockAppError::OtherError(e.to_string())),
+        }
+    }
+
+    /// The next time the schedule fires after `now`, if it ever does.
+    fn next_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Every(secs) => {
+                now.checked_add_signed(chrono::Duration::seconds(i64::try_from(*secs).ok()?))
+            }
+            Schedule::Cron(expr) => CronSchedule::parse(expr).ok()?.next_after(now),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, NounDecode)]
+enum TimerEffect {
+    #[noun(tag = "set")]
+    Set { name: String, schedule: Schedule },
+    #[noun(tag = "cancel")]
+    Cancel(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Timer {
+    schedule: Schedule,
+    /// Unix time in seconds the timer next fires at
+    next: i64,
+}
+
+/// The set of live timers, saved to `path` whenever it changes.
+#[derive(Debug, Default)]
+struct Timers {
+    path: Option<PathBuf>,
+    timers: BTreeMap<String, Timer>,
+}
+
+impl Timers {
+    fn load(path: Option<PathBuf>) -> Result<Self, NockAppError> {
+        let timers = match &path {
+            Some(path) => match std::fs::read(path) {
+                Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                    NockAppError::OtherError(format!(
+                        "Invalid timer state in {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => return Err(NockAppError::IoError(e)),
+            },
+            None => BTreeMap::new(),
+        };
+        Ok(Self { path, timers })
+    }
+
+    /// Write the timers to disk, replacing the old state atomically.
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(e) = write_state(path, &self.timers) {
+            error!(
+                "scheduler: failed to save timers to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    fn set(&mut self, name: String, schedule: Schedule, now: DateTime<Utc>) {
+        let Some(next) = schedule.next_after(now) else {
+            warn!("scheduler: timer {} would never fire, not setting it", name);
+            return;
+        };
+        debug!("scheduler: setting timer {} for {}", name, next);
+        self.timers.insert(
+            name,
+            Timer {
+                schedule,
+                next: next.timestamp(),
+            },
+        );
+    }
+
+    fn cancel(&mut self, name: &str) {
+        if self.timers.remove(name).is_none() {
+            debug!("scheduler: no timer {} to cancel", name);
+        }
+    }
+
+    /// How long until the next timer is due, if any are set.
+    fn until_next(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let next = self.timers.values().map(|timer| timer.next).min()?;
+        Some(Duration::from_secs(
+            u64::try_from(next - now.timestamp()).unwrap_or(0),
+        ))
+    }
+
+    /// Take every timer due at `now`, scheduling each from `now` again. A timer missed while
+    /// the app was down fires once, not once for each time it was missed.
+    fn take_due(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        let due: Vec<String> = self
+            .timers
+            .iter()
+            .filter(|(_, timer)| timer.next <= now.timestamp())
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &due {
+            let timer = self.timers.get_mut(name).expect("due timer is set");
+            match timer.schedule.next_after(now) {
+                Some(next) => timer.next = next.timestamp(),
+                None => {
+                    self.timers.remove(name);
+                }
+            }
+        }
+        due
+    }
+}
+
+fn write_state(path: &Path, timers: &BTreeMap<String, Timer>) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(timers)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn fire_poke(name: &str, now: DateTime<Utc>) -> Result<NounSlab, NockAppError> {
+    let mut slab = NounSlab::new();
+    let name = Atom::from_value(&mut slab, name)?;
+    let time = Atom::from_value(&mut slab, now.timestamp().max(0) as u64)?;
+    let poke = T(
+        &mut slab,
+        &[D(tas!(b"timer")), D(tas!(b"fire")), name.as_noun(), time.as_noun()],
+    );
+    slab.set_root(poke);
+    Ok(slab)
+}
+
+/// Scheduler IO driver, delivering timer pokes the kernel asks for.
+///
+/// Timers are kept in `state_path` when given, so they survive restarts. A timer that came due
+/// while the app was down fires once as soon as the driver starts.
+///
+/// ## Effects
+/// `[%timer %set name=@t [%every secs=@ud]]`
+/// `[%timer %set name=@t [%cron expr=@t]]`
+/// sets (or replaces) the named timer, the cron expression being evaluated in UTC
+///
+/// `[%timer %cancel name=@t]`
+/// cancels the named timer
+///
+/// ## Pokes
+/// `[%timer %fire name=@t time=@ud]`
+/// each time a timer fires, `time` being unix seconds
+pub fn scheduler(state_path: Option<PathBuf>) -> IODriverFn {
+    make_driver(move |handle| async move {
+        let mut timers = Timers::load(state_path)?;
+        if !timers.timers.is_empty() {
+            info!("scheduler: restored {} timers", timers.timers.len());
+        }
+
+        loop {
+            let until_next = timers.until_next(Utc::now());
+            let due = async {
+                match until_next {
+                    Some(duration) => tokio::time::sleep(duration).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                effect = handle.next_effect() => {
+                    let slab = match effect {
+                        Ok(slab) => slab,
+                        Err(e) => {
+                            error!("Error receiving effect: {:?}", e);
+                            continue;
+                        }
+                    };
+                    let Ok(effect_cell) = unsafe { slab.root() }.as_cell() else {
+                        continue;
+                    };
+                    if !unsafe { effect_cell.head().raw_equals(&D(tas!(b"timer"))) } {
+                        continue;
+                    }
+                    let effect = match TimerEffect::from_noun(&effect_cell.tail()) {
+                        Ok(effect) => effect,
+                        Err(e) => {
+                            error!("scheduler: invalid timer effect: {:?}", e);
+                            continue;
+                        }
+                    };
+                    match effect {
+                        TimerEffect::Set { name, schedule } => {
+                            if let Err(e) = schedule.validate() {
+                                error!("scheduler: invalid schedule for timer {}: {}", name, e);
+                                continue;
+                            }
+                            timers.set(name, schedule, Utc::now());
+                        }
+                        TimerEffect::Cancel(name) => timers.cancel(&name),
+                    }
+                    timers.save();
+                }
+                _ = due => {
+                    let now = Utc::now();
+                    let fired = timers.take_due(now);
+                    timers.save();
+                    for name in fired {
+                        debug!("scheduler: firing timer {}", name);
+                        handle.poke(SchedulerWire::Fire.to_wire(), fire_poke(&name, now)?).await?;
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_take_due() {
+        let mut timers = Timers::default();
+        timers.set("fast".to_string(), Schedule::Every(10), at(0));
+        timers.set("slow".to_string(), Schedule::Every(60), at(0));
+        assert_eq!(timers.until_next(at(0)), Some(Duration::from_secs(10)));
+
+        assert!(timers.take_due(at(5)).is_empty());
+        assert_eq!(timers.take_due(at(10)), vec!["fast".to_string()]);
+        assert_eq!(timers.until_next(at(10)), Some(Duration::from_secs(10)));
+        // Missed firings are coalesced
+        assert_eq!(timers.take_due(at(100)), vec!["fast", "slow"]);
+        assert_eq!(timers.timers["fast"].next, 110);
+
+        timers.cancel("fast");
+        assert_eq!(timers.until_next(at(100)), Some(Duration::from_secs(60)));
+        timers.cancel("slow");
+        assert_eq!(timers.until_next(at(100)), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Schedule::Every(0).validate().is_err());
+        assert!(Schedule::Every(1).validate().is_ok());
+        assert!(Schedule::Cron("*/5 * * * *".to_string()).validate().is_ok());
+        assert!(Schedule::Cron("every minute".to_string())
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_timers_persist() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("timers.json");
+        let mut timers = Timers::load(Some(path.clone())).unwrap();
+        timers.set("tick".to_string(), Schedule::Every(30), at(0));
+        timers.set(
+            "nightly".to_string(),
+            Schedule::Cron("0 0 * * *".to_string()),
+            at(0),
+        );
+        timers.save();
+
+        let restored = Timers::load(Some(path)).unwrap();
+        assert_eq!(restored.timers, timers.timers);
+        assert_eq!(restored.timers["nightly"].next, 86400);
+    }
+}
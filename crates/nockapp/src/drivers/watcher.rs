@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use nockvm::noun::{Atom, IndirectAtom, Noun, D, T};
+use nockvm_macros::tas;
+use tracing::{debug, error, warn};
+
+use crate::nockapp::driver::{make_driver, IODriverFn};
+use crate::nockapp::error::NockAppError;
+use crate::nockapp::wire::{Wire, WireRepr};
+use crate::noun::slab::NounSlab;
+use crate::{AtomExt, IndirectAtomExt};
+
+pub enum WatcherWire {
+    Create,
+    Modify,
+    Delete,
+}
+
+impl Wire for WatcherWire {
+    const VERSION: u64 = 1;
+    const SOURCE: &'static str = "watch";
+
+    fn to_wire(&self) -> WireRepr {
+        let tags = match self {
+            WatcherWire::Create => vec!["create".into()],
+            WatcherWire::Modify => vec!["modify".into()],
+            WatcherWire::Delete => vec!["delete".into()],
+        };
+        WireRepr::new(WatcherWire::SOURCE, WatcherWire::VERSION, tags)
+    }
+}
+
+/// What to watch, and how often
+#[derive(Clone, Debug)]
+pub struct WatcherConfig {
+    /// Files and directories to watch
+    pub paths: Vec<PathBuf>,
+    /// Whether to watch subdirectories of watched directories
+    pub recursive: bool,
+    /// How often to scan the watched paths
+    pub poll_interval: Duration,
+    /// How long a file must go unchanged before its change is reported, so a file still being
+    /// written is reported once, when it's done
+    pub debounce: Duration,
+}
+
+impl WatcherConfig {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            recursive: true,
+            poll_interval: Duration::from_millis(500),
+            debounce: Duration::from_secs(1),
+        }
+    }
+
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileEvent {
+    Create { path: PathBuf, hash: blake3::Hash },
+    Modify { path: PathBuf, hash: blake3::Hash },
+    Delete { path: PathBuf },
+}
+
+/// Modification time and length, cheap to check on every scan
+type FileMeta = (Option<SystemTime>, u64);
+
+/// A change seen on a scan that hasn't yet settled for long enough to report. `None` meta is a
+/// deletion.
+struct PendingChange {
+    meta: Option<FileMeta>,
+    since: Instant,
+}
+
+struct KnownFile {
+    meta: FileMeta,
+    hash: blake3::Hash,
+}
+
+/// Polls the watched paths, turning what changed between scans into [`FileEvent`]s.
+pub(super) struct Watcher {
+    config: WatcherConfig,
+    known: HashMap<PathBuf, KnownFile>,
+    pending: HashMap<PathBuf, PendingChange>,
+}
+
+impl Watcher {
+    /// Start watching, taking the files already there as the baseline rather than reporting
+    /// them as created.
+    pub(super) fn new(config: WatcherConfig) -> Self {
+        let mut known = HashMap::new();
+        for (path, meta) in scan(&config) {
+            match hash_file(&path) {
+                Ok(hash) => {
+                    known.insert(path, KnownFile { meta, hash });
+                }
+                Err(e) => warn!("watcher: failed to read {}: {}", path.display(), e),
+            }
+        }
+        Self {
+            config,
+            known,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub(super) fn poll(&mut self, now: Instant) -> Vec<FileEvent> {
+        let current = scan(&self.config);
+        let mut changed: Vec<(PathBuf, Option<FileMeta>)> = current
+            .iter()
+            .filter(|(path, meta)| self.known.get(*path).map(|known| &known.meta) != Some(meta))
+            .map(|(path, meta)| (path.clone(), Some(*meta)))
+            .collect();
+        changed.extend(
+            self.known
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .map(|path| (path.clone(), None)),
+        );
+
+        // Anything not changed any more has settled back to what was last reported
+        self.pending
+            .retain(|path, _| changed.iter().any(|(changed, _)| changed == path));
+
+        let mut events = Vec::new();
+        for (path, meta) in changed {
+            let settled = match self.pending.get(&path) {
+                Some(pending) if pending.meta == meta => {
+                    now.duration_since(pending.since) >= self.config.debounce
+                }
+                _ => {
+                    self.pending
+                        .insert(path.clone(), PendingChange { meta, since: now });
+                    false
+                }
+            };
+            if !settled {
+                continue;
+            }
+            self.pending.remove(&path);
+            if let Some(event) = self.settle(path, meta) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Record a change that has settled, returning the event for it if the contents changed.
+    fn settle(&mut self, path: PathBuf, meta: Option<FileMeta>) -> Option<FileEvent> {
+        let Some(meta) = meta else {
+            self.known.remove(&path);
+            return Some(FileEvent::Delete { path });
+        };
+        let hash = match hash_file(&path) {
+            Ok(hash) => hash,
+            Err(e) => {
+                warn!("watcher: failed to read {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        match self.known.insert(path.clone(), KnownFile { meta, hash }) {
+            None => Some(FileEvent::Create { path, hash }),
+            Some(old) if old.hash != hash => Some(FileEvent::Modify { path, hash }),
+            Some(_) => None,
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> std::io::Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(std::fs::File::open(path)?)?;
+    Ok(hasher.finalize())
+}
+
+/// Every file under the watched paths.
+fn scan(config: &WatcherConfig) -> HashMap<PathBuf, FileMeta> {
+    let mut files = HashMap::new();
+    for path in &config.paths {
+        scan_path(path, config.recursive, true, &mut files);
+    }
+    files
+}
+
+fn scan_path(path: &Path, recursive: bool, top: bool, files: &mut HashMap<PathBuf, FileMeta>) {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return;
+    };
+    if metadata.is_file() {
+        files.insert(
+            path.to_path_buf(),
+            (metadata.modified().ok(), metadata.len()),
+        );
+    } else if metadata.is_dir() && (top || recursive) {
+        let entries = match std::fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!(
+                    "watcher: failed to read directory {}: {}",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            scan_path(&entry.path(), recursive, false, files);
+        }
+    }
+}
+
+fn event_poke(event: &FileEvent) -> Result<(WatcherWire, NounSlab), NockAppError> {
+    let mut slab = NounSlab::new();
+    let (wire, tag, path, hash) = match event {
+        FileEvent::Create { path, hash } => {
+            (WatcherWire::Create, tas!(b"create"), path, Some(hash))
+        }
+        FileEvent::Modify { path, hash } => {
+            (WatcherWire::Modify, tas!(b"modify"), path, Some(hash))
+        }
+        FileEvent::Delete { path } => (WatcherWire::Delete, tas!(b"delete"), path, None),
+    };
+    let path_atom = Atom::from_value(&mut slab, path.to_string_lossy().as_ref())?;
+    let mut items: Vec<Noun> = vec![D(tas!(b"watch")), D(tag), path_atom.as_noun()];
+    if let Some(hash) = hash {
+        let hash_atom = <IndirectAtom as IndirectAtomExt>::from_bytes(&mut slab, hash.as_bytes());
+        items.push(hash_atom.as_noun());
+    }
+    let poke = T(&mut slab, &items);
+    slab.set_root(poke);
+    Ok((wire, slab))
+}
+
+/// File watcher IO driver
+///
+/// Polls the configured paths and pokes the kernel when a file is created, modified or deleted,
+/// once the change has settled for the configured debounce. A file whose contents hash the same
+/// as before isn't reported as modified. Files already present when the driver starts aren't
+/// reported.
+///
+/// ## Pokes
+/// `[%watch %create path=@t hash=@ux]`
+/// `[%watch %modify path=@t hash=@ux]`
+/// `[%watch %delete path=@t]`
+/// `hash` being the blake3 hash of the file's new contents
+pub fn file_watcher(config: WatcherConfig) -> IODriverFn {
+    make_driver(move |handle| async move {
+        let poll_interval = config.poll_interval;
+        let mut watcher = tokio::task::spawn_blocking(move || Watcher::new(config)).await?;
+        let mut interval = tokio::time::interval(poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            let (returned, events) = tokio::task::spawn_blocking(move || {
+                let events = watcher.poll(Instant::now());
+                (watcher, events)
+            })
+            .await?;
+            watcher = returned;
+
+            for event in events {
+                debug!("watcher: {:?}", event);
+                let (wire, poke) = match event_poke(&event) {
+                    Ok(poke) => poke,
+                    Err(e) => {
+                        error!("watcher: failed to build poke for {:?}: {}", event, e);
+                        continue;
+                    }
+                };
+                handle.poke(wire.to_wire(), poke).await?;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Poll until the watcher reports something, or give up after a few tries.
+    fn settle(watcher: &mut Watcher, start: Instant) -> Vec<FileEvent> {
+        for tick in 0..4 {
+            let events = watcher.poll(start + Duration::from_secs(tick));
+            if !events.is_empty() {
+                return events;
+            }
+        }
+        Vec::new()
+    }
+
+    #[test]
+    fn test_watcher_events() {
+        let dir = TempDir::new().unwrap();
+        let existing = dir.path().join("existing.txt");
+        std::fs::write(&existing, b"already here").unwrap();
+        let config =
+            WatcherConfig::new(vec![dir.path().to_path_buf()]).debounce(Duration::from_secs(1));
+        let mut watcher = Watcher::new(config);
+        assert!(watcher.poll(Instant::now()).is_empty());
+
+        let file = dir.path().join("sub").join("new.txt");
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(&file, b"hello").unwrap();
+        let start = Instant::now();
+        // Not reported until it has settled
+        assert!(watcher.poll(start).is_empty());
+        assert!(watcher.poll(start + Duration::from_millis(500)).is_empty());
+        assert_eq!(
+            watcher.poll(start + Duration::from_secs(1)),
+            vec![FileEvent::Create {
+                path: file.clone(),
+                hash: blake3::hash(b"hello")
+            }]
+        );
+
+        std::fs::write(&file, b"hello, world").unwrap();
+        assert_eq!(
+            settle(&mut watcher, Instant::now()),
+            vec![FileEvent::Modify {
+                path: file.clone(),
+                hash: blake3::hash(b"hello, world")
+            }]
+        );
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(
+            settle(&mut watcher, Instant::now()),
+            vec![FileEvent::Delete { path: file }]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_contents_not_reported() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, b"same").unwrap();
+        let mut watcher = Watcher::new(WatcherConfig::new(vec![file.clone()]));
+
+        // Rewrite with the same contents, moving the modification time
+        let later = SystemTime::now() + Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(settle(&mut watcher, Instant::now()).is_empty());
+        assert!(watcher.pending.is_empty());
+    }
+
+    #[test]
+    fn test_non_recursive() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let config = WatcherConfig::new(vec![dir.path().to_path_buf()])
+            .recursive(false)
+            .debounce(Duration::ZERO);
+        let mut watcher = Watcher::new(config);
+
+        std::fs::write(dir.path().join("sub").join("ignored.txt"), b"x").unwrap();
+        std::fs::write(dir.path().join("top.txt"), b"x").unwrap();
+        let events = settle(&mut watcher, Instant::now());
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], FileEvent::Create { path, .. } if path.ends_with("top.txt")));
+    }
+}
@@ -1,32 +1,53 @@
 #![allow(clippy::items_after_test_module)]
 use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use chrono;
 use clap::{Args, ColorChoice, Parser, ValueEnum};
 use nockvm::jets::hot::HotEntry;
+use nockvm::meter::{Meter, MeterLimits};
 use nockvm::noun::Atom;
-use nockvm::trace::{IntervalFilter, KeywordFilter, TraceFilter, TraceInfo, TracingBackend};
+use nockvm::trace::{
+    IntervalFilter, KeywordFilter, ProfileBackend, TraceBackend, TraceFilter, TraceInfo,
+    TracingBackend,
+};
 use tokio::fs;
 use tracing::{debug, info, Level, Subscriber};
 use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::util::SubscriberInitExt;
-#[cfg(feature = "tracing-tracy")]
-use tracing_subscriber::Layer as _;
-use tracing_subscriber::{fmt, EnvFilter};
+use tracing_subscriber::{filter, fmt, EnvFilter, Layer as _};
 
+use crate::checkpoint_store::{CheckpointStore, StoreLocation};
+use crate::data_dir::{self, DataDir};
+use crate::drivers::{checkpoint_upload_driver, kernel_reload_driver};
+use crate::event_log::{EventLog, EVENT_LOG_FILE};
 use crate::export::ExportedState;
-use crate::kernel::form::Kernel;
+use crate::kernel::form::{Kernel, StackSizing};
+use crate::kernel::source::KernelSource;
+use crate::logging::{ComponentLevels, JsonFormatter, RotatingFile, RotationPolicy};
 use crate::noun::slab::{Jammer, NounSlab};
-use crate::save::SaveableCheckpoint;
+use crate::save::{CheckpointFile, SaveableCheckpoint, CHECKPOINT_FILES};
+use crate::upgraded_kernel::{UpgradedKernel, UPGRADED_KERNEL_FILE};
 use crate::utils::error::{CrownError, ExternalError};
+use crate::utils::{
+    NOCK_STACK_SIZE, NOCK_STACK_SIZE_HUGE, NOCK_STACK_SIZE_LARGE, NOCK_STACK_SIZE_MEDIUM,
+    NOCK_STACK_SIZE_SMALL, NOCK_STACK_SIZE_TINY,
+};
 use crate::{default_data_dir, AtomExt, NockApp};
 
 pub const DEFAULT_SAVE_INTERVAL: u64 = 120000;
 const DEFAULT_SAVE_INTERVAL_STR: &str = "120000";
 const DEFAULT_LOG_FILTER: &str = "info";
+pub const DEFAULT_LOG_MAX_SIZE_MB: u64 = 100;
+pub const DEFAULT_LOG_MAX_AGE_HOURS: u64 = 24;
+pub const DEFAULT_LOG_KEEP: usize = 10;
+pub const DEFAULT_CHECKPOINT_UPLOAD_INTERVAL: u64 = 600;
+const DEFAULT_CHECKPOINT_UPLOAD_INTERVAL_STR: &str = "600";
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum NockStackSize {
@@ -38,13 +59,33 @@ pub enum NockStackSize {
     Huge,
 }
 
+impl NockStackSize {
+    /// The stack size in 64-bit words.
+    pub fn words(&self) -> usize {
+        match self {
+            NockStackSize::Tiny => NOCK_STACK_SIZE_TINY,
+            NockStackSize::Small => NOCK_STACK_SIZE_SMALL,
+            NockStackSize::Normal => NOCK_STACK_SIZE,
+            NockStackSize::Medium => NOCK_STACK_SIZE_MEDIUM,
+            NockStackSize::Large => NOCK_STACK_SIZE_LARGE,
+            NockStackSize::Huge => NOCK_STACK_SIZE_HUGE,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 pub enum TraceMode {
+    /// Emit a tracing span per fast-hinted arm (integrates with Tracy)
     Tracing,
+    /// Sample the fast-hinted arm stack and write folded stacks for flamegraph tools
+    Profile,
 }
 
+const DEFAULT_PROFILE_OUTPUT: &str = "nock-profile.folded";
+const DEFAULT_PROFILE_INTERVAL_US: u64 = 100;
+
 /// Trace options for NockApp
-#[derive(Args, Clone, Debug, Default)]
+#[derive(Args, Clone, Debug)]
 pub struct TraceOpts {
     /// Enable nock interpreter tracing (integrates with Tracy profiler)
     #[arg(long = "trace", help = "Enable nock interpreter tracing")]
@@ -55,6 +96,26 @@ pub struct TraceOpts {
 
     #[arg(long, requires = "mode")]
     pub interval_filter: Option<usize>,
+
+    /// Where `--trace profile` writes its folded stacks
+    #[arg(long, default_value = DEFAULT_PROFILE_OUTPUT)]
+    pub profile_output: PathBuf,
+
+    /// Sampling interval for `--trace profile`, in microseconds
+    #[arg(long, default_value_t = DEFAULT_PROFILE_INTERVAL_US)]
+    pub profile_interval_us: u64,
+}
+
+impl Default for TraceOpts {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            keyword_filter: None,
+            interval_filter: None,
+            profile_output: PathBuf::from(DEFAULT_PROFILE_OUTPUT),
+            profile_interval_us: DEFAULT_PROFILE_INTERVAL_US,
+        }
+    }
 }
 
 impl From<TraceOpts> for Option<TraceInfo> {
@@ -74,9 +135,15 @@ impl From<TraceOpts> for Option<TraceInfo> {
             (None, None) => None,
         };
 
-        trace_opts.mode.map(|_mode| TraceInfo {
-            backend: Box::new(TracingBackend::new()),
-            filter,
+        trace_opts.mode.map(|mode| {
+            let backend: Box<dyn TraceBackend> = match mode {
+                TraceMode::Tracing => Box::new(TracingBackend::new()),
+                TraceMode::Profile => Box::new(ProfileBackend::new(
+                    trace_opts.profile_output,
+                    Duration::from_micros(trace_opts.profile_interval_us),
+                )),
+            };
+            TraceInfo { backend, filter }
         })
     }
 }
@@ -91,6 +158,21 @@ pub struct Cli {
     )]
     pub new: bool,
 
+    #[arg(
+        long,
+        env = "NOCKAPP_DATA_DIR",
+        help = "Keep all state and a copy of the logs under this directory, one subdirectory per app"
+    )]
+    pub data_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        requires = "data_dir",
+        help = "Move this app's state from its old location into --data-dir before starting",
+        default_value = "false"
+    )]
+    pub migrate_data_dir: bool,
+
     #[command(flatten)]
     pub trace_opts: TraceOpts,
 
@@ -102,9 +184,71 @@ pub struct Cli {
     )]
     pub save_interval: Option<u64>,
 
+    #[arg(
+        long,
+        help = "Deduplicate identical subtrees of the kernel state when saving checkpoints",
+        default_value = "false"
+    )]
+    pub hash_cons_snapshots: bool,
+
+    #[arg(
+        long,
+        help = "Append every committed poke to an event log in the checkpoints directory, first replaying any events it holds past the loaded checkpoint",
+        default_value = "false"
+    )]
+    pub event_log: bool,
+
+    #[arg(
+        long,
+        help = "Replay the event log on top of the loaded checkpoint before starting",
+        default_value = "false"
+    )]
+    pub replay_event_log: bool,
+
     #[arg(long, help = "Control colored output", value_enum, default_value_t = ColorChoice::Auto)]
     pub color: ColorChoice,
 
+    #[arg(
+        long,
+        help = "Write logs to stderr instead of stdout, e.g. when stdout carries a protocol",
+        default_value = "false"
+    )]
+    pub log_stderr: bool,
+
+    #[arg(
+        long,
+        help = "Also write JSON logs to this file, rotating it. Defaults to the logs directory under --data-dir."
+    )]
+    pub log_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Rotate the log file once it reaches this many megabytes",
+        default_value_t = DEFAULT_LOG_MAX_SIZE_MB
+    )]
+    pub log_max_size_mb: u64,
+
+    #[arg(
+        long,
+        help = "Rotate the log file once it is this many hours old",
+        default_value_t = DEFAULT_LOG_MAX_AGE_HOURS
+    )]
+    pub log_max_age_hours: u64,
+
+    #[arg(
+        long,
+        help = "Number of rotated log files to keep",
+        default_value_t = DEFAULT_LOG_KEEP
+    )]
+    pub log_keep: usize,
+
+    #[arg(
+        long,
+        help = "Log file levels per component, e.g. 'info,kernel=debug,drivers=info,grpc=warn'. Only narrows what RUST_LOG lets through.",
+        default_value = "info"
+    )]
+    pub log_levels: ComponentLevels,
+
     #[arg(
         long,
         help = "Path to a jam file containing existing kernel state. Supports both JammedCheckpoint and ExportedState formats."
@@ -119,14 +263,129 @@ pub struct Cli {
 
     #[arg(
         long,
+        env = "NOCKAPP_CHECKPOINT_STORE",
+        help = "Upload checkpoints to this s3://bucket/prefix, and restore from it when there is no local checkpoint"
+    )]
+    pub checkpoint_store: Option<StoreLocation>,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_CHECKPOINT_UPLOAD_INTERVAL",
+        requires = "checkpoint_store",
+        help = "Seconds between checkpoint uploads to --checkpoint-store",
+        default_value = DEFAULT_CHECKPOINT_UPLOAD_INTERVAL_STR
+    )]
+    pub checkpoint_upload_interval: u64,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_KERNEL",
+        help = "Boot the kernel jam at this path or http(s) URL instead of the built-in one"
+    )]
+    pub kernel: Option<KernelSource>,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_KERNEL_SHA256",
+        requires = "kernel",
+        help = "Hex SHA-256 the --kernel jam must match. Required when loading from a URL."
+    )]
+    pub kernel_sha256: Option<String>,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_RELOAD_KERNEL",
+        value_name = "PATH",
+        help = "Switch to the kernel jam at this path whenever it is rewritten, keeping the state"
+    )]
+    pub reload_kernel: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_STACK_SIZE",
         help = "Nock stack size to use",
         value_enum,
         default_value_t = NockStackSize::Normal
     )]
     pub stack_size: NockStackSize,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_MAX_STACK_SIZE",
+        help = "Largest size the Nock stack may grow to when an event runs out of memory",
+        value_enum,
+        default_value_t = NockStackSize::Huge
+    )]
+    pub max_stack_size: NockStackSize,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_EVENT_STEP_LIMIT",
+        help = "Fail any event that takes more than this many Nock steps"
+    )]
+    pub event_step_limit: Option<u64>,
+
+    #[arg(
+        long,
+        env = "NOCKAPP_EVENT_ALLOC_LIMIT",
+        help = "Fail any event that has more than this many 64-bit words allocated at once"
+    )]
+    pub event_alloc_limit: Option<usize>,
 }
 
 impl Cli {
+    /// The kernel's stack sizing: `--stack-size` to start, growing up to `--max-stack-size`.
+    pub fn stack_sizing(&self) -> StackSizing {
+        StackSizing::growable(self.stack_size.words(), self.max_stack_size.words())
+    }
+
+    /// The per-event limits from `--event-step-limit` and `--event-alloc-limit`.
+    pub fn meter_limits(&self) -> MeterLimits {
+        MeterLimits {
+            steps: self.event_step_limit,
+            alloc_words: self.event_alloc_limit,
+        }
+    }
+
+    /// The first flag given that changes the saved state at boot, which
+    /// [`load_saved_state`] can't honour.
+    pub fn state_writing_flag(&self) -> Option<&'static str> {
+        [
+            (self.new, "--new"),
+            (self.migrate_data_dir, "--migrate-data-dir"),
+            (self.state_jam.is_some(), "--state-jam"),
+            (self.export_state_jam.is_some(), "--export-state-jam"),
+            (self.checkpoint_store.is_some(), "--checkpoint-store"),
+            (self.reload_kernel.is_some(), "--reload-kernel"),
+        ]
+        .into_iter()
+        .find_map(|(set, flag)| set.then_some(flag))
+    }
+
+    /// `--log-file`, or else the log file under `--data-dir` named for the running binary.
+    fn log_file_path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.log_file {
+            return Some(path.clone());
+        }
+        let data_dir = DataDir::new(self.data_dir.as_ref()?);
+        let name = std::env::current_exe()
+            .ok()
+            .and_then(|exe| {
+                exe.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "nockapp".to_string());
+        Some(data_dir.log_file(&name))
+    }
+
+    fn log_rotation(&self) -> RotationPolicy {
+        RotationPolicy {
+            max_bytes: self.log_max_size_mb.saturating_mul(1 << 20),
+            max_age: Duration::from_secs(self.log_max_age_hours.saturating_mul(3600)),
+            keep: self.log_keep,
+        }
+    }
+
     fn normalized_save_interval(&self) -> Option<u64> {
         self.save_interval
             .and_then(|value| if value == 0 { None } else { Some(value) })
@@ -148,7 +407,8 @@ fn parse_save_interval(input: &str) -> Result<u64, String> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_save_interval;
+    use super::{parse_save_interval, NockStackSize, StackSizing};
+    use crate::utils::{NOCK_STACK_SIZE, NOCK_STACK_SIZE_MEDIUM, NOCK_STACK_SIZE_SMALL};
 
     #[test]
     fn parse_save_interval_none_variants() {
@@ -172,6 +432,27 @@ mod tests {
         assert!(parse_save_interval("abc").is_err());
     }
 
+    #[test]
+    fn stack_sizing_grows_up_to_max() {
+        let mut cli = super::default_boot_cli(false);
+        cli.stack_size = NockStackSize::Small;
+        cli.max_stack_size = NockStackSize::Medium;
+        let sizing = cli.stack_sizing();
+        assert_eq!(sizing.next_size(sizing.initial), Some(NOCK_STACK_SIZE));
+        assert_eq!(
+            sizing.next_size(NOCK_STACK_SIZE),
+            Some(NOCK_STACK_SIZE_MEDIUM)
+        );
+        assert_eq!(sizing.next_size(NOCK_STACK_SIZE_MEDIUM), None);
+
+        // A maximum below the starting size leaves the stack fixed.
+        cli.max_stack_size = NockStackSize::Tiny;
+        assert_eq!(
+            cli.stack_sizing(),
+            StackSizing::fixed(NOCK_STACK_SIZE_SMALL)
+        );
+    }
+
     #[test]
     fn normalized_save_interval_filters_zero() {
         let mut cli = super::default_boot_cli(false);
@@ -195,12 +476,31 @@ pub enum SetupResult<J> {
 pub fn default_boot_cli(new: bool) -> Cli {
     Cli {
         save_interval: Some(DEFAULT_SAVE_INTERVAL),
+        hash_cons_snapshots: false,
+        event_log: false,
+        replay_event_log: false,
         new,
+        data_dir: None,
+        migrate_data_dir: false,
         trace_opts: Default::default(),
         color: ColorChoice::Auto,
+        log_stderr: false,
+        log_file: None,
+        log_max_size_mb: DEFAULT_LOG_MAX_SIZE_MB,
+        log_max_age_hours: DEFAULT_LOG_MAX_AGE_HOURS,
+        log_keep: DEFAULT_LOG_KEEP,
+        log_levels: ComponentLevels::default(),
         state_jam: None,
         export_state_jam: None,
+        checkpoint_store: None,
+        checkpoint_upload_interval: DEFAULT_CHECKPOINT_UPLOAD_INTERVAL,
+        kernel: None,
+        kernel_sha256: None,
+        reload_kernel: None,
         stack_size: NockStackSize::Normal,
+        max_stack_size: NockStackSize::Huge,
+        event_step_limit: None,
+        event_alloc_limit: None,
     }
 }
 
@@ -327,26 +627,72 @@ fn init_with_default_filter<T: Subscriber + Send + Sync + for<'a> LookupSpan<'a>
 }
 
 /// Initialize tracing with appropriate configuration based on CLI arguments.
+fn log_writer(cli: &Cli) -> BoxMakeWriter {
+    if cli.log_stderr {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    }
+}
+
+fn log_file_layer<S>(cli: &Cli) -> Option<impl tracing_subscriber::Layer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let path = cli.log_file_path()?;
+    let file = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| RotatingFile::open(&path, cli.log_rotation()))
+        .inspect_err(|e| eprintln!("Not writing logs to {}: {}", path.display(), e))
+        .ok()?;
+    let levels = cli.log_levels;
+    Some(
+        fmt::layer()
+            .with_ansi(false)
+            .with_writer(std::sync::Arc::new(file))
+            .event_format(JsonFormatter)
+            .with_filter(filter::filter_fn(move |metadata| levels.enabled(metadata))),
+    )
+}
+
 pub fn init_default_tracing(cli: &Cli) {
     let use_ansi = cli.color == ColorChoice::Auto || cli.color == ColorChoice::Always;
 
+    if cli.log_file_path().is_some() {
+        // Get panics into the log file too
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            tracing::error!(target: "panic", "{}", info);
+            default_hook(info);
+        }));
+    }
+
     // Build and initialize the subscriber
     // If RUST_LOG is set and MINIMAL_LOG_FORMAT is unset, we will do production-grade logging.
     // Otherwise we will do more minimal logging suitable for an interactive terminal.
     if std::env::var("MINIMAL_LOG_FORMAT").is_ok() || std::env::var("RUST_LOG").is_err() {
         let fmt_layer = fmt::layer()
             .with_ansi(use_ansi)
+            .with_writer(log_writer(cli))
             .event_format(MinimalFormatter);
 
-        init_with_default_filter(tracing_subscriber::registry().with(fmt_layer));
+        init_with_default_filter(
+            tracing_subscriber::registry()
+                .with(fmt_layer)
+                .with(log_file_layer(cli)),
+        );
     } else {
         init_with_default_filter(
-            tracing_subscriber::registry().with(
-                fmt::layer()
-                    .with_ansi(use_ansi)
-                    .with_target(true)
-                    .with_level(true),
-            ),
+            tracing_subscriber::registry()
+                .with(
+                    fmt::layer()
+                        .with_ansi(use_ansi)
+                        .with_writer(log_writer(cli))
+                        .with_target(true)
+                        .with_level(true),
+                )
+                .with(log_file_layer(cli)),
         );
     }
 }
@@ -377,11 +723,7 @@ pub async fn setup_<J: Jammer + Send + 'static>(
 ) -> Result<SetupResult<J>, Box<dyn std::error::Error>> {
     let nock_test_jets_env = std::env::var("NOCK_TEST_JETS").unwrap_or_default();
     let test_jets = parse_test_jets(nock_test_jets_env.as_str());
-    let data_dir = if let Some(data_path) = data_dir.clone() {
-        data_path.join(name)
-    } else {
-        default_data_dir(name)
-    };
+    let data_dir = app_data_dir(&cli, name, data_dir)?;
     let pma_dir = data_dir.join("pma");
     let jams_dir = data_dir.join("checkpoints");
 
@@ -408,48 +750,88 @@ pub async fn setup_<J: Jammer + Send + 'static>(
         .normalized_save_interval()
         .map(std::time::Duration::from_millis);
 
-    let kernel_f = async |checkpoint| {
-        let kernel: Kernel<SaveableCheckpoint> = match cli.stack_size {
-            NockStackSize::Tiny => {
-                Kernel::load_with_hot_state_tiny(
-                    jam, checkpoint, hot_state, test_jets, cli.trace_opts,
-                )
-                .await?
-            }
-            NockStackSize::Small => {
-                Kernel::load_with_hot_state_small(
-                    jam, checkpoint, hot_state, test_jets, cli.trace_opts,
-                )
-                .await?
-            }
-            NockStackSize::Normal => {
-                Kernel::load_with_hot_state(jam, checkpoint, hot_state, test_jets, cli.trace_opts)
-                    .await?
-            }
-            NockStackSize::Medium => {
-                Kernel::load_with_hot_state_medium(
-                    jam, checkpoint, hot_state, test_jets, cli.trace_opts,
-                )
-                .await?
-            }
-            NockStackSize::Large => {
-                Kernel::load_with_hot_state_large(
-                    jam, checkpoint, hot_state, test_jets, cli.trace_opts,
-                )
-                .await?
-            }
-            NockStackSize::Huge => {
-                Kernel::load_with_hot_state_huge(
-                    jam, checkpoint, hot_state, test_jets, cli.trace_opts,
-                )
-                .await?
+    let checkpoint_store = match &cli.checkpoint_store {
+        Some(location) => {
+            let store = CheckpointStore::from_env(location.clone())?;
+            if !cli.new && CheckpointFile::latest(&jams_dir).await.is_none() {
+                info!("No local checkpoint, restoring from {}", location);
+                match store.restore_latest(&jams_dir).await? {
+                    Some(event_num) => info!("Restored checkpoint at event {}", event_num),
+                    None => info!("No checkpoint in {} yet, booting fresh", location),
+                }
             }
-        };
+            Some(store)
+        }
+        None => None,
+    };
+
+    let loaded_jam = match &cli.kernel {
+        Some(source) => Some(source.load(cli.kernel_sha256.as_deref()).await?),
+        None => None,
+    };
+    let jam = loaded_jam.as_deref().unwrap_or(jam);
+
+    // A kernel switched to at runtime replaces the one it was upgraded from on every boot, until
+    // the app is given a different kernel.
+    let upgraded_path = jams_dir.join(UPGRADED_KERNEL_FILE);
+    let base_hash = blake3::hash(jam);
+    let upgraded = match UpgradedKernel::read(&upgraded_path)? {
+        Some(upgraded) if upgraded.base == base_hash => Some(upgraded),
+        Some(upgraded) => {
+            info!(
+                "Booting kernel {} instead of {}, which kernel {} was upgraded to at runtime",
+                base_hash,
+                upgraded.ker_hash(),
+                upgraded.base
+            );
+            UpgradedKernel::remove(&upgraded_path)?;
+            None
+        }
+        None => None,
+    };
+
+    let stack_sizing = cli.stack_sizing();
+    let kernel_f = async |checkpoint: Option<SaveableCheckpoint>| {
+        let jam = boot_jam(jam, upgraded.as_ref(), checkpoint.as_ref());
+        let kernel: Kernel<SaveableCheckpoint> = Kernel::load_with_stack_sizing(
+            jam,
+            checkpoint,
+            hot_state,
+            test_jets,
+            cli.trace_opts.clone(),
+            stack_sizing,
+        )
+        .await?;
         let res: Result<Kernel<SaveableCheckpoint>, CrownError<ExternalError>> = Ok(kernel);
         res
     };
 
-    let app: NockApp<J> = NockApp::new(kernel_f, &jams_dir, save_interval).await?;
+    let mut app: NockApp<J> = NockApp::new(kernel_f, &jams_dir, save_interval).await?;
+    if cli.hash_cons_snapshots {
+        app.set_snapshot_hash_cons(true).await;
+    }
+
+    app.kernel
+        .persist_upgrades(upgraded_path, base_hash)
+        .await?;
+
+    let event_log_path = jams_dir.join(EVENT_LOG_FILE);
+    // A log holding events past the checkpoint can only be appended to once they are replayed
+    if cli.replay_event_log || cli.event_log {
+        replay_event_log(&app, &event_log_path).await?;
+    }
+    if cli.event_log {
+        let event_num = app.kernel.serf.event_number.load(Ordering::SeqCst);
+        let log = EventLog::open(&event_log_path, event_num)?;
+        info!("Logging events to {:?}", event_log_path);
+        app.kernel.set_event_log(Some(log)).await?;
+    }
+
+    let meter_limits = cli.meter_limits();
+    if meter_limits != MeterLimits::default() {
+        info!("Metering events with limits: {:?}", meter_limits);
+        app.kernel.set_meter(Some(Meter::new(meter_limits))).await?;
+    }
 
     if let Some(export_path) = cli.export_state_jam.clone() {
         export_kernel_state(&app.kernel, &export_path).await?;
@@ -460,9 +842,148 @@ pub async fn setup_<J: Jammer + Send + 'static>(
         import_kernel_state(&app.kernel, &import_path).await?;
     }
 
+    if let Some(store) = checkpoint_store {
+        let interval = Duration::from_secs(cli.checkpoint_upload_interval);
+        app.add_io_driver(checkpoint_upload_driver(store, jams_dir, interval))
+            .await;
+    }
+
+    if let Some(jam) = cli.reload_kernel.clone() {
+        app.add_io_driver(kernel_reload_driver(jam)).await;
+    }
+
     Ok(SetupResult::App(app))
 }
 
+/// Load the state [`setup`] would boot the app from without changing anything under its data
+/// directory. Nothing is restored, migrated, imported or saved; an upgraded kernel is booted but
+/// neither kept nor discarded, and the event log is only read. Flags that would change the saved
+/// state are refused.
+pub async fn load_saved_state<J: Jammer + Send + 'static>(
+    jam: &[u8],
+    cli: Cli,
+    hot_state: &[HotEntry],
+    name: &str,
+    data_dir: Option<PathBuf>,
+) -> Result<NockApp<J>, Box<dyn std::error::Error>> {
+    if let Some(flag) = cli.state_writing_flag() {
+        return Err(format!("{flag} changes the saved state, which is only read here").into());
+    }
+    let nock_test_jets_env = std::env::var("NOCK_TEST_JETS").unwrap_or_default();
+    let test_jets = parse_test_jets(nock_test_jets_env.as_str());
+    let jams_dir = app_data_dir(&cli, name, data_dir)?.join("checkpoints");
+    // Loading from an empty directory would create it
+    if !CHECKPOINT_FILES
+        .iter()
+        .any(|file| jams_dir.join(file).exists())
+    {
+        return Err(format!("No saved state in {}", jams_dir.display()).into());
+    }
+
+    let loaded_jam = match &cli.kernel {
+        Some(source) => Some(source.load(cli.kernel_sha256.as_deref()).await?),
+        None => None,
+    };
+    let jam = loaded_jam.as_deref().unwrap_or(jam);
+    let upgraded_path = jams_dir.join(UPGRADED_KERNEL_FILE);
+    let base_hash = blake3::hash(jam);
+    let upgraded =
+        UpgradedKernel::read(&upgraded_path)?.filter(|upgraded| upgraded.base == base_hash);
+
+    let stack_sizing = cli.stack_sizing();
+    let kernel_f = async |checkpoint: Option<SaveableCheckpoint>| {
+        let jam = boot_jam(jam, upgraded.as_ref(), checkpoint.as_ref());
+        let kernel: Kernel<SaveableCheckpoint> = Kernel::load_with_stack_sizing(
+            jam,
+            checkpoint,
+            hot_state,
+            test_jets,
+            cli.trace_opts.clone(),
+            stack_sizing,
+        )
+        .await?;
+        let res: Result<Kernel<SaveableCheckpoint>, CrownError<ExternalError>> = Ok(kernel);
+        res
+    };
+    let app: NockApp<J> = NockApp::new(kernel_f, &jams_dir, None).await?;
+
+    if upgraded.is_some() {
+        // Lets replay switch to the upgraded kernel. The file is only written when the kernel is
+        // upgraded again, which nothing here does.
+        app.kernel
+            .persist_upgrades(upgraded_path, base_hash)
+            .await?;
+    }
+    if cli.replay_event_log || cli.event_log {
+        replay_event_log(&app, &jams_dir.join(EVENT_LOG_FILE)).await?;
+    }
+    Ok(app)
+}
+
+/// The app's data directory: under `--data-dir` if it is set, moving the app's state there first
+/// with `--migrate-data-dir`, and otherwise the legacy location under `data_dir`.
+fn app_data_dir(
+    cli: &Cli,
+    name: &str,
+    data_dir: Option<PathBuf>,
+) -> Result<PathBuf, data_dir::DataDirError> {
+    let legacy_data_dir = if let Some(data_path) = data_dir {
+        data_path.join(name)
+    } else {
+        default_data_dir(name)
+    };
+    match &cli.data_dir {
+        Some(root) => {
+            let data_dir = DataDir::new(root).app(name);
+            if cli.migrate_data_dir {
+                data_dir::migrate(&legacy_data_dir, &data_dir)?;
+            }
+            Ok(data_dir)
+        }
+        None => {
+            data_dir::check_not_moved(&legacy_data_dir)?;
+            Ok(legacy_data_dir)
+        }
+    }
+}
+
+/// The kernel to boot `checkpoint` on: the one upgraded to at runtime if the checkpoint was taken
+/// on it, and otherwise `jam`. An upgrade no checkpoint was taken on is switched to again as the
+/// events logged on it are replayed.
+fn boot_jam<'a>(
+    jam: &'a [u8],
+    upgraded: Option<&'a UpgradedKernel>,
+    checkpoint: Option<&SaveableCheckpoint>,
+) -> &'a [u8] {
+    match (upgraded, checkpoint) {
+        (Some(upgraded), Some(checkpoint)) if checkpoint.ker_hash == upgraded.ker_hash() => {
+            info!(
+                "Booting kernel {} upgraded to at runtime",
+                checkpoint.ker_hash
+            );
+            upgraded.kernel.as_slice()
+        }
+        _ => jam,
+    }
+}
+
+/// Replay the events in the log at `path` past the app's loaded checkpoint.
+async fn replay_event_log<J>(
+    app: &NockApp<J>,
+    path: &std::path::Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event_num = app.kernel.serf.event_number.load(Ordering::SeqCst);
+    let events = EventLog::read_after(path, event_num)?;
+    info!(
+        "Replaying {} logged events after event {}",
+        events.len(),
+        event_num
+    );
+    let replayed = app.kernel.replay(events).await?;
+    info!("Replayed {} events", replayed);
+    Ok(())
+}
+
 /// Exports the kernel state to a jam file at the specified path
 async fn export_kernel_state<C>(
     kernel: &Kernel<C>,
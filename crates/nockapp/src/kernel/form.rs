@@ -2,6 +2,8 @@
 #![allow(clippy::items_after_test_module)]
 use std::any::Any;
 use std::future::Future;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
@@ -13,17 +15,20 @@ use nockvm::interpreter::{self, interpret, Error, Mote, NockCancelToken};
 use nockvm::jets::cold::{Cold, Nounable};
 use nockvm::jets::hot::{HotEntry, URBIT_HOT_STATE};
 use nockvm::jets::nock::util::mook;
-use nockvm::mem::NockStack;
+use nockvm::mem::{AllocationError, NockStack};
+use nockvm::meter::{Meter, MeterUsage};
 use nockvm::mug::met3_usize;
 use nockvm::noun::{Atom, Cell, DirectAtom, IndirectAtom, Noun, Slots, D, T};
 use nockvm::trace::{path_to_cord, write_serf_trace_safe};
 use nockvm_macros::tas;
 use tokio::sync::{mpsc, oneshot};
 use tokio::time::Duration;
-use tracing::{debug, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::kernel::boot::TraceOpts;
 use crate::metrics::NockAppMetrics;
+use crate::nockapp::event_log::{EventLog, EventLogError, LoggedEvent};
+use crate::nockapp::upgraded_kernel::UpgradedKernel;
 use crate::nockapp::wire::{wire_to_noun, WireRepr};
 use crate::noun::slab::NounSlab;
 use crate::noun::slam;
@@ -32,13 +37,49 @@ use crate::utils::{
     create_context, current_da, NOCK_STACK_SIZE, NOCK_STACK_SIZE_HUGE, NOCK_STACK_SIZE_LARGE,
     NOCK_STACK_SIZE_MEDIUM, NOCK_STACK_SIZE_SMALL, NOCK_STACK_SIZE_TINY,
 };
-use crate::{AtomExt, CrownError, IndirectAtomExt, NounExt, Result, ToBytesExt};
+use crate::{AtomExt, CrownError, IndirectAtomExt, JammedNoun, NounExt, Result, ToBytesExt};
 
 pub(crate) const STATE_AXIS: u64 = 6;
 const LOAD_AXIS: u64 = 4;
 const PEEK_AXIS: u64 = 22;
 const POKE_AXIS: u64 = 23;
 
+/// Size of the serf's [`NockStack`] in 64-bit words, and how large it may grow.
+///
+/// When an event runs out of memory and `max` is larger than the current stack, the serf is
+/// rebooted from its last committed state on a stack twice the size (capped at `max`) and the
+/// event is retried, instead of the serf thread panicking. Once the stack is at `max`, the event
+/// fails with [`CrownError::OutOfMemory`] instead. Stacks are lazily mapped, so the cost
+/// of a large `max` is only paid by workloads that need it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackSizing {
+    pub initial: usize,
+    pub max: usize,
+}
+
+impl StackSizing {
+    /// A stack that never grows, failing the event if it runs out of memory.
+    pub fn fixed(words: usize) -> Self {
+        Self {
+            initial: words,
+            max: words,
+        }
+    }
+
+    /// A stack starting at `initial` words that may grow up to `max` words.
+    pub fn growable(initial: usize, max: usize) -> Self {
+        Self {
+            initial,
+            max: max.max(initial),
+        }
+    }
+
+    /// The size to grow a stack of `current` words to, if it may grow at all.
+    pub fn next_size(&self, current: usize) -> Option<usize> {
+        (current < self.max).then(|| current.saturating_mul(2).min(self.max))
+    }
+}
+
 const SERF_FINISHED_INTERVAL: Duration = Duration::from_millis(100);
 const SERF_THREAD_STACK_SIZE: usize = 256 * 1024 * 1024; // 8MB
 
@@ -48,6 +89,24 @@ pub struct LoadState {
     pub kernel_state: NounSlab,
 }
 
+/// The result of a poke or peek, along with what it consumed if the serf is metered.
+#[derive(Debug)]
+pub struct Metered<T> {
+    pub result: Result<T>,
+    /// Nock steps and peak allocation, across every slam the event took
+    pub usage: Option<MeterUsage>,
+}
+
+/// A new kernel to switch a running serf over to.
+#[derive(Debug)]
+pub struct KernelUpgrade {
+    /// The new kernel, as a jammed noun
+    pub kernel: Vec<u8>,
+    /// Poke the new kernel with this once the state is loaded into it, e.g. to migrate the
+    /// state further or announce the upgrade. The upgrade fails if the poke does.
+    pub poke: Option<(WireRepr, NounSlab)>,
+}
+
 // Actions to request of the serf thread
 pub enum SerfAction<C> {
     // Make a CheckPoint
@@ -72,7 +131,7 @@ pub enum SerfAction<C> {
     // Run a peek
     Peek {
         ovo: NounSlab,
-        result: oneshot::Sender<Result<NounSlab>>,
+        result: oneshot::Sender<Metered<NounSlab>>,
     },
     // Run a poke
     //
@@ -80,7 +139,7 @@ pub enum SerfAction<C> {
     Poke {
         wire: WireRepr,
         cause: NounSlab,
-        result: oneshot::Sender<Result<NounSlab>>,
+        result: oneshot::Sender<Metered<NounSlab>>,
         result_ack: oneshot::Receiver<()>,
     },
     // Provide metrics
@@ -88,6 +147,43 @@ pub enum SerfAction<C> {
         metrics: Arc<NockAppMetrics>,
         result: oneshot::Sender<()>,
     },
+    // Run a poke against a throwaway fork of the kernel state
+    PokeSpeculative {
+        wire: WireRepr,
+        cause: NounSlab,
+        result: oneshot::Sender<Result<NounSlab>>,
+    },
+    // Replay logged events on top of the current state
+    Replay {
+        events: Vec<LoggedEvent>,
+        result: oneshot::Sender<Result<u64>>,
+    },
+    // Set or clear the log committed pokes are appended to
+    SetEventLog {
+        log: Option<EventLog>,
+        result: oneshot::Sender<()>,
+    },
+    // Drop event log records a checkpoint covers
+    CompactEventLog {
+        through: u64,
+        result: oneshot::Sender<Result<()>>,
+    },
+    // Set or clear the per-event meter
+    SetMeter {
+        meter: Option<Meter>,
+        result: oneshot::Sender<()>,
+    },
+    // Keep the kernel the serf is upgraded to at `path`
+    PersistUpgrades {
+        path: PathBuf,
+        base: Hash,
+        result: oneshot::Sender<()>,
+    },
+    // Switch to a new kernel, migrating the state into it
+    Upgrade {
+        upgrade: KernelUpgrade,
+        result: oneshot::Sender<Result<NounSlab>>,
+    },
     // Stop the loop
     Stop,
 }
@@ -105,7 +201,7 @@ impl<C: SerfCheckpoint + Send + 'static> SerfThread<C> {
         kernel_bytes: Vec<u8>,
         checkpoint: Option<C>,
         constant_hot_state: Vec<HotEntry>,
-        nock_stack_size: usize,
+        sizing: StackSizing,
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
     ) -> Result<Self> {
@@ -118,17 +214,31 @@ impl<C: SerfCheckpoint + Send + 'static> SerfThread<C> {
             .name("serf".to_string())
             .stack_size(SERF_THREAD_STACK_SIZE)
             .spawn(move || {
-                let stack = NockStack::new(nock_stack_size, 0);
+                let stack = NockStack::new(sizing.initial, 0);
                 let serf = Serf::new(
-                    stack, checkpoint, &kernel_bytes, &constant_hot_state, test_jets, trace,
+                    stack,
+                    checkpoint,
+                    &kernel_bytes,
+                    &constant_hot_state,
+                    test_jets.clone(),
+                    trace.clone(),
                 );
+                let boot = SerfBoot {
+                    kernel_bytes,
+                    hot_state: constant_hot_state,
+                    test_jets,
+                    trace,
+                    sizing,
+                    stack_words: sizing.initial,
+                    upgrades: None,
+                };
                 event_number_sender
                     .send(serf.event_num.clone())
                     .expect("Could not send event number out of serf thread");
                 cancel_token_sender
                     .send(serf.context.cancel_token())
                     .expect("Could not send cancel token out of serf thread");
-                serf_loop(serf, action_receiver, inhibit_clone);
+                serf_loop(serf, boot, action_receiver, inhibit_clone);
             })?;
 
         let event_number = event_number_receiver.await?;
@@ -158,6 +268,76 @@ impl<C> SerfThread<C> {
         }
     }
 
+    pub(crate) fn replay(&self, events: Vec<LoggedEvent>) -> impl Future<Output = Result<u64>> {
+        let action_sender = self.action_sender.clone();
+        let (result, result_recv) = oneshot::channel();
+        async move {
+            action_sender
+                .send(SerfAction::Replay { events, result })
+                .await?;
+            result_recv.await?
+        }
+    }
+
+    pub(crate) fn set_event_log(&self, log: Option<EventLog>) -> impl Future<Output = Result<()>> {
+        let action_sender = self.action_sender.clone();
+        let (result, result_recv) = oneshot::channel();
+        async move {
+            action_sender
+                .send(SerfAction::SetEventLog { log, result })
+                .await?;
+            Ok(result_recv.await?)
+        }
+    }
+
+    pub(crate) fn compact_event_log(&self, through: u64) -> impl Future<Output = Result<()>> {
+        let action_sender = self.action_sender.clone();
+        let (result, result_recv) = oneshot::channel();
+        async move {
+            action_sender
+                .send(SerfAction::CompactEventLog { through, result })
+                .await?;
+            result_recv.await?
+        }
+    }
+
+    pub(crate) fn set_meter(&self, meter: Option<Meter>) -> impl Future<Output = Result<()>> {
+        let action_sender = self.action_sender.clone();
+        let (result, result_recv) = oneshot::channel();
+        async move {
+            action_sender
+                .send(SerfAction::SetMeter { meter, result })
+                .await?;
+            Ok(result_recv.await?)
+        }
+    }
+
+    pub(crate) fn persist_upgrades(
+        &self,
+        path: PathBuf,
+        base: Hash,
+    ) -> impl Future<Output = Result<()>> {
+        let action_sender = self.action_sender.clone();
+        let (result, result_recv) = oneshot::channel();
+        async move {
+            action_sender
+                .send(SerfAction::PersistUpgrades { path, base, result })
+                .await?;
+            Ok(result_recv.await?)
+        }
+    }
+
+    pub(crate) fn upgrade(&self, upgrade: KernelUpgrade) -> impl Future<Output = Result<NounSlab>> {
+        let action_sender = self.action_sender.clone();
+        let (result, result_recv) = oneshot::channel();
+        async move {
+            action_sender
+                .send(SerfAction::Upgrade { upgrade, result })
+                .await?;
+            result_recv.await?
+        }
+    }
+
     pub(crate) fn stop(&mut self) -> impl Future<Output = Result<()>> {
         let action_sender = self.action_sender.clone();
         let cancel_token = self.cancel_token.clone();
@@ -202,16 +382,33 @@ impl<C> SerfThread<C> {
     }
 
     pub(crate) fn peek(&self, ovo: NounSlab) -> impl Future<Output = Result<NounSlab>> {
+        let metered = self.peek_metered(ovo);
+        async move { metered.await?.result }
+    }
+
+    pub(crate) fn peek_metered(
+        &self,
+        ovo: NounSlab,
+    ) -> impl Future<Output = Result<Metered<NounSlab>>> {
         let (result, result_fut) = oneshot::channel();
         let action_sender = self.action_sender.clone();
         async move {
             action_sender.send(SerfAction::Peek { ovo, result }).await?;
-            result_fut.await?
+            Ok(result_fut.await?)
         }
     }
 
     // We are very carefully ensuring that the future does not contain the &self reference, to allow spawning a task without lifetime issues
     pub fn poke(&self, wire: WireRepr, cause: NounSlab) -> impl Future<Output = Result<NounSlab>> {
+        let metered = self.poke_metered(wire, cause);
+        async move { metered.await?.result }
+    }
+
+    pub fn poke_metered(
+        &self,
+        wire: WireRepr,
+        cause: NounSlab,
+    ) -> impl Future<Output = Result<Metered<NounSlab>>> {
         let (result, result_fut) = oneshot::channel();
         let (result_ack_sender, result_ack) = oneshot::channel();
         let action_sender = self.action_sender.clone();
@@ -226,7 +423,7 @@ impl<C> SerfThread<C> {
                 .await?;
             let res = result_fut.await?;
             let _ = result_ack_sender.send(());
-            res
+            Ok(res)
         }
     }
 
@@ -258,7 +455,26 @@ impl<C> SerfThread<C> {
             cancel_task.abort();
             let _ = cancel_task.await;
             let _ = result_ack_sender.send(());
-            res
+            res.result
+        }
+    }
+
+    pub fn poke_speculative(
+        &self,
+        wire: WireRepr,
+        cause: NounSlab,
+    ) -> impl Future<Output = Result<NounSlab>> {
+        let (result, result_fut) = oneshot::channel();
+        let action_sender = self.action_sender.clone();
+        async move {
+            action_sender
+                .send(SerfAction::PokeSpeculative {
+                    wire,
+                    cause,
+                    result,
+                })
+                .await?;
+            result_fut.await?
         }
     }
 
@@ -273,14 +489,14 @@ impl<C> SerfThread<C> {
         })?;
         let res = result_fut.blocking_recv()?;
         let _ = result_ack_sender.send(());
-        res
+        res.result
     }
 
     pub(crate) fn peek_sync(&self, ovo: NounSlab) -> Result<NounSlab> {
         let (result, result_fut) = oneshot::channel();
         self.action_sender
             .blocking_send(SerfAction::Peek { ovo, result })?;
-        result_fut.blocking_recv()?
+        result_fut.blocking_recv()?.result
     }
 
     pub(crate) fn checkpoint(&self) -> impl Future<Output = Result<C>> {
@@ -315,8 +531,254 @@ impl<C> SerfThread<C> {
     }
 }
 
+/// Everything needed to boot the serf again on a larger stack.
+struct SerfBoot {
+    kernel_bytes: Vec<u8>,
+    hot_state: Vec<HotEntry>,
+    test_jets: Vec<NounSlab>,
+    trace: TraceOpts,
+    sizing: StackSizing,
+    stack_words: usize,
+    /// Where to keep the kernel the serf is upgraded to, and the hash of the kernel the app was
+    /// booted with, see [`UpgradedKernel`]
+    upgrades: Option<(PathBuf, Hash)>,
+}
+
+impl SerfBoot {
+    /// Run `f` against the serf, growing the stack and retrying whenever it runs out of memory,
+    /// until `f` finishes or the stack has reached its maximum size.
+    ///
+    /// Once the stack cannot grow any further, the serf is rebooted from its last committed state
+    /// on a stack of the same size and the event fails with [`CrownError::OutOfMemory`], leaving
+    /// the rest of the app running. Nock that recurses without bound ends up here too, since every
+    /// frame it pushes is checked against the end of the stack.
+    fn run_growing<T>(
+        &mut self,
+        serf: &mut Serf,
+        mut f: impl FnMut(&mut Serf) -> Result<T>,
+    ) -> Result<T> {
+        loop {
+            let payload = match catch_unwind(AssertUnwindSafe(|| f(serf))) {
+                Ok(res) => return res,
+                Err(payload) => payload,
+            };
+            let recoverable = payload
+                .downcast_ref::<AllocationError>()
+                .is_some_and(AllocationError::is_recoverable);
+            if !recoverable {
+                resume_unwind(payload);
+            }
+            // The interrupted computation never marked itself finished; a cancellation that
+            // arrived in the meantime wins and the panic propagates as before.
+            let running = serf.context.running_status.load(Ordering::SeqCst);
+            if running < NockCancelToken::RUNNING_IDLE
+                || serf
+                    .context
+                    .running_status
+                    .compare_exchange(
+                        running,
+                        NockCancelToken::RUNNING_IDLE,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                    )
+                    .is_err()
+            {
+                resume_unwind(payload);
+            }
+            if let Some(words) = self.sizing.next_size(self.stack_words) {
+                warn!(
+                    "serf: out of memory with a {} word stack, retrying with {} words",
+                    self.stack_words, words
+                );
+                match self.regrow(serf, words) {
+                    Ok(()) => continue,
+                    Err(err) => warn!("serf: could not grow the stack: {err}"),
+                }
+            }
+            let words = self.stack_words;
+            warn!("serf: out of memory with a {words} word stack, failing the event");
+            if let Err(err) = self.regrow(serf, words) {
+                warn!("serf: could not reset the stack: {err}");
+                resume_unwind(payload);
+            }
+            return Err(CrownError::OutOfMemory(words));
+        }
+    }
+
+    /// Reboot `serf` on a stack of `words` words from its last committed state.
+    ///
+    /// Only the kernel state is carried over. The cold state may reference nouns allocated by the
+    /// interrupted event, so the new serf starts with an empty one and jets are matched again as
+    /// cores are registered.
+    fn regrow(&mut self, serf: &mut Serf, words: usize) -> Result<()> {
+        let mut grown = self.reboot(serf, words, &self.kernel_bytes)?;
+        grown.adopt_handles(serf);
+        *serf = grown;
+        self.stack_words = words;
+        Ok(())
+    }
+
+    /// Boot `kernel` on a fresh stack of `words` words and load `serf`'s committed state into it,
+    /// leaving `serf` untouched. The new serf still has to adopt `serf`'s handles to replace it.
+    fn reboot(&self, serf: &Serf, words: usize, kernel: &[u8]) -> Result<Serf> {
+        let kernel_state = serf.arvo.slot(STATE_AXIS)?;
+        let mut state = NounSlab::new();
+        let state_root = state.copy_into(kernel_state);
+        state.set_root(state_root);
+
+        let (mut stack, _) =
+            NockStack::new_(words, 0).map_err(|err| CrownError::Unknown(err.to_string()))?;
+        let mut cold = NounSlab::new();
+        let cold_noun = Cold::new(&mut stack).into_noun(&mut stack);
+        let cold_root = cold.copy_into(cold_noun);
+        cold.set_root(cold_root);
+
+        let checkpoint = SaveableCheckpoint {
+            ker_hash: serf.ker_hash,
+            event_num: serf.event_num.load(Ordering::SeqCst),
+            state,
+            cold,
+        };
+        // Running out of memory while booting panics, which mustn't take the serf thread down
+        // with it.
+        catch_unwind(AssertUnwindSafe(|| {
+            Serf::try_new(
+                stack,
+                Some(checkpoint),
+                kernel,
+                &self.hot_state,
+                self.test_jets.clone(),
+                self.trace.clone(),
+            )
+        }))
+        .map_err(|_| CrownError::Unknown("kernel panicked while booting".to_string()))?
+    }
+
+    /// Switch `serf` over to a new kernel, loading its committed state into it with the new
+    /// kernel's `+load` and then running the upgrade poke, if there is one.
+    ///
+    /// The new kernel is booted on a stack of its own, so if it fails to boot, load the state or
+    /// take the poke, `serf` is left running the old kernel untouched. Otherwise it is replaced
+    /// and later reboots, e.g. to grow the stack, use the new kernel. If upgrades are persisted,
+    /// the new kernel is written out before it commits anything, so no checkpoint or logged event
+    /// can name a kernel the next boot can't find. The upgrade poke is logged as the new kernel's
+    /// first event. Returns the effects of the upgrade poke.
+    fn upgrade(&mut self, serf: &mut Serf, upgrade: KernelUpgrade) -> Result<NounSlab> {
+        let mut upgraded = self.reboot(serf, self.stack_words, &upgrade.kernel)?;
+        upgraded.adopt_handles(serf);
+
+        let previous = match self.persist_upgrade(&upgrade.kernel) {
+            Ok(previous) => previous,
+            Err(err) => {
+                serf.adopt_handles(&mut upgraded);
+                return Err(err);
+            }
+        };
+
+        let effects = match upgrade.poke {
+            Some((wire, cause)) => {
+                let poked = catch_unwind(AssertUnwindSafe(|| {
+                    let cause = cause.copy_to_stack(upgraded.stack());
+                    upgraded.poke_strict(wire, cause).map(|fec| {
+                        let mut slab = NounSlab::new();
+                        slab.copy_into(fec);
+                        slab
+                    })
+                }))
+                .unwrap_or_else(|_| {
+                    Err(CrownError::Unknown(
+                        "new kernel panicked on the upgrade poke".to_string(),
+                    ))
+                });
+                match poked {
+                    Ok(effects) => effects,
+                    Err(err) => {
+                        serf.adopt_handles(&mut upgraded);
+                        self.restore_upgrade(previous);
+                        return Err(err);
+                    }
+                }
+            }
+            None => {
+                let mut slab = NounSlab::new();
+                slab.set_root(D(0));
+                slab
+            }
+        };
+
+        info!(
+            "serf: upgraded kernel {} to {}",
+            serf.ker_hash, upgraded.ker_hash
+        );
+        *serf = upgraded;
+        self.kernel_bytes = upgrade.kernel;
+        Ok(effects)
+    }
+
+    /// Write `kernel` out as the upgraded kernel, if upgrades are persisted, returning the one it
+    /// replaces.
+    fn persist_upgrade(&self, kernel: &[u8]) -> Result<Option<UpgradedKernel>> {
+        let Some((path, base)) = &self.upgrades else {
+            return Ok(None);
+        };
+        let previous = UpgradedKernel::read(path)?;
+        UpgradedKernel {
+            base: *base,
+            kernel: kernel.to_vec(),
+        }
+        .write(path)?;
+        Ok(previous)
+    }
+
+    /// Put back the upgraded kernel [`SerfBoot::persist_upgrade`] replaced, after the upgrade
+    /// failed.
+    fn restore_upgrade(&self, previous: Option<UpgradedKernel>) {
+        let Some((path, _)) = &self.upgrades else {
+            return;
+        };
+        let restored = match previous {
+            Some(previous) => previous.write(path),
+            None => UpgradedKernel::remove(path),
+        };
+        if let Err(err) = restored {
+            warn!(
+                "serf: could not restore {} after a failed upgrade: {err}",
+                path.display()
+            );
+        }
+    }
+
+    /// Switch `serf` to the kernel a logged event ran on before replaying it, if that is the
+    /// kernel the app was last upgraded to. Events logged on any other kernel replay on the
+    /// current one, as they do when the app is restarted with a new kernel.
+    fn switch_for_replay(&mut self, serf: &mut Serf, ker_hash: Hash) -> Result<()> {
+        if ker_hash == serf.ker_hash {
+            return Ok(());
+        }
+        let Some((path, _)) = &self.upgrades else {
+            return Ok(());
+        };
+        let Some(upgraded) = UpgradedKernel::read(path)? else {
+            return Ok(());
+        };
+        if upgraded.ker_hash() != ker_hash {
+            return Ok(());
+        }
+        let mut switched = self.reboot(serf, self.stack_words, &upgraded.kernel)?;
+        switched.adopt_handles(serf);
+        info!(
+            "serf: replaying from kernel {} on kernel {}",
+            serf.ker_hash, switched.ker_hash
+        );
+        *serf = switched;
+        self.kernel_bytes = upgraded.kernel;
+        Ok(())
+    }
+}
+
 fn serf_loop<C: SerfCheckpoint>(
     mut serf: Serf,
+    mut boot: SerfBoot,
     mut action_receiver: mpsc::Receiver<SerfAction<C>>,
     inhibit: Arc<AtomicBool>,
 ) {
@@ -437,19 +899,28 @@ fn serf_loop<C: SerfCheckpoint>(
             SerfAction::Peek { ovo, result } => {
                 if inhibit.load(Ordering::SeqCst) {
                     let _ = result
-                        .send(Err(CrownError::Unknown("Serf stopping".to_string())))
+                        .send(Metered {
+                            result: Err(CrownError::Unknown("Serf stopping".to_string())),
+                            usage: None,
+                        })
                         .inspect_err(|_e| {
                             debug!("Tried to send inhibited peek state to dropped channel");
                         });
                 } else {
-                    let ovo_noun = ovo.copy_to_stack(serf.stack());
-                    let noun_res = serf.peek(ovo_noun);
-                    let noun_slab_res = noun_res.map(|noun| {
-                        let mut slab = NounSlab::new();
-                        slab.copy_into(noun);
-                        slab
+                    serf.event_usage = None;
+                    let noun_slab_res = boot.run_growing(&mut serf, |serf| {
+                        let ovo_noun = ovo.clone().copy_to_stack(serf.stack());
+                        serf.peek(ovo_noun).map(|noun| {
+                            let mut slab = NounSlab::new();
+                            slab.copy_into(noun);
+                            slab
+                        })
                     });
-                    let _ = result.send(noun_slab_res).inspect_err(|_e| {
+                    let metered = Metered {
+                        result: noun_slab_res,
+                        usage: serf.event_usage.take(),
+                    };
+                    let _ = result.send(metered).inspect_err(|_e| {
                         debug!("Tried to send peek state to dropped channel");
                     });
                 };
@@ -466,19 +937,28 @@ fn serf_loop<C: SerfCheckpoint>(
             } => {
                 if inhibit.load(Ordering::SeqCst) {
                     let _ = result
-                        .send(Err(CrownError::Unknown("Serf stopping".to_string())))
+                        .send(Metered {
+                            result: Err(CrownError::Unknown("Serf stopping".to_string())),
+                            usage: None,
+                        })
                         .inspect_err(|_e| {
                             debug!("Failed to send inihibited poke result from serf thread");
                         });
                 } else {
-                    let cause_noun = cause.copy_to_stack(serf.stack());
-                    let noun_res = serf.poke(wire, cause_noun);
-                    let noun_slab_res = noun_res.map(|noun| {
-                        let mut slab = NounSlab::new();
-                        slab.copy_into(noun);
-                        slab
+                    serf.event_usage = None;
+                    let noun_slab_res = boot.run_growing(&mut serf, |serf| {
+                        let cause_noun = cause.clone().copy_to_stack(serf.stack());
+                        serf.poke(wire.clone(), cause_noun).map(|noun| {
+                            let mut slab = NounSlab::new();
+                            slab.copy_into(noun);
+                            slab
+                        })
                     });
-                    let _ = result.send(noun_slab_res).inspect_err(|_e| {
+                    let metered = Metered {
+                        result: noun_slab_res,
+                        usage: serf.event_usage.take(),
+                    };
+                    let _ = result.send(metered).inspect_err(|_e| {
                         debug!("Failed to send poke result from serf thread");
                     });
                 };
@@ -490,6 +970,93 @@ fn serf_loop<C: SerfCheckpoint>(
                     nockapp_metrics.serf_loop_poke.add_timing(&action_elapsed);
                 };
             }
+            SerfAction::PokeSpeculative {
+                wire,
+                cause,
+                result,
+            } => {
+                if inhibit.load(Ordering::SeqCst) {
+                    let _ = result
+                        .send(Err(CrownError::Unknown("Serf stopping".to_string())))
+                        .inspect_err(|_e| {
+                            debug!("Failed to send inhibited speculative poke result");
+                        });
+                } else {
+                    let noun_slab_res = boot.run_growing(&mut serf, |serf| {
+                        serf.poke_speculative(wire.clone(), cause.clone())
+                    });
+                    let _ = result.send(noun_slab_res).inspect_err(|_e| {
+                        debug!("Failed to send speculative poke result from serf thread");
+                    });
+                };
+                let action_elapsed = action_start.elapsed();
+                if let Some(nockapp_metrics) = &serf.metrics {
+                    nockapp_metrics
+                        .serf_loop_poke_speculative
+                        .add_timing(&action_elapsed);
+                };
+            }
+            SerfAction::Replay { events, result } => {
+                let mut replayed = 0;
+                let mut replay_res = Ok(());
+                for event in &events {
+                    replay_res = boot
+                        .switch_for_replay(&mut serf, event.ker_hash)
+                        .and_then(|()| boot.run_growing(&mut serf, |serf| serf.replay(event)));
+                    if replay_res.is_err() {
+                        break;
+                    }
+                    replayed += 1;
+                }
+                let _ = result
+                    .send(replay_res.map(|()| replayed))
+                    .inspect_err(|_e| {
+                        debug!("Failed to send replay result from serf thread");
+                    });
+            }
+            SerfAction::SetEventLog { log, result } => {
+                serf.event_log = log;
+                let _ = result.send(()).inspect_err(|_e| {
+                    debug!("Failed to send event log result from serf thread");
+                });
+            }
+            SerfAction::CompactEventLog { through, result } => {
+                let compact_res = match serf.event_log.as_mut() {
+                    Some(log) => log.compact(through).map_err(Into::into),
+                    None => Ok(()),
+                };
+                let _ = result.send(compact_res).inspect_err(|_e| {
+                    debug!("Failed to send compaction result from serf thread");
+                });
+            }
+            SerfAction::SetMeter { meter, result } => {
+                serf.context.meter = meter;
+                let _ = result.send(()).inspect_err(|_e| {
+                    debug!("Failed to send meter result from serf thread");
+                });
+            }
+            SerfAction::PersistUpgrades { path, base, result } => {
+                boot.upgrades = Some((path, base));
+                let _ = result.send(()).inspect_err(|_e| {
+                    debug!("Failed to send persist-upgrades result from serf thread");
+                });
+            }
+            SerfAction::Upgrade { upgrade, result } => {
+                let upgrade_res = if inhibit.load(Ordering::SeqCst) {
+                    Err(CrownError::Unknown("Serf stopping".to_string()))
+                } else {
+                    boot.upgrade(&mut serf, upgrade)
+                };
+                let _ = result.send(upgrade_res).inspect_err(|_e| {
+                    debug!("Failed to send upgrade result from serf thread");
+                });
+                let action_elapsed = action_start.elapsed();
+                if let Some(nockapp_metrics) = &serf.metrics {
+                    nockapp_metrics
+                        .serf_loop_upgrade
+                        .add_timing(&action_elapsed);
+                };
+            }
             SerfAction::ProvideMetrics { metrics, result } => {
                 serf.metrics = Some(metrics);
                 let _ = result.send(()).inspect_err(|_e| {
@@ -562,13 +1129,15 @@ impl<C: SerfCheckpoint + 'static> Kernel<C> {
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
     ) -> Result<Self> {
-        let kernel_vec = Vec::from(kernel);
-        let hot_state_vec = Vec::from(hot_state);
-        let serf = SerfThread::new(
-            kernel_vec, checkpoint, hot_state_vec, NOCK_STACK_SIZE, test_jets, trace,
+        Self::load_with_stack_sizing(
+            kernel,
+            checkpoint,
+            hot_state,
+            test_jets,
+            trace,
+            StackSizing::fixed(NOCK_STACK_SIZE),
         )
-        .await?;
-        Ok(Self { serf })
+        .await
     }
 
     pub async fn load_with_hot_state_tiny(
@@ -578,13 +1147,15 @@ impl<C: SerfCheckpoint + 'static> Kernel<C> {
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
     ) -> Result<Self> {
-        let kernel_vec = Vec::from(kernel);
-        let hot_state_vec = Vec::from(hot_state);
-        let serf = SerfThread::new(
-            kernel_vec, checkpoint, hot_state_vec, NOCK_STACK_SIZE_TINY, test_jets, trace,
+        Self::load_with_stack_sizing(
+            kernel,
+            checkpoint,
+            hot_state,
+            test_jets,
+            trace,
+            StackSizing::fixed(NOCK_STACK_SIZE_TINY),
         )
-        .await?;
-        Ok(Self { serf })
+        .await
     }
 
     pub async fn load_with_hot_state_small(
@@ -594,13 +1165,15 @@ impl<C: SerfCheckpoint + 'static> Kernel<C> {
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
     ) -> Result<Self> {
-        let kernel_vec = Vec::from(kernel);
-        let hot_state_vec = Vec::from(hot_state);
-        let serf = SerfThread::new(
-            kernel_vec, checkpoint, hot_state_vec, NOCK_STACK_SIZE_SMALL, test_jets, trace,
+        Self::load_with_stack_sizing(
+            kernel,
+            checkpoint,
+            hot_state,
+            test_jets,
+            trace,
+            StackSizing::fixed(NOCK_STACK_SIZE_SMALL),
         )
-        .await?;
-        Ok(Self { serf })
+        .await
     }
 
     pub async fn load_with_hot_state_medium(
@@ -610,13 +1183,15 @@ impl<C: SerfCheckpoint + 'static> Kernel<C> {
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
     ) -> Result<Self> {
-        let kernel_vec = Vec::from(kernel);
-        let hot_state_vec = Vec::from(hot_state);
-        let serf = SerfThread::new(
-            kernel_vec, checkpoint, hot_state_vec, NOCK_STACK_SIZE_MEDIUM, test_jets, trace,
+        Self::load_with_stack_sizing(
+            kernel,
+            checkpoint,
+            hot_state,
+            test_jets,
+            trace,
+            StackSizing::fixed(NOCK_STACK_SIZE_MEDIUM),
         )
-        .await?;
-        Ok(Self { serf })
+        .await
     }
 
     pub async fn load_with_hot_state_large(
@@ -626,13 +1201,15 @@ impl<C: SerfCheckpoint + 'static> Kernel<C> {
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
     ) -> Result<Self> {
-        let kernel_vec = Vec::from(kernel);
-        let hot_state_vec = Vec::from(hot_state);
-        let serf = SerfThread::new(
-            kernel_vec, checkpoint, hot_state_vec, NOCK_STACK_SIZE_LARGE, test_jets, trace,
+        Self::load_with_stack_sizing(
+            kernel,
+            checkpoint,
+            hot_state,
+            test_jets,
+            trace,
+            StackSizing::fixed(NOCK_STACK_SIZE_LARGE),
         )
-        .await?;
-        Ok(Self { serf })
+        .await
     }
 
     pub async fn load_with_hot_state_huge(
@@ -641,11 +1218,31 @@ impl<C: SerfCheckpoint + 'static> Kernel<C> {
         hot_state: &[HotEntry],
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
+    ) -> Result<Self> {
+        Self::load_with_stack_sizing(
+            kernel,
+            checkpoint,
+            hot_state,
+            test_jets,
+            trace,
+            StackSizing::fixed(NOCK_STACK_SIZE_HUGE),
+        )
+        .await
+    }
+
+    /// Loads a kernel with a custom hot state on a stack sized by `sizing`.
+    pub async fn load_with_stack_sizing(
+        kernel: &[u8],
+        checkpoint: Option<C>,
+        hot_state: &[HotEntry],
+        test_jets: Vec<NounSlab>,
+        trace: TraceOpts,
+        sizing: StackSizing,
     ) -> Result<Self> {
         let kernel_vec = Vec::from(kernel);
         let hot_state_vec = Vec::from(hot_state);
         let serf = SerfThread::new(
-            kernel_vec, checkpoint, hot_state_vec, NOCK_STACK_SIZE_HUGE, test_jets, trace,
+            kernel_vec, checkpoint, hot_state_vec, sizing, test_jets, trace,
         )
         .await?;
         Ok(Self { serf })
@@ -683,6 +1280,16 @@ impl<C> Kernel<C> {
         self.serf.poke(wire, cause)
     }
 
+    /// Like [`Kernel::poke`], but also returns the Nock steps and allocation the poke took if a
+    /// meter is set with [`Kernel::set_meter`]. The usage is reported even if the poke fails.
+    pub fn poke_metered(
+        &self,
+        wire: WireRepr,
+        cause: NounSlab,
+    ) -> impl Future<Output = Result<Metered<NounSlab>>> {
+        self.serf.poke_metered(wire, cause)
+    }
+
     pub fn poke_sync(&self, wire: WireRepr, cause: NounSlab) -> Result<NounSlab> {
         self.serf.poke_sync(wire, cause)
     }
@@ -700,12 +1307,29 @@ impl<C> Kernel<C> {
         self.serf.poke_timeout(wire, cause, timeout)
     }
 
+    /// Runs a poke against a fork of the kernel state and throws the fork away, returning the
+    /// effects it would have produced. The canonical state and event number are left untouched,
+    /// so this is safe for pre-validation and estimates. A crash fails with no `%crud`.
+    pub fn poke_speculative(
+        &self,
+        wire: WireRepr,
+        cause: NounSlab,
+    ) -> impl Future<Output = Result<NounSlab>> {
+        self.serf.poke_speculative(wire, cause)
+    }
+
     // We are very carefully ensuring the future does not contain the "self" reference to ensure no lifetime issues when spawning tasks
     #[tracing::instrument(name = "crown::Kernel::peek", skip_all)]
     pub(crate) fn peek(&self, ovo: NounSlab) -> impl Future<Output = Result<NounSlab>> {
         self.serf.peek(ovo)
     }
 
+    /// Like [`Kernel::peek`], but also returns the Nock steps and allocation the peek took if a
+    /// meter is set with [`Kernel::set_meter`].
+    pub fn peek_metered(&self, ovo: NounSlab) -> impl Future<Output = Result<Metered<NounSlab>>> {
+        self.serf.peek_metered(ovo)
+    }
+
     pub fn import(&self, state: LoadState) -> impl Future<Output = Result<()>> {
         self.serf.import(state)
     }
@@ -720,6 +1344,49 @@ impl<C> Kernel<C> {
     ) -> impl Future<Output = Result<()>> {
         self.serf.provide_metrics(metrics)
     }
+
+    /// Replay logged events on top of the current state, in order, returning how many were
+    /// replayed. Each must be the event after the last; replay stops at the first that isn't, or
+    /// that fails to commit.
+    pub fn replay(&self, events: Vec<LoggedEvent>) -> impl Future<Output = Result<u64>> {
+        self.serf.replay(events)
+    }
+
+    /// Append every poke committed from now on to `log`, or stop logging with `None`.
+    pub fn set_event_log(&self, log: Option<EventLog>) -> impl Future<Output = Result<()>> {
+        self.serf.set_event_log(log)
+    }
+
+    /// Drop the event log's records up to and including `through`, once a checkpoint that won't
+    /// be fallen back past covers them. Does nothing if no event log is set.
+    pub fn compact_event_log(&self, through: u64) -> impl Future<Output = Result<()>> {
+        self.serf.compact_event_log(through)
+    }
+
+    /// Meter every event from now on against `meter`'s limits, or stop metering with `None`.
+    /// Events that go over budget bail with `%intr` (steps) or `%meme` (allocation) and fail.
+    pub fn set_meter(&self, meter: Option<Meter>) -> impl Future<Output = Result<()>> {
+        self.serf.set_meter(meter)
+    }
+
+    /// Write each kernel the app is upgraded to from now on to `path`, see [`UpgradedKernel`].
+    /// `base` is the hash of the kernel the app was booted with, so the next boot can tell
+    /// whether it is still the one that was upgraded from.
+    pub fn persist_upgrades(&self, path: PathBuf, base: Hash) -> impl Future<Output = Result<()>> {
+        self.serf.persist_upgrades(path, base)
+    }
+
+    /// Switch to a new kernel without restarting, loading the current state into it and then
+    /// running the upgrade poke, if any, returning the poke's effects. Pokes and peeks queued
+    /// behind the upgrade run against the new kernel. If the new kernel fails to boot, load the
+    /// state or take the poke, the old kernel keeps running as if nothing happened.
+    ///
+    /// Unless upgrades are persisted with [`Kernel::persist_upgrades`], only the running serf is
+    /// switched: checkpoints record the new kernel's hash, but the app boots whatever kernel it is
+    /// given on its next start.
+    pub fn upgrade(&self, upgrade: KernelUpgrade) -> impl Future<Output = Result<NounSlab>> {
+        self.serf.upgrade(upgrade)
+    }
 }
 
 /// Represents the Serf, which maintains context and provides an interface to
@@ -737,6 +1404,10 @@ pub struct Serf {
     pub event_num: Arc<AtomicU64>,
     /// A metrics
     pub metrics: Option<Arc<NockAppMetrics>>,
+    /// Log committed pokes are appended to, if any
+    pub event_log: Option<EventLog>,
+    /// What the slams since this was last cleared consumed, if a meter is set
+    pub event_usage: Option<MeterUsage>,
 }
 
 impl Serf {
@@ -754,13 +1425,36 @@ impl Serf {
     ///
     /// A new `Serf` instance.
     fn new<C: SerfCheckpoint>(
-        mut stack: NockStack,
+        stack: NockStack,
         checkpoint: Option<C>,
         kernel_bytes: &[u8],
         constant_hot_state: &[HotEntry],
         test_jets: Vec<NounSlab>,
         trace: TraceOpts,
     ) -> Self {
+        Self::try_new(
+            stack, checkpoint, kernel_bytes, constant_hot_state, test_jets, trace,
+        )
+        .unwrap_or_else(|err| {
+            panic!(
+                "Panicked with {err:?} at {}:{} (git sha: {:?})",
+                file!(),
+                line!(),
+                option_env!("GIT_SHA")
+            )
+        })
+    }
+
+    /// Creates a new Serf instance, failing instead of panicking if the kernel doesn't boot or
+    /// can't load the checkpointed state.
+    fn try_new<C: SerfCheckpoint>(
+        mut stack: NockStack,
+        checkpoint: Option<C>,
+        kernel_bytes: &[u8],
+        constant_hot_state: &[HotEntry],
+        test_jets: Vec<NounSlab>,
+        trace: TraceOpts,
+    ) -> Result<Self> {
         let hot_state = [URBIT_HOT_STATE, constant_hot_state].concat();
 
         let mut hasher = Hasher::new();
@@ -776,7 +1470,7 @@ impl Serf {
                 .expect("Could not load cold state from snapshot");
             let cold = Cold::from_vecs(&mut stack, cold_vecs.0, cold_vecs.1, cold_vecs.2);
             if saveable.ker_hash != ker_hash {
-                debug!(
+                info!(
                     "Loading snapshot from kernel {} into kernel {}",
                     saveable.ker_hash, ker_hash
                 );
@@ -793,30 +1487,16 @@ impl Serf {
 
         let mut arvo = {
             let kernel_trap = Noun::cue_bytes_slice(&mut context.stack, kernel_bytes)
-                .expect("invalid kernel jam");
+                .map_err(|_| CrownError::InvalidKernelInput)?;
             let fol = T(&mut context.stack, &[D(9), D(2), D(0), D(1)]);
 
             if context.trace_info.is_some() {
                 let start = Instant::now();
-                let arvo = interpret(&mut context, kernel_trap, fol).unwrap_or_else(|err| {
-                    panic!(
-                        "Panicked with {err:?} at {}:{} (git sha: {:?})",
-                        file!(),
-                        line!(),
-                        option_env!("GIT_SHA")
-                    )
-                });
+                let arvo = interpret(&mut context, kernel_trap, fol)?;
                 write_serf_trace_safe(&mut context, "boot", start);
                 arvo
             } else {
-                interpret(&mut context, kernel_trap, fol).unwrap_or_else(|err| {
-                    panic!(
-                        "Panicked with {err:?} at {}:{} (git sha: {:?})",
-                        file!(),
-                        line!(),
-                        option_env!("GIT_SHA")
-                    )
-                })
+                interpret(&mut context, kernel_trap, fol)?
             }
         };
 
@@ -827,17 +1507,19 @@ impl Serf {
             event_num,
             cancel_token,
             metrics: None,
+            event_log: None,
+            event_usage: None,
         };
 
         if let Some(kernel_state) = maybe_state {
-            arvo = serf.load(kernel_state).expect("serf: load failed");
+            arvo = serf.load(kernel_state)?;
         }
 
         unsafe {
             serf.event_update(event_num_raw, arvo);
             serf.preserve_event_update_leftovers();
         }
-        serf
+        Ok(serf)
     }
 
     /// Performs a peek operation on the Arvo state.
@@ -951,7 +1633,27 @@ impl Serf {
     /// Result containing the slammed result or an error.
     pub fn slam(&mut self, axis: u64, ovo: Noun) -> Result<Noun> {
         let arvo = self.arvo;
-        slam(&mut self.context, arvo, axis, ovo, self.metrics.clone())
+        if let Some(meter) = self.context.meter.as_mut() {
+            meter.start(&mut self.context.stack);
+        }
+        let res = slam(&mut self.context, arvo, axis, ovo, self.metrics.clone());
+        if let Some(meter) = self.context.meter.as_mut() {
+            let usage = meter.finish(&self.context.stack);
+            debug!(
+                steps = usage.steps,
+                alloc_words = usage.alloc_words,
+                "serf: event usage"
+            );
+            if let Some(metrics) = &self.metrics {
+                metrics.event_nock_steps.swap(usage.steps as f64);
+                metrics.event_alloc_words.swap(usage.alloc_words as f64);
+            }
+            self.event_usage = Some(
+                self.event_usage
+                    .map_or(usage, |before| before.combine(usage)),
+            );
+        }
+        res
     }
 
     /// Performs a "soft" computation, handling errors gracefully.
@@ -1149,6 +1851,165 @@ impl Serf {
         src = wire.source
     ))]
     pub fn poke(&mut self, wire: WireRepr, cause: Noun) -> Result<Noun> {
+        let poke = self.poke_job(wire, cause)?;
+        // The job doesn't survive the event update, so it has to be jammed beforehand.
+        let logged = if self.event_log.is_some() {
+            Some(JammedNoun::from_noun(self.stack(), poke))
+        } else {
+            None
+        };
+        let fec = self.do_poke(poke)?;
+        if let Some(job) = logged {
+            self.log_event(job);
+        }
+        Ok(fec)
+    }
+
+    /// Appends a committed poke to the event log. Logging stops if the append fails, so the log
+    /// is never left with a gap in it.
+    fn log_event(&mut self, job: JammedNoun) {
+        let Some(log) = self.event_log.as_mut() else {
+            return;
+        };
+        let event = LoggedEvent::new(self.event_num.load(Ordering::SeqCst), self.ker_hash, job);
+        if let Err(e) = log.append(&event) {
+            error!(
+                "Failed to append event {} to {}, no longer logging events: {}",
+                event.event_num,
+                log.path().display(),
+                e
+            );
+            self.event_log = None;
+        }
+    }
+
+    /// Replays a logged poke on top of the current state.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The logged event, which must be the one after the current event.
+    ///
+    /// # Returns
+    ///
+    /// Result indicating whether the event was committed again.
+    #[tracing::instrument(level = "info", skip_all, fields(event_num = event.event_num))]
+    pub fn replay(&mut self, event: &LoggedEvent) -> Result<()> {
+        let last = self.event_num.load(Ordering::SeqCst);
+        if event.event_num != last + 1 {
+            return Err(EventLogError::Gap {
+                last,
+                next: event.event_num,
+            }
+            .into());
+        }
+        let job = event.job.cue_self(self.stack())?;
+        self.do_poke(job)?;
+        Ok(())
+    }
+
+    /// Runs a poke with a given cause on a fork of the kernel state, then discards the fork.
+    ///
+    /// # Arguments
+    ///
+    /// * `wire` - The wire noun.
+    /// * `cause` - The cause noun.
+    ///
+    /// # Returns
+    ///
+    /// Result containing the effects the poke would have produced, or an error if it crashed.
+    #[tracing::instrument(level = "info", skip_all, fields(
+        src = wire.source
+    ))]
+    pub fn poke_speculative(&mut self, wire: WireRepr, cause: NounSlab) -> Result<NounSlab> {
+        self.fork(|serf| {
+            let cause = cause.copy_to_stack(serf.stack());
+            let job = serf.poke_job(wire, cause)?;
+            match serf.soft(job, POKE_AXIS, Some("speculative poke".to_string())) {
+                Ok(res) => {
+                    let fec = res.as_cell()?.head();
+                    let mut slab = NounSlab::new();
+                    slab.copy_into(fec);
+                    Ok(slab)
+                }
+                Err(goof) => {
+                    if goof.is_cell() {
+                        serf.print_goof(goof);
+                    }
+                    Err(CrownError::KernelError(None))
+                }
+            }
+        })
+    }
+
+    /// Runs `f` against a fork of the kernel state that is discarded afterwards.
+    ///
+    /// Nouns are immutable, so the fork is a fresh stack frame: everything `f` allocates lands
+    /// in it, and popping the frame and restoring the context and Arvo puts the serf back where
+    /// it was. Anything `f` returns must be copied off the stack first, e.g. into a slab.
+    fn fork<T>(&mut self, f: impl FnOnce(&mut Serf) -> Result<T>) -> Result<T> {
+        let arvo = self.arvo;
+        let event_num = self.event_num.load(Ordering::SeqCst);
+        let snapshot = self.context.save();
+        let scry_stack = self.context.scry_stack;
+
+        // SAFETY: `f` copies whatever it returns off the stack, and everything else it can leave
+        // pointing into the frame (the context's caches, the scry stack, Arvo and the event
+        // number) is put back below before anything reads it.
+        let res = unsafe { NockStack::with_frame(self, Serf::stack, f) };
+
+        self.context.restore(&snapshot);
+        self.context.scry_stack = scry_stack;
+        self.event_num.store(event_num, Ordering::SeqCst);
+        self.arvo = arvo;
+        res
+    }
+
+    /// Runs a poke like [`Serf::poke`], except that a crash fails it rather than being delivered
+    /// to the kernel as `%crud`.
+    fn poke_strict(&mut self, wire: WireRepr, cause: Noun) -> Result<Noun> {
+        let poke = self.poke_job(wire, cause)?;
+        let logged = if self.event_log.is_some() {
+            Some(JammedNoun::from_noun(self.stack(), poke))
+        } else {
+            None
+        };
+        match self.soft(poke, POKE_AXIS, Some("poke".to_string())) {
+            Ok(res) => {
+                let cell = res.as_cell()?;
+                let mut fec = cell.head();
+                let eve = self.event_num.load(Ordering::SeqCst);
+                unsafe {
+                    self.event_update(eve + 1, cell.tail());
+                    self.stack().preserve(&mut fec);
+                    self.preserve_event_update_leftovers();
+                }
+                if let Some(job) = logged {
+                    self.log_event(job);
+                }
+                Ok(fec)
+            }
+            Err(goof) => {
+                if goof.is_cell() {
+                    self.print_goof(goof);
+                }
+                Err(CrownError::KernelError(None))
+            }
+        }
+    }
+
+    /// Takes over the handles the rest of the app holds on to from the serf this one replaces,
+    /// along with its meter and event log.
+    fn adopt_handles(&mut self, old: &mut Serf) {
+        self.context.running_status = old.context.running_status.clone();
+        self.cancel_token = old.cancel_token.clone();
+        self.event_num = old.event_num.clone();
+        self.metrics = old.metrics.clone();
+        self.context.meter = old.context.meter.take();
+        self.event_log = old.event_log.take();
+    }
+
+    /// Builds the poke job for a cause, stamped with the next event number.
+    fn poke_job(&mut self, wire: WireRepr, cause: Noun) -> Result<Noun> {
         let random_bytes = rand::random::<u64>();
         let bytes = random_bytes.as_bytes()?;
         let eny: Atom = Atom::from_bytes(&mut self.context.stack, &bytes);
@@ -1167,8 +2028,7 @@ impl Serf {
             &mut self.context.stack,
             &[event_num, wire, eny.as_noun(), our.as_noun(), now.as_noun(), cause],
         );
-
-        self.do_poke(poke)
+        Ok(poke)
     }
 
     /// Updates the Serf's state after an event.
@@ -1259,6 +2119,250 @@ mod tests {
     use std::path::Path;
 
     use super::*;
+    use crate::nockapp::event_log::EVENT_LOG_FILE;
+    use crate::nockapp::upgraded_kernel::UPGRADED_KERNEL_FILE;
+    use crate::nockapp::wire::{SystemWire, Wire};
+    use crate::noun::slab::slab_equality;
+
+    /// Large enough to boot the test kernel, small enough to run out of quickly.
+    const TEST_STACK_WORDS: usize = 1 << 24;
+
+    /// Boot the test kernel on this thread, along with what it takes to reboot it.
+    fn boot_test_serf(sizing: StackSizing) -> (Serf, SerfBoot) {
+        let kernel_bytes = fs::read(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("test-jams")
+                .join("test-ker.jam"),
+        )
+        .expect("Failed to read test-ker.jam");
+        let serf = Serf::new(
+            NockStack::new(sizing.initial, 0),
+            None::<SaveableCheckpoint>,
+            &kernel_bytes,
+            &[],
+            vec![],
+            TraceOpts::default(),
+        );
+        let boot = SerfBoot {
+            kernel_bytes,
+            hot_state: vec![],
+            test_jets: vec![],
+            trace: TraceOpts::default(),
+            sizing,
+            stack_words: sizing.initial,
+            upgrades: None,
+        };
+        (serf, boot)
+    }
+
+    /// Run `f` on a thread with the serf thread's stack size, as the serf loop would.
+    fn on_serf_thread(f: impl FnOnce() + Send + 'static) {
+        std::thread::Builder::new()
+            .stack_size(SERF_THREAD_STACK_SIZE)
+            .spawn(f)
+            .expect("Failed to spawn serf thread")
+            .join()
+            .unwrap_or_else(|err| resume_unwind(err));
+    }
+
+    fn kernel_state(serf: &Serf) -> NounSlab {
+        let mut slab = NounSlab::new();
+        let root = slab.copy_into(serf.arvo.slot(STATE_AXIS).expect("kernel state"));
+        slab.set_root(root);
+        slab
+    }
+
+    /// Poke the test kernel's counter, the way the serf loop runs a poke.
+    fn poke_inc(boot: &mut SerfBoot, serf: &mut Serf) -> Result<()> {
+        boot.run_growing(serf, |serf| {
+            serf.poke(SystemWire.to_wire(), D(tas!(b"inc"))).map(|_| ())
+        })
+    }
+
+    /// The test kernel's counter, from `[%state ~]`, which peeks as `[~ ~ %0 counter]`.
+    fn counter(serf: &mut Serf) -> u64 {
+        let path = T(serf.stack(), &[D(tas!(b"state")), D(0)]);
+        let res = serf.peek(path).expect("state peek should succeed");
+        res.slot(7)
+            .expect("peek should return a value")
+            .as_cell()
+            .expect("peek value should be a cell")
+            .tail()
+            .as_direct()
+            .expect("counter should be a direct atom")
+            .data()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn exhausted_stack_fails_only_the_event() {
+        on_serf_thread(|| {
+            let (mut serf, mut boot) = boot_test_serf(StackSizing::fixed(TEST_STACK_WORDS));
+            poke_inc(&mut boot, &mut serf).expect("first poke should succeed");
+            let state = kernel_state(&serf);
+
+            // *[f f] with f = [[2 [0 1] [0 1]] [1 0]] recurses in the head of an autocons, so it
+            // never becomes a tail call and exhausts any stack.
+            let res = boot.run_growing(&mut serf, |serf| {
+                let stack = serf.stack();
+                let whole = T(stack, &[D(0), D(1)]);
+                let recur = T(stack, &[D(2), whole, whole]);
+                let konst = T(stack, &[D(1), D(0)]);
+                let formula = T(stack, &[recur, konst]);
+                interpret(&mut serf.context, formula, formula)?;
+                Ok(())
+            });
+            assert!(matches!(
+                res,
+                Err(CrownError::OutOfMemory(TEST_STACK_WORDS))
+            ));
+            assert_eq!(boot.stack_words, TEST_STACK_WORDS);
+            assert_eq!(serf.event_num.load(Ordering::SeqCst), 1);
+            assert!(slab_equality(&kernel_state(&serf), &state));
+
+            poke_inc(&mut boot, &mut serf)
+                .expect("poke after running out of memory should succeed");
+            assert_eq!(serf.event_num.load(Ordering::SeqCst), 2);
+            assert_eq!(counter(&mut serf), 2);
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn exhausted_stack_grows_and_retries() {
+        on_serf_thread(|| {
+            let (mut serf, mut boot) = boot_test_serf(StackSizing::growable(
+                TEST_STACK_WORDS,
+                TEST_STACK_WORDS * 4,
+            ));
+            poke_inc(&mut boot, &mut serf).expect("first poke should succeed");
+
+            // A whole initial stack's worth of words can never fit in it, but fits once it doubles.
+            let mut attempts = 0;
+            boot.run_growing(&mut serf, |serf| {
+                attempts += 1;
+                unsafe { IndirectAtom::new_raw_mut_zeroed(serf.stack(), TEST_STACK_WORDS) };
+                Ok(())
+            })
+            .expect("allocation should succeed on a grown stack");
+            assert_eq!(attempts, 2);
+            assert_eq!(boot.stack_words, TEST_STACK_WORDS * 2);
+
+            poke_inc(&mut boot, &mut serf).expect("poke on the grown stack should succeed");
+            assert_eq!(counter(&mut serf), 2);
+        });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn speculative_poke_leaves_serf_untouched() {
+        on_serf_thread(|| {
+            let dir = tempfile::tempdir().expect("Failed to create temp dir");
+            let log_path = dir.path().join("events.log");
+            let (mut serf, mut boot) = boot_test_serf(StackSizing::fixed(TEST_STACK_WORDS));
+            serf.event_log = Some(EventLog::open(&log_path, 0).expect("Failed to open log"));
+            poke_inc(&mut boot, &mut serf).expect("first poke should succeed");
+            let state = kernel_state(&serf);
+
+            let mut cause = NounSlab::new();
+            cause.copy_into(D(tas!(b"inc")));
+            boot.run_growing(&mut serf, |serf| {
+                serf.poke_speculative(SystemWire.to_wire(), cause.clone())
+            })
+            .expect("speculative poke should succeed");
+
+            assert_eq!(serf.event_num.load(Ordering::SeqCst), 1);
+            assert!(slab_equality(&kernel_state(&serf), &state));
+            let logged = EventLog::read_after(&log_path, 0).expect("Failed to read log");
+            assert_eq!(logged.len(), 1);
+
+            // Had the speculative poke stuck, the counter would now be at 3.
+            poke_inc(&mut boot, &mut serf).expect("poke after speculation should succeed");
+            assert_eq!(serf.event_num.load(Ordering::SeqCst), 2);
+            assert_eq!(counter(&mut serf), 2);
+            let logged = EventLog::read_after(&log_path, 0).expect("Failed to read log");
+            assert_eq!(logged.len(), 2);
+        });
+    }
+
+    /// The test kernel inside a trap that fires it: `[[7 [0 3] [9 2 0 1]] kernel]` boots the same
+    /// core from a different jam, so it upgrades to a kernel with a different hash.
+    fn wrapped_kernel(stack: &mut NockStack, kernel: &[u8]) -> Vec<u8> {
+        let trap = Noun::cue_bytes_slice(stack, kernel).expect("kernel should cue");
+        let tail = T(stack, &[D(0), D(3)]);
+        let fire = T(stack, &[D(9), D(2), D(0), D(1)]);
+        let formula = T(stack, &[D(7), tail, fire]);
+        let wrapped = T(stack, &[formula, trap]);
+        JammedNoun::from_noun(stack, wrapped).0.to_vec()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn upgrade_swaps_kernel_in_place() {
+        on_serf_thread(|| {
+            let dir = tempfile::tempdir().expect("Failed to create temp dir");
+            let log_path = dir.path().join(EVENT_LOG_FILE);
+            let upgraded_path = dir.path().join(UPGRADED_KERNEL_FILE);
+            let (mut serf, mut boot) = boot_test_serf(StackSizing::fixed(TEST_STACK_WORDS));
+            let old_hash = serf.ker_hash;
+            boot.upgrades = Some((upgraded_path.clone(), old_hash));
+            serf.event_log = Some(EventLog::open(&log_path, 0).expect("Failed to open log"));
+            poke_inc(&mut boot, &mut serf).expect("first poke should succeed");
+
+            let kernel = wrapped_kernel(serf.stack(), &boot.kernel_bytes);
+            let new_hash = blake3::hash(&kernel);
+            let mut cause = NounSlab::new();
+            cause.copy_into(D(tas!(b"inc")));
+            let upgrade = KernelUpgrade {
+                kernel: kernel.clone(),
+                poke: Some((SystemWire.to_wire(), cause)),
+            };
+            boot.upgrade(&mut serf, upgrade)
+                .expect("upgrade should succeed");
+
+            // The counter carried over and the upgrade poke ran on the new kernel.
+            assert_eq!(serf.ker_hash, new_hash);
+            assert_eq!(serf.event_num.load(Ordering::SeqCst), 2);
+            assert_eq!(counter(&mut serf), 2);
+            assert_eq!(
+                UpgradedKernel::read(&upgraded_path).expect("Failed to read upgraded kernel"),
+                Some(UpgradedKernel {
+                    base: old_hash,
+                    kernel,
+                })
+            );
+
+            poke_inc(&mut boot, &mut serf).expect("poke after the upgrade should succeed");
+            assert_eq!(counter(&mut serf), 3);
+            boot.regrow(&mut serf, TEST_STACK_WORDS)
+                .expect("reboot should succeed");
+            assert_eq!(serf.ker_hash, new_hash);
+            assert_eq!(counter(&mut serf), 3);
+
+            let logged = EventLog::read_after(&log_path, 0).expect("Failed to read log");
+            let ker_hashes: Vec<_> = logged.iter().map(|event| event.ker_hash).collect();
+            assert_eq!(ker_hashes, vec![old_hash, new_hash, new_hash]);
+
+            // Replaying the log from scratch on the old kernel switches over where it did.
+            let (mut replayed, mut replay_boot) =
+                boot_test_serf(StackSizing::fixed(TEST_STACK_WORDS));
+            replay_boot.upgrades = Some((upgraded_path, old_hash));
+            for event in &logged {
+                replay_boot
+                    .switch_for_replay(&mut replayed, event.ker_hash)
+                    .expect("switch should succeed");
+                replay_boot
+                    .run_growing(&mut replayed, |serf| serf.replay(event))
+                    .expect("replay should succeed");
+            }
+            assert_eq!(replayed.ker_hash, new_hash);
+            assert_eq!(counter(&mut replayed), 3);
+            assert!(slab_equality(
+                &kernel_state(&replayed),
+                &kernel_state(&serf)
+            ));
+        });
+    }
 
     async fn setup_kernel(jam: &str) -> Kernel<SaveableCheckpoint> {
         let jam_path = Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -0,0 +1,176 @@
+//! Loading kernel jams from outside the binary.
+//!
+//! Binaries ship with their kernels built in, but `--kernel` can point them at a jam on disk or
+//! at an `http(s)://` URL instead, so a patched kernel can be run without rebuilding. Kernels
+//! fetched over the network must be pinned with `--kernel-sha256`; for kernels on disk the
+//! checksum is optional but checked when given.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+/// How long to wait for a kernel download before giving up
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, thiserror::Error)]
+pub enum KernelSourceError {
+    #[error("Failed to read kernel from {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Failed to download kernel from {url}: {source}")]
+    Download { url: String, source: reqwest::Error },
+    #[error("Kernel at {0} must be pinned with --kernel-sha256")]
+    Unpinned(String),
+    #[error("Invalid kernel checksum {0}, expected 64 hex digits")]
+    InvalidChecksum(String),
+    #[error("Kernel checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+}
+
+/// Where to load a kernel jam from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KernelSource {
+    Path(PathBuf),
+    Url(String),
+}
+
+impl FromStr for KernelSource {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            Ok(KernelSource::Url(s.to_string()))
+        } else {
+            Ok(KernelSource::Path(PathBuf::from(s)))
+        }
+    }
+}
+
+impl std::fmt::Display for KernelSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KernelSource::Path(path) => write!(f, "{}", path.display()),
+            KernelSource::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+impl KernelSource {
+    /// Load the jam, checking it against `sha256` (hex) when given. URLs must be given one.
+    pub async fn load(&self, sha256: Option<&str>) -> Result<Vec<u8>, KernelSourceError> {
+        let jam = match self {
+            KernelSource::Path(path) => {
+                tokio::fs::read(path)
+                    .await
+                    .map_err(|source| KernelSourceError::Read {
+                        path: path.clone(),
+                        source,
+                    })?
+            }
+            KernelSource::Url(url) => {
+                if sha256.is_none() {
+                    return Err(KernelSourceError::Unpinned(url.clone()));
+                }
+                download(url).await?
+            }
+        };
+        if let Some(expected) = sha256 {
+            verify_sha256(&jam, expected)?;
+        }
+        info!(
+            "Loaded {} byte kernel from {} (sha256 {})",
+            jam.len(),
+            self,
+            hex::encode(Sha256::digest(&jam))
+        );
+        Ok(jam)
+    }
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, KernelSourceError> {
+    let to_error = |source| KernelSourceError::Download {
+        url: url.to_string(),
+        source,
+    };
+    let response = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(to_error)?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(to_error)?;
+    Ok(response.bytes().await.map_err(to_error)?.to_vec())
+}
+
+/// Check that `jam` hashes to `expected`, a hex SHA-256 digest.
+pub fn verify_sha256(jam: &[u8], expected: &str) -> Result<(), KernelSourceError> {
+    let expected = expected.trim().to_ascii_lowercase();
+    if expected.len() != 64 || !expected.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(KernelSourceError::InvalidChecksum(expected));
+    }
+    let actual = hex::encode(Sha256::digest(jam));
+    if actual != expected {
+        return Err(KernelSourceError::ChecksumMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            "https://example.com/dumb.jam"
+                .parse::<KernelSource>()
+                .unwrap(),
+            KernelSource::Url("https://example.com/dumb.jam".to_string())
+        );
+        assert_eq!(
+            "./assets/dumb.jam".parse::<KernelSource>().unwrap(),
+            KernelSource::Path(PathBuf::from("./assets/dumb.jam"))
+        );
+    }
+
+    #[test]
+    fn test_verify_sha256() {
+        let actual = hex::encode(Sha256::digest(b"kernel"));
+        assert!(verify_sha256(b"kernel", &actual).is_ok());
+        assert!(verify_sha256(b"kernel", &actual.to_uppercase()).is_ok());
+        assert!(matches!(
+            verify_sha256(b"kernel!", &actual),
+            Err(KernelSourceError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_sha256(b"kernel", "abc"),
+            Err(KernelSourceError::InvalidChecksum(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("kernel.jam");
+        std::fs::write(&path, b"kernel").unwrap();
+        let source = KernelSource::Path(path);
+        let checksum = hex::encode(Sha256::digest(b"kernel"));
+
+        assert_eq!(source.load(None).await.unwrap(), b"kernel");
+        assert_eq!(source.load(Some(&checksum)).await.unwrap(), b"kernel");
+        assert!(source.load(Some(&"0".repeat(64))).await.is_err());
+
+        let url = KernelSource::Url("https://example.com/kernel.jam".to_string());
+        assert!(matches!(
+            url.load(None).await,
+            Err(KernelSourceError::Unpinned(_))
+        ));
+    }
+}
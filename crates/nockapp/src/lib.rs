@@ -14,8 +14,10 @@
 //! - `noun`: Extensions and utilities for working with Urbit nouns.
 //! - `utils`: Errors, misc functions and extensions.
 //!
+pub mod data_dir;
 pub mod drivers;
 pub mod kernel;
+pub mod logging;
 pub mod nockapp;
 pub mod noun;
 pub mod observability;
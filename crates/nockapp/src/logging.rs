@@ -0,0 +1,442 @@
+//! Log files for long-running apps: one JSON object per line, rotated by size and age, with old
+//! files pruned, and a level per component so that e.g. kernel debug output can be kept without
+//! the rest.
+//!
+//! Rotated files sit next to the live one, named `<stem>.<UTC timestamp>.<ext>`.
+
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Metadata, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// When to start a new log file, and how many old ones to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// Rotate once the file would grow past this many bytes
+    pub max_bytes: u64,
+    /// Rotate once the file is this old
+    pub max_age: Duration,
+    /// Rotated files kept, oldest deleted first
+    pub keep: usize,
+}
+
+/// A log file that rotates itself according to a [`RotationPolicy`]. Writes go straight to the
+/// file, so nothing is lost if the process crashes.
+pub struct RotatingFile {
+    path: PathBuf,
+    policy: RotationPolicy,
+    active: Mutex<Active>,
+}
+
+struct Active {
+    file: File,
+    size: u64,
+    opened: SystemTime,
+}
+
+impl RotatingFile {
+    /// Append to the log file at `path`, creating it if needed.
+    pub fn open(path: impl Into<PathBuf>, policy: RotationPolicy) -> io::Result<Self> {
+        let path = path.into();
+        let active = Active::open(&path)?;
+        Ok(Self {
+            path,
+            policy,
+            active: Mutex::new(active),
+        })
+    }
+
+    fn due(&self, active: &Active, incoming: usize, now: SystemTime) -> bool {
+        let age = now.duration_since(active.opened).unwrap_or_default();
+        active.size > 0
+            && (active.size + incoming as u64 > self.policy.max_bytes || age >= self.policy.max_age)
+    }
+
+    fn rotate(&self, active: &mut Active, now: SystemTime) -> io::Result<()> {
+        let stamp = chrono::DateTime::<chrono::Utc>::from(now).format("%Y%m%dT%H%M%S%.3f");
+        let (stem, ext) = self.stem_and_ext();
+        fs::rename(
+            &self.path,
+            self.path.with_file_name(format!("{stem}.{stamp}.{ext}")),
+        )?;
+        *active = Active::open(&self.path)?;
+        self.prune()
+    }
+
+    /// Delete rotated files beyond the newest `keep`.
+    fn prune(&self) -> io::Result<()> {
+        let (stem, ext) = self.stem_and_ext();
+        let (prefix, suffix) = (format!("{stem}."), format!(".{ext}"));
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut rotated: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                name.len() > prefix.len() + suffix.len()
+                    && name.starts_with(&prefix)
+                    && name.ends_with(&suffix)
+            })
+            .collect();
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.policy.keep);
+        for path in &rotated[..excess] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn stem_and_ext(&self) -> (String, String) {
+        let stem = self.path.file_stem().unwrap_or_default();
+        let ext = self.path.extension().unwrap_or_default();
+        (
+            stem.to_string_lossy().into_owned(),
+            ext.to_string_lossy().into_owned(),
+        )
+    }
+}
+
+impl Active {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let metadata = file.metadata()?;
+        Ok(Self {
+            file,
+            size: metadata.len(),
+            opened: metadata
+                .created()
+                .or_else(|_| metadata.modified())
+                .unwrap_or_else(|_| SystemTime::now()),
+        })
+    }
+}
+
+impl Write for &RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let now = SystemTime::now();
+        if self.due(&active, buf.len(), now) {
+            if let Err(e) = self.rotate(&mut active, now) {
+                eprintln!("Failed to rotate {}: {}", self.path.display(), e);
+                // Try again after another full period rather than on every write
+                active.size = 0;
+                active.opened = now;
+            }
+        }
+        let written = active.file.write(buf)?;
+        active.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .file
+            .flush()
+    }
+}
+
+/// The parts of an app that log levels can be set for, by the target of their events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    Kernel,
+    Drivers,
+    Grpc,
+    Other,
+}
+
+const KERNEL_TARGETS: &[&str] = &[
+    "nockapp::kernel", "nockapp::nockapp", "nockapp::save", "nockvm", "zkvm_jetpack", "slogger",
+    "nockcode",
+];
+const DRIVER_TARGETS: &[&str] = &[
+    "nockapp::drivers", "nockchain_libp2p_io", "libp2p", "nockchain::mining", "nockchain::stratum",
+];
+const GRPC_TARGETS: &[&str] = &["nockapp_grpc", "tonic", "h2"];
+
+impl Component {
+    pub fn of(target: &str) -> Self {
+        let matches = |prefixes: &[&str]| {
+            prefixes.iter().any(|prefix| {
+                target == *prefix
+                    || target
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+        };
+        if matches(KERNEL_TARGETS) {
+            Component::Kernel
+        } else if matches(DRIVER_TARGETS) {
+            Component::Drivers
+        } else if matches(GRPC_TARGETS) {
+            Component::Grpc
+        } else {
+            Component::Other
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Component::Kernel => "kernel",
+            Component::Drivers => "drivers",
+            Component::Grpc => "grpc",
+            Component::Other => "other",
+        }
+    }
+}
+
+/// Log file levels per component, parsed from e.g. `info,kernel=debug,grpc=warn`. A bare level
+/// sets the default for every component not named.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentLevels {
+    default: LevelFilter,
+    kernel: Option<LevelFilter>,
+    drivers: Option<LevelFilter>,
+    grpc: Option<LevelFilter>,
+}
+
+impl ComponentLevels {
+    pub fn level(&self, component: Component) -> LevelFilter {
+        let level = match component {
+            Component::Kernel => self.kernel,
+            Component::Drivers => self.drivers,
+            Component::Grpc => self.grpc,
+            Component::Other => None,
+        };
+        level.unwrap_or(self.default)
+    }
+
+    pub fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level(Component::of(metadata.target()))
+    }
+}
+
+impl Default for ComponentLevels {
+    fn default() -> Self {
+        Self {
+            default: LevelFilter::INFO,
+            kernel: None,
+            drivers: None,
+            grpc: None,
+        }
+    }
+}
+
+impl FromStr for ComponentLevels {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut levels = ComponentLevels::default();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let parse = |level: &str| {
+                LevelFilter::from_str(level).map_err(|_| format!("Invalid log level '{level}'"))
+            };
+            match directive.split_once('=') {
+                None => levels.default = parse(directive)?,
+                Some(("kernel", level)) => levels.kernel = Some(parse(level)?),
+                Some(("drivers", level)) => levels.drivers = Some(parse(level)?),
+                Some(("grpc", level)) => levels.grpc = Some(parse(level)?),
+                Some((component, _)) => {
+                    return Err(format!(
+                        "Unknown log component '{component}', expected kernel, drivers or grpc"
+                    ))
+                }
+            }
+        }
+        Ok(levels)
+    }
+}
+
+/// Formats each event as a single line of JSON.
+pub struct JsonFormatter;
+
+impl<S, N> FormatEvent<S, N> for JsonFormatter
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let spans: Vec<Value> = ctx
+            .event_scope()
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| Value::from(span.name()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+                .into(),
+        );
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert(
+            "component".into(),
+            Component::of(metadata.target()).name().into(),
+        );
+        line.insert("target".into(), metadata.target().into());
+        if !spans.is_empty() {
+            line.insert("spans".into(), spans.into());
+        }
+        line.insert("fields".into(), fields.into());
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    fn policy(max_bytes: u64, keep: usize) -> RotationPolicy {
+        RotationPolicy {
+            max_bytes,
+            max_age: Duration::from_secs(3600),
+            keep,
+        }
+    }
+
+    fn rotated(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name != "node.log")
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn rotates_by_size_and_prunes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("node.log");
+        let log = RotatingFile::open(&path, policy(10, 2)).unwrap();
+
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            (&log).write_all(line.as_bytes()).unwrap();
+            // Rotated names have millisecond timestamps
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "dddddddd\n");
+        let rotated = rotated(tmp.path());
+        assert_eq!(rotated.len(), 2);
+        let contents: Vec<String> = rotated
+            .iter()
+            .map(|name| fs::read_to_string(tmp.path().join(name)).unwrap())
+            .collect();
+        assert_eq!(contents, vec!["bbbbbbbb\n", "cccccccc\n"]);
+    }
+
+    #[test]
+    fn rotates_by_age() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("node.log");
+        let log = RotatingFile::open(&path, policy(1 << 20, 5)).unwrap();
+        (&log).write_all(b"old\n").unwrap();
+        log.active.lock().unwrap().opened = SystemTime::now() - Duration::from_secs(7200);
+        (&log).write_all(b"new\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        assert_eq!(rotated(tmp.path()).len(), 1);
+    }
+
+    #[test]
+    fn component_levels() {
+        let levels: ComponentLevels = "warn,kernel=debug,grpc=off".parse().unwrap();
+        assert_eq!(levels.level(Component::Kernel), LevelFilter::DEBUG);
+        assert_eq!(levels.level(Component::Drivers), LevelFilter::WARN);
+        assert_eq!(levels.level(Component::Grpc), LevelFilter::OFF);
+        assert_eq!(levels.level(Component::Other), LevelFilter::WARN);
+        assert!("mempool=info".parse::<ComponentLevels>().is_err());
+        assert!("kernel=loud".parse::<ComponentLevels>().is_err());
+
+        assert_eq!(Component::of("nockvm::interpreter"), Component::Kernel);
+        assert_eq!(Component::of("nockapp_grpc::server"), Component::Grpc);
+        assert_eq!(Component::of("nockchain::mining"), Component::Drivers);
+        assert_eq!(Component::of("nockvmx"), Component::Other);
+    }
+
+    #[test]
+    fn events_are_written_as_json_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("node.log");
+        let log = Arc::new(RotatingFile::open(&path, policy(1 << 20, 5)).unwrap());
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(log)
+                .event_format(JsonFormatter),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("poke");
+            let _guard = span.enter();
+            tracing::warn!(target: "nockapp_grpc::server", peers = 3, "serving");
+        });
+
+        let line: Value = serde_json::from_str(fs::read_to_string(&path).unwrap().trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["component"], "grpc");
+        assert_eq!(line["spans"], serde_json::json!(["poke"]));
+        assert_eq!(line["fields"]["message"], "serving");
+        assert_eq!(line["fields"]["peers"], 3);
+    }
+}
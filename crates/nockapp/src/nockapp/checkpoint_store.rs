@@ -0,0 +1,447 @@
+//! Copies of the checkpoint directory kept in S3-compatible object storage.
+//!
+//! Checkpoints are mirrored as they are on disk: chunks are stored once under
+//! `<prefix>/chunks/<blake3>.jam`, each uploaded checkpoint manifest under
+//! `<prefix>/checkpoints/<event_num>.chkjam`, and `<prefix>/latest.json` names the newest one
+//! along with its SHA-256. The pointer is only written once everything it refers to is stored, so
+//! a node restoring from the bucket never sees a half-uploaded checkpoint. Old checkpoints are
+//! left in place; expire them with a lifecycle rule on the bucket.
+//!
+//! The store is named by an `s3://bucket/prefix` URL. Requests are path-style and signed with the
+//! usual `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`, against
+//! `AWS_REGION` (default `us-east-1`). `NOCKAPP_CHECKPOINT_S3_ENDPOINT` points them at another
+//! S3-compatible service, e.g. `http://localhost:9000` for MinIO.
+
+use std::collections::HashSet;
+use std::env;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use bytes::Bytes;
+use reqwest::{Client, Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::{debug, info};
+
+use crate::chunks::{ChunkHash, ChunkStore};
+use crate::save::{CheckpointError, CheckpointFile, CHECKPOINT_FILES};
+use crate::utils::sigv4::sigv4_authorization;
+
+const DEFAULT_REGION: &str = "us-east-1";
+const LATEST_KEY: &str = "latest.json";
+/// How long a single object transfer may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+/// Where a restored checkpoint is written until all of its chunks are in place
+const RESTORE_TEMP_FILE: &str = "restore.chkjam.tmp";
+
+#[derive(Debug, Error)]
+pub enum CheckpointStoreError {
+    #[error("Invalid checkpoint store {0}, expected s3://bucket/prefix")]
+    InvalidUrl(String),
+    #[error("Checkpoint store is not configured: {0}")]
+    Config(String),
+    #[error("Request for {key} failed: {source}")]
+    Request { key: String, source: reqwest::Error },
+    #[error("Request for {key} failed with {status}: {body}")]
+    Status {
+        key: String,
+        status: StatusCode,
+        body: String,
+    },
+    #[error("Object {0} is missing from the checkpoint store")]
+    Missing(String),
+    #[error("Object {0} does not match its checksum")]
+    ChecksumMismatch(String),
+    #[error("Invalid {LATEST_KEY}: {0}")]
+    InvalidLatest(#[from] serde_json::Error),
+    #[error("Checkpoint error: {0}")]
+    Checkpoint(#[from] CheckpointError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A bucket and key prefix, parsed from `s3://bucket/prefix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreLocation {
+    pub bucket: String,
+    pub prefix: String,
+}
+
+impl FromStr for StoreLocation {
+    type Err = CheckpointStoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CheckpointStoreError::InvalidUrl(s.to_string());
+        let rest = s.strip_prefix("s3://").ok_or_else(invalid)?;
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        // Keys go into the signed path as they are, so keep them to characters that need no
+        // percent-encoding
+        let plain = |c: char| c.is_ascii_alphanumeric() || "-_./".contains(c);
+        if bucket.is_empty() || !bucket.chars().all(plain) || !prefix.chars().all(plain) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for StoreLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "s3://{}/{}", self.bucket, self.prefix)
+    }
+}
+
+/// The contents of `latest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatestCheckpoint {
+    pub event_num: u64,
+    /// Key of the checkpoint manifest, relative to the prefix
+    pub checkpoint: String,
+    /// Hex SHA-256 of the checkpoint manifest
+    pub sha256: String,
+}
+
+/// A minimal S3 client: path-style GET, PUT and HEAD of whole objects.
+struct S3Bucket {
+    client: Client,
+    endpoint: Url,
+    location: StoreLocation,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl S3Bucket {
+    fn from_env(location: StoreLocation) -> Result<Self, CheckpointStoreError> {
+        let var = |name: &str| {
+            env::var(name).map_err(|_| CheckpointStoreError::Config(format!("{} is not set", name)))
+        };
+        let region = env::var("AWS_REGION").unwrap_or_else(|_| DEFAULT_REGION.to_string());
+        let endpoint = env::var("NOCKAPP_CHECKPOINT_S3_ENDPOINT")
+            .unwrap_or_else(|_| format!("https://s3.{}.amazonaws.com", region));
+        let endpoint = Url::parse(&endpoint).map_err(|e| {
+            CheckpointStoreError::Config(format!("invalid endpoint {}: {}", endpoint, e))
+        })?;
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| CheckpointStoreError::Config(e.to_string()))?;
+        Ok(Self {
+            client,
+            endpoint,
+            location,
+            region,
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    fn object_path(&self, key: &str) -> String {
+        if self.location.prefix.is_empty() {
+            format!("/{}/{}", self.location.bucket, key)
+        } else {
+            format!("/{}/{}/{}", self.location.bucket, self.location.prefix, key)
+        }
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        key: &str,
+        body: Option<Bytes>,
+    ) -> Result<reqwest::Response, CheckpointStoreError> {
+        let path = self.object_path(key);
+        let host = match (self.endpoint.host_str(), self.endpoint.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(CheckpointStoreError::Config(format!(
+                    "endpoint {} has no host",
+                    self.endpoint
+                )))
+            }
+        };
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(body.as_deref().unwrap_or_default()));
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization = sigv4_authorization(
+            &self.access_key_id,
+            &self.secret_access_key,
+            method.as_str(),
+            &path,
+            &headers,
+            &payload_hash,
+            &amz_date,
+            &self.region,
+            "s3",
+        );
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("authorization", authorization);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, value);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        request
+            .send()
+            .await
+            .map_err(|source| CheckpointStoreError::Request {
+                key: key.to_string(),
+                source,
+            })
+    }
+
+    async fn check(
+        key: &str,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response, CheckpointStoreError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(CheckpointStoreError::Status {
+            key: key.to_string(),
+            status,
+            body,
+        })
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>, CheckpointStoreError> {
+        let response = self.request(Method::GET, key, None).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = Self::check(key, response).await?;
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|source| CheckpointStoreError::Request {
+                key: key.to_string(),
+                source,
+            })?;
+        Ok(Some(bytes))
+    }
+
+    async fn put(&self, key: &str, body: Bytes) -> Result<(), CheckpointStoreError> {
+        let response = self.request(Method::PUT, key, Some(body)).await?;
+        Self::check(key, response).await?;
+        Ok(())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, CheckpointStoreError> {
+        let response = self.request(Method::HEAD, key, None).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        Self::check(key, response).await?;
+        Ok(true)
+    }
+}
+
+fn chunk_key(hash: &ChunkHash) -> String {
+    format!("chunks/{}.jam", blake3::Hash::from_bytes(*hash).to_hex())
+}
+
+fn checkpoint_key(event_num: u64) -> String {
+    format!("checkpoints/{}.chkjam", event_num)
+}
+
+/// Uploads checkpoints to, and restores them from, a bucket.
+pub struct CheckpointStore {
+    bucket: S3Bucket,
+    /// Chunks known to be in the bucket already
+    uploaded_chunks: HashSet<ChunkHash>,
+    last_uploaded: Option<u64>,
+}
+
+impl CheckpointStore {
+    /// Connect to the store at `location`, taking credentials from the environment.
+    pub fn from_env(location: StoreLocation) -> Result<Self, CheckpointStoreError> {
+        Ok(Self {
+            bucket: S3Bucket::from_env(location)?,
+            uploaded_chunks: HashSet::new(),
+            last_uploaded: None,
+        })
+    }
+
+    pub fn location(&self) -> &StoreLocation {
+        &self.bucket.location
+    }
+
+    /// Upload the newest checkpoint in `dir`, unless it has been uploaded already. Returns the
+    /// event number of the checkpoint uploaded.
+    pub async fn upload_latest(&mut self, dir: &Path) -> Result<Option<u64>, CheckpointStoreError> {
+        let Some(checkpoint) = CheckpointFile::latest(dir).await else {
+            return Ok(None);
+        };
+        if self
+            .last_uploaded
+            .is_some_and(|last| last >= checkpoint.event_num)
+        {
+            return Ok(None);
+        }
+        let manifest = Bytes::from(tokio::fs::read(&checkpoint.path).await?);
+
+        let chunks = ChunkStore::new(dir);
+        let mut uploaded = 0;
+        for hash in &checkpoint.chunks {
+            if self.uploaded_chunks.contains(hash) {
+                continue;
+            }
+            let key = chunk_key(hash);
+            if !self.bucket.exists(&key).await? {
+                // Read one at a time, checking each against its hash so a chunk damaged on disk
+                // is never uploaded
+                let jam = chunks.read(std::iter::once(hash)).await?.remove(hash);
+                let jam = jam.ok_or_else(|| CheckpointStoreError::Missing(key.clone()))?;
+                self.bucket.put(&key, jam).await?;
+                uploaded += 1;
+            }
+            self.uploaded_chunks.insert(*hash);
+        }
+
+        let latest = LatestCheckpoint {
+            event_num: checkpoint.event_num,
+            checkpoint: checkpoint_key(checkpoint.event_num),
+            sha256: hex::encode(Sha256::digest(&manifest)),
+        };
+        self.bucket.put(&latest.checkpoint, manifest).await?;
+        self.bucket
+            .put(LATEST_KEY, Bytes::from(serde_json::to_vec(&latest)?))
+            .await?;
+        // Forget chunks the checkpoint no longer uses, they may be expired from the bucket
+        self.uploaded_chunks
+            .retain(|hash| checkpoint.chunks.contains(hash));
+        self.last_uploaded = Some(checkpoint.event_num);
+        debug!(
+            "Uploaded {} new chunks of {} for checkpoint",
+            uploaded,
+            checkpoint.chunks.len()
+        );
+        Ok(Some(checkpoint.event_num))
+    }
+
+    /// Download the newest checkpoint in the store into the empty checkpoint directory `dir`,
+    /// verifying every object against its checksum. Returns the event number restored, or
+    /// `None` if the store is empty.
+    pub async fn restore_latest(&self, dir: &Path) -> Result<Option<u64>, CheckpointStoreError> {
+        let Some(latest) = self.bucket.get(LATEST_KEY).await? else {
+            return Ok(None);
+        };
+        let latest: LatestCheckpoint = serde_json::from_slice(&latest)?;
+        let manifest = self
+            .bucket
+            .get(&latest.checkpoint)
+            .await?
+            .ok_or_else(|| CheckpointStoreError::Missing(latest.checkpoint.clone()))?;
+        if hex::encode(Sha256::digest(&manifest)) != latest.sha256.to_ascii_lowercase() {
+            return Err(CheckpointStoreError::ChecksumMismatch(latest.checkpoint));
+        }
+
+        tokio::fs::create_dir_all(dir).await?;
+        let temp = dir.join(RESTORE_TEMP_FILE);
+        tokio::fs::write(&temp, &manifest).await?;
+        let restored = self.restore_chunks(dir, &temp, &latest).await;
+        if restored.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        restored?;
+        tokio::fs::rename(&temp, dir.join(CHECKPOINT_FILES[0])).await?;
+        Ok(Some(latest.event_num))
+    }
+
+    async fn restore_chunks(
+        &self,
+        dir: &Path,
+        manifest: &Path,
+        latest: &LatestCheckpoint,
+    ) -> Result<(), CheckpointStoreError> {
+        let checkpoint = CheckpointFile::read(manifest).await?;
+        if checkpoint.event_num != latest.event_num {
+            return Err(CheckpointStoreError::ChecksumMismatch(
+                latest.checkpoint.clone(),
+            ));
+        }
+        let chunks = ChunkStore::new(dir);
+        for hash in &checkpoint.chunks {
+            let key = chunk_key(hash);
+            let jam = self
+                .bucket
+                .get(&key)
+                .await?
+                .ok_or_else(|| CheckpointStoreError::Missing(key.clone()))?;
+            if blake3::hash(&jam).as_bytes() != hash {
+                return Err(CheckpointStoreError::ChecksumMismatch(key));
+            }
+            chunks.write_missing(std::iter::once(&(*hash, jam))).await?;
+        }
+        info!(
+            "Restored checkpoint at event {} with {} chunks from {}",
+            latest.event_num,
+            checkpoint.chunks.len(),
+            self.location()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(
+            "s3://backups/nodes/a/".parse::<StoreLocation>().unwrap(),
+            StoreLocation {
+                bucket: "backups".to_string(),
+                prefix: "nodes/a".to_string(),
+            }
+        );
+        assert_eq!("s3://backups".parse::<StoreLocation>().unwrap().prefix, "");
+        assert!("https://backups/nodes".parse::<StoreLocation>().is_err());
+        assert!("s3:///nodes".parse::<StoreLocation>().is_err());
+        assert!("s3://backups/nodes a".parse::<StoreLocation>().is_err());
+    }
+
+    #[test]
+    fn test_object_path() {
+        let bucket = |location: &str| S3Bucket {
+            client: Client::new(),
+            endpoint: Url::parse("http://localhost:9000").unwrap(),
+            location: location.parse().unwrap(),
+            region: DEFAULT_REGION.to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            session_token: None,
+        };
+        assert_eq!(
+            bucket("s3://backups/node").object_path(LATEST_KEY),
+            "/backups/node/latest.json"
+        );
+        assert_eq!(
+            bucket("s3://backups").object_path(&checkpoint_key(42)),
+            "/backups/checkpoints/42.chkjam"
+        );
+    }
+}
@@ -0,0 +1,564 @@
+//! Content-addressed chunks for incremental checkpoints.
+//!
+//! A checkpointed noun is cut into chunks at large subtrees and at subtrees shared from several
+//! places. Each chunk is jammed with the chunks below it replaced by a reference cell
+//! `[CHUNK_REF_TAG hash]` and stored under the blake3 hash of its jam. A subtree that hasn't
+//! changed since the last checkpoint produces the same chunk, so a save only writes the chunks
+//! that are new, plus a small manifest naming the roots. Chunks that neither checkpoint refers to
+//! are deleted after each save. On load, chunks are independent jams, so they are cued in
+//! parallel. Legacy checkpoints saved as a single jam don't get this: their backreferences can
+//! point anywhere earlier in the stream, so they cue on one thread until the next save rewrites
+//! them as chunks.
+//!
+//! Every save still copies, jams and hashes the whole state: the state is copied out of the serf
+//! afresh each time, so there is no subtree identity to remember a hash by across saves. What
+//! chunking saves is disk writes and checkpoint size, not the time the serf is paused.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use either::Either;
+use intmap::IntMap;
+use nockvm::ext::AtomExt;
+use nockvm::noun::{Atom, Cell, Noun, T};
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, warn};
+
+use crate::noun::slab::{CueError, Jammer, NounSlab};
+use crate::save::CheckpointError;
+
+pub type ChunkHash = [u8; 32];
+
+/// Subtrees weighing at least this much (cells plus atom words) become their own chunk.
+const CHUNK_WEIGHT: usize = 1 << 16;
+
+/// Shared subtrees become their own chunk at this weight, so they aren't duplicated into every
+/// chunk that points at them.
+const SHARED_CHUNK_WEIGHT: usize = 1 << 8;
+
+/// Head of a reference to another chunk. A noun containing this atom can't be chunked
+/// unambiguously, and is saved whole instead.
+const CHUNK_REF_TAG: &[u8] = b"nockapp/snapshot/chunk-reference";
+
+/// A noun cut into chunks.
+pub struct ChunkedNoun {
+    pub root: ChunkHash,
+    /// Every distinct chunk of the noun, each after the chunks it refers to.
+    pub chunks: Vec<(ChunkHash, Bytes)>,
+}
+
+impl ChunkedNoun {
+    pub fn hashes(&self) -> Vec<ChunkHash> {
+        self.chunks.iter().map(|(hash, _)| *hash).collect()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Part {
+    /// The subtree with its chunks replaced by references, or the reference itself if the
+    /// subtree was cut.
+    noun: Noun,
+    /// Weight left in the subtree once its chunks have been cut out.
+    weight: usize,
+    chunk: Option<ChunkHash>,
+}
+
+struct Chunker<J> {
+    /// Holds rebuilt cells and references until the chunks using them are jammed.
+    work: NounSlab<J>,
+    tag: Noun,
+    in_degree: IntMap<u64, u32>,
+    parts: IntMap<u64, Part>,
+    seen: HashSet<ChunkHash>,
+    chunks: Vec<(ChunkHash, Bytes)>,
+}
+
+impl<J: Jammer> Chunker<J> {
+    fn new(root: Noun) -> Self {
+        let mut work = NounSlab::new();
+        let tag = <Atom as AtomExt>::from_bytes(&mut work, CHUNK_REF_TAG).as_noun();
+        Self {
+            work,
+            tag,
+            in_degree: count_in_degree(root),
+            parts: IntMap::new(),
+            seen: HashSet::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    fn part(&self, noun: Noun) -> Part {
+        match noun.as_allocated() {
+            Ok(_) => *self
+                .parts
+                .get(unsafe { noun.as_raw() })
+                .expect("children are chunked before their parents"),
+            Err(_) => Part {
+                noun,
+                weight: 0,
+                chunk: None,
+            },
+        }
+    }
+
+    fn chunk(mut self, root: Noun) -> Result<ChunkedNoun, CheckpointError> {
+        let mut stack = vec![(root, false)];
+        while let Some((noun, expanded)) = stack.pop() {
+            let Ok(allocated) = noun.as_allocated() else {
+                continue;
+            };
+            let key = unsafe { noun.as_raw() };
+            if self.parts.get(key).is_some() {
+                continue;
+            }
+            let part = match allocated.as_either() {
+                Either::Left(indirect) => {
+                    if indirect.as_atom().eq_bytes(CHUNK_REF_TAG) {
+                        return Err(CheckpointError::ChunkTagCollision);
+                    }
+                    self.finish(key, noun, indirect.size())
+                }
+                Either::Right(cell) if !expanded => {
+                    stack.push((noun, true));
+                    stack.push((cell.tail(), false));
+                    stack.push((cell.head(), false));
+                    continue;
+                }
+                Either::Right(cell) => {
+                    let head = self.part(cell.head());
+                    let tail = self.part(cell.tail());
+                    let unchanged = unsafe {
+                        head.noun.as_raw() == cell.head().as_raw()
+                            && tail.noun.as_raw() == cell.tail().as_raw()
+                    };
+                    let rebuilt = if unchanged {
+                        noun
+                    } else {
+                        Cell::new(&mut self.work, head.noun, tail.noun).as_noun()
+                    };
+                    self.finish(key, rebuilt, 1 + head.weight + tail.weight)
+                }
+            };
+            self.parts.insert(key, part);
+        }
+
+        let root = match self.part(root) {
+            Part {
+                chunk: Some(hash), ..
+            } => hash,
+            Part { noun, .. } => self.cut(noun),
+        };
+        Ok(ChunkedNoun {
+            root,
+            chunks: self.chunks,
+        })
+    }
+
+    fn finish(&mut self, key: u64, noun: Noun, weight: usize) -> Part {
+        let shared = self.in_degree.get(key).copied().unwrap_or(0) > 1;
+        if weight >= CHUNK_WEIGHT || (shared && weight >= SHARED_CHUNK_WEIGHT) {
+            let hash = self.cut(noun);
+            let hash_atom = <Atom as AtomExt>::from_bytes(&mut self.work, &hash).as_noun();
+            let reference = T(&mut self.work, &[self.tag, hash_atom]);
+            Part {
+                noun: reference,
+                weight: 1,
+                chunk: Some(hash),
+            }
+        } else {
+            Part {
+                noun,
+                weight,
+                chunk: None,
+            }
+        }
+    }
+
+    fn cut(&mut self, noun: Noun) -> ChunkHash {
+        let jam = J::jam(noun);
+        let hash = *blake3::hash(&jam).as_bytes();
+        if self.seen.insert(hash) {
+            self.chunks.push((hash, jam));
+        }
+        hash
+    }
+}
+
+/// How many times each allocated noun is referenced from within `root`.
+fn count_in_degree(root: Noun) -> IntMap<u64, u32> {
+    let mut in_degree: IntMap<u64, u32> = IntMap::new();
+    let mut stack = vec![root];
+    while let Some(noun) = stack.pop() {
+        if noun.as_allocated().is_err() {
+            continue;
+        }
+        let key = unsafe { noun.as_raw() };
+        if let Some(count) = in_degree.get_mut(key) {
+            *count += 1;
+            continue;
+        }
+        in_degree.insert(key, 1);
+        if let Ok(cell) = noun.as_cell() {
+            stack.push(cell.tail());
+            stack.push(cell.head());
+        }
+    }
+    in_degree
+}
+
+/// Cut `root` into chunks, or fail with [`CheckpointError::ChunkTagCollision`] if it contains the
+/// chunk reference tag.
+pub fn chunk_noun<J: Jammer>(root: Noun) -> Result<ChunkedNoun, CheckpointError> {
+    Chunker::<J>::new(root).chunk(root)
+}
+
+/// Rebuild a noun from its chunks. `order` lists the noun's chunks with every chunk after the
+/// chunks it refers to, as [`ChunkedNoun::chunks`] does.
+///
+/// Each chunk is a self-contained jam, so the chunks are cued in parallel and only the cheap
+/// pass that splices references back together runs on one thread.
+pub fn assemble_noun<J: Jammer>(
+    root: &ChunkHash,
+    order: &[ChunkHash],
+    chunks: &HashMap<ChunkHash, Bytes>,
+) -> Result<NounSlab<J>, CheckpointError> {
+    let jams = order
+        .iter()
+        .map(|hash| {
+            chunks
+                .get(hash)
+                .map(|jam| (*hash, jam.clone()))
+                .ok_or_else(|| CheckpointError::MissingChunk(hex(hash)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut slab = NounSlab::<J>::new();
+    let mut bodies = Vec::with_capacity(jams.len());
+    for cued in cue_parallel::<J>(&jams)? {
+        slab.absorb(cued.slab);
+        bodies.extend(cued.bodies);
+    }
+
+    let mut resolved: HashMap<ChunkHash, Noun> = HashMap::new();
+    for (hash, body) in bodies {
+        let noun = resolve_references(&mut slab, body, &resolved)?;
+        resolved.insert(hash, noun);
+    }
+    let root = resolved
+        .get(root)
+        .ok_or_else(|| CheckpointError::MissingChunk(hex(root)))?;
+    slab.set_root(*root);
+    Ok(slab)
+}
+
+/// Chunks cued by one worker, in the order they were given.
+struct CuedChunks {
+    slab: NounSlab,
+    bodies: Vec<(ChunkHash, Noun)>,
+}
+
+// SAFETY: the bodies only point into `slab`, which moves with them.
+unsafe impl Send for CuedChunks {}
+
+/// Cue `jams` on up to one thread per core, splitting them into contiguous runs of roughly equal
+/// size. The results come back in the same order as `jams`.
+fn cue_parallel<J: Jammer>(jams: &[(ChunkHash, Bytes)]) -> Result<Vec<CuedChunks>, CueError> {
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, jams.len().max(1));
+    let total: usize = jams.iter().map(|(_, jam)| jam.len()).sum();
+    let target = total.div_ceil(workers).max(1);
+
+    let mut groups = Vec::with_capacity(workers);
+    let mut start = 0;
+    let mut size = 0;
+    for (i, (_, jam)) in jams.iter().enumerate() {
+        size += jam.len();
+        if size >= target {
+            groups.push(&jams[start..=i]);
+            start = i + 1;
+            size = 0;
+        }
+    }
+    if start < jams.len() {
+        groups.push(&jams[start..]);
+    }
+
+    if groups.len() <= 1 {
+        return groups.into_iter().map(cue_group::<J>).collect();
+    }
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = groups
+            .into_iter()
+            .map(|group| scope.spawn(move || cue_group::<J>(group)))
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("chunk cue worker panicked"))
+            .collect()
+    })
+}
+
+fn cue_group<J: Jammer>(group: &[(ChunkHash, Bytes)]) -> Result<CuedChunks, CueError> {
+    let mut slab = NounSlab::<J>::new();
+    let mut bodies = Vec::with_capacity(group.len());
+    for (hash, jam) in group {
+        bodies.push((*hash, slab.cue_into(jam.clone())?));
+    }
+    Ok(CuedChunks {
+        slab: slab.coerce_jammer(),
+        bodies,
+    })
+}
+
+/// Replace the chunk references in `body` with the chunks they name.
+fn resolve_references<J>(
+    slab: &mut NounSlab<J>,
+    body: Noun,
+    resolved: &HashMap<ChunkHash, Noun>,
+) -> Result<Noun, CheckpointError> {
+    let mut done: IntMap<u64, Noun> = IntMap::new();
+    let mut stack = vec![(body, false)];
+    while let Some((noun, expanded)) = stack.pop() {
+        let Ok(cell) = noun.as_cell() else {
+            continue;
+        };
+        let key = unsafe { noun.as_raw() };
+        if done.get(key).is_some() {
+            continue;
+        }
+        let replacement = if let Some(hash) = reference_hash(cell)? {
+            *resolved
+                .get(&hash)
+                .ok_or_else(|| CheckpointError::MissingChunk(hex(&hash)))?
+        } else if !expanded {
+            stack.push((noun, true));
+            stack.push((cell.tail(), false));
+            stack.push((cell.head(), false));
+            continue;
+        } else {
+            let lookup = |child: Noun| match child.as_cell() {
+                Ok(_) => *done
+                    .get(unsafe { child.as_raw() })
+                    .expect("children are resolved before their parents"),
+                Err(_) => child,
+            };
+            let head = lookup(cell.head());
+            let tail = lookup(cell.tail());
+            if unsafe {
+                head.as_raw() == cell.head().as_raw() && tail.as_raw() == cell.tail().as_raw()
+            } {
+                noun
+            } else {
+                Cell::new(slab, head, tail).as_noun()
+            }
+        };
+        done.insert(key, replacement);
+    }
+    match body.as_cell() {
+        Ok(_) => Ok(*done
+            .get(unsafe { body.as_raw() })
+            .expect("body is resolved")),
+        Err(_) => Ok(body),
+    }
+}
+
+fn reference_hash(cell: Cell) -> Result<Option<ChunkHash>, CheckpointError> {
+    let Ok(head) = cell.head().as_atom() else {
+        return Ok(None);
+    };
+    if !head.eq_bytes(CHUNK_REF_TAG) {
+        return Ok(None);
+    }
+    let bytes = cell
+        .tail()
+        .as_atom()
+        .map_err(|_| CheckpointError::MalformedChunkReference)?;
+    let bytes = bytes.as_ne_bytes();
+    if bytes.len() > 32 && bytes[32..].iter().any(|byte| *byte != 0) {
+        return Err(CheckpointError::MalformedChunkReference);
+    }
+    let mut hash = [0u8; 32];
+    let len = bytes.len().min(32);
+    hash[..len].copy_from_slice(&bytes[..len]);
+    Ok(Some(hash))
+}
+
+/// Make the renames in `dir` durable.
+#[cfg(unix)]
+async fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    tokio::fs::File::open(dir).await?.sync_all().await
+}
+
+/// Directories can't be opened for syncing here; renames are as durable as the platform makes
+/// them.
+#[cfg(not(unix))]
+async fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn hex(hash: &ChunkHash) -> String {
+    blake3::Hash::from_bytes(*hash).to_hex().to_string()
+}
+
+/// The directory of chunk files next to the checkpoint manifests.
+pub struct ChunkStore {
+    dir: PathBuf,
+}
+
+impl ChunkStore {
+    pub fn new(checkpoint_dir: &Path) -> Self {
+        Self {
+            dir: checkpoint_dir.join("chunks"),
+        }
+    }
+
+    /// Where the chunk with this hash is stored.
+    pub fn path(&self, hash: &ChunkHash) -> PathBuf {
+        self.dir.join(format!("{}.jam", hex(hash)))
+    }
+
+    /// Write the chunks that aren't stored yet, returning how many were written. Every chunk is
+    /// on disk when this returns, so a manifest written afterwards never names a lost chunk.
+    pub async fn write_missing<'a>(
+        &self,
+        chunks: impl IntoIterator<Item = &'a (ChunkHash, Bytes)>,
+    ) -> Result<usize, CheckpointError> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let mut written = 0;
+        for (hash, jam) in chunks {
+            let path = self.path(hash);
+            if tokio::fs::try_exists(&path).await? {
+                continue;
+            }
+            // Sync, then rename into place, so a crash never leaves a truncated chunk that looks
+            // stored.
+            let temp = path.with_extension("tmp");
+            let mut file = tokio::fs::File::create(&temp).await?;
+            file.write_all(jam).await?;
+            file.sync_all().await?;
+            drop(file);
+            tokio::fs::rename(&temp, &path).await?;
+            written += 1;
+        }
+        if written > 0 {
+            sync_dir(&self.dir).await?;
+        }
+        Ok(written)
+    }
+
+    /// Read and verify the named chunks.
+    pub async fn read<'a>(
+        &self,
+        hashes: impl IntoIterator<Item = &'a ChunkHash>,
+    ) -> Result<HashMap<ChunkHash, Bytes>, CheckpointError> {
+        let mut chunks = HashMap::new();
+        for hash in hashes {
+            if chunks.contains_key(hash) {
+                continue;
+            }
+            let jam = match tokio::fs::read(self.path(hash)).await {
+                Ok(jam) => jam,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(CheckpointError::MissingChunk(hex(hash)));
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if blake3::hash(&jam).as_bytes() != hash {
+                return Err(CheckpointError::CorruptChunk(hex(hash)));
+            }
+            chunks.insert(*hash, Bytes::from(jam));
+        }
+        Ok(chunks)
+    }
+
+    /// Delete every stored chunk not in `live`. Failures are logged rather than returned, since
+    /// an extra chunk on disk is harmless.
+    pub async fn retain(&self, live: &HashSet<ChunkHash>) {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("failed to list chunks in {}: {}", self.dir.display(), e);
+                return;
+            }
+        };
+        let mut removed = 0;
+        loop {
+            let entry = match entries.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("failed to list chunks in {}: {}", self.dir.display(), e);
+                    break;
+                }
+            };
+            let path = entry.path();
+            let keep = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| blake3::Hash::from_hex(stem).ok())
+                .is_some_and(|hash| {
+                    path.extension().is_some_and(|ext| ext == "jam")
+                        && live.contains(hash.as_bytes())
+                });
+            if keep {
+                continue;
+            }
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("failed to remove chunk {}: {}", path.display(), e),
+            }
+        }
+        debug!("Removed {} unreferenced checkpoint chunks", removed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nockvm::noun::D;
+
+    use super::*;
+    use crate::noun::slab::{slab_equality, NockJammer};
+
+    fn chunk_map(chunked: &ChunkedNoun) -> HashMap<ChunkHash, Bytes> {
+        chunked.chunks.iter().cloned().collect()
+    }
+
+    #[test]
+    fn reassembles_large_and_shared_chunks() {
+        let mut slab = NounSlab::<NockJammer>::new();
+        let mut shared = D(0);
+        for i in 0..1_000 {
+            shared = T(&mut slab, &[D(i), shared]);
+        }
+        let mut list = D(0);
+        for i in 0..(4 * CHUNK_WEIGHT as u64) {
+            list = T(&mut slab, &[D(i), list]);
+            if i % 50_000 == 0 {
+                list = T(&mut slab, &[shared, list]);
+            }
+        }
+        slab.set_root(list);
+
+        let chunked = chunk_noun::<NockJammer>(list).expect("chunk noun");
+        assert!(
+            chunked.chunks.len() > 4,
+            "noun should be cut into several chunks"
+        );
+        let assembled =
+            assemble_noun::<NockJammer>(&chunked.root, &chunked.hashes(), &chunk_map(&chunked))
+                .expect("assemble noun");
+        assert!(slab_equality(&assembled, &slab));
+    }
+
+    #[test]
+    fn missing_chunk_fails() {
+        let mut slab = NounSlab::<NockJammer>::new();
+        let noun = T(&mut slab, &[D(1), D(2)]);
+        let chunked = chunk_noun::<NockJammer>(noun).expect("chunk noun");
+        let result = assemble_noun::<NockJammer>(&chunked.root, &chunked.hashes(), &HashMap::new());
+        assert!(matches!(result, Err(CheckpointError::MissingChunk(_))));
+    }
+}
@@ -11,6 +11,7 @@ use super::error::NockAppError;
 use super::metrics::NockAppMetrics;
 use super::wire::WireRepr;
 use super::NockAppExit;
+use crate::kernel::form::KernelUpgrade;
 use crate::noun::slab::NounSlab;
 
 pub type IODriverFuture = Pin<Box<dyn Future<Output = Result<(), NockAppError>> + Send>>;
@@ -68,6 +69,19 @@ pub enum IOAction {
         path: NounSlab,
         result_channel: oneshot::Sender<Option<NounSlab>>,
     },
+    /// Poke request to [`crate::NockApp`] run against a throwaway fork of the kernel state.
+    /// The effects are returned instead of broadcast, and the state is left untouched.
+    PokeSpeculative {
+        wire: WireRepr,
+        poke: NounSlab,
+        result_channel: oneshot::Sender<Option<Vec<NounSlab>>>,
+    },
+    /// Request to [`crate::NockApp`] to switch to a new kernel in place. The effects of the
+    /// upgrade poke are broadcast like those of any other poke.
+    UpgradeKernel {
+        upgrade: KernelUpgrade,
+        result_channel: oneshot::Sender<Result<(), NockAppError>>,
+    },
 }
 
 impl NockAppHandle {
@@ -196,6 +210,39 @@ impl NockAppHandle {
         Ok(result_future.await?)
     }
 
+    /// Run a poke without committing its result, returning the effects it would produce, or
+    /// `None` if it crashed.
+    #[tracing::instrument(name = "nockapp::NockAppHandle::poke_speculative", skip_all)]
+    pub async fn poke_speculative(
+        &self,
+        wire: WireRepr,
+        poke: NounSlab,
+    ) -> Result<Option<Vec<NounSlab>>, NockAppError> {
+        let (result_channel, result_future) = oneshot::channel();
+        self.io_sender
+            .send(IOAction::PokeSpeculative {
+                wire,
+                poke,
+                result_channel,
+            })
+            .await?;
+        Ok(result_future.await?)
+    }
+
+    /// Switch the app to a new kernel without restarting it, carrying the state over. Drivers
+    /// stay attached throughout; the kernel is only swapped between events.
+    #[tracing::instrument(name = "nockapp::NockAppHandle::upgrade_kernel", skip_all)]
+    pub async fn upgrade_kernel(&self, upgrade: KernelUpgrade) -> Result<(), NockAppError> {
+        let (result_channel, result_future) = oneshot::channel();
+        self.io_sender
+            .send(IOAction::UpgradeKernel {
+                upgrade,
+                result_channel,
+            })
+            .await?;
+        result_future.await?
+    }
+
     #[instrument(skip(self))]
     pub async fn next_effect(&self) -> Result<NounSlab, NockAppError> {
         let mut effect_receiver = self.effect_receiver.lock().await;
@@ -0,0 +1,408 @@
+//! Append-only log of the pokes a kernel has committed, for replaying on top of a snapshot.
+//!
+//! Each committed poke is written as the full job the kernel was slammed with, entropy and
+//! timestamp included, along with the hash of the kernel it ran on, so replaying the log against
+//! the snapshot it was taken on reproduces the same events in the same order, switching kernels
+//! where the app was upgraded in between. A record is a little-endian `u32` length followed by a
+//! bincode-encoded [`LoggedEvent`]. Every append is synced to disk before the poke's effects are
+//! released, so a committed event is never lost to a crash.
+//!
+//! A record cut off by a crash, or one that fails to decode or checksum, ends the log: it and
+//! everything after it are dropped with a warning when the log is reopened. Once a checkpoint
+//! that will never be fallen back past covers the start of the log, [`EventLog::compact`] drops
+//! the records it covers.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use bincode::config::Configuration;
+use bincode::{config, encode_to_vec, Decode, Encode};
+use blake3::{Hash, Hasher};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::JammedNoun;
+
+/// File name of the event log in the checkpoints directory
+pub const EVENT_LOG_FILE: &str = "events.log";
+
+#[derive(Error, Debug)]
+pub enum EventLogError {
+    #[error("IO error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("Bincode decoding error: {0}")]
+    DecodeError(#[from] bincode::error::DecodeError),
+    #[error("Bincode encoding error: {0}")]
+    EncodeError(#[from] bincode::error::EncodeError),
+    #[error("Invalid checksum for event {0}")]
+    InvalidChecksum(u64),
+    #[error("Event log skips from event {last} to event {next}")]
+    Gap { last: u64, next: u64 },
+    #[error(
+        "Event log holds events up to {last} past the checkpoint at event {event_num}; replay them \
+         before logging new events, or move the log aside"
+    )]
+    Unreplayed { event_num: u64, last: u64 },
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Debug)]
+pub struct LoggedEvent {
+    /// Checksum derived from event_num, ker_hash and job (the entries below)
+    #[bincode(with_serde)]
+    pub checksum: Hash,
+    /// Event number the poke was committed as
+    pub event_num: u64,
+    /// Hash of the kernel the poke ran on
+    #[bincode(with_serde)]
+    pub ker_hash: Hash,
+    /// The job the kernel was poked with
+    pub job: JammedNoun,
+}
+
+impl LoggedEvent {
+    pub fn new(event_num: u64, ker_hash: Hash, job: JammedNoun) -> Self {
+        let checksum = Self::checksum(event_num, &ker_hash, &job);
+        Self {
+            checksum,
+            event_num,
+            ker_hash,
+            job,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), EventLogError> {
+        if self.checksum != Self::checksum(self.event_num, &self.ker_hash, &self.job) {
+            Err(EventLogError::InvalidChecksum(self.event_num))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn checksum(event_num: u64, ker_hash: &Hash, job: &JammedNoun) -> Hash {
+        let mut hasher = Hasher::new();
+        hasher.update(&event_num.to_le_bytes());
+        hasher.update(ker_hash.as_bytes());
+        hasher.update(&job.0);
+        hasher.finalize()
+    }
+}
+
+pub struct EventLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl EventLog {
+    /// Open the log at `path` for appending after `event_num`, creating it if needed.
+    ///
+    /// Refuses to open a log holding events after `event_num`: those events were committed, so
+    /// they have to be replayed on top of the checkpoint (see [`EventLog::read_after`]) before new
+    /// events can follow them. A torn or corrupt record at the end of the log is cut off.
+    pub fn open(path: &Path, event_num: u64) -> Result<Self, EventLogError> {
+        let records = Self::read_records(path)?;
+        if let Some(last) = records.events.last().map(|(event, _)| event.event_num) {
+            if last > event_num {
+                return Err(EventLogError::Unreplayed { event_num, last });
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .read(true)
+            .open(path)?;
+        let valid_len = records.valid_len();
+        let len = file.metadata()?.len();
+        if len > valid_len {
+            warn!(
+                "Truncating {} bytes of unreadable records from the end of {}",
+                len - valid_len,
+                path.display()
+            );
+            file.set_len(valid_len)?;
+            file.sync_data()?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a committed event to the log, syncing it to disk before returning.
+    pub fn append(&mut self, event: &LoggedEvent) -> Result<(), EventLogError> {
+        let record = encode_to_vec(event, config::standard())?;
+        let len = u32::try_from(record.len()).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "event log record too large",
+            )
+        })?;
+        let mut bytes = Vec::with_capacity(4 + record.len());
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&record);
+        self.file.write_all(&bytes)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Drop the records for events up to and including `through`, which a checkpoint covers.
+    /// The remaining records are written to a temporary file that then replaces the log.
+    pub fn compact(&mut self, through: u64) -> Result<(), EventLogError> {
+        let records = Self::read_records(&self.path)?;
+        let start = records
+            .events
+            .iter()
+            .take_while(|(event, _)| event.event_num <= through)
+            .last()
+            .map_or(0, |(_, end)| *end);
+        if start == 0 {
+            return Ok(());
+        }
+
+        let tmp_path = self.path.with_extension("log.tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(&records.bytes[start as usize..records.valid_len() as usize])?;
+        tmp.sync_all()?;
+        drop(tmp);
+        fs::rename(&tmp_path, &self.path)?;
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            File::open(dir)?.sync_all()?;
+        }
+        self.file = OpenOptions::new()
+            .append(true)
+            .read(true)
+            .open(&self.path)?;
+        Ok(())
+    }
+
+    /// Read every event in the log at `path` after `event_num`, checking that they follow on from
+    /// it with no gaps. A missing log has no events.
+    pub fn read_after(path: &Path, event_num: u64) -> Result<Vec<LoggedEvent>, EventLogError> {
+        let mut last = event_num;
+        let mut after = Vec::new();
+        for (event, _) in Self::read_records(path)?.events {
+            if event.event_num <= event_num {
+                continue;
+            }
+            if event.event_num != last + 1 {
+                return Err(EventLogError::Gap {
+                    last,
+                    next: event.event_num,
+                });
+            }
+            last = event.event_num;
+            after.push(event);
+        }
+        Ok(after)
+    }
+
+    /// Every valid record in the log, up to the first one that is torn or corrupt.
+    fn read_records(path: &Path) -> Result<Records, EventLogError> {
+        let mut bytes = Vec::new();
+        match File::open(path) {
+            Ok(mut file) => {
+                file.read_to_end(&mut bytes)?;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Records {
+                    bytes,
+                    events: Vec::new(),
+                })
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut events = Vec::new();
+        let mut offset = 0;
+        while let Some(len_bytes) = bytes.get(offset..offset + 4) {
+            let len = u32::from_le_bytes(len_bytes.try_into().expect("4 bytes")) as usize;
+            let Some(record) = bytes.get(offset + 4..offset + 4 + len) else {
+                break;
+            };
+            let decoded = bincode::decode_from_slice::<LoggedEvent, Configuration>(
+                record,
+                config::standard(),
+            )
+            .map_err(EventLogError::from)
+            .and_then(|(event, _)| event.validate().map(|()| event));
+            let event = match decoded {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!(
+                        "Corrupt record at offset {} of {}, ignoring it and everything after it: {}",
+                        offset,
+                        path.display(),
+                        e
+                    );
+                    break;
+                }
+            };
+            offset += 4 + len;
+            events.push((event, offset as u64));
+        }
+        Ok(Records { bytes, events })
+    }
+}
+
+/// The raw contents of a log and the valid records read from it, each with the offset it ends at.
+struct Records {
+    bytes: Vec<u8>,
+    events: Vec<(LoggedEvent, u64)>,
+}
+
+impl Records {
+    fn valid_len(&self) -> u64 {
+        self.events.last().map_or(0, |(_, end)| *end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn event(event_num: u64) -> LoggedEvent {
+        LoggedEvent::new(
+            event_num,
+            blake3::hash(b"kernel"),
+            JammedNoun::from(vec![event_num as u8; 8]),
+        )
+    }
+
+    #[test]
+    fn test_append_and_read_after() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(EVENT_LOG_FILE);
+        let mut log = EventLog::open(&path, 0).unwrap();
+        for event_num in 1..=5 {
+            log.append(&event(event_num)).unwrap();
+        }
+        drop(log);
+
+        let events = EventLog::read_after(&path, 2).unwrap();
+        assert_eq!(events, vec![event(3), event(4), event(5)]);
+        assert!(EventLog::read_after(&path, 5).unwrap().is_empty());
+        assert!(EventLog::read_after(&dir.path().join("missing.log"), 0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_torn_record_is_dropped() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(EVENT_LOG_FILE);
+        let mut log = EventLog::open(&path, 0).unwrap();
+        log.append(&event(1)).unwrap();
+        log.append(&event(2)).unwrap();
+        drop(log);
+
+        let len = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        let mut log = EventLog::open(&path, 2).unwrap();
+        log.append(&event(2)).unwrap();
+        log.append(&event(3)).unwrap();
+        drop(log);
+        let events = EventLog::read_after(&path, 0).unwrap();
+        assert_eq!(events, vec![event(1), event(2), event(3)]);
+    }
+
+    #[test]
+    fn test_gap_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(EVENT_LOG_FILE);
+        let mut log = EventLog::open(&path, 0).unwrap();
+        log.append(&event(1)).unwrap();
+        log.append(&event(3)).unwrap();
+        drop(log);
+
+        assert!(matches!(
+            EventLog::read_after(&path, 0),
+            Err(EventLogError::Gap { last: 1, next: 3 })
+        ));
+        assert_eq!(EventLog::read_after(&path, 2).unwrap(), vec![event(3)]);
+    }
+
+    #[test]
+    fn test_corrupt_record_ends_the_log() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(EVENT_LOG_FILE);
+        let mut log = EventLog::open(&path, 0).unwrap();
+        for event_num in 1..=3 {
+            log.append(&event(event_num)).unwrap();
+        }
+        drop(log);
+
+        // Flip the last byte of event 2's job so its checksum no longer matches.
+        let records = EventLog::read_records(&path).unwrap();
+        let mut bytes = records.bytes;
+        let end = records.events[1].1 as usize;
+        bytes[end - 1] ^= 0xff;
+        std::fs::write(&path, &bytes).unwrap();
+
+        assert_eq!(EventLog::read_after(&path, 0).unwrap(), vec![event(1)]);
+        let mut log = EventLog::open(&path, 1).unwrap();
+        log.append(&event(2)).unwrap();
+        drop(log);
+        assert_eq!(
+            EventLog::read_after(&path, 0).unwrap(),
+            vec![event(1), event(2)]
+        );
+    }
+
+    #[test]
+    fn test_open_refuses_unreplayed_events() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(EVENT_LOG_FILE);
+        let mut log = EventLog::open(&path, 0).unwrap();
+        for event_num in 1..=5 {
+            log.append(&event(event_num)).unwrap();
+        }
+        drop(log);
+
+        assert!(matches!(
+            EventLog::open(&path, 3),
+            Err(EventLogError::Unreplayed {
+                event_num: 3,
+                last: 5
+            })
+        ));
+        assert_eq!(
+            EventLog::read_after(&path, 3).unwrap(),
+            vec![event(4), event(5)]
+        );
+        EventLog::open(&path, 5).unwrap();
+    }
+
+    #[test]
+    fn test_compact_drops_covered_events() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(EVENT_LOG_FILE);
+        let mut log = EventLog::open(&path, 0).unwrap();
+        for event_num in 1..=5 {
+            log.append(&event(event_num)).unwrap();
+        }
+
+        log.compact(3).unwrap();
+        log.append(&event(6)).unwrap();
+        drop(log);
+        assert_eq!(
+            EventLog::read_after(&path, 3).unwrap(),
+            vec![event(4), event(5), event(6)]
+        );
+        assert!(matches!(
+            EventLog::read_after(&path, 0),
+            Err(EventLogError::Gap { last: 0, next: 4 })
+        ));
+    }
+}
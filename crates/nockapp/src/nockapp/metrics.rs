@@ -9,6 +9,8 @@ metrics_struct![
     (poke_during_exit, "nockapp.poke_during_exit", Count),
     (peek_during_exit, "nockapp.peek_during_exit", Count),
     (least_free_space_seen_in_slam, "nockapp.least_free_space_seen_in_slam", Gauge),
+    (event_nock_steps, "nockapp.event.nock_steps", Gauge),
+    (event_alloc_words, "nockapp.event.alloc_words", Gauge),
     (save_jam_time, "nockapp.save_jam_time", TimingCount),
     (load_cue_time, "nockapp.load_cue_time", TimingCount),
     (serf_loop_blocking_recv, "nockapp.serf_loop.blocking_recv", TimingCount),
@@ -22,6 +24,8 @@ metrics_struct![
     (serf_loop_copy_state_noun, "nockapp.serf_loop.copy_state_noun", TimingCount),
     (serf_loop_peek, "nockapp.serf_loop.peek", TimingCount),
     (serf_loop_poke, "nockapp.serf_loop.poke", TimingCount),
+    (serf_loop_poke_speculative, "nockapp.serf_loop.poke_speculative", TimingCount),
+    (serf_loop_upgrade, "nockapp.serf_loop.upgrade", TimingCount),
     (serf_loop_provide_metrics, "nockapp.serf_loop.provide_metrics", TimingCount),
     (next_effect_lagged_error, "nockapp.next_effect.lag", Count)
 ];
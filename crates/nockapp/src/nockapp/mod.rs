@@ -1,10 +1,14 @@
 // pub(crate) mod actors;
+pub mod checkpoint_store;
+pub mod chunks;
 pub mod driver;
 pub mod error;
+pub mod event_log;
 pub mod export;
 pub(crate) mod metrics;
 pub mod save;
 pub mod test;
+pub mod upgraded_kernel;
 pub mod wire;
 
 use std::future::Future;
@@ -28,7 +32,7 @@ use tokio_util::task::TaskTracker;
 use tracing::{debug, error, info, instrument, trace, warn};
 use wire::WireRepr;
 
-use crate::kernel::form::Kernel;
+use crate::kernel::form::{Kernel, KernelUpgrade};
 use crate::noun::slab::{Jammer, NockJammer, NounSlab};
 use crate::save::{SaveableCheckpoint, Saver};
 
@@ -275,6 +279,9 @@ impl<J: Jammer + Send + 'static> NockApp<J> {
         mut save_permit: OwnedMutexGuard<Saver<J>>,
     ) -> Result<tokio::task::JoinHandle<NockAppResult>, NockAppError> {
         let checkpoint_fut = self.kernel.checkpoint();
+        // Once this save lands, the checkpoint it doesn't overwrite is the oldest one a load can
+        // fall back to, so the event log only needs the events after it.
+        let compact_fut = self.kernel.compact_event_log(save_permit.last_event_num());
         let metrics = self.metrics.clone();
 
         trace!("Spawning save task from save_f");
@@ -285,6 +292,9 @@ impl<J: Jammer + Send + 'static> NockApp<J> {
             trace!("Save task from save_f: checkpoint_fut.await done");
             save_permit.save(checkpoint, metrics).await?;
             trace!("Save task from save_f: save_permit.save done");
+            if let Err(e) = compact_fut.await {
+                warn!("Failed to compact the event log: {:?}", e);
+            }
 
             drop(save_permit);
             Ok::<(), NockAppError>(())
@@ -300,6 +310,11 @@ impl<J: Jammer + Send + 'static> NockApp<J> {
         Ok(())
     }
 
+    /// Deduplicate identical subtrees of the kernel state when saving, see [`Saver::set_hash_cons`].
+    pub async fn set_snapshot_hash_cons(&self, enabled: bool) {
+        self.save_mutex.lock().await.set_hash_cons(enabled);
+    }
+
     pub async fn save_locked(&mut self) -> NockAppResult {
         trace!("save_locked: locking save_mutex");
         let guard = self.save_mutex.clone().lock_owned().await;
@@ -392,6 +407,29 @@ impl<J: Jammer + Send + 'static> NockApp<J> {
         Ok(effects_slab.to_vec())
     }
 
+    /// Run a poke against a throwaway fork of the kernel state, returning the effects it would
+    /// produce without changing the canonical state.
+    #[tracing::instrument(skip(self, wire, cause))]
+    pub async fn poke_speculative(
+        &mut self,
+        wire: WireRepr,
+        cause: NounSlab,
+    ) -> Result<Vec<NounSlab>, NockAppError> {
+        let effects_slab = self.kernel.poke_speculative(wire, cause).await?;
+        Ok(effects_slab.to_vec())
+    }
+
+    /// Switch to a new kernel in place, carrying the state over, and return the effects of the
+    /// upgrade poke. See [`Kernel::upgrade`].
+    #[tracing::instrument(skip_all)]
+    pub async fn upgrade_kernel(
+        &mut self,
+        upgrade: KernelUpgrade,
+    ) -> Result<Vec<NounSlab>, NockAppError> {
+        let effects_slab = self.kernel.upgrade(upgrade).await?;
+        Ok(effects_slab.to_vec())
+    }
+
     /// Runs until the nockapp is done (returns exit 0 or an error)
     /// TODO: we should print most errors rather than exiting immediately
     #[instrument(skip(self))]
@@ -573,6 +611,18 @@ impl<J: Jammer + Send + 'static> NockApp<J> {
                 path,
                 result_channel,
             } => self.handle_peek(path, result_channel).await,
+            IOAction::PokeSpeculative {
+                wire,
+                poke,
+                result_channel,
+            } => {
+                self.handle_poke_speculative(wire, poke, result_channel)
+                    .await
+            }
+            IOAction::UpgradeKernel {
+                upgrade,
+                result_channel,
+            } => self.handle_upgrade_kernel(upgrade, result_channel).await,
         }
     }
 
@@ -643,6 +693,52 @@ impl<J: Jammer + Send + 'static> NockApp<J> {
         }));
     }
 
+    #[instrument(skip_all)]
+    async fn handle_poke_speculative(
+        &self,
+        wire: WireRepr,
+        cause: NounSlab,
+        result_channel: tokio::sync::oneshot::Sender<Option<Vec<NounSlab>>>,
+    ) {
+        let poke_future = self.kernel.poke_speculative(wire, cause);
+        drop(self.tasks.spawn(async move {
+            match poke_future.await {
+                Ok(effects) => {
+                    let _ = result_channel.send(Some(effects.to_vec()));
+                }
+                Err(e) => {
+                    debug!("Speculative poke failed: {:?}", e);
+                    let _ = result_channel.send(None);
+                }
+            }
+        }));
+    }
+
+    #[instrument(skip_all)]
+    async fn handle_upgrade_kernel(
+        &self,
+        upgrade: KernelUpgrade,
+        result_channel: tokio::sync::oneshot::Sender<Result<(), NockAppError>>,
+    ) {
+        let upgrade_future = self.kernel.upgrade(upgrade);
+        let effect_broadcast = self.effect_broadcast.clone();
+        drop(self.tasks.spawn(async move {
+            match upgrade_future.await {
+                Ok(effects) => {
+                    info!("Upgraded kernel");
+                    let _ = result_channel.send(Ok(()));
+                    for effect_slab in effects.to_vec() {
+                        let _ = effect_broadcast.send(effect_slab);
+                    }
+                }
+                Err(e) => {
+                    error!("Kernel upgrade failed: {:?}", e);
+                    let _ = result_channel.send(Err(e.into()));
+                }
+            }
+        }));
+    }
+
     // TODO: We should explicitly kick off a save somehow
     // TOOD: :>) spawn a task which awaits the signal stream and if there is a SIGINT, then call std::process::exit(1)
     #[instrument(skip_all)]
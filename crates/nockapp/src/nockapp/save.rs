@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -11,8 +12,9 @@ use nockvm_macros::tas;
 use thiserror::Error;
 use tokio::fs::create_dir_all;
 use tokio::sync::oneshot;
-use tracing::{debug, error, trace, warn};
+use tracing::{debug, error, info, trace, warn};
 
+use crate::chunks::{assemble_noun, chunk_noun, ChunkHash, ChunkStore, ChunkedNoun};
 use crate::metrics::NockAppMetrics;
 use crate::noun::slab::{Jammer, NockJammer, NounSlab};
 use crate::JammedNoun;
@@ -21,7 +23,10 @@ pub const JAM_MAGIC_BYTES: u64 = tas!(b"CHKJAM");
 const SNAPSHOT_VERSION_0: u32 = 0;
 const SNAPSHOT_VERSION_1: u32 = 1;
 const SNAPSHOT_VERSION_2: u32 = 2;
-pub const LATEST_SNAPSHOT_VERSION: u32 = SNAPSHOT_VERSION_2;
+const SNAPSHOT_VERSION_3: u32 = 3;
+pub const LATEST_SNAPSHOT_VERSION: u32 = SNAPSHOT_VERSION_3;
+/// The two checkpoint files a [`Saver`] alternates between
+pub const CHECKPOINT_FILES: [&str; 2] = ["0.chkjam", "1.chkjam"];
 
 pub enum WhichSnapshot {
     Snapshot0,
@@ -35,6 +40,13 @@ impl WhichSnapshot {
             WhichSnapshot::Snapshot1 => WhichSnapshot::Snapshot0,
         }
     }
+
+    fn index(&self) -> usize {
+        match self {
+            WhichSnapshot::Snapshot0 => 0,
+            WhichSnapshot::Snapshot1 => 1,
+        }
+    }
 }
 
 /// State object which handles all NockApp saves and loads
@@ -44,6 +56,11 @@ pub struct Saver<J = NockJammer> {
     save_to_next: WhichSnapshot,
     waiters: Vec<(u64, oneshot::Sender<()>)>,
     last_event_num: u64,
+    chunks: ChunkStore,
+    /// Chunks referenced by the checkpoints at `path_0` and `path_1`
+    live_chunks: [HashSet<ChunkHash>; 2],
+    /// Whether to hash-cons the state before writing it, see [`Saver::set_hash_cons`]
+    hash_cons: bool,
     _phantom: std::marker::PhantomData<J>,
 }
 
@@ -79,10 +96,21 @@ impl<J> Saver<J> {
         futures::future::Either::Right(rx)
     }
 
+    /// Event number of the most recent checkpoint saved or loaded
+    pub fn last_event_num(&self) -> u64 {
+        self.last_event_num
+    }
+
     /// Check if we need to save
     pub fn save_needed(&self, event_num: u64) -> bool {
         self.last_event_num < event_num
     }
+
+    /// Deduplicate identical subtrees of the state before each save. This makes saves slower,
+    /// but state with a lot of repetition gets smaller snapshots that are also faster to load.
+    pub fn set_hash_cons(&mut self, enabled: bool) {
+        self.hash_cons = enabled;
+    }
 }
 
 impl<J: Jammer> Saver<J> {
@@ -90,8 +118,8 @@ impl<J: Jammer> Saver<J> {
         path: &PathBuf,
         metrics: Option<Arc<NockAppMetrics>>,
     ) -> Result<(Self, Option<C>), CheckpointError> {
-        let path_0 = path.join("0.chkjam");
-        let path_1 = path.join("1.chkjam");
+        let path_0 = path.join(CHECKPOINT_FILES[0]);
+        let path_1 = path.join(CHECKPOINT_FILES[1]);
         let waiters = Vec::new();
 
         // No snapshot to load
@@ -104,6 +132,9 @@ impl<J: Jammer> Saver<J> {
                     save_to_next: WhichSnapshot::Snapshot0,
                     waiters,
                     last_event_num: 0,
+                    chunks: ChunkStore::new(path),
+                    live_chunks: Default::default(),
+                    hash_cons: false,
                     _phantom: std::marker::PhantomData,
                 },
                 None,
@@ -112,42 +143,40 @@ impl<J: Jammer> Saver<J> {
 
         let checkpoint_0 = load_checkpoint_file(&path_0).await;
         let checkpoint_1 = load_checkpoint_file(&path_1).await;
-
-        let (loaded_checkpoint, save_to_next) = match (checkpoint_0, checkpoint_1) {
+        let live_chunks = [
+            checkpoint_0
+                .as_ref()
+                .map(LoadedCheckpoint::chunk_hashes)
+                .unwrap_or_default(),
+            checkpoint_1
+                .as_ref()
+                .map(LoadedCheckpoint::chunk_hashes)
+                .unwrap_or_default(),
+        ];
+        let chunks = ChunkStore::new(path);
+
+        // Newest first, each with the slot to save to next if it is the one loaded
+        let candidates = match (checkpoint_0, checkpoint_1) {
             (Ok(c0), Ok(c1)) => {
                 if c0.event_num() > c1.event_num() {
-                    debug!(
-                        "Loading checkpoint at: {}, checksum: {}",
-                        path_0.display(),
-                        c0.checksum()
-                    );
-                    (c0, WhichSnapshot::Snapshot1)
+                    vec![
+                        (c0, &path_0, WhichSnapshot::Snapshot1),
+                        (c1, &path_1, WhichSnapshot::Snapshot0),
+                    ]
                 } else {
-                    debug!(
-                        "Loading checkpoint at: {}, checksum: {}",
-                        path_1.display(),
-                        c1.checksum()
-                    );
-                    (c1, WhichSnapshot::Snapshot0)
+                    vec![
+                        (c1, &path_1, WhichSnapshot::Snapshot0),
+                        (c0, &path_0, WhichSnapshot::Snapshot1),
+                    ]
                 }
             }
             (Ok(c0), Err(e1)) => {
                 warn!("checkpoint at {} failed to load: {}", path_1.display(), e1);
-                debug!(
-                    "Loading checkpoint at: {}, checksum: {}",
-                    path_0.display(),
-                    c0.checksum()
-                );
-                (c0, WhichSnapshot::Snapshot1)
+                vec![(c0, &path_0, WhichSnapshot::Snapshot1)]
             }
             (Err(e0), Ok(c1)) => {
                 warn!("checkpoint at {} failed to load: {}", path_0.display(), e0);
-                debug!(
-                    "Loading checkpoint at: {}, checksum: {}",
-                    path_1.display(),
-                    c1.checksum()
-                );
-                (c1, WhichSnapshot::Snapshot0)
+                vec![(c1, &path_1, WhichSnapshot::Snapshot0)]
             }
             (Err(e0), Err(e1)) => {
                 error!("checkpoint at {} failed to load: {}", path_0.display(), e0);
@@ -158,8 +187,37 @@ impl<J: Jammer> Saver<J> {
                 ));
             }
         };
-        let last_event_num = loaded_checkpoint.event_num();
-        let saveable = loaded_checkpoint.into_saveable(metrics.clone())?;
+
+        // A manifest can be intact while the chunks it names are not, so fall back to the other
+        // slot if the newer one's chunks are missing or corrupt.
+        let mut candidates = candidates.into_iter().peekable();
+        let (saveable, last_event_num, save_to_next) = loop {
+            let Some((checkpoint, checkpoint_path, save_to_next)) = candidates.next() else {
+                unreachable!("at least one checkpoint loaded");
+            };
+            debug!(
+                "Loading checkpoint at: {}, checksum: {}",
+                checkpoint_path.display(),
+                checkpoint.checksum()
+            );
+            let event_num = checkpoint.event_num();
+            match checkpoint
+                .into_saveable::<J>(&chunks, metrics.clone())
+                .await
+            {
+                Ok(saveable) => break (saveable, event_num, save_to_next),
+                Err(e @ (CheckpointError::MissingChunk(_) | CheckpointError::CorruptChunk(_)))
+                    if candidates.peek().is_some() =>
+                {
+                    warn!(
+                        "checkpoint at {} has unusable chunks, falling back to the older checkpoint: {}",
+                        checkpoint_path.display(),
+                        e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        };
         trace!("After from_jammed_checkpoint");
         let c = C::from_saveable(saveable)?;
         Ok((
@@ -169,6 +227,9 @@ impl<J: Jammer> Saver<J> {
                 save_to_next,
                 waiters,
                 last_event_num,
+                chunks,
+                live_chunks,
+                hash_cons: false,
                 _phantom: std::marker::PhantomData,
             },
             Some(c),
@@ -183,15 +244,44 @@ impl<J: Jammer> Saver<J> {
     ) -> Result<(), CheckpointError> {
         let event_num = checkpoint.event_num();
         trace!("Saving checkpoint at event_num {}", event_num);
-        let saveable = checkpoint.to_saveable();
+        let mut saveable = checkpoint.to_saveable();
         trace!("Converted checkpoint to saveable");
-        let jammed = saveable.to_jammed_checkpoint::<J>(metrics);
-        trace!("Converted saveable to jammed");
+        if self.hash_cons {
+            let hash_cons_start = Instant::now();
+            let shared = saveable.state.hash_cons() + saveable.cold.hash_cons();
+            debug!(
+                "Shared {} repeated subtrees in {:?}",
+                shared,
+                hash_cons_start.elapsed()
+            );
+        }
         let path = self.next_path();
-        jammed.save_to_file(&path).await?;
+        let slot = self.save_to_next.index();
+        match saveable.to_chunked_checkpoint::<J>(metrics.clone()) {
+            Ok((manifest, chunks)) => {
+                trace!("Converted saveable to chunks");
+                let written = self.chunks.write_missing(chunks.iter()).await?;
+                manifest.save_to_file(&path).await?;
+                debug!(
+                    "Wrote {} new chunks of {} for checkpoint",
+                    written,
+                    chunks.len()
+                );
+                self.live_chunks[slot] = manifest.chunk_hashes();
+            }
+            Err(CheckpointError::ChunkTagCollision) => {
+                warn!("Kernel state contains the chunk reference tag, saving a full checkpoint");
+                let jammed = saveable.to_jammed_checkpoint::<J>(metrics);
+                trace!("Converted saveable to jammed");
+                jammed.save_to_file(&path).await?;
+                self.live_chunks[slot].clear();
+            }
+            Err(e) => return Err(e),
+        }
         self.save_to_next = self.save_to_next.next();
-        std::mem::drop(jammed);
         debug!("Saved checkpoint to file: {}", path.display());
+        let live = &self.live_chunks[0] | &self.live_chunks[1];
+        self.chunks.retain(&live).await;
         let mut still_waiting = Vec::new();
         for (waiting_event_num, waiter) in self.waiters.drain(..) {
             if waiting_event_num <= event_num {
@@ -243,6 +333,24 @@ impl SaveableCheckpoint {
         JammedCheckpointV2::new(ker_hash, event_num, cold_jam, state_jam)
     }
 
+    /// Cut the state and cold state into chunks, returning the manifest and every chunk it
+    /// refers to. Fails with [`CheckpointError::ChunkTagCollision`] if either can't be chunked.
+    #[tracing::instrument(skip(self, metrics))]
+    fn to_chunked_checkpoint<J: Jammer>(
+        &self,
+        metrics: Arc<NockAppMetrics>,
+    ) -> Result<(JammedCheckpointV3, Vec<(ChunkHash, Bytes)>), CheckpointError> {
+        let jam_start = Instant::now();
+        let state = chunk_noun::<J>(unsafe { *self.state.root() })?;
+        let cold = chunk_noun::<J>(unsafe { *self.cold.root() })?;
+        metrics.save_jam_time.add_timing(&jam_start.elapsed());
+
+        let manifest = JammedCheckpointV3::new(self.ker_hash, self.event_num, &cold, &state);
+        let mut chunks = state.chunks;
+        chunks.extend(cold.chunks);
+        Ok((manifest, chunks))
+    }
+
     fn from_jammed_checkpoint_v1(
         jammed: JammedCheckpointV1,
         metrics: Option<Arc<NockAppMetrics>>,
@@ -301,6 +409,30 @@ impl SaveableCheckpoint {
             cold: cold_slab,
         })
     }
+
+    async fn from_jammed_checkpoint_v3<J: Jammer>(
+        manifest: JammedCheckpointV3,
+        chunks: &ChunkStore,
+        metrics: Option<Arc<NockAppMetrics>>,
+    ) -> Result<Self, CheckpointError> {
+        let jams = chunks
+            .read(manifest.state_chunks.iter().chain(&manifest.cold_chunks))
+            .await?;
+
+        let cue_start = Instant::now();
+        let state = assemble_noun::<J>(&manifest.state_root, &manifest.state_chunks, &jams)?;
+        let cold = assemble_noun::<J>(&manifest.cold_root, &manifest.cold_chunks, &jams)?;
+        if let Some(metrics) = metrics {
+            metrics.load_cue_time.add_timing(&cue_start.elapsed());
+        }
+
+        Ok(Self {
+            ker_hash: manifest.ker_hash,
+            event_num: manifest.event_num,
+            state: state.coerce_jammer(),
+            cold: cold.coerce_jammer(),
+        })
+    }
 }
 
 impl Checkpoint for SaveableCheckpoint {
@@ -339,6 +471,14 @@ pub enum CheckpointError {
     SwordInterpreterError,
     #[error("Cue error: {0}")]
     CueError(#[from] crate::noun::slab::CueError),
+    #[error("Missing checkpoint chunk {0}")]
+    MissingChunk(String),
+    #[error("Corrupt checkpoint chunk {0}")]
+    CorruptChunk(String),
+    #[error("Malformed checkpoint chunk reference")]
+    MalformedChunkReference,
+    #[error("Noun contains the checkpoint chunk reference tag")]
+    ChunkTagCollision,
     #[error("Loading at version 1 failed: {v1}\\nLoading at version 0 failed: {v0}")]
     VersionsFailed {
         v1: Box<CheckpointError>,
@@ -352,6 +492,15 @@ pub enum CheckpointError {
         v1: Box<CheckpointError>,
         v0: Box<CheckpointError>,
     },
+    #[error(
+        "Loading at version 3 failed: {v3}\\nLoading at version 2 failed: {v2}\\nLoading at version 1 failed: {v1}\\nLoading at version 0 failed: {v0}"
+    )]
+    VersionsFailedV3 {
+        v3: Box<CheckpointError>,
+        v2: Box<CheckpointError>,
+        v1: Box<CheckpointError>,
+        v0: Box<CheckpointError>,
+    },
 }
 
 pub type JammedCheckpoint = JammedCheckpointV2;
@@ -533,7 +682,7 @@ impl JammedCheckpointV2 {
         if envelope.magic_bytes != JAM_MAGIC_BYTES {
             return Err(CheckpointError::InvalidVersion(path_or_memory(path)));
         }
-        if envelope.version != LATEST_SNAPSHOT_VERSION {
+        if envelope.version != SNAPSHOT_VERSION_2 {
             return Err(CheckpointError::InvalidVersion(path_or_memory(path)));
         }
 
@@ -555,6 +704,124 @@ impl JammedCheckpointV2 {
     }
 }
 
+/// A checkpoint saved as chunks: the manifest naming the chunks of the state and cold state,
+/// which live in a [`ChunkStore`] next to it.
+#[derive(Clone, Encode, Decode, PartialEq, Debug)]
+pub struct JammedCheckpointV3 {
+    /// Hash of the boot kernel
+    #[bincode(with_serde)]
+    pub ker_hash: Hash,
+    /// Checksum derived from event_num and the chunk hashes (the entries below)
+    #[bincode(with_serde)]
+    pub checksum: Hash,
+    /// Event number
+    pub event_num: u64,
+    pub cold_root: ChunkHash,
+    /// Every chunk of the cold state, each after the chunks it refers to
+    pub cold_chunks: Vec<ChunkHash>,
+    pub state_root: ChunkHash,
+    /// Every chunk of the kernel state, each after the chunks it refers to
+    pub state_chunks: Vec<ChunkHash>,
+}
+
+impl JammedCheckpointV3 {
+    pub fn new(ker_hash: Hash, event_num: u64, cold: &ChunkedNoun, state: &ChunkedNoun) -> Self {
+        let cold_chunks = cold.hashes();
+        let state_chunks = state.hashes();
+        let checksum = Self::checksum(
+            event_num, &cold.root, &cold_chunks, &state.root, &state_chunks,
+        );
+        Self {
+            ker_hash,
+            checksum,
+            event_num,
+            cold_root: cold.root,
+            cold_chunks,
+            state_root: state.root,
+            state_chunks,
+        }
+    }
+
+    pub fn validate(&self, path: &Path) -> Result<(), CheckpointError> {
+        let checksum = Self::checksum(
+            self.event_num, &self.cold_root, &self.cold_chunks, &self.state_root,
+            &self.state_chunks,
+        );
+        if self.checksum != checksum {
+            Err(CheckpointError::InvalidChecksum(path.to_path_buf()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Every chunk this checkpoint refers to.
+    pub fn chunk_hashes(&self) -> HashSet<ChunkHash> {
+        self.cold_chunks
+            .iter()
+            .chain(&self.state_chunks)
+            .copied()
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub fn encode(&self) -> Result<Vec<u8>, bincode::error::EncodeError> {
+        let payload = encode_to_vec(self, config::standard())?;
+        let envelope = JammedCheckpointV2Envelope {
+            magic_bytes: JAM_MAGIC_BYTES,
+            version: SNAPSHOT_VERSION_3,
+            payload,
+        };
+        encode_to_vec(envelope, config::standard())
+    }
+
+    fn checksum(
+        event_num: u64,
+        cold_root: &ChunkHash,
+        cold_chunks: &[ChunkHash],
+        state_root: &ChunkHash,
+        state_chunks: &[ChunkHash],
+    ) -> Hash {
+        let mut hasher = Hasher::new();
+        hasher.update(&event_num.to_le_bytes());
+        hasher.update(cold_root);
+        hasher.update(&cold_chunks.len().to_le_bytes());
+        for hash in cold_chunks {
+            hasher.update(hash);
+        }
+        hasher.update(state_root);
+        hasher.update(&state_chunks.len().to_le_bytes());
+        for hash in state_chunks {
+            hasher.update(hash);
+        }
+        hasher.finalize()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn load_from_file(path: &Path) -> Result<Self, CheckpointError> {
+        debug!("Loading checkpoint manifest from file: {}", path.display());
+        let bytes = tokio::fs::read(path).await?;
+        let config = bincode::config::standard();
+        let (envelope, _) = bincode::decode_from_slice::<JammedCheckpointV2Envelope, Configuration>(
+            &bytes, config,
+        )?;
+        if envelope.magic_bytes != JAM_MAGIC_BYTES || envelope.version != SNAPSHOT_VERSION_3 {
+            return Err(CheckpointError::InvalidVersion(path.to_path_buf()));
+        }
+        let (checkpoint, _) =
+            bincode::decode_from_slice::<Self, Configuration>(&envelope.payload, config)?;
+        checkpoint.validate(path)?;
+        Ok(checkpoint)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn save_to_file(&self, path: &Path) -> Result<(), CheckpointError> {
+        let bytes = self.encode()?;
+        trace!("Saving checkpoint manifest to file: {}", path.display());
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+}
+
 fn path_or_memory(path: Option<&Path>) -> PathBuf {
     path.map(Path::to_path_buf)
         .unwrap_or_else(|| PathBuf::from("<memory>"))
@@ -562,6 +829,7 @@ fn path_or_memory(path: Option<&Path>) -> PathBuf {
 
 #[derive(Clone, Debug)]
 enum LoadedCheckpoint {
+    V3(JammedCheckpointV3),
     V2(JammedCheckpointV2),
     V1(JammedCheckpointV1),
 }
@@ -569,6 +837,7 @@ enum LoadedCheckpoint {
 impl LoadedCheckpoint {
     fn event_num(&self) -> u64 {
         match self {
+            LoadedCheckpoint::V3(cp) => cp.event_num,
             LoadedCheckpoint::V2(cp) => cp.event_num,
             LoadedCheckpoint::V1(cp) => cp.event_num,
         }
@@ -576,30 +845,97 @@ impl LoadedCheckpoint {
 
     fn checksum(&self) -> Hash {
         match self {
+            LoadedCheckpoint::V3(cp) => cp.checksum,
             LoadedCheckpoint::V2(cp) => cp.checksum,
             LoadedCheckpoint::V1(cp) => cp.checksum,
         }
     }
 
-    fn into_saveable(
+    fn chunk_hashes(&self) -> HashSet<ChunkHash> {
+        match self {
+            LoadedCheckpoint::V3(cp) => cp.chunk_hashes(),
+            LoadedCheckpoint::V2(_) | LoadedCheckpoint::V1(_) => HashSet::new(),
+        }
+    }
+
+    /// Cue the checkpoint's state and cold state.
+    ///
+    /// Only chunked (v3) checkpoints cue in parallel. A v1 or v2 checkpoint is one jam whose
+    /// backreferences can point anywhere earlier in the stream, so it cues on a single thread.
+    /// Saves always write v3, so a legacy checkpoint is only loaded slowly once.
+    async fn into_saveable<J: Jammer>(
         self,
+        chunks: &ChunkStore,
         metrics: Option<Arc<NockAppMetrics>>,
     ) -> Result<SaveableCheckpoint, CheckpointError> {
+        if !matches!(self, LoadedCheckpoint::V3(_)) {
+            info!(
+                "Cueing legacy checkpoint on one thread; the next save writes a chunked checkpoint"
+            );
+        }
         match self {
+            LoadedCheckpoint::V3(cp) => {
+                SaveableCheckpoint::from_jammed_checkpoint_v3::<J>(cp, chunks, metrics).await
+            }
             LoadedCheckpoint::V2(cp) => SaveableCheckpoint::from_jammed_checkpoint_v2(cp, metrics),
             LoadedCheckpoint::V1(cp) => SaveableCheckpoint::from_jammed_checkpoint_v1(cp, metrics),
         }
     }
 }
 
+/// A checkpoint file on disk, read without loading the state it holds.
+#[derive(Debug, Clone)]
+pub struct CheckpointFile {
+    pub path: PathBuf,
+    pub event_num: u64,
+    /// The chunks the checkpoint refers to, empty for checkpoints saved whole
+    pub chunks: HashSet<ChunkHash>,
+}
+
+impl CheckpointFile {
+    /// Read and validate the checkpoint file at `path`.
+    pub async fn read(path: &Path) -> Result<Self, CheckpointError> {
+        let checkpoint = load_checkpoint_file(path).await?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            event_num: checkpoint.event_num(),
+            chunks: checkpoint.chunk_hashes(),
+        })
+    }
+
+    /// The newest valid checkpoint in the checkpoint directory `dir`, if there is one.
+    pub async fn latest(dir: &Path) -> Option<Self> {
+        let mut latest: Option<Self> = None;
+        for name in CHECKPOINT_FILES {
+            let path = dir.join(name);
+            if !path.exists() {
+                continue;
+            }
+            match Self::read(&path).await {
+                Ok(file) if latest.as_ref().is_none_or(|l| l.event_num < file.event_num) => {
+                    latest = Some(file);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("checkpoint at {} failed to load: {}", path.display(), e),
+            }
+        }
+        latest
+    }
+}
+
 async fn load_checkpoint_file(path: &Path) -> Result<LoadedCheckpoint, CheckpointError> {
+    let e_v3 = match JammedCheckpointV3::load_from_file(path).await {
+        Ok(cp) => return Ok(LoadedCheckpoint::V3(cp)),
+        Err(e) => e,
+    };
     match JammedCheckpointV2::load_from_file(path).await {
         Ok(cp) => Ok(LoadedCheckpoint::V2(cp)),
         Err(e_v2) => match JammedCheckpointV1::load_from_file(path).await {
             Ok(cp) => Ok(LoadedCheckpoint::V1(cp)),
             Err(e_v1) => match JammedCheckpointV0::load_from_file(path).await {
                 Ok(cp0) => Ok(LoadedCheckpoint::V2(JammedCheckpoint::from(cp0))),
-                Err(e_v0) => Err(CheckpointError::VersionsFailedV2 {
+                Err(e_v0) => Err(CheckpointError::VersionsFailedV3 {
+                    v3: Box::new(e_v3),
                     v2: Box::new(e_v2),
                     v1: Box::new(e_v1),
                     v0: Box::new(e_v0),
@@ -728,6 +1064,7 @@ mod version_tests {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::noun::slab::slab_equality;
 
     fn legacy_pair_jam(state_value: u64, cold_value: u64) -> JammedNoun {
         let mut slab = NounSlab::<NockJammer>::new();
@@ -796,6 +1133,158 @@ mod version_tests {
         assert_eq!(loaded_state, state_value);
         assert_eq!(loaded_cold, cold_value);
     }
+
+    fn list_slab(len: u64, prepend: Option<u64>) -> NounSlab {
+        let mut slab = NounSlab::<NockJammer>::new();
+        let mut list = D(0);
+        for i in 0..len {
+            list = T(&mut slab, &[D(i), list]);
+        }
+        if let Some(head) = prepend {
+            list = T(&mut slab, &[D(head), list]);
+        }
+        slab.set_root(list);
+        slab
+    }
+
+    fn chunk_files(dir: &Path) -> usize {
+        std::fs::read_dir(dir.join("chunks"))
+            .expect("read chunk dir")
+            .count()
+    }
+
+    #[tokio::test]
+    async fn chunked_checkpoints_only_write_changed_chunks() {
+        let temp = TempDir::new().expect("create temp dir");
+        let path = temp.path().to_path_buf();
+        let metrics = Arc::new(
+            NockAppMetrics::register(gnort::global_metrics_registry()).expect("register metrics"),
+        );
+        let ker_hash = hash(b"chunked");
+        let (mut saver, _) = Saver::<NockJammer>::try_load::<SaveableCheckpoint>(&path, None)
+            .await
+            .expect("set up saver");
+
+        let first = SaveableCheckpoint {
+            ker_hash,
+            event_num: 1,
+            state: list_slab(200_000, None),
+            cold: list_slab(10, None),
+        };
+        saver
+            .save(first, metrics.clone())
+            .await
+            .expect("save first checkpoint");
+        let first_chunks = chunk_files(&path);
+        assert!(first_chunks > 2, "state should be cut into several chunks");
+
+        // Prepending to the list leaves every chunk below the root unchanged.
+        let second = SaveableCheckpoint {
+            ker_hash,
+            event_num: 2,
+            state: list_slab(200_000, Some(7)),
+            cold: list_slab(10, None),
+        };
+        let expected = second.state.clone();
+        saver
+            .save(second, metrics.clone())
+            .await
+            .expect("save second checkpoint");
+        assert_eq!(chunk_files(&path), first_chunks + 1);
+
+        let (_, loaded) = Saver::<NockJammer>::try_load::<SaveableCheckpoint>(&path, None)
+            .await
+            .expect("load checkpoint");
+        let loaded = loaded.expect("expected a checkpoint");
+        assert_eq!(loaded.event_num, 2);
+        assert!(slab_equality(&loaded.state, &expected));
+
+        // Once neither manifest refers to the large state, its chunks are deleted. The small state
+        // and cold state are the same noun, so they share a single chunk.
+        let third = SaveableCheckpoint {
+            ker_hash,
+            event_num: 3,
+            state: list_slab(10, None),
+            cold: list_slab(10, None),
+        };
+        saver
+            .save(third, metrics.clone())
+            .await
+            .expect("save third checkpoint");
+        let fourth = SaveableCheckpoint {
+            ker_hash,
+            event_num: 4,
+            state: list_slab(10, None),
+            cold: list_slab(10, None),
+        };
+        saver
+            .save(fourth, metrics)
+            .await
+            .expect("save fourth checkpoint");
+        assert_eq!(chunk_files(&path), 1);
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_newer_checkpoint_chunks_are_bad() {
+        let temp = TempDir::new().expect("create temp dir");
+        let path = temp.path().to_path_buf();
+        let metrics = Arc::new(
+            NockAppMetrics::register(gnort::global_metrics_registry()).expect("register metrics"),
+        );
+        let ker_hash = hash(b"fallback");
+        let (mut saver, _) = Saver::<NockJammer>::try_load::<SaveableCheckpoint>(&path, None)
+            .await
+            .expect("set up saver");
+        let first = SaveableCheckpoint {
+            ker_hash,
+            event_num: 1,
+            state: list_slab(200_000, None),
+            cold: list_slab(10, None),
+        };
+        let expected = first.state.clone();
+        saver
+            .save(first, metrics.clone())
+            .await
+            .expect("save first checkpoint");
+        let second = SaveableCheckpoint {
+            ker_hash,
+            event_num: 2,
+            state: list_slab(200_000, Some(7)),
+            cold: list_slab(10, None),
+        };
+        saver
+            .save(second, metrics)
+            .await
+            .expect("save second checkpoint");
+
+        let newer = CheckpointFile::read(&path.join(CHECKPOINT_FILES[1]))
+            .await
+            .expect("read newer checkpoint");
+        let older = CheckpointFile::read(&path.join(CHECKPOINT_FILES[0]))
+            .await
+            .expect("read older checkpoint");
+        assert_eq!(newer.event_num, 2);
+        let only_newer: Vec<ChunkHash> = newer.chunks.difference(&older.chunks).copied().collect();
+        assert_eq!(only_newer.len(), 1);
+        let chunk_path = ChunkStore::new(&path).path(&only_newer[0]);
+
+        // A corrupt chunk and a missing chunk both fall back to the older checkpoint.
+        std::fs::write(&chunk_path, b"not a jam").expect("corrupt chunk");
+        let (_, loaded) = Saver::<NockJammer>::try_load::<SaveableCheckpoint>(&path, None)
+            .await
+            .expect("load older checkpoint");
+        let loaded = loaded.expect("expected a checkpoint");
+        assert_eq!(loaded.event_num, 1);
+        assert!(slab_equality(&loaded.state, &expected));
+
+        std::fs::remove_file(&chunk_path).expect("remove chunk");
+        let (saver, loaded) = Saver::<NockJammer>::try_load::<SaveableCheckpoint>(&path, None)
+            .await
+            .expect("load older checkpoint");
+        assert_eq!(loaded.expect("expected a checkpoint").event_num, 1);
+        // The broken slot is the one overwritten next.
+        assert_eq!(saver.next_path(), path.join(CHECKPOINT_FILES[1]));
+    }
 }
 
 /*
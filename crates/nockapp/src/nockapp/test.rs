@@ -45,6 +45,7 @@ pub mod tests {
     use bytes::Bytes;
     use nockvm::jets::util::slot;
     use nockvm::mem::NockStack;
+    use nockvm::meter::{Meter, MeterLimits};
     use nockvm::noun::{Noun, D, T};
     use nockvm::serialization::{cue, jam};
     use nockvm::unifying_equality::unifying_equality;
@@ -215,6 +216,51 @@ pub mod tests {
         assert!(!slab_equality(&checkpoint.state, &state_before_poke.state));
     }
 
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_metered_poke_and_peek_report_usage() {
+        let (_temp, nockapp) = setup_nockapp("test-ker.jam").await;
+        let kernel = nockapp.kernel;
+
+        let mut poke = NounSlab::new();
+        poke.copy_into(D(tas!(b"inc")));
+        let unmetered = kernel
+            .poke_metered(SystemWire.to_wire(), poke.clone())
+            .await
+            .expect("Could not send poke");
+        assert!(unmetered.result.is_ok());
+        assert!(unmetered.usage.is_none());
+
+        kernel
+            .set_meter(Some(Meter::new(MeterLimits::default())))
+            .await
+            .expect("Could not set meter");
+        let metered = kernel
+            .poke_metered(SystemWire.to_wire(), poke)
+            .await
+            .expect("Could not send poke");
+        assert!(metered.result.is_ok());
+        let usage = metered.usage.expect("metered poke should report usage");
+        assert!(usage.steps > 0);
+        assert!(usage.alloc_words > 0);
+
+        let mut peek = NounSlab::new();
+        let mut stack = NockStack::new(NOCK_STACK_SIZE, 0);
+        peek.copy_into(T(&mut stack, &[D(tas!(b"state")), D(0)]));
+        let metered = kernel
+            .peek_metered(peek)
+            .await
+            .expect("Could not send peek");
+        assert!(metered.result.is_ok());
+        assert!(
+            metered
+                .usage
+                .expect("metered peek should report usage")
+                .steps
+                > 0
+        );
+    }
+
     #[tokio::test]
     #[traced_test]
     #[cfg_attr(miri, ignore)]
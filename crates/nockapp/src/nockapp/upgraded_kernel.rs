@@ -0,0 +1,75 @@
+//! The kernel a running app was last switched to with [`Kernel::upgrade`], kept next to the
+//! checkpoints so the app comes back up on it.
+//!
+//! The file holds the hash of the kernel the app was booted with when it was upgraded, followed
+//! by the new kernel's jam. On the next boot the upgraded kernel is used in place of the one the
+//! app is given, as long as that is still the kernel it was upgraded from. An app given any other
+//! kernel was deliberately moved on to it, and the upgrade is discarded.
+//!
+//! [`Kernel::upgrade`]: crate::kernel::form::Kernel::upgrade
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use blake3::Hash;
+
+/// File name of the upgraded kernel in the checkpoints directory
+pub const UPGRADED_KERNEL_FILE: &str = "upgraded-kernel.jam";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpgradedKernel {
+    /// Hash of the kernel the app was booted with when it was upgraded
+    pub base: Hash,
+    /// The kernel it was upgraded to, as a jammed noun
+    pub kernel: Vec<u8>,
+}
+
+impl UpgradedKernel {
+    /// Hash of the upgraded kernel, as recorded in checkpoints and logged events
+    pub fn ker_hash(&self) -> Hash {
+        blake3::hash(&self.kernel)
+    }
+
+    /// Read the upgraded kernel at `path`, if the app has been upgraded.
+    pub fn read(path: &Path) -> std::io::Result<Option<Self>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let Some((base, kernel)) = bytes.split_first_chunk::<32>() else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{} is too short", path.display()),
+            ));
+        };
+        Ok(Some(Self {
+            base: Hash::from_bytes(*base),
+            kernel: kernel.to_vec(),
+        }))
+    }
+
+    /// Write the upgraded kernel to `path`, replacing any earlier one only once it is on disk.
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let tmp_path = path.with_extension("jam.tmp");
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(self.base.as_bytes())?;
+        tmp.write_all(&self.kernel)?;
+        tmp.sync_all()?;
+        drop(tmp);
+        fs::rename(&tmp_path, path)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Remove the upgraded kernel at `path`, if there is one.
+    pub fn remove(path: &Path) -> std::io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
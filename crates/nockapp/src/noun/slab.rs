@@ -54,6 +54,13 @@ impl<J> NounSlab<J> {
         }
     }
 
+    /// Take over `other`'s memory without copying, so nouns allocated in it stay valid for as
+    /// long as this slab does. Returns `other`'s root.
+    pub fn absorb<I>(&mut self, mut other: NounSlab<I>) -> Noun {
+        self.slabs.append(&mut other.slabs);
+        other.root
+    }
+
     unsafe fn raw_alloc(new_layout: Layout) -> *mut u8 {
         if new_layout.size() == 0 {
             std::alloc::handle_alloc_error(new_layout);
@@ -320,6 +327,62 @@ impl<J> NounSlab<J> {
         self.root
     }
 
+    /// Hash-cons the root noun, so that every repeated subtree is stored once.
+    ///
+    /// Jam only back-references nouns that share an allocation, so equal subtrees built separately
+    /// are otherwise written out in full each time. Returns how many cells and indirect atoms were
+    /// replaced by an earlier copy.
+    pub fn hash_cons(&mut self) -> usize {
+        enum Visit {
+            Noun(Noun, *mut Noun),
+            Cell(Noun, u64, *mut Noun),
+        }
+
+        let mut canonical: NounMap<Noun> = NounMap::new();
+        let mut visited: IntMap<u64, Noun> = IntMap::new();
+        let mut shared = 0;
+        let mut stack = vec![Visit::Noun(self.root, std::ptr::addr_of_mut!(self.root))];
+        while let Some(visit) = stack.pop() {
+            let (noun, ptr, dest) = match visit {
+                Visit::Noun(noun, dest) => {
+                    let Ok(allocated) = noun.as_allocated() else {
+                        continue;
+                    };
+                    let ptr = unsafe { allocated.to_raw_pointer() } as u64;
+                    if let Some(seen) = visited.get(ptr) {
+                        set_if_changed(dest, *seen);
+                        continue;
+                    }
+                    if let Ok(cell) = noun.as_cell() {
+                        stack.push(Visit::Cell(noun, ptr, dest));
+                        unsafe {
+                            stack.push(Visit::Noun(cell.tail(), cell.tail_as_mut()));
+                            stack.push(Visit::Noun(cell.head(), cell.head_as_mut()));
+                        }
+                        continue;
+                    }
+                    (noun, ptr, dest)
+                }
+                // Both children are canonical by now, so comparing against an earlier equal cell
+                // stops at the first level.
+                Visit::Cell(noun, ptr, dest) => (noun, ptr, dest),
+            };
+            let found = match canonical.get(noun) {
+                Some(found) => {
+                    shared += 1;
+                    *found
+                }
+                None => {
+                    canonical.insert(noun, noun);
+                    noun
+                }
+            };
+            visited.insert(ptr, found);
+            set_if_changed(dest, found);
+        }
+        shared
+    }
+
     /// Copy the root noun from this slab into the given NockStack, only leaving references into the PMA
     ///
     /// Note that this consumes the slab, the slab will be freed after and the root noun returned
@@ -630,6 +693,15 @@ pub fn slab_noun_equality(a: &Noun, b: &Noun) -> bool {
     }
 }
 
+/// Overwrite `*dest` with an equal noun, leaving memory untouched if it already is that noun.
+fn set_if_changed(dest: *mut Noun, noun: Noun) {
+    unsafe {
+        if !(*dest).raw_equals(&noun) {
+            *dest = noun;
+        }
+    }
+}
+
 fn slab_mug(a: Noun) -> u32 {
     let mut stack = vec![a];
     while let Some(noun) = stack.pop() {
@@ -944,6 +1016,36 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_hash_cons_shares_equal_subtrees() {
+        let mut slab: NounSlab = NounSlab::new();
+        let big_exp = ubig!(0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF);
+        let big = Atom::from_ubig(&mut slab, &big_exp).as_noun();
+        let notes: Vec<Noun> = (0..8)
+            .map(|_| {
+                let big = Atom::from_ubig(&mut slab, &big_exp).as_noun();
+                T(&mut slab, &[D(tas!(b"note")), big, D(1), D(2)])
+            })
+            .collect();
+        let list = T(&mut slab, &[notes[0], notes[1], notes[2], notes[3], big]);
+        let root = T(&mut slab, &[list, notes[4], notes[5], notes[6], notes[7]]);
+        slab.set_root(root);
+        let expected = slab.clone();
+        let full = slab.jam();
+
+        // Seven repeated notes of three cells and an atom each, plus the loose atom
+        assert_eq!(slab.hash_cons(), 29);
+        assert!(slab_equality(&slab, &expected));
+        let shared = slab.jam();
+        assert!(shared.len() < full.len());
+        assert_eq!(slab.hash_cons(), 0);
+
+        let mut cued: NounSlab = NounSlab::new();
+        let cued_root = cued.cue_into(shared).expect("Cue should succeed");
+        assert!(slab_noun_equality(&cued_root, unsafe { expected.root() }));
+    }
+
     #[test]
     fn test_complex_noun() {
         let mut slab: NounSlab = NounSlab::new();
@@ -50,6 +50,10 @@ pub enum CrownError<T = ExternalError> {
     TankError,
     #[error("play bail")]
     PlayBail,
+    #[error("out of memory with a {0} word stack")]
+    OutOfMemory(usize),
+    #[error("event log error: {0}")]
+    EventLog(#[from] crate::nockapp::event_log::EventLogError),
     #[error("queue error")]
     QueueRecv(yaque::TryRecvError),
     #[error("save error: {0}")]
@@ -1,6 +1,7 @@
 pub mod bytes;
 pub mod error;
 pub mod scry;
+pub mod sigv4;
 pub mod slogger;
 
 use std::ptr::copy_nonoverlapping;
@@ -182,5 +183,6 @@ pub fn create_context(
         trace_info,
         test_jets,
         running_status: cancel,
+        meter: None,
     }
 }
@@ -252,6 +252,9 @@ async fn test_specific_block(addr: &str, height: u64) -> Result<(), Box<dyn Erro
                                     Some(get_transaction_details_response::Result::Pending(_)) => {
                                         println!("    Status: Pending");
                                     }
+                                    Some(get_transaction_details_response::Result::Pruned(_)) => {
+                                        println!("    Status: Pruned");
+                                    }
                                     Some(get_transaction_details_response::Result::Error(e)) => {
                                         println!("    ERROR in response: {}", e.message);
                                     }
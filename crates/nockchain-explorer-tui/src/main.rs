@@ -39,7 +39,7 @@ use nockapp_grpc_proto::pb::public::v2::{
     TransactionBlockData, TransactionDetails as RpcTransactionDetails,
 };
 use nockchain_math::belt::Belt;
-use nockchain_types::tx_engine::common::Hash as Tip5Hash;
+use nockchain_types::tx_engine::common::{Hash as Tip5Hash, NICKS_PER_NOCK};
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
@@ -91,7 +91,6 @@ const PAGE_JUMP: usize = 20;
 const EMPTY_CACHE_BACKOFF: Duration = Duration::from_secs(30);
 const ERROR_REFRESH_BACKOFF: Duration = Duration::from_secs(5);
 const WALLET_INDEX_CHUNK: usize = 64;
-const NICKS_PER_NOCK: u64 = 65_536;
 const SPINNER_FRAMES: [&str; 4] = ["◴", "◷", "◶", "◵"];
 const SPINNER_COLORS: [Color; 6] = [
     Color::Red,
@@ -185,6 +184,8 @@ enum TxSearchResult {
 enum TxDetailStatus {
     Confirmed(RpcTransactionDetails),
     Pending,
+    /// Confirmed at this height, but the node has pruned the block's transactions
+    Pruned(u64),
     NotFound,
     Error(String),
 }
@@ -825,6 +826,9 @@ impl App {
                 TxDetailStatus::Confirmed(details)
             }
             Some(get_transaction_details_response::Result::Pending(_)) => TxDetailStatus::Pending,
+            Some(get_transaction_details_response::Result::Pruned(pruned)) => {
+                TxDetailStatus::Pruned(pruned.block.map_or(0, |block| block.height))
+            }
             Some(get_transaction_details_response::Result::Error(err)) => {
                 if err.message.contains("not found") {
                     TxDetailStatus::NotFound
@@ -2272,6 +2276,13 @@ fn render_block_info_pane(
 
     // Coinbase section (if we have full details)
     if let Some(details) = full_details {
+        if details.pruned {
+            lines.push(Line::from(vec![
+                Span::styled("  Body        ", label_style),
+                Span::styled("Pruned by this node", Style::default().fg(Color::Yellow)),
+            ]));
+        }
+
         if let Some(ref coinbase) = details.coinbase {
             lines.push(Line::from(""));
             lines.push(Line::from(vec![Span::styled(
@@ -2551,6 +2562,18 @@ fn render_transaction_details(
             "Pending (not yet included in a block)",
             Color::Yellow,
         ),
+        TxDetailStatus::Pruned(height) => render_tx_status_message(
+            f,
+            area,
+            tx_state,
+            block_idx,
+            tx_idx,
+            &format!(
+                "Confirmed in block {}, but this node has pruned its transactions",
+                height
+            ),
+            Color::Yellow,
+        ),
         TxDetailStatus::NotFound => render_tx_status_message(
             f,
             area,
@@ -4014,6 +4037,12 @@ async fn wallet_index_worker(
                             message: "Transaction pending confirmation".into(),
                         });
                     }
+                    Some(get_transaction_details_response::Result::Pruned(_)) => {
+                        let _ = result_tx.send(WalletWorkerResult::Error {
+                            tx_ids: vec![task.tx_id],
+                            message: "Transaction pruned by the node".into(),
+                        });
+                    }
                     Some(get_transaction_details_response::Result::Error(err)) => {
                         let _ = result_tx.send(WalletWorkerResult::Error {
                             tx_ids: vec![task.tx_id],
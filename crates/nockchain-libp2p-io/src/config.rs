@@ -4,6 +4,8 @@ use std::time::Duration;
 use config::{Config, ConfigError, Environment};
 use serde::Deserialize;
 
+use crate::mempool::MempoolPolicy;
+
 // Kademlia constants
 /** How often we should run a kademlia bootstrap to keep our peer table fresh */
 const KADEMLIA_BOOTSTRAP_INTERVAL: Duration = Duration::from_secs(300);
@@ -70,6 +72,13 @@ const POKE_TIMEOUT_SECS: u64 = 180;
 // Default max failed pings before closing connection
 const FAILED_PINGS_BEFORE_CLOSE: u64 = 4;
 
+// Mempool policy defaults
+const MEMPOOL_MAX_TXS: usize = 10_000;
+const MEMPOOL_MAX_TX_BYTES: usize = 1 << 20;
+const MEMPOOL_MAX_TXS_PER_PEER: usize = 1_000;
+/** How often the driver's view of the mempool is reconciled with the kernel */
+const MEMPOOL_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Configuration struct that allows overriding default constants from environment variables
 #[derive(Debug, Deserialize, Clone)]
 pub struct LibP2PConfig {
@@ -179,6 +188,31 @@ pub struct LibP2PConfig {
     /// Number of failed pings before closing connection
     #[serde(default = "default_failed_pings_before_close")]
     pub failed_pings_before_close: u64,
+
+    /// Maximum number of transactions heard from peers to keep in the mempool
+    #[serde(default = "default_mempool_max_txs")]
+    pub mempool_max_txs: usize,
+
+    /// Largest transaction accepted from peers (jammed bytes)
+    #[serde(default = "default_mempool_max_tx_bytes")]
+    pub mempool_max_tx_bytes: usize,
+
+    /// Smallest fee rate accepted from peers (nicks per KiB)
+    #[serde(default)]
+    pub mempool_min_fee_rate: u64,
+
+    /// Maximum number of mempool transactions first heard from a single peer
+    #[serde(default = "default_mempool_max_txs_per_peer")]
+    pub mempool_max_txs_per_peer: usize,
+
+    /// Fee rate increase, in percent, a transaction needs to replace the mempool
+    /// transactions it conflicts with. Conflicting transactions are refused if unset.
+    #[serde(default)]
+    pub mempool_replacement_fee_bump_percent: Option<u64>,
+
+    /// How often the mempool view is reconciled with the kernel (seconds)
+    #[serde(default = "default_mempool_sync_interval_secs")]
+    pub mempool_sync_interval_secs: u64,
 }
 
 // Default value functions
@@ -260,6 +294,22 @@ fn default_failed_pings_before_close() -> u64 {
     FAILED_PINGS_BEFORE_CLOSE // Number of failed pings before closing connection
 }
 
+fn default_mempool_max_txs() -> usize {
+    MEMPOOL_MAX_TXS
+}
+
+fn default_mempool_max_tx_bytes() -> usize {
+    MEMPOOL_MAX_TX_BYTES
+}
+
+fn default_mempool_max_txs_per_peer() -> usize {
+    MEMPOOL_MAX_TXS_PER_PEER
+}
+
+fn default_mempool_sync_interval_secs() -> u64 {
+    MEMPOOL_SYNC_INTERVAL.as_secs()
+}
+
 // Do _not_ use this default implementation in production code. It's just a fallback.
 // Use from_env() to load from environment variables with sensible defaults.
 impl Default for LibP2PConfig {
@@ -288,6 +338,12 @@ impl Default for LibP2PConfig {
             seen_tx_clear_interval: default_seen_tx_clear_interval(),
             poke_timeout_secs: default_poke_timeout_secs(),
             failed_pings_before_close: default_failed_pings_before_close(),
+            mempool_max_txs: default_mempool_max_txs(),
+            mempool_max_tx_bytes: default_mempool_max_tx_bytes(),
+            mempool_min_fee_rate: 0,
+            mempool_max_txs_per_peer: default_mempool_max_txs_per_peer(),
+            mempool_replacement_fee_bump_percent: None,
+            mempool_sync_interval_secs: default_mempool_sync_interval_secs(),
         }
     }
 }
@@ -401,4 +457,20 @@ impl LibP2PConfig {
     pub fn failed_pings_before_close(&self) -> u64 {
         self.failed_pings_before_close
     }
+
+    /// Initial mempool policy, which can be changed at runtime through a
+    /// [`crate::mempool::MempoolAdmin`]
+    pub fn mempool_policy(&self) -> MempoolPolicy {
+        MempoolPolicy {
+            max_txs: self.mempool_max_txs,
+            max_tx_bytes: self.mempool_max_tx_bytes,
+            min_fee_rate: self.mempool_min_fee_rate,
+            max_txs_per_peer: self.mempool_max_txs_per_peer,
+            replacement_fee_bump_percent: self.mempool_replacement_fee_bump_percent,
+        }
+    }
+
+    pub fn mempool_sync_interval(&self) -> Duration {
+        Duration::from_secs(self.mempool_sync_interval_secs)
+    }
 }
@@ -40,10 +40,12 @@ use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::behaviour::{NockchainBehaviour, NockchainEvent};
 use crate::config::LibP2PConfig;
+use crate::mempool::{self, MempoolAdminReceiver};
 use crate::messages::{NockchainDataRequest, NockchainFact, NockchainRequest, NockchainResponse};
 use crate::metrics::NockchainP2PMetrics;
 use crate::p2p_state::{CacheResponse, P2PState};
 use crate::p2p_util::{log_fail2ban_ipv4, log_fail2ban_ipv6, MultiaddrExt, PeerIdExt};
+use crate::peer_admin::{self, PeerAdminReceiver};
 #[cfg(test)]
 use crate::tip5_util::tip5_hash_to_base58;
 use crate::tip5_util::tip5_hash_to_base58_stack;
@@ -145,7 +147,9 @@ pub enum SwarmAction {
     },
 }
 
-#[instrument(skip(keypair, bind, allowed, limits, memory_limits, equix_builder))]
+#[instrument(skip(
+    keypair, bind, allowed, limits, memory_limits, equix_builder, peer_admin, mempool_admin
+))]
 pub fn make_libp2p_driver(
     keypair: Keypair,
     bind: Vec<Multiaddr>,
@@ -158,6 +162,8 @@ pub fn make_libp2p_driver(
     fast_sync: bool,
     equix_builder: equix::EquiXBuilder,
     chain_interval: Duration,
+    mut peer_admin: Option<PeerAdminReceiver>,
+    mut mempool_admin: Option<MempoolAdminReceiver>,
     init_complete_tx: Option<tokio::sync::oneshot::Sender<()>>,
 ) -> IODriverFn {
     let initial_peers = Vec::from(initial_peers);
@@ -182,6 +188,8 @@ pub fn make_libp2p_driver(
             let min_peers = libp2p_config.min_peers();
             let poke_timeout = libp2p_config.poke_timeout();
             let failed_pings_before_close = libp2p_config.failed_pings_before_close();
+            let mempool_policy = libp2p_config.mempool_policy();
+            let mempool_sync_interval = libp2p_config.mempool_sync_interval();
             let mut swarm =
                 match start_swarm(libp2p_config, keypair, bind, allowed, limits, memory_limits) {
                     Ok(swarm) => swarm,
@@ -204,6 +212,7 @@ pub fn make_libp2p_driver(
                 metrics.clone(),
                 seen_tx_clear_interval,
             )));
+            driver_state.lock().await.mempool.set_policy(mempool_policy);
             let mut kad_bootstrap = tokio::time::interval(kademlia_bootstrap_interval);
             kad_bootstrap.set_missed_tick_behavior(MissedTickBehavior::Skip);
             let mut force_peer_dial = tokio::time::interval(force_peer_dial_interval);
@@ -214,12 +223,16 @@ pub fn make_libp2p_driver(
             reset_elders_debounce.set_missed_tick_behavior(MissedTickBehavior::Skip);
             let mut nockchain_timer = tokio::time::interval(chain_interval);
             nockchain_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let mut mempool_sync = tokio::time::interval(mempool_sync_interval);
+            mempool_sync.set_missed_tick_behavior(MissedTickBehavior::Skip);
             let nockchain_timer_mutex = Arc::new(Mutex::new(()));
             let (traffic_handle, effect_handle) = handle.dup();
             let traffic_cop =
                 traffic_cop::TrafficCop::new(traffic_handle, &mut join_set, poke_timeout);
 
             let mut initial_peer_retries_remaining = initial_peer_retries;
+            // Peers added through the peer admin, redialed with the force peers
+            let mut manual_peers: Vec<Multiaddr> = Vec::new();
             dial_peers(&mut swarm, &initial_peers)?;
             if let Some(tx) = init_complete_tx {
                 let _ = tx.send(());
@@ -271,6 +284,7 @@ pub fn make_libp2p_driver(
                             },
                             SwarmEvent::Behaviour(NockchainEvent::Identify(Received { connection_id: _, peer_id, info })) => {
                                 trace!("SEvent: identify_received");
+                                driver_state.lock().await.identified(peer_id, &info);
                                 identify_received(&mut swarm, peer_id, info)?;
                             },
                             SwarmEvent::ConnectionEstablished { connection_id, peer_id, endpoint, .. } => {
@@ -406,6 +420,18 @@ pub fn make_libp2p_driver(
                             },
                         }
                     },
+                    Some(request) = peer_admin::next_request(&mut peer_admin) => {
+                        peer_admin::handle_request(&mut swarm, &driver_state, &mut manual_peers, request).await;
+                    },
+                    Some(request) = mempool::next_request(&mut mempool_admin) => {
+                        let evicted = mempool::handle_request(&driver_state, &metrics, request).await;
+                        if !evicted.is_empty() {
+                            join_set.spawn("mempool_evict".to_string(), mempool::drop_txs(traffic_cop.clone(), evicted));
+                        }
+                    },
+                    _ = mempool_sync.tick() => {
+                        join_set.spawn("mempool_sync".to_string(), mempool::sync(traffic_cop.clone(), driver_state.clone(), metrics.clone()));
+                    },
                     _ = kad_bootstrap.tick() => {
                         // If we don't have any peers, we should retry dialing our initial peers
                         if let Err(NoKnownPeers())= swarm.behaviour_mut().kad.bootstrap() {
@@ -421,6 +447,7 @@ pub fn make_libp2p_driver(
                     _ = force_peer_dial.tick() => {
                         debug!("Force dialing peers");
                         dial_peers(&mut swarm, &force_peers)?;
+                        dial_peers(&mut swarm, &manual_peers)?;
                     },
                     _ = reset_request_counts.tick() => {
                         trace!("Resetting request counts");
@@ -971,6 +998,7 @@ async fn handle_request_response(
                 }
                 NockchainRequest::Gossip { message } => {
                     trace!("handle_request_response: Gossip received");
+                    let message_len = message.len();
                     let message_bytes = Bytes::from(message.to_vec());
                     let request_noun = request_slab.cue_into(message_bytes)?;
                     request_slab.set_root(request_noun);
@@ -993,6 +1021,24 @@ async fn handle_request_response(
                     let poke_kernel = tokio::task::spawn(async move {
                         let mut request_slab = request_slab;
                         let gossip = NockchainFact::from_noun_slab(&mut request_slab)?;
+                        let mut heard_tx = None;
+                        if let NockchainFact::HeardTx(ref id, _) = gossip {
+                            let raw_tx = unsafe { request_slab.root() }.as_cell()?.tail();
+                            match mempool::check_heard_tx(
+                                &driver_state, &metrics, peer, id, &raw_tx, message_len,
+                            )
+                            .await
+                            {
+                                Err(_) => return Ok(()),
+                                Ok(Some(heard)) if heard.replaces() => {
+                                    return mempool::replace_txs(
+                                        &traffic, &driver_state, &metrics, heard, &raw_tx,
+                                    )
+                                    .await;
+                                }
+                                Ok(heard) => heard_tx = heard,
+                            }
+                        }
                         let state_arc = driver_state.clone();
                         let metrics_arc = metrics.clone();
                         let enable_fut: Pin<Box<dyn Future<Output = bool> + Send>> = match gossip {
@@ -1063,6 +1109,12 @@ async fn handle_request_response(
                             }
                             _ => {}
                         }
+                        if let Some(heard) = heard_tx {
+                            if let Ok(PokeResult::Ack) = poke_result {
+                                mempool::confirm_heard_tx(&traffic, &driver_state, &metrics, heard)
+                                    .await?;
+                            }
+                        }
                         match poke_result {
                             Ok(PokeResult::Ack) => match gossip {
                                 NockchainFact::HeardBlock(..) => {
@@ -1142,6 +1194,7 @@ async fn handle_request_response(
         Response { response, .. } => match response {
             NockchainResponse::Result { message } => {
                 trace!("handle_request_response: Response result received");
+                let message_len = message.len();
                 let mut response_slab = NounSlab::new();
                 let message_bytes = Bytes::from(message.to_vec());
                 let response_noun = response_slab.cue_into(message_bytes)?;
@@ -1153,6 +1206,24 @@ async fn handle_request_response(
                 );
 
                 let response = NockchainFact::from_noun_slab(&mut response_slab)?;
+                let mut heard_tx = None;
+                if let NockchainFact::HeardTx(ref id, _) = response {
+                    let raw_tx = unsafe { response_slab.root() }.as_cell()?.tail();
+                    match mempool::check_heard_tx(
+                        &driver_state, &metrics, peer, id, &raw_tx, message_len,
+                    )
+                    .await
+                    {
+                        Err(_) => return Ok(()),
+                        Ok(Some(heard)) if heard.replaces() => {
+                            return mempool::replace_txs(
+                                &traffic, &driver_state, &metrics, heard, &raw_tx,
+                            )
+                            .await;
+                        }
+                        Ok(heard) => heard_tx = heard,
+                    }
+                }
                 let response_cell = unsafe { response_slab.root().as_cell() }?;
                 let state_arc = driver_state.clone();
                 let metrics_arc = metrics.clone();
@@ -1227,6 +1298,11 @@ async fn handle_request_response(
                     metrics.heard_elders_poke_time.add_timing(&elapsed);
                 }
 
+                if let Some(heard) = heard_tx {
+                    if let Ok(PokeResult::Ack) = poke_result {
+                        mempool::confirm_heard_tx(&traffic, &driver_state, &metrics, heard).await?;
+                    }
+                }
                 match poke_result {
                     Ok(PokeResult::Ack) => match response {
                         NockchainFact::HeardBlock(..) => {
@@ -1524,6 +1600,12 @@ fn record_crown_error_metric(error: &CrownError<ExternalError>, metrics: &Nockch
         CrownError::PlayBail => {
             metrics.requests_crown_error_play_bail.increment();
         }
+        CrownError::OutOfMemory(_) => {
+            metrics.requests_crown_error_out_of_memory.increment();
+        }
+        CrownError::EventLog(_) => {
+            metrics.requests_crown_error_event_log.increment();
+        }
         CrownError::QueueRecv(_) => {
             metrics.requests_crown_error_queue_recv.increment();
         }
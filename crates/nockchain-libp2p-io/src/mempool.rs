@@ -0,0 +1,789 @@
+//! Admission policy for transactions heard from peers.
+//!
+//! The mempool itself lives in the kernel. The driver keeps a view of the transactions it has
+//! let in from the network, and checks each newly heard transaction against a
+//! [`MempoolPolicy`] before poking it. Nothing is dropped until the kernel has accepted the new
+//! transaction: transactions that lose their place to a better paying one are then dropped with
+//! `%drop-txs`, and a replacement is poked as `%replace-txs`, which drops the transactions it
+//! conflicts with only if it validates.
+//! Transactions the kernel has since included in a block or discarded are pruned from the view
+//! by a periodic peek of `/excluded-txs`.
+//!
+//! Transactions submitted locally never pass through the driver and are not counted.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use libp2p::PeerId;
+use nockapp::driver::PokeResult;
+use nockapp::noun::slab::NounSlab;
+use nockapp::wire::{Wire, WireRepr};
+use nockapp::{AtomExt, NockAppError};
+use nockchain_math::structs::HoonMapIter;
+use nockchain_types::tx_engine::{common, v0, v1};
+use nockvm::noun::{Atom, Noun, D, T, YES};
+use nockvm_macros::tas;
+use noun_serde::NounDecode;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::metrics::NockchainP2PMetrics;
+use crate::p2p_state::P2PState;
+use crate::tip5_util::tip5_hash_to_base58_stack;
+use crate::traffic_cop::TrafficCop;
+
+/// Requests waiting for the swarm loop
+const MEMPOOL_ADMIN_QUEUE: usize = 16;
+
+/// Limits on the transactions the node accepts from peers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MempoolPolicy {
+    /// Most transactions heard from peers kept at once. When full, a new transaction evicts the
+    /// lowest fee rate transactions if it pays a strictly higher rate.
+    pub max_txs: usize,
+    /// Largest transaction accepted, in jammed bytes
+    pub max_tx_bytes: usize,
+    /// Smallest fee rate accepted, in nicks per KiB
+    pub min_fee_rate: u64,
+    /// Most transactions kept that were first heard from any one peer
+    pub max_txs_per_peer: usize,
+    /// Fee rate increase, in percent over the highest paying conflicting transaction, needed to
+    /// replace the transactions that spend the same inputs. `None` refuses all conflicts.
+    pub replacement_fee_bump_percent: Option<u64>,
+}
+
+/// Why a transaction was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    TooLarge,
+    FeeRateTooLow,
+    PeerCapReached,
+    MempoolFull,
+    Conflict,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Rejection::TooLarge => "transaction too large",
+            Rejection::FeeRateTooLow => "fee rate below minimum",
+            Rejection::PeerCapReached => "peer has too many transactions in the mempool",
+            Rejection::MempoolFull => "mempool full of higher fee rate transactions",
+            Rejection::Conflict => "conflicts with a mempool transaction",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// What the policy needs to know about a transaction.
+#[derive(Debug, Clone)]
+pub(crate) struct TxSummary {
+    pub id: String,
+    pub fee: u64,
+    pub bytes: usize,
+    /// First and last hashes of the names of the notes spent
+    pub inputs: Vec<(common::Hash, common::Hash)>,
+}
+
+impl TxSummary {
+    /// Summarize a v0 or v1 `raw-tx` noun that jams to `bytes` bytes.
+    pub(crate) fn from_raw_tx(id: String, raw_tx: &Noun, bytes: usize) -> Option<Self> {
+        let (fee, names) = if let Ok(raw_tx) = v1::RawTx::from_noun(raw_tx) {
+            let fee = raw_tx
+                .spends
+                .0
+                .iter()
+                .map(|(_, spend)| match spend {
+                    v1::Spend::Legacy(spend) => spend.fee.0 as u64,
+                    v1::Spend::Witness(spend) => spend.fee.0 as u64,
+                })
+                .sum();
+            let names = raw_tx.spends.0.into_iter().map(|(name, _)| name).collect();
+            (fee, names)
+        } else {
+            let raw_tx = v0::RawTx::from_noun(raw_tx).ok()?;
+            let names: Vec<common::Name> =
+                raw_tx.inputs.0.into_iter().map(|(name, _)| name).collect();
+            (raw_tx.total_fees.0 as u64, names)
+        };
+        Some(Self {
+            id,
+            fee,
+            bytes,
+            inputs: names
+                .into_iter()
+                .map(|name| (name.first, name.last))
+                .collect(),
+        })
+    }
+
+    fn fee_rate(&self) -> u64 {
+        fee_rate(self.fee, self.bytes)
+    }
+}
+
+fn fee_rate(fee: u64, bytes: usize) -> u64 {
+    fee.saturating_mul(1024) / bytes.max(1) as u64
+}
+
+/// A transaction let in by the policy.
+#[derive(Debug, Clone)]
+struct PendingTx {
+    fee_rate: u64,
+    peer: Option<PeerId>,
+    inputs: Vec<(common::Hash, common::Hash)>,
+}
+
+/// A transaction let in, and the transactions it displaced.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Admission {
+    /// Conflicting transactions it outbid
+    pub replaced: Vec<String>,
+    /// Lower fee rate transactions dropped to make room
+    pub evicted: Vec<String>,
+}
+
+/// The driver's view of the transactions it has let into the kernel's mempool.
+#[derive(Debug)]
+pub(crate) struct Mempool {
+    policy: MempoolPolicy,
+    txs: HashMap<String, PendingTx>,
+    spent_by: HashMap<(common::Hash, common::Hash), String>,
+    per_peer: HashMap<PeerId, usize>,
+}
+
+impl Mempool {
+    pub(crate) fn new(policy: MempoolPolicy) -> Self {
+        Self {
+            policy,
+            txs: HashMap::new(),
+            spent_by: HashMap::new(),
+            per_peer: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> &MempoolPolicy {
+        &self.policy
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    /// Replace the policy, returning the transactions evicted to fit a smaller `max_txs`.
+    pub(crate) fn set_policy(&mut self, policy: MempoolPolicy) -> Vec<String> {
+        self.policy = policy;
+        let excess = self.txs.len().saturating_sub(self.policy.max_txs);
+        let evicted: Vec<String> = self
+            .lowest_fee_rates(&BTreeSet::new())
+            .into_iter()
+            .take(excess)
+            .map(|(_, id)| id)
+            .collect();
+        for id in &evicted {
+            self.remove(id);
+        }
+        evicted
+    }
+
+    /// Check `tx`, heard from `peer`, against the policy, and track it if it is let in.
+    pub(crate) fn admit(
+        &mut self,
+        tx: TxSummary,
+        peer: Option<PeerId>,
+    ) -> Result<Admission, Rejection> {
+        if self.txs.contains_key(&tx.id) {
+            return Ok(Admission::default());
+        }
+        let admission = self.check(&tx, peer)?;
+        for id in admission.replaced.iter().chain(&admission.evicted) {
+            self.remove(id);
+        }
+        let fee_rate = tx.fee_rate();
+        self.insert(tx, fee_rate, peer);
+        Ok(admission)
+    }
+
+    /// Check `tx`, heard from `peer`, against the policy without tracking it.
+    pub(crate) fn check(
+        &self,
+        tx: &TxSummary,
+        peer: Option<PeerId>,
+    ) -> Result<Admission, Rejection> {
+        if tx.bytes > self.policy.max_tx_bytes {
+            return Err(Rejection::TooLarge);
+        }
+        let fee_rate = tx.fee_rate();
+        if fee_rate < self.policy.min_fee_rate {
+            return Err(Rejection::FeeRateTooLow);
+        }
+        if let Some(peer) = peer {
+            if self.per_peer.get(&peer).copied().unwrap_or(0) >= self.policy.max_txs_per_peer {
+                return Err(Rejection::PeerCapReached);
+            }
+        }
+
+        let conflicts: BTreeSet<String> = tx
+            .inputs
+            .iter()
+            .filter_map(|input| self.spent_by.get(input).cloned())
+            .collect();
+        if !conflicts.is_empty() {
+            let Some(bump) = self.policy.replacement_fee_bump_percent else {
+                return Err(Rejection::Conflict);
+            };
+            let highest = conflicts
+                .iter()
+                .map(|id| self.txs[id].fee_rate)
+                .max()
+                .unwrap_or(0);
+            if fee_rate < highest.saturating_mul(100 + bump) / 100 {
+                return Err(Rejection::Conflict);
+            }
+        }
+
+        let excess = (self.txs.len() - conflicts.len() + 1).saturating_sub(self.policy.max_txs);
+        let mut evicted = Vec::with_capacity(excess);
+        for (lowest, id) in self.lowest_fee_rates(&conflicts).into_iter().take(excess) {
+            if lowest >= fee_rate {
+                return Err(Rejection::MempoolFull);
+            }
+            evicted.push(id);
+        }
+        if evicted.len() < excess {
+            return Err(Rejection::MempoolFull);
+        }
+
+        Ok(Admission {
+            replaced: conflicts.into_iter().collect(),
+            evicted,
+        })
+    }
+
+    /// Forget every transaction not in `live`, the kernel's unconfirmed transactions.
+    pub(crate) fn retain(&mut self, live: &HashSet<String>) {
+        let gone: Vec<String> = self
+            .txs
+            .keys()
+            .filter(|id| !live.contains(*id))
+            .cloned()
+            .collect();
+        for id in &gone {
+            self.remove(id);
+        }
+    }
+
+    /// Tracked transactions not in `skip`, lowest fee rate first
+    fn lowest_fee_rates(&self, skip: &BTreeSet<String>) -> Vec<(u64, String)> {
+        let mut rates: Vec<(u64, String)> = self
+            .txs
+            .iter()
+            .filter(|(id, _)| !skip.contains(*id))
+            .map(|(id, tx)| (tx.fee_rate, id.clone()))
+            .collect();
+        rates.sort();
+        rates
+    }
+
+    fn insert(&mut self, tx: TxSummary, fee_rate: u64, peer: Option<PeerId>) {
+        for input in &tx.inputs {
+            self.spent_by.insert(input.clone(), tx.id.clone());
+        }
+        if let Some(peer) = peer {
+            *self.per_peer.entry(peer).or_default() += 1;
+        }
+        self.txs.insert(
+            tx.id,
+            PendingTx {
+                fee_rate,
+                peer,
+                inputs: tx.inputs,
+            },
+        );
+    }
+
+    fn remove(&mut self, id: &str) {
+        let Some(tx) = self.txs.remove(id) else {
+            return;
+        };
+        for input in &tx.inputs {
+            if self
+                .spent_by
+                .get(input)
+                .is_some_and(|spender| spender == id)
+            {
+                self.spent_by.remove(input);
+            }
+        }
+        if let Some(peer) = tx.peer {
+            if let Some(count) = self.per_peer.get_mut(&peer) {
+                *count -= 1;
+                if *count == 0 {
+                    self.per_peer.remove(&peer);
+                }
+            }
+        }
+    }
+}
+
+/// Wire for pokes the driver makes to enforce the mempool policy. The kernel only takes commands
+/// from a fixed set of sources, so these go out as local nockchain pokes.
+enum MempoolWire {
+    Evict,
+    Replace,
+}
+
+impl Wire for MempoolWire {
+    const VERSION: u64 = 1;
+    const SOURCE: &'static str = "nc";
+
+    fn to_wire(&self) -> WireRepr {
+        match self {
+            MempoolWire::Evict => WireRepr::new(
+                Self::SOURCE,
+                Self::VERSION,
+                vec!["mempool".into(), "evict".into()],
+            ),
+            MempoolWire::Replace => WireRepr::new(
+                Self::SOURCE,
+                Self::VERSION,
+                vec!["mempool".into(), "replace".into()],
+            ),
+        }
+    }
+}
+
+/// A heard transaction the policy lets in, not yet accepted by the kernel.
+pub(crate) struct HeardTx {
+    summary: TxSummary,
+    peer: PeerId,
+    /// Conflicting mempool transactions it outbids
+    replaced: Vec<String>,
+}
+
+impl HeardTx {
+    /// Whether it must be poked with [`replace_txs`]. The kernel discards a heard transaction
+    /// that spends inputs a mempool transaction already spends.
+    pub(crate) fn replaces(&self) -> bool {
+        !self.replaced.is_empty()
+    }
+}
+
+/// Check a heard transaction against the mempool policy. Returns `Err` if it should not be
+/// poked, and `Ok(None)` if it should be poked without tracking it.
+///
+/// `raw_tx` is the `raw-tx` noun from a `[%heard-tx raw-tx]` fact that arrived as `bytes` bytes.
+pub(crate) async fn check_heard_tx(
+    state: &Mutex<P2PState>,
+    metrics: &NockchainP2PMetrics,
+    peer: PeerId,
+    id: &str,
+    raw_tx: &Noun,
+    bytes: usize,
+) -> Result<Option<HeardTx>, Rejection> {
+    let Some(summary) = TxSummary::from_raw_tx(id.to_string(), raw_tx, bytes) else {
+        // Malformed transactions are for the kernel to judge
+        return Ok(None);
+    };
+    let checked = {
+        let state_guard = state.lock().await;
+        if state_guard.seen_txs.contains(id) || state_guard.mempool.txs.contains_key(id) {
+            return Ok(None);
+        }
+        state_guard.mempool.check(&summary, Some(peer))
+    };
+    match checked {
+        Ok(admission) => Ok(Some(HeardTx {
+            summary,
+            peer,
+            replaced: admission.replaced,
+        })),
+        Err(rejection) => {
+            debug!("Refusing tx {} from {}: {}", id, peer, rejection);
+            match rejection {
+                Rejection::TooLarge => metrics.mempool_rejected_too_large.increment(),
+                Rejection::FeeRateTooLow => metrics.mempool_rejected_fee_rate.increment(),
+                Rejection::PeerCapReached => metrics.mempool_rejected_peer_cap.increment(),
+                Rejection::MempoolFull => metrics.mempool_rejected_full.increment(),
+                Rejection::Conflict => metrics.mempool_rejected_conflict.increment(),
+            };
+            Err(rejection)
+        }
+    }
+}
+
+/// Poke `[%command %replace-txs raw-tx displaced]` to add `heard` in place of the mempool
+/// transactions it replaces, then track it if the kernel accepted it.
+pub(crate) async fn replace_txs(
+    traffic: &TrafficCop,
+    state: &Mutex<P2PState>,
+    metrics: &NockchainP2PMetrics,
+    heard: HeardTx,
+    raw_tx: &Noun,
+) -> Result<(), NockAppError> {
+    let mut slab = NounSlab::new();
+    let raw_tx = slab.copy_into(*raw_tx);
+    let mut list = D(0);
+    for id in heard.replaced.iter().rev() {
+        let hash = common::Hash::from_base58(id)
+            .map_err(|e| NockAppError::OtherError(format!("Invalid tx id {id}: {e}")))?;
+        let hash = noun_serde::NounEncode::to_noun(&hash, &mut slab);
+        list = T(&mut slab, &[hash, list]);
+    }
+    let poke = T(
+        &mut slab,
+        &[D(tas!(b"command")), D(tas!(b"replace-txs")), raw_tx, list],
+    );
+    slab.set_root(poke);
+    let result = traffic
+        .poke_high_priority(
+            Some(heard.peer),
+            MempoolWire::Replace.to_wire(),
+            slab,
+            Box::pin(async { true }),
+            None,
+        )
+        .await?;
+    if let PokeResult::Nack = result {
+        debug!("Replacement tx {} nacked", heard.summary.id);
+        return Ok(());
+    }
+    confirm_heard_tx(traffic, state, metrics, heard).await
+}
+
+/// Track `heard` once the kernel has accepted it, dropping the transactions it displaced. If
+/// the kernel refused it, the driver's view is left as it was.
+pub(crate) async fn confirm_heard_tx(
+    traffic: &TrafficCop,
+    state: &Mutex<P2PState>,
+    metrics: &NockchainP2PMetrics,
+    heard: HeardTx,
+) -> Result<(), NockAppError> {
+    let id = heard.summary.id.clone();
+    if !tx_accepted(traffic, &id).await? {
+        debug!(
+            "Kernel did not accept tx {}, leaving the mempool as it was",
+            id
+        );
+        return Ok(());
+    }
+    let admitted = {
+        let mut state_guard = state.lock().await;
+        let admitted = state_guard.mempool.admit(heard.summary, Some(heard.peer));
+        metrics.mempool_size.swap(state_guard.mempool.len() as f64);
+        admitted
+    };
+    let admission = match admitted {
+        Ok(admission) => admission,
+        Err(rejection) => {
+            // The view changed while the kernel validated it
+            debug!("Not tracking accepted tx {}: {}", id, rejection);
+            return Ok(());
+        }
+    };
+    for _ in &admission.replaced {
+        metrics.mempool_replaced.increment();
+    }
+    for _ in &admission.evicted {
+        metrics.mempool_evicted.increment();
+    }
+    let dropped: Vec<String> = admission
+        .replaced
+        .into_iter()
+        .chain(admission.evicted)
+        .collect();
+    if !dropped.is_empty() {
+        debug!("Tx {} displaced {} mempool txs", id, dropped.len());
+        drop_txs(traffic.clone(), dropped).await?;
+    }
+    Ok(())
+}
+
+/// Whether the kernel holds the transaction `id`, by a peek of `/tx-accepted`.
+async fn tx_accepted(traffic: &TrafficCop, id: &str) -> Result<bool, NockAppError> {
+    let mut path = NounSlab::new();
+    let id_atom = Atom::from_value(&mut path, id)?.as_noun();
+    let path_noun = T(&mut path, &[D(tas!(b"tx-accepted")), id_atom, D(0)]);
+    path.set_root(path_noun);
+    let Some(result) = traffic.peek(None, path).await? else {
+        warn!("Tx accepted peek returned nothing");
+        return Ok(false);
+    };
+    // (unit (unit ?))
+    let accepted = unsafe { result.root() }.as_cell()?.tail().as_cell()?.tail();
+    Ok(unsafe { accepted.raw_equals(&YES) })
+}
+
+/// Poke `[%command %drop-txs ids]` to remove transactions from the kernel's mempool.
+pub(crate) async fn drop_txs(traffic: TrafficCop, ids: Vec<String>) -> Result<(), NockAppError> {
+    let mut slab = NounSlab::new();
+    let mut list = D(0);
+    for id in ids.iter().rev() {
+        let hash = common::Hash::from_base58(id)
+            .map_err(|e| NockAppError::OtherError(format!("Invalid tx id {id}: {e}")))?;
+        let hash = noun_serde::NounEncode::to_noun(&hash, &mut slab);
+        list = T(&mut slab, &[hash, list]);
+    }
+    let poke = T(
+        &mut slab,
+        &[D(tas!(b"command")), D(tas!(b"drop-txs")), list],
+    );
+    slab.set_root(poke);
+    traffic
+        .poke_high_priority(
+            None,
+            MempoolWire::Evict.to_wire(),
+            slab,
+            Box::pin(async { true }),
+            None,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Prune transactions the kernel no longer holds unconfirmed from the driver's view.
+pub(crate) async fn sync(
+    traffic: TrafficCop,
+    state: std::sync::Arc<Mutex<P2PState>>,
+    metrics: std::sync::Arc<NockchainP2PMetrics>,
+) -> Result<(), NockAppError> {
+    let mut path = NounSlab::new();
+    let path_noun = T(&mut path, &[D(tas!(b"excluded-txs")), D(0)]);
+    path.set_root(path_noun);
+    let Some(mut result) = traffic.peek(None, path).await? else {
+        warn!("Mempool sync peek returned nothing");
+        return Ok(());
+    };
+    // (unit (unit (z-set tx-id)))
+    let excluded = unsafe { result.root() }.as_cell()?.tail().as_cell()?.tail();
+    let mut live = HashSet::new();
+    for tx_id in HoonMapIter::from(excluded) {
+        live.insert(tip5_hash_to_base58_stack(&mut result, tx_id)?);
+    }
+    let mut state_guard = state.lock().await;
+    state_guard.mempool.retain(&live);
+    metrics.mempool_size.swap(state_guard.mempool.len() as f64);
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) enum MempoolAdminRequest {
+    GetPolicy {
+        result: oneshot::Sender<MempoolPolicy>,
+    },
+    SetPolicy {
+        policy: MempoolPolicy,
+        result: oneshot::Sender<usize>,
+    },
+}
+
+/// Handle for changing the mempool policy of a running libp2p driver.
+#[derive(Clone)]
+pub struct MempoolAdmin {
+    sender: mpsc::Sender<MempoolAdminRequest>,
+}
+
+/// The libp2p driver's end of a [`MempoolAdmin`], passed to
+/// [`crate::driver::make_libp2p_driver`].
+pub struct MempoolAdminReceiver(mpsc::Receiver<MempoolAdminRequest>);
+
+/// Create a [`MempoolAdmin`] and the receiver to hand to the libp2p driver.
+pub fn mempool_admin_channel() -> (MempoolAdmin, MempoolAdminReceiver) {
+    let (sender, receiver) = mpsc::channel(MEMPOOL_ADMIN_QUEUE);
+    (MempoolAdmin { sender }, MempoolAdminReceiver(receiver))
+}
+
+impl MempoolAdmin {
+    async fn request<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<T>) -> MempoolAdminRequest,
+    ) -> Result<T, NockAppError> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(request(tx))
+            .await
+            .map_err(|_| NockAppError::ChannelClosedError)?;
+        Ok(rx.await?)
+    }
+
+    /// The policy currently in force.
+    pub async fn policy(&self) -> Result<MempoolPolicy, NockAppError> {
+        self.request(|result| MempoolAdminRequest::GetPolicy { result })
+            .await
+    }
+
+    /// Replace the policy, returning how many transactions were evicted to fit it.
+    pub async fn set_policy(&self, policy: MempoolPolicy) -> Result<usize, NockAppError> {
+        self.request(|result| MempoolAdminRequest::SetPolicy { policy, result })
+            .await
+    }
+}
+
+/// The next request, or never if the driver was started without a [`MempoolAdminReceiver`].
+pub(crate) async fn next_request(
+    receiver: &mut Option<MempoolAdminReceiver>,
+) -> Option<MempoolAdminRequest> {
+    match receiver {
+        Some(MempoolAdminReceiver(receiver)) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Answer `request`, returning the transactions to drop from the kernel.
+pub(crate) async fn handle_request(
+    state: &Mutex<P2PState>,
+    metrics: &NockchainP2PMetrics,
+    request: MempoolAdminRequest,
+) -> Vec<String> {
+    let mut state_guard = state.lock().await;
+    match request {
+        MempoolAdminRequest::GetPolicy { result } => {
+            let _ = result.send(state_guard.mempool.policy().clone());
+            Vec::new()
+        }
+        MempoolAdminRequest::SetPolicy { policy, result } => {
+            info!("Setting mempool policy by operator request: {policy:?}");
+            let evicted = state_guard.mempool.set_policy(policy);
+            for _ in &evicted {
+                metrics.mempool_evicted.increment();
+            }
+            metrics.mempool_size.swap(state_guard.mempool.len() as f64);
+            let _ = result.send(evicted.len());
+            evicted
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nockchain_math::belt::Belt;
+
+    use super::*;
+
+    fn policy() -> MempoolPolicy {
+        MempoolPolicy {
+            max_txs: 2,
+            max_tx_bytes: 4096,
+            min_fee_rate: 10,
+            max_txs_per_peer: 2,
+            replacement_fee_bump_percent: None,
+        }
+    }
+
+    fn input(n: u64) -> (common::Hash, common::Hash) {
+        let hash = common::Hash([Belt(n); 5]);
+        (hash.clone(), hash)
+    }
+
+    fn tx(id: &str, fee: u64, inputs: &[u64]) -> TxSummary {
+        TxSummary {
+            id: id.to_string(),
+            fee,
+            bytes: 1024,
+            inputs: inputs.iter().copied().map(input).collect(),
+        }
+    }
+
+    #[test]
+    fn test_admission_limits() {
+        let mut mempool = Mempool::new(policy());
+        let peer = PeerId::random();
+
+        let mut large = tx("large", 1000, &[1]);
+        large.bytes = 8192;
+        assert_eq!(mempool.admit(large, None), Err(Rejection::TooLarge));
+        assert_eq!(
+            mempool.admit(tx("cheap", 5, &[1]), None),
+            Err(Rejection::FeeRateTooLow)
+        );
+
+        assert!(mempool.admit(tx("a", 20, &[1]), Some(peer)).is_ok());
+        assert!(mempool.admit(tx("b", 30, &[2]), Some(peer)).is_ok());
+        assert_eq!(
+            mempool.admit(tx("c", 40, &[3]), Some(peer)),
+            Err(Rejection::PeerCapReached)
+        );
+
+        // Full: a higher fee rate evicts the lowest, an equal one is refused
+        assert_eq!(
+            mempool.admit(tx("d", 20, &[4]), None),
+            Err(Rejection::MempoolFull)
+        );
+        let admission = mempool.admit(tx("e", 50, &[5]), None).unwrap();
+        assert_eq!(admission.evicted, vec!["a".to_string()]);
+        assert_eq!(mempool.len(), 2);
+
+        // The evicted tx no longer counts against its peer
+        assert_eq!(
+            mempool.admit(tx("f", 40, &[6]), Some(peer)),
+            Ok(Admission {
+                replaced: Vec::new(),
+                evicted: vec!["b".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut mempool = Mempool::new(MempoolPolicy {
+            max_txs: 10,
+            ..policy()
+        });
+        mempool.admit(tx("a", 100, &[1, 2]), None).unwrap();
+        assert_eq!(
+            mempool.admit(tx("b", 500, &[2]), None),
+            Err(Rejection::Conflict)
+        );
+
+        let mut policy = mempool.policy().clone();
+        policy.replacement_fee_bump_percent = Some(25);
+        mempool.set_policy(policy);
+        assert_eq!(
+            mempool.admit(tx("b", 120, &[2]), None),
+            Err(Rejection::Conflict)
+        );
+        let admission = mempool.admit(tx("b", 125, &[2]), None).unwrap();
+        assert_eq!(admission.replaced, vec!["a".to_string()]);
+        // a's other input is free again
+        assert!(mempool.admit(tx("c", 20, &[1]), None).is_ok());
+    }
+
+    #[test]
+    fn test_check_leaves_view_unchanged() {
+        let mut mempool = Mempool::new(MempoolPolicy {
+            replacement_fee_bump_percent: Some(25),
+            ..policy()
+        });
+        mempool.admit(tx("a", 100, &[1]), None).unwrap();
+        mempool.admit(tx("b", 20, &[2]), None).unwrap();
+
+        // Nothing is displaced until the kernel accepts the new tx
+        let replacement = tx("c", 200, &[1]);
+        let admission = mempool.check(&replacement, None).unwrap();
+        assert_eq!(admission.replaced, vec!["a".to_string()]);
+        assert_eq!(mempool.len(), 2);
+        assert_eq!(mempool.check(&replacement, None), Ok(admission));
+    }
+
+    #[test]
+    fn test_set_policy_and_retain() {
+        let mut mempool = Mempool::new(MempoolPolicy {
+            max_txs: 10,
+            ..policy()
+        });
+        for (i, id) in ["a", "b", "c"].into_iter().enumerate() {
+            mempool
+                .admit(tx(id, 20 + i as u64, &[i as u64]), None)
+                .unwrap();
+        }
+        let evicted = mempool.set_policy(MempoolPolicy {
+            max_txs: 2,
+            ..policy()
+        });
+        assert_eq!(evicted, vec!["a".to_string()]);
+
+        mempool.retain(&HashSet::from(["c".to_string()]));
+        assert_eq!(mempool.len(), 1);
+        assert!(mempool.admit(tx("d", 20, &[1]), None).is_ok());
+    }
+}
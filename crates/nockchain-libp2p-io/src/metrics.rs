@@ -69,6 +69,11 @@ metrics_struct![
         Count
     ),
     (requests_crown_error_play_bail, "nockchain-libp2p-io.requests_crown_error_play_bail", Count),
+    (
+        requests_crown_error_out_of_memory,
+        "nockchain-libp2p-io.requests_crown_error_out_of_memory", Count
+    ),
+    (requests_crown_error_event_log, "nockchain-libp2p-io.requests_crown_error_event_log", Count),
     (
         requests_crown_error_queue_recv, "nockchain-libp2p-io.requests_crown_error_queue_recv",
         Count
@@ -162,6 +167,15 @@ metrics_struct![
     (request_failed, "nockchain-libp2p-io.request_failed", Count),
     (response_failed_not_dropped, "nockchain-libp2p-io.response_failed_not_dropped", Count),
     (response_dropped, "nockchain-libp2p-io.response_dropped", Count),
+    // Mempool policy
+    (mempool_size, "nockchain-libp2p-io.mempool_size", Gauge),
+    (mempool_rejected_too_large, "nockchain-libp2p-io.mempool_rejected_too_large", Count),
+    (mempool_rejected_fee_rate, "nockchain-libp2p-io.mempool_rejected_fee_rate", Count),
+    (mempool_rejected_peer_cap, "nockchain-libp2p-io.mempool_rejected_peer_cap", Count),
+    (mempool_rejected_full, "nockchain-libp2p-io.mempool_rejected_full", Count),
+    (mempool_rejected_conflict, "nockchain-libp2p-io.mempool_rejected_conflict", Count),
+    (mempool_evicted, "nockchain-libp2p-io.mempool_evicted", Count),
+    (mempool_replaced, "nockchain-libp2p-io.mempool_replaced", Count),
     // Per-cause poke timings
     (timer_poke_time, "nockchain-libp2p-io.timer_poke_time", TimingCount),
     (heard_tx_poke_time, "nockchain-libp2p-io.heard_tx_poke_time", TimingCount),
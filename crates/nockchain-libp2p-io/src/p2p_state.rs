@@ -1,6 +1,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use libp2p::core::ConnectedPoint;
 use libp2p::swarm::ConnectionId;
@@ -11,9 +12,12 @@ use nockvm::noun::Noun;
 use rand::prelude::SliceRandom;
 use tracing::{debug, info, trace};
 
+use crate::config::LibP2PConfig;
+use crate::mempool::Mempool;
 use crate::messages::NockchainDataRequest;
 use crate::metrics::NockchainP2PMetrics;
 use crate::p2p_util::MultiaddrExt;
+use crate::peer_admin::{PeerDirection, PeerInfo};
 use crate::tip5_util::tip5_hash_to_base58;
 
 #[derive(Default)]
@@ -23,6 +27,12 @@ struct IpInfo {
     connections: BTreeSet<ConnectionId>,
 }
 
+struct PeerDetails {
+    agent_version: Option<String>,
+    protocol_version: Option<String>,
+    last_seen: SystemTime,
+}
+
 pub struct P2PState {
     metrics: Arc<NockchainP2PMetrics>,
     block_id_to_peers: BTreeMap<String, BTreeSet<PeerId>>,
@@ -32,6 +42,8 @@ pub struct P2PState {
     // subset of connections: all inbound connections
     inbound_connections: BTreeMap<ConnectionId, PeerId>,
     pub(crate) peer_connections: BTreeMap<PeerId, BTreeMap<ConnectionId, Multiaddr>>,
+    // What we know of each connected peer, for operators
+    peer_details: BTreeMap<PeerId, PeerDetails>,
     ip_info: BTreeMap<IpAddr, IpInfo>,
     pub seen_blocks: BTreeSet<String>,
     pub seen_txs: BTreeSet<String>,
@@ -44,6 +56,8 @@ pub struct P2PState {
     pub first_negative: u64,
     pub seen_tx_clear_interval: u64,
     pub last_tx_cache_clear_height: u64,
+    // Transactions let in from peers, under the mempool policy
+    pub(crate) mempool: Mempool,
 }
 
 impl P2PState {
@@ -55,6 +69,7 @@ impl P2PState {
             connections: BTreeMap::new(),
             inbound_connections: BTreeMap::new(),
             peer_connections: BTreeMap::new(),
+            peer_details: BTreeMap::new(),
             ip_info: BTreeMap::new(),
             seen_blocks: BTreeSet::new(),
             seen_txs: BTreeSet::new(),
@@ -66,6 +81,7 @@ impl P2PState {
             first_negative: 0,
             seen_tx_clear_interval,
             last_tx_cache_clear_height: 0,
+            mempool: Mempool::new(LibP2PConfig::default().mempool_policy()),
         }
     }
 
@@ -80,6 +96,7 @@ impl P2PState {
         if let ConnectedPoint::Listener { .. } = endpoint {
             self.inbound_connections.insert(connection_id, peer_id);
         }
+        self.saw_peer(peer_id);
         if let Some(c) = self.peer_connections.get_mut(&peer_id) {
             c.insert(connection_id, addr.clone());
         } else {
@@ -114,6 +131,7 @@ impl P2PState {
                 let addr = c.remove(&connection_id);
                 if c.is_empty() {
                     self.peer_connections.remove(&peer_id);
+                    self.peer_details.remove(&peer_id);
                     self.remove_peer(&peer_id);
                 }
                 if let Some(addr) = addr {
@@ -172,7 +190,55 @@ impl P2PState {
         }
     }
 
+    /// Note that we just heard from the peer.
+    pub(crate) fn saw_peer(&mut self, peer_id: PeerId) {
+        self.peer_details
+            .entry(peer_id)
+            .and_modify(|details| details.last_seen = SystemTime::now())
+            .or_insert_with(|| PeerDetails {
+                agent_version: None,
+                protocol_version: None,
+                last_seen: SystemTime::now(),
+            });
+    }
+
+    /// Record the versions a peer sent us in identify.
+    pub(crate) fn identified(&mut self, peer_id: PeerId, info: &libp2p::identify::Info) {
+        self.saw_peer(peer_id);
+        if let Some(details) = self.peer_details.get_mut(&peer_id) {
+            details.agent_version = Some(info.agent_version.clone());
+            details.protocol_version = Some(info.protocol_version.clone());
+        }
+    }
+
+    /// Every connected peer, described by its oldest open connection.
+    pub(crate) fn peer_infos(&self) -> Vec<PeerInfo> {
+        self.peer_connections
+            .iter()
+            .filter_map(|(peer_id, connections)| {
+                let (connection_id, address) = connections.iter().next()?;
+                let direction = if self.inbound_connections.contains_key(connection_id) {
+                    PeerDirection::Inbound
+                } else {
+                    PeerDirection::Outbound
+                };
+                let details = self.peer_details.get(peer_id);
+                Some(PeerInfo {
+                    peer_id: *peer_id,
+                    address: address.clone(),
+                    direction,
+                    agent_version: details.and_then(|d| d.agent_version.clone()),
+                    protocol_version: details.and_then(|d| d.protocol_version.clone()),
+                    last_seen: details.map_or(SystemTime::UNIX_EPOCH, |d| d.last_seen),
+                })
+            })
+            .collect()
+    }
+
     pub(crate) fn ping_succeeded(&mut self, connection: ConnectionId) {
+        if let Some(peer_id) = self.connections.get(&connection).copied() {
+            self.saw_peer(peer_id);
+        }
         let addr = self.connection_address(connection);
         let Some(addr) = addr else {
             trace!("No address for connection {connection}. Please inform the developers.");
@@ -793,4 +859,36 @@ mod tests {
         assert_eq!(ipv4_display, "192.168.1.1");
         assert_eq!(ipv6_display, "2001:db8:db8:db8:db8:db8:db8:1");
     }
+
+    #[test]
+    fn test_peer_infos() {
+        let metrics = Arc::new(
+            NockchainP2PMetrics::register(gnort::global_metrics_registry())
+                .expect("Could not register metrics"),
+        );
+        let mut tracker = P2PState::new(metrics, LIBP2P_CONFIG.seen_tx_clear_interval);
+        let peer_id = PeerId::random();
+        let local: Multiaddr = "/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let remote: Multiaddr = "/ip4/10.0.0.1/udp/4001/quic-v1".parse().unwrap();
+        let connection = ConnectionId::new_unchecked(1);
+        tracker.track_connection(
+            connection,
+            peer_id,
+            &remote,
+            ConnectedPoint::Listener {
+                local_addr: local,
+                send_back_addr: remote.clone(),
+            },
+        );
+
+        let peers = tracker.peer_infos();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].peer_id, peer_id);
+        assert_eq!(peers[0].address, remote);
+        assert_eq!(peers[0].direction, PeerDirection::Inbound);
+        assert_eq!(peers[0].agent_version, None);
+
+        tracker.lost_connection(connection);
+        assert!(tracker.peer_infos().is_empty());
+    }
 }
@@ -0,0 +1,159 @@
+//! Operator control of the peer set: listing connected peers, banning and unbanning them, and
+//! dialing peers by hand, without restarting the node.
+//!
+//! A [`PeerAdmin`] sends requests to the libp2p driver, which answers them from its swarm loop
+//! alongside [`crate::driver::SwarmAction`]s. Bans and manual peers last until the node restarts.
+
+use std::time::SystemTime;
+
+use libp2p::{Multiaddr, PeerId, Swarm};
+use nockapp::NockAppError;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tracing::{info, warn};
+
+use crate::behaviour::NockchainBehaviour;
+use crate::p2p_state::P2PState;
+
+/// Requests waiting for the swarm loop
+const PEER_ADMIN_QUEUE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerDirection {
+    /// The peer dialed us
+    Inbound,
+    /// We dialed the peer
+    Outbound,
+}
+
+/// A connected peer, as seen by the libp2p driver.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    pub peer_id: PeerId,
+    /// Remote address of the peer's oldest open connection
+    pub address: Multiaddr,
+    pub direction: PeerDirection,
+    /// Agent version from identify, once the peer has sent it
+    pub agent_version: Option<String>,
+    /// Protocol version from identify, once the peer has sent it
+    pub protocol_version: Option<String>,
+    /// When the peer last connected, identified itself or answered a ping
+    pub last_seen: SystemTime,
+}
+
+#[derive(Debug)]
+pub(crate) enum PeerAdminRequest {
+    ListPeers {
+        result: oneshot::Sender<Vec<PeerInfo>>,
+    },
+    BanPeer {
+        peer_id: PeerId,
+        result: oneshot::Sender<()>,
+    },
+    UnbanPeer {
+        peer_id: PeerId,
+        result: oneshot::Sender<()>,
+    },
+    AddPeer {
+        address: Multiaddr,
+        result: oneshot::Sender<Result<(), NockAppError>>,
+    },
+}
+
+/// Handle for managing the peers of a running libp2p driver.
+#[derive(Clone)]
+pub struct PeerAdmin {
+    sender: mpsc::Sender<PeerAdminRequest>,
+}
+
+/// The libp2p driver's end of a [`PeerAdmin`], passed to
+/// [`crate::driver::make_libp2p_driver`].
+pub struct PeerAdminReceiver(mpsc::Receiver<PeerAdminRequest>);
+
+/// Create a [`PeerAdmin`] and the receiver to hand to the libp2p driver.
+pub fn peer_admin_channel() -> (PeerAdmin, PeerAdminReceiver) {
+    let (sender, receiver) = mpsc::channel(PEER_ADMIN_QUEUE);
+    (PeerAdmin { sender }, PeerAdminReceiver(receiver))
+}
+
+impl PeerAdmin {
+    async fn request<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<T>) -> PeerAdminRequest,
+    ) -> Result<T, NockAppError> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(request(tx))
+            .await
+            .map_err(|_| NockAppError::ChannelClosedError)?;
+        Ok(rx.await?)
+    }
+
+    /// Every currently connected peer.
+    pub async fn list_peers(&self) -> Result<Vec<PeerInfo>, NockAppError> {
+        self.request(|result| PeerAdminRequest::ListPeers { result })
+            .await
+    }
+
+    /// Disconnect the peer and refuse its connections from now on.
+    pub async fn ban_peer(&self, peer_id: PeerId) -> Result<(), NockAppError> {
+        self.request(|result| PeerAdminRequest::BanPeer { peer_id, result })
+            .await
+    }
+
+    /// Lift a ban, whether it was made by hand or by the driver for misbehaviour.
+    pub async fn unban_peer(&self, peer_id: PeerId) -> Result<(), NockAppError> {
+        self.request(|result| PeerAdminRequest::UnbanPeer { peer_id, result })
+            .await
+    }
+
+    /// Dial the peer at `address`, and keep redialing it along with the force peers.
+    pub async fn add_peer(&self, address: Multiaddr) -> Result<(), NockAppError> {
+        self.request(|result| PeerAdminRequest::AddPeer { address, result })
+            .await?
+    }
+}
+
+/// The next request, or never if the driver was started without a [`PeerAdminReceiver`].
+pub(crate) async fn next_request(
+    receiver: &mut Option<PeerAdminReceiver>,
+) -> Option<PeerAdminRequest> {
+    match receiver {
+        Some(PeerAdminReceiver(receiver)) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+pub(crate) async fn handle_request(
+    swarm: &mut Swarm<NockchainBehaviour>,
+    state: &Mutex<P2PState>,
+    manual_peers: &mut Vec<Multiaddr>,
+    request: PeerAdminRequest,
+) {
+    match request {
+        PeerAdminRequest::ListPeers { result } => {
+            let peers = state.lock().await.peer_infos();
+            let _ = result.send(peers);
+        }
+        PeerAdminRequest::BanPeer { peer_id, result } => {
+            warn!("Banning peer {peer_id} by operator request");
+            swarm.behaviour_mut().allow_block_list.block_peer(peer_id);
+            let _ = swarm.disconnect_peer_id(peer_id);
+            let _ = result.send(());
+        }
+        PeerAdminRequest::UnbanPeer { peer_id, result } => {
+            info!("Unbanning peer {peer_id} by operator request");
+            swarm.behaviour_mut().allow_block_list.unblock_peer(peer_id);
+            let _ = result.send(());
+        }
+        PeerAdminRequest::AddPeer { address, result } => {
+            info!("Adding peer {address} by operator request");
+            let dialed = swarm
+                .dial(address.clone())
+                .map_err(|e| NockAppError::OtherError(format!("Failed to dial {address}: {e}")));
+            if dialed.is_ok() && !manual_peers.contains(&address) {
+                manual_peers.push(address);
+            }
+            let _ = result.send(dialed);
+        }
+    }
+}
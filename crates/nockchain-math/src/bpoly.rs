@@ -2,6 +2,7 @@ use std::vec;
 
 use crate::belt::{bpow, Belt, FieldError};
 use crate::poly::*;
+use crate::simd;
 
 pub fn bpadd(a: &[Belt], b: &[Belt], res: &mut [Belt]) {
     let min: &[Belt];
@@ -125,9 +126,7 @@ pub fn bpmul_(left: &[Belt], right: &[Belt]) -> Vec<Belt> {
 
 #[inline(always)]
 pub fn bpscal(scalar: Belt, b: &[Belt], res: &mut [Belt]) {
-    for (res, bp) in res.iter_mut().zip(b.iter()) {
-        *res = scalar * *bp;
-    }
+    simd::scale(scalar, b, res);
 }
 
 #[inline(always)]
@@ -146,12 +145,7 @@ pub fn bp_hadamard(a: &[Belt], b: &[Belt], res: &mut [Belt]) {
         a.len(),
         b.len()
     );
-    res.iter_mut()
-        .zip(a.iter())
-        .zip(b.iter())
-        .for_each(|((res_i, a_i), b_i)| {
-            *res_i = *a_i * *b_i;
-        });
+    simd::hadamard(a, b, res);
 }
 
 #[inline(always)]
@@ -164,12 +158,7 @@ pub fn bp_hadamard_(a: &[Belt], b: &[Belt]) -> Vec<Belt> {
         b.len()
     );
     let mut res = vec![Belt(0); a.len()];
-    res.iter_mut()
-        .zip(a.iter())
-        .zip(b.iter())
-        .for_each(|((res_i, a_i), b_i)| {
-            *res_i = *a_i * *b_i;
-        });
+    simd::hadamard(a, b, &mut res);
     res
 }
 
@@ -213,6 +202,31 @@ pub fn bp_fft(bp: &[Belt]) -> Result<Vec<Belt>, FieldError> {
 }
 
 pub fn bp_ntt(bp: &[Belt], root: &Belt) -> Vec<Belt> {
+    bp_ntt_with_twiddles(bp, &bp_ntt_twiddles(bp.len(), root))
+}
+
+/// The twiddle factors of every layer of a length-`n` NTT, concatenated: layer `m` (for `m` =
+/// 1, 2, 4, ..., `n`/2) holds the `m` powers of its `2m`-th root of unity starting at offset
+/// `m - 1`. Depends only on `n` and `root`, so callers transforming many vectors of one size can
+/// compute it once.
+pub fn bp_ntt_twiddles(n: usize, root: &Belt) -> Vec<Belt> {
+    debug_assert!(n.is_power_of_two());
+    let mut twiddles = Vec::with_capacity(n.saturating_sub(1));
+    let mut m = 1;
+    while m < n {
+        let w_m: Belt = bpow(root.0, (n / (2 * m)) as u64).into();
+        let mut w = Belt(1);
+        for _ in 0..m {
+            twiddles.push(w);
+            w = w * w_m;
+        }
+        m *= 2;
+    }
+    twiddles
+}
+
+/// [`bp_ntt`] with a table from [`bp_ntt_twiddles`] for `bp.len()`.
+pub fn bp_ntt_with_twiddles(bp: &[Belt], twiddles: &[Belt]) -> Vec<Belt> {
     let n = bp.len() as u32;
 
     if n == 1 {
@@ -220,6 +234,7 @@ pub fn bp_ntt(bp: &[Belt], root: &Belt) -> Vec<Belt> {
     }
 
     debug_assert!(n.is_power_of_two());
+    debug_assert_eq!(twiddles.len(), n as usize - 1);
 
     let log_2_of_n = n.ilog2();
 
@@ -233,48 +248,59 @@ pub fn bp_ntt(bp: &[Belt], root: &Belt) -> Vec<Belt> {
         }
     }
 
-    let mut m = 1;
-    for _ in 0..log_2_of_n {
-        let w_m: Belt = bpow(root.0, (n / (2 * m)) as u64).into();
-
-        let mut k = 0;
-        while k < n {
-            let mut w = Belt(1);
+    #[cfg(feature = "gpu")]
+    if x.len() >= crate::gpu::MIN_NTT_LEN && crate::gpu::try_ntt_bitreversed(&mut x, twiddles) {
+        return x;
+    }
 
-            for j in 0..m {
-                let u: Belt = x[(k + j) as usize];
-                let v: Belt = x[(k + j + m) as usize] * w;
-                x[(k + j) as usize] = u + v;
-                x[(k + j + m) as usize] = u - v;
-                w = w * w_m;
-            }
+    ntt_bitreversed(&mut x, twiddles);
+    x
+}
 
-            k += 2 * m;
+/// Every butterfly layer of a radix-2 NTT over `x`, already in bit-reversed order, on the CPU.
+pub(crate) fn ntt_bitreversed(x: &mut [Belt], twiddles: &[Belt]) {
+    let mut m = 1;
+    while m < x.len() {
+        let layer = &twiddles[m - 1..2 * m - 1];
+        for block in x.chunks_exact_mut(2 * m) {
+            let (lo, hi) = block.split_at_mut(m);
+            simd::ntt_butterflies(lo, hi, layer);
         }
 
         m *= 2;
     }
-    x
 }
 
+/// Evaluate `bp` at `x` by Horner's rule, as `++bpeval` does.
 #[inline(always)]
-pub fn bp_shift(poly_a: &[Belt], belt_b: &Belt, poly_res: &mut [Belt]) {
-    let mut belt_power: Belt = Belt(1);
+pub fn bpeval(bp: &[Belt], x: Belt) -> Belt {
+    simd::eval(bp, x)
+}
 
-    for i in 0..poly_a.len() {
-        poly_res[i] = poly_a[i] * belt_power;
-        belt_power = belt_power * *belt_b;
-    }
+#[inline(always)]
+pub fn bp_shift(poly_a: &[Belt], belt_b: &Belt, poly_res: &mut [Belt]) {
+    simd::shift(poly_a, *belt_b, poly_res);
 }
 
 #[inline(always)]
 pub fn bp_coseword(bp: &[Belt], offset: &Belt, order: u32, root: &Belt) -> Vec<Belt> {
+    bp_coseword_with_twiddles(bp, offset, order, &bp_ntt_twiddles(order as usize, root))
+}
+
+/// [`bp_coseword`] with a table from [`bp_ntt_twiddles`] for `order`.
+#[inline(always)]
+pub fn bp_coseword_with_twiddles(
+    bp: &[Belt],
+    offset: &Belt,
+    order: u32,
+    twiddles: &[Belt],
+) -> Vec<Belt> {
     // shift
     let len_res: u32 = order;
     let mut res = vec![Belt::zero(); len_res as usize];
     bp_shift(bp, offset, &mut res);
 
-    bp_ntt(&res, root)
+    bp_ntt_with_twiddles(&res, twiddles)
 }
 
 #[inline(always)]
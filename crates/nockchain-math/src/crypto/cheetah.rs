@@ -5,7 +5,7 @@ use nockvm::jets::JetErr;
 use noun_serde::{NounDecode, NounEncode};
 use once_cell::sync::Lazy;
 
-use crate::belt::{bneg, Belt, PRIME};
+use crate::belt::{based_check, bneg, Belt, PRIME};
 use crate::bpoly::{bpegcd, bpscal};
 
 pub static G_ORDER: Lazy<UBig> = Lazy::new(|| {
@@ -337,6 +337,195 @@ pub fn trunc_g_order(a: &[u64]) -> UBig {
 
     result % &*G_ORDER
 }
+
+/// `b` in the curve equation `y² = x³ + x + b`, recovered from the generator.
+static CURVE_B: Lazy<F6lt> = Lazy::new(|| {
+    let x = A_GEN.x;
+    f6_sub(
+        &f6_square(&A_GEN.y),
+        &f6_add(&f6_mul(&f6_square(&x), &x), &x),
+    )
+});
+
+/// Whether `p` is the identity or an affine point with canonical coordinates satisfying the curve
+/// equation. Unlike [`CheetahPoint::in_curve`] this does not check subgroup membership, so it is
+/// cheap enough to run on every pubkey in a block.
+pub fn ch_on_curve(p: &CheetahPoint) -> bool {
+    if p.inf {
+        return *p == A_ID;
+    }
+    if !p
+        .x
+        .0
+        .iter()
+        .chain(p.y.0.iter())
+        .all(|belt| based_check(belt.0))
+    {
+        return false;
+    }
+    let rhs = f6_add(&f6_add(&f6_mul(&f6_square(&p.x), &p.x), &p.x), &CURVE_B);
+    f6_square(&p.y) == rhs
+}
+
+/// A point in Jacobian coordinates, standing for the affine point `(x/z², y/z³)`; `z = 0` is the
+/// identity. Adding and doubling need no field inversions, so a batch of scalar multiplications
+/// can be carried out entirely in this form and converted back with [`ch_batch_to_affine`].
+///
+/// The formulas are only meaningful for points on the curve; callers are expected to check
+/// [`ch_on_curve`] first.
+#[derive(Debug, Clone, Copy)]
+pub struct JacobianPoint {
+    pub x: F6lt,
+    pub y: F6lt,
+    pub z: F6lt,
+}
+
+impl JacobianPoint {
+    pub const IDENTITY: JacobianPoint = JacobianPoint {
+        x: F6_ONE,
+        y: F6_ONE,
+        z: F6_ZERO,
+    };
+
+    pub fn from_affine(p: &CheetahPoint) -> Self {
+        if p.inf {
+            return Self::IDENTITY;
+        }
+        JacobianPoint {
+            x: p.x,
+            y: p.y,
+            z: F6_ONE,
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_identity(&self) -> bool {
+        self.z == F6_ZERO
+    }
+
+    pub fn neg(&self) -> Self {
+        JacobianPoint {
+            x: self.x,
+            y: f6_neg(&self.y),
+            z: self.z,
+        }
+    }
+
+    pub fn double(&self) -> Self {
+        if self.is_identity() || self.y == F6_ZERO {
+            return Self::IDENTITY;
+        }
+        let xx = f6_square(&self.x);
+        let yy = f6_square(&self.y);
+        let zz = f6_square(&self.z);
+        let s = f6_scal(Belt(4), &f6_mul(&self.x, &yy));
+        // 3x² + a·z⁴ with a = 1
+        let m = f6_add(&f6_scal(Belt(3), &xx), &f6_square(&zz));
+        let x = f6_sub(&f6_square(&m), &f6_scal(Belt(2), &s));
+        let y = f6_sub(
+            &f6_mul(&m, &f6_sub(&s, &x)),
+            &f6_scal(Belt(8), &f6_square(&yy)),
+        );
+        let z = f6_scal(Belt(2), &f6_mul(&self.y, &self.z));
+        JacobianPoint { x, y, z }
+    }
+
+    pub fn add(&self, q: &Self) -> Self {
+        if self.is_identity() {
+            return *q;
+        }
+        if q.is_identity() {
+            return *self;
+        }
+        let z1z1 = f6_square(&self.z);
+        let z2z2 = f6_square(&q.z);
+        let u1 = f6_mul(&self.x, &z2z2);
+        let u2 = f6_mul(&q.x, &z1z1);
+        let s1 = f6_mul(&self.y, &f6_mul(&q.z, &z2z2));
+        let s2 = f6_mul(&q.y, &f6_mul(&self.z, &z1z1));
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double()
+            } else {
+                Self::IDENTITY
+            };
+        }
+        let h = f6_sub(&u2, &u1);
+        let r = f6_sub(&s2, &s1);
+        let hh = f6_square(&h);
+        let hhh = f6_mul(&h, &hh);
+        let v = f6_mul(&u1, &hh);
+        let x = f6_sub(&f6_sub(&f6_square(&r), &hhh), &f6_scal(Belt(2), &v));
+        let y = f6_sub(&f6_mul(&r, &f6_sub(&v, &x)), &f6_mul(&s1, &hhh));
+        let z = f6_mul(&f6_mul(&self.z, &q.z), &h);
+        JacobianPoint { x, y, z }
+    }
+
+    /// `n·p` by left-to-right double-and-add.
+    pub fn scal(n: &UBig, p: &CheetahPoint) -> Self {
+        let p = Self::from_affine(p);
+        let mut acc = Self::IDENTITY;
+        for i in (0..n.bit_len()).rev() {
+            acc = acc.double();
+            if n.bit(i) {
+                acc = acc.add(&p);
+            }
+        }
+        acc
+    }
+}
+
+/// `2^i·G` for every bit of a scalar below the group order, so multiples of the generator need
+/// only additions.
+static GEN_POWERS: Lazy<Vec<JacobianPoint>> = Lazy::new(|| {
+    let mut powers = Vec::with_capacity(G_ORDER.bit_len());
+    let mut p = JacobianPoint::from_affine(&A_GEN);
+    for _ in 0..G_ORDER.bit_len() {
+        powers.push(p);
+        p = p.double();
+    }
+    powers
+});
+
+/// `n·G` using the shared table of powers of the generator. `n` must be below [`G_ORDER`].
+pub fn ch_gen_scal(n: &UBig) -> JacobianPoint {
+    debug_assert!(n < &*G_ORDER);
+    (0..n.bit_len())
+        .filter(|&i| n.bit(i))
+        .fold(JacobianPoint::IDENTITY, |acc, i| acc.add(&GEN_POWERS[i]))
+}
+
+/// Convert a batch of points back to affine coordinates with a single field inversion, using
+/// Montgomery's trick.
+pub fn ch_batch_to_affine(points: &[JacobianPoint]) -> Result<Vec<CheetahPoint>, JetErr> {
+    // prefix[i] is the product of the non-identity z's up to and including point i
+    let mut prefix = Vec::with_capacity(points.len());
+    let mut acc = F6_ONE;
+    for p in points {
+        if !p.is_identity() {
+            acc = f6_mul(&acc, &p.z);
+        }
+        prefix.push(acc);
+    }
+    let mut inv = f6_inv(&acc)?;
+    let mut out = vec![A_ID; points.len()];
+    for i in (0..points.len()).rev() {
+        let p = &points[i];
+        if p.is_identity() {
+            continue;
+        }
+        let before = if i == 0 { F6_ONE } else { prefix[i - 1] };
+        let z_inv = f6_mul(&inv, &before);
+        inv = f6_mul(&inv, &p.z);
+        let z_inv2 = f6_square(&z_inv);
+        out[i] = CheetahPoint {
+            x: f6_mul(&p.x, &z_inv2),
+            y: f6_mul(&p.y, &f6_mul(&z_inv2, &z_inv)),
+            inf: false,
+        };
+    }
+    Ok(out)
+}
 #[cfg(test)]
 mod test {
     use super::*;
@@ -452,4 +641,38 @@ mod test {
         assert_eq!(res, exp_pt);
         Ok(())
     }
+
+    #[test]
+    fn test_jacobian_matches_affine() -> Result<(), JetErr> {
+        let p = ch_scal(12345, &A_GEN)?;
+        assert!(ch_on_curve(&p));
+        assert!(!ch_on_curve(&CheetahPoint {
+            x: p.x,
+            y: F6_ONE,
+            inf: false
+        }));
+
+        let scalars = [
+            UBig::from(0u64),
+            UBig::from(1u64),
+            UBig::from(2u64),
+            UBig::from(0xdead_beef_u64),
+            &*G_ORDER - UBig::from(1u64),
+        ];
+        let mut jacobian = Vec::new();
+        let mut expected = Vec::new();
+        for n in &scalars {
+            jacobian.push(JacobianPoint::scal(n, &p));
+            expected.push(ch_scal_big(n, &p)?);
+            jacobian.push(ch_gen_scal(n));
+            expected.push(ch_scal_big(n, &A_GEN)?);
+            // n·G − n·G hits the identity through the addition formula
+            jacobian.push(ch_gen_scal(n).add(&JacobianPoint::scal(n, &A_GEN).neg()));
+            expected.push(A_ID);
+        }
+        jacobian.push(JacobianPoint::from_affine(&p).add(&JacobianPoint::from_affine(&p)));
+        expected.push(ch_double(p)?);
+        assert_eq!(ch_batch_to_affine(&jacobian)?, expected);
+        Ok(())
+    }
 }
@@ -1,2 +1,3 @@
 pub mod argon2;
+pub mod blake3;
 pub mod cheetah;
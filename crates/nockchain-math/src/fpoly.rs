@@ -218,6 +218,32 @@ pub fn fp_shift(poly_a: &[Felt], felt_b: &Felt, poly_res: &mut [Felt]) {
 }
 
 pub fn fp_ntt(fp: &[Felt], root: &Felt) -> Vec<Felt> {
+    fp_ntt_with_twiddles(fp, &fp_ntt_twiddles(fp.len(), root))
+}
+
+const FELT0: Felt = Felt([Belt(0), Belt(0), Belt(0)]);
+const FELT1: Felt = Felt([Belt(1), Belt(0), Belt(0)]);
+
+/// Per-layer twiddle factors laid out as in [`crate::bpoly::bp_ntt_twiddles`].
+pub fn fp_ntt_twiddles(n: usize, root: &Felt) -> Vec<Felt> {
+    debug_assert!(n.is_power_of_two());
+    let mut twiddles = Vec::with_capacity(n.saturating_sub(1));
+    let mut m = 1;
+    while m < n {
+        let mut w_m: Felt = Default::default();
+        fpow(root, (n / (2 * m)) as u64, &mut w_m);
+        let mut w = FELT1;
+        for _ in 0..m {
+            twiddles.push(w);
+            w = w * w_m;
+        }
+        m *= 2;
+    }
+    twiddles
+}
+
+/// [`fp_ntt`] with a table from [`fp_ntt_twiddles`] for `fp.len()`.
+pub fn fp_ntt_with_twiddles(fp: &[Felt], twiddles: &[Felt]) -> Vec<Felt> {
     let n = fp.len() as u32;
 
     if n == 1 {
@@ -225,12 +251,10 @@ pub fn fp_ntt(fp: &[Felt], root: &Felt) -> Vec<Felt> {
     }
 
     debug_assert!(n.is_power_of_two());
+    debug_assert_eq!(twiddles.len(), n as usize - 1);
 
     let log_2_of_n = n.ilog2();
 
-    const FELT0: Felt = Felt([Belt(0), Belt(0), Belt(0)]);
-    const FELT1: Felt = Felt([Belt(1), Belt(0), Belt(0)]);
-
     let mut x: Vec<Felt> = vec![FELT0; n as usize];
     x.copy_from_slice(fp);
 
@@ -243,19 +267,15 @@ pub fn fp_ntt(fp: &[Felt], root: &Felt) -> Vec<Felt> {
 
     let mut m = 1;
     for _ in 0..log_2_of_n {
-        let mut w_m: Felt = Default::default();
-        fpow(root, (n / (2 * m)) as u64, &mut w_m);
+        let layer = &twiddles[m as usize - 1..2 * m as usize - 1];
 
         let mut k = 0;
         while k < n {
-            let mut w = FELT1;
-
             for j in 0..m {
                 let u: Felt = x[(k + j) as usize];
-                let v: Felt = x[(k + j + m) as usize] * w;
+                let v: Felt = x[(k + j + m) as usize] * layer[j as usize];
                 x[(k + j) as usize] = u + v;
                 x[(k + j + m) as usize] = u - v;
-                w = w * w_m;
             }
 
             k += 2 * m;
@@ -268,12 +288,23 @@ pub fn fp_ntt(fp: &[Felt], root: &Felt) -> Vec<Felt> {
 
 #[inline(always)]
 pub fn fp_coseword(fp: &[Felt], offset: &Felt, order: u32, root: &Felt) -> Vec<Felt> {
+    fp_coseword_with_twiddles(fp, offset, order, &fp_ntt_twiddles(order as usize, root))
+}
+
+/// [`fp_coseword`] with a table from [`fp_ntt_twiddles`] for `order`.
+#[inline(always)]
+pub fn fp_coseword_with_twiddles(
+    fp: &[Felt],
+    offset: &Felt,
+    order: u32,
+    twiddles: &[Felt],
+) -> Vec<Felt> {
     // shift
     let len_res: u32 = order;
     let mut res = vec![Felt::zero(); len_res as usize];
     fp_shift(fp, offset, &mut res);
 
-    fp_ntt(&res, root)
+    fp_ntt_with_twiddles(&res, twiddles)
 }
 
 // MIT License
@@ -0,0 +1,212 @@
+// Goldilocks (p = 2^64 - 2^32 + 1) arithmetic on little-endian u32 pairs, one radix-2 NTT layer
+// per dispatch, and a batched Tip5 permutation. Mirrors `reduce_159` in belt.rs so results match
+// the CPU bit for bit.
+
+struct Params {
+    // Half the butterfly span of this layer.
+    m: u32,
+    // Number of butterflies in the layer (n / 2).
+    count: u32,
+}
+
+@group(0) @binding(0) var<storage, read_write> data: array<vec2<u32>>;
+@group(0) @binding(1) var<storage, read> twiddles: array<vec2<u32>>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+struct PermuteParams {
+    // Number of sponge states in the batch.
+    count: u32,
+}
+
+// Each pipeline only binds the variables its entry point uses, so the Tip5 bindings follow on.
+@group(0) @binding(3) var<storage, read_write> states: array<vec2<u32>>;
+@group(0) @binding(4) var<storage, read> lookup: array<u32, 256>;
+@group(0) @binding(5) var<storage, read> round_constants: array<vec2<u32>>;
+@group(0) @binding(6) var<storage, read> mds: array<u32, 256>;
+@group(0) @binding(7) var<uniform> permute_params: PermuteParams;
+
+const P_HI: u32 = 0xffffffffu;
+// 2^64 - p
+const EPSILON: u32 = 0xffffffffu;
+
+fn carry(sum: u32, addend: u32) -> u32 {
+    return select(0u, 1u, sum < addend);
+}
+
+// a >= p, for a < 2^64
+fn ge_p(a: vec2<u32>) -> bool {
+    return a.y == P_HI && a.x >= 1u;
+}
+
+fn canonicalize(a: vec2<u32>) -> vec2<u32> {
+    if ge_p(a) {
+        // a - p = a + EPSILON - 2^64
+        let lo = a.x + EPSILON;
+        return vec2<u32>(lo, a.y + carry(lo, EPSILON));
+    }
+    return a;
+}
+
+fn add_eps(a: vec2<u32>) -> vec2<u32> {
+    let lo = a.x + EPSILON;
+    return vec2<u32>(lo, a.y + carry(lo, EPSILON));
+}
+
+fn sub_eps(a: vec2<u32>) -> vec2<u32> {
+    let borrow = select(0u, 1u, a.x < EPSILON);
+    return vec2<u32>(a.x - EPSILON, a.y - borrow);
+}
+
+fn lt64(a: vec2<u32>, b: vec2<u32>) -> bool {
+    return a.y < b.y || (a.y == b.y && a.x < b.x);
+}
+
+fn add64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let lo = a.x + b.x;
+    return vec2<u32>(lo, a.y + b.y + carry(lo, b.x));
+}
+
+fn sub64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let borrow = select(0u, 1u, a.x < b.x);
+    return vec2<u32>(a.x - b.x, a.y - b.y - borrow);
+}
+
+fn fadd(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let sum = add64(a, b);
+    if lt64(sum, a) {
+        return canonicalize(add_eps(sum));
+    }
+    return canonicalize(sum);
+}
+
+fn fsub(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let diff = sub64(a, b);
+    if lt64(a, b) {
+        return sub_eps(diff);
+    }
+    return diff;
+}
+
+// Full 32x32 -> 64 bit product from 16-bit halves.
+fn mul32(a: u32, b: u32) -> vec2<u32> {
+    let a0 = a & 0xffffu;
+    let a1 = a >> 16u;
+    let b0 = b & 0xffffu;
+    let b1 = b >> 16u;
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+    let mid = p01 + p10;
+    let mid_carry = carry(mid, p01) << 16u;
+    let lo = p00 + (mid << 16u);
+    let hi = p11 + (mid >> 16u) + mid_carry + carry(lo, p00);
+    return vec2<u32>(lo, hi);
+}
+
+fn fmul(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let p00 = mul32(a.x, b.x);
+    let p01 = mul32(a.x, b.y);
+    let p10 = mul32(a.y, b.x);
+    let p11 = mul32(a.y, b.y);
+
+    // 128-bit product as four u32 limbs r0..r3.
+    let r0 = p00.x;
+    let s1 = p00.y + p01.x;
+    var c1 = carry(s1, p01.x);
+    let r1 = s1 + p10.x;
+    c1 = c1 + carry(r1, p10.x);
+    let s2 = p11.x + p01.y;
+    var c2 = carry(s2, p01.y);
+    let s3 = s2 + p10.y;
+    c2 = c2 + carry(s3, p10.y);
+    let r2 = s3 + c1;
+    c2 = c2 + carry(r2, c1);
+    let r3 = p11.y + c2;
+
+    // hi * 2^64 + lo with hi = r3 * 2^32 + r2, using 2^64 = 2^32 - 1 and 2^96 = -1 (mod p).
+    let lo = vec2<u32>(r0, r1);
+    let hi_hi = vec2<u32>(r3, 0u);
+    var t = sub64(lo, hi_hi);
+    if lt64(lo, hi_hi) {
+        t = sub_eps(t);
+    }
+    // r2 * (2^32 - 1) = (r2 << 32) - r2
+    let u = sub64(vec2<u32>(0u, r2), vec2<u32>(r2, 0u));
+    var r = add64(t, u);
+    if lt64(r, t) {
+        r = add_eps(r);
+    }
+    return canonicalize(r);
+}
+
+@compute @workgroup_size(64)
+fn ntt_layer(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    // Large layers are dispatched as a 2D grid to stay under the per-dimension workgroup limit.
+    let i = id.y * groups.x * 64u + id.x;
+    if i >= params.count {
+        return;
+    }
+    let m = params.m;
+    let j = i % m;
+    let k = (i / m) * 2u * m + j;
+    let u = data[k];
+    let v = fmul(data[k + m], twiddles[m - 1u + j]);
+    data[k] = fadd(u, v);
+    data[k + m] = fsub(u, v);
+}
+
+// Split-and-lookup on one u32 half of a state element, a byte at a time.
+fn lookup_word(w: u32) -> u32 {
+    return lookup[w & 0xffu]
+        | (lookup[(w >> 8u) & 0xffu] << 8u)
+        | (lookup[(w >> 16u) & 0xffu] << 16u)
+        | (lookup[w >> 24u] << 24u);
+}
+
+fn pow7(x: vec2<u32>) -> vec2<u32> {
+    let x2 = fmul(x, x);
+    let x3 = fmul(x2, x);
+    let x6 = fmul(x3, x3);
+    return fmul(x6, x);
+}
+
+// One Tip5 permutation per invocation over 16 consecutive elements of `states`. Mirrors
+// `tip5::permute`: states and round constants are in Montgomery form.
+@compute @workgroup_size(64)
+fn tip5_permute(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+) {
+    let i = id.y * groups.x * 64u + id.x;
+    if i >= permute_params.count {
+        return;
+    }
+    let base = i * 16u;
+    var state: array<vec2<u32>, 16>;
+    for (var j = 0u; j < 16u; j++) {
+        state[j] = states[base + j];
+    }
+    for (var round = 0u; round < 7u; round++) {
+        var sbox: array<vec2<u32>, 16>;
+        for (var j = 0u; j < 4u; j++) {
+            sbox[j] = vec2<u32>(lookup_word(state[j].x), lookup_word(state[j].y));
+        }
+        for (var j = 4u; j < 16u; j++) {
+            sbox[j] = pow7(state[j]);
+        }
+        for (var r = 0u; r < 16u; r++) {
+            var acc = vec2<u32>(0u, 0u);
+            for (var c = 0u; c < 16u; c++) {
+                acc = fadd(acc, fmul(vec2<u32>(mds[r * 16u + c], 0u), sbox[c]));
+            }
+            state[r] = fadd(round_constants[round * 16u + r], acc);
+        }
+    }
+    for (var j = 0u; j < 16u; j++) {
+        states[base + j] = state[j];
+    }
+}
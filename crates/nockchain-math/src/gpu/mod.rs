@@ -0,0 +1,435 @@
+//! Optional wgpu backend for the base-field NTT and batched Tip5 hashing.
+//!
+//! The adapter is picked once, the first time a large enough transform is requested: Metal on
+//! macOS, Vulkan or DX12 elsewhere. When no adapter is available, when `NOCKCHAIN_DISABLE_GPU` is
+//! set, or when a dispatch fails, callers fall back to the CPU kernels, so enabling the `gpu`
+//! feature never changes results, only where they are computed.
+
+use std::sync::mpsc;
+
+use once_cell::sync::Lazy;
+use thiserror::Error;
+use tracing::{info, warn};
+use wgpu::util::DeviceExt;
+
+use crate::belt::Belt;
+use crate::tip5::{LOOKUP_TABLE, MDS_MATRIX_I64, MONT_ROUND_CONSTANTS, STATE_SIZE};
+
+/// Transforms shorter than this stay on the CPU; below it, transfer costs outweigh the speedup.
+pub const MIN_NTT_LEN: usize = 1 << 14;
+
+/// Batches of fewer Tip5 permutations than this stay on the CPU, for the same reason.
+pub const MIN_HASH_BATCH: usize = 1 << 12;
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS_PER_DIM: u32 = 65_535;
+
+#[derive(Debug, Error)]
+pub enum GpuError {
+    #[error("no compatible GPU adapter: {0}")]
+    NoAdapter(String),
+    #[error("failed to open GPU device: {0}")]
+    Device(String),
+    #[error("input of {0} elements exceeds the device's storage buffer limit")]
+    TooLarge(usize),
+    #[error("failed to read back GPU results: {0}")]
+    Readback(String),
+}
+
+pub struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    ntt_layer: wgpu::ComputePipeline,
+    tip5_permute: wgpu::ComputePipeline,
+    tip5_lookup: wgpu::Buffer,
+    tip5_round_constants: wgpu::Buffer,
+    tip5_mds: wgpu::Buffer,
+    max_binding_size: u64,
+}
+
+static CONTEXT: Lazy<Option<GpuContext>> = Lazy::new(|| {
+    if std::env::var("NOCKCHAIN_DISABLE_GPU").is_ok() {
+        info!("GPU backend disabled by NOCKCHAIN_DISABLE_GPU");
+        return None;
+    }
+    match pollster::block_on(GpuContext::new()) {
+        Ok(context) => Some(context),
+        Err(err) => {
+            info!("GPU backend unavailable, using CPU kernels: {err}");
+            None
+        }
+    }
+});
+
+/// The process-wide GPU context, or `None` if the CPU kernels should be used.
+pub fn context() -> Option<&'static GpuContext> {
+    CONTEXT.as_ref()
+}
+
+impl GpuContext {
+    async fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                force_fallback_adapter: false,
+                compatible_surface: None,
+            })
+            .await
+            .map_err(|err| GpuError::NoAdapter(err.to_string()))?;
+        let adapter_info = adapter.get_info();
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("nockchain-math"),
+                required_features: wgpu::Features::empty(),
+                required_limits: adapter.limits(),
+                memory_hints: wgpu::MemoryHints::Performance,
+                trace: wgpu::Trace::Off,
+            })
+            .await
+            .map_err(|err| GpuError::Device(err.to_string()))?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("goldilocks"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("goldilocks.wgsl").into()),
+        });
+        let ntt_layer = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("ntt_layer"),
+            layout: None,
+            module: &module,
+            entry_point: Some("ntt_layer"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let tip5_permute = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("tip5_permute"),
+            layout: None,
+            module: &module,
+            entry_point: Some("tip5_permute"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let lookup: Vec<u32> = LOOKUP_TABLE.iter().map(|&b| b as u32).collect();
+        let tip5_lookup = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tip5 lookup"),
+            contents: bytemuck::cast_slice(&lookup),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let tip5_round_constants = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tip5 round constants"),
+            contents: bytemuck::cast_slice(&MONT_ROUND_CONSTANTS),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let mds: Vec<u32> = MDS_MATRIX_I64
+            .as_flattened()
+            .iter()
+            .map(|&m| m as u32)
+            .collect();
+        let tip5_mds = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("tip5 mds"),
+            contents: bytemuck::cast_slice(&mds),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let max_binding_size = device.limits().max_storage_buffer_binding_size as u64;
+        info!(
+            "GPU backend using {} ({:?})",
+            adapter_info.name, adapter_info.backend
+        );
+        Ok(Self {
+            device,
+            queue,
+            ntt_layer,
+            tip5_permute,
+            tip5_lookup,
+            tip5_round_constants,
+            tip5_mds,
+            max_binding_size,
+        })
+    }
+
+    /// Run every butterfly layer of a radix-2 NTT over `x`, which must already be in
+    /// bit-reversed order, using a table from [`crate::bpoly::bp_ntt_twiddles`]. `x` is only
+    /// written if the whole transform succeeds.
+    pub fn ntt_bitreversed(&self, x: &mut [Belt], twiddles: &[Belt]) -> Result<(), GpuError> {
+        let n = x.len();
+        debug_assert!(n.is_power_of_two() && n >= 2);
+        let bytes = (n * std::mem::size_of::<u64>()) as u64;
+        if bytes > self.max_binding_size {
+            return Err(GpuError::TooLarge(n));
+        }
+
+        let data = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("ntt data"),
+                contents: bytemuck::cast_slice(as_u64s(x)),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            });
+        let twiddles = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("ntt twiddles"),
+                contents: bytemuck::cast_slice(as_u64s(twiddles)),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+
+        let count = (n / 2) as u32;
+        let (groups_x, groups_y) = grid(count);
+        let layout = self.ntt_layer.get_bind_group_layout(0);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("ntt") });
+        let mut m = 1u32;
+        while (m as usize) < n {
+            // `m`, `count`, and padding to the 16-byte uniform alignment.
+            let params: [u32; 4] = [m, count, 0, 0];
+            let params = self
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("ntt params"),
+                    contents: bytemuck::cast_slice(&params),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("ntt layer"),
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: data.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: twiddles.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params.as_entire_binding(),
+                    },
+                ],
+            });
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("ntt layer"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.ntt_layer);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups_x, groups_y, 1);
+            drop(pass);
+            m *= 2;
+        }
+        self.submit_and_read(encoder, &data, as_u64s_mut(x))
+    }
+
+    /// Apply [`crate::tip5::permute`] to every state in `states`, which are in Montgomery form
+    /// as the sponge keeps them. `states` is only written if every permutation succeeds.
+    pub fn tip5_permute(&self, states: &mut [[u64; STATE_SIZE]]) -> Result<(), GpuError> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        let bytes = std::mem::size_of_val(states) as u64;
+        if bytes > self.max_binding_size || states.len() > u32::MAX as usize {
+            return Err(GpuError::TooLarge(states.len() * STATE_SIZE));
+        }
+
+        let data = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("tip5 states"),
+                contents: bytemuck::cast_slice(states),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            });
+        let count = states.len() as u32;
+        // `count` and padding to the 16-byte uniform alignment.
+        let params: [u32; 4] = [count, 0, 0, 0];
+        let params = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("tip5 params"),
+                contents: bytemuck::cast_slice(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("tip5 permute"),
+            layout: &self.tip5_permute.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: data.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: self.tip5_lookup.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: self.tip5_round_constants.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 6,
+                    resource: self.tip5_mds.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 7,
+                    resource: params.as_entire_binding(),
+                },
+            ],
+        });
+
+        let (groups_x, groups_y) = grid(count);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("tip5"),
+            });
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("tip5 permute"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.tip5_permute);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(groups_x, groups_y, 1);
+        drop(pass);
+        self.submit_and_read(encoder, &data, states.as_flattened_mut())
+    }
+
+    /// Submit `encoder` followed by a copy of `data` to a staging buffer, wait for it, and read
+    /// the copy into `out`.
+    fn submit_and_read(
+        &self,
+        mut encoder: wgpu::CommandEncoder,
+        data: &wgpu::Buffer,
+        out: &mut [u64],
+    ) -> Result<(), GpuError> {
+        let bytes = std::mem::size_of_val(out) as u64;
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: bytes,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        encoder.copy_buffer_to_buffer(data, 0, &readback, 0, bytes);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (tx, rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |res| {
+            let _ = tx.send(res);
+        });
+        self.device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|err| GpuError::Readback(err.to_string()))?;
+        rx.recv()
+            .map_err(|err| GpuError::Readback(err.to_string()))?
+            .map_err(|err| GpuError::Readback(err.to_string()))?;
+        out.copy_from_slice(bytemuck::cast_slice(&slice.get_mapped_range()));
+        readback.unmap();
+        Ok(())
+    }
+}
+
+/// Workgroups for one invocation per item, as a 2D grid under the per-dimension limit.
+fn grid(count: u32) -> (u32, u32) {
+    let groups = count.div_ceil(WORKGROUP_SIZE).max(1);
+    let groups_x = groups.min(MAX_WORKGROUPS_PER_DIM);
+    (groups_x, groups.div_ceil(groups_x))
+}
+
+/// Run the GPU NTT if a device is available, logging and reporting `false` on failure so the
+/// caller can fall back to the CPU.
+pub fn try_ntt_bitreversed(x: &mut [Belt], twiddles: &[Belt]) -> bool {
+    let Some(gpu) = context() else {
+        return false;
+    };
+    match gpu.ntt_bitreversed(x, twiddles) {
+        Ok(()) => true,
+        Err(err) => {
+            warn!("GPU NTT failed, falling back to CPU: {err}");
+            false
+        }
+    }
+}
+
+/// Run the Tip5 permutations on the GPU if a device is available, logging and reporting `false`
+/// on failure so the caller can fall back to the CPU.
+pub fn try_tip5_permute(states: &mut [[u64; STATE_SIZE]]) -> bool {
+    let Some(gpu) = context() else {
+        return false;
+    };
+    match gpu.tip5_permute(states) {
+        Ok(()) => true,
+        Err(err) => {
+            warn!("GPU Tip5 failed, falling back to CPU: {err}");
+            false
+        }
+    }
+}
+
+fn as_u64s(x: &[Belt]) -> &[u64] {
+    // SAFETY: `Belt` is `#[repr(transparent)]` over `u64`.
+    unsafe { std::slice::from_raw_parts(x.as_ptr() as *const u64, x.len()) }
+}
+
+fn as_u64s_mut(x: &mut [Belt]) -> &mut [u64] {
+    // SAFETY: `Belt` is `#[repr(transparent)]` over `u64`.
+    unsafe { std::slice::from_raw_parts_mut(x.as_mut_ptr() as *mut u64, x.len()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::belt::PRIME;
+    use crate::bpoly::{bp_ntt_twiddles, ntt_bitreversed};
+    use crate::tip5::permute;
+
+    /// splitmix64, reduced into the field.
+    fn random_belts(mut seed: u64, n: usize) -> Vec<Belt> {
+        (0..n)
+            .map(|_| {
+                seed = seed.wrapping_add(0x9e3779b97f4a7c15);
+                let mut z = seed;
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+                Belt((z ^ (z >> 31)) % PRIME)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ntt_matches_cpu() {
+        let Some(gpu) = context() else {
+            eprintln!("no GPU adapter, skipping");
+            return;
+        };
+        for (seed, n) in [(1, MIN_NTT_LEN), (2, 2 * MIN_NTT_LEN), (3, 16 * MIN_NTT_LEN)] {
+            let root = Belt(n as u64).ordered_root().expect("ordered root");
+            let twiddles = bp_ntt_twiddles(n, &root);
+            let mut expected = random_belts(seed, n);
+            let mut actual = expected.clone();
+            ntt_bitreversed(&mut expected, &twiddles);
+            gpu.ntt_bitreversed(&mut actual, &twiddles)
+                .expect("GPU NTT");
+            assert_eq!(actual, expected, "n = {n}");
+        }
+    }
+
+    #[test]
+    fn tip5_permute_matches_cpu() {
+        let Some(gpu) = context() else {
+            eprintln!("no GPU adapter, skipping");
+            return;
+        };
+        for (seed, n) in [(4, MIN_HASH_BATCH), (5, 3 * MIN_HASH_BATCH + 1)] {
+            let mut expected: Vec<[u64; STATE_SIZE]> = random_belts(seed, n * STATE_SIZE)
+                .chunks_exact(STATE_SIZE)
+                .map(|chunk| std::array::from_fn(|i| chunk[i].0))
+                .collect();
+            expected[0] = [0; STATE_SIZE];
+            expected[1] = [PRIME - 1; STATE_SIZE];
+            let mut actual = expected.clone();
+            expected.iter_mut().for_each(permute);
+            gpu.tip5_permute(&mut actual).expect("GPU Tip5");
+            assert_eq!(actual, expected, "n = {n}");
+        }
+    }
+}
@@ -1,15 +1,19 @@
 #![feature(cold_path)]
+#![feature(portable_simd)]
 pub mod belt;
 pub mod bpoly;
 pub mod convert;
 pub mod crypto;
 pub mod felt;
 pub mod fpoly;
+#[cfg(feature = "gpu")]
+pub mod gpu;
 pub mod handle;
 pub mod mary;
 pub mod noun_ext;
 pub mod poly;
 pub mod shape;
+pub mod simd;
 pub mod structs;
 pub mod tip5;
 pub mod zoon;
@@ -0,0 +1,436 @@
+//! Packed base-field kernels for the NTT, pointwise polynomial arithmetic and evaluation.
+//!
+//! Goldilocks products don't fit a native SIMD multiply, so [`mul`] builds the 128-bit product
+//! from four 32×32 lane multiplies (`vpmuludq` on AVX2, `umull` on NEON) and folds it with the same
+//! `2^64 ≡ 2^32 - 1` identity as [`reduce_159`](crate::belt::reduce_159). Every kernel returns
+//! canonical elements, so results are bit-for-bit identical to the scalar code.
+//!
+//! The kernels are written once against [`std::simd`] and instantiated per target: AVX2 is chosen
+//! at runtime on x86_64, NEON is baseline on aarch64, and everything else takes the scalar loop.
+
+use std::simd::cmp::SimdPartialOrd;
+use std::simd::Simd;
+
+use once_cell::sync::Lazy;
+
+use crate::belt::{Belt, PRIME};
+
+pub const LANES: usize = 4;
+
+type Packed = Simd<u64, LANES>;
+
+/// `2^64 - PRIME`, what an overflowing add or sub has to be corrected by.
+const EPSILON: u64 = 0xFFFF_FFFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdLevel {
+    Scalar,
+    Avx2,
+    Neon,
+}
+
+static SIMD_LEVEL: Lazy<SimdLevel> = Lazy::new(detect);
+
+fn detect() -> SimdLevel {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        return SimdLevel::Avx2;
+    }
+    if cfg!(target_arch = "aarch64") {
+        SimdLevel::Neon
+    } else {
+        SimdLevel::Scalar
+    }
+}
+
+/// The instruction set the kernels in this module dispatch to on this machine.
+pub fn simd_level() -> SimdLevel {
+    *SIMD_LEVEL
+}
+
+/// Expands to a match on [`simd_level`] that runs `$kernel` compiled for AVX2, compiled for the
+/// (NEON) baseline, or falls back to `$scalar`.
+macro_rules! dispatch {
+    ($kernel:ident, $scalar:ident, ($($arg:expr),*)) => {{
+        match simd_level() {
+            #[cfg(target_arch = "x86_64")]
+            SimdLevel::Avx2 => {
+                #[target_feature(enable = "avx2")]
+                unsafe fn avx2(res: &mut [u64], a: &[u64], b: &[u64]) {
+                    $kernel(res, a, b)
+                }
+                // SAFETY: `SimdLevel::Avx2` is only selected when the CPU reports AVX2.
+                unsafe { avx2($($arg),*) }
+            }
+            #[cfg(target_arch = "aarch64")]
+            SimdLevel::Neon => $kernel($($arg),*),
+            _ => $scalar($($arg),*),
+        }
+    }};
+}
+
+#[inline(always)]
+fn canonicalize(x: Packed) -> Packed {
+    let p = Packed::splat(PRIME);
+    x.simd_ge(p).select(x - p, x)
+}
+
+#[inline(always)]
+fn add(a: Packed, b: Packed) -> Packed {
+    let sum = a + b;
+    let overflow = sum.simd_lt(a);
+    canonicalize(overflow.select(sum + Packed::splat(EPSILON), sum))
+}
+
+#[inline(always)]
+fn sub(a: Packed, b: Packed) -> Packed {
+    let diff = a - b;
+    let borrow = a.simd_lt(b);
+    borrow.select(diff - Packed::splat(EPSILON), diff)
+}
+
+#[inline(always)]
+fn mul(a: Packed, b: Packed) -> Packed {
+    let low_mask = Packed::splat(0xFFFF_FFFF);
+    let (a0, a1) = (a & low_mask, a >> 32);
+    let (b0, b1) = (b & low_mask, b >> 32);
+
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+
+    let mid = p01 + p10;
+    let mid_carry = mid
+        .simd_lt(p01)
+        .select(Packed::splat(1 << 32), Packed::splat(0));
+    let lo = p00 + (mid << 32);
+    let lo_carry = lo.simd_lt(p00).select(Packed::splat(1), Packed::splat(0));
+    let hi = p11 + (mid >> 32) + mid_carry + lo_carry;
+
+    reduce(lo, hi)
+}
+
+/// Reduce `hi * 2^64 + lo` into the field, as `reduce_159` does for one element.
+#[inline(always)]
+fn reduce(lo: Packed, hi: Packed) -> Packed {
+    let hi_hi = hi >> 32;
+    let hi_lo = hi & Packed::splat(0xFFFF_FFFF);
+
+    let t = lo - hi_hi;
+    let t = lo.simd_lt(hi_hi).select(t - Packed::splat(EPSILON), t);
+    let u = (hi_lo << 32) - hi_lo;
+    let r = t + u;
+    let r = r.simd_lt(t).select(r + Packed::splat(EPSILON), r);
+    canonicalize(r)
+}
+
+fn as_u64s(x: &[Belt]) -> &[u64] {
+    // SAFETY: `Belt` is `#[repr(transparent)]` over `u64`.
+    unsafe { std::slice::from_raw_parts(x.as_ptr() as *const u64, x.len()) }
+}
+
+fn as_u64s_mut(x: &mut [Belt]) -> &mut [u64] {
+    // SAFETY: `Belt` is `#[repr(transparent)]` over `u64`.
+    unsafe { std::slice::from_raw_parts_mut(x.as_mut_ptr() as *mut u64, x.len()) }
+}
+
+/// One layer of radix-2 butterflies: `(lo, hi) <- (lo + w·hi, lo - w·hi)` with `w = twiddles[j]`.
+///
+/// `lo`, `hi` and `twiddles` must have the same length.
+pub fn ntt_butterflies(lo: &mut [Belt], hi: &mut [Belt], twiddles: &[Belt]) {
+    debug_assert_eq!(lo.len(), hi.len());
+    debug_assert_eq!(lo.len(), twiddles.len());
+    if lo.len() < LANES {
+        return butterflies_scalar(as_u64s_mut(lo), as_u64s_mut(hi), as_u64s(twiddles));
+    }
+    let (lo, hi, twiddles) = (as_u64s_mut(lo), as_u64s_mut(hi), as_u64s(twiddles));
+    match simd_level() {
+        #[cfg(target_arch = "x86_64")]
+        SimdLevel::Avx2 => {
+            #[target_feature(enable = "avx2")]
+            unsafe fn avx2(lo: &mut [u64], hi: &mut [u64], twiddles: &[u64]) {
+                butterflies_packed(lo, hi, twiddles)
+            }
+            // SAFETY: `SimdLevel::Avx2` is only selected when the CPU reports AVX2.
+            unsafe { avx2(lo, hi, twiddles) }
+        }
+        #[cfg(target_arch = "aarch64")]
+        SimdLevel::Neon => butterflies_packed(lo, hi, twiddles),
+        _ => butterflies_scalar(lo, hi, twiddles),
+    }
+}
+
+#[inline(always)]
+fn butterflies_packed(lo: &mut [u64], hi: &mut [u64], twiddles: &[u64]) {
+    let mut lo_chunks = lo.chunks_exact_mut(LANES);
+    let mut hi_chunks = hi.chunks_exact_mut(LANES);
+    let mut tw_chunks = twiddles.chunks_exact(LANES);
+    for ((lo, hi), tw) in (&mut lo_chunks).zip(&mut hi_chunks).zip(&mut tw_chunks) {
+        let u = Packed::from_slice(lo);
+        let v = mul(Packed::from_slice(hi), Packed::from_slice(tw));
+        add(u, v).copy_to_slice(lo);
+        sub(u, v).copy_to_slice(hi);
+    }
+    butterflies_scalar(
+        lo_chunks.into_remainder(),
+        hi_chunks.into_remainder(),
+        tw_chunks.remainder(),
+    );
+}
+
+#[inline(always)]
+fn butterflies_scalar(lo: &mut [u64], hi: &mut [u64], twiddles: &[u64]) {
+    for ((lo, hi), tw) in lo.iter_mut().zip(hi.iter_mut()).zip(twiddles) {
+        let u = Belt(*lo);
+        let v = Belt(*hi) * Belt(*tw);
+        *lo = (u + v).0;
+        *hi = (u - v).0;
+    }
+}
+
+/// Pointwise product of `a` and `b` into `res`, over the shortest of the three.
+pub fn hadamard(a: &[Belt], b: &[Belt], res: &mut [Belt]) {
+    let len = res.len().min(a.len()).min(b.len());
+    let (a, b, res) = (
+        as_u64s(&a[..len]),
+        as_u64s(&b[..len]),
+        as_u64s_mut(&mut res[..len]),
+    );
+    dispatch!(hadamard_packed, hadamard_scalar, (res, a, b));
+}
+
+#[inline(always)]
+fn hadamard_packed(res: &mut [u64], a: &[u64], b: &[u64]) {
+    let mut res_chunks = res.chunks_exact_mut(LANES);
+    let mut a_chunks = a.chunks_exact(LANES);
+    let mut b_chunks = b.chunks_exact(LANES);
+    for ((res, a), b) in (&mut res_chunks).zip(&mut a_chunks).zip(&mut b_chunks) {
+        mul(Packed::from_slice(a), Packed::from_slice(b)).copy_to_slice(res);
+    }
+    hadamard_scalar(
+        res_chunks.into_remainder(),
+        a_chunks.remainder(),
+        b_chunks.remainder(),
+    );
+}
+
+#[inline(always)]
+fn hadamard_scalar(res: &mut [u64], a: &[u64], b: &[u64]) {
+    for ((res, a), b) in res.iter_mut().zip(a).zip(b) {
+        *res = (Belt(*a) * Belt(*b)).0;
+    }
+}
+
+/// `res[i] = scalar · b[i]`, over the shorter of `b` and `res`.
+pub fn scale(scalar: Belt, b: &[Belt], res: &mut [Belt]) {
+    let len = res.len().min(b.len());
+    let scalar = [scalar.0];
+    let (b, res) = (as_u64s(&b[..len]), as_u64s_mut(&mut res[..len]));
+    dispatch!(scale_packed, scale_scalar, (res, b, &scalar));
+}
+
+#[inline(always)]
+fn scale_packed(res: &mut [u64], b: &[u64], scalar: &[u64]) {
+    let s = Packed::splat(scalar[0]);
+    let mut res_chunks = res.chunks_exact_mut(LANES);
+    let mut b_chunks = b.chunks_exact(LANES);
+    for (res, b) in (&mut res_chunks).zip(&mut b_chunks) {
+        mul(s, Packed::from_slice(b)).copy_to_slice(res);
+    }
+    scale_scalar(res_chunks.into_remainder(), b_chunks.remainder(), scalar);
+}
+
+#[inline(always)]
+fn scale_scalar(res: &mut [u64], b: &[u64], scalar: &[u64]) {
+    for (res, b) in res.iter_mut().zip(b) {
+        *res = (Belt(scalar[0]) * Belt(*b)).0;
+    }
+}
+
+/// `res[i] = a[i] · offset^i` for `i < a.len()`, the coset shift ahead of a coset NTT.
+pub fn shift(a: &[Belt], offset: Belt, res: &mut [Belt]) {
+    let len = a.len();
+    let offset = [offset.0];
+    let (a, res) = (as_u64s(a), as_u64s_mut(&mut res[..len]));
+    dispatch!(shift_packed, shift_scalar, (res, a, &offset));
+}
+
+#[inline(always)]
+fn shift_packed(res: &mut [u64], a: &[u64], offset: &[u64]) {
+    let b = Belt(offset[0]);
+    let b2 = b * b;
+    let mut powers = Packed::from_array([1, b.0, b2.0, (b2 * b).0]);
+    let step = Packed::splat((b2 * b2).0);
+    let mut res_chunks = res.chunks_exact_mut(LANES);
+    let mut a_chunks = a.chunks_exact(LANES);
+    for (res, a) in (&mut res_chunks).zip(&mut a_chunks) {
+        mul(Packed::from_slice(a), powers).copy_to_slice(res);
+        powers = mul(powers, step);
+    }
+    let res = res_chunks.into_remainder();
+    let mut power = Belt(powers[0]);
+    for (res, a) in res.iter_mut().zip(a_chunks.remainder()) {
+        *res = (Belt(*a) * power).0;
+        power = power * b;
+    }
+}
+
+#[inline(always)]
+fn shift_scalar(res: &mut [u64], a: &[u64], offset: &[u64]) {
+    let mut power = Belt(1);
+    for (res, a) in res.iter_mut().zip(a) {
+        *res = (Belt(*a) * power).0;
+        power = power * Belt(offset[0]);
+    }
+}
+
+/// Evaluate the polynomial with coefficients `coeffs` (lowest degree first) at `x`.
+///
+/// The packed path splits the polynomial into [`LANES`] interleaved ones in `x^LANES`, runs
+/// Horner's rule on all of them at once and recombines the lanes with `1, x, x^2, x^3`.
+pub fn eval(coeffs: &[Belt], x: Belt) -> Belt {
+    if coeffs.len() < 2 * LANES {
+        return Belt(eval_scalar(as_u64s(coeffs), x.0));
+    }
+    let (coeffs, x) = (as_u64s(coeffs), [x.0]);
+    let mut res = [0u64];
+    dispatch!(eval_packed, eval_scalar_into, (&mut res, coeffs, &x));
+    Belt(res[0])
+}
+
+#[inline(always)]
+fn eval_packed(res: &mut [u64], coeffs: &[u64], x: &[u64]) {
+    let x = Belt(x[0]);
+    let x2 = x * x;
+    let x4 = Packed::splat((x2 * x2).0);
+    let mut chunks = coeffs.chunks(LANES).rev();
+    // Only the highest chunk can be short; its missing coefficients are zero.
+    let mut top = [0u64; LANES];
+    let first = chunks.next().unwrap_or(&[]);
+    top[..first.len()].copy_from_slice(first);
+    let mut acc = Packed::from_array(top);
+    for chunk in chunks {
+        acc = add(mul(acc, x4), Packed::from_slice(chunk));
+    }
+    let powers = Packed::from_array([1, x.0, x2.0, (x2 * x).0]);
+    let lanes = mul(acc, powers).to_array();
+    res[0] = lanes.iter().fold(Belt(0), |sum, lane| sum + Belt(*lane)).0;
+}
+
+#[inline(always)]
+fn eval_scalar_into(res: &mut [u64], coeffs: &[u64], x: &[u64]) {
+    res[0] = eval_scalar(coeffs, x[0]);
+}
+
+#[inline(always)]
+fn eval_scalar(coeffs: &[u64], x: u64) -> u64 {
+    coeffs
+        .iter()
+        .rev()
+        .fold(Belt(0), |acc, c| acc * Belt(x) + Belt(*c))
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::{quickcheck, TestResult};
+
+    use super::*;
+
+    fn based(xs: Vec<u64>) -> Vec<Belt> {
+        xs.into_iter().map(|x| Belt(x % PRIME)).collect()
+    }
+
+    fn packed_mul(a: u64, b: u64) -> u64 {
+        mul(Packed::splat(a), Packed::splat(b))[0]
+    }
+
+    #[test]
+    fn packed_arithmetic_edge_cases() {
+        let edges = [0, 1, 2, EPSILON, EPSILON + 1, 1 << 32, PRIME - 2, PRIME - 1];
+        for &a in &edges {
+            for &b in &edges {
+                let (x, y) = (Packed::splat(a), Packed::splat(b));
+                assert_eq!(add(x, y)[0], (Belt(a) + Belt(b)).0, "{a} + {b}");
+                assert_eq!(sub(x, y)[0], (Belt(a) - Belt(b)).0, "{a} - {b}");
+                assert_eq!(packed_mul(a, b), (Belt(a) * Belt(b)).0, "{a} * {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn ntt_matches_naive_evaluation() {
+        for log_n in [1, 2, 3, 5, 9] {
+            let n = 1u64 << log_n;
+            let root = Belt(n).ordered_root().expect("ordered root");
+            let coeffs: Vec<Belt> = (0..n).map(|i| Belt(i * i + 7)).collect();
+            let evals = crate::bpoly::bp_ntt(&coeffs, &root);
+            let mut x = Belt(1);
+            for eval in evals {
+                let expected = coeffs.iter().rev().fold(Belt(0), |acc, c| acc * x + *c);
+                assert_eq!(eval, expected, "n = {n}");
+                x = x * root;
+            }
+        }
+    }
+
+    #[test]
+    fn eval_matches_horner_across_lengths() {
+        for len in 0..=4 * LANES + 1 {
+            let coeffs: Vec<Belt> = (0..len as u64).map(|i| Belt(PRIME - 1 - i * i)).collect();
+            for x in [Belt(0), Belt(1), Belt(PRIME - 1), Belt(EPSILON), Belt(7)] {
+                let expected = coeffs.iter().rev().fold(Belt(0), |acc, c| acc * x + *c);
+                assert_eq!(eval(&coeffs, x), expected, "len = {len}, x = {}", x.0);
+            }
+        }
+    }
+
+    quickcheck! {
+        fn packed_mul_matches_scalar(a: u64, b: u64) -> bool {
+            let (a, b) = (a % PRIME, b % PRIME);
+            packed_mul(a, b) == (Belt(a) * Belt(b)).0
+        }
+
+        fn butterflies_match_scalar(lo: Vec<u64>, hi: Vec<u64>, tw: Vec<u64>) -> TestResult {
+            let len = lo.len().min(hi.len()).min(tw.len());
+            let (mut lo, mut hi, tw) = (based(lo), based(hi), based(tw));
+            lo.truncate(len);
+            hi.truncate(len);
+            let tw = &tw[..len];
+            let (mut lo_s, mut hi_s) = (lo.clone(), hi.clone());
+            butterflies_scalar(as_u64s_mut(&mut lo_s), as_u64s_mut(&mut hi_s), as_u64s(tw));
+            ntt_butterflies(&mut lo, &mut hi, tw);
+            TestResult::from_bool(lo == lo_s && hi == hi_s)
+        }
+
+        fn shift_matches_scalar(a: Vec<u64>, offset: u64) -> bool {
+            let a = based(a);
+            let offset = Belt(offset % PRIME);
+            let mut res = vec![Belt(0); a.len()];
+            shift(&a, offset, &mut res);
+            let mut expected = vec![Belt(0); a.len()];
+            shift_scalar(as_u64s_mut(&mut expected), as_u64s(&a), &[offset.0]);
+            res == expected
+        }
+
+        fn eval_matches_horner(coeffs: Vec<u64>, x: u64) -> bool {
+            let coeffs = based(coeffs);
+            let x = Belt(x % PRIME);
+            let expected = coeffs.iter().rev().fold(Belt(0), |acc, c| acc * x + *c);
+            eval(&coeffs, x) == expected
+        }
+
+        fn hadamard_and_scale_match_scalar(a: Vec<u64>, b: Vec<u64>, s: u64) -> bool {
+            let (a, b) = (based(a), based(b));
+            let len = a.len().min(b.len());
+            let mut res = vec![Belt(0); len];
+            hadamard(&a, &b, &mut res);
+            let hadamard_ok = res.iter().zip(a.iter().zip(&b)).all(|(r, (x, y))| *r == *x * *y);
+            let s = Belt(s % PRIME);
+            let mut res = vec![Belt(0); a.len()];
+            scale(s, &a, &mut res);
+            hadamard_ok && res.iter().zip(&a).all(|(r, x)| *r == s * *x)
+        }
+    }
+}
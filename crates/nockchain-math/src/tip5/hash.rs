@@ -124,6 +124,32 @@ pub fn hash_10(input_vec: &mut Vec<Belt>) -> [u64; 5] {
     tip5_calc_digest(&sponge)
 }
 
+/// [`hash_10`] over many independent inputs. With the `gpu` feature, large batches are permuted
+/// on the GPU when one is available.
+pub fn hash_10_batch(inputs: &[[Belt; RATE]]) -> Vec<[u64; DIGEST_LENGTH]> {
+    let mut sponges: Vec<[u64; STATE_SIZE]> = inputs
+        .iter()
+        .map(|input| {
+            let mut sponge = create_init_sponge_fixed();
+            for (lane, belt) in sponge.iter_mut().zip(input) {
+                *lane = montify(belt.0);
+            }
+            sponge
+        })
+        .collect();
+
+    #[cfg(feature = "gpu")]
+    let permuted =
+        sponges.len() >= crate::gpu::MIN_HASH_BATCH && crate::gpu::try_tip5_permute(&mut sponges);
+    #[cfg(not(feature = "gpu"))]
+    let permuted = false;
+    if !permuted {
+        sponges.iter_mut().for_each(permute);
+    }
+
+    sponges.iter().map(tip5_calc_digest).collect()
+}
+
 pub fn hash_noun_varlen<A: NounAllocator>(stack: &mut A, n: Noun) -> Result<Noun, JetErr> {
     let leaf = leaf_sequence(stack, n)?;
     let dyck = dyck(stack, n)?;
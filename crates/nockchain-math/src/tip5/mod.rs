@@ -17,7 +17,7 @@ pub const R_MOD_P: u64 = 4294967295;
 pub const RP: u128 = 0xffffffff000000010000000000000000;
 pub const P: u64 = 0xffffffff00000001;
 
-const LOOKUP_TABLE: [u8; 256] = [
+pub(crate) const LOOKUP_TABLE: [u8; 256] = [
     0, 7, 26, 63, 124, 215, 85, 254, 214, 228, 45, 185, 140, 173, 33, 240, 29, 177, 176, 32, 8,
     110, 87, 202, 204, 99, 150, 106, 230, 14, 235, 128, 213, 239, 212, 138, 23, 130, 208, 6, 44,
     71, 93, 116, 146, 189, 251, 81, 199, 97, 38, 28, 73, 179, 95, 84, 152, 48, 35, 119, 49, 88,
@@ -71,7 +71,18 @@ const ROUND_CONSTANTS: [u64; NUM_ROUNDS * STATE_SIZE] = [
     7134876918849821827, 5796994175286958720, 7251651436095127661, 4565856221886323991,
 ];
 
-const MDS_MATRIX_I64: [[i64; STATE_SIZE]; STATE_SIZE] = [
+/// [`ROUND_CONSTANTS`] in Montgomery form, as [`permute`] adds them.
+pub(crate) const MONT_ROUND_CONSTANTS: [u64; NUM_ROUNDS * STATE_SIZE] = {
+    let mut mont = [0u64; NUM_ROUNDS * STATE_SIZE];
+    let mut i = 0;
+    while i < mont.len() {
+        mont[i] = (((ROUND_CONSTANTS[i] as u128) * R) % PRIME_128) as u64;
+        i += 1;
+    }
+    mont
+};
+
+pub(crate) const MDS_MATRIX_I64: [[i64; STATE_SIZE]; STATE_SIZE] = [
     [
         61402, 17845, 26798, 59689, 12021, 40901, 41351, 27521, 56951, 12034, 53865, 43244, 7454,
         33823, 28750, 1108,
@@ -144,8 +155,7 @@ pub fn permute(sponge: &mut [u64; 16]) {
         let b = linear_layer(&a);
 
         for j in 0..STATE_SIZE {
-            let r_cons = (((ROUND_CONSTANTS[i * STATE_SIZE + j] as u128) * R) % PRIME_128) as u64;
-            sponge[j] = badd(r_cons, b[j]);
+            sponge[j] = badd(MONT_ROUND_CONSTANTS[i * STATE_SIZE + j], b[j]);
         }
     }
 }
@@ -0,0 +1,279 @@
+//! Versioned envelopes for persisted and RPC-transported values.
+//!
+//! An envelope is the jam of `[tag=@tas version=@ud payload=*]`. The tag names the type, and the
+//! version lets readers decode payloads written before the type last changed: each
+//! [`Versioned`] type declares its current version and how to upgrade older payloads.
+
+use bytes::Bytes;
+use nockapp::noun::slab::NounSlab;
+use nockapp::utils::make_tas;
+use nockapp::AtomExt;
+use nockvm::noun::{Atom, Noun};
+use noun_serde::{NounDecode, NounDecodeError, NounEncode};
+use thiserror::Error;
+
+use crate::tx_engine::common::Page;
+use crate::tx_engine::{v0, v1};
+
+#[derive(Debug, Error)]
+pub enum EnvelopeError {
+    #[error("envelope is malformed: {0}")]
+    Malformed(String),
+    #[error("expected envelope tag %{expected}, found %{found}")]
+    WrongTag {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("%{tag} version {version} is newer than the supported version {supported}")]
+    FromTheFuture {
+        tag: &'static str,
+        version: u32,
+        supported: u32,
+    },
+    #[error("%{tag} version {version} is no longer supported")]
+    Unsupported { tag: &'static str, version: u32 },
+    #[error("failed to decode %{tag} v{version} payload: {source}")]
+    Decode {
+        tag: &'static str,
+        version: u32,
+        #[source]
+        source: NounDecodeError,
+    },
+}
+
+/// A type that can be sealed into an [`Envelope`].
+pub trait Versioned: NounEncode + NounDecode + Sized {
+    /// Type tag written into the envelope head. Must be a valid `@tas`.
+    const TAG: &'static str;
+    /// Version written by [`seal`]; payloads at this version decode with [`NounDecode`].
+    const VERSION: u32;
+
+    /// Decode a payload written at an older `version`.
+    fn decode_legacy(version: u32, _payload: &Noun) -> Result<Self, EnvelopeError> {
+        Err(EnvelopeError::Unsupported {
+            tag: Self::TAG,
+            version,
+        })
+    }
+}
+
+/// A decoded envelope header with its still-encoded payload.
+#[derive(Debug)]
+pub struct Envelope {
+    pub tag: String,
+    pub version: u32,
+    slab: NounSlab,
+    payload: Noun,
+}
+
+impl Envelope {
+    /// Parse the envelope header without interpreting the payload.
+    pub fn parse(jam: Bytes) -> Result<Self, EnvelopeError> {
+        let mut slab: NounSlab = NounSlab::new();
+        let root = slab
+            .cue_into(jam)
+            .map_err(|err| EnvelopeError::Malformed(err.to_string()))?;
+        let malformed = |what: &str| EnvelopeError::Malformed(what.to_string());
+        let cell = root
+            .as_cell()
+            .map_err(|_| malformed("root is not a cell"))?;
+        let tag = cell
+            .head()
+            .as_atom()
+            .map_err(|_| malformed("tag is not an atom"))?
+            .into_string()
+            .map_err(|_| malformed("tag is not a valid cord"))?;
+        let rest = cell
+            .tail()
+            .as_cell()
+            .map_err(|_| malformed("missing version"))?;
+        let version = rest
+            .head()
+            .as_atom()
+            .ok()
+            .and_then(|atom| atom.as_u64().ok())
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| malformed("version is not a u32"))?;
+        let payload = rest.tail();
+        Ok(Self {
+            tag,
+            version,
+            slab,
+            payload,
+        })
+    }
+
+    /// Decode the payload as `T`, upgrading older versions through [`Versioned::decode_legacy`].
+    pub fn open<T: Versioned>(&self) -> Result<T, EnvelopeError> {
+        if self.tag != T::TAG {
+            return Err(EnvelopeError::WrongTag {
+                expected: T::TAG,
+                found: self.tag.clone(),
+            });
+        }
+        if self.version > T::VERSION {
+            return Err(EnvelopeError::FromTheFuture {
+                tag: T::TAG,
+                version: self.version,
+                supported: T::VERSION,
+            });
+        }
+        if self.version == T::VERSION {
+            return T::from_noun(&self.payload).map_err(|source| EnvelopeError::Decode {
+                tag: T::TAG,
+                version: self.version,
+                source,
+            });
+        }
+        T::decode_legacy(self.version, &self.payload)
+    }
+
+    /// The slab holding the payload, for callers that need the raw noun.
+    pub fn slab(&self) -> &NounSlab {
+        &self.slab
+    }
+}
+
+/// Seal `value` into a jammed envelope at its current version.
+pub fn seal<T: Versioned>(value: &T) -> Bytes {
+    let mut slab: NounSlab = NounSlab::new();
+    let tag = make_tas(&mut slab, T::TAG).as_noun();
+    let version = Atom::new(&mut slab, T::VERSION as u64).as_noun();
+    let payload = value.to_noun(&mut slab);
+    let root = nockvm::noun::T(&mut slab, &[tag, version, payload]);
+    slab.set_root(root);
+    slab.jam()
+}
+
+/// Parse and decode a jammed envelope in one step.
+pub fn open<T: Versioned>(jam: Bytes) -> Result<T, EnvelopeError> {
+    Envelope::parse(jam)?.open()
+}
+
+fn decode_at<T: NounDecode>(
+    tag: &'static str,
+    version: u32,
+    payload: &Noun,
+) -> Result<T, EnvelopeError> {
+    T::from_noun(payload).map_err(|source| EnvelopeError::Decode {
+        tag,
+        version,
+        source,
+    })
+}
+
+impl Versioned for v1::RawTx {
+    const TAG: &'static str = "raw-tx";
+    const VERSION: u32 = 1;
+}
+
+impl Versioned for v0::RawTx {
+    const TAG: &'static str = "raw-tx-v0";
+    const VERSION: u32 = 0;
+}
+
+impl Versioned for v1::Note {
+    const TAG: &'static str = "note";
+    const VERSION: u32 = 1;
+
+    fn decode_legacy(version: u32, payload: &Noun) -> Result<Self, EnvelopeError> {
+        match version {
+            0 => Ok(v1::Note::V0(decode_at(Self::TAG, version, payload)?)),
+            _ => Err(EnvelopeError::Unsupported {
+                tag: Self::TAG,
+                version,
+            }),
+        }
+    }
+}
+
+impl Versioned for v1::Balance {
+    const TAG: &'static str = "balance";
+    const VERSION: u32 = 1;
+
+    fn decode_legacy(version: u32, payload: &Noun) -> Result<Self, EnvelopeError> {
+        match version {
+            0 => {
+                let legacy: v0::Balance = decode_at(Self::TAG, version, payload)?;
+                Ok(v1::Balance(
+                    legacy
+                        .0
+                        .into_iter()
+                        .map(|(name, note)| (name, v1::Note::V0(note)))
+                        .collect(),
+                ))
+            }
+            _ => Err(EnvelopeError::Unsupported {
+                tag: Self::TAG,
+                version,
+            }),
+        }
+    }
+}
+
+impl Versioned for Page {
+    const TAG: &'static str = "page";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE_V0_JAM: &[u8] = include_bytes!("../jams/v0/note.jam");
+    const NOTE_V1_JAM: &[u8] = include_bytes!("../jams/v1/note.jam");
+
+    fn decode_fixture<T: NounDecode>(jam: &'static [u8]) -> T {
+        let mut slab: NounSlab = NounSlab::new();
+        let noun = slab
+            .cue_into(Bytes::from_static(jam))
+            .expect("fixture should cue");
+        T::from_noun(&noun).expect("fixture should decode")
+    }
+
+    /// Seal a payload under an explicit tag/version, as an older writer would have.
+    fn seal_raw<T: NounEncode>(tag: &str, version: u32, value: &T) -> Bytes {
+        let mut slab: NounSlab = NounSlab::new();
+        let tag = make_tas(&mut slab, tag).as_noun();
+        let version = Atom::new(&mut slab, version as u64).as_noun();
+        let payload = value.to_noun(&mut slab);
+        let root = nockvm::noun::T(&mut slab, &[tag, version, payload]);
+        slab.set_root(root);
+        slab.jam()
+    }
+
+    #[test]
+    fn current_version_roundtrips() {
+        let note: v1::Note = decode_fixture(NOTE_V1_JAM);
+        let sealed = seal(&note);
+        let envelope = Envelope::parse(sealed.clone()).expect("parse");
+        assert_eq!(envelope.tag, "note");
+        assert_eq!(envelope.version, 1);
+        assert_eq!(open::<v1::Note>(sealed).expect("open"), note);
+    }
+
+    #[test]
+    fn older_note_versions_upgrade() {
+        let legacy: v0::NoteV0 = decode_fixture(NOTE_V0_JAM);
+        let sealed = seal_raw("note", 0, &legacy);
+        assert_eq!(
+            open::<v1::Note>(sealed).expect("open"),
+            v1::Note::V0(legacy)
+        );
+    }
+
+    #[test]
+    fn newer_versions_and_wrong_tags_are_rejected() {
+        let note: v1::Note = decode_fixture(NOTE_V1_JAM);
+        let future = seal_raw("note", 2, &note);
+        assert!(matches!(
+            open::<v1::Note>(future),
+            Err(EnvelopeError::FromTheFuture { version: 2, .. })
+        ));
+        let sealed = seal(&note);
+        assert!(matches!(
+            open::<Page>(sealed),
+            Err(EnvelopeError::WrongTag { .. })
+        ));
+    }
+}
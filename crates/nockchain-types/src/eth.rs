@@ -1,4 +1,6 @@
-use alloy::primitives::Address as AlloyAddress;
+use alloy::primitives::{keccak256, Address as AlloyAddress};
+use alloy::signers::k256::ecdsa::VerifyingKey;
+use alloy::signers::k256::elliptic_curve::sec1::ToEncodedPoint;
 use hex::FromHex;
 use nockvm::noun::{Atom, IndirectAtom, Noun, NounAllocator};
 use noun_serde::{NounDecode, NounDecodeError, NounEncode};
@@ -44,6 +46,48 @@ impl EthAddress {
             .map_err(|err| EthAddressParseError::InvalidHex(err.to_string()))?;
         Ok(Self(bytes))
     }
+
+    /// Derives the address controlled by a secp256k1 public key.
+    ///
+    /// Accepts SEC1 compressed (33 bytes) or uncompressed (65 bytes) encodings, as well as the
+    /// raw 64-byte `x || y` form. The address is the last 20 bytes of the keccak256 of `x || y`.
+    pub fn from_pubkey(pubkey: &[u8]) -> Result<Self, EthAddressParseError> {
+        let uncompressed = match pubkey.len() {
+            64 => {
+                let mut sec1 = [0u8; 65];
+                sec1[0] = 0x04;
+                sec1[1..].copy_from_slice(pubkey);
+                Self::decompress(&sec1)?
+            }
+            _ => Self::decompress(pubkey)?,
+        };
+        let digest = keccak256(&uncompressed[1..]);
+        let mut bytes = [0u8; Self::LEN];
+        bytes.copy_from_slice(&digest[12..]);
+        Ok(Self(bytes))
+    }
+
+    /// Hex-string variant of [`EthAddress::from_pubkey`] (optional `0x` prefix).
+    pub fn from_pubkey_hex(raw: &str) -> Result<Self, EthAddressParseError> {
+        let trimmed = raw.trim();
+        let without_prefix = trimmed
+            .strip_prefix("0x")
+            .or_else(|| trimmed.strip_prefix("0X"))
+            .unwrap_or(trimmed);
+        let bytes = hex::decode(without_prefix)
+            .map_err(|err| EthAddressParseError::InvalidPublicKey(err.to_string()))?;
+        Self::from_pubkey(&bytes)
+    }
+
+    /// Validates a SEC1-encoded key is on the curve and returns its uncompressed encoding.
+    fn decompress(sec1: &[u8]) -> Result<[u8; 65], EthAddressParseError> {
+        let key = VerifyingKey::from_sec1_bytes(sec1)
+            .map_err(|err| EthAddressParseError::InvalidPublicKey(err.to_string()))?;
+        let point = key.to_encoded_point(false);
+        let mut out = [0u8; 65];
+        out.copy_from_slice(point.as_bytes());
+        Ok(out)
+    }
 }
 
 impl From<[u8; EthAddress::LEN]> for EthAddress {
@@ -131,6 +175,8 @@ pub enum EthAddressParseError {
     InvalidCharacters,
     #[error("Failed to parse EVM address: {0}")]
     InvalidHex(String),
+    #[error("Invalid secp256k1 public key: {0}")]
+    InvalidPublicKey(String),
 }
 
 #[cfg(test)]
@@ -203,6 +249,50 @@ mod tests {
         assert_eq!(decoded, addr);
     }
 
+    // Public keys for the secp256k1 private keys 1 and 2, with their well-known addresses.
+    const PUBKEY_1_X: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    const PUBKEY_1_Y: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+    const ADDRESS_1: &str = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+    const PUBKEY_2_X: &str = "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5";
+    const PUBKEY_2_Y: &str = "1ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a";
+    const ADDRESS_2: &str = "0x2b5ad5c4795c026514f8317c7a215e218dccd6cf";
+
+    #[test]
+    fn derive_address_from_uncompressed_pubkey() {
+        let addr = EthAddress::from_pubkey_hex(&format!("0x04{PUBKEY_1_X}{PUBKEY_1_Y}"))
+            .expect("valid pubkey");
+        assert_eq!(addr.to_string(), ADDRESS_1);
+
+        let addr = EthAddress::from_pubkey_hex(&format!("04{PUBKEY_2_X}{PUBKEY_2_Y}"))
+            .expect("valid pubkey");
+        assert_eq!(addr.to_string(), ADDRESS_2);
+    }
+
+    #[test]
+    fn derive_address_from_compressed_and_raw_pubkeys() {
+        let compressed = EthAddress::from_pubkey_hex(&format!("02{PUBKEY_1_X}")).expect("valid");
+        assert_eq!(compressed.to_string(), ADDRESS_1);
+
+        let raw = EthAddress::from_pubkey_hex(&format!("{PUBKEY_2_X}{PUBKEY_2_Y}")).expect("valid");
+        assert_eq!(raw.to_string(), ADDRESS_2);
+    }
+
+    #[test]
+    fn reject_invalid_pubkeys() {
+        assert!(matches!(
+            EthAddress::from_pubkey(&[0x04; 65]),
+            Err(EthAddressParseError::InvalidPublicKey(_))
+        ));
+        assert!(matches!(
+            EthAddress::from_pubkey(&[0x02; 20]),
+            Err(EthAddressParseError::InvalidPublicKey(_))
+        ));
+        assert!(matches!(
+            EthAddress::from_pubkey_hex("0xzz"),
+            Err(EthAddressParseError::InvalidPublicKey(_))
+        ));
+    }
+
     #[test]
     fn display_is_lower_hex() {
         let addr =
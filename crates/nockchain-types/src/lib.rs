@@ -1,4 +1,6 @@
+pub mod envelope;
 pub mod eth;
+pub mod stream;
 pub mod tx_engine;
 
 pub use eth::*;
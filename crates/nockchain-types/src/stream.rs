@@ -0,0 +1,145 @@
+//! Streaming jam/cue for large chain values.
+//!
+//! [`NounSlab::jam`] materializes the whole jammed buffer before it can be written anywhere, which
+//! is wasteful for blocks and full transactions that are about to be chunked onto a gRPC stream or
+//! exported to a file. The helpers here run nockvm's [`jam_to_writer`] and [`cue_from_reader`] on
+//! a scratch [`NockStack`], so the jam bitstream goes straight through [`Write`] and [`Read`] and is
+//! byte-for-byte the encoding [`NockJammer`] produces.
+//!
+//! [`NockJammer`]: nockapp::noun::slab::NockJammer
+//! [`jam_to_writer`]: serialization::jam_to_writer
+//! [`cue_from_reader`]: serialization::cue_from_reader
+
+use std::io::{self, Read, Write};
+
+use nockapp::noun::slab::NounSlab;
+use nockapp::utils::NOCK_STACK_SIZE;
+use nockvm::mem::NockStack;
+use nockvm::noun::Noun;
+use nockvm::serialization;
+use noun_serde::{NounDecode, NounDecodeError, NounEncode};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("cue: truncated stream")]
+    TruncatedStream,
+    #[error("cue: invalid jam")]
+    InvalidJam,
+    #[error("decode error: {0}")]
+    Decode(#[from] NounDecodeError),
+}
+
+impl From<serialization::StreamError> for StreamError {
+    fn from(err: serialization::StreamError) -> Self {
+        match err {
+            serialization::StreamError::Io(err) => StreamError::Io(err),
+            serialization::StreamError::Truncated => StreamError::TruncatedStream,
+            serialization::StreamError::Cue(_) => StreamError::InvalidJam,
+        }
+    }
+}
+
+/// Scratch stack for the backreference table and traversal. It is mapped lazily, so only the
+/// pages a value actually needs are ever touched.
+fn scratch_stack() -> NockStack {
+    NockStack::new(NOCK_STACK_SIZE, 0)
+}
+
+/// Encode `value` as a noun and stream its jam into `writer`.
+///
+/// Returns the number of bytes written.
+pub fn jam_to_writer<T: NounEncode, W: Write>(value: &T, writer: W) -> io::Result<u64> {
+    let mut stack = scratch_stack();
+    let noun = value.to_noun(&mut stack);
+    Ok(serialization::jam_to_writer(&mut stack, noun, writer)? as u64)
+}
+
+/// Stream the jam of `noun` into `writer`, returning the number of bytes written.
+///
+/// Only the backreference table is held in memory; the encoded bits are flushed to `writer` as
+/// they are produced.
+pub fn jam_noun_to_writer<W: Write>(noun: Noun, writer: W) -> io::Result<u64> {
+    let mut stack = scratch_stack();
+    Ok(serialization::jam_to_writer(&mut stack, noun, writer)? as u64)
+}
+
+/// Read a jammed noun from `reader` and decode it as `T`.
+pub fn cue_from_reader<T: NounDecode, R: Read>(reader: R) -> Result<T, StreamError> {
+    let mut stack = scratch_stack();
+    let noun = serialization::cue_from_reader(&mut stack, reader)?;
+    Ok(T::from_noun(&noun)?)
+}
+
+/// Read a jammed noun from `reader` into `slab`, setting it as the slab root.
+///
+/// The input is consumed incrementally, so the jammed bytes themselves are never buffered in full.
+pub fn cue_noun_from_reader<R: Read>(slab: &mut NounSlab, reader: R) -> Result<Noun, StreamError> {
+    let mut stack = scratch_stack();
+    let noun = serialization::cue_from_reader(&mut stack, reader)?;
+    Ok(slab.copy_into(noun))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use nockapp::noun::slab::slab_noun_equality;
+    use nockvm::noun::{Atom, D, T};
+
+    use super::*;
+
+    fn roundtrip(jam: &'static [u8]) {
+        let mut slab: NounSlab = NounSlab::new();
+        let noun = slab
+            .cue_into(Bytes::from_static(jam))
+            .expect("fixture should cue");
+
+        let mut streamed = Vec::new();
+        let written = jam_noun_to_writer(noun, &mut streamed).expect("jam to vec");
+        assert_eq!(written as usize, streamed.len());
+        assert_eq!(streamed.as_slice(), &slab.jam()[..]);
+
+        let mut cue_slab: NounSlab = NounSlab::new();
+        let cued = cue_noun_from_reader(&mut cue_slab, streamed.as_slice()).expect("stream cue");
+        assert!(slab_noun_equality(&cued, &noun));
+    }
+
+    #[test]
+    fn matches_slab_jam_for_fixtures() {
+        roundtrip(include_bytes!("../jams/v0/raw-tx.jam"));
+        roundtrip(include_bytes!("../jams/v1/raw-tx.jam"));
+        roundtrip(include_bytes!("../jams/v1/note.jam"));
+    }
+
+    #[test]
+    fn roundtrips_backrefs_and_large_atoms() {
+        let mut slab: NounSlab = NounSlab::new();
+        let big = Atom::new(&mut slab, u64::MAX).as_noun();
+        let pair = T(&mut slab, &[D(7), big]);
+        let noun = T(&mut slab, &[pair, pair, big, D(0)]);
+        slab.set_root(noun);
+
+        let mut streamed = Vec::new();
+        jam_noun_to_writer(noun, &mut streamed).expect("jam to vec");
+        assert_eq!(streamed.as_slice(), &slab.jam()[..]);
+
+        let mut cue_slab: NounSlab = NounSlab::new();
+        let cued = cue_noun_from_reader(&mut cue_slab, streamed.as_slice()).expect("stream cue");
+        assert!(slab_noun_equality(&cued, &noun));
+    }
+
+    #[test]
+    fn truncated_stream_errors() {
+        let mut slab: NounSlab = NounSlab::new();
+        let noun = T(&mut slab, &[D(1), D(2), D(3)]);
+        let mut streamed = Vec::new();
+        jam_noun_to_writer(noun, &mut streamed).expect("jam to vec");
+        streamed.pop();
+
+        let mut cue_slab: NounSlab = NounSlab::new();
+        let err = cue_noun_from_reader(&mut cue_slab, streamed.as_slice()).unwrap_err();
+        assert!(matches!(err, StreamError::TruncatedStream));
+    }
+}
@@ -78,6 +78,9 @@ pub struct BlockHeight(pub Belt);
 #[derive(Debug, Clone, PartialEq, Eq, NounEncode, NounDecode, Serialize, Deserialize)]
 pub struct BlockHeightDelta(pub Belt);
 
+/// Number of nicks in one NOCK
+pub const NICKS_PER_NOCK: u64 = 65_536;
+
 #[derive(Debug, Clone, PartialEq, Eq, NounDecode, NounEncode)]
 pub struct Nicks(pub usize);
 
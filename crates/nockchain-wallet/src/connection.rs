@@ -162,7 +162,8 @@ pub(crate) async fn sync_wallet_balance(
                     endpoint.clone(),
                 ))
                 .await;
-            Wallet::update_balance_grpc_private(&mut client, pubkeys, tracked_names).await
+            let synced = wallet.synced_balance_block().await?;
+            Wallet::update_balance_grpc_private(&mut client, synced, pubkeys, tracked_names).await
         }
         GrpcTarget::Public { endpoint } => {
             let mut client = public_nockchain::PublicNockchainGrpcClient::connect(endpoint.clone())
@@ -42,7 +42,10 @@ use nockapp::{
 use nockapp_grpc::pb::common::v1::Base58Hash as PbBase58Hash;
 use nockapp_grpc::pb::public::v2::transaction_accepted_response;
 use nockapp_grpc::{private_nockapp, public_nockchain};
-use nockchain_types::common::{Hash, SchnorrPubkey, TimelockRangeAbsolute, TimelockRangeRelative};
+use nockchain_math::belt::Belt;
+use nockchain_types::common::{
+    Hash, Page, SchnorrPubkey, TimelockRangeAbsolute, TimelockRangeRelative,
+};
 use nockchain_types::{v0, v1};
 use nockvm::jets::cold::Nounable;
 use nockvm::noun::{Atom, Cell, IndirectAtom, Noun, D, NO, SIG, T, YES};
@@ -72,14 +75,18 @@ async fn main() -> Result<(), NockAppError> {
     }
 
     let prover_hot_state = produce_prover_hot_state();
-    let data_dir = wallet_data_dir().await?;
+    // --data-dir takes precedence in boot::setup
+    let data_dir = match cli.boot.data_dir {
+        Some(_) => None,
+        None => Some(wallet_data_dir().await?),
+    };
 
     let kernel = boot::setup(
         KERNEL,
         cli.boot.clone(),
         prover_hot_state.as_slice(),
         "wallet",
-        Some(data_dir),
+        data_dir,
     )
     .await
     .map_err(|e| CrownError::Unknown(format!("Kernel setup failed: {}", e)))?;
@@ -995,7 +1002,8 @@ impl Wallet {
         pubkeys: Vec<String>,
         first_names: Vec<String>,
     ) -> Result<Vec<NounSlab>, NockAppError> {
-        let mut results = Vec::new();
+        // Roll back before updating, so that an update from a shorter chain is not ignored
+        let mut results = Self::reorg_rollbacks_grpc_public(client).await?;
 
         for first_name in first_names {
             let mut slab = NounSlab::new(); // Define slab - adjust as needed
@@ -1038,8 +1046,111 @@ impl Wallet {
         Ok(results)
     }
 
+    /// `%reorg` pokes for the reorgs the node remembers. The wallet drops its balance if it was
+    /// synced from a block one of them orphaned.
+    async fn reorg_rollbacks_grpc_public(
+        client: &mut public_nockchain::PublicNockchainGrpcClient,
+    ) -> Result<Vec<NounSlab>, NockAppError> {
+        let reorgs = match client.recent_reorgs().await {
+            Ok(reorgs) => reorgs,
+            Err(e) => {
+                // Nodes from before reorg notifications don't serve them
+                warn!("Could not fetch recent reorgs, not checking for orphaned balance: {e}");
+                return Ok(Vec::new());
+            }
+        };
+
+        let mut results = Vec::new();
+        for reorg in reorgs {
+            let orphaned = reorg
+                .orphaned
+                .into_iter()
+                .map(Hash::try_from)
+                .collect::<Result<Vec<Hash>, _>>()
+                .map_err(|e| {
+                    NockAppError::OtherError(format!("Failed to parse orphaned block id: {}", e))
+                })?;
+            results.push(Self::reorg_poke(reorg.ancestor_height, &orphaned));
+        }
+        Ok(results)
+    }
+
+    /// A `%reorg` poke if the balance the wallet last synced, at `synced`, is from a block that
+    /// is no longer on the node's heaviest chain.
+    async fn reorg_rollbacks_grpc_private(
+        client: &mut private_nockapp::PrivateNockAppGrpcClient,
+        request_index: &mut i32,
+        synced: Option<(u64, Hash)>,
+    ) -> Result<Vec<NounSlab>, NockAppError> {
+        let Some((height, block_id)) = synced else {
+            return Ok(Vec::new());
+        };
+
+        let mut path_slab = NounSlab::<NockJammer>::new();
+        let head = make_tas(&mut path_slab, "heavy-n").as_noun();
+        let height_noun = Atom::new(&mut path_slab, height).as_noun();
+        let path_noun = T(&mut path_slab, &[head, height_noun, SIG]);
+        path_slab.set_root(path_noun);
+        let path_bytes = path_slab.jam().to_vec();
+
+        let response = client.peek(*request_index, path_bytes).await.map_err(|e| {
+            NockAppError::OtherError(format!("Failed to peek heaviest block at {height}: {e}"))
+        })?;
+        *request_index = request_index.wrapping_add(1);
+
+        let mut slab = NounSlab::new();
+        let page = slab.cue_into(response.as_bytes()?)?;
+        let heaviest = match Option::<Option<Page>>::from_noun(&page)? {
+            Some(page) => page.map(|page| page.digest),
+            None => {
+                warn!(
+                    "Could not peek heaviest block at {height}, not checking for orphaned balance"
+                );
+                return Ok(Vec::new());
+            }
+        };
+        if heaviest.as_ref() == Some(&block_id) {
+            return Ok(Vec::new());
+        }
+        // The private API doesn't say where the chains forked. Rolling back to height 0 lets the
+        // balance update that follows replace the dropped balance whatever its height.
+        Ok(vec![Self::reorg_poke(0, &[block_id])])
+    }
+
+    /// The `%reorg` poke telling the wallet that `orphaned` left the heaviest chain, which now
+    /// forks from the old one at height `ancestor`.
+    fn reorg_poke(ancestor: u64, orphaned: &[Hash]) -> NounSlab {
+        let mut slab = NounSlab::new();
+        let head = make_tas(&mut slab, "reorg").as_noun();
+        let ancestor = Atom::new(&mut slab, ancestor).as_noun();
+        let orphaned = orphaned.to_vec().to_noun(&mut slab);
+        let full = T(&mut slab, &[head, ancestor, orphaned]);
+        slab.set_root(full);
+        slab
+    }
+
+    /// Height and block id of the balance the wallet last synced, if it has synced one.
+    async fn synced_balance_block(&mut self) -> Result<Option<(u64, Hash)>, NockAppError> {
+        let mut slab = NounSlab::new();
+        let head = make_tas(&mut slab, "balance").as_noun();
+        let path = T(&mut slab, &[head, SIG]);
+        slab.set_root(path);
+        let Some(balance) = self.app.peek_handle(slab).await? else {
+            return Ok(None);
+        };
+        let balance = unsafe { balance.root() }.as_cell()?;
+        let height = u64::from_noun(&balance.head())?;
+        let block_id = Hash::from_noun(&balance.tail().as_cell()?.head())?;
+        // A wallet that has never synced has the bunted balance
+        if block_id == Hash([Belt(0); 5]) {
+            return Ok(None);
+        }
+        Ok(Some((height, block_id)))
+    }
+
     async fn update_balance_grpc_private(
         client: &mut private_nockapp::PrivateNockAppGrpcClient,
+        synced: Option<(u64, Hash)>,
         mut pubkeys: Vec<String>,
         mut first_names: Vec<String>,
     ) -> Result<Vec<NounSlab>, NockAppError> {
@@ -1049,7 +1160,9 @@ impl Wallet {
         pubkeys.dedup();
 
         let mut request_index: i32 = 0;
-        let mut results = Vec::new();
+        // Roll back before updating, so that an update from a shorter chain is not ignored
+        let mut results =
+            Self::reorg_rollbacks_grpc_private(client, &mut request_index, synced).await?;
 
         for first_name in first_names {
             let mut slab = NounSlab::new();
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::time::Duration;
 
-use clap::{value_parser, ArgAction, Parser};
+use clap::{value_parser, ArgAction, Parser, Subcommand};
 use nockchain_types::tx_engine::common::Hash;
 
 use crate::mining::MiningPkhConfig;
@@ -10,6 +10,9 @@ use crate::mining::MiningPkhConfig;
 /** Path to read current node's identity from */
 pub const IDENTITY_PATH: &str = ".nockchain_identity";
 
+/** Name of the node's identity file in its directory under --data-dir */
+pub const IDENTITY_FILE: &str = "identity";
+
 /** Path to read current node's peer ID from */
 pub const PEER_ID_EXTENSION: &str = ".peer_id";
 
@@ -30,6 +33,15 @@ pub const REALNET_BACKBONE_NODES: &[&str] = &["/dnsaddr/nockchain-backbone.zorp.
 /** How often we should affirmatively ask other nodes for their heaviest chain */
 pub const CHAIN_INTERVAL: Duration = Duration::from_secs(20);
 
+/** Shallowest --prune-depth we accept, well past any reorg we expect to see */
+pub const MIN_PRUNE_DEPTH: u64 = 1000;
+
+/** How often a pruned node drops block transactions that have fallen below the prune depth */
+pub const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
+/** How many times easier than the block target a stratum share is, unless --stratum-share-factor is given */
+pub const DEFAULT_STRATUM_SHARE_FACTOR: u64 = 16;
+
 /// The height of the bitcoin block that we want to sync our genesis block to
 /// Currently, this is the height of an existing block for testing. It will be
 /// switched to a future block for launch.
@@ -39,6 +51,8 @@ pub const GENESIS_HEIGHT: u64 = 897767;
 #[derive(Parser, Debug, Clone)]
 #[command(name = "nockchain")]
 pub struct NockchainCli {
+    #[command(subcommand)]
+    pub command: Option<NockchainCommand>,
     #[command(flatten)]
     pub nockapp_cli: nockapp::kernel::boot::Cli,
     #[arg(long, help = "Mine in-kernel", default_value = "false")]
@@ -96,6 +110,11 @@ pub struct NockchainCli {
     pub max_system_memory_bytes: Option<usize>,
     #[arg(long, help = "Number of threads to mine with defaults to one less than the number of cpus available.", default_value = None)]
     pub num_threads: Option<u64>,
+    #[arg(
+        long,
+        help = "Number of threads used to verify block proofs. Defaults to the number of cpus available."
+    )]
+    pub verifier_threads: Option<usize>,
     #[arg(
         long,
         help = "Size of Proof of Work puzzle for mining on fakenet. Mainnet uses 64. Must be a power of 2. Defaults to 2. Ignored on mainnet.",
@@ -121,10 +140,61 @@ pub struct NockchainCli {
     pub fakenet_genesis_jam_path: Option<PathBuf>,
     #[arg(long, help = "Public gRPC binding address (off by default), recommended value = \"127.0.0.1:5555\"", value_parser = clap::value_parser!(std::net::SocketAddr))]
     pub bind_public_grpc_addr: Option<std::net::SocketAddr>,
+    #[arg(
+        long,
+        help = "Index the heaviest chain into an embedded database at this path and serve it to explorers over the public gRPC API",
+        requires = "bind_public_grpc_addr"
+    )]
+    pub index_db: Option<PathBuf>,
     #[arg(long, default_value = "5555")]
     pub bind_private_grpc_port: u16,
     #[arg(long, default_value = "false")]
     pub fast_sync: bool,
+    #[arg(
+        long,
+        requires = "bind_public_grpc_addr",
+        help = "Serve peer management (list, ban, unban and add peers) on the public gRPC address",
+        default_value = "false"
+    )]
+    pub grpc_peer_admin: bool,
+    #[arg(
+        long,
+        requires = "bind_public_grpc_addr",
+        help = "Serve mempool policy management on the public gRPC address",
+        default_value = "false"
+    )]
+    pub grpc_mempool_admin: bool,
+    #[arg(
+        long,
+        requires = "bind_public_grpc_addr",
+        help = "Serve mining driver performance stats on the public gRPC address",
+        default_value = "false"
+    )]
+    pub grpc_mining_stats: bool,
+    #[arg(
+        long,
+        help = "Run a pruned node: discard the transactions of blocks this many blocks below the heaviest block, keeping headers and balances"
+    )]
+    pub prune_depth: Option<u64>,
+    #[arg(
+        long,
+        help = "Serve candidate blocks to external miners over the stratum protocol on this address (e.g. 0.0.0.0:3333)"
+    )]
+    pub stratum_bind: Option<std::net::SocketAddr>,
+    #[arg(
+        long,
+        requires = "stratum_bind",
+        help = "How many times easier than the block target a stratum share is",
+        default_value_t = DEFAULT_STRATUM_SHARE_FACTOR
+    )]
+    pub stratum_share_factor: u64,
+}
+
+/// Commands run instead of the node
+#[derive(Subcommand, Debug, Clone)]
+pub enum NockchainCommand {
+    /// Check the saved state for corruption, then exit without starting the node
+    VerifyState,
 }
 
 impl NockchainCli {
@@ -145,6 +215,27 @@ impl NockchainCli {
             Hash::from_base58(pkh).map_err(|err| format!("Invalid mining_pkh: {err}"))?;
         }
 
+        if self.stratum_bind.is_some()
+            && !(self.mining_pkh.is_some() || self.mining_pkh_adv.is_some())
+        {
+            return Err(
+                "Cannot specify stratum_bind without either mining_pkh or mining_pkh_adv"
+                    .to_string(),
+            );
+        }
+
+        if self.stratum_share_factor == 0 {
+            return Err("stratum_share_factor must be at least 1".to_string());
+        }
+
+        if let Some(depth) = self.prune_depth {
+            if depth < MIN_PRUNE_DEPTH {
+                return Err(format!(
+                    "prune_depth must be at least {MIN_PRUNE_DEPTH}, got {depth}"
+                ));
+            }
+        }
+
         if let Some(pkh_configs) = &self.mining_pkh_adv {
             for config in pkh_configs {
                 Hash::from_base58(&config.pkh).map_err(|err| {
@@ -168,6 +259,7 @@ mod tests {
 
     fn base_cli() -> NockchainCli {
         NockchainCli {
+            command: None,
             nockapp_cli: default_boot_cli(false),
             mine: false,
             mining_pkh: None,
@@ -189,13 +281,21 @@ mod tests {
             max_system_memory_fraction: None,
             max_system_memory_bytes: None,
             num_threads: None,
+            verifier_threads: None,
             fakenet_pow_len: 2,
             fakenet_log_difficulty: 1,
             fakenet_v1_phase: None,
             fakenet_genesis_jam_path: None,
             bind_public_grpc_addr: Some("127.0.0.1:5555".parse().unwrap()),
+            index_db: None,
             bind_private_grpc_port: 5555,
             fast_sync: false,
+            grpc_peer_admin: false,
+            grpc_mempool_admin: false,
+            grpc_mining_stats: false,
+            prune_depth: None,
+            stratum_bind: None,
+            stratum_share_factor: DEFAULT_STRATUM_SHARE_FACTOR,
         }
     }
 
@@ -223,4 +323,36 @@ mod tests {
         let err = cli.validate().expect_err("expected invalid pkh adv");
         assert!(err.contains("Invalid mining_pkh_adv entry"));
     }
+
+    #[test]
+    fn validate_rejects_shallow_prune_depth() {
+        let mut cli = base_cli();
+        cli.prune_depth = Some(MIN_PRUNE_DEPTH);
+        assert!(cli.validate().is_ok());
+
+        cli.prune_depth = Some(MIN_PRUNE_DEPTH - 1);
+        let err = cli
+            .validate()
+            .expect_err("expected shallow prune depth to be rejected");
+        assert!(err.contains("prune_depth"));
+    }
+
+    #[test]
+    fn validate_rejects_stratum_without_mining_pkh() {
+        let mut cli = base_cli();
+        cli.stratum_bind = Some("127.0.0.1:3333".parse().unwrap());
+        let err = cli
+            .validate()
+            .expect_err("expected stratum without a mining pkh to be rejected");
+        assert!(err.contains("stratum_bind"));
+
+        cli.mining_pkh = Some(VALID_MINING_PKH.to_string());
+        assert!(cli.validate().is_ok());
+
+        cli.stratum_share_factor = 0;
+        let err = cli
+            .validate()
+            .expect_err("expected zero share factor to be rejected");
+        assert!(err.contains("stratum_share_factor"));
+    }
 }
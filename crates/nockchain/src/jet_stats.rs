@@ -0,0 +1,31 @@
+//! Jet call counters from [`zkvm_jetpack::instrument`], served as `JetStats` on the private gRPC
+//! port. They are only collected when the node is started with `ZKVM_JET_STATS` set.
+
+use nockapp_grpc::pb::private::v1::JetStat;
+use nockapp_grpc::private_nockapp::JetStatsSource;
+use zkvm_jetpack::instrument;
+
+pub struct JetStats;
+
+impl JetStatsSource for JetStats {
+    fn enabled(&self) -> bool {
+        instrument::enabled()
+    }
+
+    fn snapshot(&self) -> Vec<JetStat> {
+        instrument::snapshot()
+            .into_iter()
+            .map(|stats| JetStat {
+                path: stats.path,
+                calls: stats.calls,
+                punts: stats.punts,
+                errors: stats.errors,
+                total_nanos: stats.total.as_nanos() as u64,
+            })
+            .collect()
+    }
+
+    fn reset(&self) {
+        instrument::reset();
+    }
+}
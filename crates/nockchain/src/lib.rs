@@ -9,17 +9,25 @@
 #![cfg_attr(test, allow(clippy::unwrap_used))]
 
 pub mod config;
+pub mod jet_stats;
+pub mod mempool_admin;
 pub mod mining;
+pub mod mining_stats;
+pub mod peer_admin;
 pub mod setup;
+pub mod stratum;
+pub mod verify;
 
 use std::error::Error;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 pub use config::NockchainCli;
 use libp2p::identity::Keypair;
 use libp2p::multiaddr::Multiaddr;
 use libp2p::{allow_block_list, connection_limits, memory_connection_limits, PeerId};
+use nockapp::data_dir::{self, DataDir};
 use nockapp::kernel::boot;
 use nockapp::utils::make_tas;
 use nockapp::NockApp;
@@ -31,7 +39,7 @@ use nockapp::noun::slab::{Jammer, NounSlab};
 use nockvm::jets::hot::HotEntry;
 use nockvm::noun::{D, T, YES};
 use nockvm_macros::tas;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 use crate::mining::{MiningKeyConfig, MiningPkhConfig};
 use crate::setup::fakenet_blockchain_constants;
@@ -209,6 +217,26 @@ fn load_keypair(keypair_path: &Path, force_old: bool) -> Result<Keypair, Box<dyn
     }
 }
 
+/// Where the node's identity is kept: in its directory under --data-dir, or else the working
+/// directory. Moves the identity there first when migrating.
+fn identity_path(nockapp_cli: &boot::Cli) -> Result<PathBuf, Box<dyn Error>> {
+    let legacy = Path::new(config::IDENTITY_PATH);
+    let Some(root) = &nockapp_cli.data_dir else {
+        return Ok(legacy.to_path_buf());
+    };
+    let path = DataDir::new(root)
+        .app("nockchain")
+        .join(config::IDENTITY_FILE);
+    if nockapp_cli.migrate_data_dir {
+        data_dir::migrate(legacy, &path)?;
+        data_dir::migrate(
+            &legacy.with_extension(config::PEER_ID_FILE_EXTENSION),
+            &path.with_extension(config::PEER_ID_FILE_EXTENSION),
+        )?;
+    }
+    Ok(path)
+}
+
 #[instrument(skip(kernel_jam, hot_state))]
 pub async fn init_with_kernel<J: Jammer + Send + 'static>(
     cli: config::NockchainCli,
@@ -220,6 +248,12 @@ pub async fn init_with_kernel<J: Jammer + Send + 'static>(
 
     cli.validate()?;
 
+    if let Some(verifier_threads) = cli.verifier_threads {
+        if !zkvm_jetpack::jets::verifier_jets::init_verifier_pool(verifier_threads) {
+            warn!("verifier thread pool already initialized, ignoring --verifier-threads");
+        }
+    }
+
     let mut nockapp_cli = cli.nockapp_cli.clone();
     nockapp_cli.stack_size = nockapp::kernel::boot::NockStackSize::Medium;
 
@@ -227,8 +261,8 @@ pub async fn init_with_kernel<J: Jammer + Send + 'static>(
         boot::setup::<J>(kernel_jam, nockapp_cli, hot_state, "nockchain", None).await?;
 
     let keypair = {
-        let keypair_path = Path::new(config::IDENTITY_PATH);
-        load_keypair(keypair_path, cli.no_new_peer_id)?
+        let keypair_path = identity_path(&cli.nockapp_cli)?;
+        load_keypair(&keypair_path, cli.no_new_peer_id)?
     };
     info!("allowed_peers_path: {:?}", cli.allowed_peers_path);
     let allowed = cli.allowed_peers_path.as_ref().map(|path| {
@@ -474,15 +508,50 @@ pub async fn init_with_kernel<J: Jammer + Send + 'static>(
         1
     };
 
+    let mining_stats = Arc::new(crate::mining_stats::MiningStats::default());
     let mining_driver = crate::mining::create_mining_driver(
         mining_config,
         mining_pkh_config,
         mine,
+        cli.stratum_bind.is_some(),
         threads,
+        mining_stats.clone(),
         Some(mining_init_tx),
     );
     nockapp.add_io_driver(mining_driver).await;
 
+    if let Some(addr) = cli.stratum_bind {
+        nockapp
+            .add_io_driver(crate::stratum::create_stratum_driver(
+                addr, cli.stratum_share_factor,
+            ))
+            .await;
+    }
+
+    let (peer_manager, peer_admin_rx) = if cli.grpc_peer_admin {
+        let (admin, receiver) = nockchain_libp2p_io::peer_admin::peer_admin_channel();
+        let manager: Arc<dyn nockapp_grpc::public_nockchain::PeerManager> =
+            Arc::new(crate::peer_admin::LibP2PPeerManager(admin));
+        (Some(manager), Some(receiver))
+    } else {
+        (None, None)
+    };
+    let (mempool_manager, mempool_admin_rx) = if cli.grpc_mempool_admin {
+        let (admin, receiver) = nockchain_libp2p_io::mempool::mempool_admin_channel();
+        let manager: Arc<dyn nockapp_grpc::public_nockchain::MempoolManager> =
+            Arc::new(crate::mempool_admin::LibP2PMempoolManager(admin));
+        (Some(manager), Some(receiver))
+    } else {
+        (None, None)
+    };
+
+    let mining_monitor = if cli.grpc_mining_stats {
+        let monitor: Arc<dyn nockapp_grpc::public_nockchain::MiningMonitor> = mining_stats;
+        Some(monitor)
+    } else {
+        None
+    };
+
     let libp2p_driver = nockchain_libp2p_io::driver::make_libp2p_driver(
         keypair,
         bind_multiaddrs,
@@ -495,10 +564,26 @@ pub async fn init_with_kernel<J: Jammer + Send + 'static>(
         cli.fast_sync,
         equix_builder,
         config::CHAIN_INTERVAL,
+        peer_admin_rx,
+        mempool_admin_rx,
         Some(libp2p_init_tx),
     );
     nockapp.add_io_driver(libp2p_driver).await;
 
+    if let Some(depth) = cli.prune_depth {
+        info!("Pruning the transactions of blocks more than {depth} blocks deep");
+        let mut prune_slab = NounSlab::new();
+        let tag = make_tas(&mut prune_slab, "prune-blocks").as_noun();
+        let prune = T(&mut prune_slab, &[D(tas!(b"command")), tag, D(depth)]);
+        prune_slab.set_root(prune);
+        nockapp
+            .add_io_driver(nockapp::drivers::timer_driver(
+                config::PRUNE_INTERVAL.as_secs(),
+                prune_slab,
+            ))
+            .await;
+    }
+
     // Create the born driver that waits for the born signal
     // Make the born poke
     let mut born_slab = NounSlab::new();
@@ -516,14 +601,31 @@ pub async fn init_with_kernel<J: Jammer + Send + 'static>(
         let addr = server_config
             .addr()
             .expect("addr should be Some when deploy_public is true");
+        let index = match &cli.index_db {
+            Some(path) => {
+                let index =
+                    nockapp_grpc::public_nockchain::ExplorerIndex::open(path).map_err(|e| {
+                        format!("Failed to open explorer index at {}: {}", path.display(), e)
+                    })?;
+                Some(Arc::new(index))
+            }
+            None => None,
+        };
         nockapp
-            .add_io_driver(nockapp_grpc::public_nockchain::grpc_server_driver(addr))
+            .add_io_driver(
+                nockapp_grpc::public_nockchain::grpc_server_driver_with_admin(
+                    addr, peer_manager, mempool_manager, mining_monitor, index,
+                ),
+            )
             .await;
     }
     nockapp
-        .add_io_driver(nockapp_grpc::private_nockapp::grpc_server_driver(
-            cli.bind_private_grpc_port,
-        ))
+        .add_io_driver(
+            nockapp_grpc::private_nockapp::grpc_server_driver_with_jet_stats(
+                cli.bind_private_grpc_port,
+                Arc::new(jet_stats::JetStats),
+            ),
+        )
         .await;
 
     nockapp.add_io_driver(nockapp::exit_driver()).await;
@@ -4,8 +4,10 @@ use clap::Parser;
 use kernels::dumb::KERNEL;
 use nockapp::kernel::boot;
 use nockapp::NockApp;
+use nockchain::config::NockchainCommand;
 use nockchain::NockchainAPIConfig;
 use zkvm_jetpack::hot::produce_prover_hot_state;
+use zkvm_jetpack::instrument;
 
 // When enabled, use jemalloc for more stable memory allocation
 #[cfg(all(feature = "jemalloc", not(feature = "tracing-heap")))]
@@ -25,6 +27,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let prover_hot_state = produce_prover_hot_state();
 
+    if let Some(NockchainCommand::VerifyState) = &cli.command {
+        let report =
+            nockchain::verify::verify_state(cli, KERNEL, prover_hot_state.as_slice()).await?;
+        report.log();
+        std::process::exit(if report.is_clean() { 0 } else { 1 });
+    }
+
     let api_config = if let Some(addr) = cli.bind_public_grpc_addr {
         NockchainAPIConfig::EnablePublicServer(addr)
     } else {
@@ -33,6 +42,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let mut nockchain: NockApp =
         nockchain::init_with_kernel(cli, KERNEL, prover_hot_state.as_slice(), api_config).await?;
-    nockchain.run().await?;
+    let result = nockchain.run().await;
+    if instrument::enabled() {
+        instrument::log_report();
+    }
+    result?;
     Ok(())
 }
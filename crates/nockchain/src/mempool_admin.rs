@@ -0,0 +1,34 @@
+//! Serves the public gRPC mempool policy methods from the libp2p driver's [`MempoolAdmin`].
+
+use async_trait::async_trait;
+use nockapp_grpc::error::Result;
+use nockapp_grpc::pb::public::v2::MempoolPolicy as GrpcMempoolPolicy;
+use nockapp_grpc::public_nockchain::MempoolManager;
+use nockchain_libp2p_io::mempool::{MempoolAdmin, MempoolPolicy};
+
+pub struct LibP2PMempoolManager(pub MempoolAdmin);
+
+#[async_trait]
+impl MempoolManager for LibP2PMempoolManager {
+    async fn get_policy(&self) -> Result<GrpcMempoolPolicy> {
+        let policy = self.0.policy().await?;
+        Ok(GrpcMempoolPolicy {
+            max_txs: policy.max_txs as u64,
+            max_tx_bytes: policy.max_tx_bytes as u64,
+            min_fee_rate: policy.min_fee_rate,
+            max_txs_per_peer: policy.max_txs_per_peer as u64,
+            replacement_fee_bump_percent: policy.replacement_fee_bump_percent,
+        })
+    }
+
+    async fn set_policy(&self, policy: GrpcMempoolPolicy) -> Result<u64> {
+        let policy = MempoolPolicy {
+            max_txs: policy.max_txs as usize,
+            max_tx_bytes: policy.max_tx_bytes as usize,
+            min_fee_rate: policy.min_fee_rate,
+            max_txs_per_peer: policy.max_txs_per_peer as usize,
+            replacement_fee_bump_percent: policy.replacement_fee_bump_percent,
+        };
+        Ok(self.0.set_policy(policy).await? as u64)
+    }
+}
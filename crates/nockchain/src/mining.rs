@@ -1,4 +1,5 @@
 use std::str::FromStr;
+use std::sync::Arc;
 
 use kernels::miner::KERNEL;
 use nockapp::kernel::form::SerfThread;
@@ -22,6 +23,8 @@ use zkvm_jetpack::form::belt::PRIME;
 use zkvm_jetpack::form::noun_ext::NounMathExt;
 use zkvm_jetpack::form::structs::HoonList;
 
+use crate::mining_stats::{Attempt, MiningStats};
+
 pub enum MiningWire {
     Mined,
     Candidate,
@@ -114,7 +117,9 @@ pub fn create_mining_driver(
     _mining_config: Option<Vec<MiningKeyConfig>>,
     mining_pkh_config: Option<Vec<MiningPkhConfig>>,
     mine: bool,
+    serve_work: bool,
     num_threads: u64,
+    stats: Arc<MiningStats>,
     init_complete_tx: Option<tokio::sync::oneshot::Sender<()>>,
 ) -> IODriverFn {
     Box::new(move |handle| {
@@ -142,7 +147,9 @@ pub fn create_mining_driver(
                 return Ok(());
             };
             set_mining_key_advanced(&handle, configs, pkh_configs).await?;
-            enable_mining(&handle, mine).await?;
+            // The kernel only emits candidate blocks when mining is enabled, so the stratum server
+            // needs it on even if this node doesn't mine itself
+            enable_mining(&handle, mine || serve_work).await?;
 
             if let Some(tx) = init_complete_tx {
                 tx.send(()).map_err(|_| {
@@ -180,7 +187,8 @@ pub fn create_mining_driver(
 
                             match HoonList::try_from(*result) {
                                 Err(_) => {
-                                    start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, None, id).await;
+                                    stats.attempt_finished(id, Attempt::Abandoned);
+                                    start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, None, id, &stats).await;
                                 }
                                 Ok(effects) => {
                                     let mining_result =
@@ -202,7 +210,8 @@ pub fn create_mining_driver(
                                     }).next();
                                     match mining_result {
                                         None => {
-                                            start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, None, id).await;
+                                            stats.attempt_finished(id, Attempt::Abandoned);
+                                            start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, None, id, &stats).await;
                                         },
                                         Some(mine_result) => {
                                             let Ok([res, tail]) = mine_result.uncell() else {
@@ -212,6 +221,7 @@ pub fn create_mining_driver(
                                                 // success
                                                 // poke main kernel with mined block and start a new attempt
                                                 info!("Found block! thread={id}");
+                                                stats.attempt_finished(id, Attempt::Block);
                                                 let Ok([hash, poke]) = tail.uncell() else {
                                                     error!("Expected two elements in tail");
                                                     return Err(NockAppError::OtherError(String::from("Expected two elements in tail")));
@@ -223,15 +233,16 @@ pub fn create_mining_driver(
                                                 // launch new attempt
                                                 let mut nonce_slab = NounSlab::new();
                                                 nonce_slab.copy_into(hash);
-                                                start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, Some(nonce_slab), id).await;
+                                                start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, Some(nonce_slab), id, &stats).await;
                                             } else {
                                                 // failure
                                                 //  launch new attempt, using hash as new nonce
                                                 //  nonce is tail
                                                 debug!("didn't find block, starting new attempt. thread={id}");
+                                                stats.attempt_finished(id, Attempt::Proof);
                                                 let mut nonce_slab = NounSlab::new();
                                                 nonce_slab.copy_into(tail);
-                                                start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, Some(nonce_slab), id).await;
+                                                start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, Some(nonce_slab), id, &stats).await;
                                             }
                                         }
                                     }
@@ -268,10 +279,10 @@ pub fn create_mining_driver(
                                         .expect("Expected pow-len to be a u64");
                                 (version_slab, header_slab, target_slab, pow_len)
                             };
-                            debug!("received new candidate block header: {:?}",
-                                tip5_hash_to_base58(*unsafe { header_slab.root() })
-                                .expect("Failed to convert header to Base58")
-                            );
+                            let header_b58 = tip5_hash_to_base58(*unsafe { header_slab.root() })
+                                .expect("Failed to convert header to Base58");
+                            debug!("received new candidate block header: {:?}", header_b58);
+                            stats.new_candidate(header_b58);
                             *(mining_data.lock().await) = Some(MiningData {
                                 block_header: header_slab,
                                 version: version_slab,
@@ -297,7 +308,7 @@ pub fn create_mining_driver(
 
                                     cancel_tokens.push(serf.cancel_token.clone());
 
-                                    start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, None, i).await;
+                                    start_mining_attempt(serf, mining_data.lock().await, &mut mining_attempts, None, i, &stats).await;
                                 }
                                 info!("mining threads started with {} threads", num_threads);
                             } else {
@@ -414,6 +425,7 @@ async fn start_mining_attempt(
     )>,
     nonce: Option<NounSlab>,
     id: u64,
+    stats: &MiningStats,
 ) {
     let nonce = nonce.unwrap_or_else(|| {
         let mut rng = rand::rng();
@@ -441,6 +453,7 @@ async fn start_mining_attempt(
         tip5_hash_to_base58(*unsafe { nonce.root() }).expect("Failed to convert nonce to Base58"),
     );
     let poke_slab = create_poke(mining_data_ref, &nonce);
+    stats.attempt_started(id);
     mining_attempts.spawn(async move {
         let result = serf.poke(MiningWire::Candidate.to_wire(), poke_slab).await;
         (serf, id, result)
@@ -0,0 +1,287 @@
+//! Performance of the mining driver: proof rate, attempts per candidate, per-thread utilization
+//! and recent proof timings. Served as `NockchainMiningService` on the public gRPC address when
+//! the node is started with --grpc-mining-stats.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use nockapp_grpc::error::Result;
+use nockapp_grpc::pb::public::v2::{
+    CandidateStats, MiningStats as GrpcMiningStats, MiningThreadStats, ProofTiming,
+};
+use nockapp_grpc::public_nockchain::MiningMonitor;
+
+/// Window the proof rate is measured over
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Earlier candidates kept, besides the current one
+const RECENT_CANDIDATES: usize = 16;
+
+/// Proof timings kept
+const RECENT_PROOFS: usize = 64;
+
+/// How a proof attempt ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attempt {
+    /// Completed without meeting the block target
+    Proof,
+    /// Completed and met the block target
+    Block,
+    /// Cancelled for a new candidate, or failed
+    Abandoned,
+}
+
+/// Mining performance counters, shared between the mining driver and the gRPC server.
+#[derive(Default)]
+pub struct MiningStats {
+    inner: Mutex<Inner>,
+}
+
+impl MiningStats {
+    /// Start counting attempts against a new candidate block.
+    pub fn new_candidate(&self, header: String) {
+        self.lock().new_candidate(header, SystemTime::now());
+    }
+
+    pub fn attempt_started(&self, thread: u64) {
+        self.lock().attempt_started(thread, Instant::now());
+    }
+
+    pub fn attempt_finished(&self, thread: u64, attempt: Attempt) {
+        self.lock()
+            .attempt_finished(thread, attempt, Instant::now(), SystemTime::now());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl MiningMonitor for MiningStats {
+    async fn mining_stats(&self) -> Result<GrpcMiningStats> {
+        Ok(self.lock().snapshot(Instant::now()))
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    /// When the first proof attempt started
+    started: Option<Instant>,
+    proofs: u64,
+    blocks: u64,
+    /// When each proof in the rate window finished
+    finished: VecDeque<Instant>,
+    /// Current candidate first
+    candidates: VecDeque<Candidate>,
+    threads: Vec<ThreadStats>,
+    /// Newest first
+    recent_proofs: VecDeque<Proof>,
+}
+
+struct Candidate {
+    header: String,
+    attempts: u64,
+    proofs: u64,
+    started_at: SystemTime,
+}
+
+struct ThreadStats {
+    started: Instant,
+    /// Time spent on finished attempts
+    busy: Duration,
+    /// When the running attempt started
+    attempt_started: Option<Instant>,
+    proofs: u64,
+}
+
+struct Proof {
+    thread: u64,
+    duration: Duration,
+    finished_at: SystemTime,
+    block: bool,
+}
+
+impl Inner {
+    fn new_candidate(&mut self, header: String, now: SystemTime) {
+        self.candidates.push_front(Candidate {
+            header,
+            attempts: 0,
+            proofs: 0,
+            started_at: now,
+        });
+        self.candidates.truncate(RECENT_CANDIDATES + 1);
+    }
+
+    fn attempt_started(&mut self, thread: u64, now: Instant) {
+        self.started.get_or_insert(now);
+        let idx = thread as usize;
+        if self.threads.len() <= idx {
+            self.threads.resize_with(idx + 1, || ThreadStats {
+                started: now,
+                busy: Duration::ZERO,
+                attempt_started: None,
+                proofs: 0,
+            });
+        }
+        self.threads[idx].attempt_started = Some(now);
+        if let Some(candidate) = self.candidates.front_mut() {
+            candidate.attempts += 1;
+        }
+    }
+
+    fn attempt_finished(&mut self, thread: u64, attempt: Attempt, now: Instant, at: SystemTime) {
+        let Some(stats) = self.threads.get_mut(thread as usize) else {
+            return;
+        };
+        let Some(started) = stats.attempt_started.take() else {
+            return;
+        };
+        let duration = now.saturating_duration_since(started);
+        stats.busy += duration;
+        if attempt == Attempt::Abandoned {
+            return;
+        }
+        stats.proofs += 1;
+        self.proofs += 1;
+        if attempt == Attempt::Block {
+            self.blocks += 1;
+        }
+        if let Some(candidate) = self.candidates.front_mut() {
+            candidate.proofs += 1;
+        }
+        self.finished.push_back(now);
+        self.recent_proofs.push_front(Proof {
+            thread,
+            duration,
+            finished_at: at,
+            block: attempt == Attempt::Block,
+        });
+        self.recent_proofs.truncate(RECENT_PROOFS);
+    }
+
+    fn snapshot(&mut self, now: Instant) -> GrpcMiningStats {
+        while self
+            .finished
+            .front()
+            .is_some_and(|finished| now.saturating_duration_since(*finished) > RATE_WINDOW)
+        {
+            self.finished.pop_front();
+        }
+        let threads = self
+            .threads
+            .iter()
+            .enumerate()
+            .map(|(thread, stats)| {
+                let running = stats
+                    .attempt_started
+                    .map(|started| now.saturating_duration_since(started))
+                    .unwrap_or_default();
+                let lifetime = now.saturating_duration_since(stats.started);
+                let utilization = if lifetime.is_zero() {
+                    0.0
+                } else {
+                    ((stats.busy + running).as_secs_f64() / lifetime.as_secs_f64()).min(1.0)
+                };
+                MiningThreadStats {
+                    thread: thread as u64,
+                    proofs: stats.proofs,
+                    utilization,
+                    busy: stats.attempt_started.is_some(),
+                }
+            })
+            .collect();
+        GrpcMiningStats {
+            proof_rate: self.finished.len() as f64 / RATE_WINDOW.as_secs_f64(),
+            proofs: self.proofs,
+            blocks: self.blocks,
+            uptime_secs: self
+                .started
+                .map(|started| now.saturating_duration_since(started).as_secs())
+                .unwrap_or(0),
+            candidates: self
+                .candidates
+                .iter()
+                .map(|candidate| CandidateStats {
+                    header: candidate.header.clone(),
+                    attempts: candidate.attempts,
+                    proofs: candidate.proofs,
+                    started_at: unix_secs(candidate.started_at),
+                })
+                .collect(),
+            threads,
+            recent_proofs: self
+                .recent_proofs
+                .iter()
+                .map(|proof| ProofTiming {
+                    thread: proof.thread,
+                    duration_ms: proof.duration.as_millis() as u64,
+                    finished_at: unix_secs(proof.finished_at),
+                    block: proof.block,
+                })
+                .collect(),
+        }
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attempts_and_proofs_are_counted() {
+        let start = Instant::now();
+        let secs = |n: u64| start + Duration::from_secs(n);
+        let mut stats = Inner::default();
+
+        stats.new_candidate("first".to_string(), SystemTime::now());
+        stats.attempt_started(0, secs(0));
+        stats.attempt_started(1, secs(0));
+        stats.attempt_finished(0, Attempt::Proof, secs(10), SystemTime::now());
+        stats.attempt_started(0, secs(10));
+
+        stats.new_candidate("second".to_string(), SystemTime::now());
+        stats.attempt_finished(0, Attempt::Abandoned, secs(12), SystemTime::now());
+        stats.attempt_finished(1, Attempt::Abandoned, secs(12), SystemTime::now());
+        stats.attempt_started(0, secs(12));
+        stats.attempt_started(1, secs(12));
+        stats.attempt_finished(1, Attempt::Block, secs(20), SystemTime::now());
+
+        let snapshot = stats.snapshot(secs(30));
+        assert_eq!(snapshot.proofs, 2);
+        assert_eq!(snapshot.blocks, 1);
+        assert_eq!(snapshot.uptime_secs, 30);
+        assert_eq!(snapshot.proof_rate, 2.0 / 60.0);
+
+        let candidates: Vec<_> = snapshot
+            .candidates
+            .iter()
+            .map(|c| (c.header.as_str(), c.attempts, c.proofs))
+            .collect();
+        assert_eq!(candidates, vec![("second", 2, 1), ("first", 3, 1)]);
+
+        // Thread 0 has been proving the whole time, thread 1 idle since it found the block
+        assert_eq!(snapshot.threads[0].utilization, 1.0);
+        assert!(snapshot.threads[0].busy);
+        assert_eq!(snapshot.threads[1].utilization, 20.0 / 30.0);
+        assert!(!snapshot.threads[1].busy);
+
+        let timings: Vec<_> = snapshot
+            .recent_proofs
+            .iter()
+            .map(|p| (p.thread, p.duration_ms, p.block))
+            .collect();
+        assert_eq!(timings, vec![(1, 8_000, true), (0, 10_000, false)]);
+
+        // Proofs fall out of the rate once they are older than the window
+        assert_eq!(stats.snapshot(secs(75)).proof_rate, 1.0 / 60.0);
+    }
+}
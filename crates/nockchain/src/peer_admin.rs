@@ -0,0 +1,59 @@
+//! Serves the public gRPC peer management methods from the libp2p driver's [`PeerAdmin`].
+
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use libp2p::{Multiaddr, PeerId};
+use nockapp_grpc::error::{NockAppGrpcError, Result};
+use nockapp_grpc::pb::public::v2::{PeerDirection as GrpcPeerDirection, PeerEntry};
+use nockapp_grpc::public_nockchain::PeerManager;
+use nockchain_libp2p_io::peer_admin::{PeerAdmin, PeerDirection, PeerInfo};
+
+pub struct LibP2PPeerManager(pub PeerAdmin);
+
+fn parse_peer_id(peer_id: &str) -> Result<PeerId> {
+    PeerId::from_str(peer_id)
+        .map_err(|e| NockAppGrpcError::InvalidRequest(format!("invalid peer ID {peer_id}: {e}")))
+}
+
+fn peer_entry(peer: PeerInfo) -> PeerEntry {
+    let direction = match peer.direction {
+        PeerDirection::Inbound => GrpcPeerDirection::Inbound,
+        PeerDirection::Outbound => GrpcPeerDirection::Outbound,
+    };
+    PeerEntry {
+        peer_id: peer.peer_id.to_base58(),
+        address: peer.address.to_string(),
+        direction: direction as i32,
+        agent_version: peer.agent_version.unwrap_or_default(),
+        protocol_version: peer.protocol_version.unwrap_or_default(),
+        last_seen: peer
+            .last_seen
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs()),
+    }
+}
+
+#[async_trait]
+impl PeerManager for LibP2PPeerManager {
+    async fn list_peers(&self) -> Result<Vec<PeerEntry>> {
+        let peers = self.0.list_peers().await?;
+        Ok(peers.into_iter().map(peer_entry).collect())
+    }
+
+    async fn ban_peer(&self, peer_id: &str) -> Result<()> {
+        Ok(self.0.ban_peer(parse_peer_id(peer_id)?).await?)
+    }
+
+    async fn unban_peer(&self, peer_id: &str) -> Result<()> {
+        Ok(self.0.unban_peer(parse_peer_id(peer_id)?).await?)
+    }
+
+    async fn add_peer(&self, address: &str) -> Result<()> {
+        let address = Multiaddr::from_str(address).map_err(|e| {
+            NockAppGrpcError::InvalidRequest(format!("invalid multiaddr {address}: {e}"))
+        })?;
+        Ok(self.0.add_peer(address).await?)
+    }
+}
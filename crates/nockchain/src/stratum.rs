@@ -0,0 +1,555 @@
+//! A stratum-style mining pool server, so that several external miners can work against one node.
+//!
+//! Miners connect over TCP and speak newline-delimited JSON-RPC:
+//!
+//! - `mining.subscribe` starts a session. The server answers with a session ID, then sends the
+//!   current job.
+//! - `mining.authorize [worker, password]` names a worker to credit shares to. The password is
+//!   ignored.
+//! - `mining.set_target [share_target]` (from the server) is the hex target a proof digest must
+//!   meet to count as a share. It is sent before every job.
+//! - `mining.notify [job_id, version, header, target, pow_len, clean_jobs]` (from the server) is a
+//!   candidate block to prove: the proof version, the block commitment in base58, the block target
+//!   in hex and the proof length. Each job replaces the last, so `clean_jobs` is always true.
+//! - `mining.submit [worker, job_id, nonce, digest, proof]` is a share: the base58 nonce the miner
+//!   proved with, the proof's hex digest and the base64 jam of the proof.
+//!
+//! The share target is the block target made `share_factor` times easier. Shares whose digest
+//! also meets the block target are poked into the kernel as `%pow`, and the kernel verifies the
+//! proof when it validates the block. Other shares are credited on their digest alone, so the
+//! server should only be opened to miners the operator trusts.
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ibig::UBig;
+use nockapp::nockapp::driver::{IODriverFn, NockAppHandle};
+use nockapp::nockapp::wire::Wire;
+use nockapp::nockapp::NockAppError;
+use nockapp::noun::slab::NounSlab;
+use nockapp::Bytes;
+use nockchain_types::tx_engine::common::{BigNum, Hash};
+use nockvm::ext::NounExt;
+use nockvm::noun::{Atom, D, T};
+use nockvm_macros::tas;
+use noun_serde::{NounDecode, NounEncode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{debug, info, warn};
+use zkvm_jetpack::form::noun_ext::NounMathExt;
+
+use crate::mining::MiningWire;
+
+/// Shares waiting for the driver
+const SUBMISSION_QUEUE: usize = 64;
+
+/// Longest worker name we keep stats for
+const MAX_WORKER_NAME: usize = 128;
+
+/// A candidate block, as handed to miners.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Job {
+    id: u64,
+    version: u64,
+    header: Hash,
+    target: UBig,
+    share_target: UBig,
+    pow_len: u64,
+}
+
+impl Job {
+    fn messages(&self) -> [Value; 2] {
+        [
+            json!({
+                "id": null,
+                "method": "mining.set_target",
+                "params": [format!("{:x}", self.share_target)],
+            }),
+            json!({
+                "id": null,
+                "method": "mining.notify",
+                "params": [
+                    format!("{:x}", self.id),
+                    self.version,
+                    self.header.to_base58(),
+                    format!("{:x}", self.target),
+                    self.pow_len,
+                    true,
+                ],
+            }),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Share {
+    /// Meets the share target only
+    Accepted,
+    /// Meets the block target, and was poked into the kernel
+    Block,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+enum ShareError {
+    #[error("job not found")]
+    StaleJob,
+    #[error("duplicate share")]
+    Duplicate,
+    #[error("digest does not meet the share target")]
+    LowDifficulty,
+    #[error("unauthorized worker")]
+    Unauthorized,
+    #[error("not subscribed")]
+    NotSubscribed,
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl ShareError {
+    /// Error code, following the stratum convention
+    fn code(&self) -> u64 {
+        match self {
+            ShareError::Invalid(_) => 20,
+            ShareError::StaleJob => 21,
+            ShareError::Duplicate => 22,
+            ShareError::LowDifficulty => 23,
+            ShareError::Unauthorized => 24,
+            ShareError::NotSubscribed => 25,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct WorkerStats {
+    shares: u64,
+    rejected: u64,
+    blocks: u64,
+}
+
+/// Job and share bookkeeping for the pool.
+struct Pool {
+    share_factor: u64,
+    job: Option<Arc<Job>>,
+    next_job_id: u64,
+    /// Nonces already submitted for the current job
+    nonces: HashSet<Hash>,
+    workers: HashMap<String, WorkerStats>,
+}
+
+impl Pool {
+    fn new(share_factor: u64) -> Self {
+        Self {
+            share_factor,
+            job: None,
+            next_job_id: 0,
+            nonces: HashSet::new(),
+            workers: HashMap::new(),
+        }
+    }
+
+    fn new_job(&mut self, version: u64, header: Hash, target: UBig, pow_len: u64) -> Arc<Job> {
+        self.next_job_id += 1;
+        self.nonces.clear();
+        let job = Arc::new(Job {
+            id: self.next_job_id,
+            version,
+            header,
+            share_target: &target * UBig::from(self.share_factor),
+            target,
+            pow_len,
+        });
+        self.job = Some(job.clone());
+        job
+    }
+
+    fn submit(
+        &mut self,
+        worker: &str,
+        job_id: u64,
+        nonce: &Hash,
+        digest: &UBig,
+    ) -> Result<Share, ShareError> {
+        let result = self.check(job_id, nonce, digest);
+        let stats = self.workers.entry(worker.to_string()).or_default();
+        match result {
+            Ok(Share::Accepted) => stats.shares += 1,
+            Ok(Share::Block) => {
+                stats.shares += 1;
+                stats.blocks += 1;
+            }
+            Err(_) => stats.rejected += 1,
+        }
+        result
+    }
+
+    fn check(&mut self, job_id: u64, nonce: &Hash, digest: &UBig) -> Result<Share, ShareError> {
+        let job = self
+            .job
+            .as_ref()
+            .filter(|job| job.id == job_id)
+            .ok_or(ShareError::StaleJob)?;
+        if digest > &job.share_target {
+            return Err(ShareError::LowDifficulty);
+        }
+        if !self.nonces.insert(nonce.clone()) {
+            return Err(ShareError::Duplicate);
+        }
+        if digest <= &job.target {
+            Ok(Share::Block)
+        } else {
+            Ok(Share::Accepted)
+        }
+    }
+}
+
+/// A share on its way from a miner's connection to the driver.
+struct Submission {
+    worker: String,
+    job_id: u64,
+    nonce: Hash,
+    digest: UBig,
+    proof: NounSlab,
+    result: oneshot::Sender<Result<Share, ShareError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+fn string_param<'a>(params: &'a [Value], idx: usize, name: &str) -> Result<&'a str, ShareError> {
+    params
+        .get(idx)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ShareError::Invalid(format!("missing {name}")))
+}
+
+/// Parse the params of `mining.submit`, cueing the proof.
+fn parse_submit(params: &[Value]) -> Result<(String, u64, Hash, UBig, NounSlab), ShareError> {
+    let worker = string_param(params, 0, "worker")?;
+    let job_id = u64::from_str_radix(string_param(params, 1, "job_id")?, 16)
+        .map_err(|e| ShareError::Invalid(format!("invalid job_id: {e}")))?;
+    let nonce = Hash::from_base58(string_param(params, 2, "nonce")?)
+        .map_err(|e| ShareError::Invalid(format!("invalid nonce: {e}")))?;
+    let digest = UBig::from_str_radix(string_param(params, 3, "digest")?, 16)
+        .map_err(|e| ShareError::Invalid(format!("invalid digest: {e}")))?;
+    let proof_bytes = BASE64
+        .decode(string_param(params, 4, "proof")?)
+        .map_err(|e| ShareError::Invalid(format!("invalid proof encoding: {e}")))?;
+    let mut proof = NounSlab::new();
+    let proof_noun = proof
+        .cue_into(Bytes::from(proof_bytes))
+        .map_err(|e| ShareError::Invalid(format!("invalid proof: {e:?}")))?;
+    proof.set_root(proof_noun);
+    Ok((worker.to_string(), job_id, nonce, digest, proof))
+}
+
+/// Decode a `[%mine version commit target pow-len]` effect.
+fn parse_mine_effect(effect: &NounSlab) -> Option<(u64, Hash, UBig, u64)> {
+    let effect_cell = unsafe { effect.root() }.as_cell().ok()?;
+    if !effect_cell.head().eq_bytes("mine") {
+        return None;
+    }
+    let [version, commit, target, pow_len] = effect_cell.tail().uncell().ok()?;
+    Some((
+        version.as_atom().ok()?.as_u64().ok()?,
+        Hash::from_noun(&commit).ok()?,
+        BigNum::from_noun(&target).ok()?.0,
+        pow_len.as_atom().ok()?.as_u64().ok()?,
+    ))
+}
+
+/// Create a driver serving the node's candidate blocks to stratum miners on `addr`, crediting
+/// shares that meet the block target made `share_factor` times easier.
+pub fn create_stratum_driver(addr: SocketAddr, share_factor: u64) -> IODriverFn {
+    Box::new(move |handle| {
+        Box::pin(async move {
+            let listener = TcpListener::bind(addr).await.map_err(|e| {
+                NockAppError::OtherError(format!("Failed to bind stratum server to {addr}: {e}"))
+            })?;
+            info!("Stratum server listening on {addr}");
+
+            let mut pool = Pool::new(share_factor);
+            let (jobs_tx, jobs_rx) = watch::channel::<Option<Arc<Job>>>(None);
+            let (submissions_tx, mut submissions_rx) = mpsc::channel(SUBMISSION_QUEUE);
+            let mut next_session = 0u64;
+
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        let (stream, peer) = match accepted {
+                            Ok(accepted) => accepted,
+                            Err(e) => {
+                                warn!("Failed to accept stratum connection: {e}");
+                                continue;
+                            }
+                        };
+                        next_session += 1;
+                        debug!("Stratum miner connected from {peer}, session {next_session}");
+                        let jobs = jobs_rx.clone();
+                        let submissions = submissions_tx.clone();
+                        let session = next_session;
+                        tokio::spawn(async move {
+                            if let Err(e) = serve_miner(stream, session, jobs, submissions).await {
+                                debug!("Stratum session {session} from {peer} ended: {e}");
+                            }
+                        });
+                    }
+                    effect_res = handle.next_effect() => {
+                        let Ok(effect) = effect_res else {
+                            warn!("Error receiving effect in stratum driver: {effect_res:?}");
+                            continue;
+                        };
+                        let Some((version, header, target, pow_len)) = parse_mine_effect(&effect) else {
+                            continue;
+                        };
+                        let job = pool.new_job(version, header, target, pow_len);
+                        debug!("New stratum job {} for header {}", job.id, job.header.to_base58());
+                        jobs_tx.send_replace(Some(job));
+                    }
+                    Some(submission) = submissions_rx.recv() => {
+                        let Submission { worker, job_id, nonce, digest, proof, result } = submission;
+                        let share = pool.submit(&worker, job_id, &nonce, &digest);
+                        if let (Ok(Share::Block), Some(job)) = (&share, &pool.job) {
+                            let stats = pool.workers[&worker];
+                            info!(
+                                "Stratum worker {worker} found a block for job {job_id} \
+                                 ({} shares, {} rejected, {} blocks)",
+                                stats.shares, stats.rejected, stats.blocks
+                            );
+                            poke_pow(&handle, job, &nonce, &digest, &proof).await?;
+                        }
+                        let _ = result.send(share);
+                    }
+                }
+            }
+        })
+    })
+}
+
+/// Poke `[%command %pow prf dig header nonce]` for a share that meets the block target.
+async fn poke_pow(
+    handle: &NockAppHandle,
+    job: &Job,
+    nonce: &Hash,
+    digest: &UBig,
+    proof: &NounSlab,
+) -> Result<(), NockAppError> {
+    let mut slab = NounSlab::new();
+    let proof = slab.copy_into(unsafe { *proof.root() });
+    let digest = Atom::from_ubig(&mut slab, digest).as_noun();
+    let header = job.header.to_noun(&mut slab);
+    let nonce = nonce.to_noun(&mut slab);
+    let poke = T(
+        &mut slab,
+        &[D(tas!(b"command")), D(tas!(b"pow")), proof, digest, header, nonce],
+    );
+    slab.set_root(poke);
+    handle.poke(MiningWire::Mined.to_wire(), slab).await?;
+    Ok(())
+}
+
+async fn send(writer: &mut OwnedWriteHalf, message: &Value) -> std::io::Result<()> {
+    let mut line = message.to_string();
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await
+}
+
+fn reply(id: Value, result: Result<Value, ShareError>) -> Value {
+    match result {
+        Ok(result) => json!({ "id": id, "result": result, "error": null }),
+        Err(e) => json!({ "id": id, "result": null, "error": [e.code(), e.to_string(), null] }),
+    }
+}
+
+async fn serve_miner(
+    stream: TcpStream,
+    session: u64,
+    mut jobs: watch::Receiver<Option<Arc<Job>>>,
+    submissions: mpsc::Sender<Submission>,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut subscribed = false;
+    let mut workers = HashSet::new();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    return Ok(());
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let request: Request = match serde_json::from_str(&line) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        let error = ShareError::Invalid(format!("invalid request: {e}"));
+                        send(&mut writer, &reply(Value::Null, Err(error))).await?;
+                        continue;
+                    }
+                };
+                match request.method.as_str() {
+                    "mining.subscribe" => {
+                        subscribed = true;
+                        let result = Ok(json!([format!("{session:016x}")]));
+                        send(&mut writer, &reply(request.id, result)).await?;
+                        let job = jobs.borrow_and_update().clone();
+                        if let Some(job) = job {
+                            for message in job.messages() {
+                                send(&mut writer, &message).await?;
+                            }
+                        }
+                    }
+                    "mining.authorize" => {
+                        let result = string_param(&request.params, 0, "worker").and_then(|worker| {
+                            if worker.is_empty() || worker.len() > MAX_WORKER_NAME {
+                                return Err(ShareError::Invalid("invalid worker name".into()));
+                            }
+                            workers.insert(worker.to_string());
+                            Ok(json!(true))
+                        });
+                        send(&mut writer, &reply(request.id, result)).await?;
+                    }
+                    "mining.submit" => {
+                        let result = submit(subscribed, &workers, &request.params, &submissions).await;
+                        send(&mut writer, &reply(request.id, result.map(|_| json!(true)))).await?;
+                    }
+                    method => {
+                        let error = ShareError::Invalid(format!("unknown method {method}"));
+                        send(&mut writer, &reply(request.id, Err(error))).await?;
+                    }
+                }
+            }
+            changed = jobs.changed(), if subscribed => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+                let job = jobs.borrow_and_update().clone();
+                if let Some(job) = job {
+                    for message in job.messages() {
+                        send(&mut writer, &message).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn submit(
+    subscribed: bool,
+    workers: &HashSet<String>,
+    params: &[Value],
+    submissions: &mpsc::Sender<Submission>,
+) -> Result<Share, ShareError> {
+    if !subscribed {
+        return Err(ShareError::NotSubscribed);
+    }
+    let (worker, job_id, nonce, digest, proof) = parse_submit(params)?;
+    if !workers.contains(&worker) {
+        return Err(ShareError::Unauthorized);
+    }
+    let (result, share) = oneshot::channel();
+    submissions
+        .send(Submission {
+            worker,
+            job_id,
+            nonce,
+            digest,
+            proof,
+            result,
+        })
+        .await
+        .map_err(|_| ShareError::Invalid("stratum server shutting down".into()))?;
+    share
+        .await
+        .map_err(|_| ShareError::Invalid("stratum server shutting down".into()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use nockchain_math::belt::Belt;
+
+    use super::*;
+
+    fn hash(n: u64) -> Hash {
+        Hash([Belt(n), Belt(0), Belt(0), Belt(0), Belt(0)])
+    }
+
+    #[test]
+    fn shares_are_checked_against_the_current_job() {
+        let mut pool = Pool::new(4);
+        let job = pool.new_job(2, hash(1), UBig::from(100u32), 64);
+        assert_eq!(job.share_target, UBig::from(400u32));
+
+        let share = pool.submit("alice", job.id, &hash(10), &UBig::from(300u32));
+        assert_eq!(share, Ok(Share::Accepted));
+        let block = pool.submit("alice", job.id, &hash(11), &UBig::from(100u32));
+        assert_eq!(block, Ok(Share::Block));
+        let duplicate = pool.submit("alice", job.id, &hash(11), &UBig::from(100u32));
+        assert_eq!(duplicate, Err(ShareError::Duplicate));
+        let low = pool.submit("bob", job.id, &hash(12), &UBig::from(401u32));
+        assert_eq!(low, Err(ShareError::LowDifficulty));
+
+        let next = pool.new_job(2, hash(2), UBig::from(100u32), 64);
+        let stale = pool.submit("bob", job.id, &hash(13), &UBig::from(1u32));
+        assert_eq!(stale, Err(ShareError::StaleJob));
+        // Nonces are only unique within a job
+        let reused = pool.submit("bob", next.id, &hash(11), &UBig::from(300u32));
+        assert_eq!(reused, Ok(Share::Accepted));
+
+        assert_eq!(
+            pool.workers["alice"],
+            WorkerStats {
+                shares: 2,
+                rejected: 1,
+                blocks: 1
+            }
+        );
+        assert_eq!(
+            pool.workers["bob"],
+            WorkerStats {
+                shares: 1,
+                rejected: 2,
+                blocks: 0
+            }
+        );
+    }
+
+    #[test]
+    fn submit_params_are_parsed() {
+        let mut slab: NounSlab = NounSlab::new();
+        let proof = T(&mut slab, &[D(1), D(2)]);
+        slab.set_root(proof);
+        let params = vec![
+            json!("alice"),
+            json!("1f"),
+            json!(hash(5).to_base58()),
+            json!("ff"),
+            json!(BASE64.encode(slab.jam())),
+        ];
+
+        let (worker, job_id, nonce, digest, proof) = parse_submit(&params).unwrap();
+        assert_eq!(worker, "alice");
+        assert_eq!(job_id, 31);
+        assert_eq!(nonce, hash(5));
+        assert_eq!(digest, UBig::from(255u32));
+        assert!(unsafe { proof.root() }.is_cell());
+
+        let missing = parse_submit(&params[..4]);
+        assert!(matches!(missing, Err(ShareError::Invalid(_))));
+    }
+}
@@ -0,0 +1,193 @@
+//! `nockchain verify-state`: check the node's saved state for corruption before starting it, e.g.
+//! after an unclean shutdown.
+//!
+//! Both checkpoint files are read and their checksums recomputed. A node boots from the newest
+//! checkpoint that reads cleanly, so a damaged newer checkpoint would otherwise go unnoticed while
+//! the node runs from older state. The state is then loaded as it is at boot, which rehashes
+//! every chunk it is stored in, and the kernel checks every block it holds against its header:
+//! that its digest is the hash of the header, that it follows its parent, that it matches the
+//! checkpointed digests on mainnet, and that the heaviest chain follows block parents.
+//!
+//! Nothing under the data directory is changed. Flags that would change the saved state at boot,
+//! such as `--state-jam` or `--reload-kernel`, are refused.
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use nockapp::data_dir::DataDir;
+use nockapp::kernel::boot;
+use nockapp::noun::slab::{NockJammer, NounSlab};
+use nockapp::save::{CheckpointFile, CHECKPOINT_FILES};
+use nockapp::utils::make_tas;
+use nockapp::{default_data_dir, NockApp};
+use nockchain_types::tx_engine::common::Hash;
+use nockvm::jets::hot::HotEntry;
+use nockvm::noun::{Noun, SIG, T};
+use noun_serde::{NounDecode, NounDecodeError};
+use tracing::{error, info};
+
+use crate::NockchainCli;
+
+/// Something wrong with the saved state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// The block the problem was found in, if any
+    pub block: Option<Hash>,
+    pub description: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.block {
+            Some(block) => write!(f, "block {}: {}", block.to_base58(), self.description),
+            None => write!(f, "{}", self.description),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StateReport {
+    /// Event number of each checkpoint that read cleanly
+    pub checkpoints: Vec<(PathBuf, u64)>,
+    pub problems: Vec<Problem>,
+}
+
+impl StateReport {
+    pub fn is_clean(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn log(&self) {
+        for (path, event_num) in &self.checkpoints {
+            info!(
+                "Checkpoint {} is intact at event {event_num}",
+                path.display()
+            );
+        }
+        for problem in &self.problems {
+            error!("State is corrupt: {problem}");
+        }
+        if self.is_clean() {
+            info!("Saved state verified, no problems found");
+        } else {
+            error!(
+                "Found {} problems in the saved state; restore it from a checkpoint store or resync with --new",
+                self.problems.len()
+            );
+        }
+    }
+}
+
+fn checkpoints_dir(cli: &boot::Cli) -> PathBuf {
+    match &cli.data_dir {
+        Some(root) => DataDir::new(root).checkpoints("nockchain"),
+        None => default_data_dir("nockchain").join("checkpoints"),
+    }
+}
+
+/// Check the saved state the node would start from.
+pub async fn verify_state(
+    cli: NockchainCli,
+    kernel_jam: &[u8],
+    hot_state: &[HotEntry],
+) -> Result<StateReport, Box<dyn Error>> {
+    if cli.nockapp_cli.new {
+        return Err("--new discards the saved state, nothing to verify".into());
+    }
+    if let Some(flag) = cli.nockapp_cli.state_writing_flag() {
+        return Err(
+            format!("{flag} changes the saved state, which verify-state only reads").into(),
+        );
+    }
+    let dir = checkpoints_dir(&cli.nockapp_cli);
+    let mut report = StateReport::default();
+    let mut found = false;
+    for name in CHECKPOINT_FILES {
+        let path = dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        found = true;
+        match CheckpointFile::read(&path).await {
+            Ok(file) => report.checkpoints.push((path, file.event_num)),
+            Err(e) => report.problems.push(Problem {
+                block: None,
+                description: format!("checkpoint {} is unreadable: {e}", path.display()),
+            }),
+        }
+    }
+    if !found {
+        return Err(format!("No saved state in {}", dir.display()).into());
+    }
+
+    let mut app: NockApp<NockJammer> =
+        match boot::load_saved_state(kernel_jam, cli.nockapp_cli, hot_state, "nockchain", None)
+            .await
+        {
+            Ok(app) => app,
+            Err(e) => {
+                report.problems.push(Problem {
+                    block: None,
+                    description: format!("state failed to load: {e}"),
+                });
+                return Ok(report);
+            }
+        };
+
+    let mut path = NounSlab::new();
+    let tag = make_tas(&mut path, "verify-state").as_noun();
+    let path_noun = T(&mut path, &[tag, SIG]);
+    path.set_root(path_noun);
+    let Some(result) = app.peek_handle(path).await? else {
+        return Err("Kernel does not support verify-state".into());
+    };
+    report
+        .problems
+        .extend(decode_problems(unsafe { result.root() })?);
+    Ok(report)
+}
+
+/// Decode the kernel's `(list [(unit block-id) @t])` of problems.
+fn decode_problems(noun: &Noun) -> Result<Vec<Problem>, NounDecodeError> {
+    Ok(Vec::<(Option<Hash>, String)>::from_noun(noun)?
+        .into_iter()
+        .map(|(block, description)| Problem { block, description })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use nockchain_math::belt::Belt;
+    use nockvm::noun::D;
+    use noun_serde::NounEncode;
+
+    use super::*;
+
+    #[test]
+    fn problems_are_decoded() {
+        let mut slab: NounSlab = NounSlab::new();
+        let block = Hash([Belt(1), Belt(2), Belt(3), Belt(4), Belt(5)]);
+        let some_block = Some(block.clone()).to_noun(&mut slab);
+        let missing = make_tas(&mut slab, "parent block is missing").as_noun();
+        let inconsistent = make_tas(&mut slab, "consensus state is inconsistent").as_noun();
+        let first = T(&mut slab, &[some_block, missing]);
+        let second = T(&mut slab, &[D(0), inconsistent]);
+        let list = T(&mut slab, &[first, second, D(0)]);
+
+        let problems = decode_problems(&list).unwrap();
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    block: Some(block),
+                    description: "parent block is missing".to_string(),
+                },
+                Problem {
+                    block: None,
+                    description: "consensus state is inconsistent".to_string(),
+                },
+            ]
+        );
+        assert_eq!(problems[1].to_string(), "consensus state is inconsistent");
+    }
+}
@@ -17,6 +17,8 @@ GITHUB_REPO="nockchain/nockchain"
 VERSION="unknown"
 RELEASE_TAG="unknown"
 CHANNEL="stable"
+# Where nockup keeps its binaries, caches and config
+NOCKUP_HOME="${NOCKUP_HOME:-$HOME/.nockup}"
 CONFIG_URL_MACOS="https://raw.githubusercontent.com/nockchain/nockchain/refs/heads/master/crates/nockup/default-config-aarch64-apple-darwin.toml"
 CONFIG_URL_LINUX="https://raw.githubusercontent.com/nockchain/nockchain/refs/heads/master/crates/nockup/default-config-x86_64-unknown-linux-gnu.toml"
 # Determine config URL based on OS
@@ -183,7 +185,7 @@ detect_platform() {
 
 # Function to setup toolchain directory with channel manifests
 setup_toolchain() {
-    local toolchain_dir="$HOME/.nockup/toolchains"
+    local toolchain_dir="$NOCKUP_HOME/toolchains"
     
     mkdir -p "$toolchain_dir"
     
@@ -269,7 +271,7 @@ EOF
 
 # Function to setup config file
 setup_config() {
-    local config_dir="$HOME/.nockup"
+    local config_dir="$NOCKUP_HOME"
     local config_file="$config_dir/config.toml"
     
     mkdir -p "$config_dir"
@@ -332,12 +334,13 @@ add_to_path() {
         fi
     fi
     
-    local activate_script="$HOME/.nockup/activate.sh"
-    cat > "$activate_script" << 'EOF'
+    local activate_script="$NOCKUP_HOME/activate.sh"
+    cat > "$activate_script" << EOF
 #!/bin/bash
 # Nockup environment activation script
-# Usage: source ~/.nockup/activate.sh
-export PATH="$HOME/.nockup/bin:$PATH"
+# Usage: source $activate_script
+export NOCKUP_HOME="$NOCKUP_HOME"
+export PATH="$NOCKUP_HOME/bin:\$PATH"
 echo "✅ Nockup environment activated!"
 echo "📦 nockup is now available in PATH"
 EOF
@@ -466,7 +469,7 @@ main() {
 
     print_success "Extracted Nockup binary"
 
-    local install_dir="$HOME/.nockup/bin"
+    local install_dir="$NOCKUP_HOME/bin"
     local nockup_path="$install_dir/nockup"
     
     print_step "Installing Nockup binary"
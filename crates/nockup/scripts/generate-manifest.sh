@@ -147,6 +147,18 @@ else
     SHA1_HASH="0000000000000000000000000000000000000000"
 fi
 
+# Compute SHA-256 hash
+if command -v sha256sum >/dev/null 2>&1; then
+    SHA256_HASH=$(sha256sum "$DOWNLOAD_PATH" | awk '{print $1}')
+    echo "SHA-256: $SHA256_HASH" >&2
+elif command -v shasum >/dev/null 2>&1; then
+    SHA256_HASH=$(shasum -a 256 "$DOWNLOAD_PATH" | awk '{print $1}')
+    echo "SHA-256: $SHA256_HASH" >&2
+else
+    echo "Error: sha256sum/shasum not found; nockup will not install binaries without a SHA-256 hash" >&2
+    exit 1
+fi
+
 # Create manifest directory if it doesn't exist
 MANIFEST_DIR="${MANIFEST_DIR:-crates/nockup/toolchains}"
 mkdir -p "$MANIFEST_DIR"
@@ -168,6 +180,7 @@ components = ["core"]
 [pkg.$BINARY.target.$TARGET]
 available = true
 url = "$URL"
+hash_sha256 = "$SHA256_HASH"
 hash_blake3 = "$BLAKE3_HASH"
 hash_sha1 = "$SHA1_HASH"
 EOF
@@ -176,4 +189,5 @@ echo "" >&2
 echo "✓ Generated manifest: $MANIFEST_FILE" >&2
 echo "✓ Version: $VERSION" >&2
 echo "✓ BLAKE3: $BLAKE3_HASH" >&2
-echo "✓ SHA-1: $SHA1_HASH" >&2
\ No newline at end of file
+echo "✓ SHA-1: $SHA1_HASH" >&2
+echo "✓ SHA-256: $SHA256_HASH" >&2
\ No newline at end of file
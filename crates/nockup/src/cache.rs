@@ -1,8 +1,46 @@
 use std::collections::HashMap;
+use std::fs::{File, TryLockError};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use anyhow::{Context, Result};
+use colored::Colorize;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::commands::common::get_cache_dir;
+
+/// File in the cache root that nockups lock while they change the cache
+const LOCK_FILE: &str = ".lock";
+
+/// The cache locks this process holds, by lock file, with how many guards share each
+static HELD_LOCKS: Lazy<Mutex<HashMap<PathBuf, (File, usize)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Exclusive hold on a cache, for as long as it lives
+///
+/// Guards nest: a command that holds the lock across several changes can make them with methods
+/// that lock the cache themselves. The lock is released with the last guard.
+#[derive(Debug)]
+pub struct CacheLock {
+    path: PathBuf,
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        let mut held = HELD_LOCKS
+            .lock()
+            .expect("cache lock table should not be poisoned");
+        if let Some((_, guards)) = held.get_mut(&self.path) {
+            *guards -= 1;
+            if *guards == 0 {
+                // Closing the file unlocks it
+                held.remove(&self.path);
+            }
+        }
+    }
+}
 
 /// Metadata about a cached package
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +50,8 @@ pub struct CachedPackage {
     pub commit: String,       // Exact commit hash
     pub cached_at: u64,       // Unix timestamp
     pub source_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>, // content_hash() when cached, checked by `nockup cache verify`
 }
 
 /// Cache index tracking all cached packages
@@ -28,14 +68,15 @@ pub struct PackageCache {
 impl PackageCache {
     /// Create a new PackageCache, creating directories if needed
     pub fn new() -> Result<Self> {
-        let home =
-            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-        let root = home.join(".nockup").join("cache");
+        let root = get_cache_dir()?.join("cache");
 
         // Create cache directories
         std::fs::create_dir_all(root.join("git"))?;
         std::fs::create_dir_all(root.join("packages"))?;
         std::fs::create_dir_all(root.join("registry"))?;
+        std::fs::create_dir_all(root.join("cas"))?;
+        std::fs::create_dir_all(root.join("builds"))?;
+        std::fs::create_dir_all(root.join("templates"))?;
 
         Ok(Self { root })
     }
@@ -45,6 +86,9 @@ impl PackageCache {
         std::fs::create_dir_all(root.join("git"))?;
         std::fs::create_dir_all(root.join("packages"))?;
         std::fs::create_dir_all(root.join("registry"))?;
+        std::fs::create_dir_all(root.join("cas"))?;
+        std::fs::create_dir_all(root.join("builds"))?;
+        std::fs::create_dir_all(root.join("templates"))?;
 
         Ok(Self { root })
     }
@@ -69,6 +113,65 @@ impl PackageCache {
         self.root.join("registry")
     }
 
+    /// Get the content-addressed store that cached package files are hardlinked from
+    /// Format: ~/.nockup/cache/cas/<first 2 hex digits>/<rest of the SHA-256 of the contents>
+    pub fn cas_dir(&self) -> PathBuf {
+        self.root.join("cas")
+    }
+
+    /// Get the directory that hoonc outputs are cached in, by the hash of their sources
+    pub fn builds_dir(&self) -> PathBuf {
+        self.root.join("builds")
+    }
+
+    /// Get the directory that templates from git repositories are cloned into (for GitFetcher)
+    pub fn templates_dir(&self) -> PathBuf {
+        self.root.join("templates")
+    }
+
+    /// Lock the cache against other nockups, waiting for any that holds it
+    ///
+    /// The lock is advisory, on ~/.nockup/cache/.lock; every change to the cache index, the
+    /// package directories and the store is made holding it.
+    pub async fn lock(&self) -> Result<CacheLock> {
+        let path = self.root.join(LOCK_FILE);
+        if let Some((_, guards)) = HELD_LOCKS
+            .lock()
+            .expect("cache lock table should not be poisoned")
+            .get_mut(&path)
+        {
+            *guards += 1;
+            return Ok(CacheLock { path });
+        }
+
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let file = match file.try_lock() {
+            Ok(()) => file,
+            Err(TryLockError::WouldBlock) => {
+                say!(
+                    "{} Waiting for another nockup to finish with the cache...",
+                    "⏳".yellow()
+                );
+                tokio::task::spawn_blocking(move || file.lock().map(|()| file))
+                    .await?
+                    .with_context(|| format!("Failed to lock {}", path.display()))?
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+            }
+        };
+        HELD_LOCKS
+            .lock()
+            .expect("cache lock table should not be poisoned")
+            .insert(path.clone(), (file, 1));
+        Ok(CacheLock { path })
+    }
+
     /// Get the path for a specific package version
     /// Format: ~/.nockup/cache/packages/<name>/<version-spec>/
     pub fn package_path(&self, name: &str, version_spec: &str) -> PathBuf {
@@ -82,6 +185,9 @@ impl PackageCache {
     }
 
     /// Cache a package from a git repo path
+    ///
+    /// The package is copied next to where it goes and then renamed into place, replacing any
+    /// earlier copy, so its directory is never seen half written.
     pub async fn cache_package(
         &self,
         name: &str,
@@ -90,6 +196,7 @@ impl PackageCache {
         source_url: &str,
         source_path: &Path,
     ) -> Result<PathBuf> {
+        let _lock = self.lock().await?;
         let target_path = self.package_path(name, version_spec);
 
         // Create parent directory
@@ -97,8 +204,24 @@ impl PackageCache {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        // Copy source to cache
-        self.copy_directory(source_path, &target_path).await?;
+        // Copy source to cache, under a temporary name left behind only by an interrupted copy
+        let partial = target_path.with_file_name(format!(
+            "{}.partial",
+            self.sanitize_version_spec(version_spec)
+        ));
+        if partial.exists() {
+            tokio::fs::remove_dir_all(&partial).await?;
+        }
+        self.copy_directory(source_path, &partial).await?;
+        let checksum = content_hash(&partial)?;
+        if target_path.exists() {
+            tokio::fs::remove_dir_all(&target_path)
+                .await
+                .with_context(|| format!("Failed to remove {}", target_path.display()))?;
+        }
+        tokio::fs::rename(&partial, &target_path)
+            .await
+            .with_context(|| format!("Failed to move package into {}", target_path.display()))?;
 
         let cached_at = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -112,12 +235,25 @@ impl PackageCache {
             commit: commit.to_string(),
             cached_at,
             source_url: source_url.to_string(),
+            checksum: Some(checksum),
         })
         .await?;
 
         Ok(target_path)
     }
 
+    /// Remove a cached package version, e.g. before fetching it again
+    pub async fn evict(&self, name: &str, version_spec: &str) -> Result<()> {
+        let _lock = self.lock().await?;
+        let path = self.package_path(name, version_spec);
+        if path.exists() {
+            tokio::fs::remove_dir_all(&path)
+                .await
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+
     /// Load the cache index
     pub async fn load_index(&self) -> Result<CacheIndex> {
         let index_path = self.root.join("cache-index.json");
@@ -134,27 +270,62 @@ impl PackageCache {
     }
 
     /// Save the cache index
+    ///
+    /// Callers that loaded `index` to change it should hold the cache's lock since loading it.
     pub async fn save_index(&self, index: &CacheIndex) -> Result<()> {
-        let index_path = self.root.join("cache-index.json");
+        let _lock = self.lock().await?;
         let contents = serde_json::to_string_pretty(index)?;
-        tokio::fs::write(&index_path, contents).await?;
-        Ok(())
+        write_atomically(&self.root.join("cache-index.json"), contents).await
     }
 
     /// Add a package to the cache index
     async fn add_to_index(&self, package: CachedPackage) -> Result<()> {
         let mut index = self.load_index().await?;
 
-        index
+        let versions = index
             .packages
             .entry(package.name.clone())
-            .or_insert_with(Vec::new)
-            .push(package);
+            .or_insert_with(Vec::new);
+        // Re-caching a version replaces its entry
+        versions.retain(|p| p.version_spec != package.version_spec);
+        versions.push(package);
 
         self.save_index(&index).await?;
         Ok(())
     }
 
+    /// Remember a project's nockapp.lock, so garbage collection keeps what it references
+    pub async fn register_lockfile(&self, lock_path: &Path) -> Result<()> {
+        let _lock = self.lock().await?;
+        let lock_path = lock_path
+            .canonicalize()
+            .with_context(|| format!("Failed to find {}", lock_path.display()))?;
+        let mut lockfiles = self.known_lockfiles().await?;
+        if !lockfiles.contains(&lock_path) {
+            lockfiles.push(lock_path);
+            lockfiles.sort();
+            self.save_lockfiles(&lockfiles).await?;
+        }
+        Ok(())
+    }
+
+    /// The nockapp.lock files of projects installed from this cache
+    pub async fn known_lockfiles(&self) -> Result<Vec<PathBuf>> {
+        let path = self.root.join("lockfiles.json");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = tokio::fs::read_to_string(&path).await?;
+        serde_json::from_str(&contents).context("Failed to parse list of known lockfiles")
+    }
+
+    /// Replace the list of known lockfiles, e.g. to forget projects that were deleted
+    pub async fn save_lockfiles(&self, lockfiles: &[PathBuf]) -> Result<()> {
+        let _lock = self.lock().await?;
+        let contents = serde_json::to_string_pretty(lockfiles)?;
+        write_atomically(&self.root.join("lockfiles.json"), contents).await
+    }
+
     /// List all cached packages
     pub async fn list_cached(&self) -> Result<Vec<CachedPackage>> {
         let index = self.load_index().await?;
@@ -183,14 +354,54 @@ impl PackageCache {
         Ok(None)
     }
 
+    /// Find the most recently cached commit of a package, which is what a wildcard
+    /// ("*" or "latest") version was last resolved to
+    pub async fn find_latest_commit(&self, name: &str) -> Result<Option<CachedPackage>> {
+        let index = self.load_index().await?;
+
+        Ok(index.packages.get(name).and_then(|packages| {
+            packages
+                .iter()
+                .filter(|pkg| pkg.version_spec.starts_with("commit:"))
+                .max_by_key(|pkg| pkg.cached_at)
+                .cloned()
+        }))
+    }
+
+    /// Find the most recently cached commit of a package fetched from `source_url`
+    pub async fn find_latest_commit_from(
+        &self,
+        name: &str,
+        source_url: &str,
+    ) -> Result<Option<CachedPackage>> {
+        let index = self.load_index().await?;
+
+        Ok(index.packages.get(name).and_then(|packages| {
+            packages
+                .iter()
+                .filter(|pkg| {
+                    pkg.version_spec.starts_with("commit:") && pkg.source_url == source_url
+                })
+                .max_by_key(|pkg| pkg.cached_at)
+                .cloned()
+        }))
+    }
+
     /// Clean the cache (remove all cached packages)
     pub async fn clean(&self) -> Result<()> {
+        let _lock = self.lock().await?;
         // Remove packages directory
         if self.packages_dir().exists() {
             tokio::fs::remove_dir_all(self.packages_dir()).await?;
             tokio::fs::create_dir_all(self.packages_dir()).await?;
         }
 
+        // Remove the files they were linked from
+        if self.cas_dir().exists() {
+            tokio::fs::remove_dir_all(self.cas_dir()).await?;
+            tokio::fs::create_dir_all(self.cas_dir()).await?;
+        }
+
         // Clear index
         self.save_index(&CacheIndex::default()).await?;
 
@@ -199,6 +410,7 @@ impl PackageCache {
 
     /// Prune old cached packages (keep only latest N versions per package)
     pub async fn prune(&self, keep_versions: usize) -> Result<()> {
+        let _lock = self.lock().await?;
         let mut index = self.load_index().await?;
 
         for (name, packages) in &mut index.packages {
@@ -217,14 +429,45 @@ impl PackageCache {
                 if path.exists() {
                     tokio::fs::remove_dir_all(&path).await?;
                 }
-                println!("  Pruned {}@{}", name, pkg.version_spec);
+                say!("  Pruned {}@{}", name, pkg.version_spec);
             }
         }
 
         self.save_index(&index).await?;
+        self.collect_garbage().await?;
         Ok(())
     }
 
+    /// Remove files from the content-addressed store that no cached package links to any more,
+    /// returning how many bytes were freed
+    ///
+    /// A stored file with a single link is only in the store. Link counts are not available on
+    /// every platform; elsewhere nothing is removed.
+    pub async fn collect_garbage(&self) -> Result<u64> {
+        let _lock = self.lock().await?;
+        let mut freed = 0;
+        if !self.cas_dir().exists() {
+            return Ok(freed);
+        }
+
+        let mut shards = tokio::fs::read_dir(self.cas_dir()).await?;
+        while let Some(shard) = shards.next_entry().await? {
+            if !shard.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut objects = tokio::fs::read_dir(shard.path()).await?;
+            while let Some(object) = objects.next_entry().await? {
+                let metadata = object.metadata().await?;
+                if metadata.is_file() && link_count(&metadata) == Some(1) {
+                    tokio::fs::remove_file(object.path()).await?;
+                    freed += metadata.len();
+                }
+            }
+        }
+
+        Ok(freed)
+    }
+
     /// Get cache statistics
     pub async fn stats(&self) -> Result<CacheStats> {
         let index = self.load_index().await?;
@@ -232,8 +475,10 @@ impl PackageCache {
         let total_packages = index.packages.values().map(|v| v.len()).sum();
         let unique_packages = index.packages.len();
 
-        // Calculate total size (approximate)
-        let total_size = self.calculate_directory_size(&self.packages_dir()).await?;
+        // Calculate total size (approximate): package files are mostly links into the store, so
+        // only files that are not are counted from the packages directory
+        let total_size = self.calculate_directory_size(&self.cas_dir()).await?
+            + self.calculate_unlinked_size(&self.packages_dir()).await?;
 
         Ok(CacheStats {
             total_packages,
@@ -281,7 +526,7 @@ impl PackageCache {
                 if src_path.is_dir() {
                     self.copy_directory(&src_path, &dst_path).await?;
                 } else {
-                    tokio::fs::copy(&src_path, &dst_path).await?;
+                    self.link_from_store(&src_path, &dst_path).await?;
                 }
             }
 
@@ -289,6 +534,68 @@ impl PackageCache {
         })
     }
 
+    /// Put a file in the content-addressed store, if it is not there yet, and hardlink it to
+    /// `dst`; identical files across packages and versions share one copy on disk
+    ///
+    /// Falls back to copying where hardlinks are not possible, e.g. across filesystems.
+    async fn link_from_store(&self, src: &Path, dst: &Path) -> Result<()> {
+        let contents = tokio::fs::read(src)
+            .await
+            .with_context(|| format!("Failed to read {}", src.display()))?;
+        let digest = hex::encode(Sha256::digest(&contents));
+        let object = self.cas_dir().join(&digest[..2]).join(&digest[2..]);
+
+        if !object.exists() {
+            let shard = object
+                .parent()
+                .expect("store objects are in a shard directory");
+            tokio::fs::create_dir_all(shard).await?;
+            // Write under a temporary name first, so an interrupted write never leaves a
+            // truncated object behind
+            let partial = shard.join(format!("{}.partial", &digest[2..]));
+            tokio::fs::write(&partial, &contents).await?;
+            tokio::fs::rename(&partial, &object).await?;
+        }
+
+        if dst.exists() {
+            tokio::fs::remove_file(dst).await?;
+        }
+        if tokio::fs::hard_link(&object, dst).await.is_err() {
+            tokio::fs::copy(&object, dst)
+                .await
+                .with_context(|| format!("Failed to copy {}", src.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Size of the files under `path` that are not hardlinked elsewhere (recursive)
+    fn calculate_unlinked_size<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<u64>> + 'a>> {
+        Box::pin(async move {
+            if !path.exists() {
+                return Ok(0);
+            }
+
+            let mut total_size = 0u64;
+            let mut entries = tokio::fs::read_dir(path).await?;
+
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let metadata = tokio::fs::metadata(&path).await?;
+
+                if path.is_dir() {
+                    total_size += self.calculate_unlinked_size(&path).await?;
+                } else if link_count(&metadata).is_none_or(|links| links < 2) {
+                    total_size += metadata.len();
+                }
+            }
+
+            Ok(total_size)
+        })
+    }
+
     /// Calculate total size of a directory (recursive)
     fn calculate_directory_size<'a>(
         &'a self,
@@ -318,6 +625,73 @@ impl PackageCache {
     }
 }
 
+/// Number of hardlinks to a file, where the platform reports it
+/// Write `path` under a temporary name and rename it into place, so readers that do not lock
+/// the cache never see it half written
+async fn write_atomically(path: &Path, contents: String) -> Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    tokio::fs::write(&partial, contents)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    tokio::fs::rename(&partial, path)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(unix)]
+fn link_count(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.nlink())
+}
+
+#[cfg(not(unix))]
+fn link_count(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// SHA-256 digest of a package directory's contents, as recorded in nockapp.lock
+///
+/// Covers every file's path relative to `dir` and its contents, in path order, so the digest of
+/// an installed copy matches that of the cached package it was copied from. `.git` directories
+/// are skipped, as they are when caching.
+pub fn content_hash(dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for relative in files {
+        let contents = std::fs::read(dir.join(&relative))
+            .with_context(|| format!("Failed to read {}", dir.join(&relative).display()))?;
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+
+    Ok(format!("sha256:{}", hex::encode(hasher.finalize())))
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path.strip_prefix(root)?;
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    Ok(())
+}
+
 /// Cache statistics
 #[derive(Debug)]
 pub struct CacheStats {
@@ -359,4 +733,137 @@ mod tests {
 
         assert_eq!(path, PathBuf::from("/tmp/test/packages/arvo/k414"));
     }
+
+    #[test]
+    fn test_content_hash() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let package = tmp.path().join("package");
+        std::fs::create_dir_all(package.join("lib")).unwrap();
+        std::fs::create_dir_all(package.join(".git")).unwrap();
+        std::fs::write(package.join("lib/map.hoon"), "|%  ++  map  ~  --").unwrap();
+        std::fs::write(package.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+
+        let hash = content_hash(&package).unwrap();
+        assert!(hash.starts_with("sha256:"));
+
+        // A copy without .git hashes the same
+        let copy = tmp.path().join("copy");
+        std::fs::create_dir_all(copy.join("lib")).unwrap();
+        std::fs::copy(package.join("lib/map.hoon"), copy.join("lib/map.hoon")).unwrap();
+        assert_eq!(content_hash(&copy).unwrap(), hash);
+
+        // Changed contents or a moved file change the digest
+        std::fs::write(copy.join("lib/map.hoon"), "|%  ++  map  !!  --").unwrap();
+        assert_ne!(content_hash(&copy).unwrap(), hash);
+        std::fs::copy(package.join("lib/map.hoon"), copy.join("lib/map.hoon")).unwrap();
+        std::fs::rename(copy.join("lib"), copy.join("sur")).unwrap();
+        assert_ne!(content_hash(&copy).unwrap(), hash);
+    }
+
+    #[tokio::test]
+    async fn test_find_latest_commit_from() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = PackageCache::with_root(tmp.path().join("cache")).unwrap();
+        let source = tmp.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("zuse.hoon"), "~").unwrap();
+
+        let upstream = "https://github.com/urbit/urbit";
+        let fork = "/home/me/zuse-fork";
+        cache
+            .cache_package("urbit/zuse", "commit:aaa", "aaa", upstream, &source)
+            .await
+            .unwrap();
+        cache
+            .cache_package("urbit/zuse", "commit:bbb", "bbb", fork, &source)
+            .await
+            .unwrap();
+
+        let found = cache
+            .find_latest_commit_from("urbit/zuse", upstream)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.commit, "aaa");
+        let found = cache
+            .find_latest_commit_from("urbit/zuse", fork)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.commit, "bbb");
+        assert!(cache
+            .find_latest_commit_from("urbit/zuse", "https://example.com/other")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cached_files_are_shared() {
+        use std::os::unix::fs::MetadataExt;
+
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = PackageCache::with_root(tmp.path().join("cache")).unwrap();
+        let source = tmp.path().join("source");
+        std::fs::create_dir_all(source.join("lib")).unwrap();
+        std::fs::write(source.join("lib/map.hoon"), "|%  ++  map  ~  --").unwrap();
+
+        let k409 = cache
+            .cache_package("urbit/zuse", "k409", "aaa", "https://example.com", &source)
+            .await
+            .unwrap();
+        let k408 = cache
+            .cache_package("urbit/zuse", "k408", "bbb", "https://example.com", &source)
+            .await
+            .unwrap();
+
+        // Both versions link to one stored copy
+        let a = std::fs::metadata(k409.join("lib/map.hoon")).unwrap();
+        let b = std::fs::metadata(k408.join("lib/map.hoon")).unwrap();
+        assert_eq!(a.ino(), b.ino());
+        assert_eq!(a.nlink(), 3);
+        assert_eq!(content_hash(&k409).unwrap(), content_hash(&source).unwrap());
+
+        // The stored copy goes once no package links to it
+        cache.evict("urbit/zuse", "k409").await.unwrap();
+        assert_eq!(cache.collect_garbage().await.unwrap(), 0);
+        cache.evict("urbit/zuse", "k408").await.unwrap();
+        assert_eq!(
+            cache.collect_garbage().await.unwrap(),
+            "|%  ++  map  ~  --".len() as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lock() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = PackageCache::with_root(tmp.path().join("cache")).unwrap();
+        let source = tmp.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("zuse.hoon"), "~").unwrap();
+        // Another nockup's attempt to lock the cache
+        let other = || {
+            let file = File::open(cache.root().join(LOCK_FILE)).unwrap();
+            file.try_lock().is_ok()
+        };
+
+        // Held across changes that lock the cache themselves
+        let lock = cache.lock().await.unwrap();
+        let path = cache
+            .cache_package("zuse", "k409", "aaa", "https://example.com", &source)
+            .await
+            .unwrap();
+        assert!(!other());
+        drop(lock);
+        assert!(other());
+
+        // Nothing of the copy is left under its temporary name
+        assert!(path.join("zuse.hoon").exists());
+        let names: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["k409"]);
+    }
 }
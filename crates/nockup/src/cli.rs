@@ -1,5 +1,10 @@
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
 
+use crate::commands::package::sbom::SbomFormat;
+use crate::output::OutputFormat;
+
 #[derive(Parser)]
 #[command(name = "nockup")]
 #[command(about = "A developer support framework for NockApp development")]
@@ -7,6 +12,22 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Never use the network: resolve packages only from ~/.nockup/cache
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Output format: text for people, or json events on stdout for tools
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    /// Plain one-line progress and documented exit codes, for CI (also on when CI is set)
+    #[arg(long, global = true)]
+    pub ci: bool,
+
+    /// Keep binaries, caches and config here instead of ~/.nockup (also $NOCKUP_HOME)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -28,6 +49,38 @@ pub enum Commands {
     #[command(subcommand)]
     Channel(ChannelCommand),
 
+    /// Choose the toolchain components that are installed for a channel
+    #[command(subcommand)]
+    Component(ComponentCommand),
+
+    /// Project templates, from the channel or git repositories
+    #[command(subcommand)]
+    Template(TemplateCommand),
+
+    /// Save a token for a private package registry
+    ///
+    /// The token is read from standard input. REGISTRY is a registry's URL or its name in
+    /// ~/.nockup/config.toml or nockapp.toml.
+    Login {
+        registry: String,
+        /// Keep the token in the OS keychain instead of ~/.nockup/credentials.toml
+        #[arg(long)]
+        keychain: bool,
+    },
+
+    /// Forget the token saved for a package registry
+    Logout { registry: String },
+
+    /// Check that nockup's environment is set up, and how to fix what is not
+    ///
+    /// Checks git, hoon and hoonc, PATH, ~/.nockup, symlinks in the current directory, and the
+    /// package registries. Exits with an error if any check fails.
+    Doctor,
+
+    /// Update, roll back or uninstall nockup itself
+    #[command(name = "self", subcommand)]
+    SelfUpdate(SelfCommand),
+
     // Legacy flat commands (backward compatible)
     /// Build a NockApp project
     #[command(hide = true)]
@@ -44,7 +97,11 @@ pub enum Commands {
     },
 
     /// Check for updates to nockup, hoon, and hoonc
-    Update,
+    Update {
+        /// Install binaries without checking the manifest signature or their checksums
+        #[arg(long)]
+        insecure_skip_verify: bool,
+    },
 
     /// Initialize nockup cache and download templates
     Install,
@@ -65,10 +122,33 @@ pub enum Commands {
 #[derive(clap::Subcommand, Debug)]
 pub enum ProjectCommand {
     /// Build a NockApp project
-    Build { project: Option<String> },
+    Build {
+        project: Option<String>,
+        /// Run hoonc for every binary, even if its sources are unchanged
+        #[arg(long)]
+        force: bool,
+        /// Fail if nockapp.lock is out of date or records another hoon or hoonc
+        #[arg(long)]
+        locked: bool,
+        /// Build for this target triple, with outputs in target/<TRIPLE>/
+        #[arg(long, value_name = "TRIPLE")]
+        target: Option<String>,
+        /// Build profile: dev, release, or one from [profile] in nockapp.toml
+        #[arg(long, value_name = "NAME", default_value = "release")]
+        profile: String,
+    },
     /// Run a NockApp project
     Run {
         project: Option<String>,
+        /// Binary to run, in a project with several
+        #[arg(long, value_name = "NAME")]
+        bin: Option<String>,
+        /// Build profile: dev, release, or one from [profile] in nockapp.toml
+        #[arg(long, value_name = "NAME", default_value = "release")]
+        profile: String,
+        /// Switch the running app to its kernel jam whenever it is rebuilt, keeping its state
+        #[arg(long)]
+        hot_reload: bool,
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
@@ -86,8 +166,10 @@ pub enum PackageCommand {
         /// Package name
         name: String,
         /// Version specification (e.g., @k409, ^1.2.3, @tag:v1.0.0)
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with_all = ["branch", "tag", "commit"])]
         version: Option<String>,
+        #[command(flatten)]
+        source: GitSource,
     },
 
     /// Remove a dependency from nockapp.toml
@@ -99,12 +181,66 @@ pub enum PackageCommand {
     /// List all dependencies and their installation status
     List,
 
+    /// Search the package registry by name, alias or description
+    Search {
+        /// Text to search for; lists every package if omitted
+        query: Option<String>,
+    },
+
     /// Install dependencies from nockapp.toml
-    Install,
+    Install {
+        /// Clone whole repositories instead of only the paths packages use
+        #[arg(long)]
+        full_clone: bool,
+
+        /// Fail if nockapp.lock does not match what nockapp.toml resolves to
+        #[arg(long, conflicts_with = "frozen")]
+        locked: bool,
+
+        /// Install the commits in nockapp.lock without resolving nockapp.toml again
+        #[arg(long)]
+        frozen: bool,
+    },
 
     /// Update dependencies to latest versions
     Update,
 
+    /// List locked packages that have newer versions upstream
+    Outdated,
+
+    /// Copy all dependencies into the project's vendor/ directory
+    Vendor,
+
+    /// Check locked packages against registry advisories and yanked versions
+    Audit,
+
+    /// Validate nockapp.toml or hoon.toml, nockapp.lock and the package files in hoon/
+    Check,
+
+    /// Print the licenses of the locked packages
+    Licenses {
+        /// Fail if a package has this license (repeatable)
+        #[arg(long, value_name = "LICENSE")]
+        deny: Vec<String>,
+    },
+
+    /// Write a software bill of materials for the locked packages
+    Sbom {
+        /// Document format (the global --format is for nockup's own output)
+        #[arg(value_enum, default_value_t)]
+        format: SbomFormat,
+        /// File to write it to, instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Print the resolved dependency graph
+    Tree {
+        /// Highlight packages requested at more than one version
+        #[arg(short = 'd', long)]
+        duplicates: bool,
+    },
+
     /// Clear the package cache
     Purge {
         /// Only show what would be deleted without actually deleting
@@ -112,12 +248,33 @@ pub enum PackageCommand {
         dry_run: bool,
     },
 
+    /// Publish the hoon.toml library in the current directory to the registry
+    Publish {
+        /// Registry endpoint to publish to (defaults to $NOCKUP_REGISTRY_URL)
+        #[arg(long, value_name = "URL")]
+        registry: Option<String>,
+        /// Repository the registry fetches the package from (defaults to the origin remote)
+        #[arg(long, value_name = "URL")]
+        git_url: Option<String>,
+        /// Only show the registry entry without publishing it
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+
     /// Grab a package (deprecated - use add)
     #[command(hide = true)]
     Grab { spec: String },
 
-    /// Generate proxy files for a package
-    GenerateProxy { url: String, path: Option<String> },
+    /// Add the Hoon libraries of a git repository, with a proxy library that imports them all
+    GenerateProxy {
+        /// Repository to wrap
+        url: String,
+        /// Directory of the repository its Hoon libraries are under
+        path: Option<String>,
+        /// Name of the dependency and the proxy (defaults to the repository's name)
+        #[arg(long)]
+        name: Option<String>,
+    },
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -133,10 +290,114 @@ pub enum CacheCommand {
         /// Clear registry cache
         #[arg(long)]
         registry: bool,
+        /// Clear cached hoonc builds
+        #[arg(long)]
+        builds: bool,
         /// Clear all caches
         #[arg(long)]
         all: bool,
     },
+    /// Remove git checkouts and cached packages that no known nockapp.lock references
+    Gc {
+        /// Keep unreferenced entries only while the cache is within this size, e.g. 2G
+        #[arg(long, value_name = "SIZE")]
+        max_size: Option<String>,
+        /// Keep unreferenced entries only if used within this many days
+        #[arg(long, value_name = "DAYS")]
+        max_age: Option<u64>,
+        /// Show what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check cached packages against the cache index and their checksums, evicting bad ones
+    Verify {
+        /// Report problems without repairing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum ComponentCommand {
+    /// Install components (hoon, hoonc, nockchain, nockchain-wallet, templates)
+    Add {
+        #[arg(required = true, value_name = "COMPONENT")]
+        components: Vec<String>,
+        /// Channel to add them to (defaults to the configured channel)
+        #[arg(long)]
+        channel: Option<String>,
+        /// Install them without checking the manifest signature or their checksums
+        #[arg(long)]
+        insecure_skip_verify: bool,
+    },
+    /// Uninstall components, so `nockup update` no longer fetches them
+    Remove {
+        #[arg(required = true, value_name = "COMPONENT")]
+        components: Vec<String>,
+        /// Channel to remove them from (defaults to the configured channel)
+        #[arg(long)]
+        channel: Option<String>,
+    },
+    /// List components, marking those installed
+    List {
+        /// Channel to list (defaults to the configured channel)
+        #[arg(long)]
+        channel: Option<String>,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Add a template from a git repository, as git:<url>#<ref> or just <url>
+    Add {
+        url: String,
+        /// Name to use it by in nockapp.toml (defaults to the repository's name)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// List available templates
+    List,
+}
+
+/// Where `nockup package add` fetches a package from, instead of the registry
+#[derive(clap::Args, Debug, Default)]
+pub struct GitSource {
+    /// Git repository to fetch the package from
+    #[arg(long, value_name = "URL")]
+    pub git: Option<String>,
+    /// Track a branch of the repository
+    #[arg(long, requires = "git", conflicts_with_all = ["tag", "commit"])]
+    pub branch: Option<String>,
+    /// Pin a tag of the repository
+    #[arg(long, requires = "git", conflicts_with = "commit")]
+    pub tag: Option<String>,
+    /// Pin a commit of the repository
+    #[arg(long, requires = "git")]
+    pub commit: Option<String>,
+    /// Directory of the repository holding the package
+    #[arg(long, requires = "git")]
+    pub path: Option<String>,
+    /// Install only this file, relative to --path and without .hoon (repeatable)
+    #[arg(long = "file", value_name = "FILE", requires = "git")]
+    pub files: Vec<String>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum SelfCommand {
+    /// Replace this nockup with the latest one on the current channel
+    Update {
+        /// Install it without checking the manifest signature or its checksums
+        #[arg(long)]
+        insecure_skip_verify: bool,
+    },
+    /// Swap back to the nockup that the last `self update` replaced
+    Rollback,
+    /// Remove ~/.nockup and the PATH lines nockup added to shell startup files
+    Uninstall {
+        /// Remove them; without this, only list what would be removed
+        #[arg(long)]
+        yes: bool,
+    },
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -0,0 +1,230 @@
+// src/commands/build/apps.rs
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::manifest::AppSpec;
+
+/// A Hoon kernel to compile with hoonc, and the jam to build it into
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct App {
+    pub name: String, // The binary it is for, or "app" for a project's only binary
+    pub bin: Option<String>, // The [[bin]] of Cargo.toml that runs it, if it names one
+    pub entry: PathBuf, // The kernel, relative to the project's hoon/ directory
+    pub jam: PathBuf, // Relative to the directory jams are built into
+}
+
+/// The apps of a project with the `binaries` of its Cargo.toml
+///
+/// These are the `[[app]]` entries of nockapp.toml. Without any, kernels are matched to
+/// binaries by name: a project with one binary runs hoon/app/app.hoon, built to out.jam, and
+/// each of several binaries runs hoon/app/<bin>.hoon, built to <bin>.jam.
+pub(super) fn plan(declared: &[AppSpec], binaries: &[String]) -> Result<Vec<App>> {
+    if declared.is_empty() {
+        Ok(by_name(binaries))
+    } else {
+        from_manifest(declared, binaries)
+    }
+}
+
+/// Fail if the kernel of an app is missing
+///
+/// This is checked apart from `plan`, as pre-build hooks may generate kernels.
+pub(super) fn check_entries(project_dir: &Path, apps: &[App]) -> Result<()> {
+    for app in apps {
+        let path = project_dir.join("hoon").join(&app.entry);
+        if !path.is_file() {
+            anyhow::bail!("Hoon app file not found: '{}'", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn by_name(binaries: &[String]) -> Vec<App> {
+    if binaries.len() > 1 {
+        binaries
+            .iter()
+            .map(|bin| App {
+                name: bin.clone(),
+                bin: Some(bin.clone()),
+                entry: PathBuf::from(format!("app/{}.hoon", bin)),
+                jam: PathBuf::from(format!("{}.jam", bin)),
+            })
+            .collect()
+    } else {
+        vec![App {
+            name: "app".to_string(),
+            bin: None,
+            entry: PathBuf::from("app/app.hoon"),
+            jam: PathBuf::from("out.jam"),
+        }]
+    }
+}
+
+fn from_manifest(declared: &[AppSpec], binaries: &[String]) -> Result<Vec<App>> {
+    let mut apps: Vec<App> = Vec::new();
+    for (i, spec) in declared.iter().enumerate() {
+        let which = match &spec.bin {
+            Some(bin) => format!("[[app]] for '{}'", bin),
+            None => format!("[[app]] #{}", i + 1),
+        };
+
+        let bin = match &spec.bin {
+            Some(bin) if !binaries.is_empty() && !binaries.contains(bin) => anyhow::bail!(
+                "{} in nockapp.toml: Cargo.toml has no [[bin]] named '{}'; its binaries are {}",
+                which,
+                bin,
+                binaries.join(", ")
+            ),
+            Some(bin) => Some(bin.clone()),
+            None if binaries.len() > 1 => anyhow::bail!(
+                "{} in nockapp.toml: name its `bin`, as Cargo.toml has several ({})",
+                which,
+                binaries.join(", ")
+            ),
+            None => binaries.first().cloned(),
+        };
+
+        let entry = Path::new(&spec.entry);
+        let entry = match entry.strip_prefix("hoon") {
+            Ok(relative)
+                if is_plain_relative(relative) && entry.extension() == Some("hoon".as_ref()) =>
+            {
+                relative.to_path_buf()
+            }
+            _ => anyhow::bail!(
+                "{} in nockapp.toml: entry '{}' must be a .hoon file under hoon/", which,
+                spec.entry
+            ),
+        };
+
+        let jam = match &spec.jam {
+            Some(jam) => PathBuf::from(jam),
+            None if declared.len() == 1 => PathBuf::from("out.jam"),
+            None => match &bin {
+                Some(bin) => PathBuf::from(format!("{}.jam", bin)),
+                None => PathBuf::from("out.jam"),
+            },
+        };
+        if !is_plain_relative(&jam) || jam.extension() != Some("jam".as_ref()) {
+            anyhow::bail!(
+                "{} in nockapp.toml: jam '{}' must be a .jam file inside the project",
+                which,
+                jam.display()
+            );
+        }
+
+        let name = bin.clone().unwrap_or_else(|| "app".to_string());
+        if let Some(other) = apps.iter().find(|app| app.name == name) {
+            anyhow::bail!(
+                "{} in nockapp.toml: '{}' already has a kernel, {}",
+                which,
+                name,
+                other.entry.display()
+            );
+        }
+        if apps.iter().any(|app| app.jam == jam) {
+            anyhow::bail!(
+                "{} in nockapp.toml: another app is also built to {}",
+                which,
+                jam.display()
+            );
+        }
+        apps.push(App {
+            name,
+            bin,
+            entry,
+            jam,
+        });
+    }
+    Ok(apps)
+}
+
+/// Whether `path` is relative and stays inside the directory it is relative to
+fn is_plain_relative(path: &Path) -> bool {
+    path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(bin: Option<&str>, entry: &str, jam: Option<&str>) -> AppSpec {
+        AppSpec {
+            bin: bin.map(String::from),
+            entry: entry.to_string(),
+            jam: jam.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let project = tmp.path();
+        std::fs::create_dir_all(project.join("hoon/app")).unwrap();
+        for name in ["app", "listen", "talk", "listener"] {
+            std::fs::write(project.join(format!("hoon/app/{}.hoon", name)), "~").unwrap();
+        }
+        let binaries = vec!["listen".to_string(), "talk".to_string()];
+
+        // Without [[app]], kernels are matched by name
+        let apps = plan(&[], &binaries).unwrap();
+        assert_eq!(apps[1].entry, Path::new("app/talk.hoon"));
+        assert_eq!(apps[1].jam, Path::new("talk.jam"));
+        assert_eq!(plan(&[], &[]).unwrap()[0].jam, Path::new("out.jam"));
+
+        let apps = plan(
+            &[
+                spec(Some("listen"), "hoon/app/listener.hoon", None),
+                spec(Some("talk"), "hoon/app/talk.hoon", Some("kernels/talk.jam")),
+            ],
+            &binaries,
+        )
+        .unwrap();
+        assert_eq!(
+            apps[0],
+            App {
+                name: "listen".to_string(),
+                bin: Some("listen".to_string()),
+                entry: PathBuf::from("app/listener.hoon"),
+                jam: PathBuf::from("listen.jam"),
+            }
+        );
+        assert_eq!(apps[1].jam, Path::new("kernels/talk.jam"));
+
+        // A lone app needs no bin, and builds to out.jam
+        let apps = plan(&[spec(None, "hoon/app/listener.hoon", None)], &[]).unwrap();
+        assert_eq!(
+            (apps[0].name.as_str(), apps[0].jam.as_path()),
+            ("app", Path::new("out.jam"))
+        );
+
+        let fails = |declared: &[AppSpec]| plan(declared, &binaries).is_err();
+        assert!(fails(&[spec(Some("shout"), "hoon/app/talk.hoon", None)]));
+        assert!(fails(&[spec(None, "hoon/app/talk.hoon", None)]));
+        assert!(fails(&[spec(Some("talk"), "app/talk.hoon", None)]));
+        assert!(fails(&[spec(Some("talk"), "hoon/../talk.hoon", None)]));
+        assert!(fails(&[spec(
+            Some("talk"),
+            "hoon/app/talk.hoon",
+            Some("../talk.jam")
+        )]));
+        assert!(fails(&[
+            spec(Some("talk"), "hoon/app/talk.hoon", None),
+            spec(Some("talk"), "hoon/app/listener.hoon", None),
+        ]));
+        assert!(fails(&[
+            spec(Some("listen"), "hoon/app/listener.hoon", Some("out.jam")),
+            spec(Some("talk"), "hoon/app/talk.hoon", Some("out.jam")),
+        ]));
+
+        // Kernels are checked once pre-build hooks have had a chance to generate them
+        assert!(check_entries(project, &apps).is_ok());
+        let missing = plan(
+            &[spec(Some("talk"), "hoon/app/missing.hoon", None)],
+            &binaries,
+        )
+        .unwrap();
+        assert!(check_entries(project, &missing).is_err());
+    }
+}
@@ -1,13 +1,74 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use anyhow::{Context, Result};
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tokio::process::Command;
 
-use crate::manifest::NockAppManifest;
+use super::hooks::{self, HookEnv};
+use super::profile::Profile;
+use super::toolchain::{self, Toolchain};
+use super::{apps, deps, target};
+use crate::cache::PackageCache;
+use crate::commands::package::install::LockMode;
+use crate::commands::package::placement;
+use crate::failure::Failure;
+use crate::manifest::{LockedToolchain, NockAppLock, NockAppManifest};
+use crate::output;
+
+/// Record of the last hoonc build of each binary, kept in the project's target/ directory
+const BUILD_STATE: &str = "nockup-build.toml";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildState {
+    #[serde(default)]
+    binary: BTreeMap<String, BinaryBuild>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BinaryBuild {
+    hash: String,                      // build_hash() of the last build
+    sources: BTreeMap<String, String>, // SHA-256 of each source, relative to hoon/
+}
+
+impl BuildState {
+    /// Load the build state; a missing or unreadable one means everything is rebuilt
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
 
-pub async fn run(project: &str) -> Result<()> {
+/// Build a project with cargo, then compile each binary's Hoon app with hoonc
+///
+/// Apps whose sources have not changed since their last build are skipped, and builds of sources
+/// seen before are restored from the build cache, unless `force` is set. With a `target` triple,
+/// cargo cross-compiles for it and the jams go to target/<triple>/ with its binaries, next to the
+/// channel's hoon runtime for that target. `profile` names the build profile in nockapp.toml, or
+/// the built-in `dev` or `release`. The `[hooks]` of nockapp.toml run before cargo and after
+/// hoonc. The versions of hoon and hoonc are recorded in nockapp.lock, and a toolchain other than
+/// the recorded one is warned about, or with `locked` refused along with an outdated lockfile.
+pub async fn run(
+    project: &str,
+    offline: bool,
+    force: bool,
+    locked: bool,
+    target: Option<&str>,
+    profile: &str,
+) -> Result<()> {
     // If project is ".", try to read nockapp.toml to get the actual project name
     let project_name = if project == "." {
         let cwd = std::env::current_dir()?;
@@ -38,19 +99,25 @@ pub async fn run(project: &str) -> Result<()> {
     if nockapp_manifest.exists() {
         // Check if dependencies need to be installed
         if should_install_dependencies(project_dir).await? {
-            println!("{} Installing dependencies...", "📦".cyan());
+            say!("{} Installing dependencies...", "📦".cyan());
             // Change to project directory to run install
             let original_dir = std::env::current_dir()?;
             std::env::set_current_dir(project_dir)?;
 
             // Run package install
-            let install_result = crate::commands::package::install::run().await;
+            let lock_mode = if locked {
+                LockMode::Locked
+            } else {
+                LockMode::Update
+            };
+            let install_result =
+                crate::commands::package::install::run(offline, false, lock_mode).await;
 
             // Change back to original directory
             std::env::set_current_dir(original_dir)?;
 
             install_result?;
-            println!();
+            say!();
         }
     }
 
@@ -60,49 +127,85 @@ pub async fn run(project: &str) -> Result<()> {
         return Err(anyhow::anyhow!("No Cargo.toml found in '{}'", project_name));
     }
 
-    println!(
+    let toolchain = toolchain::select(project_dir, offline).await?;
+
+    // Jams are not comparable across toolchains, so nockapp.lock records the one they came from
+    let lock_path = project_dir.join("nockapp.lock");
+    let versions = toolchain.versions().await;
+    if nockapp_manifest.exists() {
+        check_toolchain(&lock_path, &versions, locked)?;
+    }
+
+    say!(
         "{} Building project '{}'...",
         "🔨".green(),
         project_name.cyan()
     );
 
-    // Extract expected binary names from Cargo.toml
-    let cargo_toml_content = tokio::fs::read_to_string(&cargo_toml)
-        .await
-        .context("Failed to read Cargo.toml")?;
-
-    let cargo_toml_parsed: toml::Value =
-        toml::from_str(&cargo_toml_content).context("Failed to parse Cargo.toml")?;
-
-    let expected_binaries = if let Some(bins) = cargo_toml_parsed.get("bin") {
-        bins.as_array()
-            .context("Invalid format for [[bin]] in Cargo.toml")?
-            .iter()
-            .filter_map(|bin| bin.get("name").and_then(|n| n.as_str()))
-            .map(String::from)
-            .collect::<Vec<String>>()
+    let manifest = if nockapp_manifest.exists() {
+        NockAppManifest::load(&nockapp_manifest).context("Failed to parse nockapp.toml")?
     } else {
-        Vec::new()
+        NockAppManifest::default()
+    };
+    let profile_name = profile;
+    let profile = Profile::select(&manifest.profile, profile_name)?;
+
+    // Match each binary of Cargo.toml to its Hoon kernel, before spending time on cargo
+    let declared = &manifest.apps;
+    let binaries = cargo_binaries(&cargo_toml)?;
+    let apps = apps::plan(declared, &binaries)?;
+    if !declared.is_empty() {
+        for bin in &binaries {
+            if !apps.iter().any(|app| app.bin.as_ref() == Some(bin)) {
+                say!(
+                    "{} Binary '{}' has no [[app]] in nockapp.toml, so no kernel is built for it",
+                    "⚠️".yellow(),
+                    bin
+                );
+            }
+        }
+    }
+
+    // Jams and build state live in the project root and target/, or per target triple
+    let (jam_dir, state_path) = match target {
+        Some(triple) => {
+            let output_dir = target::output_dir(project_dir, triple);
+            (output_dir.clone(), output_dir.join(BUILD_STATE))
+        }
+        None => (
+            project_dir.to_path_buf(),
+            project_dir.join("target").join(BUILD_STATE),
+        ),
     };
 
-    // Check number of expected binaries; if more than one, check primary source files.
-    let binaries: Vec<std::path::PathBuf> = if expected_binaries.len() > 1 {
-        expected_binaries
+    let hook_env = HookEnv {
+        project_name: project_name.clone(),
+        project_dir: std::path::absolute(project_dir)?,
+        profile: profile_name.to_string(),
+        target: target.map(String::from),
+        jams: apps
             .iter()
-            .map(|bin_name| project_dir.join("src").join(format!("{}.rs", bin_name)))
-            .collect()
-    } else {
-        vec![project_dir.join("src").join("main.rs")]
+            .map(|app| std::path::absolute(jam_dir.join(&app.jam)))
+            .collect::<std::io::Result<_>>()?,
     };
+    hooks::run(
+        "pre-build", &manifest.hooks.pre_build, &hook_env, &toolchain,
+    )
+    .await?;
+    apps::check_entries(project_dir, &apps)?;
 
     // Run cargo build in the project directory
     let mut cargo_command = Command::new("cargo");
     cargo_command
         .arg("build")
-        .arg("--release") // Build in release mode by default
         .current_dir(project_dir)
-        .stdout(Stdio::inherit())
+        .stdout(output::child_stdout())
         .stderr(Stdio::inherit());
+    profile.apply(&mut cargo_command);
+    if let Some(triple) = target {
+        cargo_command.arg("--target").arg(triple);
+    }
+    toolchain.apply(&mut cargo_command)?;
 
     let status = cargo_command
         .status()
@@ -110,92 +213,310 @@ pub async fn run(project: &str) -> Result<()> {
         .context("Failed to execute cargo build")?;
 
     if !status.success() {
-        return Err(anyhow::anyhow!(
+        return Err(Failure::Build.wrap(anyhow::anyhow!(
             "Cargo build failed with exit code: {}",
             status.code().unwrap_or(-1)
-        ));
+        )));
     }
 
-    println!("{} Cargo build completed successfully!", "✓".green());
+    say!("{} Cargo build completed successfully!", "✓".green());
+
+    if let Some(triple) = target {
+        target::bundle_runtime(triple, &jam_dir.join(profile.output_dir()), offline).await?;
+    }
+    let mut state = BuildState::load(&state_path);
 
     // Check if hoon app file exists
     //  If there is only one binary, then check in the normal spot.
     //  If there are multiple binaries, then check at each location by name.
-    for bin_path in &binaries {
-        // if this is main.rs, then load app.hoon
-        let name = if bin_path
-            .file_name()
-            .expect("bin_path should have a file name")
-            == "main.rs"
-        {
-            "app".to_string()
-        } else {
-            bin_path
-                .file_stem()
-                .expect("bin_path should have a file stem")
-                .to_string_lossy()
-                .to_string()
-        };
-        let hoon_app_path = project_dir.join(format!("hoon/app/{}.hoon", name));
-        println!("Compiling Hoon app file at: {}", hoon_app_path.display());
-
-        if !hoon_app_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Hoon app file not found: '{}'",
-                hoon_app_path.display()
-            ));
+    for app in apps {
+        let name = app.name;
+        let hoon_app_path = project_dir.join("hoon").join(&app.entry);
+        say!("Compiling Hoon app file at: {}", hoon_app_path.display());
+
+        // Skip hoonc when nothing the app imports has changed since the last build
+        let relative_app = hoon_app_path
+            .strip_prefix(project_dir)
+            .expect("hoon_app_path should be under project_dir");
+        let sources = deps::source_hashes(&project_dir.join("hoon"), &app.entry.to_string_lossy())?;
+        let hash = build_hash(relative_app, &sources, &toolchain, &profile.hoonc_flags);
+
+        // hoonc writes out.jam, which is moved to the app's jam if that is another
+        let out_jam = project_dir.join("out.jam");
+        let target_jam = jam_dir.join(&app.jam);
+        if let Some(parent) = target_jam.parent() {
+            tokio::fs::create_dir_all(parent).await?;
         }
 
-        println!("{} Compiling Hoon app...", "📦".green());
-
-        // Run hoonc command from project directory
-        let mut hoonc_command = Command::new("hoonc");
-        hoonc_command
-            .arg(
-                hoon_app_path
-                    .strip_prefix(project_dir)
-                    .expect("hoon_app_path should be under project_dir"),
-            )
-            .current_dir(project_dir) // Run in project directory
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
-
-        let hoonc_status = hoonc_command.status().await.context(
-            "Failed to execute hoonc command - make sure hoonc is installed and in PATH",
-        )?;
-
-        if !hoonc_status.success() {
-            return Err(anyhow::anyhow!(
-                "hoonc compilation failed with exit code: {}",
-                hoonc_status.code().unwrap_or(-1)
-            ));
+        let previous = state.binary.get(&name);
+        if !force && target_jam.exists() && previous.is_some_and(|p| p.hash == hash) {
+            say!(
+                "{} {} is up to date, skipping hoonc",
+                "✓".green(),
+                target_jam.display().to_string().cyan()
+            );
+            continue;
+        }
+        if let Some(previous) = previous {
+            let changed = changed_sources(&previous.sources, &sources);
+            if !changed.is_empty() {
+                say!("  {} Changed: {}", "→".cyan(), changed.join(", "));
+            }
         }
 
-        // move out.jam to {bin_name}.jam if the program has multiple names
-        if binaries.len() > 1 {
-            let target_jam = project_dir.join(format!(
-                "{}.jam",
-                bin_path
-                    .file_stem()
-                    .expect("bin_path should have a file stem")
-                    .to_string_lossy()
-            ));
-            tokio::fs::rename(project_dir.join("out.jam"), &target_jam)
+        // Reuse the jam of an earlier build of the same sources
+        let cached_jam = build_cache_path(&hash)?;
+        if !force && cached_jam.exists() {
+            tokio::fs::copy(&cached_jam, &target_jam)
                 .await
-                .context(format!(
-                    "Failed to rename out.jam to {}",
-                    target_jam.display()
-                ))?;
-            println!(
-                "{} Renamed out.jam to {}",
-                "🔀".green(),
+                .with_context(|| format!("Failed to restore {}", cached_jam.display()))?;
+            say!(
+                "{} Restored {} from the build cache",
+                "✓".green(),
                 target_jam.display().to_string().cyan()
             );
+        } else {
+            compile(project_dir, relative_app, &toolchain, &profile.hoonc_flags).await?;
+            store_build(&out_jam, &cached_jam).await?;
+
+            // move out.jam to {bin_name}.jam if the program has multiple names, or to the
+            // target's directory
+            if target_jam != out_jam {
+                tokio::fs::rename(&out_jam, &target_jam)
+                    .await
+                    .context(format!(
+                        "Failed to rename out.jam to {}",
+                        target_jam.display()
+                    ))?;
+                say!(
+                    "{} Renamed out.jam to {}",
+                    "🔀".green(),
+                    target_jam.display().to_string().cyan()
+                );
+            }
+        }
+
+        state.binary.insert(name, BinaryBuild { hash, sources });
+        state.save(&state_path)?;
+    }
+
+    say!("{} Hoon compilation completed successfully!", "✓".green());
+
+    if nockapp_manifest.exists() && !locked {
+        record_toolchain(&lock_path, versions)?;
+    }
+
+    hooks::run(
+        "post-build", &manifest.hooks.post_build, &hook_env, &toolchain,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Names of the `[[bin]]` targets in Cargo.toml, none if it only has the default one
+pub(super) fn cargo_binaries(cargo_toml: &Path) -> Result<Vec<String>> {
+    let cargo_toml_content =
+        std::fs::read_to_string(cargo_toml).context("Failed to read Cargo.toml")?;
+
+    let cargo_toml_parsed: toml::Value =
+        toml::from_str(&cargo_toml_content).context("Failed to parse Cargo.toml")?;
+
+    let Some(bins) = cargo_toml_parsed.get("bin") else {
+        return Ok(Vec::new());
+    };
+    Ok(bins
+        .as_array()
+        .context("Invalid format for [[bin]] in Cargo.toml")?
+        .iter()
+        .filter_map(|bin| bin.get("name").and_then(|n| n.as_str()))
+        .map(String::from)
+        .collect())
+}
+
+/// Compile a Hoon app with the toolchain's hoonc, which writes out.jam to the project directory
+async fn compile(
+    project_dir: &Path,
+    hoon_app: &Path,
+    toolchain: &Toolchain,
+    hoonc_flags: &[String],
+) -> Result<()> {
+    say!("{} Compiling Hoon app...", "📦".green());
+
+    // Run hoonc command from project directory
+    let mut hoonc_command = Command::new(toolchain.hoonc());
+    hoonc_command
+        .arg(hoon_app)
+        .args(hoonc_flags)
+        .current_dir(project_dir) // Run in project directory
+        .stdout(output::child_stdout())
+        .stderr(Stdio::inherit());
+    toolchain.apply(&mut hoonc_command)?;
+
+    let hoonc_status = hoonc_command
+        .status()
+        .await
+        .context("Failed to execute hoonc command - make sure hoonc is installed and in PATH")?;
+
+    if !hoonc_status.success() {
+        return Err(Failure::Build.wrap(anyhow::anyhow!(
+            "hoonc compilation failed with exit code: {}",
+            hoonc_status.code().unwrap_or(-1)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Where the jam of a build with this hash is cached
+/// Format: ~/.nockup/cache/builds/<hash>/out.jam
+fn build_cache_path(hash: &str) -> Result<PathBuf> {
+    Ok(PackageCache::new()?.builds_dir().join(hash).join("out.jam"))
+}
+
+/// Copy a freshly built jam into the build cache
+async fn store_build(out_jam: &Path, cached_jam: &Path) -> Result<()> {
+    let dir = cached_jam
+        .parent()
+        .expect("cached builds are in a directory per hash");
+    tokio::fs::create_dir_all(dir).await?;
+    // Copy under a temporary name first, so an interrupted copy is never restored
+    let partial = dir.join("out.jam.partial");
+    tokio::fs::copy(out_jam, &partial)
+        .await
+        .with_context(|| format!("Failed to cache {}", out_jam.display()))?;
+    tokio::fs::rename(&partial, cached_jam).await?;
+    Ok(())
+}
+
+/// SHA-256 over everything a hoonc build of `hoon_app` depends on
+///
+/// That is the app's path, its sources, the profile's hoonc flags, and the toolchain's hoonc
+/// binary's size and modification time, so upgrading hoonc or pinning another invalidates earlier
+/// builds.
+fn build_hash(
+    hoon_app: &Path,
+    sources: &BTreeMap<String, String>,
+    toolchain: &Toolchain,
+    hoonc_flags: &[String],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(hoon_app.to_string_lossy().as_bytes());
+    hasher.update([0]);
+    for flag in hoonc_flags {
+        hasher.update(flag.as_bytes());
+        hasher.update([0]);
+    }
+    if let Some(metadata) = which::which(toolchain.hoonc())
+        .ok()
+        .and_then(|hoonc| std::fs::metadata(hoonc).ok())
+    {
+        hasher.update(metadata.len().to_le_bytes());
+        if let Some(modified) = metadata
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        {
+            hasher.update(modified.as_nanos().to_le_bytes());
+        }
+    }
+    for (relative, hash) in sources {
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update(hash.as_bytes());
+        hasher.update([0]);
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+/// Sources added, changed or removed since the last build
+fn changed_sources(
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(path, hash)| previous.get(*path) != Some(hash))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(
+        previous
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .map(|path| format!("{} (removed)", path)),
+    );
+    changed
+}
+
+/// Warn if the toolchain differs from the one nockapp.lock records, or fail if `locked`
+///
+/// With `locked`, a lockfile that records no toolchain fails too.
+fn check_toolchain(lock_path: &Path, versions: &LockedToolchain, locked: bool) -> Result<()> {
+    let lock = NockAppLock::load(lock_path)?;
+    let Some(recorded) = &lock.toolchain else {
+        if locked {
+            return Err(Failure::Build.wrap(anyhow::anyhow!(
+                "nockapp.lock records no toolchain; run `nockup project build` without --locked \
+                to record it"
+            )));
         }
+        return Ok(());
+    };
+
+    let drift = toolchain_drift(recorded, versions);
+    if drift.is_empty() {
+        return Ok(());
     }
+    if locked {
+        return Err(Failure::Build.wrap(anyhow::anyhow!(
+            "The toolchain differs from the one nockapp.lock records:\n  - {}\n\
+            Run `nockup project build` without --locked to build with it and record it.",
+            drift.join("\n  - ")
+        )));
+    }
+    say!(
+        "{} The toolchain differs from the one nockapp.lock records, so jams will not match \
+        earlier builds:",
+        "⚠️".yellow()
+    );
+    for line in &drift {
+        say!("  - {}", line);
+    }
+    Ok(())
+}
 
-    println!("{} Hoon compilation completed successfully!", "✓".green());
+/// How the current toolchain differs from the recorded one, for the binaries it records
+fn toolchain_drift(recorded: &LockedToolchain, current: &LockedToolchain) -> Vec<String> {
+    [
+        ("hoon", &recorded.hoon, &current.hoon),
+        ("hoonc", &recorded.hoonc, &current.hoonc),
+    ]
+    .into_iter()
+    .filter_map(|(binary, recorded, current)| {
+        let recorded = recorded.as_ref()?;
+        (Some(recorded) != current.as_ref()).then(|| {
+            format!(
+                "{} {} (found {})",
+                binary,
+                recorded,
+                current.as_deref().unwrap_or("no version")
+            )
+        })
+    })
+    .collect()
+}
 
+/// Record the toolchain the jams were built with in nockapp.lock, if it is not already
+fn record_toolchain(lock_path: &Path, versions: LockedToolchain) -> Result<()> {
+    let mut lock = NockAppLock::load(lock_path)?;
+    if lock.toolchain.as_ref() == Some(&versions) {
+        return Ok(());
+    }
+    lock.toolchain = Some(versions);
+    lock.save(lock_path)
+        .with_context(|| format!("Failed to write {}", lock_path.display()))?;
+    say!("  Recorded the toolchain in nockapp.lock");
     Ok(())
 }
 
@@ -250,22 +571,114 @@ async fn should_install_dependencies(project_dir: &Path) -> Result<bool> {
         }
     }
 
-    // Check if hoon/packages directories exist for all locked packages
+    // Check if hoon/packages (or vendor/) directories exist for all locked packages
     let packages_dir = project_dir.join("hoon").join("packages");
-    if !packages_dir.exists() {
+    let vendor_dir = project_dir.join(crate::commands::package::vendor::VENDOR_DIR);
+    if !packages_dir.exists() && !vendor_dir.exists() {
         return Ok(true); // Packages directory missing, need to install
     }
 
     for pkg in &lockfile.package {
-        let pkg_dir = packages_dir.join(format!(
+        let dir_name = format!(
             "{}--{}",
             pkg.name.replace('/', "-"),
             pkg.version.replace(['.', ':'], "-")
-        ));
-        if !pkg_dir.exists() {
+        );
+        if !packages_dir.join(&dir_name).exists() && !vendor_dir.join(&dir_name).exists() {
             return Ok(true); // Package directory missing, need to install
         }
     }
 
+    // Copies and hard links of package files go stale when their packages change
+    let mode = placement::install_mode(&manifest.package)?;
+    let stale = placement::stale_files(&project_dir.join("hoon"), mode)?;
+    if !stale.is_empty() {
+        say!(
+            "{} Installed package files are out of date: {}",
+            "⚠️".yellow(),
+            stale.join(", ")
+        );
+        return Ok(true);
+    }
+
     Ok(false) // Everything looks good, no install needed
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_sources() {
+        let sources = |entries: &[(&str, &str)]| -> BTreeMap<String, String> {
+            entries
+                .iter()
+                .map(|(path, hash)| (path.to_string(), hash.to_string()))
+                .collect()
+        };
+        let previous =
+            sources(&[("app/app.hoon", "a"), ("lib/seq.hoon", "b"), ("sur/old.hoon", "c")]);
+        let current =
+            sources(&[("app/app.hoon", "a"), ("lib/seq.hoon", "B"), ("lib/new.hoon", "d")]);
+
+        assert_eq!(
+            changed_sources(&previous, &current),
+            vec!["lib/new.hoon", "lib/seq.hoon", "sur/old.hoon (removed)"]
+        );
+        assert!(changed_sources(&current, &current).is_empty());
+
+        let app = Path::new("hoon/app/app.hoon");
+        let toolchain = Toolchain::default();
+        assert_eq!(
+            build_hash(app, &current, &toolchain, &[]),
+            build_hash(app, &current, &toolchain, &[])
+        );
+        assert_ne!(
+            build_hash(app, &previous, &toolchain, &[]),
+            build_hash(app, &current, &toolchain, &[])
+        );
+        assert_ne!(
+            build_hash(app, &current, &toolchain, &["--arbitrary".to_string()]),
+            build_hash(app, &current, &toolchain, &[])
+        );
+        assert_ne!(
+            build_hash(Path::new("hoon/app/other.hoon"), &current, &toolchain, &[]),
+            build_hash(app, &current, &toolchain, &[])
+        );
+    }
+
+    #[test]
+    fn test_toolchain_drift() {
+        let toolchain = |hoon: Option<&str>, hoonc: Option<&str>| LockedToolchain {
+            hoon: hoon.map(String::from),
+            hoonc: hoonc.map(String::from),
+        };
+        let recorded = toolchain(Some("1.0.0"), Some("0.2.0"));
+        assert!(toolchain_drift(&recorded, &recorded).is_empty());
+        assert_eq!(
+            toolchain_drift(&recorded, &toolchain(Some("1.0.0"), Some("0.3.0"))),
+            vec!["hoonc 0.2.0 (found 0.3.0)"]
+        );
+        assert_eq!(
+            toolchain_drift(&recorded, &toolchain(None, Some("0.2.0"))),
+            vec!["hoon 1.0.0 (found no version)"]
+        );
+        assert!(toolchain_drift(&toolchain(None, Some("0.2.0")), &recorded).is_empty());
+
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let lock_path = tmp.path().join("nockapp.lock");
+        NockAppLock::new(vec![]).save(&lock_path).unwrap();
+        assert!(check_toolchain(&lock_path, &recorded, false).is_ok());
+        assert!(check_toolchain(&lock_path, &recorded, true).is_err());
+
+        record_toolchain(&lock_path, recorded.clone()).unwrap();
+        assert_eq!(
+            NockAppLock::load(&lock_path).unwrap().toolchain,
+            Some(recorded.clone())
+        );
+        assert!(check_toolchain(&lock_path, &recorded, true).is_ok());
+        let newer = toolchain(Some("1.0.0"), Some("0.3.0"));
+        assert!(check_toolchain(&lock_path, &newer, false).is_ok());
+        assert!(check_toolchain(&lock_path, &newer, true).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+// src/commands/build/deps.rs
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+/// The Hoon sources a hoonc build of `entry` reads, with the SHA-256 of each
+///
+/// Paths are relative to `hoon_dir`, hoonc's dependency directory, and include `entry` itself.
+/// Imports are followed as hoonc resolves them: `/-` from sur/, `/+` from lib/, `/#` from dat/,
+/// and `/=` and `/*` by path. Files are read through symlinks, so linked packages are covered.
+pub(super) fn source_hashes(hoon_dir: &Path, entry: &str) -> Result<BTreeMap<String, String>> {
+    let mut sources = BTreeMap::new();
+    let mut pending = vec![entry.to_string()];
+
+    while let Some(relative) = pending.pop() {
+        if sources.contains_key(&relative) {
+            continue;
+        }
+        let path = hoon_dir.join(&relative);
+        let contents =
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        sources.insert(relative.clone(), hex::encode(Sha256::digest(&contents)));
+
+        // Only Hoon files have imports; /* pulls in files of any mark
+        if !relative.ends_with(".hoon") {
+            continue;
+        }
+        for import in imports(&String::from_utf8_lossy(&contents)) {
+            // Every file the import may resolve to counts; one that resolves nowhere fails the
+            // build in hoonc anyway
+            pending.extend(
+                import
+                    .candidates()
+                    .into_iter()
+                    .filter(|c| hoon_dir.join(c).is_file()),
+            );
+        }
+    }
+
+    Ok(sources)
+}
+
+/// An import rune at the top of a Hoon file
+#[derive(Debug, PartialEq, Eq)]
+enum Import {
+    Sur(String),  // /-  name
+    Lib(String),  // /+  name
+    Dat(String),  // /#  name
+    Path(String), // /=  face  /path/to/file, a .hoon file
+    File(String), // /*  face  %mark  /path/to/file/ext
+}
+
+impl Import {
+    /// Paths, relative to the dependency directory, that the import may resolve to
+    fn candidates(&self) -> Vec<String> {
+        match self {
+            Import::Sur(name) => fits("sur", name),
+            Import::Lib(name) => fits("lib", name),
+            Import::Dat(name) => fits("dat", name),
+            Import::Path(path) => vec![format!("{}.hoon", path.trim_start_matches('/'))],
+            Import::File(path) => {
+                let path = path.trim_start_matches('/');
+                match path.rsplit_once('/') {
+                    Some((file, ext)) => vec![format!("{}.{}", file, ext)],
+                    None => Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+/// Files a library name can resolve to: each hyphen may stand for a directory separator, so
+/// `foo-bar` is lib/foo-bar.hoon or lib/foo/bar.hoon
+fn fits(dir: &str, name: &str) -> Vec<String> {
+    let parts: Vec<&str> = name.split('-').collect();
+    let mut joined = vec![parts[0].to_string()];
+    for part in &parts[1..] {
+        joined = joined
+            .into_iter()
+            .flat_map(|prefix| [format!("{}-{}", prefix, part), format!("{}/{}", prefix, part)])
+            .collect();
+    }
+    joined
+        .into_iter()
+        .map(|name| format!("{}/{}.hoon", dir, name))
+        .collect()
+}
+
+/// The imports of a Hoon file, read from the runes before its first line of code
+fn imports(text: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    let mut lines = text.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        let line = strip_comment(line);
+        if line.is_empty() {
+            continue;
+        }
+        let Some(rune) = line.get(..2).filter(|r| r.starts_with('/')) else {
+            break;
+        };
+        let mut rest = line[2..].trim().to_string();
+
+        match rune {
+            "/-" | "/+" | "/#" => {
+                // A list of imports continues onto the next line after a trailing comma
+                while rest.ends_with(',') {
+                    match lines.next() {
+                        Some(next) => {
+                            rest.push(' ');
+                            rest.push_str(strip_comment(next));
+                        }
+                        None => break,
+                    }
+                }
+                for taut in rest.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                    // *foo, bar=foo and foo all import foo
+                    let name = taut
+                        .rsplit('=')
+                        .next()
+                        .unwrap_or(taut)
+                        .trim_start_matches('*');
+                    imports.push(match rune {
+                        "/-" => Import::Sur(name.to_string()),
+                        "/+" => Import::Lib(name.to_string()),
+                        _ => Import::Dat(name.to_string()),
+                    });
+                }
+            }
+            "/=" => {
+                if let Some(path) = rest.split_whitespace().nth(1) {
+                    imports.push(Import::Path(path.to_string()));
+                }
+            }
+            "/*" => {
+                if let Some(path) = rest.split_whitespace().nth(2) {
+                    imports.push(Import::File(path.to_string()));
+                }
+            }
+            // /? pins a kelvin and imports nothing
+            "/?" => {}
+            _ => break,
+        }
+    }
+
+    imports
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find("::") {
+        Some(i) => line[..i].trim(),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imports() {
+        let text = "\
+::  the app
+/?  310
+/-  *sequent, spec=sequent-spec
+/+  lagoon,
+    *wrap  :: wrapper
+/=  tiny  /common/tiny
+/*  data  %jam  /dat/table/jam
+/#  consts
+|%
+/+  not-an-import
+--
+";
+        assert_eq!(
+            imports(text),
+            vec![
+                Import::Sur("sequent".to_string()),
+                Import::Sur("sequent-spec".to_string()),
+                Import::Lib("lagoon".to_string()),
+                Import::Lib("wrap".to_string()),
+                Import::Path("/common/tiny".to_string()),
+                Import::File("/dat/table/jam".to_string()),
+                Import::Dat("consts".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            Import::Sur("sequent-spec".to_string()).candidates(),
+            vec!["sur/sequent-spec.hoon", "sur/sequent/spec.hoon"]
+        );
+        assert_eq!(
+            Import::File("/dat/table/jam".to_string()).candidates(),
+            vec!["dat/table.jam"]
+        );
+    }
+
+    #[test]
+    fn test_source_hashes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let hoon = tmp.path();
+        for dir in ["app", "lib/seq", "sur"] {
+            std::fs::create_dir_all(hoon.join(dir)).unwrap();
+        }
+        std::fs::write(hoon.join("app/app.hoon"), "/+  seq-core\n/-  spec\n~").unwrap();
+        std::fs::write(hoon.join("app/other.hoon"), "~").unwrap();
+        std::fs::write(hoon.join("lib/seq/core.hoon"), "/+  seq-core\n~").unwrap();
+        std::fs::write(hoon.join("sur/spec.hoon"), "~").unwrap();
+        std::fs::write(hoon.join("lib/unused.hoon"), "~").unwrap();
+
+        let sources = source_hashes(hoon, "app/app.hoon").unwrap();
+        assert_eq!(
+            sources.keys().collect::<Vec<_>>(),
+            vec!["app/app.hoon", "lib/seq/core.hoon", "sur/spec.hoon"]
+        );
+        assert_eq!(source_hashes(hoon, "app/other.hoon").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,131 @@
+// src/commands/build/hooks.rs
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use tokio::process::Command;
+
+use super::toolchain::Toolchain;
+use crate::failure::Failure;
+use crate::output;
+
+/// What the hooks of a build are told about it
+#[derive(Debug)]
+pub(super) struct HookEnv {
+    pub(super) project_name: String,
+    pub(super) project_dir: PathBuf,
+    pub(super) profile: String,
+    pub(super) target: Option<String>,
+    pub(super) jams: Vec<PathBuf>, // Where each app's jam is built to
+}
+
+impl HookEnv {
+    /// The environment variables hooks run with
+    ///
+    /// JAM_PATH lists the jams as PATH does, so it is a single path unless there are several apps.
+    fn vars(&self) -> Result<Vec<(&'static str, OsString)>> {
+        let mut vars = vec![
+            ("PROJECT_NAME", OsString::from(&self.project_name)),
+            ("PROJECT_DIR", self.project_dir.clone().into_os_string()),
+            ("PROFILE", OsString::from(&self.profile)),
+            (
+                "JAM_PATH",
+                std::env::join_paths(&self.jams).context("Invalid jam path for hooks")?,
+            ),
+        ];
+        if let Some(target) = &self.target {
+            vars.push(("TARGET", OsString::from(target)));
+        }
+        Ok(vars)
+    }
+}
+
+/// Run the commands of a `stage` hook in order, stopping at the first that fails
+///
+/// Each runs in a shell, from the project directory, with the toolchain first on its PATH.
+pub(super) async fn run(
+    stage: &str,
+    commands: &[String],
+    env: &HookEnv,
+    toolchain: &Toolchain,
+) -> Result<()> {
+    let vars = env.vars()?;
+    for command in commands {
+        say!("{} Running {} hook: {}", "🪝".cyan(), stage, command.cyan());
+        let mut hook = shell(command);
+        hook.current_dir(&env.project_dir)
+            .envs(vars.iter().cloned())
+            .stdout(output::child_stdout())
+            .stderr(Stdio::inherit());
+        toolchain.apply(&mut hook)?;
+
+        let status = hook
+            .status()
+            .await
+            .with_context(|| format!("Failed to run {} hook '{}'", stage, command))?;
+        if !status.success() {
+            return Err(Failure::Build.wrap(anyhow::anyhow!(
+                "{} hook '{}' failed with exit code: {}",
+                stage,
+                command,
+                status.code().unwrap_or(-1)
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let env = HookEnv {
+            project_name: "arcade".to_string(),
+            project_dir: tmp.path().to_path_buf(),
+            profile: "release".to_string(),
+            target: None,
+            jams: vec![tmp.path().join("out.jam")],
+        };
+
+        let commands = vec![
+            "echo \"$PROJECT_NAME $PROFILE ${TARGET:-host}\" > hook.txt".to_string(),
+            "echo \"$JAM_PATH\" >> hook.txt".to_string(),
+        ];
+        run("post-build", &commands, &env, &Toolchain::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("hook.txt")).unwrap(),
+            format!(
+                "arcade release host\n{}\n",
+                tmp.path().join("out.jam").display()
+            )
+        );
+
+        // A failing command stops the ones after it
+        let commands = vec!["exit 3".to_string(), "touch after".to_string()];
+        assert!(run("pre-build", &commands, &env, &Toolchain::default())
+            .await
+            .is_err());
+        assert!(!tmp.path().join("after").exists());
+    }
+}
@@ -6,9 +6,11 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 use handlebars::Handlebars;
 
+use crate::commands::package::install::LockMode;
+use crate::commands::template;
 use crate::manifest::NockAppManifest;
 
-pub async fn run() -> Result<()> {
+pub async fn run(offline: bool) -> Result<()> {
     let cwd = std::env::current_dir()?;
     let manifest_path = cwd.join("nockapp.toml");
 
@@ -31,7 +33,7 @@ pub async fn run() -> Result<()> {
 
     let template_commit = manifest.package.template_commit.as_deref();
 
-    println!(
+    say!(
         "Initializing new NockApp project '{}' using template '{}'...",
         project_name.green(),
         template_name.cyan()
@@ -44,25 +46,8 @@ pub async fn run() -> Result<()> {
         );
     }
 
-    // Resolve template directory (supports pinned commit)
-    let cache_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?
-        .join(".nockup/templates");
-
-    let template_src = if let Some(commit) = template_commit {
-        cache_dir.join(format!("{}-{}", template_name, commit))
-    } else {
-        cache_dir.join(template_name)
-    };
-
-    if !template_src.exists() {
-        anyhow::bail!(
-            "Template '{}' not found in cache at {}.\n\
-             Run `nockup channel update` or check your template-commit hash.",
-            template_name,
-            template_src.display()
-        );
-    }
+    // Resolve template directory (supports pinned commit and git templates)
+    let template_src = template::resolve(template_name, template_commit, offline).await?;
 
     // Build Handlebars context from manifest (same as your old one, but cleaner)
     let context = build_handlebars_context(&manifest)?;
@@ -74,15 +59,15 @@ pub async fn run() -> Result<()> {
     let final_manifest_path = target_dir.join("nockapp.toml");
     manifest.save(&final_manifest_path)?;
 
-    println!("Running dependency installation…");
+    say!("Running dependency installation…");
     // Package install will automatically detect the project directory based on manifest name
-    crate::commands::package::install::run()
+    crate::commands::package::install::run(offline, false, LockMode::Update)
         .await
         .context("Failed to install dependencies")?;
 
-    println!("\nAll done! Project is ready.");
-    println!("   cd {}", project_name.cyan());
-    println!("   nockup run");
+    say!("\nAll done! Project is ready.");
+    say!("   cd {}", project_name.cyan());
+    say!("   nockup run");
     Ok(())
 }
 
@@ -131,6 +116,10 @@ fn copy_dir_recursive(
         let file_name = entry.file_name();
         let dest_path = dest_dir.join(&file_name);
 
+        // A template cloned from git brings its repository along
+        if file_name == ".git" {
+            continue;
+        }
         if src_path.is_dir() {
             fs::create_dir_all(&dest_path)?;
             copy_dir_recursive(&src_path, &dest_path, handlebars, context, project_root)?;
@@ -142,7 +131,7 @@ fn copy_dir_recursive(
 
             fs::write(&dest_path, rendered)?;
             let rel = dest_path.strip_prefix(project_root).unwrap_or(&dest_path);
-            println!("  {} {}", "create".green(), rel.display());
+            say!("  {} {}", "create".green(), rel.display());
         }
     }
     Ok(())
@@ -1,22 +1,48 @@
+mod apps;
 #[path = "build.rs"]
 mod builder_impl;
+mod deps;
+mod hooks;
 pub mod init;
+mod profile;
 pub mod run;
+mod target;
+mod toolchain;
 
 use anyhow::Result;
 
 use crate::cli::ProjectCommand;
 
-pub async fn run(cmd: ProjectCommand) -> Result<()> {
+pub async fn run(cmd: ProjectCommand, offline: bool) -> Result<()> {
     match cmd {
-        ProjectCommand::Build { project } => {
+        ProjectCommand::Build {
+            project,
+            force,
+            locked,
+            target,
+            profile,
+        } => {
             let project = project.as_deref().unwrap_or(".");
-            builder_impl::run(project).await
+            builder_impl::run(project, offline, force, locked, target.as_deref(), &profile).await
         }
-        ProjectCommand::Run { project, args } => {
+        ProjectCommand::Run {
+            project,
+            bin,
+            profile,
+            hot_reload,
+            args,
+        } => {
             let project = project.as_deref().unwrap_or(".");
-            run::run(project.to_string(), args).await
+            run::run(
+                project.to_string(),
+                bin,
+                args,
+                &profile,
+                hot_reload,
+                offline,
+            )
+            .await
         }
-        ProjectCommand::Init => init::run().await,
+        ProjectCommand::Init => init::run(offline).await,
     }
 }
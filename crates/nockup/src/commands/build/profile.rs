@@ -0,0 +1,100 @@
+// src/commands/build/profile.rs
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use tokio::process::Command;
+
+use crate::manifest::ProfileSpec;
+
+/// How to build a project: with which cargo profile and features, and which hoonc flags
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct Profile {
+    pub cargo_profile: String,
+    pub hoonc_flags: Vec<String>,
+    pub features: Vec<String>,
+}
+
+impl Profile {
+    /// The profile called `name`, from the `[profile]` tables of nockapp.toml
+    ///
+    /// `dev` and `release` need no table, building with the cargo profiles of those names.
+    pub(super) fn select(declared: &BTreeMap<String, ProfileSpec>, name: &str) -> Result<Self> {
+        let spec = match declared.get(name) {
+            Some(spec) => spec.clone(),
+            None if name == "dev" || name == "release" => ProfileSpec::default(),
+            None => {
+                let mut names: Vec<&str> = vec!["dev", "release"];
+                names.extend(declared.keys().map(String::as_str));
+                names.sort_unstable();
+                names.dedup();
+                anyhow::bail!(
+                    "No profile '{}' in nockapp.toml; profiles are {}",
+                    name,
+                    names.join(", ")
+                );
+            }
+        };
+        Ok(Profile {
+            cargo_profile: spec.cargo_profile.unwrap_or_else(|| name.to_string()),
+            hoonc_flags: spec.hoonc_flags,
+            features: spec.features,
+        })
+    }
+
+    /// Add the profile and features to a `cargo build` or `cargo run`
+    pub(super) fn apply(&self, cargo_command: &mut Command) {
+        cargo_command.arg("--profile").arg(&self.cargo_profile);
+        if !self.features.is_empty() {
+            cargo_command.arg("--features").arg(self.features.join(","));
+        }
+    }
+
+    /// The directory under target/ that cargo puts the profile's binaries in
+    pub(super) fn output_dir(&self) -> &str {
+        match self.cargo_profile.as_str() {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let declared: BTreeMap<String, ProfileSpec> = toml::from_str(
+            r#"
+[dev]
+hoonc_flags = ["--arbitrary"]
+
+[fast]
+cargo_profile = "dev"
+features = ["trace", "mock-chain"]
+"#,
+        )
+        .unwrap();
+
+        let release = Profile::select(&declared, "release").unwrap();
+        assert_eq!(release.cargo_profile, "release");
+        assert_eq!(release.output_dir(), "release");
+
+        let dev = Profile::select(&declared, "dev").unwrap();
+        assert_eq!(dev.hoonc_flags, ["--arbitrary"]);
+        assert_eq!(dev.output_dir(), "debug");
+
+        let fast = Profile::select(&declared, "fast").unwrap();
+        assert_eq!(
+            fast,
+            Profile {
+                cargo_profile: "dev".to_string(),
+                hoonc_flags: Vec::new(),
+                features: vec!["trace".to_string(), "mock-chain".to_string()],
+            }
+        );
+
+        assert!(Profile::select(&declared, "slow").is_err());
+    }
+}
@@ -5,10 +5,30 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 use tokio::process::Command;
 
+use super::builder_impl::cargo_binaries;
+use super::profile::Profile;
+use super::{apps, toolchain};
 use crate::manifest::NockAppManifest;
 
-pub async fn run(project: String, args: Vec<String>) -> Result<()> {
+/// Where a NockApp looks for a kernel jam to switch to when it changes
+const RELOAD_KERNEL_ENV: &str = "NOCKAPP_RELOAD_KERNEL";
+
+/// Run a project's binary with cargo
+///
+/// A project with several binaries runs `bin`, or else the `default_bin` of its nockapp.toml.
+/// A toolchain pinned in nockup-toolchain.toml comes first on the PATH of what runs, and cargo
+/// builds it with the build `profile`. With `hot_reload`, the app is told to switch to its
+/// kernel jam each time it is rebuilt, e.g. by `nockup project build` in another terminal.
+pub async fn run(
+    project: String,
+    bin: Option<String>,
+    args: Vec<String>,
+    profile: &str,
+    hot_reload: bool,
+    offline: bool,
+) -> Result<()> {
     // If project is ".", try to read nockapp.toml to get the actual project name
+    let mut default_bin = None;
     let project_name = if project == "." {
         let cwd = std::env::current_dir()?;
         let manifest_path = cwd.join("nockapp.toml");
@@ -16,6 +36,7 @@ pub async fn run(project: String, args: Vec<String>) -> Result<()> {
         if manifest_path.exists() {
             let manifest =
                 NockAppManifest::load(&manifest_path).context("Failed to parse nockapp.toml")?;
+            default_bin = manifest.package.default_bin;
             manifest.package.name.trim().to_string()
         } else {
             project
@@ -39,7 +60,58 @@ pub async fn run(project: String, args: Vec<String>) -> Result<()> {
         return Err(anyhow::anyhow!("No Cargo.toml found in '{}'", project_name));
     }
 
-    println!(
+    let binaries = cargo_binaries(&cargo_toml)?;
+    let bin = select_binary(&binaries, bin, default_bin)?;
+
+    let nockapp_manifest = project_dir.join("nockapp.toml");
+    let manifest = if nockapp_manifest.exists() {
+        NockAppManifest::load(&nockapp_manifest).context("Failed to parse nockapp.toml")?
+    } else {
+        NockAppManifest::default()
+    };
+    let profile = Profile::select(&manifest.profile, profile)?;
+
+    // With several binaries, each kernel is built to its own jam
+    let apps = apps::plan(&manifest.apps, &binaries)?;
+    if let Some(bin) = &bin {
+        if let Some(app) = apps.iter().find(|app| app.bin.as_ref() == Some(bin)) {
+            let jam = project_dir.join(&app.jam);
+            if !jam.exists() {
+                anyhow::bail!(
+                    "Kernel {} not found; run `nockup project build` first",
+                    jam.display()
+                );
+            }
+            if app.jam != Path::new("out.jam") && project_dir.join("out.jam").exists() {
+                say!(
+                    "{} {} also has an out.jam, which drivers may load instead of {}",
+                    "⚠".yellow(),
+                    project_name,
+                    app.jam.display()
+                );
+            }
+        }
+    }
+
+    // NockApps booted with nockapp's boot::setup watch the jam that NOCKAPP_RELOAD_KERNEL names
+    let reload_jam = if hot_reload {
+        let app = apps
+            .iter()
+            .find(|app| apps.len() == 1 || app.bin == bin)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No kernel to reload: no [[app]] of nockapp.toml runs binary '{}'",
+                    bin.as_deref().unwrap_or_default()
+                )
+            })?;
+        Some(std::path::absolute(project_dir.join(&app.jam))?)
+    } else {
+        None
+    };
+
+    let toolchain = toolchain::select(project_dir, offline).await?;
+
+    say!(
         "{} Running project '{}'...",
         "🔨".green(),
         project_name.cyan()
@@ -49,10 +121,22 @@ pub async fn run(project: String, args: Vec<String>) -> Result<()> {
     let mut command = Command::new("cargo");
     command
         .arg("run")
-        .arg("--release") // Run in release mode by default
         .current_dir(project_dir)
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit());
+    profile.apply(&mut command);
+    toolchain.apply(&mut command)?;
+    if let Some(bin) = &bin {
+        command.arg("--bin").arg(bin);
+    }
+    if let Some(jam) = &reload_jam {
+        say!(
+            "{} Reloading the kernel whenever {} is rebuilt",
+            "🔁".cyan(),
+            jam.display().to_string().cyan()
+        );
+        command.env(RELOAD_KERNEL_ENV, jam);
+    }
 
     // Add separator and pass through additional arguments to the program
     if !args.is_empty() {
@@ -65,7 +149,7 @@ pub async fn run(project: String, args: Vec<String>) -> Result<()> {
         .context("Failed to execute cargo run")?;
 
     if status.success() {
-        println!("{} Run completed successfully!", "✓".green());
+        say!("{} Run completed successfully!", "✓".green());
     } else {
         return Err(anyhow::anyhow!(
             "Run failed with exit code: {}",
@@ -75,3 +159,55 @@ pub async fn run(project: String, args: Vec<String>) -> Result<()> {
 
     Ok(())
 }
+
+/// The binary to run, if the project has named binaries
+fn select_binary(
+    binaries: &[String],
+    requested: Option<String>,
+    default_bin: Option<String>,
+) -> Result<Option<String>> {
+    let Some(bin) = requested.or(default_bin) else {
+        if binaries.len() > 1 {
+            anyhow::bail!(
+                "This project has several binaries ({}); choose one with --bin or set \
+                default_bin in the [package] table of nockapp.toml",
+                binaries.join(", ")
+            );
+        }
+        return Ok(None);
+    };
+    if !binaries.is_empty() && !binaries.contains(&bin) {
+        anyhow::bail!(
+            "No binary named '{}' in Cargo.toml; its binaries are {}",
+            bin,
+            binaries.join(", ")
+        );
+    }
+    Ok(Some(bin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_binary() {
+        let binaries = vec!["listen".to_string(), "talk".to_string()];
+        let some = |name: &str| Some(name.to_string());
+
+        assert!(select_binary(&binaries, None, None).is_err());
+        assert_eq!(
+            select_binary(&binaries, None, some("talk")).unwrap(),
+            some("talk")
+        );
+        // --bin wins over the manifest's default
+        assert_eq!(
+            select_binary(&binaries, some("listen"), some("talk")).unwrap(),
+            some("listen")
+        );
+        assert!(select_binary(&binaries, some("shout"), None).is_err());
+
+        // A single-binary project needs no choice
+        assert_eq!(select_binary(&[], None, None).unwrap(), None);
+    }
+}
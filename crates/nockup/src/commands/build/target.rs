@@ -0,0 +1,61 @@
+// src/commands/build/target.rs
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::commands::common;
+
+/// Where the jams and binaries of a build for `triple` go, cargo's target/<triple>/
+pub(super) fn output_dir(project_dir: &Path, triple: &str) -> PathBuf {
+    project_dir.join("target").join(triple)
+}
+
+/// Put the hoon runtime that the configured channel builds for `triple` into `release_dir`
+///
+/// Runtimes are cached in ~/.nockup/targets/<channel>/<triple>/. A channel that publishes none
+/// for `triple` only warns, as the NockApp's own binary does not need it.
+pub(super) async fn bundle_runtime(triple: &str, release_dir: &Path, offline: bool) -> Result<()> {
+    let config = common::get_config()?;
+    let channel = common::configured_channel(&config)?;
+    let cache_dir = common::get_cache_dir()?
+        .join("targets")
+        .join(channel)
+        .join(triple);
+    let cached = cache_dir.join("hoon");
+
+    if !cached.exists() {
+        if offline {
+            say!(
+                "{} The {} hoon runtime for {} is not cached; run without --offline to bundle it",
+                "⚠️".yellow(),
+                channel,
+                triple
+            );
+            return Ok(());
+        }
+        if let Err(e) = common::download_target_runtime(&config, triple, &cache_dir, true).await {
+            say!(
+                "{} Not bundling the hoon runtime for {}: {}",
+                "⚠️".yellow(),
+                triple,
+                e
+            );
+            return Ok(());
+        }
+    }
+
+    tokio::fs::create_dir_all(release_dir).await?;
+    let bundled = release_dir.join("hoon");
+    tokio::fs::copy(&cached, &bundled)
+        .await
+        .with_context(|| format!("Failed to copy {}", cached.display()))?;
+    say!(
+        "{} Bundled the {} hoon runtime for {} at {}",
+        "✓".green(),
+        channel,
+        triple.cyan(),
+        bundled.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,241 @@
+// src/commands/build/toolchain.rs
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+use tokio::process::Command;
+
+use crate::commands::{channel, common};
+use crate::manifest::LockedToolchain;
+
+/// The file in a project's root that pins the toolchain it builds with
+const TOOLCHAIN_FILE: &str = "nockup-toolchain.toml";
+
+#[derive(Debug, Deserialize)]
+struct ToolchainFile {
+    toolchain: ToolchainPin,
+}
+
+/// A channel, and optionally the versions of hoon and hoonc it must provide
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+struct ToolchainPin {
+    channel: String,
+    hoon: Option<String>,
+    hoonc: Option<String>,
+}
+
+/// Where a project's hoon and hoonc come from
+#[derive(Debug, Default)]
+pub(super) struct Toolchain {
+    bin_dir: Option<PathBuf>, // The pinned channel's binaries; None means whatever is on PATH
+}
+
+impl Toolchain {
+    /// The hoonc to compile with
+    pub(super) fn hoonc(&self) -> PathBuf {
+        self.binary("hoonc")
+    }
+
+    fn binary(&self, name: &str) -> PathBuf {
+        match &self.bin_dir {
+            Some(dir) => dir.join(name),
+            None => PathBuf::from(name),
+        }
+    }
+
+    /// The versions that this toolchain's hoon and hoonc report, None for any that is missing
+    pub(super) async fn versions(&self) -> LockedToolchain {
+        LockedToolchain {
+            hoon: binary_version(&self.binary("hoon")).await,
+            hoonc: binary_version(&self.binary("hoonc")).await,
+        }
+    }
+
+    /// Put the toolchain first on the PATH of `command`, so it and anything it runs find it
+    pub(super) fn apply(&self, command: &mut Command) -> Result<()> {
+        if let Some(dir) = &self.bin_dir {
+            let mut paths = vec![dir.clone()];
+            if let Some(path) = std::env::var_os("PATH") {
+                paths.extend(std::env::split_paths(&path));
+            }
+            let path: OsString = std::env::join_paths(paths)?;
+            command.env("PATH", path);
+        }
+        Ok(())
+    }
+}
+
+/// The toolchain that `project_dir` pins in nockup-toolchain.toml, installed if it is missing
+///
+/// Pinned channels are installed to ~/.nockup/channels/<channel>/bin/, apart from the global
+/// channel's binaries. A channel that no longer provides the pinned versions is downloaded again
+/// in case it has caught up; if it still does not, that is an error. Without the file, hoon and
+/// hoonc come from PATH.
+pub(super) async fn select(project_dir: &Path, offline: bool) -> Result<Toolchain> {
+    let path = project_dir.join(TOOLCHAIN_FILE);
+    if !path.exists() {
+        return Ok(Toolchain::default());
+    }
+    let pin = load(&path, &common::get_or_create_config()?)?;
+
+    let bin_dir = common::get_cache_dir()?
+        .join("channels")
+        .join(&pin.channel)
+        .join("bin");
+    let installed = ["hoon", "hoonc"].iter().all(|b| bin_dir.join(b).exists());
+    let mut downloaded = false;
+    if !installed {
+        if offline {
+            anyhow::bail!(
+                "{} pins the {} toolchain, which is not installed; run without --offline to \
+                download it",
+                TOOLCHAIN_FILE,
+                pin.channel
+            );
+        }
+        say!(
+            "{} {} pins the {} toolchain; downloading it...",
+            "⬇️".green(),
+            TOOLCHAIN_FILE,
+            pin.channel.cyan()
+        );
+        common::download_channel_toolchain(&pin.channel, &bin_dir, true).await?;
+        downloaded = true;
+    }
+
+    let mut mismatches = check_versions(&pin, &bin_dir).await?;
+    if !mismatches.is_empty() && !downloaded && !offline {
+        say!(
+            "{} The installed {} toolchain does not match {}; downloading it again...",
+            "⬇️".green(),
+            pin.channel.cyan(),
+            TOOLCHAIN_FILE
+        );
+        common::download_channel_toolchain(&pin.channel, &bin_dir, true).await?;
+        mismatches = check_versions(&pin, &bin_dir).await?;
+    }
+    if !mismatches.is_empty() {
+        anyhow::bail!(
+            "{} pins versions that the {} channel does not provide:\n  - {}",
+            TOOLCHAIN_FILE,
+            pin.channel,
+            mismatches.join("\n  - ")
+        );
+    }
+
+    say!(
+        "{} Using the {} toolchain from {}",
+        "🔧".cyan(),
+        pin.channel.cyan(),
+        TOOLCHAIN_FILE
+    );
+    Ok(Toolchain {
+        bin_dir: Some(bin_dir),
+    })
+}
+
+fn load(path: &Path, config: &toml::Value) -> Result<ToolchainPin> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: ToolchainFile =
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+    // The channels `nockup channel set` accepts
+    channel::source(config, &file.toolchain.channel)
+        .with_context(|| format!("Invalid toolchain in {}", path.display()))?;
+    Ok(file.toolchain)
+}
+
+/// How the installed binaries differ from the versions `pin` asks for
+async fn check_versions(pin: &ToolchainPin, bin_dir: &Path) -> Result<Vec<String>> {
+    let mut mismatches = Vec::new();
+    for (binary, wanted) in [("hoon", &pin.hoon), ("hoonc", &pin.hoonc)] {
+        let Some(wanted) = wanted else {
+            continue;
+        };
+        let output = Command::new(bin_dir.join(binary))
+            .arg("--version")
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {} --version", binary))?;
+        let reported = String::from_utf8_lossy(&output.stdout);
+        match reported_version(&reported) {
+            Some(version) if version_matches(wanted, version) => {}
+            Some(version) => mismatches.push(format!("{} {} (found {})", binary, wanted, version)),
+            None => mismatches.push(format!("{} {} (found no version)", binary, wanted)),
+        }
+    }
+    Ok(mismatches)
+}
+
+async fn binary_version(binary: &Path) -> Option<String> {
+    let output = Command::new(binary).arg("--version").output().await.ok()?;
+    reported_version(&String::from_utf8_lossy(&output.stdout)).map(String::from)
+}
+
+/// The version in the output of `--version`, such as `hoonc 0.2.0`
+fn reported_version(output: &str) -> Option<&str> {
+    output
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('v'))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Whether `version` is `pin`, or starts with it a component at a time, so `0.2` takes `0.2.1`
+fn version_matches(pin: &str, version: &str) -> bool {
+    let pin: Vec<&str> = pin.trim_start_matches('v').split('.').collect();
+    let version: Vec<&str> = version.split(['.', '-', '+']).collect();
+    pin.len() <= version.len() && pin.iter().zip(&version).all(|(p, v)| p == v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = tmp.path().join(TOOLCHAIN_FILE);
+        let config: toml::Value =
+            toml::from_str("[channels.internal]\nurl = \"https://example.com/m.toml\"\n").unwrap();
+
+        std::fs::write(
+            &path, "[toolchain]\nchannel = \"nightly\"\nhoonc = \"0.2\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load(&path, &config).unwrap(),
+            ToolchainPin {
+                channel: "nightly".to_string(),
+                hoon: None,
+                hoonc: Some("0.2".to_string()),
+            }
+        );
+
+        std::fs::write(&path, "[toolchain]\nchannel = \"internal\"\n").unwrap();
+        assert_eq!(load(&path, &config).unwrap().channel, "internal");
+
+        std::fs::write(&path, "[toolchain]\nchannel = \"alpha\"\n").unwrap();
+        assert!(load(&path, &config).is_err());
+        std::fs::write(&path, "[toolchain]\nchannel = \"stable\"\nhoon-c = \"1\"\n").unwrap();
+        assert!(load(&path, &config).is_err());
+    }
+
+    #[test]
+    fn test_version_matches() {
+        assert_eq!(reported_version("hoonc 0.2.0\n"), Some("0.2.0"));
+        assert_eq!(
+            reported_version("hoon v1.0.0-20251016"),
+            Some("1.0.0-20251016")
+        );
+        assert_eq!(reported_version("hoon"), None);
+
+        assert!(version_matches("0.2.0", "0.2.0"));
+        assert!(version_matches("0.2", "0.2.1"));
+        assert!(version_matches("1.0.0", "1.0.0-20251016"));
+        assert!(!version_matches("0.2", "0.20.0"));
+        assert!(!version_matches("0.2.0", "0.2"));
+    }
+}
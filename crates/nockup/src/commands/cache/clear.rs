@@ -3,32 +3,37 @@ use std::fs;
 
 use anyhow::{Context, Result};
 use colored::Colorize;
+use serde_json::json;
 
 use crate::commands::common::get_cache_dir;
+use crate::failure::Failure;
+use crate::output;
 
 /// Clear nockup cache directories
-pub async fn run(git: bool, packages: bool, registry: bool, all: bool) -> Result<()> {
+pub async fn run(git: bool, packages: bool, registry: bool, builds: bool, all: bool) -> Result<()> {
     let cache_dir = get_cache_dir()?.join("cache");
 
     // Determine what to clear
     let clear_git = all || git;
     let clear_packages = all || packages;
     let clear_registry = all || registry;
-
-    // If no flags specified, show help
-    if !clear_git && !clear_packages && !clear_registry {
-        println!("{}", "Please specify what to clear:".yellow());
-        println!("  --git       Clear git repository cache");
-        println!("  --packages  Clear processed packages cache");
-        println!("  --registry  Clear registry cache");
-        println!("  --all       Clear all caches");
-        println!();
-        println!("Example: nockup cache clear --all");
-        return Ok(());
+    let clear_builds = all || builds;
+
+    // If no flags specified, show help and fail, so scripts notice
+    if !clear_git && !clear_packages && !clear_registry && !clear_builds {
+        say!("{}", "Please specify what to clear:".yellow());
+        say!("  --git       Clear git repository cache");
+        say!("  --packages  Clear processed packages cache");
+        say!("  --registry  Clear registry cache");
+        say!("  --builds    Clear cached hoonc builds");
+        say!("  --all       Clear all caches");
+        say!();
+        say!("Example: nockup cache clear --all");
+        return Err(Failure::Usage.wrap(anyhow::anyhow!("Nothing to clear")));
     }
 
-    println!("{} Clearing nockup cache...", "🗑️".cyan());
-    println!();
+    say!("{} Clearing nockup cache...", "🗑️".cyan());
+    say!();
 
     let mut cleared_any = false;
 
@@ -39,14 +44,19 @@ pub async fn run(git: bool, packages: bool, registry: bool, all: bool) -> Result
             let size = calculate_dir_size(&git_dir)?;
             fs::remove_dir_all(&git_dir)
                 .with_context(|| format!("Failed to remove git cache at {}", git_dir.display()))?;
-            println!(
+            say!(
                 "  {} Cleared git cache (freed {})",
                 "✓".green(),
                 format_size(size).cyan()
             );
+            output::emit(
+                "cache-cleared",
+                json!({ "cache": "git", "freed_bytes": size }),
+            );
             cleared_any = true;
         } else {
-            println!("  {} Git cache already empty", "→".cyan());
+            say!("  {} Git cache already empty", "→".cyan());
+            output::emit("cache-cleared", json!({ "cache": "git", "freed_bytes": 0 }));
         }
     }
 
@@ -61,14 +71,29 @@ pub async fn run(git: bool, packages: bool, registry: bool, all: bool) -> Result
                     packages_dir.display()
                 )
             })?;
-            println!(
+            // Package files are hardlinks into the content-addressed store
+            let cas_dir = cache_dir.join("cas");
+            if cas_dir.exists() {
+                fs::remove_dir_all(&cas_dir).with_context(|| {
+                    format!("Failed to remove file store at {}", cas_dir.display())
+                })?;
+            }
+            say!(
                 "  {} Cleared packages cache (freed {})",
                 "✓".green(),
                 format_size(size).cyan()
             );
+            output::emit(
+                "cache-cleared",
+                json!({ "cache": "packages", "freed_bytes": size }),
+            );
             cleared_any = true;
         } else {
-            println!("  {} Packages cache already empty", "→".cyan());
+            say!("  {} Packages cache already empty", "→".cyan());
+            output::emit(
+                "cache-cleared",
+                json!({ "cache": "packages", "freed_bytes": 0 }),
+            );
         }
     }
 
@@ -83,33 +108,68 @@ pub async fn run(git: bool, packages: bool, registry: bool, all: bool) -> Result
                     registry_dir.display()
                 )
             })?;
-            println!(
+            say!(
                 "  {} Cleared registry cache (freed {})",
                 "✓".green(),
                 format_size(size).cyan()
             );
+            output::emit(
+                "cache-cleared",
+                json!({ "cache": "registry", "freed_bytes": size }),
+            );
+            cleared_any = true;
+        } else {
+            say!("  {} Registry cache already empty", "→".cyan());
+            output::emit(
+                "cache-cleared",
+                json!({ "cache": "registry", "freed_bytes": 0 }),
+            );
+        }
+    }
+
+    // Clear build cache
+    if clear_builds {
+        let builds_dir = cache_dir.join("builds");
+        if builds_dir.exists() {
+            let size = calculate_dir_size(&builds_dir)?;
+            fs::remove_dir_all(&builds_dir).with_context(|| {
+                format!("Failed to remove build cache at {}", builds_dir.display())
+            })?;
+            say!(
+                "  {} Cleared build cache (freed {})",
+                "✓".green(),
+                format_size(size).cyan()
+            );
+            output::emit(
+                "cache-cleared",
+                json!({ "cache": "builds", "freed_bytes": size }),
+            );
             cleared_any = true;
         } else {
-            println!("  {} Registry cache already empty", "→".cyan());
+            say!("  {} Build cache already empty", "→".cyan());
+            output::emit(
+                "cache-cleared",
+                json!({ "cache": "builds", "freed_bytes": 0 }),
+            );
         }
     }
 
-    println!();
+    say!();
     if cleared_any {
-        println!("{} Cache cleared successfully", "✓".green());
-        println!(
+        say!("{} Cache cleared successfully", "✓".green());
+        say!(
             "  Run {} to re-download dependencies",
             "nockup package install".cyan()
         );
     } else {
-        println!("{} No cache to clear", "→".cyan());
+        say!("{} No cache to clear", "→".cyan());
     }
 
     Ok(())
 }
 
 /// Calculate the total size of a directory recursively
-fn calculate_dir_size(path: &std::path::Path) -> Result<u64> {
+pub(super) fn calculate_dir_size(path: &std::path::Path) -> Result<u64> {
     let mut total = 0u64;
 
     if path.is_dir() {
@@ -129,7 +189,7 @@ fn calculate_dir_size(path: &std::path::Path) -> Result<u64> {
 }
 
 /// Format bytes as human-readable size
-fn format_size(bytes: u64) -> String {
+pub(super) fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
     const GB: u64 = MB * 1024;
@@ -0,0 +1,334 @@
+// src/commands/cache/gc.rs
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use super::clear::{calculate_dir_size, format_size};
+use crate::cache::PackageCache;
+use crate::git_fetcher::GitFetcher;
+use crate::manifest::{HoonPackage, LockSource, NockAppLock};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A git checkout or cached package that garbage collection may remove
+#[derive(Debug)]
+struct Entry {
+    label: String,
+    path: PathBuf,
+    package: Option<(String, String)>, // Name and version spec of a cached package
+    size: u64,
+    last_used: u64, // Unix timestamp
+    referenced: bool,
+}
+
+/// Remove git checkouts and cached packages that no known nockapp.lock references
+///
+/// With no limits, everything unreferenced is removed. Otherwise unreferenced entries are kept,
+/// newest first, as long as they are younger than `max_age` days and the cache stays within
+/// `max_size`. Referenced entries are never removed.
+pub async fn run(max_size: Option<String>, max_age: Option<u64>, dry_run: bool) -> Result<()> {
+    let max_size = max_size.as_deref().map(parse_size).transpose()?;
+    let cache = PackageCache::new()?;
+    // What is removed is decided from what is read here, so nothing may change it meanwhile
+    let _lock = cache.lock().await?;
+
+    say!("{} Collecting garbage in the nockup cache...", "🗑️".cyan());
+    say!();
+
+    let lockfiles = known_lockfiles(&cache).await?;
+    let mut referenced_commits = HashSet::new();
+    let mut referenced_checkouts = HashSet::new();
+    let fetcher = GitFetcher::new(cache.git_dir());
+    for lock_path in &lockfiles {
+        let lock = NockAppLock::load(lock_path)
+            .with_context(|| format!("Failed to read {}", lock_path.display()))?;
+        for pkg in lock.package {
+            if let LockSource::Git { url, commit, .. } = pkg.source {
+                referenced_checkouts.insert(fetcher.get_repo_cache_path(&url, &commit));
+                referenced_commits.insert((pkg.name, commit));
+            }
+        }
+    }
+    say!(
+        "  {} Keeping what {} known lockfiles reference",
+        "→".cyan(),
+        lockfiles.len()
+    );
+
+    let mut entries = git_checkouts(&cache.git_dir(), &referenced_checkouts)?;
+    for pkg in cache.list_cached().await? {
+        let path = cache.package_path(&pkg.name, &pkg.version_spec);
+        entries.push(Entry {
+            label: format!("{}@{}", pkg.name, pkg.version_spec),
+            size: calculate_dir_size(&path)?,
+            path,
+            referenced: referenced_commits.contains(&(pkg.name.clone(), pkg.commit.clone())),
+            package: Some((pkg.name, pkg.version_spec)),
+            last_used: pkg.cached_at,
+        });
+    }
+
+    let remove = select_for_removal(&entries, max_size, max_age, now());
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    let removed_size: u64 = remove.iter().map(|&i| entries[i].size).sum();
+
+    if remove.is_empty() {
+        say!();
+        say!(
+            "{} Nothing to remove ({} in cache)",
+            "✓".green(),
+            format_size(total).cyan()
+        );
+        return Ok(());
+    }
+
+    say!();
+    for &i in &remove {
+        let entry = &entries[i];
+        let action = if dry_run { "Would remove" } else { "Removed" };
+        if !dry_run {
+            match &entry.package {
+                Some((name, version_spec)) => cache.evict(name, version_spec).await?,
+                None => tokio::fs::remove_dir_all(&entry.path)
+                    .await
+                    .with_context(|| format!("Failed to remove {}", entry.path.display()))?,
+            }
+        }
+        say!(
+            "  {} {} {} ({})",
+            "✓".green(),
+            action,
+            entry.label,
+            format_size(entry.size).dimmed()
+        );
+    }
+
+    say!();
+    if dry_run {
+        say!(
+            "{} Dry run: {} entries would be removed (about {} of {})",
+            "→".cyan(),
+            remove.len(),
+            format_size(removed_size).cyan(),
+            format_size(total)
+        );
+        return Ok(());
+    }
+
+    // Forget the removed packages, then drop the stored files only they linked to
+    let removed_packages: HashSet<&(String, String)> = remove
+        .iter()
+        .filter_map(|&i| entries[i].package.as_ref())
+        .collect();
+    let mut index = cache.load_index().await?;
+    for versions in index.packages.values_mut() {
+        versions.retain(|p| !removed_packages.contains(&(p.name.clone(), p.version_spec.clone())));
+    }
+    index.packages.retain(|_, versions| !versions.is_empty());
+    cache.save_index(&index).await?;
+
+    let checkouts_size: u64 = remove
+        .iter()
+        .filter(|&&i| entries[i].package.is_none())
+        .map(|&i| entries[i].size)
+        .sum();
+    let reclaimed = checkouts_size + cache.collect_garbage().await?;
+
+    say!(
+        "{} Removed {} entries, reclaiming {}",
+        "✓".green(),
+        remove.len(),
+        format_size(reclaimed).cyan()
+    );
+    if let Some(max_size) = max_size {
+        let remaining = total - removed_size;
+        if remaining > max_size {
+            say!(
+                "{} The cache is still {}, over --max-size, because lockfiles reference it",
+                "⚠".yellow(),
+                format_size(remaining)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Known lockfiles that still exist, plus the current project's
+///
+/// Lockfiles of deleted projects are forgotten.
+async fn known_lockfiles(cache: &PackageCache) -> Result<Vec<PathBuf>> {
+    let known = cache.known_lockfiles().await?;
+    let mut lockfiles: Vec<PathBuf> = known.iter().filter(|p| p.exists()).cloned().collect();
+    if lockfiles.len() != known.len() {
+        cache.save_lockfiles(&lockfiles).await?;
+    }
+
+    // Projects installed before lockfiles were registered are covered from inside them
+    let cwd = env::current_dir()?;
+    if let Some(manifest) = HoonPackage::load(&cwd.join("nockapp.toml"))? {
+        let lock_path = cwd.join(&manifest.package.name).join("nockapp.lock");
+        if lock_path.exists() && !lockfiles.contains(&lock_path.canonicalize()?) {
+            lockfiles.push(lock_path);
+        }
+    }
+    Ok(lockfiles)
+}
+
+/// Checkouts in the git cache, which are laid out as `<url hash>/<short commit>/`
+fn git_checkouts(git_dir: &Path, referenced: &HashSet<PathBuf>) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    if !git_dir.exists() {
+        return Ok(entries);
+    }
+    for repo in std::fs::read_dir(git_dir)? {
+        let repo = repo?;
+        if !repo.file_type()?.is_dir() {
+            continue;
+        }
+        for checkout in std::fs::read_dir(repo.path())? {
+            let checkout = checkout?;
+            let metadata = checkout.metadata()?;
+            if !metadata.is_dir() {
+                continue;
+            }
+            let path = checkout.path();
+            entries.push(Entry {
+                label: format!("git checkout {}", checkout.file_name().to_string_lossy()),
+                size: calculate_dir_size(&path)?,
+                referenced: referenced.contains(&path),
+                package: None,
+                last_used: metadata
+                    .modified()?
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+                path,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Indexes of the entries to remove, oldest first
+fn select_for_removal(
+    entries: &[Entry],
+    max_size: Option<u64>,
+    max_age: Option<u64>,
+    now: u64,
+) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..entries.len())
+        .filter(|&i| !entries[i].referenced)
+        .collect();
+    candidates.sort_by_key(|&i| entries[i].last_used);
+    if max_size.is_none() && max_age.is_none() {
+        return candidates;
+    }
+
+    let mut size: u64 = entries.iter().map(|e| e.size).sum();
+    candidates
+        .into_iter()
+        .filter(|&i| {
+            let entry = &entries[i];
+            let too_old = max_age
+                .is_some_and(|days| now.saturating_sub(entry.last_used) > days * SECONDS_PER_DAY);
+            let too_big = max_size.is_some_and(|max| size > max);
+            if too_old || too_big {
+                size -= entry.size;
+            }
+            too_old || too_big
+        })
+        .collect()
+}
+
+/// Parse a size such as `500M`, `2G` or `1.5GB`; plain numbers are bytes
+fn parse_size(size: &str) -> Result<u64> {
+    let upper = size.trim().to_ascii_uppercase();
+    let number = upper.trim_end_matches('B').trim_end_matches('I');
+    let (digits, unit) = match number.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&number[..i], c),
+        _ => (number, ' '),
+    };
+    let multiplier: u64 = match unit {
+        ' ' => 1,
+        'K' => 1024,
+        'M' => 1024 * 1024,
+        'G' => 1024 * 1024 * 1024,
+        'T' => 1024 * 1024 * 1024 * 1024,
+        _ => anyhow::bail!("Unknown unit in size '{}'; use K, M, G or T", size),
+    };
+    let value: f64 = digits
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid size '{}'", size))?;
+    Ok((value * multiplier as f64) as u64)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(label: &str, size: u64, last_used: u64, referenced: bool) -> Entry {
+        Entry {
+            label: label.to_string(),
+            path: PathBuf::from(label),
+            package: None,
+            size,
+            last_used,
+            referenced,
+        }
+    }
+
+    #[test]
+    fn test_select_for_removal() {
+        let now = 100 * SECONDS_PER_DAY;
+        let entries = vec![
+            entry("locked", 500, 0, true),
+            entry("old", 100, 10 * SECONDS_PER_DAY, false),
+            entry("recent", 100, 95 * SECONDS_PER_DAY, false),
+            entry("older", 100, 5 * SECONDS_PER_DAY, false),
+        ];
+
+        // Without limits everything unreferenced goes, oldest first
+        assert_eq!(select_for_removal(&entries, None, None, now), vec![3, 1, 2]);
+        // Only what is over the age limit
+        assert_eq!(
+            select_for_removal(&entries, None, Some(30), now),
+            vec![3, 1]
+        );
+        // The oldest until the cache fits
+        assert_eq!(
+            select_for_removal(&entries, Some(650), None, now),
+            vec![3, 1]
+        );
+        assert_eq!(
+            select_for_removal(&entries, Some(800), None, now),
+            Vec::<usize>::new()
+        );
+        // Referenced entries stay even over the size limit
+        assert_eq!(
+            select_for_removal(&entries, Some(100), None, now),
+            vec![3, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500M").unwrap(), 500 * 1024 * 1024);
+        assert_eq!(parse_size("2g").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert_eq!(parse_size("1.5GB").unwrap(), 3 * 512 * 1024 * 1024);
+        assert_eq!(parse_size("10KiB").unwrap(), 10 * 1024);
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("big").is_err());
+    }
+}
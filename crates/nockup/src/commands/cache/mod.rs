@@ -1,5 +1,7 @@
 // src/commands/cache/mod.rs
 pub mod clear;
+pub mod gc;
+pub mod verify;
 
 use anyhow::Result;
 
@@ -11,7 +13,14 @@ pub async fn run(cmd: CacheCommand) -> Result<()> {
             git,
             packages,
             registry,
+            builds,
             all,
-        } => clear::run(git, packages, registry, all).await,
+        } => clear::run(git, packages, registry, builds, all).await,
+        CacheCommand::Gc {
+            max_size,
+            max_age,
+            dry_run,
+        } => gc::run(max_size, max_age, dry_run).await,
+        CacheCommand::Verify { dry_run } => verify::run(dry_run).await,
     }
 }
@@ -0,0 +1,291 @@
+// src/commands/cache/verify.rs
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use sha2::{Digest, Sha256};
+
+use crate::cache::{content_hash, CachedPackage, PackageCache};
+
+/// What verifying the package cache found, and repaired unless it was a dry run
+#[derive(Debug, Default)]
+struct Report {
+    checked: usize,
+    missing: Vec<CachedPackage>,   // Indexed, but their directory is gone
+    corrupted: Vec<CachedPackage>, // Contents no longer hash to the recorded checksum
+    unrecorded: usize,             // Cached before checksums were recorded; recorded now
+    orphans: Vec<PathBuf>,         // Paths in the cache that the index does not know
+    bad_objects: Vec<PathBuf>,     // Stored files whose contents do not match their name
+}
+
+impl Report {
+    fn is_clean(&self) -> bool {
+        self.missing.is_empty()
+            && self.corrupted.is_empty()
+            && self.orphans.is_empty()
+            && self.bad_objects.is_empty()
+    }
+}
+
+/// Check the package cache against cache-index.json and the packages' checksums
+///
+/// Index entries whose directory is gone are dropped, and corrupted packages and directories
+/// missing from the index are evicted, so the next install fetches them again. With `dry_run`,
+/// problems are only reported.
+pub async fn run(dry_run: bool) -> Result<()> {
+    let cache = PackageCache::new()?;
+
+    say!(
+        "{} Verifying packages in {}...",
+        "🔍".cyan(),
+        cache.packages_dir().display()
+    );
+    say!();
+
+    let report = verify(&cache, !dry_run).await?;
+    let action = |done: &str| if dry_run { "would be" } else { done }.to_string();
+
+    for pkg in &report.missing {
+        say!(
+            "  {} {}@{} is indexed but missing; entry {}",
+            "⚠".yellow(),
+            pkg.name.yellow(),
+            pkg.version_spec,
+            action("dropped")
+        );
+    }
+    for pkg in &report.corrupted {
+        say!(
+            "  {} {}@{} does not match its checksum; {}",
+            "✗".red(),
+            pkg.name.red(),
+            pkg.version_spec,
+            action("evicted")
+        );
+    }
+    for path in &report.orphans {
+        say!(
+            "  {} {} is not in the index; {}",
+            "⚠".yellow(),
+            path.display(),
+            action("evicted")
+        );
+    }
+    for path in &report.bad_objects {
+        say!(
+            "  {} Stored file {} is corrupted; {}",
+            "✗".red(),
+            path.display(),
+            action("removed")
+        );
+    }
+    if report.unrecorded > 0 {
+        say!(
+            "  {} {} packages had no checksum; {}",
+            "→".cyan(),
+            report.unrecorded,
+            if dry_run {
+                "run without --dry-run to record them"
+            } else {
+                "recorded their current contents"
+            }
+        );
+    }
+
+    say!();
+    if report.is_clean() {
+        say!(
+            "{} {} cached packages verified",
+            "✓".green(),
+            report.checked
+        );
+    } else if dry_run {
+        say!("Run without --dry-run to repair the cache");
+    } else {
+        say!(
+            "{} Cache repaired; run {} to fetch evicted packages again",
+            "✓".green(),
+            "nockup package install".cyan()
+        );
+    }
+
+    Ok(())
+}
+
+async fn verify(cache: &PackageCache, repair: bool) -> Result<Report> {
+    // Repairs rewrite the index as read here, so nothing may change it meanwhile
+    let _lock = match repair {
+        true => Some(cache.lock().await?),
+        false => None,
+    };
+
+    // Stored files are named by their SHA-256, so they can be checked on their own. A corrupted
+    // one must go, or newly cached packages would link to it.
+    let mut report = Report {
+        bad_objects: corrupted_objects(&cache.cas_dir())?,
+        ..Report::default()
+    };
+    if repair {
+        for object in &report.bad_objects {
+            tokio::fs::remove_file(object)
+                .await
+                .with_context(|| format!("Failed to remove {}", object.display()))?;
+        }
+    }
+
+    let mut index = cache.load_index().await?;
+    let mut indexed = HashSet::new();
+    for versions in index.packages.values_mut() {
+        let mut kept = Vec::new();
+        for mut pkg in versions.drain(..) {
+            let path = cache.package_path(&pkg.name, &pkg.version_spec);
+            if !path.is_dir() {
+                report.missing.push(pkg);
+                continue;
+            }
+
+            report.checked += 1;
+            let checksum = content_hash(&path)?;
+            indexed.insert(path);
+            match &pkg.checksum {
+                Some(expected) if *expected != checksum => {
+                    if repair {
+                        cache.evict(&pkg.name, &pkg.version_spec).await?;
+                    }
+                    report.corrupted.push(pkg);
+                }
+                _ => {
+                    if pkg.checksum.is_none() {
+                        report.unrecorded += 1;
+                        pkg.checksum = Some(checksum);
+                    }
+                    kept.push(pkg);
+                }
+            }
+        }
+        *versions = kept;
+    }
+    index.packages.retain(|_, versions| !versions.is_empty());
+
+    collect_orphans(&cache.packages_dir(), &indexed, &mut report.orphans)?;
+
+    if repair {
+        for orphan in &report.orphans {
+            if orphan.is_dir() {
+                tokio::fs::remove_dir_all(orphan).await
+            } else {
+                tokio::fs::remove_file(orphan).await
+            }
+            .with_context(|| format!("Failed to remove {}", orphan.display()))?;
+        }
+        cache.save_index(&index).await?;
+        cache.collect_garbage().await?;
+    }
+
+    Ok(report)
+}
+
+/// Files in the content-addressed store whose contents do not hash to their name
+fn corrupted_objects(cas_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut corrupted = Vec::new();
+    if !cas_dir.exists() {
+        return Ok(corrupted);
+    }
+    for shard in std::fs::read_dir(cas_dir)? {
+        let shard = shard?;
+        if !shard.file_type()?.is_dir() {
+            continue;
+        }
+        for object in std::fs::read_dir(shard.path())? {
+            let object = object?;
+            let name = object.file_name().to_string_lossy().into_owned();
+            // Partly written objects are left for garbage collection
+            if name.ends_with(".partial") {
+                continue;
+            }
+            let contents = std::fs::read(object.path())?;
+            let digest = hex::encode(Sha256::digest(&contents));
+            if format!("{}{}", shard.file_name().to_string_lossy(), name) != digest {
+                corrupted.push(object.path());
+            }
+        }
+    }
+    Ok(corrupted)
+}
+
+/// Entries under `dir` that are neither indexed package directories nor lead to one
+fn collect_orphans(
+    dir: &Path,
+    indexed: &HashSet<PathBuf>,
+    orphans: &mut Vec<PathBuf>,
+) -> Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if indexed.contains(&path) {
+            continue;
+        }
+        if path.is_dir() && indexed.iter().any(|p| p.starts_with(&path)) {
+            collect_orphans(&path, indexed, orphans)?;
+        } else {
+            orphans.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn cache_version(cache: &PackageCache, source: &Path, version: &str) -> PathBuf {
+        cache
+            .cache_package("sequent", version, "aaa", "https://example.com", source)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = PackageCache::with_root(tmp.path().join("cache")).unwrap();
+        let source = tmp.path().join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("seq.hoon"), "|%  ++  seq  ~  --").unwrap();
+
+        cache_version(&cache, &source, "v1.0.0").await;
+        let missing = cache_version(&cache, &source, "v1.1.0").await;
+        let orphan = cache.packages_dir().join("lagoon/v0.1.0");
+        std::fs::create_dir_all(&orphan).unwrap();
+        std::fs::remove_dir_all(&missing).unwrap();
+
+        let report = verify(&cache, true).await.unwrap();
+        assert_eq!(report.checked, 1);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].version_spec, "v1.1.0");
+        assert_eq!(report.orphans, vec![cache.packages_dir().join("lagoon")]);
+        assert!(report.corrupted.is_empty() && report.bad_objects.is_empty());
+        assert!(!orphan.exists());
+        assert_eq!(cache.list_cached().await.unwrap().len(), 1);
+
+        // Writing to a cached file changes the stored copy it is linked to
+        let cached = cache_version(&cache, &source, "v1.2.0").await;
+        std::fs::write(cached.join("seq.hoon"), "corrupted").unwrap();
+        let report = verify(&cache, true).await.unwrap();
+        assert_eq!(report.corrupted.len(), 2);
+        assert_eq!(report.bad_objects.len(), 1);
+        assert!(!cached.exists());
+        assert!(cache.list_cached().await.unwrap().is_empty());
+
+        // Caching it again stores the right contents
+        let cached = cache_version(&cache, &source, "v1.2.0").await;
+        assert!(verify(&cache, true).await.unwrap().is_clean());
+        assert_eq!(
+            std::fs::read_to_string(cached.join("seq.hoon")).unwrap(),
+            "|%  ++  seq  ~  --"
+        );
+    }
+}
@@ -1,7 +1,8 @@
 pub mod set;
 pub mod show;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
 
 use crate::cli::ChannelCommand;
 
@@ -11,3 +12,116 @@ pub async fn run(command: ChannelCommand) -> Result<()> {
         ChannelCommand::Show => show::run(),
     }
 }
+
+/// Where a channel's manifest is published
+#[derive(Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The release built from a nockchain branch: its head, or its last commit on or before `until`
+    Branch {
+        branch: &'static str,
+        until: Option<NaiveDate>,
+    },
+    /// A manifest hosted elsewhere, given as the `url` of a channel in config.toml
+    Url(String),
+}
+
+/// Where `channel` is downloaded from
+///
+/// A channel in the `[channels]` table of config.toml comes from its `url`, even one named like
+/// a built-in channel, so an organization can host its own builds. Otherwise `stable`, `beta` and
+/// `nightly` are built from nockchain's master, beta and nightly branches, and
+/// `nightly-YYYY-MM-DD` is the last nightly built on that day (UTC).
+pub fn source(config: &toml::Value, channel: &str) -> Result<Source> {
+    if let Some(custom) = config.get("channels").and_then(|c| c.get(channel)) {
+        // The name is used in file and directory names under ~/.nockup
+        if !channel
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            anyhow::bail!("Invalid channel name '{}': use letters, digits, '-' and '_'", channel);
+        }
+        let url = custom
+            .get("url")
+            .and_then(|u| u.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Channel '{}' in config.toml has no url", channel))?;
+        return Ok(Source::Url(url.to_string()));
+    }
+
+    let branch = match channel {
+        "stable" => "master",
+        "beta" => "beta",
+        "nightly" => "nightly",
+        _ => {
+            if let Some(date) = channel.strip_prefix("nightly-") {
+                let until = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .with_context(|| format!("Invalid date in channel '{}'", channel))?;
+                return Ok(Source::Branch {
+                    branch: "nightly",
+                    until: Some(until),
+                });
+            }
+            anyhow::bail!(
+                "Invalid channel: {}. Use stable, beta, nightly, nightly-YYYY-MM-DD, or a channel \
+                in the [channels] table of ~/.nockup/config.toml",
+                channel
+            );
+        }
+    };
+    Ok(Source::Branch {
+        branch,
+        until: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source() {
+        let config: toml::Value = toml::from_str(
+            r#"
+[channels.internal]
+url = "https://builds.example.com/nockup/manifest.toml"
+
+[channels."bad/name"]
+url = "https://builds.example.com/bad.toml"
+
+[channels.nourl]
+public_key = "RW..."
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            source(&config, "stable").unwrap(),
+            Source::Branch {
+                branch: "master",
+                until: None
+            }
+        );
+        assert_eq!(
+            source(&config, "beta").unwrap(),
+            Source::Branch {
+                branch: "beta",
+                until: None
+            }
+        );
+        assert_eq!(
+            source(&config, "nightly-2025-01-15").unwrap(),
+            Source::Branch {
+                branch: "nightly",
+                until: NaiveDate::from_ymd_opt(2025, 1, 15)
+            }
+        );
+        assert_eq!(
+            source(&config, "internal").unwrap(),
+            Source::Url("https://builds.example.com/nockup/manifest.toml".to_string())
+        );
+
+        assert!(source(&config, "nightly-2025-02-30").is_err());
+        assert!(source(&config, "alpha").is_err());
+        assert!(source(&config, "bad/name").is_err());
+        assert!(source(&config, "nourl").is_err());
+    }
+}
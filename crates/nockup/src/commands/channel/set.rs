@@ -1,27 +1,23 @@
-use std::path::PathBuf;
-
 use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::commands::common::get_cache_dir;
+use crate::output;
 
 pub fn run(channel: &str) -> Result<()> {
-    // validate that is 'nightly' or 'stable', change later when more are supported
-    if channel != "nightly" && channel != "stable" {
-        return Err(anyhow::anyhow!("Invalid channel: {}", channel));
-    }
     let mut config = get_config()?;
+    super::source(&config, channel)?;
     config["channel"] = toml::Value::String(channel.to_string());
     let cache_dir = get_cache_dir()?;
     let config_path = cache_dir.join("config.toml");
     std::fs::write(config_path, toml::to_string(&config)?)
         .context("Failed to write config file")?;
-    println!("Set default channel to '{}'.", channel);
+    say!("Set default channel to '{}'.", channel);
+    say!("Run {} to install its binaries.", "nockup update".cyan());
+    output::emit("channel-set", serde_json::json!({ "channel": channel }));
     Ok(())
 }
 
-fn get_cache_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-    Ok(home.join(".nockup"))
-}
-
 fn get_config() -> Result<toml::Value> {
     let cache_dir = get_cache_dir()?;
     let config_path = cache_dir.join("config.toml");
@@ -1,19 +1,22 @@
-use std::path::PathBuf;
-
 use anyhow::{Context, Result};
 
+use crate::commands::common::get_cache_dir;
+use crate::output;
+
 pub fn run() -> Result<()> {
     let config = get_config()?;
-    println!("Default channel: {}", config["channel"]);
-    println!("Architecture: {}", config["architecture"]);
+    say!("Default channel: {}", config["channel"]);
+    say!("Architecture: {}", config["architecture"]);
+    output::emit(
+        "channel",
+        serde_json::json!({
+            "channel": config["channel"].as_str(),
+            "architecture": config["architecture"].as_str(),
+        }),
+    );
     Ok(())
 }
 
-fn get_cache_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-    Ok(home.join(".nockup"))
-}
-
 fn get_config() -> Result<toml::Value> {
     let cache_dir = get_cache_dir()?;
     let config_path = cache_dir.join("config.toml");
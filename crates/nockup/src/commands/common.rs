@@ -1,5 +1,5 @@
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
@@ -7,15 +7,45 @@ use anyhow::{anyhow, Context, Result};
 use blake3;
 use colored::Colorize;
 use flate2::read::GzDecoder;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use once_cell::sync::OnceCell;
 use sha1::{Digest, Sha1};
 use tar::Archive;
 use tokio::fs as tokio_fs;
 use tokio::process::Command;
 
+use crate::commands::{channel, component};
+use crate::minisign::PublicKey;
+use crate::network::{self, NetworkConfig};
+use crate::output;
+
 const GITHUB_REPO: &str = "nockchain/nockchain";
+/// Minisign public key that signs release manifests, set when release builds are compiled
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("NOCKUP_RELEASE_PUBLIC_KEY");
 const TEMPLATES_BRANCH: &str = "master";
 
+/// Environment variable naming the directory nockup keeps its binaries, caches and config in
+pub const HOME_ENV: &str = "NOCKUP_HOME";
+
+static CACHE_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// Use `dir` instead of ~/.nockup, once, before any command runs (from `--cache-dir`)
+pub fn set_cache_dir(dir: PathBuf) {
+    let dir = std::path::absolute(&dir).unwrap_or(dir);
+    let _ = CACHE_DIR.set(dir);
+}
+
+/// The directory nockup keeps its binaries, caches and config in
+///
+/// `--cache-dir` if given, else `$NOCKUP_HOME`, else ~/.nockup.
 pub fn get_cache_dir() -> Result<PathBuf> {
+    if let Some(dir) = CACHE_DIR.get() {
+        return Ok(dir.clone());
+    }
+    if let Some(dir) = std::env::var_os(HOME_ENV).filter(|dir| !dir.is_empty()) {
+        let dir = PathBuf::from(dir);
+        return Ok(std::path::absolute(&dir).unwrap_or(dir));
+    }
     let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
     Ok(home.join(".nockup"))
 }
@@ -61,6 +91,12 @@ pub fn get_or_create_config() -> Result<toml::Value> {
     Ok(config)
 }
 
+/// Save `config` as ~/.nockup/config.toml
+pub fn write_config(config: &toml::Value) -> Result<()> {
+    let config_path = get_cache_dir()?.join("config.toml");
+    std::fs::write(config_path, toml::to_string(config)?).context("Failed to write config file")
+}
+
 fn write_default_config(config_path: &Path) -> Result<()> {
     let default_config = format!(
         r#"channel = "stable"
@@ -76,10 +112,10 @@ pub async fn download_templates(cache_dir: &Path) -> Result<()> {
     let templates_dir = cache_dir.join("templates");
 
     if has_existing_templates(&templates_dir).await? {
-        println!("{} Existing templates found, updating...", "🔄".yellow());
+        say!("{} Existing templates found, updating...", "🔄".yellow());
         update_templates(&templates_dir).await?;
     } else {
-        println!("{}  Downloading templates from GitHub...", "⬇️".green());
+        say!("{}  Downloading templates from GitHub...", "⬇️".green());
         clone_templates(&templates_dir).await?;
     }
 
@@ -122,12 +158,12 @@ async fn clone_templates(templates_dir: &Path) -> Result<()> {
                 toml::de::from_str(&commit_content).context("Failed to parse commit file")?;
             let local_commit_id = commit["commit"]["id"].to_string().replace("\"", "");
             if local_commit_id == commit_id {
-                println!("{} Templates are up to date", "✅".green());
+                say!("{} Templates are up to date", "✅".green());
                 return Ok(());
             }
         }
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-            println!("{} No local commit ID found", "🔍".yellow());
+            say!("{} No local commit ID found", "🔍".yellow());
         }
         Err(e) => {
             return Err(anyhow::anyhow!("Failed to read commit file: {}", e));
@@ -163,11 +199,15 @@ async fn clone_templates(templates_dir: &Path) -> Result<()> {
         .arg("--branch")
         .arg(TEMPLATES_BRANCH)
         .arg(&repo_url)
-        .arg(&temp_dir);
+        .arg(&temp_dir)
+        .envs(NetworkConfig::load()?.git_env());
 
     command.stdout(Stdio::null());
     command.stderr(Stdio::null());
-    let status = command.status().await?;
+    let spinner = progress_spinner("Cloning templates...");
+    let status = command.status().await;
+    spinner.finish_and_clear();
+    let status = status?;
 
     if !status.success() {
         return Err(anyhow::anyhow!(
@@ -187,7 +227,7 @@ async fn clone_templates(templates_dir: &Path) -> Result<()> {
     match fs::rename(&repo_templates_dir, templates_dir) {
         Ok(_) => {}
         Err(e) if e.raw_os_error() == Some(66) => {
-            println!("{} Rename failed, copying instead...", "⚠️".yellow());
+            say!("{} Rename failed, copying instead...", "⚠️".yellow());
             copy_dir_recursive(&repo_templates_dir, templates_dir)?;
         }
         Err(e) => return Err(e.into()),
@@ -212,7 +252,7 @@ async fn clone_templates(templates_dir: &Path) -> Result<()> {
     match fs::rename(&repo_manifests_dir, &manifests_dir) {
         Ok(_) => {}
         Err(e) if e.raw_os_error() == Some(66) => {
-            println!(
+            say!(
                 "{} Rename failed for manifests, copying instead...",
                 "⚠️".yellow()
             );
@@ -226,7 +266,7 @@ async fn clone_templates(templates_dir: &Path) -> Result<()> {
     fs::write(&commit_file, commit_data)?;
 
     fs::remove_dir_all(&temp_dir)?;
-    println!(
+    say!(
         "{} Templates and manifests downloaded successfully",
         "✓".green()
     );
@@ -260,13 +300,13 @@ pub async fn download_toolchain_files(cache_dir: &Path) -> Result<()> {
     let toolchain_dir = cache_dir.join("toolchains");
 
     if has_existing_toolchain_files(&toolchain_dir).await? {
-        println!(
+        say!(
             "{} Existing toolchain files found, updating...",
             "🔄".yellow()
         );
         update_toolchain_files(&toolchain_dir).await?;
     } else {
-        println!(
+        say!(
             "{}  Downloading toolchain files from GitHub...",
             "⬇️".green()
         );
@@ -300,65 +340,24 @@ async fn clone_toolchain_files(toolchain_dir: &Path) -> Result<()> {
     }
     fs::create_dir_all(toolchain_dir)?;
 
-    println!(
+    say!(
         "{} Fetching latest channel manifests from GitHub releases...",
         "⬇️".green()
     );
 
-    async fn get_latest_manifest(channel: &str, toolchain_dir: &Path) -> Result<()> {
-        let manifest_file = "nockchain-manifest.toml";
-        let output_file = toolchain_dir.join(format!("channel-nockup-{}.toml", channel));
-
-        println!("{} Fetching manifest for {}...", "🔍".yellow(), channel);
-
-        let latest_tag = get_git_commit_id().await?;
-
-        let manifest_url = format!(
-            "https://github.com/nockchain/nockchain/releases/download/build-{}/{}",
-            latest_tag, manifest_file
-        );
-
-        println!("{} Downloading from: {}", "⬇️".blue(), manifest_url);
-
-        let client = reqwest::Client::new();
-        let response = client
-            .get(&manifest_url)
-            .header("User-Agent", "nockup")
-            .send()
-            .await
-            .context("Failed to download manifest")?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to download manifest: HTTP {}",
-                response.status()
-            ));
+    // Stable, and the configured channel if it is another
+    let config = get_or_create_config()?;
+    let mut channels = vec!["stable"];
+    if let Some(configured) = config.get("channel").and_then(|c| c.as_str()) {
+        if configured != "stable" {
+            channels.push(configured);
         }
-
-        let content = response
-            .text()
-            .await
-            .context("Failed to read manifest content")?;
-
-        tokio_fs::write(&output_file, content)
-            .await
-            .context("Failed to write manifest file")?;
-
-        println!(
-            "{} Downloaded: channel-nockup-{}.toml",
-            "✅".green(),
-            channel
-        );
-
-        Ok(())
     }
-
-    let channels = ["stable"];
     let mut errors = Vec::new();
 
     for channel in &channels {
-        if let Err(e) = get_latest_manifest(channel, toolchain_dir).await {
-            println!(
+        if let Err(e) = download_channel_manifest(&config, channel, toolchain_dir).await {
+            say!(
                 "{} Failed to download {} manifest: {}",
                 "⚠️".yellow(),
                 channel,
@@ -376,21 +375,251 @@ async fn clone_toolchain_files(toolchain_dir: &Path) -> Result<()> {
     }
 
     if !errors.is_empty() {
-        println!(
+        say!(
             "{} Some manifests failed to download: {}",
             "⚠️".yellow(),
             errors.join(", ")
         );
     }
 
-    println!("{} Toolchain files setup complete", "✅".green());
+    say!("{} Toolchain files setup complete", "✅".green());
     Ok(())
 }
 
-pub async fn download_binaries(config: &toml::Value) -> Result<()> {
-    let channel = config["channel"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Invalid channel in config"))?;
+/// Download the latest manifest of `channel`, and its signature, into `toolchain_dir`
+async fn download_channel_manifest(
+    config: &toml::Value,
+    channel: &str,
+    toolchain_dir: &Path,
+) -> Result<()> {
+    let manifest_file = "nockchain-manifest.toml";
+    let output_file = toolchain_dir.join(format!("channel-nockup-{}.toml", channel));
+
+    say!("{} Fetching manifest for {}...", "🔍".yellow(), channel);
+
+    let manifest_url = match channel::source(config, channel)? {
+        channel::Source::Branch { branch, until } => {
+            let commit_id = get_branch_commit_id(branch, until).await?;
+            format!(
+                "https://github.com/{}/releases/download/build-{}/{}",
+                GITHUB_REPO, commit_id, manifest_file
+            )
+        }
+        channel::Source::Url(url) => url,
+    };
+
+    say!("{} Downloading from: {}", "⬇️".blue(), manifest_url);
+
+    let client = network::client()?;
+    let response = client
+        .get(&manifest_url)
+        .header("User-Agent", "nockup")
+        .send()
+        .await
+        .context("Failed to download manifest")?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to download manifest: HTTP {}",
+            response.status()
+        ));
+    }
+
+    // Kept byte for byte, as its signature covers the exact bytes
+    let content = response
+        .bytes()
+        .await
+        .context("Failed to read manifest content")?;
+
+    tokio_fs::write(&output_file, content)
+        .await
+        .context("Failed to write manifest file")?;
+
+    say!(
+        "{} Downloaded: channel-nockup-{}.toml",
+        "✅".green(),
+        channel
+    );
+
+    // Without its signature the manifest is only usable with --insecure-skip-verify
+    let signature_url = format!("{}.minisig", manifest_url);
+    let response = client
+        .get(&signature_url)
+        .header("User-Agent", "nockup")
+        .send()
+        .await
+        .context("Failed to download manifest signature")?;
+    if response.status().is_success() {
+        let signature = response
+            .bytes()
+            .await
+            .context("Failed to read manifest signature")?;
+        tokio_fs::write(signature_path(&output_file), signature)
+            .await
+            .context("Failed to write manifest signature")?;
+    } else {
+        say!(
+            "{} No signature published for the {} manifest: HTTP {}",
+            "⚠️".yellow(),
+            channel,
+            response.status()
+        );
+    }
+
+    Ok(())
+}
+
+/// Download nockup and the binary components of the configured channel into ~/.nockup/bin
+///
+/// Unless `verify` is false, the channel manifest must carry a valid minisign signature and
+/// every archive must match the checksums the manifest lists for it.
+pub async fn download_binaries(config: &toml::Value, verify: bool) -> Result<()> {
+    let components = component::installed(config, configured_channel(config)?);
+    let mut indexes = vec!["nockup"];
+    indexes.extend(component::binaries(&components));
+    let (manifest, architecture) = channel_manifest(config, verify)?;
+    let binary_path = get_cache_dir()?.join("bin");
+    install_binaries(&manifest, &architecture, &indexes, &binary_path, verify).await
+}
+
+/// Download `components` of the configured channel, with a fresh copy of its manifest
+pub async fn download_components(
+    config: &toml::Value,
+    components: &[&str],
+    verify: bool,
+) -> Result<()> {
+    let cache_dir = get_cache_dir()?;
+    let binaries = component::binaries(components);
+    if !binaries.is_empty() {
+        let toolchain_dir = cache_dir.join("toolchains");
+        fs::create_dir_all(&toolchain_dir)?;
+        download_channel_manifest(config, configured_channel(config)?, &toolchain_dir).await?;
+        let (manifest, architecture) = channel_manifest(config, verify)?;
+        install_binaries(
+            &manifest,
+            &architecture,
+            &binaries,
+            &cache_dir.join("bin"),
+            verify,
+        )
+        .await?;
+    }
+    if components.contains(&"templates") {
+        download_templates(&cache_dir).await?;
+    }
+    Ok(())
+}
+
+/// Download the hoon runtime that the configured channel builds for `triple` into `binary_path`
+///
+/// The channel manifest must already be downloaded, as by `nockup update`.
+pub async fn download_target_runtime(
+    config: &toml::Value,
+    triple: &str,
+    binary_path: &Path,
+    verify: bool,
+) -> Result<()> {
+    let mut config = config.clone();
+    config["architecture"] = toml::Value::String(triple.to_string());
+    let (manifest, _) = channel_manifest(&config, verify)?;
+    let targets = manifest
+        .get("pkg")
+        .and_then(|p| p.get("hoon"))
+        .and_then(|h| h.get("target"))
+        .and_then(|t| t.as_table());
+    if !targets.is_some_and(|t| t.contains_key(triple)) {
+        anyhow::bail!(
+            "the {} channel has no prebuilt hoon for it (it has {})",
+            configured_channel(&config)?,
+            targets
+                .map(|t| t.keys().cloned().collect::<Vec<_>>().join(", "))
+                .unwrap_or_default()
+        );
+    }
+    install_binaries(&manifest, triple, &["hoon"], binary_path, verify).await
+}
+
+/// The channel config.toml sets
+pub fn configured_channel(config: &toml::Value) -> Result<&str> {
+    config
+        .get("channel")
+        .and_then(|c| c.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid channel in config"))
+}
+
+/// Download the hoon and hoonc of `channel` into `binary_path`, whatever the configured channel
+pub async fn download_channel_toolchain(
+    channel: &str,
+    binary_path: &Path,
+    verify: bool,
+) -> Result<()> {
+    let toolchain_dir = get_cache_dir()?.join("toolchains");
+    fs::create_dir_all(&toolchain_dir)?;
+    let mut config = get_or_create_config()?;
+    download_channel_manifest(&config, channel, &toolchain_dir).await?;
+
+    config["channel"] = toml::Value::String(channel.to_string());
+    let (manifest, architecture) = channel_manifest(&config, verify)?;
+    install_binaries(
+        &manifest,
+        &architecture,
+        &["hoon", "hoonc"],
+        binary_path,
+        verify,
+    )
+    .await
+}
+
+/// Download the binaries `indexes` of a channel manifest and install them in `binary_path`
+async fn install_binaries(
+    manifest: &toml::Value,
+    architecture: &str,
+    indexes: &[&str],
+    binary_path: &Path,
+    verify: bool,
+) -> Result<()> {
+    fs::create_dir_all(binary_path)?;
+
+    for &index in indexes {
+        let temp_extract_dir = fetch_binary(manifest, architecture, index, verify).await?;
+
+        let final_binary_path = binary_path.join(index);
+        let binary_temp_path = temp_extract_dir.join(index);
+
+        if final_binary_path.exists() {
+            fs::remove_file(&final_binary_path)?;
+        }
+
+        fs::rename(&binary_temp_path, &final_binary_path)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&final_binary_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&final_binary_path, perms)?;
+        }
+
+        say!(
+            "{} Installed {} to {}",
+            "✅".green(),
+            index,
+            final_binary_path.display()
+        );
+
+        // Clean up
+        fs::remove_dir_all(&temp_extract_dir)?;
+    }
+
+    Ok(())
+}
+
+/// Read the manifest of the configured channel, with the architecture to download for
+///
+/// With `verify`, the manifest's minisign signature, saved next to it, is checked first, and a
+/// manifest older than one already installed from the channel is refused.
+pub fn channel_manifest(config: &toml::Value, verify: bool) -> Result<(toml::Value, String)> {
+    let channel = configured_channel(config)?;
     let architecture = config["architecture"]
         .as_str()
         .ok_or_else(|| anyhow::anyhow!("Invalid architecture in config"))?;
@@ -405,113 +634,274 @@ pub async fn download_binaries(config: &toml::Value) -> Result<()> {
         channel_name,
         manifest_path.display()
     ))?;
+    if verify {
+        verify_manifest_signature(config, &manifest_path, manifest.as_bytes())?;
+    } else {
+        say!(
+            "{} Skipping signature and checksum verification (--insecure-skip-verify)",
+            "⚠️".yellow()
+        );
+    }
     let manifest: toml::Value = toml::de::from_str(&manifest).context(format!(
         "Failed to parse channel manifest for '{}'",
         channel_name
     ))?;
+    if verify {
+        check_not_rolled_back(config, channel, &manifest_path, &manifest)?;
+    }
 
-    println!(
+    say!(
         "{} Downloading binaries for channel '{}' and architecture '{}'...",
         "⬇️".green(),
         channel_name.cyan(),
         architecture.cyan()
     );
 
-    for index in ["hoon", "hoonc", "nockup"] {
-        println!("{} Downloading {} binary...", "⬇️".green(), index.cyan());
-        let archive_url = manifest["pkg"][index]["target"][architecture]["url"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("{} Invalid URL for {} binary", "❌".red(), index))?;
-        let archive_url = archive_url.replace("http://", "https://");
-
-        let archive_blake3 = manifest["pkg"][index]["target"][architecture]["hash_blake3"]
-            .as_str()
-            .ok_or_else(|| {
-                anyhow::anyhow!("{} Invalid Blake3 hash for {} binary", "❌".red(), index)
-            })?;
-        let archive_sha1 = manifest["pkg"][index]["target"][architecture]["hash_sha1"]
-            .as_str()
-            .ok_or_else(|| {
-                anyhow::anyhow!("{} Invalid SHA1 hash for {} binary", "❌".red(), index)
-            })?;
-
-        let archive_path = download_file(&archive_url).await?;
-
-        verify_checksums(&archive_path, archive_blake3, archive_sha1).await?;
-        println!("{} Blake3 checksum passed.", "✅".green());
-        println!("{} SHA1 checksum passed.", "✅".green());
-
-        let target_dir = get_cache_dir()?;
-        let binary_path = target_dir.join("bin");
-        fs::create_dir_all(&binary_path)?;
-
-        let temp_extract_dir = std::env::temp_dir().join(format!("nockup_extract_{}", index));
-        if temp_extract_dir.exists() {
-            fs::remove_dir_all(&temp_extract_dir)?;
-        }
-        fs::create_dir_all(&temp_extract_dir)?;
+    Ok((manifest, architecture.to_string()))
+}
 
-        extract_archive_contents(&archive_path, &temp_extract_dir, index).await?;
+/// Refuse a channel manifest older than what nockup has already installed from the channel
+///
+/// A signed manifest stays valid forever, so without this a mirror could serve an old one to roll
+/// binaries back to releases with known bugs. The manifest's `date` must be no earlier than that
+/// of the newest manifest accepted for the channel before, which is recorded next to it. Unless
+/// the channel is pinned to a day, the nockup it offers must also be no older than this one.
+fn check_not_rolled_back(
+    config: &toml::Value,
+    channel: &str,
+    manifest_path: &Path,
+    manifest: &toml::Value,
+) -> Result<()> {
+    let accepted_path = accepted_date_path(manifest_path);
+    let accepted = match fs::read_to_string(&accepted_path) {
+        Ok(date) => Some(
+            date.trim()
+                .parse::<chrono::NaiveDate>()
+                .with_context(|| format!("Invalid date in {}", accepted_path.display()))?,
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let pinned = matches!(
+        channel::source(config, channel)?,
+        channel::Source::Branch { until: Some(_), .. }
+    );
+    let installed = semver::Version::parse(env!("CARGO_PKG_VERSION"))?;
+    let date = manifest_freshness(manifest, accepted, (!pinned).then_some(&installed))
+        .with_context(|| {
+            format!(
+                "Refusing to roll back with channel manifest {}. To go back on purpose, pin a \
+                channel to a day, e.g. `nockup channel set nightly-YYYY-MM-DD`.",
+                manifest_path.display()
+            )
+        })?;
+    if let Some(date) = date {
+        fs::write(&accepted_path, date.to_string()).context("Failed to record manifest date")?;
+    }
+    Ok(())
+}
 
-        // Verify GPG signature if on Linux
-        if std::env::consts::OS == "linux" {
-            let binary_temp_path = temp_extract_dir.join(index);
-            let signature_temp_path = temp_extract_dir.join(format!("{}.asc", index));
+/// The `date` of a channel manifest, once it is checked to be no earlier than `accepted` and,
+/// with `installed`, to offer a nockup no older than that
+fn manifest_freshness(
+    manifest: &toml::Value,
+    accepted: Option<chrono::NaiveDate>,
+    installed: Option<&semver::Version>,
+) -> Result<Option<chrono::NaiveDate>> {
+    let date = manifest
+        .get("date")
+        .and_then(|d| d.as_str())
+        .map(|d| {
+            d.parse::<chrono::NaiveDate>()
+                .with_context(|| format!("Invalid date '{}' in channel manifest", d))
+        })
+        .transpose()?;
+    match (date, accepted) {
+        (Some(date), Some(accepted)) if date < accepted => {
+            return Err(anyhow!(
+                "The manifest is dated {}, before the {} manifest already installed from this \
+                channel",
+                date,
+                accepted
+            ));
+        }
+        (None, Some(accepted)) => {
+            return Err(anyhow!(
+                "The manifest has no date to check against the {} manifest already installed \
+                from this channel",
+                accepted
+            ));
+        }
+        _ => {}
+    }
 
-            if signature_temp_path.exists() {
-                verify_gpg_signature(&binary_temp_path, &signature_temp_path).await?;
-            } else {
-                println!(
-                    "{} Warning: No signature file found in archive for {}",
-                    "⚠️".yellow(),
-                    index
-                );
-            }
-        } else {
-            println!(
-                "{} Skipping signature verification on {} (not yet supported)",
-                "⚠️".yellow(),
-                std::env::consts::OS
-            );
+    let offered = manifest
+        .get("pkg")
+        .and_then(|p| p.get("nockup"))
+        .and_then(|n| n.get("version"))
+        .and_then(|v| v.as_str());
+    if let (Some(installed), Some(offered)) = (installed, offered) {
+        let offered = semver::Version::parse(offered)
+            .with_context(|| format!("Invalid nockup version '{}' in channel manifest", offered))?;
+        if offered < *installed {
+            return Err(anyhow!(
+                "The manifest offers nockup {}, older than this nockup {}", offered, installed
+            ));
         }
+    }
+    Ok(date)
+}
 
-        let final_binary_path = binary_path.join(index);
-        let binary_temp_path = temp_extract_dir.join(index);
+/// Where the date of the newest manifest accepted for a channel is recorded
+fn accepted_date_path(manifest_path: &Path) -> PathBuf {
+    let mut name = manifest_path.as_os_str().to_owned();
+    name.push(".accepted");
+    PathBuf::from(name)
+}
 
-        if final_binary_path.exists() {
-            fs::remove_file(&final_binary_path)?;
-        }
+/// Check the minisign signature saved next to a channel manifest
+///
+/// The key is the channel's `public_key` from the `[channels]` table of config.toml, or else
+/// `release_public_key` from config.toml, or else the one built into nockup.
+fn verify_manifest_signature(
+    config: &toml::Value,
+    manifest_path: &Path,
+    manifest: &[u8],
+) -> Result<()> {
+    let channel_key = config
+        .get("channel")
+        .and_then(|c| c.as_str())
+        .and_then(|c| config.get("channels")?.get(c)?.get("public_key"));
+    let key = channel_key
+        .or_else(|| config.get("release_public_key"))
+        .and_then(|k| k.as_str())
+        .or(RELEASE_PUBLIC_KEY)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "This nockup was built without a release signing key, so it cannot verify \
+                downloads. Set release_public_key in ~/.nockup/config.toml, or pass \
+                --insecure-skip-verify to `nockup update`, `nockup self update` or \
+                `nockup component add` to install unverified binaries."
+            )
+        })?;
+    let key = PublicKey::parse(key)?;
+
+    let signature_path = signature_path(manifest_path);
+    let signature = std::fs::read_to_string(&signature_path).with_context(|| {
+        format!(
+            "Channel manifest {} is not signed: no {}. Pass --insecure-skip-verify to `nockup \
+            update`, `nockup self update` or `nockup component add` to install unverified \
+            binaries.",
+            manifest_path.display(),
+            signature_path.display()
+        )
+    })?;
+    let trusted_comment = key.verify(manifest, &signature).with_context(|| {
+        format!(
+            "Signature verification failed for channel manifest {}",
+            manifest_path.display()
+        )
+    })?;
+
+    say!(
+        "{} Manifest signature verified ({}).",
+        "✅".green(),
+        trusted_comment
+    );
+    Ok(())
+}
 
-        fs::rename(&binary_temp_path, &final_binary_path)?;
+/// Where the minisign signature of a downloaded file is saved
+fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".minisig");
+    PathBuf::from(name)
+}
 
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = std::fs::metadata(&final_binary_path)?.permissions();
-            perms.set_mode(0o755);
-            std::fs::set_permissions(&final_binary_path, perms)?;
+/// Download the binary `index` of a channel manifest, check its checksums and signature, and
+/// extract it
+///
+/// Returns the temporary directory the binary was extracted to, as `<dir>/<index>`; the caller
+/// removes it. Without `verify`, nothing is checked.
+pub async fn fetch_binary(
+    manifest: &toml::Value,
+    architecture: &str,
+    index: &str,
+    verify: bool,
+) -> Result<PathBuf> {
+    say!("{} Downloading {} binary...", "⬇️".green(), index.cyan());
+    let target = &manifest["pkg"][index]["target"][architecture];
+    let archive_url = target["url"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("{} Invalid URL for {} binary", "❌".red(), index))?;
+    let archive_url = archive_url.replace("http://", "https://");
+
+    let archive_path = download_file(&archive_url).await?;
+
+    if verify {
+        let hash = |field: &str, name: &str| {
+            target[field].as_str().ok_or_else(|| {
+                anyhow::anyhow!("{} Invalid {} hash for {} binary", "❌".red(), name, index)
+            })
+        };
+        let expected = Checksums {
+            sha256: hash("hash_sha256", "SHA-256")?,
+            blake3: hash("hash_blake3", "Blake3")?,
+            sha1: hash("hash_sha1", "SHA1")?,
+        };
+        // Leave no download behind that does not match
+        if let Err(e) = verify_checksums(&archive_path, &expected).await {
+            fs::remove_file(&archive_path).ok();
+            return Err(e);
         }
+        say!("{} SHA-256 checksum passed.", "✅".green());
+        say!("{} Blake3 checksum passed.", "✅".green());
+        say!("{} SHA1 checksum passed.", "✅".green());
+    }
 
-        println!(
-            "{} Installed {} to {}",
-            "✅".green(),
-            index,
-            final_binary_path.display()
-        );
-
-        // Clean up
+    let temp_extract_dir = std::env::temp_dir().join(format!("nockup_extract_{}", index));
+    if temp_extract_dir.exists() {
         fs::remove_dir_all(&temp_extract_dir)?;
-        fs::remove_file(&archive_path)?;
     }
+    fs::create_dir_all(&temp_extract_dir)?;
 
-    Ok(())
+    extract_archive_contents(&archive_path, &temp_extract_dir, index).await?;
+    fs::remove_file(&archive_path)?;
+
+    if !verify {
+        return Ok(temp_extract_dir);
+    }
+
+    // Verify GPG signature if on Linux
+    if std::env::consts::OS == "linux" {
+        let binary_temp_path = temp_extract_dir.join(index);
+        let signature_temp_path = temp_extract_dir.join(format!("{}.asc", index));
+
+        if signature_temp_path.exists() {
+            verify_gpg_signature(&binary_temp_path, &signature_temp_path).await?;
+        } else {
+            say!(
+                "{} Warning: No signature file found in archive for {}",
+                "⚠️".yellow(),
+                index
+            );
+        }
+    } else {
+        say!(
+            "{} Skipping signature verification on {} (not yet supported)",
+            "⚠️".yellow(),
+            std::env::consts::OS
+        );
+    }
+
+    Ok(temp_extract_dir)
 }
 
 async fn verify_gpg_signature(
     archive_path: &std::path::Path,
     signature_path: &std::path::Path,
 ) -> Result<()> {
-    println!("{} Verifying GPG signature...", "🔐".yellow());
+    say!("{} Verifying GPG signature...", "🔐".yellow());
 
     if !archive_path.exists() {
         return Err(anyhow::anyhow!(
@@ -543,14 +933,14 @@ async fn verify_gpg_signature(
     if output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         if stderr.contains("Good signature") {
-            println!("{} GPG signature verified successfully", "✅".green());
+            say!("{} GPG signature verified successfully", "✅".green());
             return Ok(());
         }
     }
 
     let stderr = String::from_utf8_lossy(&output.stderr);
     if stderr.contains("No public key") {
-        println!(
+        say!(
             "{} Public key not found, importing from keyserver...",
             "🔑".yellow()
         );
@@ -580,7 +970,7 @@ async fn verify_gpg_signature(
             }
         }
 
-        println!("{} Public key imported successfully", "✅".green());
+        say!("{} Public key imported successfully", "✅".green());
 
         let retry_output = Command::new("gpg")
             .args([
@@ -606,7 +996,7 @@ async fn verify_gpg_signature(
 
         let retry_stderr = String::from_utf8_lossy(&retry_output.stderr);
         if retry_stderr.contains("Good signature") {
-            println!("{} GPG signature verified successfully", "✅".green());
+            say!("{} GPG signature verified successfully", "✅".green());
         } else {
             return Err(anyhow::anyhow!(
                 "GPG signature verification failed: {}", retry_stderr
@@ -626,7 +1016,7 @@ async fn extract_archive_contents(
     target_dir: &std::path::Path,
     binary_name: &str,
 ) -> Result<()> {
-    println!(
+    say!(
         "{} Extracting {} and signature from archive...",
         "📦".yellow(),
         binary_name
@@ -662,7 +1052,7 @@ async fn extract_archive_contents(
                 target_path.display()
             ))?;
 
-            println!("{} Extracted {}", "✅".green(), target_path.display());
+            say!("{} Extracted {}", "✅".green(), target_path.display());
         }
     }
 
@@ -676,39 +1066,198 @@ async fn extract_archive_contents(
     Ok(())
 }
 
+/// Download `url` to a temporary file, showing its progress, and return the file's path
+///
+/// The download is kept in a `.part` file named after the URL until it completes, so one cut
+/// off, in this run or an earlier one, resumes where it stopped with a ranged request. A
+/// completed download must be as long as the server said it would be.
 async fn download_file(url: &str) -> Result<PathBuf> {
-    let response = reqwest::get(url)
-        .await
-        .context(format!("Failed to download file from '{}'", url))?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "Failed to download file from '{}': HTTP {}",
-            url,
-            response.status()
-        ));
+    let url_filename = url.split('/').next_back().unwrap_or("download");
+    let url_hash = hex::encode(&sha2::Sha256::digest(url.as_bytes())[..8]);
+    let download_dir = std::env::temp_dir().join("nockup-downloads");
+    fs::create_dir_all(&download_dir).context("Failed to create download directory")?;
+    let temp_file = download_dir.join(format!("{}-{}", url_hash, url_filename));
+    let partial = download_dir.join(format!("{}-{}.part", url_hash, url_filename));
+
+    let client = network::client()?;
+    let bar = download_bar(url_filename);
+    let mut attempt = 1;
+    loop {
+        match download_part(&client, url, &partial, &bar).await {
+            Ok(true) => break,
+            Ok(false) if attempt < DOWNLOAD_ATTEMPTS => {}
+            Ok(false) => {
+                bar.abandon();
+                anyhow::bail!(
+                    "Download of '{}' was cut off {} times; run the command again to resume it",
+                    url, DOWNLOAD_ATTEMPTS
+                );
+            }
+            // Failures to connect or to read the body may pass; bad responses will not
+            Err(e)
+                if attempt < DOWNLOAD_ATTEMPTS
+                    && e.chain().any(|cause| cause.is::<reqwest::Error>()) =>
+            {
+                bar.suspend(|| say!("{} {:#}; resuming...", "⚠️".yellow(), e));
+            }
+            Err(e) => {
+                bar.abandon();
+                return Err(e);
+            }
+        }
+        attempt += 1;
     }
+    bar.finish_and_clear();
 
-    let url_filename = url.split('/').next_back().unwrap_or("download");
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("SystemTime should be after UNIX_EPOCH")
-        .as_secs();
-    let filename = format!("nockup_{}_{}", timestamp, url_filename);
-    let temp_file = std::env::temp_dir().join(filename);
-
-    let mut file = std::fs::File::create(&temp_file).context("Failed to create temporary file")?;
-    let content = response.bytes().await?;
-    std::io::copy(&mut content.as_ref(), &mut file).context("Failed to write to temporary file")?;
+    fs::rename(&partial, &temp_file).context("Failed to move finished download")?;
     Ok(temp_file)
 }
 
-async fn verify_checksums(
-    file_path: &PathBuf,
-    expected_blake3: &str,
-    expected_sha1: &str,
-) -> Result<()> {
+/// How many times a download is tried before giving up, each resuming the last
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Fetch what `partial` lacks of `url` into it; false if the server stopped short
+async fn download_part(
+    client: &reqwest::Client,
+    url: &str,
+    partial: &Path,
+    bar: &ProgressBar,
+) -> Result<bool> {
+    let offset = fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    let mut request = client.get(url).header("User-Agent", "nockup");
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to download file from '{}'", url))?;
+
+    let status = response.status();
+    let content_range = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range);
+    let (start, total) = match (status, content_range) {
+        (reqwest::StatusCode::PARTIAL_CONTENT, Some((start, total))) if start == offset => {
+            (offset, total)
+        }
+        // What the server has no longer continues the partial file, so it starts over
+        (reqwest::StatusCode::PARTIAL_CONTENT | reqwest::StatusCode::RANGE_NOT_SATISFIABLE, _) => {
+            fs::remove_file(partial)?;
+            return Ok(false);
+        }
+        (status, _) if status.is_success() => (0, response.content_length()),
+        (status, _) => anyhow::bail!("Failed to download file from '{}': HTTP {}", url, status),
+    };
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(start > 0)
+        .truncate(start == 0)
+        .open(partial)
+        .context("Failed to open temporary file")?;
+    if let Some(total) = total {
+        bar.set_length(total);
+        bar.set_style(download_style(true));
+    }
+    bar.set_position(start);
+
+    let mut written = start;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .with_context(|| format!("Download of '{}' was interrupted", url))?
+    {
+        file.write_all(&chunk)
+            .context("Failed to write to temporary file")?;
+        written += chunk.len() as u64;
+        bar.set_position(written);
+    }
+
+    match total {
+        Some(total) if written < total => Ok(false),
+        Some(total) if written > total => {
+            fs::remove_file(partial)?;
+            anyhow::bail!(
+                "Downloaded {} bytes of '{}', more than the {} the server announced", written, url,
+                total
+            )
+        }
+        _ => Ok(true),
+    }
+}
+
+/// The first byte and the full length of a `Content-Range: bytes <first>-<last>/<length>`
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, length) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let first = range.split_once('-')?.0.trim().parse().ok()?;
+    let length = match length.trim() {
+        "*" => None,
+        length => Some(length.parse().ok()?),
+    };
+    Some((first, length))
+}
+
+/// A progress bar for downloading `name`, drawn on stderr when it is a terminal
+fn download_bar(name: &str) -> ProgressBar {
+    if output::is_json() || output::is_ci() {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+    bar.set_style(download_style(false));
+    bar.set_message(name.to_string());
+    bar
+}
+
+/// A spinner showing `message` until it is finished, for work whose progress is unknown
+fn progress_spinner(message: &str) -> ProgressBar {
+    if output::is_json() || output::is_ci() {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+    spinner.set_style(
+        ProgressStyle::with_template("  {spinner} {msg}")
+            .expect("spinner template should be valid"),
+    );
+    spinner.set_message(message.to_string());
+    spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+    spinner
+}
+
+/// How a download's progress is drawn: a bar once its length is known, a counter before
+fn download_style(known_length: bool) -> ProgressStyle {
+    let template = if known_length {
+        "  {msg} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})"
+    } else {
+        "  {spinner} {msg} {bytes} ({bytes_per_sec})"
+    };
+    ProgressStyle::with_template(template)
+        .expect("download progress template should be valid")
+        .progress_chars("=> ")
+}
+
+/// Checksums of a release archive, as listed in the channel manifest
+struct Checksums<'a> {
+    sha256: &'a str,
+    blake3: &'a str,
+    sha1: &'a str,
+}
+
+async fn verify_checksums(file_path: &PathBuf, expected: &Checksums<'_>) -> Result<()> {
     let bytes =
         std::fs::read(file_path).context("Failed to read file for checksum verification")?;
+    let (expected_blake3, expected_sha1) = (expected.blake3, expected.sha1);
+
+    let computed_sha256 = hex::encode(sha2::Sha256::digest(&bytes));
+    if !computed_sha256.eq_ignore_ascii_case(expected.sha256) {
+        return Err(anyhow::anyhow!(
+            "Checksum verification failed: expected SHA-256 {}, got {}", expected.sha256,
+            computed_sha256
+        ));
+    }
 
     let computed_blake3 = blake3::hash(&bytes);
     if computed_blake3.to_string() != expected_blake3 {
@@ -749,10 +1298,24 @@ pub async fn write_commit_details(cache_dir: &Path) -> Result<()> {
 }
 
 async fn get_git_commit_id() -> Result<String> {
-    let repo_url = "https://api.github.com/repos/nockchain/nockchain/commits/master";
-    let client = reqwest::Client::new();
+    get_branch_commit_id("master", None).await
+}
+
+/// The head of `branch` of nockchain, or with `until`, its last commit on or before that day
+async fn get_branch_commit_id(branch: &str, until: Option<chrono::NaiveDate>) -> Result<String> {
+    let repo_url = match until {
+        None => format!(
+            "https://api.github.com/repos/{}/commits/{}",
+            GITHUB_REPO, branch
+        ),
+        Some(day) => format!(
+            "https://api.github.com/repos/{}/commits?sha={}&until={}T23:59:59Z&per_page=1",
+            GITHUB_REPO, branch, day
+        ),
+    };
+    let client = network::client()?;
     let response = client
-        .get(repo_url)
+        .get(&repo_url)
         .header("User-Agent", "nockup")
         .send()
         .await
@@ -766,8 +1329,57 @@ async fn get_git_commit_id() -> Result<String> {
     }
 
     let json: serde_json::Value = response.json().await.context("Invalid JSON response")?;
-    let commit_id = json["sha"]
+    // Listing commits returns an array, with nothing in it if the branch is younger than `until`
+    let commit = match until {
+        None => &json,
+        Some(day) => json
+            .get(0)
+            .ok_or_else(|| anyhow::anyhow!("No {} commit on or before {}", branch, day))?,
+    };
+    let commit_id = commit["sha"]
         .as_str()
         .ok_or_else(|| anyhow::anyhow!("Missing commit ID in response"))?;
     Ok(commit_id.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_freshness() {
+        let manifest: toml::Value = toml::from_str(
+            r#"
+            date = "2025-06-02"
+            [pkg.nockup]
+            version = "0.3.0"
+            "#,
+        )
+        .unwrap();
+        let day = |d: &str| d.parse::<chrono::NaiveDate>().unwrap();
+        let version = |v: &str| semver::Version::parse(v).unwrap();
+
+        assert_eq!(
+            manifest_freshness(&manifest, None, Some(&version("0.3.0"))).unwrap(),
+            Some(day("2025-06-02"))
+        );
+        assert!(manifest_freshness(&manifest, Some(day("2025-06-02")), None).is_ok());
+        assert!(manifest_freshness(&manifest, Some(day("2025-06-03")), None).is_err());
+        assert!(manifest_freshness(&manifest, None, Some(&version("0.3.1"))).is_err());
+
+        let undated: toml::Value = toml::from_str("[pkg.nockup]\nversion = \"0.3.0\"").unwrap();
+        assert_eq!(manifest_freshness(&undated, None, None).unwrap(), None);
+        assert!(manifest_freshness(&undated, Some(day("2025-06-02")), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 1024-4095/4096"),
+            Some((1024, Some(4096)))
+        );
+        assert_eq!(parse_content_range("bytes 0-99/*"), Some((0, None)));
+        assert_eq!(parse_content_range("bytes */4096"), None);
+        assert_eq!(parse_content_range("items 0-9/10"), None);
+    }
+}
@@ -0,0 +1,58 @@
+// src/commands/component/add.rs
+use anyhow::Result;
+use colored::Colorize;
+
+use super::{component, installed, record, target_channel};
+use crate::commands::common;
+
+/// Add `names` to the components of `channel`, or of the configured channel
+///
+/// Components of the configured channel are downloaded now; those of another channel are
+/// recorded, to be installed by `nockup update` once it is the configured channel.
+pub async fn run(names: &[String], channel: Option<String>, verify: bool) -> Result<()> {
+    let mut config = common::get_config()?;
+    let (channel, is_configured) = target_channel(&config, channel)?;
+    let mut components = installed(&config, &channel);
+    let mut added = Vec::new();
+    for name in names {
+        let component = component(name)?;
+        if components.contains(&component) {
+            say!(
+                "{} {} is already installed for {}",
+                "✓".green(),
+                component,
+                channel
+            );
+        } else if !added.contains(&component) {
+            added.push(component);
+        }
+    }
+    if added.is_empty() {
+        return Ok(());
+    }
+
+    if is_configured {
+        common::download_components(&config, &added, verify).await?;
+    }
+    components.extend(&added);
+    record(&mut config, &channel, &components)?;
+    common::write_config(&config)?;
+
+    for component in &added {
+        say!(
+            "{} Added {} to the {} channel",
+            "✅".green(),
+            component.cyan(),
+            channel
+        );
+    }
+    if !is_configured {
+        say!(
+            "{} Run {} and then {} to install them",
+            "→".cyan(),
+            format!("nockup channel set {}", channel).cyan(),
+            "nockup update".cyan()
+        );
+    }
+    Ok(())
+}
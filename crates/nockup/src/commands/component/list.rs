@@ -0,0 +1,38 @@
+// src/commands/component/list.rs
+use anyhow::Result;
+use colored::Colorize;
+
+use super::{installed, target_channel, COMPONENTS};
+use crate::commands::common;
+use crate::output;
+
+/// List the components there are, marking those installed for `channel`
+pub fn run(channel: Option<String>) -> Result<()> {
+    let config = common::get_config()?;
+    let (channel, _) = target_channel(&config, channel)?;
+    let components = installed(&config, &channel);
+
+    say!(
+        "{} Components of the {} channel:",
+        "📦".cyan(),
+        channel.cyan()
+    );
+    for (component, description) in COMPONENTS {
+        let is_installed = components.contains(&component);
+        let mark = if is_installed {
+            "✓".green()
+        } else {
+            "·".dimmed()
+        };
+        say!("  {} {:<18} {}", mark, component, description.dimmed());
+        output::emit(
+            "component",
+            serde_json::json!({
+                "name": component,
+                "channel": channel,
+                "installed": is_installed,
+            }),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+// src/commands/component/mod.rs
+pub mod add;
+pub mod list;
+pub mod remove;
+
+use anyhow::Result;
+
+use crate::cli::ComponentCommand;
+use crate::commands::{channel, common};
+
+/// The components of a channel's toolchain, with what each is
+pub const COMPONENTS: [(&str, &str); 5] = [
+    ("hoon", "the Hoon runtime"),
+    ("hoonc", "the Hoon compiler"),
+    ("nockchain", "the Nockchain node"),
+    ("nockchain-wallet", "the Nockchain wallet"),
+    ("templates", "project templates for `nockup project init`"),
+];
+
+/// The components of a channel that config.toml records none for, which `nockup update` has
+/// always installed
+const DEFAULT_COMPONENTS: [&str; 3] = ["hoon", "hoonc", "templates"];
+
+pub async fn run(cmd: ComponentCommand) -> Result<()> {
+    match cmd {
+        ComponentCommand::Add {
+            components,
+            channel,
+            insecure_skip_verify,
+        } => add::run(&components, channel, !insecure_skip_verify).await,
+        ComponentCommand::Remove {
+            components,
+            channel,
+        } => remove::run(&components, channel).await,
+        ComponentCommand::List { channel } => list::run(channel),
+    }
+}
+
+/// The component called `name`; `choo`, the former name of hoonc, is taken for it
+pub fn component(name: &str) -> Result<&'static str> {
+    let name = if name == "choo" { "hoonc" } else { name };
+    COMPONENTS
+        .iter()
+        .map(|(component, _)| *component)
+        .find(|component| *component == name)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown component '{}'. Components are: {}",
+                name,
+                COMPONENTS.map(|(component, _)| component).join(", ")
+            )
+        })
+}
+
+/// The components installed for `channel`, as recorded in the `[components]` table of
+/// config.toml
+pub fn installed(config: &toml::Value, channel: &str) -> Vec<&'static str> {
+    match config
+        .get("components")
+        .and_then(|c| c.get(channel))
+        .and_then(|c| c.as_array())
+    {
+        Some(recorded) => {
+            let mut components = Vec::new();
+            for name in recorded.iter().filter_map(|name| name.as_str()) {
+                if let Ok(component) = component(name) {
+                    if !components.contains(&component) {
+                        components.push(component);
+                    }
+                }
+            }
+            components
+        }
+        None => DEFAULT_COMPONENTS.to_vec(),
+    }
+}
+
+/// The components among `components` that are binaries, installed in ~/.nockup/bin
+pub fn binaries<'a>(components: &[&'a str]) -> Vec<&'a str> {
+    components
+        .iter()
+        .copied()
+        .filter(|&component| component != "templates")
+        .collect()
+}
+
+/// Record `components` as those installed for `channel`, in the order of COMPONENTS
+fn record(config: &mut toml::Value, channel: &str, components: &[&str]) -> Result<()> {
+    let recorded = COMPONENTS
+        .iter()
+        .map(|(component, _)| *component)
+        .filter(|component| components.contains(component))
+        .map(|component| toml::Value::String(component.to_string()))
+        .collect();
+    let table = config
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("Invalid config file"))?
+        .entry("components")
+        .or_insert_with(|| toml::Value::Table(toml::map::Map::new()))
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("Invalid [components] table in config file"))?;
+    table.insert(channel.to_string(), toml::Value::Array(recorded));
+    Ok(())
+}
+
+/// `channel`, or else the configured channel, with whether it is the configured one
+fn target_channel(config: &toml::Value, channel: Option<String>) -> Result<(String, bool)> {
+    let configured = common::configured_channel(config)?;
+    match channel {
+        Some(channel) => {
+            channel::source(config, &channel)?;
+            let is_configured = channel == configured;
+            Ok((channel, is_configured))
+        }
+        None => Ok((configured.to_string(), true)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installed() {
+        let mut config: toml::Value = toml::from_str(
+            r#"
+channel = "stable"
+
+[components]
+nightly = ["hoonc", "choo", "nockchain-wallet", "unknown"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(installed(&config, "stable"), DEFAULT_COMPONENTS);
+        assert_eq!(installed(&config, "nightly"), ["hoonc", "nockchain-wallet"]);
+        assert_eq!(binaries(&installed(&config, "stable")), ["hoon", "hoonc"]);
+
+        record(&mut config, "stable", &["templates", "hoonc"]).unwrap();
+        assert_eq!(installed(&config, "stable"), ["hoonc", "templates"]);
+        record(&mut config, "stable", &[]).unwrap();
+        assert!(installed(&config, "stable").is_empty());
+
+        assert_eq!(component("choo").unwrap(), "hoonc");
+        assert!(component("cargo").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+// src/commands/component/remove.rs
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use super::{component, installed, record, target_channel};
+use crate::commands::common;
+
+/// Remove `names` from the components of `channel`, or of the configured channel
+///
+/// Components of the configured channel are deleted from ~/.nockup too; `nockup update` no longer
+/// downloads them.
+pub async fn run(names: &[String], channel: Option<String>) -> Result<()> {
+    let mut config = common::get_config()?;
+    let (channel, is_configured) = target_channel(&config, channel)?;
+    let cache_dir = common::get_cache_dir()?;
+    let mut components = installed(&config, &channel);
+
+    for name in names {
+        let component = component(name)?;
+        if !components.contains(&component) {
+            say!(
+                "{} {} is not installed for {}",
+                "⚠️".yellow(),
+                component,
+                channel
+            );
+            continue;
+        }
+        components.retain(|c| *c != component);
+
+        if is_configured {
+            let path = if component == "templates" {
+                cache_dir.join("templates")
+            } else {
+                cache_dir.join("bin").join(component)
+            };
+            if path.is_dir() {
+                tokio::fs::remove_dir_all(&path).await
+            } else if path.exists() {
+                tokio::fs::remove_file(&path).await
+            } else {
+                Ok(())
+            }
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        say!(
+            "{} Removed {} from the {} channel",
+            "✅".green(),
+            component.cyan(),
+            channel
+        );
+    }
+
+    record(&mut config, &channel, &components)?;
+    common::write_config(&config)
+}
@@ -0,0 +1,419 @@
+// src/commands/doctor.rs
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use colored::Colorize;
+use serde_json::json;
+use tokio::process::Command;
+
+use crate::commands::common::{get_cache_dir, get_config};
+use crate::commands::package::placement;
+use crate::commands::self_update::uninstall::{strip_path_lines, SHELL_RCS};
+use crate::manifest::{HoonPackage, InstallMode, PackageMeta};
+use crate::resolver::registry::registry_sources;
+use crate::{credentials, network, output};
+
+/// How long a registry has to answer before it counts as unreachable
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Failed,
+    Skipped,
+}
+
+impl Status {
+    fn name(self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Warning => "warning",
+            Status::Failed => "failed",
+            Status::Skipped => "skipped",
+        }
+    }
+}
+
+/// The outcome of one check, with what to do about it if it did not pass
+#[derive(Debug)]
+struct Check {
+    name: String,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: Status, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Check that nockup's environment works, printing how to fix each problem found
+///
+/// Covers git, the hoon and hoonc on PATH, ~/.nockup/bin being on PATH, ~/.nockup itself,
+/// symlinks in the current directory, and reaching the package registries. Fails if any check
+/// does; warnings are only reported.
+pub async fn run(offline: bool) -> Result<()> {
+    let cache_dir = get_cache_dir()?;
+    let bin_dir = cache_dir.join("bin");
+    let path = std::env::var_os("PATH").unwrap_or_default();
+
+    say!("{} Checking the nockup environment...", "🩺".cyan());
+    let mut checks = vec![check_git().await];
+    for binary in ["hoon", "hoonc"] {
+        checks.push(check_binary(binary, &bin_dir).await);
+    }
+    checks.push(check_path(&path, &bin_dir));
+    checks.push(check_cache(&cache_dir));
+    checks.push(check_symlinks(&std::env::current_dir()?));
+    checks.extend(check_registries(offline).await);
+
+    for check in &checks {
+        let icon = match check.status {
+            Status::Ok => "✓".green(),
+            Status::Warning => "⚠".yellow(),
+            Status::Failed => "✗".red(),
+            Status::Skipped => "-".dimmed(),
+        };
+        say!("  {} {}: {}", icon, check.name.bold(), check.detail);
+        if let Some(fix) = &check.fix {
+            say!("      {} {}", "→".cyan(), fix);
+        }
+        output::emit(
+            "check",
+            json!({
+                "name": check.name,
+                "status": check.status.name(),
+                "detail": check.detail,
+                "fix": check.fix,
+            }),
+        );
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let (failed, warnings) = (count(Status::Failed), count(Status::Warning));
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} checks failed; see the fixes above",
+            failed,
+            checks.len()
+        );
+    }
+    if warnings > 0 {
+        say!(
+            "{} No problems that stop nockup, but {} warning{}",
+            "✓".green(),
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        );
+    } else {
+        say!("{} Everything looks good", "✅".green());
+    }
+    Ok(())
+}
+
+async fn check_git() -> Check {
+    match version_of(Path::new("git")).await {
+        Some(version) => Check::new("git", Status::Ok, version),
+        None => Check::new("git", Status::Failed, "not found on PATH")
+            .with_fix("Install git; nockup fetches packages and templates with it"),
+    }
+}
+
+/// Whether `binary` is on PATH, which one it is, and what version it reports
+async fn check_binary(binary: &str, bin_dir: &Path) -> Check {
+    let installed = bin_dir.join(binary);
+    let found = match which::which(binary) {
+        Ok(found) => found,
+        Err(_) => {
+            // choo is what hoonc used to be called
+            if binary == "hoonc" {
+                if let Ok(choo) = which::which("choo") {
+                    return Check::new(
+                        binary,
+                        Status::Warning,
+                        format!(
+                            "not found, but choo, its former name, is at {}",
+                            choo.display()
+                        ),
+                    )
+                    .with_fix("Run `nockup update` to install hoonc");
+                }
+            }
+            let check = Check::new(binary, Status::Failed, "not found on PATH");
+            return if installed.exists() {
+                check.with_fix(format!(
+                    "It is installed in {}, which is not on PATH; see the PATH check",
+                    bin_dir.display()
+                ))
+            } else {
+                check.with_fix(format!("Run `nockup update` to install {}", binary))
+            };
+        }
+    };
+
+    let version = version_of(&found)
+        .await
+        .unwrap_or_else(|| "reports no version".to_string());
+    let detail = format!("{} ({})", version, found.display());
+    if installed.exists() && !same_path(&found, &installed) {
+        return Check::new(binary, Status::Warning, detail).with_fix(format!(
+            "{} comes before nockup's {} on PATH; remove it or put {} first",
+            found.display(),
+            installed.display(),
+            bin_dir.display()
+        ));
+    }
+    Check::new(binary, Status::Ok, detail)
+}
+
+/// The first line `program --version` prints, if it runs
+async fn version_of(program: &Path) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().await.ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?.trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Whether ~/.nockup/bin is on `path`, or the shell startup files would put it there
+fn check_path(path: &OsStr, bin_dir: &Path) -> Check {
+    if on_path(path, bin_dir) {
+        return Check::new(
+            "PATH",
+            Status::Ok,
+            format!("includes {}", bin_dir.display()),
+        );
+    }
+    let edited: Vec<PathBuf> = dirs::home_dir()
+        .map(|home| {
+            SHELL_RCS
+                .iter()
+                .map(|rc| home.join(rc))
+                .filter(|rc| {
+                    std::fs::read_to_string(rc)
+                        .is_ok_and(|content| strip_path_lines(&content, bin_dir).is_some())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let check = Check::new(
+        "PATH",
+        Status::Failed,
+        format!("does not include {}", bin_dir.display()),
+    );
+    match edited.first() {
+        Some(rc) => check.with_fix(format!(
+            "{} adds it; open a new shell or run `source {}`",
+            rc.display(),
+            rc.display()
+        )),
+        None => check.with_fix(format!(
+            "Add `export PATH=\"{}:$PATH\"` to your shell's startup file",
+            bin_dir.display()
+        )),
+    }
+}
+
+fn on_path(path: &OsStr, dir: &Path) -> bool {
+    std::env::split_paths(path).any(|entry| same_path(&entry, dir))
+}
+
+/// Whether ~/.nockup exists, is writable, and has a config.toml that parses
+fn check_cache(cache_dir: &Path) -> Check {
+    let name = "cache";
+    if !cache_dir.is_dir() {
+        return Check::new(
+            name,
+            Status::Failed,
+            format!("{} does not exist", cache_dir.display()),
+        )
+        .with_fix("Run `nockup update` to set it up");
+    }
+    let probe = cache_dir.join(format!(".doctor-{}", std::process::id()));
+    if let Err(e) = std::fs::write(&probe, b"") {
+        return Check::new(
+            name,
+            Status::Failed,
+            format!("{} is not writable: {}", cache_dir.display(), e),
+        )
+        .with_fix(format!(
+            "Make {} writable by your user",
+            cache_dir.display()
+        ));
+    }
+    let _ = std::fs::remove_file(&probe);
+    if let Err(e) = get_config() {
+        return Check::new(name, Status::Failed, format!("{:#}", e)).with_fix(format!(
+            "Fix {}, or remove it and run `nockup update` to write a new one",
+            cache_dir.join("config.toml").display()
+        ));
+    }
+    Check::new(
+        name,
+        Status::Ok,
+        format!("{} is writable", cache_dir.display()),
+    )
+}
+
+/// Whether symlinks can be made in `dir`, as `nockup package install` does by default
+fn check_symlinks(dir: &Path) -> Check {
+    let name = "symlinks";
+    let works = symlinks_work(dir);
+    let mode = placement::install_mode(&PackageMeta::default()).unwrap_or(InstallMode::Symlink);
+    match (works, mode) {
+        (Ok(()), _) => Check::new(name, Status::Ok, format!("supported in {}", dir.display())),
+        (Err(e), InstallMode::Symlink) => Check::new(
+            name,
+            Status::Failed,
+            format!("cannot be made in {}: {}", dir.display(), e),
+        )
+        .with_fix("Set install-mode = \"copy\" in ~/.nockup/config.toml or in nockapp.toml"),
+        (Err(_), _) => Check::new(
+            name,
+            Status::Ok,
+            format!(
+                "not supported in {}, but install-mode does not use them",
+                dir.display()
+            ),
+        ),
+    }
+}
+
+fn symlinks_work(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".nockup-doctor-{}", std::process::id()));
+    std::fs::create_dir(&probe)?;
+    let result = (|| {
+        std::fs::write(probe.join("target"), b"nockup")?;
+        placement::symlink(Path::new("target"), &probe.join("link"))?;
+        match std::fs::read(probe.join("link"))? == b"nockup" {
+            true => Ok(()),
+            false => Err(std::io::Error::other(
+                "the link does not lead to its target",
+            )),
+        }
+    })();
+    let _ = std::fs::remove_dir_all(&probe);
+    result
+}
+
+/// Whether each package registry answers, with the token saved for it
+async fn check_registries(offline: bool) -> Vec<Check> {
+    if offline {
+        return vec![Check::new("registries", Status::Skipped, "not checked with --offline")];
+    }
+    let manifest = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| HoonPackage::load(&cwd.join("nockapp.toml")).ok().flatten());
+    let sources = match registry_sources(manifest.as_ref()) {
+        Ok(sources) => sources,
+        Err(e) => {
+            return vec![Check::new("registries", Status::Failed, format!("{:#}", e))
+                .with_fix("Fix the [registries] of ~/.nockup/config.toml or nockapp.toml")];
+        }
+    };
+    let client = match network::client() {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![Check::new("registries", Status::Failed, format!("{:#}", e))
+                .with_fix("Fix the [network] proxy in ~/.nockup/config.toml")];
+        }
+    };
+
+    let mut checks = Vec::new();
+    for source in sources {
+        let name = format!("registry {}", source.name);
+        if !source.url.starts_with("https://") && !source.url.starts_with("http://") {
+            let path = source.url.strip_prefix("file://").unwrap_or(&source.url);
+            checks.push(if Path::new(path).is_file() {
+                Check::new(name, Status::Ok, format!("{} exists", path))
+            } else {
+                Check::new(name, Status::Failed, format!("{} does not exist", path))
+                    .with_fix("Point the registry at an existing file or an http(s) URL")
+            });
+            continue;
+        }
+
+        let mut request = client.get(&source.url).timeout(REGISTRY_TIMEOUT);
+        if let Ok(Some(token)) = credentials::token_for(&source.url) {
+            request = request.bearer_auth(token);
+        }
+        checks.push(match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                Check::new(name, Status::Ok, format!("{} is reachable", source.url))
+            }
+            Ok(response) if matches!(response.status().as_u16(), 401 | 403) => Check::new(
+                name,
+                Status::Failed,
+                format!("{} refused access ({})", source.url, response.status()),
+            )
+            .with_fix(format!(
+                "Run `nockup login {}` with a valid token",
+                source.name
+            )),
+            Ok(response) => Check::new(
+                name,
+                Status::Failed,
+                format!("{} answered {}", source.url, response.status()),
+            )
+            .with_fix("Check the registry's URL in ~/.nockup/config.toml or nockapp.toml"),
+            Err(e) => Check::new(
+                name,
+                Status::Failed,
+                format!("{} is unreachable: {}", source.url, e),
+            )
+            .with_fix(
+                "Check your connection and the [network] proxy in ~/.nockup/config.toml, or use \
+                --offline to work from the cache",
+            ),
+        });
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let bin_dir = tmp.path().join(".nockup/bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+
+        let path = std::env::join_paths([Path::new("/usr/bin"), &bin_dir]).unwrap();
+        assert!(on_path(&path, &bin_dir));
+        assert_eq!(check_path(&path, &bin_dir).status, Status::Ok);
+        let path = std::env::join_paths([Path::new("/usr/bin")]).unwrap();
+        assert!(!on_path(&path, &bin_dir));
+        let check = check_path(&path, &bin_dir);
+        assert_eq!(check.status, Status::Failed);
+        assert!(check.fix.is_some());
+
+        assert_eq!(check_symlinks(tmp.path()).status, Status::Ok);
+        assert!(std::fs::read_dir(tmp.path()).unwrap().count() == 1);
+
+        let missing = tmp.path().join("missing");
+        assert_eq!(check_cache(&missing).status, Status::Failed);
+    }
+}
@@ -6,6 +6,7 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 use handlebars::Handlebars;
 
+use crate::commands::common::get_cache_dir;
 use crate::lib_manager::{process_libraries, ProjectManifest};
 
 pub async fn run(project_name: String) -> Result<()> {
@@ -13,16 +14,16 @@ pub async fn run(project_name: String) -> Result<()> {
     let manifest = load_project_config(&project_name)?;
     let project_name = &manifest.project.project_name;
 
-    println!(
+    say!(
         "Initializing new NockApp project '{}'...",
         project_name.green()
     );
 
     let target_dir = Path::new(project_name);
     // Use cache dir ~/.nockup/templates/{{manifest.template}}
-    let template_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?
-        .join(format!(".nockup/templates/{}", manifest.project.template));
+    let template_dir = get_cache_dir()?
+        .join("templates")
+        .join(&manifest.project.template);
 
     // Check if target directory already exists
     if target_dir.exists() {
@@ -50,14 +51,14 @@ pub async fn run(project_name: String) -> Result<()> {
         .await
         .context("Failed to process library dependencies")?;
 
-    println!(
+    say!(
         "{} New project created in {}/",
         "✓".green(),
         format!("./{}/", project_name).cyan()
     );
-    println!("To get started:");
-    println!("  nockup project build {}", project_name.cyan());
-    println!("  nockup project run {}", project_name.cyan());
+    say!("To get started:");
+    say!("  nockup project build {}", project_name.cyan());
+    say!("  nockup project run {}", project_name.cyan());
 
     Ok(())
 }
@@ -75,12 +76,12 @@ fn load_project_config(project_name: &str) -> Result<ProjectManifest> {
     let config_content = fs::read_to_string(config_path)
         .with_context(|| format!("Failed to read {}.toml", project_name))?;
 
-    println!(
+    say!(
         "{} Loaded project configuration from '{}'",
         "✓".green(),
         config_filename.cyan()
     );
-    println!("Config content:\n{}", config_content);
+    say!("Config content:\n{}", config_content);
     toml::from_str(&config_content)
         .with_context(|| format!("Failed to parse {}.toml", project_name))
 }
@@ -184,7 +185,7 @@ fn copy_dir_recursive(
 
             // Show relative path from project root for cleaner output
             let relative_path = dest_path.strip_prefix(project_root).unwrap_or(&dest_path);
-            println!("  {} {}", "create".green(), relative_path.display());
+            say!("  {} {}", "create".green(), relative_path.display());
         }
     }
 
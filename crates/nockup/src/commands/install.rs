@@ -7,11 +7,11 @@ use colored::Colorize;
 
 use super::common;
 
-pub async fn run() -> Result<()> {
+pub async fn run(verify: bool) -> Result<()> {
     let cache_dir = common::get_cache_dir()?;
 
-    println!("{} Setting up nockup cache directory...", "🚀".green());
-    println!(
+    say!("{} Setting up nockup cache directory...", "🚀".green());
+    say!(
         "{} Cache location: {}",
         "📁".blue(),
         cache_dir.display().to_string().cyan()
@@ -29,7 +29,7 @@ pub async fn run() -> Result<()> {
     // Set default channel to stable and this architecture
     let config_path = cache_dir.join("config.toml");
     let mut config = common::get_or_create_config()?;
-    println!("📝 Config installed at: {}", config_path.display());
+    say!("📝 Config installed at: {}", config_path.display());
     config["channel"] = toml::Value::String("stable".into());
     config["architecture"] = toml::Value::String(common::get_target_identifier());
     fs::write(config_path, toml::to_string(&config)?).context("Failed to write config file")?;
@@ -38,13 +38,13 @@ pub async fn run() -> Result<()> {
     common::write_commit_details(&cache_dir).await?;
 
     // Download binaries for current channel
-    common::download_binaries(&config).await?;
+    common::download_binaries(&config, verify).await?;
 
     // Prepend cache bin directory to PATH
     prepend_path_to_shell_rc(&cache_dir.join("bin")).await?;
 
-    println!("{} Setup complete!", "✅".green());
-    println!(
+    say!("{} Setup complete!", "✅".green());
+    say!(
         "{} Templates are now available in: {}",
         "📂".blue(),
         cache_dir.join("templates").display().to_string().cyan()
@@ -54,7 +54,7 @@ pub async fn run() -> Result<()> {
 }
 
 async fn create_cache_structure(cache_dir: &Path) -> Result<()> {
-    println!("{} Creating cache directory structure...", "📁".green());
+    say!("{} Creating cache directory structure...", "📁".green());
 
     fs::create_dir_all(cache_dir)?;
 
@@ -64,7 +64,7 @@ async fn create_cache_structure(cache_dir: &Path) -> Result<()> {
     let templates_dir = cache_dir.join("templates");
     fs::create_dir_all(&templates_dir)?;
 
-    println!("{} Created directory structure", "✓".green());
+    say!("{} Created directory structure", "✓".green());
     Ok(())
 }
 
@@ -89,11 +89,11 @@ async fn prepend_path_to_shell_rc(bin_dir: &Path) -> Result<()> {
     }
 
     let path_entry = format!("export PATH=\"{}:$PATH\"", bin_dir.display());
-    println!("{}", path_entry);
+    say!("{}", path_entry);
     if !contents.contains(&path_entry) {
         let new_contents = format!("{}\n{}", contents, path_entry);
         fs::write(&rc_file, new_contents)?;
-        println!("{} Updated {}", "📝".green(), rc_file.display());
+        say!("{} Updated {}", "📝".green(), rc_file.display());
     }
 
     Ok(())
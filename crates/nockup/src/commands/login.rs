@@ -0,0 +1,101 @@
+// src/commands/login.rs
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::credentials::{self, Credentials};
+use crate::manifest::HoonPackage;
+use crate::output;
+use crate::resolver::registry;
+
+/// Save a token for `registry`, read from standard input
+pub fn login(registry: &str, keychain: bool) -> Result<()> {
+    let host = registry_host(registry)?;
+
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Token for {}: ", host.cyan());
+        io::stderr().flush()?;
+    }
+    let mut token = String::new();
+    stdin
+        .lock()
+        .read_line(&mut token)
+        .context("Failed to read token")?;
+    let token = token.trim();
+    if token.is_empty() {
+        anyhow::bail!("No token given for {}", host);
+    }
+
+    let mut credentials = Credentials::load()?;
+    credentials.set(&host, token, keychain)?;
+    credentials.save()?;
+
+    say!(
+        "{} Logged in to {}{}",
+        "🔑".green(),
+        host.cyan(),
+        if keychain {
+            " (token in the OS keychain)"
+        } else {
+            ""
+        }
+    );
+    output::emit(
+        "login",
+        serde_json::json!({ "host": host, "keychain": keychain }),
+    );
+    Ok(())
+}
+
+/// Forget the token saved for `registry`
+pub fn logout(registry: &str) -> Result<()> {
+    let host = registry_host(registry)?;
+
+    let mut credentials = Credentials::load()?;
+    let removed = credentials.remove(&host)?;
+    credentials.save()?;
+
+    if removed {
+        say!("{} Logged out of {}", "🔒".green(), host.cyan());
+    } else {
+        say!("{} No token was saved for {}", "ℹ️".blue(), host.cyan());
+    }
+    output::emit(
+        "logout",
+        serde_json::json!({ "host": host, "removed": removed }),
+    );
+    Ok(())
+}
+
+/// The host a registry's token is kept under, given its URL or its name
+///
+/// Names are looked up in ~/.nockup/config.toml and, inside a project, its nockapp.toml.
+fn registry_host(registry: &str) -> Result<String> {
+    if let Some(host) = credentials::host_of(registry) {
+        return Ok(host);
+    }
+    let manifest = HoonPackage::load(&env::current_dir()?.join("nockapp.toml"))?;
+    let sources = registry::registry_sources(manifest.as_ref())?;
+    let source = sources
+        .iter()
+        .find(|source| source.name == registry)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown registry '{}': expected a URL or one of {}",
+                registry,
+                sources
+                    .iter()
+                    .map(|source| source.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+    credentials::host_of(&source.url).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Registry '{}' is the local file {} and needs no token", registry, source.url
+        )
+    })
+}
@@ -2,8 +2,13 @@ pub mod build;
 pub mod cache;
 pub mod channel;
 pub mod common;
+pub mod component;
+pub mod doctor;
 pub mod init;
+pub mod login;
 pub mod package;
 pub mod run;
+pub mod self_update;
+pub mod template;
 pub mod test_phase1;
 pub mod update;
@@ -1,29 +1,81 @@
 pub mod add;
+pub mod audit;
+pub mod check;
 pub mod init;
 pub mod install;
+pub mod licenses;
 pub mod list;
+pub mod outdated;
+pub mod placement;
+pub mod proxy;
+pub mod publish;
 pub mod purge;
 pub mod remove;
+pub mod sbom;
+pub mod search;
+pub mod tree;
 pub mod update;
+pub mod vendor;
 
 use anyhow::Result;
 
 use crate::cli::PackageCommand;
 
-pub async fn run(cmd: PackageCommand) -> Result<()> {
+pub async fn run(cmd: PackageCommand, offline: bool) -> Result<()> {
     match cmd {
         PackageCommand::Init { name } => init::run(name).await,
-        PackageCommand::Add { name, version } => add::run(name, version).await,
+        PackageCommand::Add {
+            name,
+            version,
+            source,
+        } => add::run(name, version, source, offline).await,
         PackageCommand::Remove { name } => remove::run(name).await,
         PackageCommand::List => list::run().await,
-        PackageCommand::Install => install::run().await,
+        PackageCommand::Search { query } => search::run(query, offline).await,
+        PackageCommand::Install {
+            full_clone,
+            locked,
+            frozen,
+        } => {
+            let lock_mode = if frozen {
+                install::LockMode::Frozen
+            } else if locked {
+                install::LockMode::Locked
+            } else {
+                install::LockMode::Update
+            };
+            install::run(offline, full_clone, lock_mode).await
+        }
+        PackageCommand::Update if offline => {
+            anyhow::bail!(
+                "`nockup package update` fetches new versions and cannot run with --offline"
+            )
+        }
         PackageCommand::Update => update::run().await,
+        PackageCommand::Outdated if offline => {
+            anyhow::bail!("`nockup package outdated` checks upstream and cannot run with --offline")
+        }
+        PackageCommand::Outdated => outdated::run().await,
+        PackageCommand::Vendor => vendor::run(offline).await,
+        PackageCommand::Audit => audit::run(offline).await,
+        PackageCommand::Check => check::run().await,
+        PackageCommand::Licenses { deny } => licenses::run(deny).await,
+        PackageCommand::Sbom { format, output } => sbom::run(format, output).await,
+        PackageCommand::Tree { duplicates } => tree::run(duplicates, offline).await,
         PackageCommand::Purge { dry_run } => purge::purge(dry_run).await,
+        PackageCommand::Publish { .. } if offline => {
+            anyhow::bail!("`nockup package publish` cannot run with --offline")
+        }
+        PackageCommand::Publish {
+            registry,
+            git_url,
+            dry_run,
+        } => publish::run(registry, git_url, dry_run).await,
         PackageCommand::Grab { .. } => {
             anyhow::bail!("`nockup package grab` is deprecated – use `add`")
         }
-        PackageCommand::GenerateProxy { .. } => {
-            anyhow::bail!("`generate-proxy` coming soon")
+        PackageCommand::GenerateProxy { url, path, name } => {
+            proxy::run(url, path, name, offline).await
         }
     }
 }
@@ -1,13 +1,25 @@
 // src/commands/package/add.rs
 use std::env;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
 
-use crate::manifest::HoonPackage;
+use crate::cli::GitSource;
+use crate::manifest::{DependencySpec, HoonPackage, NockAppLock};
+use crate::resolver::local::local_packages;
+use crate::resolver::registry::registry_sources;
+use crate::resolver::{Resolver, VersionSpec};
 
 /// Add a dependency to nockapp.toml
-pub async fn run(package_name: String, version: Option<String>) -> Result<()> {
+///
+/// The project is resolved with the dependency first, so one that cannot be fetched or
+/// conflicts with the others is not written.
+pub async fn run(
+    package_name: String,
+    version: Option<String>,
+    source: GitSource,
+    offline: bool,
+) -> Result<()> {
     let cwd = env::current_dir()?;
     let manifest_path = cwd.join("nockapp.toml");
 
@@ -15,7 +27,7 @@ pub async fn run(package_name: String, version: Option<String>) -> Result<()> {
         anyhow::bail!("No nockapp.toml found in current directory");
     }
 
-    println!(
+    say!(
         "{} Adding dependency {}...",
         "📦".cyan(),
         package_name.yellow()
@@ -27,24 +39,7 @@ pub async fn run(package_name: String, version: Option<String>) -> Result<()> {
         None => anyhow::bail!("Failed to load nockapp.toml"),
     };
 
-    // Determine the version spec to use
-    let version_spec = if let Some(v) = version {
-        v
-    } else {
-        // For registry packages, we could fetch latest version
-        // For now, prompt user or use a sensible default
-        println!(
-            "  {} No version specified, using latest available",
-            "→".cyan()
-        );
-        // For kelvin packages, we might want to determine latest kelvin
-        // For now, let's default to requiring explicit version
-        anyhow::bail!(
-            "Please specify a version for '{}'. \
-            Examples: @k409, ^1.2.3, @tag:v1.0.0, @branch:main",
-            package_name
-        );
-    };
+    let spec = dependency_spec(&package_name, version, source)?;
 
     // Initialize dependencies map if it doesn't exist
     let deps = manifest
@@ -61,22 +56,139 @@ pub async fn run(package_name: String, version: Option<String>) -> Result<()> {
         );
     }
 
-    // Add the dependency
-    use crate::manifest::DependencySpec;
-    deps.insert(package_name.clone(), DependencySpec::Simple(version_spec));
+    deps.insert(package_name.clone(), spec);
+
+    // Resolve the project with the dependency before saving it
+    say!("  {} Resolving dependencies...", "→".cyan());
+    let lock_path = cwd.join(&manifest.package.name).join("nockapp.lock");
+    let graph = Resolver::new(offline)?
+        .with_registries(registry_sources(Some(&manifest))?)
+        .with_patches(manifest.patch.clone().unwrap_or_default(), &cwd)
+        .with_local_packages(local_packages(&manifest, &cwd)?)
+        .with_lock(&NockAppLock::load(&lock_path)?)
+        .resolve(&manifest)
+        .await
+        .with_context(|| format!("'{}' was not added to nockapp.toml", package_name))?;
+    if let Some(package) = graph.packages.get(&package_name) {
+        say!(
+            "  {} {} resolves to {} ({})",
+            "→".cyan(),
+            package_name.yellow(),
+            package.version_spec.to_canonical_string(),
+            &package.commit[..package.commit.len().min(8)]
+        );
+    }
 
-    // Save the manifest
     manifest.save(&manifest_path)?;
 
-    println!(
+    say!(
         "{} Added {} to nockapp.toml",
         "✓".green(),
         package_name.yellow()
     );
-    println!(
+    say!(
         "  Run {} to install the dependency",
         "nockup package install".cyan()
     );
 
     Ok(())
 }
+
+/// The manifest entry for a dependency given on the command line
+///
+/// A registry package is just its version. With `--git`, the entry is a full spec whose version
+/// comes from `--version`, `--branch`, `--tag` or `--commit`.
+fn dependency_spec(
+    name: &str,
+    version: Option<String>,
+    source: GitSource,
+) -> Result<DependencySpec> {
+    let Some(git) = source.git else {
+        return match version {
+            Some(version) => Ok(DependencySpec::Simple(version)),
+            None => anyhow::bail!(
+                "Please specify a version for '{}'. \
+                Examples: @k409, ^1.2.3, @tag:v1.0.0, @branch:main",
+                name
+            ),
+        };
+    };
+
+    let version_spec = if let Some(branch) = source.branch {
+        VersionSpec::Branch(branch)
+    } else if let Some(tag) = source.tag {
+        VersionSpec::Tag(tag)
+    } else if let Some(commit) = source.commit {
+        VersionSpec::Commit(commit)
+    } else if let Some(version) = version {
+        VersionSpec::parse(&version)?
+    } else {
+        anyhow::bail!(
+            "Please specify a version for '{}' with --version, --branch, --tag or --commit", name
+        );
+    };
+
+    let mut spec = version_spec.to_dependency_spec(Some(git));
+    if let DependencySpec::Full { path, files, .. } = &mut spec {
+        *path = source.path;
+        if !source.files.is_empty() {
+            // Files are named without their extension, as in nockapp.toml
+            *files = Some(
+                source
+                    .files
+                    .iter()
+                    .map(|file| file.trim_end_matches(".hoon").to_string())
+                    .collect(),
+            );
+        }
+    }
+    Ok(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_spec() {
+        assert!(matches!(
+            dependency_spec("seq", Some("^1.2.0".to_string()), GitSource::default()).unwrap(),
+            DependencySpec::Simple(v) if v == "^1.2.0"
+        ));
+        assert!(dependency_spec("seq", None, GitSource::default()).is_err());
+
+        let source = GitSource {
+            git: Some("https://github.com/example/seq".to_string()),
+            tag: Some("v1.2.0".to_string()),
+            path: Some("desk/lib".to_string()),
+            files: vec!["seq".to_string(), "seq/tools.hoon".to_string()],
+            ..GitSource::default()
+        };
+        match dependency_spec("seq", None, source).unwrap() {
+            DependencySpec::Full {
+                git,
+                tag,
+                path,
+                files,
+                version,
+                ..
+            } => {
+                assert_eq!(git.as_deref(), Some("https://github.com/example/seq"));
+                assert_eq!(tag.as_deref(), Some("v1.2.0"));
+                assert_eq!(path.as_deref(), Some("desk/lib"));
+                assert_eq!(
+                    files,
+                    Some(vec!["seq".to_string(), "seq/tools".to_string()])
+                );
+                assert_eq!(version, None);
+            }
+            other => panic!("expected a full spec, got {:?}", other),
+        }
+
+        let source = GitSource {
+            git: Some("https://github.com/example/seq".to_string()),
+            ..GitSource::default()
+        };
+        assert!(dependency_spec("seq", None, source).is_err());
+    }
+}
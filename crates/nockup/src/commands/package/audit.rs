@@ -0,0 +1,209 @@
+// src/commands/package/audit.rs
+use std::env;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::manifest::{HoonPackage, LockSource, LockedPackage, NockAppLock};
+use crate::resolver::registry::{self, Advisory, Yanked};
+
+/// A locked package that an advisory or yank applies to
+#[derive(Debug, PartialEq, Eq)]
+enum Finding<'a> {
+    Vulnerable(&'a LockedPackage, &'a Advisory),
+    Yanked(&'a LockedPackage, &'a Yanked),
+}
+
+/// Check the packages in nockapp.lock against the registries' advisories and yanked versions
+///
+/// Fails if any locked package has an advisory against it; yanked versions are only warned
+/// about.
+pub async fn run(offline: bool) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest = match HoonPackage::load(&cwd.join("nockapp.toml"))? {
+        Some(m) => m,
+        None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
+    };
+
+    let lock_path = cwd.join(&manifest.package.name).join("nockapp.lock");
+    if !lock_path.exists() {
+        anyhow::bail!("No nockapp.lock found; run `nockup package install` first");
+    }
+    let lock = NockAppLock::load(&lock_path)?;
+
+    say!(
+        "{} Auditing {} locked packages of {}...",
+        "🔍".cyan(),
+        lock.package.len(),
+        manifest.package.name.yellow()
+    );
+
+    let sources = registry::registry_sources(Some(&manifest))?;
+    let (advisories, yanked) = registry::audit_feed(&sources, offline).await?;
+    let findings = audit(&lock, &advisories, &yanked);
+
+    if findings.is_empty() {
+        say!(
+            "{} No advisories or yanked versions affect the locked packages",
+            "✓".green()
+        );
+        return Ok(());
+    }
+
+    let mut vulnerable = 0;
+    for finding in &findings {
+        say!();
+        match finding {
+            Finding::Vulnerable(pkg, advisory) => {
+                vulnerable += 1;
+                say!(
+                    "{} {} {} ({})",
+                    "✗".red(),
+                    pkg.name.red().bold(),
+                    pkg.version,
+                    short_commit(pkg)
+                );
+                say!("    {} {}", advisory.id.red(), advisory.title);
+                if let Some(url) = &advisory.url {
+                    say!("    {} {}", "details:".dimmed(), url);
+                }
+                print_upgrade(&pkg.name, advisory.patched.as_deref());
+            }
+            Finding::Yanked(pkg, yanked) => {
+                say!(
+                    "{} {} {} ({}) has been yanked",
+                    "⚠".yellow(),
+                    pkg.name.yellow().bold(),
+                    pkg.version,
+                    short_commit(pkg)
+                );
+                if let Some(reason) = &yanked.reason {
+                    say!("    {} {}", "reason:".dimmed(), reason);
+                }
+                print_upgrade(&pkg.name, yanked.upgrade.as_deref());
+            }
+        }
+    }
+
+    say!();
+    let yanked_count = findings.len() - vulnerable;
+    if vulnerable > 0 {
+        anyhow::bail!("{} vulnerable and {} yanked locked packages", vulnerable, yanked_count);
+    }
+    say!("{} {} yanked locked packages", "⚠".yellow(), yanked_count);
+
+    Ok(())
+}
+
+fn print_upgrade(name: &str, version: Option<&str>) {
+    match version {
+        Some(version) => say!(
+            "    {} {}",
+            "upgrade:".dimmed(),
+            format!("nockup package add {} --version {}", name, version).cyan()
+        ),
+        None => say!("    {} no fixed version is listed", "upgrade:".dimmed()),
+    }
+}
+
+fn short_commit(pkg: &LockedPackage) -> String {
+    match &pkg.source {
+        LockSource::Git { commit, .. } => commit.chars().take(12).collect(),
+        LockSource::Path { path } => path.clone(),
+    }
+}
+
+/// Advisories and yanks that apply to the locked packages, in lock order
+fn audit<'a>(
+    lock: &'a NockAppLock,
+    advisories: &'a [Advisory],
+    yanked: &'a [Yanked],
+) -> Vec<Finding<'a>> {
+    let mut findings = Vec::new();
+    for pkg in &lock.package {
+        // Local packages have no published versions to be advised against
+        let LockSource::Git { commit, .. } = &pkg.source else {
+            continue;
+        };
+        for advisory in advisories {
+            if advisory.affects(&pkg.name, &pkg.version, commit) {
+                findings.push(Finding::Vulnerable(pkg, advisory));
+            }
+        }
+        for yank in yanked {
+            if yank.affects(&pkg.name, &pkg.version, commit) {
+                findings.push(Finding::Yanked(pkg, yank));
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::registry::RegistryToml;
+
+    fn locked(name: &str, version: &str, commit: &str) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: LockSource::Git {
+                url: format!("https://github.com/{}", name),
+                commit: commit.to_string(),
+                path: None,
+            },
+            checksum: None,
+            license: None,
+            dependencies: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_audit() {
+        let feed: RegistryToml = toml::from_str(
+            r#"
+            [[advisory]]
+            id = "NOCK-2025-0001"
+            package = "sequent"
+            title = "Unchecked subtraction"
+            versions = ["<1.2.3"]
+            patched = "^1.2.3"
+
+            [[advisory]]
+            id = "NOCK-2025-0002"
+            package = "lagoon"
+            title = "Bad jet hint"
+            commits = ["cccccccc"]
+
+            [[yanked]]
+            package = "urbit/zuse"
+            versions = ["k410"]
+            reason = "Published by mistake"
+            upgrade = "k409"
+            "#,
+        )
+        .unwrap();
+
+        let lock = NockAppLock::new(vec![
+            locked("sequent", "tag:v1.2.0", "aaaaaaaaaaaaaaaa"),
+            locked("lagoon", "latest", "cccccccccccccccc"),
+            locked("urbit/zuse", "k410", "bbbbbbbbbbbbbbbb"),
+            locked("other", "tag:v1.0.0", "dddddddddddddddd"),
+        ]);
+
+        let findings = audit(&lock, &feed.advisory, &feed.yanked);
+        assert_eq!(
+            findings,
+            vec![
+                Finding::Vulnerable(&lock.package[0], &feed.advisory[0]),
+                Finding::Vulnerable(&lock.package[1], &feed.advisory[1]),
+                Finding::Yanked(&lock.package[2], &feed.yanked[0]),
+            ]
+        );
+
+        // A fixed version is not reported
+        let fixed = NockAppLock::new(vec![locked("sequent", "tag:v1.2.3", "eeeeeeeeeeeeeeee")]);
+        assert!(audit(&fixed, &feed.advisory, &feed.yanked).is_empty());
+    }
+}
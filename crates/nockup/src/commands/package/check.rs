@@ -0,0 +1,357 @@
+// src/commands/package/check.rs
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::json;
+use toml::{Table, Value};
+
+use super::install::package_dir_name;
+use super::placement;
+use super::vendor::VENDOR_DIR;
+use crate::manifest::{DependencySpec, HoonPackage, InstallMode, NockAppLock, NockAppManifest};
+use crate::output;
+use crate::resolver::{lock_mismatches, VersionSpec};
+
+/// Keys of a nockapp.toml; [[app]], [profile] and [hooks] reject unknown keys as they are parsed
+const PROJECT_KEYS: &[&str] = &[
+    "package", "dependencies", "registries", "patch", "workspace", "app", "profile", "hooks",
+    "template", "template_commit", "build",
+];
+
+/// Keys of a library's hoon.toml
+const LIBRARY_KEYS: &[&str] = &["package", "dependencies", "registries", "patch"];
+
+const PACKAGE_KEYS: &[&str] = &[
+    "name", "version", "description", "authors", "license", "template", "template_commit",
+    "default_bin", "install-mode", "prefer-local",
+];
+
+const DEPENDENCY_KEYS: &[&str] =
+    &["version", "git", "commit", "tag", "branch", "path", "files", "kelvin"];
+
+const REGISTRY_KEYS: &[&str] = &["url", "priority", "mirrors"];
+
+const PATCH_KEYS: &[&str] = &["git", "path", "commit", "tag", "branch"];
+
+const WORKSPACE_KEYS: &[&str] = &["members"];
+
+/// Validate the manifests in the current directory and, for a project, its lockfile and hoon/
+///
+/// Fails if any problem is found, after reporting every one.
+pub async fn run() -> Result<()> {
+    let cwd = env::current_dir()?;
+    let project_manifest = cwd.join("nockapp.toml");
+    let library_manifest = cwd.join("hoon.toml");
+    if !project_manifest.exists() && !library_manifest.exists() {
+        anyhow::bail!("No nockapp.toml or hoon.toml found in {}", cwd.display());
+    }
+
+    let mut problems = 0;
+    if library_manifest.exists() {
+        let (found, _) = check_manifest(&library_manifest, LIBRARY_KEYS)?;
+        problems += report("hoon.toml", &found);
+    }
+    if project_manifest.exists() {
+        let (found, manifest) = check_manifest(&project_manifest, PROJECT_KEYS)?;
+        problems += report("nockapp.toml", &found);
+
+        // Without a manifest that parses, there is nothing to check the project against
+        if let Some(manifest) = manifest {
+            let project_dir = cwd.join(&manifest.package.name);
+            let lock_path = project_dir.join("nockapp.lock");
+            if lock_path.exists() {
+                match NockAppLock::load(&lock_path) {
+                    Ok(lock) => {
+                        let dependencies = manifest.dependencies.clone().unwrap_or_default();
+                        problems += report("nockapp.lock", &check_lock(&dependencies, &lock));
+                        let mode = placement::install_mode(&manifest.package)?;
+                        problems += report("hoon/", &check_links(&project_dir, &lock, mode)?);
+                    }
+                    Err(e) => problems += report("nockapp.lock", &[format!("{:#}", e)]),
+                }
+            } else if manifest
+                .dependencies
+                .as_ref()
+                .is_some_and(|d| !d.is_empty())
+            {
+                problems += report(
+                    "nockapp.lock",
+                    &["missing; run `nockup package install` to create it".to_string()],
+                );
+            }
+        }
+    }
+
+    output::emit("summary", json!({ "problems": problems }));
+    if problems > 0 {
+        anyhow::bail!("Found {} problems", problems);
+    }
+    say!("{} No problems found", "✓".green());
+    Ok(())
+}
+
+/// Print the problems found in one file, returning how many there are
+fn report(file: &str, problems: &[String]) -> usize {
+    if problems.is_empty() {
+        say!("{} {}", "✓".green(), file);
+    } else {
+        say!("{} {}", "✗".red(), file.red().bold());
+        for problem in problems {
+            say!("    - {}", problem);
+            output::emit("problem", json!({ "file": file, "problem": problem }));
+        }
+    }
+    problems.len()
+}
+
+/// Problems with a manifest, and the manifest if it parses
+///
+/// Unknown keys are problems although nockup ignores them, as they are most often typos.
+fn check_manifest(path: &Path, top_keys: &[&str]) -> Result<(Vec<String>, Option<HoonPackage>)> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let table: Table = match toml::from_str(&content) {
+        Ok(table) => table,
+        Err(e) => return Ok((vec![format!("invalid TOML: {}", e.message())], None)),
+    };
+
+    let mut problems = unknown_keys(&table, top_keys);
+    let manifest = match toml::from_str::<HoonPackage>(&content) {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            problems.push(e.message().to_string());
+            None
+        }
+    };
+    // nockup project build reads the sections of a project that package commands don't
+    if top_keys == PROJECT_KEYS {
+        if let Err(e) = toml::from_str::<NockAppManifest>(&content) {
+            if manifest.is_some() {
+                problems.push(e.message().to_string());
+            }
+        }
+    }
+
+    for (name, spec) in manifest
+        .iter()
+        .flat_map(|m| m.dependencies.iter().flatten())
+    {
+        if let Err(e) = VersionSpec::from_dependency_spec(spec) {
+            problems.push(format!("dependency '{}': {:#}", name, e));
+        }
+    }
+    Ok((problems, manifest))
+}
+
+/// Keys of a manifest that no part of nockup reads, as dotted paths
+fn unknown_keys(manifest: &Table, top_keys: &[&str]) -> Vec<String> {
+    let mut unknown = unknown_in(manifest, "", top_keys);
+    if let Some(Value::Table(package)) = manifest.get("package") {
+        unknown.extend(unknown_in(package, "package.", PACKAGE_KEYS));
+    }
+    if let Some(Value::Table(workspace)) = manifest.get("workspace") {
+        unknown.extend(unknown_in(workspace, "workspace.", WORKSPACE_KEYS));
+    }
+    for (section, keys) in [
+        ("dependencies", DEPENDENCY_KEYS),
+        ("registries", REGISTRY_KEYS),
+        ("patch", PATCH_KEYS),
+    ] {
+        let Some(Value::Table(entries)) = manifest.get(section) else {
+            continue;
+        };
+        for (name, entry) in entries {
+            if let Value::Table(entry) = entry {
+                unknown.extend(unknown_in(
+                    entry,
+                    &format!("{}.\"{}\".", section, name),
+                    keys,
+                ));
+            }
+        }
+    }
+    unknown
+}
+
+fn unknown_in(table: &Table, prefix: &str, keys: &[&str]) -> Vec<String> {
+    table
+        .keys()
+        .filter(|key| !keys.contains(&key.as_str()))
+        .map(|key| format!("unknown key '{}{}'", prefix, key))
+        .collect()
+}
+
+fn check_lock(dependencies: &BTreeMap<String, DependencySpec>, lock: &NockAppLock) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+    for pkg in &lock.package {
+        if !seen.insert(&pkg.name) {
+            problems.push(format!("'{}' is locked more than once", pkg.name));
+        }
+        if let Err(e) = VersionSpec::parse(&pkg.version) {
+            problems.push(format!("'{}': {:#}", pkg.name, e));
+        }
+    }
+    // Unparseable versions were reported above; their dependencies can't be compared
+    if problems.is_empty() {
+        match lock_mismatches(dependencies, lock) {
+            Ok(mismatches) => problems.extend(mismatches),
+            Err(e) => problems.push(format!("{:#}", e)),
+        }
+    }
+    problems
+}
+
+/// Problems with the package files in a project's hoon/
+///
+/// Every locked package must be installed, every symlink must point at an installed file, and
+/// every copy or hard link must still match its package's file.
+fn check_links(project_dir: &Path, lock: &NockAppLock, mode: InstallMode) -> Result<Vec<String>> {
+    let hoon_dir = project_dir.join("hoon");
+    let packages_dir = hoon_dir.join("packages");
+    let vendor_dir = project_dir.join(VENDOR_DIR);
+    let mut problems = Vec::new();
+
+    for pkg in &lock.package {
+        let dir_name = package_dir_name(&pkg.name, &pkg.version);
+        if !packages_dir.join(&dir_name).exists() && !vendor_dir.join(&dir_name).exists() {
+            problems.push(format!(
+                "'{}' is locked but not installed in hoon/packages/{}",
+                pkg.name, dir_name
+            ));
+        }
+    }
+
+    if hoon_dir.exists() {
+        let mut links = Vec::new();
+        find_symlinks(&hoon_dir, &packages_dir, &mut links)?;
+        for link in links {
+            let shown = link.strip_prefix(project_dir).unwrap_or(&link).display();
+            match fs::canonicalize(&link) {
+                Err(_) => problems.push(format!("{} is a broken symlink", shown)),
+                Ok(target) => {
+                    let installed = [&packages_dir, &vendor_dir]
+                        .iter()
+                        .filter_map(|dir| fs::canonicalize(dir).ok())
+                        .any(|dir| target.starts_with(dir));
+                    if !installed {
+                        problems.push(format!(
+                            "{} points outside hoon/packages/, at {}",
+                            shown,
+                            target.display()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    for stale in placement::stale_files(&hoon_dir, mode)? {
+        problems.push(format!(
+            "{} is out of date; run `nockup package install` to refresh it",
+            stale
+        ));
+    }
+    Ok(problems)
+}
+
+/// Symlinks under `dir`, except within `skip`
+fn find_symlinks(dir: &Path, skip: &Path, links: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path == skip {
+            continue;
+        }
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_symlink() {
+            links.push(path);
+        } else if file_type.is_dir() {
+            find_symlinks(&path, skip, links)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_manifest() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = tmp.path().join("nockapp.toml");
+        fs::write(
+            &path,
+            r#"
+            [package]
+            name = "arcade"
+            licence = "MIT"
+
+            [dependencies]
+            "urbit/zuse" = "k409"
+            sequent = { gti = "https://github.com/example/sequent", tag = "v1.0.0" }
+            broken = "not a version"
+            "#,
+        )
+        .unwrap();
+
+        let (problems, manifest) = check_manifest(&path, PROJECT_KEYS).unwrap();
+        assert!(manifest.is_some());
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert_eq!(problems[0], "unknown key 'package.licence'");
+        assert_eq!(problems[1], "unknown key 'dependencies.\"sequent\".gti'");
+        assert!(problems[2].starts_with("dependency 'broken': Invalid version spec"));
+
+        // A library's hoon.toml has no build sections
+        fs::write(
+            &path, "[package]\nname = \"lib\"\n\n[hooks]\npre-build = []\n",
+        )
+        .unwrap();
+        let (problems, _) = check_manifest(&path, LIBRARY_KEYS).unwrap();
+        assert_eq!(problems, ["unknown key 'hooks'"]);
+
+        fs::write(&path, "[package]\nversion = \"1.0.0\"\n").unwrap();
+        let (problems, manifest) = check_manifest(&path, PROJECT_KEYS).unwrap();
+        assert!(manifest.is_none());
+        assert_eq!(problems, ["missing field `name`"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_links() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let project_dir = tmp.path();
+        let package = project_dir.join("hoon/packages/sequent--k409/lib");
+        fs::create_dir_all(&package).unwrap();
+        fs::create_dir_all(project_dir.join("hoon/lib")).unwrap();
+        fs::write(package.join("seq.hoon"), "|%  --").unwrap();
+        let link = |target: &str, name: &str| {
+            std::os::unix::fs::symlink(target, project_dir.join("hoon/lib").join(name)).unwrap()
+        };
+        link("../packages/sequent--k409/lib/seq.hoon", "seq.hoon");
+
+        let lock: NockAppLock = toml::from_str(
+            r#"
+            [[package]]
+            name = "sequent"
+            version = "k409"
+            source = { type = "git", url = "https://github.com/example/sequent", commit = "abc" }
+            "#,
+        )
+        .unwrap();
+        let mode = InstallMode::Symlink;
+        assert!(check_links(project_dir, &lock, mode).unwrap().is_empty());
+
+        link("../packages/sequent--k409/lib/gone.hoon", "gone.hoon");
+        fs::write(project_dir.join("host.hoon"), "|%  --").unwrap();
+        link("../../host.hoon", "host.hoon");
+        let mut problems = check_links(project_dir, &lock, mode).unwrap();
+        problems.sort();
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert_eq!(problems[0], "hoon/lib/gone.hoon is a broken symlink");
+        assert!(problems[1].starts_with("hoon/lib/host.hoon points outside hoon/packages/"));
+    }
+}
@@ -35,8 +35,14 @@ pub async fn run(name: Option<String>) -> Result<()> {
             license: None,
             template: None,
             template_commit: None,
+            default_bin: None,
+            install_mode: None,
+            prefer_local: None,
         },
         dependencies: Some(Default::default()),
+        registries: None,
+        patch: None,
+        workspace: None,
     };
 
     pkg.save(&manifest_path)?;
@@ -49,8 +55,8 @@ pub async fn run(name: Option<String>) -> Result<()> {
     )
     .await?;
 
-    println!("Created library package: {}", dir_name);
-    println!("   {}", manifest_path.display());
+    say!("Created library package: {}", dir_name);
+    say!("   {}", manifest_path.display());
 
     Ok(())
 }
@@ -4,12 +4,33 @@ use std::{env, fs};
 
 use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
+use serde_json::json;
+
+use super::placement::{self, Placer};
+use super::vendor::{self, VENDOR_MANIFEST};
+use crate::cache::{content_hash, PackageCache};
+use crate::manifest::{HoonPackage, LockSource, LockedPackage, NockAppLock, VendorManifest};
+use crate::output;
+use crate::resolver::local::local_packages;
+use crate::resolver::registry::registry_sources;
+use crate::resolver::{ResolvedGraph, ResolvedPackage, Resolver};
+
+/// How an install treats nockapp.lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Resolve nockapp.toml and write the result to nockapp.lock
+    Update,
+    /// Resolve nockapp.toml, but fail if the result differs from nockapp.lock
+    Locked,
+    /// Install the commits in nockapp.lock without resolving anything
+    Frozen,
+}
 
-use crate::cache::PackageCache;
-use crate::manifest::{HoonPackage, LockSource, LockedPackage, NockAppLock};
-use crate::resolver::Resolver;
-
-pub async fn run() -> Result<()> {
+/// Install dependencies from nockapp.toml; offline, only packages already cached are used
+///
+/// Repositories are cloned partially unless `full_clone` is set. Except in `LockMode::Update`,
+/// nockapp.lock must already exist and is left unchanged.
+pub async fn run(offline: bool, full_clone: bool, lock_mode: LockMode) -> Result<()> {
     let cwd = env::current_dir()?;
     let manifest_path = cwd.join("nockapp.toml");
 
@@ -19,12 +40,12 @@ pub async fn run() -> Result<()> {
         None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
     };
 
-    println!(
+    say!(
         "{} Installing dependencies for {}",
         "📦".cyan(),
         manifest.package.name.yellow()
     );
-    println!();
+    say!();
 
     // Determine the project directory based on the package name
     let project_dir = cwd.join(&manifest.package.name);
@@ -37,32 +58,81 @@ pub async fn run() -> Result<()> {
         );
     }
 
+    // Vendored packages are installed without consulting the cache or git
+    if let Some(vendored) = VendorManifest::load(&project_dir.join(VENDOR_MANIFEST))? {
+        let missing = vendored.missing(&manifest);
+        if missing.is_empty() {
+            return vendor::install_vendored(
+                &project_dir,
+                &vendored,
+                lock_mode == LockMode::Update,
+                placement::install_mode(&manifest.package)?,
+            );
+        }
+        say!(
+            "{} {} does not include {}, installing from the cache instead",
+            "⚠".yellow(),
+            VENDOR_MANIFEST,
+            missing.join(", ").yellow()
+        );
+    }
+
+    let lock_path = project_dir.join("nockapp.lock");
+    if lock_mode != LockMode::Update && !lock_path.exists() {
+        anyhow::bail!(
+            "No nockapp.lock in {}; run `nockup package install` to create it",
+            project_dir.display()
+        );
+    }
+    let previous_lock = NockAppLock::load(&lock_path)?;
+
     // Initialize resolver
-    let resolver = Resolver::new()?;
+    let resolver = Resolver::new(offline)?
+        .with_full_clone(full_clone)
+        .with_registries(registry_sources(Some(&manifest))?)
+        .with_patches(manifest.patch.clone().unwrap_or_default(), &cwd)
+        .with_local_packages(local_packages(&manifest, &cwd)?)
+        .with_lock(&previous_lock);
     let cache = PackageCache::new()?;
 
-    // Resolve dependency graph
-    let graph = resolver.resolve(&manifest).await?;
+    // Resolve dependency graph, or take it from nockapp.lock
+    let graph = match lock_mode {
+        LockMode::Frozen => resolver.resolve_locked(&manifest, &previous_lock).await?,
+        _ => resolver.resolve(&manifest).await?,
+    };
+
+    if lock_mode == LockMode::Locked {
+        let changes = lock_changes(&graph, &previous_lock);
+        if !changes.is_empty() {
+            anyhow::bail!(
+                "nockapp.lock is out of date:\n  - {}\n\
+                Run `nockup package install` without --locked to update it.",
+                changes.join("\n  - ")
+            );
+        }
+    }
 
     if graph.packages.is_empty() {
-        println!("{} No dependencies to install", "✓".green());
+        say!("{} No dependencies to install", "✓".green());
 
         // Create empty lockfile if needed
-        let lock_path = project_dir.join("nockapp.lock");
-        if !lock_path.exists() {
-            let lockfile = NockAppLock {
-                package: Vec::new(),
-            };
+        let create_lock = !lock_path.exists();
+        if create_lock {
+            let lockfile = NockAppLock::new(Vec::new());
             lockfile.save(&lock_path)?;
-            println!("  Created empty nockapp.lock");
+            say!("  Created empty nockapp.lock");
         }
+        output::emit(
+            "install-complete",
+            json!({ "packages": 0, "lockfile_updated": create_lock }),
+        );
 
         return Ok(());
     }
 
-    println!();
-    println!("{} Installing packages...", "📥".cyan());
-    println!();
+    say!();
+    say!("{} Installing packages...", "📥".cyan());
+    say!();
 
     // Create hoon/packages, hoon/lib, and hoon/sur directories if they don't exist
     let hoon_dir = project_dir.join("hoon");
@@ -75,6 +145,7 @@ pub async fn run() -> Result<()> {
 
     // Install packages in topological order
     let mut locked_packages = Vec::new();
+    let mut placer = Placer::new(placement::install_mode(&manifest.package)?, &hoon_dir);
 
     for pkg_name in &graph.install_order {
         let pkg = graph
@@ -82,111 +153,279 @@ pub async fn run() -> Result<()> {
             .get(pkg_name)
             .ok_or_else(|| anyhow!("Missing package '{}' in resolved graph", pkg_name))?;
 
-        let version_str = pkg.version_spec.to_canonical_string();
+        let (display_version, cache_version) = install_versions(pkg);
 
-        // For wildcard/latest versions ("*"), display as "latest" and use commit for cache
-        let (display_version, cache_version) = if version_str == "*" {
-            ("latest".to_string(), format!("commit:{}", pkg.commit))
-        } else {
-            (version_str.clone(), version_str.clone())
-        };
-
-        println!(
+        say!(
             "  {} Installing {}@{}...",
             "→".cyan(),
             pkg.name.yellow(),
             display_version.cyan()
         );
 
-        // Check if already in cache using the cache version
-        let cached_path = cache.package_path(&pkg.name, &cache_version);
+        // Workspace libraries are installed from their directories, as they are now
+        let (cached_path, checksum) = if pkg.local.is_some() {
+            let dir = PathBuf::from(&pkg.source_url);
+            let checksum = content_hash(&dir)?;
+            (dir, checksum)
+        } else {
+            // Check if already in cache using the cache version
+            let cached_path = cache.package_path(&pkg.name, &cache_version);
+
+            if !cached_path.exists() {
+                // This shouldn't happen since resolver already cached it,
+                // but handle it gracefully
+                say!(
+                    "    {} Package not in cache (this is unexpected)",
+                    "⚠".yellow()
+                );
+                continue;
+            }
+
+            // Verify the cached copy against nockapp.lock, fetching it again if it has changed
+            let checksum = verify_cached_package(
+                &resolver,
+                pkg,
+                &cache_version,
+                &cached_path,
+                previous_lock.checksum(&pkg.name, &pkg.commit),
+            )
+            .await?;
+            (cached_path, checksum)
+        };
+
+        // Install to hoon/packages/<name>--<version>/
+        let dir_name = package_dir_name(&pkg.name, &display_version);
+        let install_dir = packages_dir.join(&dir_name);
 
-        if !cached_path.exists() {
-            // This shouldn't happen since resolver already cached it,
-            // but handle it gracefully
-            println!(
-                "    {} Package not in cache (this is unexpected)",
+        if install_dir.exists() && content_hash(&install_dir)? != checksum {
+            say!(
+                "    {} Installed copy does not match the cache, reinstalling",
                 "⚠".yellow()
             );
-            continue;
+            fs::remove_dir_all(&install_dir)
+                .with_context(|| format!("Failed to remove {}", install_dir.display()))?;
         }
 
-        // Install to hoon/packages/<name>--<version>/
-        // Sanitize package name (replace / with -) and version (replace : with -) for use in directory names
-        let safe_name = sanitize_package_name(&pkg.name);
-        let safe_version = sanitize_version(&display_version);
-        let install_dir = packages_dir.join(format!("{}--{}", safe_name, safe_version));
-
         if install_dir.exists() {
-            println!("    {} Already installed, skipping", "✓".green());
+            say!("    {} Already installed, skipping", "✓".green());
         } else {
             // Copy from cache to hoon/packages/
             copy_dir_recursive(cached_path.as_path(), install_dir.as_path()).with_context(
                 || format!("Failed to install package to {}", install_dir.display()),
             )?;
 
-            println!(
+            say!(
                 "    {} Installed to {}",
                 "✓".green(),
-                format!("hoon/packages/{}--{}", safe_name, safe_version).cyan()
+                format!("hoon/packages/{}", dir_name).cyan()
             );
         }
 
-        // Create symlinks for .hoon files
-        // If install_path is specified (from registry), preserve directory structure
-        // Otherwise, link to hoon/lib/ and hoon/sur/
-        if let (Some(ref install_path), Some(ref files)) = (&pkg.install_path, &pkg.source_files) {
-            println!("install_path: {:?}", install_path);
-            link_registry_package(
-                install_dir.as_path(),
-                hoon_dir.as_path(),
-                install_path,
-                &pkg.name,
-                files,
-            )?;
-        } else {
-            println!("No install_path specified, linking to hoon/lib/ and hoon/sur/");
-            link_package_files(
-                install_dir.as_path(),
-                lib_dir.as_path(),
-                sur_dir.as_path(),
-                &pkg.name,
-                pkg.source_path.as_deref(),
-                pkg.source_files.as_ref(),
-            )?;
-        }
+        // Put its .hoon files in hoon/
+        link_package(
+            install_dir.as_path(),
+            hoon_dir.as_path(),
+            &pkg.name,
+            pkg.install_path.as_deref(),
+            pkg.source_path.as_deref(),
+            pkg.source_files.as_ref(),
+            &mut placer,
+        )?;
+
+        output::emit(
+            "installed",
+            json!({
+                "name": pkg.name,
+                "version": display_version,
+                "commit": pkg.commit,
+                "source": pkg.source_url,
+                "checksum": checksum,
+                "path": format!("hoon/packages/{}", dir_name),
+            }),
+        );
 
         // Add to lockfile
         locked_packages.push(LockedPackage {
             name: pkg.name.clone(),
             version: display_version.clone(),
-            source: LockSource::Git {
-                url: pkg.source_url.clone(),
-                commit: pkg.commit.clone(),
-                path: pkg.source_path.clone(),
-            },
+            source: lock_source(pkg),
+            checksum: Some(checksum),
+            license: pkg.license.clone(),
+            dependencies: graph.constraints_of(&pkg.name),
         });
     }
 
-    println!();
-    println!(
+    placer.save()?;
+
+    say!();
+    say!(
         "{} Installed {} packages",
         "✓".green(),
         graph.packages.len()
     );
 
     // Generate/update lockfile
-    let lock_path = project_dir.join("nockapp.lock");
-    let lockfile = NockAppLock {
-        package: locked_packages,
-    };
+    if lock_mode == LockMode::Update {
+        let lockfile = previous_lock.with_packages(locked_packages);
 
-    lockfile.save(&lock_path)?;
-    println!("  Updated nockapp.lock");
+        lockfile.save(&lock_path)?;
+        say!("  Updated nockapp.lock");
+    }
+    cache.register_lockfile(&lock_path).await?;
+    output::emit(
+        "install-complete",
+        json!({
+            "packages": graph.packages.len(),
+            "lockfile_updated": lock_mode == LockMode::Update,
+        }),
+    );
 
     Ok(())
 }
 
+/// Where nockapp.lock records a resolved package as coming from
+pub(crate) fn lock_source(pkg: &ResolvedPackage) -> LockSource {
+    match &pkg.local {
+        Some(path) => LockSource::Path { path: path.clone() },
+        None => LockSource::Git {
+            url: pkg.source_url.clone(),
+            commit: pkg.commit.clone(),
+            path: pkg.source_path.clone(),
+        },
+    }
+}
+
+/// How a resolved graph differs from nockapp.lock, one line per package
+fn lock_changes(graph: &ResolvedGraph, lock: &NockAppLock) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    for name in &graph.install_order {
+        let Some(pkg) = graph.packages.get(name) else {
+            continue;
+        };
+        let (version, _) = install_versions(pkg);
+        let short_commit: String = pkg.commit.chars().take(12).collect();
+        let Some(locked) = lock.package.iter().find(|p| p.name == *name) else {
+            changes.push(format!(
+                "{} {} ({}) is not locked",
+                name, version, short_commit
+            ));
+            continue;
+        };
+
+        let unchanged = locked.version == version && locked.source == lock_source(pkg);
+        if !unchanged {
+            let was = match &locked.source {
+                LockSource::Git { commit, .. } => commit.chars().take(12).collect(),
+                LockSource::Path { path } => path.clone(),
+            };
+            changes.push(format!(
+                "{} {} ({}) is locked as {} ({})",
+                name, version, short_commit, locked.version, was
+            ));
+        }
+    }
+
+    for locked in &lock.package {
+        if !graph.packages.contains_key(&locked.name) {
+            changes.push(format!("{} is locked but no longer needed", locked.name));
+        }
+    }
+
+    changes
+}
+
+/// Check a cached package against the checksum recorded in nockapp.lock, returning its checksum
+///
+/// A cached copy that no longer matches is fetched again at its locked commit. If the fresh copy
+/// does not match either, the lockfile and the package source disagree and installation stops.
+pub(crate) async fn verify_cached_package(
+    resolver: &Resolver,
+    pkg: &ResolvedPackage,
+    cache_version: &str,
+    cached_path: &Path,
+    expected: Option<&str>,
+) -> Result<String> {
+    let checksum = content_hash(cached_path)?;
+    let Some(expected) = expected else {
+        return Ok(checksum);
+    };
+    if checksum == expected {
+        return Ok(checksum);
+    }
+
+    say!(
+        "    {} Cached copy does not match nockapp.lock, fetching again",
+        "⚠".yellow()
+    );
+    let cached_path = resolver.refetch(pkg, cache_version).await?;
+    let checksum = content_hash(&cached_path)?;
+    if checksum != expected {
+        anyhow::bail!(
+            "Checksum mismatch for {} at commit {}: nockapp.lock records {}, but the package \
+            contents hash to {}. Remove its entry from nockapp.lock if the change is expected.",
+            pkg.name,
+            pkg.commit,
+            expected,
+            checksum
+        );
+    }
+    Ok(checksum)
+}
+
+/// Version shown for and locked for a resolved package, and the version it is cached under
+///
+/// Wildcard/latest versions ("*") display as "latest" and are cached by commit.
+pub(crate) fn install_versions(pkg: &ResolvedPackage) -> (String, String) {
+    let version_str = pkg.version_spec.to_canonical_string();
+    if version_str == "*" {
+        ("latest".to_string(), format!("commit:{}", pkg.commit))
+    } else {
+        (version_str.clone(), version_str)
+    }
+}
+
+/// Directory a package is installed to under hoon/packages/: <name>--<version>
+/// Sanitize package name (replace / with -) and version (replace : with -) for use in directory names
+pub(crate) fn package_dir_name(name: &str, display_version: &str) -> String {
+    format!(
+        "{}--{}",
+        sanitize_package_name(name),
+        sanitize_version(display_version)
+    )
+}
+
+/// Put a package's .hoon files in hoon/, as `placer` places them
+/// If install_path is specified (from registry), preserve directory structure
+/// Otherwise, link to hoon/lib/ and hoon/sur/
+pub(crate) fn link_package(
+    package_dir: &Path,
+    hoon_dir: &Path,
+    package_name: &str,
+    install_path: Option<&str>,
+    source_path: Option<&str>,
+    source_files: Option<&Vec<String>>,
+    placer: &mut Placer,
+) -> Result<()> {
+    if let (Some(install_path), Some(files)) = (install_path, source_files) {
+        say!("install_path: {:?}", install_path);
+        link_registry_package(
+            package_dir, hoon_dir, install_path, package_name, files, placer,
+        )
+    } else {
+        say!("No install_path specified, linking to hoon/lib/ and hoon/sur/");
+        link_package_files(
+            package_dir,
+            hoon_dir.join("lib").as_path(),
+            hoon_dir.join("sur").as_path(),
+            package_name,
+            source_path,
+            source_files,
+            placer,
+        )
+    }
+}
+
 /// Sanitize package name for use in directory names (replace / with -)
 fn sanitize_package_name(name: &str) -> String {
     name.replace('/', "-")
@@ -203,7 +442,7 @@ fn sanitize_version(version: &str) -> String {
 }
 
 /// Recursively copy a directory
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+pub(crate) fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     fs::create_dir_all(dst)?;
 
     for entry in fs::read_dir(src)? {
@@ -212,7 +451,10 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
         let file_name = entry.file_name();
         let dst_path = dst.join(&file_name);
 
-        if path.is_dir() {
+        if file_name == ".git" {
+            // Workspace libraries may be repositories of their own
+            continue;
+        } else if path.is_dir() {
             copy_dir_recursive(&path, &dst_path)?;
         } else {
             fs::copy(&path, &dst_path)?;
@@ -234,19 +476,20 @@ fn link_registry_package(
     install_path: &str,
     package_name: &str,
     source_files: &Vec<String>,
+    placer: &mut Placer,
 ) -> Result<()> {
     let package_dir_name = package_dir_basename(package_dir)?;
 
     // Strip "hoon/" prefix from install_path if present (it's already included in hoon_dir)
-    println!("install_path before stripping: {:?}", install_path);
+    say!("install_path before stripping: {:?}", install_path);
     let relative_path = install_path.strip_prefix("hoon/").unwrap_or(install_path);
-    println!("relative_path: {:?}", relative_path);
+    say!("relative_path: {:?}", relative_path);
 
     // Create the target directory structure in hoon/
     let target_dir = hoon_dir.join(relative_path);
     fs::create_dir_all(&target_dir)
         .with_context(|| format!("Failed to create directory {}", target_dir.display()))?;
-    println!("  source_files: {:?}", source_files);
+    say!("  source_files: {:?}", source_files);
 
     if !source_files.is_empty() {
         // Link each specified file
@@ -257,14 +500,7 @@ fn link_registry_package(
             }
 
             let link_path = target_dir.join(filename);
-            println!("  link_path: {:?}", link_path);
-
-            // Remove existing symlink if it exists
-            if link_path.exists() || link_path.is_symlink() {
-                fs::remove_file(&link_path).with_context(|| {
-                    format!("Failed to remove existing symlink {}", link_path.display())
-                })?;
-            }
+            say!("  link_path: {:?}", link_path);
 
             // Create relative symlink
             // Calculate path from target_dir back to packages/
@@ -277,35 +513,14 @@ fn link_registry_package(
             relative_target.push("packages");
             relative_target.push(Path::new(&package_dir_name));
             relative_target.push(filename);
-            println!("  relative_target: {:?}", relative_target);
+            say!("  relative_target: {:?}", relative_target);
 
-            #[cfg(unix)]
-            {
-                std::os::unix::fs::symlink(&relative_target, &link_path).with_context(|| {
-                    format!(
-                        "Failed to create symlink {} -> {}",
-                        link_path.display(),
-                        relative_target.display()
-                    )
-                })?;
-            }
+            placer.place(&source_file, &link_path, &relative_target)?;
 
-            #[cfg(windows)]
-            {
-                std::os::windows::fs::symlink_file(&relative_target, &link_path).with_context(
-                    || {
-                        format!(
-                            "Failed to create symlink {} -> {}",
-                            link_path.display(),
-                            relative_target.display()
-                        )
-                    },
-                )?;
-            }
-
-            println!(
-                "    {} Linked {} to hoon/{}/",
+            say!(
+                "    {} {} {} to hoon/{}/",
                 "🔗".cyan(),
+                placer.verb(),
                 filename.yellow(),
                 relative_path.cyan()
             );
@@ -348,16 +563,6 @@ fn link_registry_package(
                             };
                             let link_path = dest_dir.join(file_name);
 
-                            // Remove existing symlink if it exists
-                            if link_path.exists() || link_path.is_symlink() {
-                                fs::remove_file(&link_path).with_context(|| {
-                                    format!(
-                                        "Failed to remove existing symlink {}",
-                                        link_path.display()
-                                    )
-                                })?;
-                            }
-
                             // Calculate relative path from package_root to the file
                             let relative_from_package =
                                 path.strip_prefix(package_dir).unwrap_or(&path);
@@ -370,33 +575,12 @@ fn link_registry_package(
                             relative_target.push(Path::new(&package_dir_name));
                             relative_target.push(relative_from_package);
 
-                            #[cfg(unix)]
-                            {
-                                std::os::unix::fs::symlink(&relative_target, &link_path)
-                                    .with_context(|| {
-                                        format!(
-                                            "Failed to create symlink {} -> {}",
-                                            link_path.display(),
-                                            relative_target.display()
-                                        )
-                                    })?;
-                            }
-
-                            #[cfg(windows)]
-                            {
-                                std::os::windows::fs::symlink_file(&relative_target, &link_path)
-                                    .with_context(|| {
-                                        format!(
-                                            "Failed to create symlink {} -> {}",
-                                            link_path.display(),
-                                            relative_target.display()
-                                        )
-                                    })?;
-                            }
-
-                            println!(
-                                "    {} Linked {} to hoon/{}/",
+                            placer.place(&path, &link_path, &relative_target)?;
+
+                            say!(
+                                "    {} {} {} to hoon/{}/",
                                 "🔗".cyan(),
+                                placer.verb(),
                                 file_name.to_string_lossy().yellow(),
                                 dest_subdir.cyan()
                             );
@@ -407,7 +591,7 @@ fn link_registry_package(
         }
 
         if !found_files {
-            println!(
+            say!(
                 "    {} No .hoon files found in package {}",
                 "⚠".yellow(),
                 package_name.yellow()
@@ -427,9 +611,10 @@ fn link_package_files(
     package_name: &str,
     _path_from_root: Option<&str>,
     source_files: Option<&Vec<String>>,
+    placer: &mut Placer,
 ) -> Result<()> {
     let package_dir_name = package_dir_basename(package_dir)?;
-    println!("  source_files is {:?}", source_files);
+    say!("  source_files is {:?}", source_files);
 
     // Get the parent hoon/ directory from lib_dir
     let hoon_dir = lib_dir
@@ -442,7 +627,7 @@ fn link_package_files(
         // The package is cached with contents of source_path, so we don't prepend it
         for filename in files {
             let source_file = package_dir.join(filename);
-            println!("  source_file: {:?}", source_file);
+            say!("  source_file: {:?}", source_file);
             if !source_file.exists() {
                 anyhow::bail!("Specific file {} not found in package {}", filename, package_name);
             }
@@ -465,7 +650,7 @@ fn link_package_files(
                 .ok_or_else(|| anyhow::anyhow!("Invalid filename: {}", filename))?
                 .to_os_string();
             let link_path = dest_dir.join(&file_name);
-            println!("  link_path: {:?}", link_path);
+            say!("  link_path: {:?}", link_path);
 
             // Ensure destination directory exists
             if !dest_dir.exists() {
@@ -474,47 +659,19 @@ fn link_package_files(
                 })?;
             }
 
-            // Remove existing symlink if it exists
-            if link_path.exists() || link_path.is_symlink() {
-                fs::remove_file(&link_path).with_context(|| {
-                    format!("Failed to remove existing symlink {}", link_path.display())
-                })?;
-            }
-
             // Create relative symlink
             // filename may include subdirectories (e.g., "lib/lagoon.hoon")
             let mut relative_target = PathBuf::from("../packages");
             relative_target.push(Path::new(&package_dir_name));
             relative_target.push(Path::new(filename));
-            println!("  relative_target: {:?}", relative_target);
-
-            #[cfg(unix)]
-            {
-                std::os::unix::fs::symlink(&relative_target, &link_path).with_context(|| {
-                    format!(
-                        "Failed to create symlink {} -> {}",
-                        link_path.display(),
-                        relative_target.display()
-                    )
-                })?;
-            }
+            say!("  relative_target: {:?}", relative_target);
 
-            #[cfg(windows)]
-            {
-                std::os::windows::fs::symlink_file(&relative_target, &link_path).with_context(
-                    || {
-                        format!(
-                            "Failed to create symlink {} -> {}",
-                            link_path.display(),
-                            relative_target.display()
-                        )
-                    },
-                )?;
-            }
+            placer.place(&source_file, &link_path, &relative_target)?;
 
-            println!(
-                "    {} Linked {} to hoon/{}/",
+            say!(
+                "    {} {} {} to hoon/{}/",
                 "🔗".cyan(),
+                placer.verb(),
                 filename.yellow(),
                 dest_subdir.cyan()
             );
@@ -545,7 +702,13 @@ fn link_package_files(
         }
 
         // Link .hoon files from this lib directory (non-recursive - only direct children)
-        link_hoon_files_from_dir(source_dir.as_path(), package_dir, lib_dir, &mut found_files)?;
+        link_hoon_files_from_dir(
+            source_dir.as_path(),
+            package_dir,
+            lib_dir,
+            &mut found_files,
+            placer,
+        )?;
     }
 
     // Link sur files
@@ -555,11 +718,17 @@ fn link_package_files(
         }
 
         // Link .hoon files from this sur directory (non-recursive - only direct children)
-        link_hoon_files_from_dir(source_dir.as_path(), package_dir, sur_dir, &mut found_files)?;
+        link_hoon_files_from_dir(
+            source_dir.as_path(),
+            package_dir,
+            sur_dir,
+            &mut found_files,
+            placer,
+        )?;
     }
 
     if !found_files {
-        println!(
+        say!(
             "    {} No .hoon files found in package {}",
             "⚠".yellow(),
             package_name.yellow()
@@ -575,6 +744,7 @@ fn link_hoon_files_from_dir(
     package_root: &Path,
     lib_dir: &Path,
     found_files: &mut bool,
+    placer: &mut Placer,
 ) -> Result<()> {
     let package_dir_name = package_dir_basename(package_root)?;
     for entry in fs::read_dir(source_dir)
@@ -593,13 +763,6 @@ fn link_hoon_files_from_dir(
                     *found_files = true;
                     let link_path = lib_dir.join(file_name);
 
-                    // Remove existing symlink if it exists
-                    if link_path.exists() || link_path.is_symlink() {
-                        fs::remove_file(&link_path).with_context(|| {
-                            format!("Failed to remove existing symlink {}", link_path.display())
-                        })?;
-                    }
-
                     // Create relative path from hoon/lib to the file
                     // Calculate the relative path from package_root to the actual file
                     let relative_from_package = path.strip_prefix(package_root).unwrap_or(&path);
@@ -608,34 +771,12 @@ fn link_hoon_files_from_dir(
                     relative_target.push(Path::new(&package_dir_name));
                     relative_target.push(relative_from_package);
 
-                    #[cfg(unix)]
-                    {
-                        std::os::unix::fs::symlink(&relative_target, &link_path).with_context(
-                            || {
-                                format!(
-                                    "Failed to create symlink {} -> {}",
-                                    link_path.display(),
-                                    relative_target.display()
-                                )
-                            },
-                        )?;
-                    }
+                    placer.place(&path, &link_path, &relative_target)?;
 
-                    #[cfg(windows)]
-                    {
-                        std::os::windows::fs::symlink_file(&relative_target, &link_path)
-                            .with_context(|| {
-                                format!(
-                                    "Failed to create symlink {} -> {}",
-                                    link_path.display(),
-                                    relative_target.display()
-                                )
-                            })?;
-                    }
-
-                    println!(
-                        "    {} Linked {} to hoon/lib/",
+                    say!(
+                        "    {} {} {} to hoon/lib/",
                         "🔗".cyan(),
+                        placer.verb(),
                         file_name.to_string_lossy().yellow()
                     );
                 }
@@ -653,3 +794,68 @@ fn package_dir_basename(package_dir: &Path) -> Result<String> {
         .map(|name| name.to_string_lossy().into_owned())
         .ok_or_else(|| anyhow!("Package directory '{}' has no name", package_dir.display()))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+    use crate::resolver::VersionSpec;
+
+    fn resolved(name: &str, version: &str, commit: &str) -> ResolvedPackage {
+        ResolvedPackage {
+            name: name.to_string(),
+            version_spec: VersionSpec::parse(version).unwrap(),
+            commit: commit.to_string(),
+            source_url: format!("https://github.com/{}", name),
+            source_path: None,
+            install_path: None,
+            source_files: None,
+            dependencies: HashMap::new(),
+            license: None,
+            local: None,
+        }
+    }
+
+    fn locked(name: &str, version: &str, commit: &str) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: LockSource::Git {
+                url: format!("https://github.com/{}", name),
+                commit: commit.to_string(),
+                path: None,
+            },
+            checksum: None,
+            license: None,
+            dependencies: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_lock_changes() {
+        let mut graph = ResolvedGraph::new();
+        for pkg in [
+            resolved("urbit/zuse", "k409", "aaaaaaaaaaaaaaaa"),
+            resolved("sequent", "*", "bbbbbbbbbbbbbbbb"),
+            resolved("lagoon", "*", "cccccccccccccccc"),
+        ] {
+            graph.install_order.push(pkg.name.clone());
+            graph.add_package(pkg);
+        }
+
+        let lock = NockAppLock::new(vec![
+            locked("urbit/zuse", "k409", "aaaaaaaaaaaaaaaa"),
+            locked("sequent", "latest", "0000000000000000"),
+            locked("old", "latest", "dddddddddddddddd"),
+        ]);
+
+        assert_eq!(
+            lock_changes(&graph, &lock),
+            vec![
+                "sequent latest (bbbbbbbbbbbb) is locked as latest (000000000000)",
+                "lagoon latest (cccccccccccc) is not locked", "old is locked but no longer needed",
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+// src/commands/package/licenses.rs
+use std::collections::BTreeMap;
+use std::env;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::commands::common::get_cache_dir;
+use crate::manifest::{HoonPackage, LockedPackage, NockAppLock};
+
+/// Shown for packages whose hoon.toml declares no license
+const UNKNOWN_LICENSE: &str = "unknown";
+
+/// The `[licenses]` table of ~/.nockup/config.toml
+#[derive(Debug, Default, Deserialize)]
+struct LicenseConfig {
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+/// Print the licenses of the packages in nockapp.lock, grouped by license
+///
+/// Fails if a package's license is denied, by `deny` or by `deny` in the `[licenses]` table of
+/// ~/.nockup/config.toml.
+pub async fn run(deny: Vec<String>) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest = match HoonPackage::load(&cwd.join("nockapp.toml"))? {
+        Some(m) => m,
+        None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
+    };
+
+    let lock_path = cwd.join(&manifest.package.name).join("nockapp.lock");
+    if !lock_path.exists() {
+        anyhow::bail!("No nockapp.lock found; run `nockup package install` first");
+    }
+    let lock = NockAppLock::load(&lock_path)?;
+
+    let mut denied_licenses = license_config()?.deny;
+    denied_licenses.extend(deny);
+
+    say!(
+        "{} Licenses of the {} locked packages of {}:",
+        "📜".cyan(),
+        lock.package.len(),
+        manifest.package.name.yellow()
+    );
+
+    let mut by_license: BTreeMap<&str, Vec<&LockedPackage>> = BTreeMap::new();
+    for pkg in &lock.package {
+        let license = pkg.license.as_deref().unwrap_or(UNKNOWN_LICENSE);
+        by_license.entry(license).or_default().push(pkg);
+    }
+    for (license, packages) in &by_license {
+        say!();
+        say!("  {} ({})", license.bold(), packages.len());
+        for pkg in packages {
+            say!("    {} {}", pkg.name, pkg.version.dimmed());
+        }
+    }
+
+    let denied: Vec<&LockedPackage> = lock
+        .package
+        .iter()
+        .filter(|pkg| {
+            pkg.license
+                .as_deref()
+                .is_some_and(|license| is_denied(license, &denied_licenses))
+        })
+        .collect();
+
+    say!();
+    if !denied.is_empty() {
+        for pkg in &denied {
+            say!(
+                "{} {} is licensed under {}, which is denied",
+                "✗".red(),
+                pkg.name.red(),
+                pkg.license.as_deref().unwrap_or_default()
+            );
+        }
+        anyhow::bail!("{} packages have a denied license", denied.len());
+    }
+    if let Some(unknown) = by_license.get(UNKNOWN_LICENSE) {
+        say!(
+            "{} {} packages declare no license in their hoon.toml",
+            "⚠".yellow(),
+            unknown.len()
+        );
+    }
+    if !denied_licenses.is_empty() {
+        say!("{} No package has a denied license", "✓".green());
+    }
+
+    Ok(())
+}
+
+fn license_config() -> Result<LicenseConfig> {
+    let config_path = get_cache_dir()?.join("config.toml");
+    if !config_path.exists() {
+        return Ok(LicenseConfig::default());
+    }
+    let contents = std::fs::read_to_string(&config_path).context("Failed to read config file")?;
+    #[derive(Deserialize)]
+    struct ConfigFile {
+        #[serde(default)]
+        licenses: LicenseConfig,
+    }
+    Ok(toml::from_str::<ConfigFile>(&contents)
+        .context("Failed to parse [licenses] section of config file")?
+        .licenses)
+}
+
+/// Whether a license expression leaves no choice but a denied license
+///
+/// SPDX expressions are understood as far as `OR` and `AND`: `MIT OR GPL-3.0` is allowed if
+/// only GPL-3.0 is denied, while `MIT AND GPL-3.0` is not. Identifiers compare ignoring case.
+fn is_denied(expression: &str, denied: &[String]) -> bool {
+    let is_denied_id = |id: &str| denied.iter().any(|d| d.eq_ignore_ascii_case(id));
+    expression.split(" OR ").all(|alternative| {
+        alternative
+            .split(" AND ")
+            .map(|id| id.trim().trim_matches(|c| c == '(' || c == ')').trim())
+            .any(is_denied_id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_denied() {
+        let denied = vec!["GPL-3.0".to_string(), "agpl-3.0".to_string()];
+        assert!(is_denied("GPL-3.0", &denied));
+        assert!(is_denied("AGPL-3.0", &denied));
+        assert!(!is_denied("MIT", &denied));
+        assert!(!is_denied("MIT OR GPL-3.0", &denied));
+        assert!(is_denied("MIT AND GPL-3.0", &denied));
+        assert!(is_denied("(MIT AND GPL-3.0) OR AGPL-3.0", &denied));
+        assert!(!is_denied("GPL-3.0", &[]));
+    }
+}
@@ -3,8 +3,10 @@ use std::env;
 
 use anyhow::Result;
 use colored::Colorize;
+use serde_json::json;
 
 use crate::manifest::{HoonPackage, NockAppLock};
+use crate::output;
 
 /// List all dependencies from nockapp.toml and their installation status
 pub async fn run() -> Result<()> {
@@ -36,14 +38,15 @@ pub async fn run() -> Result<()> {
     let lock_path = project_dir.join("nockapp.lock");
     let lockfile = NockAppLock::load(&lock_path)?;
 
-    println!("{} Package dependencies:", "📦".cyan());
-    println!();
+    say!("{} Package dependencies:", "📦".cyan());
+    say!();
 
     // Check if there are any dependencies
     let deps = match manifest.dependencies {
         Some(ref deps) if !deps.is_empty() => deps,
         _ => {
-            println!("  No dependencies found");
+            say!("  No dependencies found");
+            output::emit("summary", json!({ "total": 0, "installed": 0 }));
             return Ok(());
         }
     };
@@ -96,8 +99,23 @@ pub async fn run() -> Result<()> {
                 .join("packages")
                 .join(package_dir_name);
 
+            let status = if package_dir.exists() {
+                "installed"
+            } else {
+                "missing"
+            };
+            output::emit(
+                "dependency",
+                json!({
+                    "name": name,
+                    "spec": spec_str,
+                    "installed": installed_version,
+                    "status": status,
+                }),
+            );
+
             if package_dir.exists() {
-                println!(
+                say!(
                     "  {} {} {} (installed: {})",
                     "✓".green(),
                     name.yellow(),
@@ -105,7 +123,7 @@ pub async fn run() -> Result<()> {
                     installed_version.cyan()
                 );
             } else {
-                println!(
+                say!(
                     "  {} {} {} (in lockfile but missing from disk)",
                     "⚠".yellow(),
                     name.yellow(),
@@ -113,7 +131,16 @@ pub async fn run() -> Result<()> {
                 );
             }
         } else {
-            println!(
+            output::emit(
+                "dependency",
+                json!({
+                    "name": name,
+                    "spec": spec_str,
+                    "installed": null,
+                    "status": "not-installed",
+                }),
+            );
+            say!(
                 "  {} {} {} (not installed)",
                 "✗".red(),
                 name.yellow(),
@@ -122,7 +149,7 @@ pub async fn run() -> Result<()> {
         }
     }
 
-    println!();
+    say!();
 
     // Show summary
     let total = deps.len();
@@ -130,18 +157,22 @@ pub async fn run() -> Result<()> {
         .keys()
         .filter(|name| installed.contains_key(*name))
         .count();
+    output::emit(
+        "summary",
+        json!({ "total": total, "installed": installed_count }),
+    );
 
     if installed_count == total {
-        println!("{} All {} packages installed", "✓".green(), total);
+        say!("{} All {} packages installed", "✓".green(), total);
     } else {
-        println!(
+        say!(
             "{} {}/{} packages installed",
             "→".cyan(),
             installed_count,
             total
         );
         if installed_count < total {
-            println!(
+            say!(
                 "  Run {} to install missing packages",
                 "nockup package install".cyan()
             );
@@ -0,0 +1,224 @@
+// src/commands/package/outdated.rs
+use std::env;
+
+use anyhow::Result;
+use colored::Colorize;
+use semver::{Version, VersionReq};
+
+use crate::cache::PackageCache;
+use crate::git_auth::GitAuth;
+use crate::git_fetcher::GitFetcher;
+use crate::manifest::{HoonPackage, LockSource, LockedPackage, NockAppLock};
+use crate::network::NetworkConfig;
+use crate::resolver::{kelvin_of, kelvin_tags, VersionSpec};
+
+/// A locked package with newer versions upstream
+#[derive(Debug)]
+struct Outdated {
+    name: String,
+    locked: String,
+    wanted: Option<String>, // Newest version the locked spec accepts, if not the locked one
+    latest: Option<String>, // Newest version the locked spec does not accept
+}
+
+/// Print the locked packages that have newer versions upstream
+pub async fn run() -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest = match HoonPackage::load(&cwd.join("nockapp.toml"))? {
+        Some(m) => m,
+        None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
+    };
+
+    let lock_path = cwd.join(&manifest.package.name).join("nockapp.lock");
+    if !lock_path.exists() {
+        anyhow::bail!("No nockapp.lock found; run `nockup package install` first");
+    }
+    let lock = NockAppLock::load(&lock_path)?;
+
+    say!(
+        "{} Checking {} locked packages of {} for updates...",
+        "🔍".cyan(),
+        lock.package.len(),
+        manifest.package.name.yellow()
+    );
+
+    let fetcher = GitFetcher::new(PackageCache::new()?.git_dir())
+        .with_auth(GitAuth::load()?)
+        .with_network(NetworkConfig::load()?);
+    let mut outdated = Vec::new();
+    for pkg in &lock.package {
+        match check(&fetcher, pkg).await {
+            Ok(Some(found)) => outdated.push(found),
+            Ok(None) => {}
+            Err(e) => say!(
+                "  {} Could not check {}: {}",
+                "⚠".yellow(),
+                pkg.name.yellow(),
+                e
+            ),
+        }
+    }
+
+    say!();
+    if outdated.is_empty() {
+        say!("{} All locked packages are up to date", "✓".green());
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 4]> = outdated
+        .into_iter()
+        .map(|o| {
+            let dash = || "-".to_string();
+            [o.name, o.locked, o.wanted.unwrap_or_else(dash), o.latest.unwrap_or_else(dash)]
+        })
+        .collect();
+    let header = ["Package", "Locked", "Wanted", "Latest"].map(str::to_string);
+    let mut widths = header.clone().map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |row: &[String; 4]| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    say!("  {}", format_row(&header).bold());
+    for row in &rows {
+        say!("  {}", format_row(row));
+    }
+
+    say!();
+    say!("Run `nockup package update` for wanted versions, or change nockapp.toml for the latest.");
+    say!("Kelvin versions count down: k408 is newer than k409.");
+
+    Ok(())
+}
+
+/// Compare a locked package with its repository
+async fn check(fetcher: &GitFetcher, pkg: &LockedPackage) -> Result<Option<Outdated>> {
+    // Local packages have no upstream to compare with
+    let LockSource::Git { url, commit, .. } = &pkg.source else {
+        return Ok(None);
+    };
+    let spec = VersionSpec::parse(&pkg.version)?;
+    let moved = |head: &str, label: &str| (head != commit.as_str()).then(|| describe(label, head));
+
+    let (wanted, latest) = match &spec {
+        VersionSpec::Kelvin(k) => {
+            let tags = fetcher.list_tags(url).await?;
+            let latest = newest_kelvin(&tags)
+                .filter(|newest| newest < k)
+                .map(|newest| format!("k{}", newest));
+            (None, latest)
+        }
+        VersionSpec::KelvinRange(_) => {
+            let tags = fetcher.list_tags(url).await?;
+            // Every newer kelvin is in range, so the newest is also the one wanted
+            let wanted = match kelvin_tags(&tags, &spec).into_iter().next() {
+                Some((_, tag)) => moved(&fetcher.resolve_tag(url, &tag).await?, &tag),
+                None => None,
+            };
+            (wanted, None)
+        }
+        VersionSpec::Semver(req) if !spec.is_any() => {
+            let tags = fetcher.list_tags(url).await?;
+            let wanted = match newest_version(&tags, Some(req)) {
+                Some((_, tag)) => moved(&fetcher.resolve_tag(url, &tag).await?, &tag),
+                None => None,
+            };
+            let latest = newest_version(&tags, None)
+                .filter(|(version, _)| !req.matches(version))
+                .map(|(_, tag)| tag);
+            (wanted, latest)
+        }
+        VersionSpec::Semver(_) => (moved(&default_head(fetcher, url).await?, "HEAD"), None),
+        VersionSpec::Branch(branch) => (
+            moved(&fetcher.resolve_branch(url, branch).await?, branch),
+            None,
+        ),
+        VersionSpec::Tag(tag) => {
+            let latest = match Version::parse(tag.trim_start_matches('v')) {
+                Ok(current) => newest_version(&fetcher.list_tags(url).await?, None)
+                    .filter(|(version, _)| *version > current)
+                    .map(|(_, tag)| tag),
+                Err(_) => None,
+            };
+            (None, latest)
+        }
+        VersionSpec::Commit(_) => (None, moved(&default_head(fetcher, url).await?, "HEAD")),
+    };
+
+    if wanted.is_none() && latest.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(Outdated {
+        name: pkg.name.clone(),
+        locked: describe(&pkg.version, commit),
+        wanted,
+        latest,
+    }))
+}
+
+/// Commit at the head of the default branch, main or else master
+async fn default_head(fetcher: &GitFetcher, url: &str) -> Result<String> {
+    match fetcher.resolve_branch(url, "main").await {
+        Ok(commit) => Ok(commit),
+        Err(_) => fetcher.resolve_branch(url, "master").await,
+    }
+}
+
+fn describe(label: &str, commit: &str) -> String {
+    format!("{} ({})", label, commit.chars().take(8).collect::<String>())
+}
+
+/// The newest kelvin among the tags, which is the lowest number
+fn newest_kelvin(tags: &[String]) -> Option<u32> {
+    tags.iter().filter_map(|tag| kelvin_of(tag)).min()
+}
+
+/// The highest semver tag, among those meeting `req` if given
+fn newest_version(tags: &[String], req: Option<&VersionReq>) -> Option<(Version, String)> {
+    tags.iter()
+        .filter_map(|tag| {
+            Some((
+                Version::parse(tag.trim_start_matches('v')).ok()?,
+                tag.clone(),
+            ))
+        })
+        .filter(|(version, _)| req.is_none_or(|req| req.matches(version)))
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newest_tags() {
+        let tags: Vec<String> = ["v1.2.0", "v1.4.1", "v2.0.0", "410k", "409k", "k411", "nightly"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+
+        // Lower kelvins are newer
+        assert_eq!(newest_kelvin(&tags), Some(409));
+        assert_eq!(kelvin_of("nightly"), None);
+
+        let req = VersionReq::parse("^1.2.0").unwrap();
+        assert_eq!(
+            newest_version(&tags, Some(&req)).map(|(_, tag)| tag),
+            Some("v1.4.1".to_string())
+        );
+        assert_eq!(
+            newest_version(&tags, None).map(|(_, tag)| tag),
+            Some("v2.0.0".to_string())
+        );
+        let req = VersionReq::parse("^3.0.0").unwrap();
+        assert_eq!(newest_version(&tags, Some(&req)), None);
+    }
+}
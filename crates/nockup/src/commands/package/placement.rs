@@ -0,0 +1,243 @@
+// src/commands/package/placement.rs
+//! Putting installed packages' files where Hoon imports them from
+//!
+//! Files are symlinked, copied or hard linked into hoon/, by the project's install mode. Each
+//! install records what it placed in hoon/packages/.installed.toml, so that a build can tell
+//! when copies and hard links no longer match their packages.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::common;
+use crate::manifest::{InstallMode, PackageMeta};
+
+/// Record of the last install's files, in hoon/packages/
+const RECORD_FILE: &str = ".installed.toml";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Record {
+    mode: Option<InstallMode>,
+    // Each placed file, relative to the project, and the package file it came from
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+}
+
+/// The install mode of a project: its nockapp.toml's, else ~/.nockup/config.toml's
+pub fn install_mode(package: &PackageMeta) -> Result<InstallMode> {
+    if let Some(mode) = package.install_mode {
+        return Ok(mode);
+    }
+    if let Some(mode) = common::get_config()
+        .ok()
+        .and_then(|config| config.get("install-mode").cloned())
+    {
+        return mode
+            .try_into()
+            .context("Invalid install-mode in ~/.nockup/config.toml");
+    }
+    Ok(InstallMode::default())
+}
+
+/// Places the files of the packages being installed into a project's hoon/ directory
+#[derive(Debug)]
+pub struct Placer {
+    mode: InstallMode,
+    hoon_dir: PathBuf,
+    record: Record,
+}
+
+impl Placer {
+    pub fn new(mode: InstallMode, hoon_dir: &Path) -> Self {
+        Placer {
+            mode,
+            hoon_dir: hoon_dir.to_path_buf(),
+            record: Record {
+                mode: Some(mode),
+                files: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// What placing a file is called, for messages
+    pub fn verb(&self) -> &'static str {
+        match self.mode {
+            InstallMode::Symlink => "Linked",
+            InstallMode::Copy => "Copied",
+            InstallMode::Hardlink => "Hard linked",
+        }
+    }
+
+    /// Put `source` at `dest`, replacing whatever is there
+    ///
+    /// In symlink mode, `dest` links to `relative_target`, which is relative to its directory.
+    pub fn place(&mut self, source: &Path, dest: &Path, relative_target: &Path) -> Result<()> {
+        if dest.exists() || dest.is_symlink() {
+            fs::remove_file(dest)
+                .with_context(|| format!("Failed to remove existing {}", dest.display()))?;
+        }
+
+        let placed = match self.mode {
+            InstallMode::Symlink => symlink(relative_target, dest),
+            InstallMode::Copy => fs::copy(source, dest).map(|_| ()),
+            InstallMode::Hardlink => fs::hard_link(source, dest),
+        };
+        placed.with_context(|| {
+            format!(
+                "Failed to place {} at {} (install-mode = \"{}\"); set install-mode = \"copy\" \
+                in nockapp.toml or ~/.nockup/config.toml if this filesystem cannot link",
+                source.display(),
+                dest.display(),
+                mode_name(self.mode)
+            )
+        })?;
+
+        self.record
+            .files
+            .insert(self.relative(dest), self.relative(source));
+        Ok(())
+    }
+
+    /// Save the record of what was placed, replacing the last install's
+    pub fn save(&self) -> Result<()> {
+        let path = self.hoon_dir.join("packages").join(RECORD_FILE);
+        fs::create_dir_all(self.hoon_dir.join("packages"))?;
+        fs::write(&path, toml::to_string_pretty(&self.record)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// `path` relative to the project, as it is recorded
+    fn relative(&self, path: &Path) -> String {
+        let project_dir = self.hoon_dir.parent().unwrap_or(&self.hoon_dir);
+        path.strip_prefix(project_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+/// Files the last install placed in `hoon_dir` that would not be placed the same in `mode`
+///
+/// Copies go stale when they differ from their package's files, and hard links when they are no
+/// longer the same file, as when a package is installed again. Everything is stale after the
+/// install mode changes. Installs from before the record are not checked.
+pub fn stale_files(hoon_dir: &Path, mode: InstallMode) -> Result<Vec<String>> {
+    let path = hoon_dir.join("packages").join(RECORD_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let record: Record =
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let project_dir = hoon_dir.parent().unwrap_or(hoon_dir);
+    let mode_changed = record.mode != Some(mode);
+    Ok(record
+        .files
+        .into_iter()
+        .filter(|(dest, source)| {
+            let (dest, source) = (project_dir.join(dest), project_dir.join(source));
+            mode_changed
+                || match mode {
+                    InstallMode::Symlink => !dest.is_symlink() || !dest.exists(),
+                    InstallMode::Copy => fs::read(&dest).ok().is_none_or(|placed| {
+                        fs::read(&source)
+                            .ok()
+                            .is_none_or(|original| placed != original)
+                    }),
+                    InstallMode::Hardlink => !same_file(&source, &dest),
+                }
+        })
+        .map(|(dest, _)| dest)
+        .collect())
+}
+
+fn mode_name(mode: InstallMode) -> &'static str {
+    match mode {
+        InstallMode::Symlink => "symlink",
+        InstallMode::Copy => "copy",
+        InstallMode::Hardlink => "hardlink",
+    }
+}
+
+#[cfg(unix)]
+pub(crate) fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+pub(crate) fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => (a.dev(), a.ino()) == (b.dev(), b.ino()),
+        _ => false,
+    }
+}
+
+/// Without inode numbers, hard links are as good as the same file when their contents match
+#[cfg(not(unix))]
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((fs::read(a), fs::read(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_files() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let hoon_dir = tmp.path().join("hoon");
+        let package = hoon_dir.join("packages/sequent--latest/lib");
+        fs::create_dir_all(&package).unwrap();
+        fs::create_dir_all(hoon_dir.join("lib")).unwrap();
+        fs::write(package.join("seq.hoon"), "|%  ++  seq  ~  --").unwrap();
+        let place = |mode| {
+            let mut placer = Placer::new(mode, &hoon_dir);
+            placer
+                .place(
+                    &package.join("seq.hoon"),
+                    &hoon_dir.join("lib/seq.hoon"),
+                    Path::new("../packages/sequent--latest/lib/seq.hoon"),
+                )
+                .unwrap();
+            placer.save().unwrap();
+        };
+
+        for mode in [InstallMode::Symlink, InstallMode::Copy, InstallMode::Hardlink] {
+            place(mode);
+            assert!(stale_files(&hoon_dir, mode).unwrap().is_empty());
+        }
+        assert!(!hoon_dir.join("lib/seq.hoon").is_symlink());
+
+        // The package is installed again, leaving the hard link with the old file
+        fs::remove_file(package.join("seq.hoon")).unwrap();
+        fs::write(package.join("seq.hoon"), "|%  ++  seq  ~  --").unwrap();
+        assert_eq!(
+            stale_files(&hoon_dir, InstallMode::Hardlink).unwrap(),
+            ["hoon/lib/seq.hoon"]
+        );
+
+        // A copy is stale once its package changes, and anything after the mode changes
+        place(InstallMode::Copy);
+        assert!(stale_files(&hoon_dir, InstallMode::Copy)
+            .unwrap()
+            .is_empty());
+        fs::write(package.join("seq.hoon"), "|%  ++  seq  !!  --").unwrap();
+        assert_eq!(stale_files(&hoon_dir, InstallMode::Copy).unwrap().len(), 1);
+        place(InstallMode::Copy);
+        assert_eq!(
+            stale_files(&hoon_dir, InstallMode::Symlink).unwrap().len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,391 @@
+// src/commands/package/proxy.rs
+use std::collections::BTreeSet;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::json;
+
+use crate::cache::PackageCache;
+use crate::git_auth::GitAuth;
+use crate::git_fetcher::{GitFetcher, GitSpec};
+use crate::manifest::{DependencySpec, HoonPackage};
+use crate::network::NetworkConfig;
+use crate::output;
+use crate::resolver::VersionSpec;
+
+/// First line of the proxies nockup writes, so that they can be regenerated over
+const PROXY_MARKER: &str = "generated by `nockup package generate-proxy`";
+
+/// A .hoon file a library repository exports
+#[derive(Debug, PartialEq, Eq)]
+struct Export {
+    file: String, // Relative to the repository's Hoon directory, e.g. "lib/math/trig.hoon"
+    structure: bool, // Under sur/, imported with /- rather than /+
+    stem: String, // The name it is installed under in hoon/lib/ or hoon/sur/, e.g. "trig"
+}
+
+impl Export {
+    /// The arm of the proxy that holds it, with structures named apart from libraries
+    fn arm(&self) -> String {
+        if self.structure {
+            format!("sur-{}", self.stem)
+        } else {
+            self.stem.clone()
+        }
+    }
+
+    /// The face it is imported under, which the arm of the same name cannot shadow
+    fn face(&self) -> String {
+        format!(
+            "{}-{}",
+            self.stem,
+            if self.structure { "sur" } else { "lib" }
+        )
+    }
+}
+
+/// Wrap the Hoon libraries of a git repository into the project
+///
+/// The repository is fetched at the head of its default branch, and its lib/ and sur/ files,
+/// under `path` or else its desk/, hoon/ or src/ directory, are added to nockapp.toml as a
+/// dependency pinned to that commit. hoon/lib/<name>.hoon is written as a proxy that imports
+/// them all, so the project reaches them with a single `/+  <name>`. Running it again updates
+/// both to the repository's new head.
+pub async fn run(
+    url: String,
+    path: Option<String>,
+    name: Option<String>,
+    offline: bool,
+) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest_path = cwd.join("nockapp.toml");
+    let mut manifest = match HoonPackage::load(&manifest_path)? {
+        Some(m) => m,
+        None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
+    };
+    let project_dir = cwd.join(&manifest.package.name);
+    if !project_dir.exists() {
+        anyhow::bail!(
+            "Project directory '{}' not found. Run `nockup project init` first.",
+            manifest.package.name
+        );
+    }
+
+    let name = match name {
+        Some(name) => name,
+        None => repository_name(&url)?,
+    };
+    if !is_hoon_name(&name) {
+        anyhow::bail!("'{}' is not a valid Hoon name for the proxy; choose one with --name", name);
+    }
+    // A proxy generated before is regenerated, along with its dependency
+    let proxy_path = project_dir
+        .join("hoon")
+        .join("lib")
+        .join(format!("{}.hoon", name));
+    let regenerating = proxy_path.exists() && is_proxy(&proxy_path)?;
+    if proxy_path.exists() && !regenerating {
+        anyhow::bail!(
+            "{} already exists and was not generated by nockup; choose another name with --name",
+            proxy_path.display()
+        );
+    }
+    if !regenerating
+        && manifest
+            .dependencies
+            .as_ref()
+            .is_some_and(|deps| deps.contains_key(&name))
+    {
+        anyhow::bail!(
+            "Package '{}' is already in dependencies; choose another name with --name", name
+        );
+    }
+
+    say!("{} Generating a proxy for {}...", "📦".cyan(), url.cyan());
+    let fetcher = GitFetcher::new(PackageCache::new()?.git_dir())
+        .with_offline(offline)
+        .with_auth(GitAuth::load()?)
+        .with_network(NetworkConfig::load()?);
+    let mut spec = GitSpec {
+        url: url.clone(),
+        commit: None,
+        tag: None,
+        branch: None,
+        path: path.clone(),
+        install_path: None,
+        file: None,
+    };
+    let commit = fetcher.determine_target_ref(&spec).await?;
+    spec.commit = Some(commit.clone());
+    let repo_path = fetcher.fetch(&spec).await?;
+
+    let source_dir = hoon_source_dir(&repo_path.join(path.as_deref().unwrap_or_default()))?;
+    let exports = hoon_exports(&source_dir)?;
+    if exports.is_empty() {
+        anyhow::bail!("No .hoon files in lib/ or sur/ of {}", source_dir.display());
+    }
+    if let Some(export) = exports.iter().find(|e| !e.structure && e.stem == name) {
+        anyhow::bail!(
+            "{} would be installed over the proxy; choose another name with --name", export.file
+        );
+    }
+    for export in &exports {
+        say!("  {} {}", "→".cyan(), export.file);
+    }
+
+    // The dependency takes exactly the exported files, from the directory they were found in
+    let relative_source = source_dir
+        .strip_prefix(&repo_path)
+        .expect("the Hoon directory is inside the repository")
+        .to_string_lossy()
+        .into_owned();
+    let mut dependency = VersionSpec::Commit(commit.clone()).to_dependency_spec(Some(url.clone()));
+    if let DependencySpec::Full { path, files, .. } = &mut dependency {
+        *path = (!relative_source.is_empty()).then_some(relative_source);
+        *files = Some(
+            exports
+                .iter()
+                .map(|export| export.file.trim_end_matches(".hoon").to_string())
+                .collect(),
+        );
+    }
+    manifest
+        .dependencies
+        .get_or_insert_with(std::collections::BTreeMap::new)
+        .insert(name.clone(), dependency);
+
+    let proxy = proxy_source(&name, &url, &commit, &exports)?;
+    if let Some(parent) = proxy_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&proxy_path, proxy)
+        .with_context(|| format!("Failed to write {}", proxy_path.display()))?;
+    manifest.save(&manifest_path)?;
+
+    say!(
+        "{} Added {} to nockapp.toml and wrote {}",
+        "✓".green(),
+        name.yellow(),
+        format!("hoon/lib/{}.hoon", name).cyan()
+    );
+    say!(
+        "  Run {} to install it, then import it with {}",
+        "nockup package install".cyan(),
+        format!("/+  {}", name).cyan()
+    );
+    output::emit(
+        "proxy",
+        json!({
+            "name": name,
+            "url": url,
+            "commit": commit,
+            "files": exports.iter().map(|e| &e.file).collect::<Vec<_>>(),
+            "proxy": format!("hoon/lib/{}.hoon", name),
+        }),
+    );
+    Ok(())
+}
+
+/// The directory of a repository that holds its lib/ and sur/
+///
+/// That is `base` itself, or its desk/, hoon/ or src/ directory, as for installed packages.
+fn hoon_source_dir(base: &Path) -> Result<PathBuf> {
+    let has_exports = |dir: &Path| dir.join("lib").is_dir() || dir.join("sur").is_dir();
+    [base.to_path_buf(), base.join("desk"), base.join("hoon"), base.join("src")]
+        .into_iter()
+        .find(|dir| has_exports(dir))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No lib/ or sur/ directory in {}, or in a desk/, hoon/ or src/ directory of it",
+                base.display()
+            )
+        })
+}
+
+/// The .hoon files under lib/ and sur/ of `source_dir`, in order
+///
+/// Installed packages' files are flattened into hoon/lib/ and hoon/sur/, so two files with the
+/// same name in one of them cannot both be exported. Files whose names are not Hoon names cannot
+/// be imported, and are left out.
+fn hoon_exports(source_dir: &Path) -> Result<Vec<Export>> {
+    let mut exports = Vec::new();
+    for (kind, structure) in [("lib", false), ("sur", true)] {
+        let mut files = Vec::new();
+        collect_hoon_files(source_dir, &source_dir.join(kind), &mut files)?;
+        files.sort();
+
+        let mut stems = BTreeSet::new();
+        for file in files {
+            let stem = Path::new(&file)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if !is_hoon_name(&stem) {
+                say!(
+                    "  {} Skipping {}, whose name cannot be imported",
+                    "⚠".yellow(),
+                    file
+                );
+                continue;
+            }
+            if !stems.insert(stem.clone()) {
+                anyhow::bail!(
+                    "More than one file in {}/ is named {}.hoon, and they would be installed \
+                    over each other",
+                    kind,
+                    stem
+                );
+            }
+            exports.push(Export {
+                file,
+                structure,
+                stem,
+            });
+        }
+    }
+    Ok(exports)
+}
+
+fn collect_hoon_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_hoon_files(root, &path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "hoon") {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    Ok(())
+}
+
+/// The proxy library: each export imported under its own face and re-exported as an arm
+fn proxy_source(name: &str, url: &str, commit: &str, exports: &[Export]) -> Result<String> {
+    let mut source = format!(
+        "::  {}\n::\n::  /lib/{}: the Hoon libraries of {}\n::  at commit {}\n::\n",
+        PROXY_MARKER, name, url, commit
+    );
+    let mut arms = BTreeSet::new();
+    for export in exports {
+        if !arms.insert(export.arm()) {
+            anyhow::bail!(
+                "{} and another export would both be the arm {} of the proxy",
+                export.file,
+                export.arm()
+            );
+        }
+    }
+
+    let imports = |structure: bool| -> Vec<String> {
+        exports
+            .iter()
+            .filter(|e| e.structure == structure)
+            .map(|e| format!("{}={}", e.face(), e.stem))
+            .collect()
+    };
+    for (rune, structure) in [("/-", true), ("/+", false)] {
+        let imports = imports(structure);
+        if !imports.is_empty() {
+            source.push_str(&format!("{}  {}\n", rune, imports.join(", ")));
+        }
+    }
+    source.push_str("|%\n");
+    for export in exports {
+        source.push_str(&format!("++  {}  {}\n", export.arm(), export.face()));
+    }
+    source.push_str("--\n");
+    Ok(source)
+}
+
+/// Whether a hoon/lib/ file is a proxy nockup wrote
+fn is_proxy(path: &Path) -> Result<bool> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .next()
+        .is_some_and(|line| line.contains(PROXY_MARKER)))
+}
+
+/// The last segment of a repository URL, e.g. "numerics" for https://github.com/urbit/numerics
+fn repository_name(url: &str) -> Result<String> {
+    url.trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .map(|name| name.trim_end_matches(".git").to_lowercase())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Cannot name a proxy for {}; choose one with --name", url))
+}
+
+/// Whether `name` can name a Hoon face, arm or library file: lowercase letters, digits and
+/// hyphens, starting with a letter
+fn is_hoon_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let repo = tmp.path().join("numerics");
+        let desk = repo.join("lagoon/desk");
+        for file in [
+            "lib/lagoon.hoon", "lib/math/trig.hoon", "lib/Notes.hoon", "sur/lagoon.hoon",
+            "mar/lagoon.hoon",
+        ] {
+            let path = desk.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "~").unwrap();
+        }
+
+        assert!(hoon_source_dir(&repo).is_err());
+        let source_dir = hoon_source_dir(&repo.join("lagoon")).unwrap();
+        assert_eq!(source_dir, desk);
+
+        let exports = hoon_exports(&source_dir).unwrap();
+        let files: Vec<&str> = exports.iter().map(|e| e.file.as_str()).collect();
+        assert_eq!(
+            files,
+            ["lib/lagoon.hoon", "lib/math/trig.hoon", "sur/lagoon.hoon"]
+        );
+
+        let proxy = proxy_source(
+            "numerics", "https://github.com/urbit/numerics", "01905f3", &exports,
+        )
+        .unwrap();
+        assert!(proxy.starts_with(&format!("::  {}\n", PROXY_MARKER)));
+        assert!(proxy.contains("/-  lagoon-sur=lagoon\n/+  lagoon-lib=lagoon, trig-lib=trig\n"));
+        assert!(proxy.ends_with(
+            "|%\n++  lagoon  lagoon-lib\n++  trig  trig-lib\n++  sur-lagoon  lagoon-sur\n--\n"
+        ));
+
+        // Nested files are installed flat, so their names must not collide
+        std::fs::write(desk.join("lib/math/lagoon.hoon"), "~").unwrap();
+        assert!(hoon_exports(&source_dir).is_err());
+
+        assert_eq!(
+            repository_name("https://github.com/urbit/numerics.git").unwrap(),
+            "numerics"
+        );
+        assert_eq!(
+            repository_name("git@github.com:lynko/Re-Hoon").unwrap(),
+            "re-hoon"
+        );
+        assert!(is_hoon_name("re-hoon2"));
+        assert!(!is_hoon_name("re.hoon"));
+        assert!(!is_hoon_name("2re"));
+    }
+}
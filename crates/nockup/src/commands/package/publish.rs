@@ -0,0 +1,309 @@
+// src/commands/package/publish.rs
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use tokio::process::Command;
+
+use crate::credentials;
+use crate::manifest::{DependencySpec, HoonPackage};
+use crate::resolver::registry::{
+    self, Package, RegistryToml, Workspace, PUBLISH_TOKEN_ENV, PUBLISH_URL_ENV,
+};
+
+/// Directory holding a library's sources, as created by `nockup package init`
+const SOURCE_DIR: &str = "src";
+
+/// Entry point of a library
+const LIB_FILE: &str = "lib.hoon";
+
+/// Where the package's sources live, for the registry's workspace entry
+struct GitSource {
+    url: String,
+    commit: String,
+    // Path of the package within the repository, empty at its root
+    root_path: String,
+}
+
+/// Publish the hoon.toml library in the current directory to the registry
+pub async fn run(registry: Option<String>, git_url: Option<String>, dry_run: bool) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest_path = cwd.join("hoon.toml");
+
+    let manifest = match HoonPackage::load(&manifest_path)? {
+        Some(m) => m,
+        None => anyhow::bail!("No hoon.toml found in {}", cwd.display()),
+    };
+
+    say!(
+        "{} Publishing {}...",
+        "📦".cyan(),
+        manifest.package.name.yellow()
+    );
+
+    validate(&manifest, &cwd)?;
+    let source = git_source(&cwd, git_url).await?;
+    let entry = registry_entry(&manifest, &cwd, source)?;
+
+    let package = &entry.package[0];
+    say!(
+        "  {} {} files, {} dependencies",
+        "→".cyan(),
+        package.files.len(),
+        package.dependencies.len()
+    );
+
+    if dry_run {
+        say!("\nDry run: would publish this registry entry:\n");
+        say!("{}", toml::to_string_pretty(&entry)?);
+        return Ok(());
+    }
+
+    let endpoint = registry
+        .or_else(|| env::var(PUBLISH_URL_ENV).ok())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No registry to publish to. Pass --registry <URL> or set {}", PUBLISH_URL_ENV
+            )
+        })?;
+    let token = match env::var(PUBLISH_TOKEN_ENV) {
+        Ok(token) => Some(token),
+        Err(_) => credentials::token_for(&endpoint)?,
+    };
+
+    registry::publish(&endpoint, token.as_deref(), &entry).await?;
+
+    say!(
+        "{} Published {} to {}",
+        "✓".green(),
+        manifest.package.name.yellow(),
+        endpoint
+    );
+
+    Ok(())
+}
+
+/// Check that the manifest describes a library the registry can serve
+fn validate(manifest: &HoonPackage, package_dir: &Path) -> Result<()> {
+    let mut problems = Vec::new();
+    let meta = &manifest.package;
+
+    if meta.name.is_empty() {
+        problems.push("package name is empty".to_string());
+    } else if let Some(c) = meta
+        .name
+        .chars()
+        .find(|&c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '_' | '/'))
+    {
+        problems.push(format!("package name contains invalid character '{}'", c));
+    }
+
+    match &meta.version {
+        None => problems.push("package version is required to publish".to_string()),
+        Some(v) => {
+            if let Err(e) = semver::Version::parse(v) {
+                problems.push(format!(
+                    "package version '{}' is not valid semver: {}",
+                    v, e
+                ));
+            }
+        }
+    }
+
+    if !package_dir.join(SOURCE_DIR).join(LIB_FILE).is_file() {
+        problems.push(format!("{}/{} not found", SOURCE_DIR, LIB_FILE));
+    }
+
+    for (name, spec) in manifest.dependencies.iter().flatten() {
+        if let DependencySpec::Full { path: Some(_), .. } = spec {
+            problems.push(format!(
+                "dependency '{}' is a local path, which the registry cannot resolve",
+                name
+            ));
+        }
+    }
+
+    if !problems.is_empty() {
+        anyhow::bail!("Invalid hoon.toml:\n  - {}", problems.join("\n  - "));
+    }
+    Ok(())
+}
+
+/// Find the repository, commit and path the package is published from
+async fn git_source(package_dir: &Path, git_url: Option<String>) -> Result<GitSource> {
+    let status = git(package_dir, &["status", "--porcelain", "--", "."]).await?;
+    if !status.is_empty() {
+        anyhow::bail!("Package has uncommitted changes; commit them before publishing");
+    }
+
+    let url = match git_url {
+        Some(url) => url,
+        None => git(package_dir, &["remote", "get-url", "origin"])
+            .await
+            .context("No origin remote; pass --git-url")?,
+    };
+    let commit = git(package_dir, &["rev-parse", "HEAD"]).await?;
+    let root_path = git(package_dir, &["rev-parse", "--show-prefix"]).await?;
+
+    Ok(GitSource {
+        url,
+        commit,
+        root_path: root_path.trim_end_matches('/').to_string(),
+    })
+}
+
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Build the registry entry for the package: its workspace and its package record
+fn registry_entry(
+    manifest: &HoonPackage,
+    package_dir: &Path,
+    source: GitSource,
+) -> Result<RegistryToml> {
+    let name = manifest.package.name.clone();
+    let source_dir = package_dir.join(SOURCE_DIR);
+
+    let mut files = Vec::new();
+    collect_hoon_files(&source_dir, &source_dir, &mut files)?;
+    files.sort();
+
+    let workspace = Workspace {
+        git_url: source.url,
+        git_ref: source.commit,
+        description: manifest.package.description.clone(),
+        root_path: source.root_path,
+    };
+    let package = Package {
+        name: name.clone(),
+        workspace: name.clone(),
+        path: SOURCE_DIR.to_string(),
+        file: LIB_FILE.to_string(),
+        files,
+        dependencies: manifest
+            .dependencies
+            .iter()
+            .flatten()
+            .map(|(dep, _)| dep.clone())
+            .collect(),
+    };
+
+    Ok(RegistryToml {
+        workspace: HashMap::from([(name, workspace)]),
+        package: vec![package],
+        alias: Vec::new(),
+        advisory: Vec::new(),
+        yanked: Vec::new(),
+        deprecated: Vec::new(),
+    })
+}
+
+fn collect_hoon_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path: PathBuf = entry?.path();
+        if path.is_dir() {
+            collect_hoon_files(root, &path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "hoon") {
+            let relative = path.strip_prefix(root)?;
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::manifest::PackageMeta;
+
+    fn library(dir: &Path, version: Option<&str>) -> HoonPackage {
+        fs::create_dir_all(dir.join("src/util")).unwrap();
+        fs::write(dir.join("src/lib.hoon"), "~").unwrap();
+        fs::write(dir.join("src/util/math.hoon"), "~").unwrap();
+        fs::write(dir.join("src/README.md"), "").unwrap();
+        HoonPackage {
+            package: PackageMeta {
+                name: "my-lib".to_string(),
+                version: version.map(str::to_string),
+                description: Some("A library".to_string()),
+                ..Default::default()
+            },
+            dependencies: Some(BTreeMap::from([(
+                "urbit/zuse".to_string(),
+                DependencySpec::Simple("@k409".to_string()),
+            )])),
+            registries: None,
+            patch: None,
+            workspace: None,
+        }
+    }
+
+    #[test]
+    fn test_registry_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = library(tmp.path(), Some("0.1.0"));
+        validate(&manifest, tmp.path()).unwrap();
+
+        let source = GitSource {
+            url: "https://github.com/example/libs".to_string(),
+            commit: "abc123".to_string(),
+            root_path: "my-lib".to_string(),
+        };
+        let entry = registry_entry(&manifest, tmp.path(), source).unwrap();
+
+        let package = &entry.package[0];
+        assert_eq!(package.workspace, "my-lib");
+        assert_eq!(package.files, vec!["lib.hoon", "util/math.hoon"]);
+        assert_eq!(package.dependencies, vec!["urbit/zuse"]);
+        assert_eq!(entry.workspace["my-lib"].git_ref, "abc123");
+
+        // The entry reads back as registry TOML
+        let parsed: RegistryToml =
+            toml::from_str(&toml::to_string_pretty(&entry).unwrap()).unwrap();
+        assert_eq!(parsed.package[0].files, package.files);
+        assert_eq!(parsed.workspace["my-lib"].root_path, "my-lib");
+    }
+
+    #[test]
+    fn test_validate_rejects_unpublishable_manifests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut manifest = library(tmp.path(), None);
+        manifest.dependencies.as_mut().unwrap().insert(
+            "local".to_string(),
+            DependencySpec::Full {
+                version: None,
+                git: None,
+                commit: None,
+                tag: None,
+                branch: None,
+                path: Some("../local".to_string()),
+                files: None,
+                kelvin: None,
+            },
+        );
+
+        let err = validate(&manifest, tmp.path()).unwrap_err().to_string();
+        assert!(err.contains("version is required"));
+        assert!(err.contains("'local' is a local path"));
+    }
+}
@@ -11,11 +11,11 @@ pub async fn purge(dry_run: bool) -> Result<()> {
     let packages_cache = cache_dir.join("cache").join("packages");
 
     if !packages_cache.exists() {
-        println!("No package cache found at {}", packages_cache.display());
+        say!("No package cache found at {}", packages_cache.display());
         return Ok(());
     }
 
-    println!("Package cache location: {}", packages_cache.display());
+    say!("Package cache location: {}", packages_cache.display());
 
     // Collect all cached packages
     let mut total_size = 0u64;
@@ -28,7 +28,7 @@ pub async fn purge(dry_run: bool) -> Result<()> {
                 package_count += 1;
 
                 if dry_run {
-                    println!("  Would delete: {}", entry.file_name().to_string_lossy());
+                    say!("  Would delete: {}", entry.file_name().to_string_lossy());
                 }
             }
         }
@@ -37,29 +37,33 @@ pub async fn purge(dry_run: bool) -> Result<()> {
     let size_mb = total_size as f64 / 1_048_576.0;
 
     if dry_run {
-        println!(
-            "\nDry run: {} packages would be deleted ({:.2} MB)",
-            package_count, size_mb
-        );
-        println!("Run without --dry-run to actually delete the cache");
+        say!("\nDry run: {} packages would be deleted ({:.2} MB)", package_count, size_mb);
+        say!("Run without --dry-run to actually delete the cache");
     } else {
         if package_count == 0 {
-            println!("Cache is already empty");
+            say!("Cache is already empty");
             return Ok(());
         }
 
-        println!("Deleting {} packages ({:.2} MB)...", package_count, size_mb);
+        say!("Deleting {} packages ({:.2} MB)...", package_count, size_mb);
         fs::remove_dir_all(&packages_cache)?;
         fs::create_dir_all(&packages_cache)?;
 
+        // The package files were hardlinks into the content-addressed store
+        let cas = cache_dir.join("cache").join("cas");
+        if cas.exists() {
+            fs::remove_dir_all(&cas)?;
+            fs::create_dir_all(&cas)?;
+        }
+
         // Also clear the cache index
         let cache_index = cache_dir.join("cache").join("cache-index.json");
         if cache_index.exists() {
             fs::remove_file(&cache_index)?;
-            println!("Cache index cleared");
+            say!("Cache index cleared");
         }
 
-        println!("Package cache cleared successfully");
+        say!("Package cache cleared successfully");
     }
 
     Ok(())
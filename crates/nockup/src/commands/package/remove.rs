@@ -15,7 +15,7 @@ pub async fn run(package_name: String) -> Result<()> {
         anyhow::bail!("No nockapp.toml found in current directory");
     }
 
-    println!(
+    say!(
         "{} Removing dependency {}...",
         "📦".cyan(),
         package_name.yellow()
@@ -55,7 +55,7 @@ pub async fn run(package_name: String) -> Result<()> {
     // Save the manifest
     manifest.save(&manifest_path)?;
 
-    println!(
+    say!(
         "{} Removed {} from nockapp.toml",
         "✓".green(),
         package_name.yellow()
@@ -73,7 +73,7 @@ pub async fn run(package_name: String) -> Result<()> {
                 // Check if directory name starts with "packagename@"
                 if dir_name_str.starts_with(&format!("{}@", package_name)) {
                     let package_path = entry.path();
-                    println!("  {} Removing {}", "🗑".cyan(), dir_name_str.yellow());
+                    say!("  {} Removing {}", "🗑".cyan(), dir_name_str.yellow());
                     fs::remove_dir_all(&package_path)
                         .with_context(|| format!("Failed to remove {}", package_path.display()))?;
                 }
@@ -84,7 +84,7 @@ pub async fn run(package_name: String) -> Result<()> {
     // Clean up symlinks in hoon/lib
     let lib_dir = project_dir.join("hoon").join("lib");
     if lib_dir.exists() {
-        println!("  {} Cleaning up symlinks in hoon/lib/", "🧹".cyan());
+        say!("  {} Cleaning up symlinks in hoon/lib/", "🧹".cyan());
 
         if let Ok(entries) = fs::read_dir(&lib_dir) {
             for entry in entries.flatten() {
@@ -102,7 +102,7 @@ pub async fn run(package_name: String) -> Result<()> {
                                 .file_name()
                                 .map(|name| name.to_string_lossy().into_owned())
                                 .unwrap_or_else(|| path.display().to_string());
-                            println!("    {} Removing symlink {}", "→".cyan(), file_name.yellow());
+                            say!("    {} Removing symlink {}", "→".cyan(), file_name.yellow());
                             fs::remove_file(&path).with_context(|| {
                                 format!("Failed to remove symlink {}", path.display())
                             })?;
@@ -113,7 +113,7 @@ pub async fn run(package_name: String) -> Result<()> {
         }
     }
 
-    println!(
+    say!(
         "  Run {} to update dependencies",
         "nockup package install".cyan()
     );
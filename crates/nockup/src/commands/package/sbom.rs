@@ -0,0 +1,467 @@
+// src/commands/package/sbom.rs
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::manifest::{HoonPackage, LockSource, LockedPackage, NockAppLock};
+
+/// Software bill of materials formats
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 JSON
+    #[default]
+    Cyclonedx,
+    /// SPDX 2.3 JSON
+    Spdx,
+}
+
+/// Write a bill of materials for the packages in nockapp.lock to `output`, or stdout
+pub async fn run(format: SbomFormat, output: Option<PathBuf>) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest = match HoonPackage::load(&cwd.join("nockapp.toml"))? {
+        Some(m) => m,
+        None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
+    };
+
+    let lock_path = cwd.join(&manifest.package.name).join("nockapp.lock");
+    if !lock_path.exists() {
+        anyhow::bail!("No nockapp.lock found; run `nockup package install` first");
+    }
+    let lock = NockAppLock::load(&lock_path)?;
+
+    let created = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let document = match format {
+        SbomFormat::Cyclonedx => cyclonedx(&manifest, &lock, &created),
+        SbomFormat::Spdx => spdx(&manifest, &lock, &created),
+    };
+    let document = serde_json::to_string_pretty(&document)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, document + "\n")
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            say!(
+                "{} Wrote a bill of materials for {} packages to {}",
+                "✓".green(),
+                lock.package.len(),
+                path.display().to_string().cyan()
+            );
+        }
+        // The document is the output, so nothing else is printed
+        None => println!("{}", document),
+    }
+    Ok(())
+}
+
+/// A CycloneDX 1.5 document, with the project as its subject
+fn cyclonedx(manifest: &HoonPackage, lock: &NockAppLock, created: &str) -> Value {
+    let project = &manifest.package;
+    let project_ref = bom_ref(&project.name);
+
+    let components: Vec<Value> = lock
+        .package
+        .iter()
+        .map(|pkg| {
+            let mut component = json!({
+                "type": "library",
+                "bom-ref": bom_ref(&pkg.name),
+                "name": pkg.name,
+                "version": pkg.version,
+                "purl": purl(pkg),
+            });
+            if let Some(license) = &pkg.license {
+                component["licenses"] = json!([cyclonedx_license(license)]);
+            }
+            if let LockSource::Git { url, commit, .. } = &pkg.source {
+                component["externalReferences"] = json!([{
+                    "type": "vcs",
+                    "url": url,
+                    "comment": format!("commit {}", commit),
+                }]);
+            }
+            // nockup's checksum is of the package contents, not of a downloadable archive
+            if let Some(checksum) = &pkg.checksum {
+                component["properties"] = json!([{ "name": "nockup:checksum", "value": checksum }]);
+            }
+            component
+        })
+        .collect();
+
+    let direct: Vec<String> = direct_dependencies(manifest, lock)
+        .map(|pkg| bom_ref(&pkg.name))
+        .collect();
+    let mut dependencies = vec![json!({ "ref": project_ref, "dependsOn": direct })];
+    for pkg in &lock.package {
+        dependencies.push(json!({
+            "ref": bom_ref(&pkg.name),
+            "dependsOn": pkg.dependencies.keys().map(|dep| bom_ref(dep)).collect::<Vec<_>>(),
+        }));
+    }
+
+    let mut subject = json!({
+        "type": "application",
+        "bom-ref": project_ref,
+        "name": project.name,
+    });
+    if let Some(version) = &project.version {
+        subject["version"] = json!(version);
+    }
+    if let Some(license) = &project.license {
+        subject["licenses"] = json!([cyclonedx_license(license)]);
+    }
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "serialNumber": format!("urn:uuid:{}", document_id(lock, created)),
+        "version": 1,
+        "metadata": {
+            "timestamp": created,
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "nockup",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+            "component": subject,
+        },
+        "components": components,
+        "dependencies": dependencies,
+    })
+}
+
+/// An SPDX 2.3 document, describing the project
+fn spdx(manifest: &HoonPackage, lock: &NockAppLock, created: &str) -> Value {
+    let project = &manifest.package;
+    let project_id = spdx_id(&project.name);
+
+    let mut packages = vec![json!({
+        "name": project.name,
+        "SPDXID": project_id,
+        "versionInfo": project.version.as_deref().unwrap_or("NOASSERTION"),
+        "downloadLocation": "NOASSERTION",
+        "filesAnalyzed": false,
+        "licenseConcluded": "NOASSERTION",
+        "licenseDeclared": spdx_license(project.license.as_deref()),
+        "copyrightText": "NOASSERTION",
+        "primaryPackagePurpose": "APPLICATION",
+    })];
+    for pkg in &lock.package {
+        let download_location = match &pkg.source {
+            LockSource::Git { url, commit, .. } => format!("git+{}@{}", url, commit),
+            LockSource::Path { .. } => "NOASSERTION".to_string(),
+        };
+        let mut package = json!({
+            "name": pkg.name,
+            "SPDXID": spdx_id(&pkg.name),
+            "versionInfo": pkg.version,
+            "downloadLocation": download_location,
+            "filesAnalyzed": false,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": spdx_license(pkg.license.as_deref()),
+            "copyrightText": "NOASSERTION",
+            "primaryPackagePurpose": "LIBRARY",
+            "externalRefs": [{
+                "referenceCategory": "PACKAGE-MANAGER",
+                "referenceType": "purl",
+                "referenceLocator": purl(pkg),
+            }],
+        });
+        if let Some(checksum) = &pkg.checksum {
+            package["comment"] = json!(format!(
+                "nockup checksum of the package contents: {}",
+                checksum
+            ));
+        }
+        packages.push(package);
+    }
+
+    let mut relationships = vec![json!({
+        "spdxElementId": "SPDXRef-DOCUMENT",
+        "relationshipType": "DESCRIBES",
+        "relatedSpdxElement": project_id,
+    })];
+    for pkg in direct_dependencies(manifest, lock) {
+        relationships.push(json!({
+            "spdxElementId": project_id,
+            "relationshipType": "DEPENDS_ON",
+            "relatedSpdxElement": spdx_id(&pkg.name),
+        }));
+    }
+    for pkg in &lock.package {
+        for dep in pkg.dependencies.keys() {
+            relationships.push(json!({
+                "spdxElementId": spdx_id(&pkg.name),
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": spdx_id(dep),
+            }));
+        }
+    }
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("{}-sbom", project.name),
+        "documentNamespace": format!(
+            "https://spdx.org/spdxdocs/{}-{}",
+            sanitize(&project.name),
+            document_id(lock, created)
+        ),
+        "creationInfo": {
+            "created": created,
+            "creators": [format!("Tool: nockup-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+/// The locked packages the manifest asks for itself
+fn direct_dependencies<'a>(
+    manifest: &'a HoonPackage,
+    lock: &'a NockAppLock,
+) -> impl Iterator<Item = &'a LockedPackage> {
+    lock.package.iter().filter(|pkg| {
+        manifest
+            .dependencies
+            .as_ref()
+            .is_some_and(|deps| deps.contains_key(&pkg.name))
+    })
+}
+
+/// Package URL of a locked package: pkg:github/<owner>/<repo>@<commit> for GitHub repositories,
+/// else a generic purl naming the repository
+fn purl(pkg: &LockedPackage) -> String {
+    let LockSource::Git { url, commit, .. } = &pkg.source else {
+        return format!("pkg:generic/{}@{}", encode(&pkg.name), encode(&pkg.version));
+    };
+    let repo = url.trim_end_matches('/').trim_end_matches(".git");
+    if let Some(path) = repo.strip_prefix("https://github.com/") {
+        if let Some((owner, name)) = path.split_once('/') {
+            return format!(
+                "pkg:github/{}/{}@{}",
+                owner.to_lowercase(),
+                name.to_lowercase(),
+                commit
+            );
+        }
+    }
+    format!(
+        "pkg:generic/{}@{}?vcs_url={}",
+        encode(&pkg.name),
+        commit,
+        encode(&format!("git+{}@{}", url, commit))
+    )
+}
+
+/// Percent-encode everything but unreserved characters, as purl components need
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn bom_ref(name: &str) -> String {
+    format!("nockup:{}", name)
+}
+
+fn spdx_id(name: &str) -> String {
+    format!("SPDXRef-Package-{}", sanitize(name))
+}
+
+/// Letters, digits, '.' and '-' only, as SPDX identifiers allow
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Whether a declared license reads as an SPDX license expression, e.g. "MIT OR Apache-2.0":
+/// identifiers joined by AND, OR or WITH
+fn is_spdx_expression(license: &str) -> bool {
+    let tokens: Vec<&str> = license
+        .split_whitespace()
+        .map(|token| token.trim_matches(|c| c == '(' || c == ')'))
+        .collect();
+    tokens.len() % 2 == 1
+        && tokens.iter().enumerate().all(|(i, token)| {
+            if i % 2 == 1 {
+                matches!(*token, "AND" | "OR" | "WITH")
+            } else {
+                !token.is_empty()
+                    && token
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+            }
+        })
+}
+
+fn cyclonedx_license(license: &str) -> Value {
+    if !is_spdx_expression(license) {
+        json!({ "license": { "name": license } })
+    } else if license.contains(' ') {
+        json!({ "expression": license })
+    } else {
+        json!({ "license": { "id": license } })
+    }
+}
+
+fn spdx_license(license: Option<&str>) -> String {
+    match license {
+        Some(license) if is_spdx_expression(license) => license.to_string(),
+        _ => "NOASSERTION".to_string(),
+    }
+}
+
+/// A version 4 UUID identifying this document, from the lock and when it was made
+fn document_id(lock: &NockAppLock, created: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(created.as_bytes());
+    for pkg in &lock.package {
+        hasher.update(pkg.name.as_bytes());
+        hasher.update([0]);
+        hasher.update(pkg.version.as_bytes());
+        hasher.update([0]);
+        if let LockSource::Git { commit, .. } = &pkg.source {
+            hasher.update(commit.as_bytes());
+        }
+        hasher.update([0]);
+    }
+    let mut bytes: [u8; 16] = hasher.finalize()[..16].try_into().unwrap_or_default();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> (HoonPackage, NockAppLock) {
+        let manifest: HoonPackage = toml::from_str(
+            r#"
+            [package]
+            name = "arcade"
+            version = "0.1.0"
+            license = "MIT"
+
+            [dependencies]
+            lagoon = "^1.2.0"
+            "#,
+        )
+        .unwrap();
+        let lock: NockAppLock = toml::from_str(
+            r#"
+            version = 2
+
+            [[package]]
+            name = "lagoon"
+            version = "^1.2.0"
+            license = "MIT OR Apache-2.0"
+            checksum = "sha256:00ff"
+            source = { type = "git", url = "https://github.com/Urbit/numerics.git", commit = "4f2c9a1" }
+            dependencies = { "urbit/zuse" = "k409" }
+
+            [[package]]
+            name = "urbit/zuse"
+            version = "k409"
+            license = "Urbit License"
+            source = { type = "git", url = "https://git.example.com/urbit", commit = "abc123" }
+            "#,
+        )
+        .unwrap();
+        (manifest, lock)
+    }
+
+    #[test]
+    fn test_cyclonedx() {
+        let (manifest, lock) = project();
+        let bom = cyclonedx(&manifest, &lock, "2025-01-01T00:00:00Z");
+        assert_eq!(bom["metadata"]["component"]["name"], "arcade");
+
+        let lagoon = &bom["components"][0];
+        assert_eq!(lagoon["purl"], "pkg:github/urbit/numerics@4f2c9a1");
+        assert_eq!(lagoon["licenses"][0]["expression"], "MIT OR Apache-2.0");
+        assert_eq!(lagoon["properties"][0]["value"], "sha256:00ff");
+        let zuse = &bom["components"][1];
+        assert_eq!(
+            zuse["purl"],
+            "pkg:generic/urbit%2Fzuse@abc123?vcs_url=git%2Bhttps%3A%2F%2Fgit.example.com%2Furbit%40abc123"
+        );
+        assert_eq!(zuse["licenses"][0]["license"]["name"], "Urbit License");
+
+        assert_eq!(
+            bom["dependencies"],
+            json!([
+                { "ref": "nockup:arcade", "dependsOn": ["nockup:lagoon"] },
+                { "ref": "nockup:lagoon", "dependsOn": ["nockup:urbit/zuse"] },
+                { "ref": "nockup:urbit/zuse", "dependsOn": [] },
+            ])
+        );
+        let serial = bom["serialNumber"].as_str().unwrap();
+        assert_eq!(serial.len(), "urn:uuid:".len() + 36);
+        assert_eq!(&serial[23..24], "4");
+    }
+
+    #[test]
+    fn test_spdx() {
+        let (manifest, lock) = project();
+        let doc = spdx(&manifest, &lock, "2025-01-01T00:00:00Z");
+
+        let packages = doc["packages"].as_array().unwrap();
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[0]["SPDXID"], "SPDXRef-Package-arcade");
+        assert_eq!(packages[0]["licenseDeclared"], "MIT");
+        assert_eq!(
+            packages[1]["downloadLocation"],
+            "git+https://github.com/Urbit/numerics.git@4f2c9a1"
+        );
+        assert_eq!(packages[2]["SPDXID"], "SPDXRef-Package-urbit-zuse");
+        assert_eq!(packages[2]["licenseDeclared"], "NOASSERTION");
+
+        let relationships: Vec<(&str, &str)> = doc["relationships"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                (
+                    r["spdxElementId"].as_str().unwrap(),
+                    r["relatedSpdxElement"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            relationships,
+            [
+                ("SPDXRef-DOCUMENT", "SPDXRef-Package-arcade"),
+                ("SPDXRef-Package-arcade", "SPDXRef-Package-lagoon"),
+                ("SPDXRef-Package-lagoon", "SPDXRef-Package-urbit-zuse"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+// src/commands/package/search.rs
+use std::env;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::manifest::HoonPackage;
+use crate::resolver::registry;
+
+/// Search the package registries and print how to add each match
+///
+/// Inside a project, the registries of its nockapp.toml are searched too.
+pub async fn run(query: Option<String>, offline: bool) -> Result<()> {
+    let query = query.unwrap_or_default();
+    let manifest = HoonPackage::load(&env::current_dir()?.join("nockapp.toml"))?;
+    let sources = registry::registry_sources(manifest.as_ref())?;
+    let results = registry::search(&query, &sources, offline).await?;
+
+    if results.is_empty() {
+        say!("{} No packages match '{}'", "🔍".cyan(), query.yellow());
+        return Ok(());
+    }
+
+    if query.is_empty() {
+        say!(
+            "{} {} packages in the registry:",
+            "🔍".cyan(),
+            results.len()
+        );
+    } else {
+        say!(
+            "{} {} packages matching '{}':",
+            "🔍".cyan(),
+            results.len(),
+            query.yellow()
+        );
+    }
+
+    for result in &results {
+        say!();
+        match &result.description {
+            Some(description) => say!("  {} - {}", result.name.green().bold(), description),
+            None => say!("  {}", result.name.green().bold()),
+        }
+        say!("    {} {}", "registry:".dimmed(), result.registry);
+        if !result.git_url.is_empty() {
+            say!("    {} {}", "source:".dimmed(), result.git_url);
+        }
+        if !result.aliases.is_empty() {
+            say!("    {} {}", "aliases:".dimmed(), result.aliases.join(", "));
+        }
+        if !result.dependencies.is_empty() {
+            say!(
+                "    {} {}",
+                "depends on:".dimmed(),
+                result.dependencies.join(", ")
+            );
+        }
+        say!(
+            "    {} {}",
+            "install:".dimmed(),
+            format!("nockup package add {}", result.name).cyan()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+// src/commands/package/tree.rs
+use std::collections::HashSet;
+use std::env;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use super::install::install_versions;
+use crate::manifest::{HoonPackage, NockAppLock};
+use crate::resolver::local::local_packages;
+use crate::resolver::registry::registry_sources;
+use crate::resolver::{ResolvedGraph, Resolver};
+
+/// Print the resolved dependency graph of the project in the current directory
+pub async fn run(duplicates: bool, offline: bool) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest_path = cwd.join("nockapp.toml");
+
+    let manifest = match HoonPackage::load(&manifest_path)? {
+        Some(m) => m,
+        None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
+    };
+
+    let lock_path = cwd.join(&manifest.package.name).join("nockapp.lock");
+    let resolver = Resolver::new(offline)?
+        .with_registries(registry_sources(Some(&manifest))?)
+        .with_patches(manifest.patch.clone().unwrap_or_default(), &cwd)
+        .with_local_packages(local_packages(&manifest, &cwd)?)
+        .with_lock(&NockAppLock::load(&lock_path)?);
+    let graph = resolver.resolve(&manifest).await?;
+
+    let duplicated = graph.duplicates();
+    let highlight: HashSet<&str> = if duplicates {
+        duplicated.iter().map(|(name, _)| *name).collect()
+    } else {
+        HashSet::new()
+    };
+
+    say!();
+    say!("{}", manifest.package.name.bold());
+    for line in render_tree(&graph, &highlight) {
+        say!("{}", line);
+    }
+
+    if !duplicates {
+        return Ok(());
+    }
+
+    say!();
+    if duplicated.is_empty() {
+        say!(
+            "{} No packages are requested at more than one version",
+            "✓".green()
+        );
+        return Ok(());
+    }
+
+    say!(
+        "{} {} packages are requested at more than one version:",
+        "⚠".yellow(),
+        duplicated.len()
+    );
+    for (name, _) in &duplicated {
+        let resolved = graph
+            .packages
+            .get(*name)
+            .map(|pkg| install_versions(pkg).0)
+            .unwrap_or_else(|| "unresolved".to_string());
+        say!("  {} (resolved {})", name.red(), resolved.cyan());
+        for request in graph.requests.iter().filter(|r| r.name == *name) {
+            let by = match &request.dependent {
+                Some(dependent) => dependent.as_str(),
+                None => "nockapp.toml",
+            };
+            say!("    {} {} from {}", "→".cyan(), request.version, by);
+        }
+    }
+
+    Ok(())
+}
+
+/// Lines of the tree below the project, one per dependency
+///
+/// Packages the manifest does not name are marked transitive. Packages already shown higher up
+/// are marked `(*)` and not expanded again. Packages in `highlight` are marked as requested at
+/// several versions.
+fn render_tree(graph: &ResolvedGraph, highlight: &HashSet<&str>) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut shown = HashSet::new();
+    let roots = graph.direct_dependencies();
+    let direct: HashSet<&str> = roots.iter().copied().collect();
+    render_children(
+        graph, &roots, "", &direct, highlight, &mut shown, &mut lines,
+    );
+    lines
+}
+
+fn render_children<'a>(
+    graph: &'a ResolvedGraph,
+    children: &[&'a str],
+    prefix: &str,
+    direct: &HashSet<&str>,
+    highlight: &HashSet<&str>,
+    shown: &mut HashSet<&'a str>,
+    lines: &mut Vec<String>,
+) {
+    for (i, name) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let branch = if last { "└── " } else { "├── " };
+
+        let mut line = format!("{}{}{}", prefix, branch, name);
+        match graph.packages.get(*name) {
+            Some(pkg) => {
+                let short_commit: String = pkg.commit.chars().take(12).collect();
+                line.push_str(&format!(" {} ({})", install_versions(pkg).0, short_commit));
+            }
+            None => line.push_str(" (unresolved)"),
+        }
+        if !direct.contains(name) {
+            line.push_str(" [transitive]");
+        }
+        if highlight.contains(name) {
+            line.push_str(" [multiple versions]");
+        }
+
+        let expanded = !shown.insert(*name);
+        let grandchildren = graph.dependencies_of(Some(name));
+        if expanded && !grandchildren.is_empty() {
+            line.push_str(" (*)");
+        }
+        lines.push(line);
+
+        if !expanded {
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            render_children(
+                graph, &grandchildren, &prefix, direct, highlight, shown, lines,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::resolver::{ResolvedPackage, VersionSpec};
+
+    fn package(name: &str, version: &str, commit: &str) -> ResolvedPackage {
+        ResolvedPackage {
+            name: name.to_string(),
+            version_spec: VersionSpec::parse(version).unwrap(),
+            commit: commit.to_string(),
+            source_url: format!("https://github.com/{}", name),
+            source_path: None,
+            install_path: None,
+            source_files: None,
+            dependencies: HashMap::new(),
+            license: None,
+            local: None,
+        }
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let mut graph = ResolvedGraph::new();
+        graph.add_package(package("urbit/zuse", "@k409", "0123456789abcdef"));
+        graph.add_package(package("sequent", "*", "fedcba9876543210"));
+        graph.add_package(package("lagoon", "*", "aaaaaaaaaaaaaaaa"));
+        graph.add_request(None, "urbit/zuse", "k409");
+        graph.add_request(None, "sequent", "*");
+        graph.add_request(Some("sequent"), "lagoon", "*");
+        graph.add_request(Some("sequent"), "urbit/zuse", "*");
+        graph.add_request(Some("lagoon"), "urbit/zuse", "*");
+
+        assert_eq!(graph.duplicates(), vec![("urbit/zuse", vec!["k409", "*"])]);
+
+        let highlight = HashSet::from(["urbit/zuse"]);
+        assert_eq!(
+            render_tree(&graph, &highlight),
+            vec![
+                "├── sequent latest (fedcba987654)",
+                "│   ├── lagoon latest (aaaaaaaaaaaa) [transitive]",
+                "│   │   └── urbit/zuse k409 (0123456789ab) [multiple versions]",
+                "│   └── urbit/zuse k409 (0123456789ab) [multiple versions]",
+                "└── urbit/zuse k409 (0123456789ab) [multiple versions]",
+            ]
+        );
+    }
+}
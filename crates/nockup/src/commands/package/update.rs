@@ -5,7 +5,10 @@ use std::env;
 use anyhow::Result;
 use colored::Colorize;
 
+use crate::commands::package::install::LockMode;
 use crate::manifest::{DependencySpec, HoonPackage, LockSource, NockAppLock};
+use crate::resolver::local::local_packages;
+use crate::resolver::registry::registry_sources;
 use crate::resolver::Resolver;
 
 /// Update dependencies to their latest compatible versions
@@ -30,18 +33,18 @@ pub async fn run() -> Result<()> {
         );
     }
 
-    println!(
+    say!(
         "{} Checking for updates to dependencies for {}",
         "🔄".cyan(),
         manifest.package.name.yellow()
     );
-    println!();
+    say!();
 
     // Check if there are any dependencies
     let deps = match &manifest.dependencies {
         Some(deps) if !deps.is_empty() => deps,
         _ => {
-            println!("{} No dependencies to update", "✓".green());
+            say!("{} No dependencies to update", "✓".green());
             return Ok(());
         }
     };
@@ -94,24 +97,32 @@ pub async fn run() -> Result<()> {
     }
 
     if updates_available.is_empty() {
-        println!(
+        say!(
             "{} All dependencies are up to date (or pinned to fixed versions)",
             "✓".green()
         );
         return Ok(());
     }
 
-    println!("{} Checking for updates...", "🔍".cyan());
-    println!();
+    say!("{} Checking for updates...", "🔍".cyan());
+    say!();
 
     // Re-resolve dependencies (this will fetch latest commits for branches, etc.)
-    let resolver = Resolver::new()?;
+    let resolver = Resolver::new(false)?
+        .with_registries(registry_sources(Some(&manifest))?)
+        .with_patches(manifest.patch.clone().unwrap_or_default(), &cwd)
+        .with_local_packages(local_packages(&manifest, &cwd)?)
+        .with_lock(&old_lockfile);
     let new_graph = resolver.resolve(&manifest).await?;
 
     // Compare old and new versions
     let mut has_updates = false;
     for (name, old_version) in &updates_available {
         if let Some(new_pkg) = new_graph.packages.get(name) {
+            // Workspace libraries are always installed as they are
+            if new_pkg.local.is_some() {
+                continue;
+            }
             let new_version = new_pkg.version_spec.to_canonical_string();
 
             // For git-based dependencies, compare commits
@@ -136,7 +147,7 @@ pub async fn run() -> Result<()> {
                 let is_kelvin_update =
                     old_version.starts_with("@k") || old_version.starts_with("k");
 
-                println!(
+                say!(
                     "  {} {} {} → {}{}",
                     "↑".green(),
                     name.yellow(),
@@ -150,7 +161,7 @@ pub async fn run() -> Result<()> {
                 );
                 if let (Some(old_c), Some(new_c)) = (old_commit, new_commit) {
                     if old_c != new_c {
-                        println!(
+                        say!(
                             "    commit: {} → {}",
                             &old_c[..8.min(old_c.len())],
                             &new_c[..8.min(new_c.len())]
@@ -158,7 +169,7 @@ pub async fn run() -> Result<()> {
                     }
                 }
             } else {
-                println!(
+                say!(
                     "  {} {} {} (no update available)",
                     "→".blue(),
                     name.yellow(),
@@ -169,26 +180,26 @@ pub async fn run() -> Result<()> {
     }
 
     if !has_updates {
-        println!();
-        println!(
+        say!();
+        say!(
             "{} All updateable dependencies are already at their latest versions",
             "✓".green()
         );
         return Ok(());
     }
 
-    println!();
-    println!(
+    say!();
+    say!(
         "{} Running package install to apply updates...",
         "📦".cyan()
     );
-    println!();
+    say!();
 
     // Run package install to actually install the updates
-    crate::commands::package::install::run().await?;
+    crate::commands::package::install::run(false, false, LockMode::Update).await?;
 
-    println!();
-    println!("{} Updates applied successfully!", "✓".green());
+    say!();
+    say!("{} Updates applied successfully!", "✓".green());
 
     Ok(())
 }
@@ -0,0 +1,422 @@
+// src/commands/package/vendor.rs
+use std::path::{Component, Path, PathBuf};
+use std::{env, fs};
+
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use serde_json::json;
+
+use super::install::{
+    copy_dir_recursive, install_versions, link_package, package_dir_name, verify_cached_package,
+};
+use super::placement::{self, Placer};
+use crate::cache::{content_hash, PackageCache};
+use crate::manifest::{
+    HoonPackage, InstallMode, LockSource, LockedPackage, NockAppLock, VendorManifest,
+    VendoredPackage,
+};
+use crate::output;
+use crate::resolver::local::local_packages;
+use crate::resolver::registry::registry_sources;
+use crate::resolver::Resolver;
+
+/// Directory, inside the project, that vendored packages are copied to
+pub const VENDOR_DIR: &str = "vendor";
+
+/// Manifest of the vendored packages, next to nockapp.lock
+pub const VENDOR_MANIFEST: &str = "vendor.toml";
+
+/// Copy every resolved dependency into the project's vendor/ directory
+pub async fn run(offline: bool) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let manifest_path = cwd.join("nockapp.toml");
+
+    let manifest = match HoonPackage::load(&manifest_path)? {
+        Some(m) => m,
+        None => anyhow::bail!("No nockapp.toml found in {}", cwd.display()),
+    };
+
+    say!(
+        "{} Vendoring dependencies for {}",
+        "📦".cyan(),
+        manifest.package.name.yellow()
+    );
+    say!();
+
+    let project_dir = cwd.join(&manifest.package.name);
+    if !project_dir.exists() {
+        anyhow::bail!(
+            "Project directory '{}' not found. Run `nockup project init` first.",
+            manifest.package.name
+        );
+    }
+
+    let lock_path = project_dir.join("nockapp.lock");
+    let previous_lock = NockAppLock::load(&lock_path)?;
+
+    let resolver = Resolver::new(offline)?
+        .with_registries(registry_sources(Some(&manifest))?)
+        .with_patches(manifest.patch.clone().unwrap_or_default(), &cwd)
+        .with_local_packages(local_packages(&manifest, &cwd)?)
+        .with_lock(&previous_lock);
+    let cache = PackageCache::new()?;
+    let graph = resolver.resolve(&manifest).await?;
+
+    let vendor_dir = project_dir.join(VENDOR_DIR);
+    let hoon_dir = project_dir.join("hoon");
+    let packages_dir = hoon_dir.join("packages");
+    fs::create_dir_all(&vendor_dir).context("Failed to create vendor directory")?;
+    fs::create_dir_all(hoon_dir.join("lib")).context("Failed to create hoon/lib directory")?;
+    fs::create_dir_all(hoon_dir.join("sur")).context("Failed to create hoon/sur directory")?;
+
+    say!();
+    let mut vendored = VendorManifest::default();
+    let mut placer = Placer::new(placement::install_mode(&manifest.package)?, &hoon_dir);
+
+    for pkg_name in &graph.install_order {
+        let pkg = graph
+            .packages
+            .get(pkg_name)
+            .ok_or_else(|| anyhow!("Missing package '{}' in resolved graph", pkg_name))?;
+        let (display_version, cache_version) = install_versions(pkg);
+
+        say!(
+            "  {} Vendoring {}@{}...",
+            "→".cyan(),
+            pkg.name.yellow(),
+            display_version.cyan()
+        );
+
+        // Workspace libraries are vendored from their directories, the rest from the cache
+        let (cached_path, checksum) = if pkg.local.is_some() {
+            let dir = PathBuf::from(&pkg.source_url);
+            let checksum = content_hash(&dir)?;
+            (dir, checksum)
+        } else {
+            let cached_path = cache.package_path(&pkg.name, &cache_version);
+            if !cached_path.exists() {
+                anyhow::bail!("Package {}@{} is not in the cache", pkg.name, display_version);
+            }
+            let checksum = verify_cached_package(
+                &resolver,
+                pkg,
+                &cache_version,
+                &cached_path,
+                previous_lock.checksum(&pkg.name, &pkg.commit),
+            )
+            .await?;
+            (cached_path, checksum)
+        };
+
+        // Replace any previously vendored copy
+        let dir_name = package_dir_name(&pkg.name, &display_version);
+        let target = vendor_dir.join(&dir_name);
+        if target.exists() {
+            fs::remove_dir_all(&target)
+                .with_context(|| format!("Failed to remove {}", target.display()))?;
+        }
+        copy_dir_recursive(&cached_path, &target)
+            .with_context(|| format!("Failed to vendor package to {}", target.display()))?;
+
+        link_package(
+            &target,
+            &hoon_dir,
+            &pkg.name,
+            pkg.install_path.as_deref(),
+            pkg.source_path.as_deref(),
+            pkg.source_files.as_ref(),
+            &mut placer,
+        )?;
+
+        // The installed copy is superseded by the vendored one
+        let installed = packages_dir.join(&dir_name);
+        if installed.exists() {
+            fs::remove_dir_all(&installed)
+                .with_context(|| format!("Failed to remove {}", installed.display()))?;
+        }
+
+        say!(
+            "    {} Vendored to {}",
+            "✓".green(),
+            format!("{}/{}", VENDOR_DIR, dir_name).cyan()
+        );
+
+        vendored.package.push(VendoredPackage {
+            name: pkg.name.clone(),
+            version: display_version,
+            commit: pkg.commit.clone(),
+            source: pkg.source_url.clone(),
+            path: format!("{}/{}", VENDOR_DIR, dir_name),
+            checksum,
+            source_path: pkg.source_path.clone(),
+            install_path: pkg.install_path.clone(),
+            files: pkg.source_files.clone(),
+            license: pkg.license.clone(),
+            dependencies: graph.constraints_of(&pkg.name),
+        });
+    }
+
+    relink_to_vendor(&hoon_dir, &vendor_dir)?;
+    placer.save()?;
+
+    vendored.save(&project_dir.join(VENDOR_MANIFEST))?;
+    lock_vendored(&previous_lock, &vendored).save(&lock_path)?;
+
+    say!();
+    say!(
+        "{} Vendored {} packages into {}",
+        "✓".green(),
+        vendored.package.len(),
+        VENDOR_DIR.cyan()
+    );
+    say!("  Wrote {} and updated nockapp.lock", VENDOR_MANIFEST);
+
+    Ok(())
+}
+
+/// Install dependencies from the project's vendor/ directory, without the cache or git
+///
+/// nockapp.lock is rewritten to match vendor.toml if `write_lock` is set. Files are put in hoon/
+/// by the install `mode`.
+pub fn install_vendored(
+    project_dir: &Path,
+    vendored: &VendorManifest,
+    write_lock: bool,
+    mode: InstallMode,
+) -> Result<()> {
+    say!("{} Installing from {}", "📦".cyan(), VENDOR_MANIFEST.cyan());
+
+    let hoon_dir = project_dir.join("hoon");
+    fs::create_dir_all(hoon_dir.join("lib")).context("Failed to create hoon/lib directory")?;
+    fs::create_dir_all(hoon_dir.join("sur")).context("Failed to create hoon/sur directory")?;
+
+    let mut placer = Placer::new(mode, &hoon_dir);
+    for pkg in &vendored.package {
+        say!(
+            "  {} Installing {}@{}...",
+            "→".cyan(),
+            pkg.name.yellow(),
+            pkg.version.cyan()
+        );
+
+        let package_dir = project_dir.join(&pkg.path);
+        if !package_dir.exists() {
+            anyhow::bail!(
+                "{} is listed in {} but missing; run `nockup package vendor` again", pkg.path,
+                VENDOR_MANIFEST
+            );
+        }
+        let checksum = content_hash(&package_dir)?;
+        if checksum != pkg.checksum {
+            anyhow::bail!(
+                "{} does not match {} (expected {}, found {}); run `nockup package vendor` again",
+                pkg.path, VENDOR_MANIFEST, pkg.checksum, checksum
+            );
+        }
+
+        link_package(
+            &package_dir,
+            &hoon_dir,
+            &pkg.name,
+            pkg.install_path.as_deref(),
+            pkg.source_path.as_deref(),
+            pkg.files.as_ref(),
+            &mut placer,
+        )?;
+        output::emit(
+            "installed",
+            json!({
+                "name": pkg.name,
+                "version": pkg.version,
+                "commit": pkg.commit,
+                "source": pkg.source,
+                "checksum": pkg.checksum,
+                "path": pkg.path,
+            }),
+        );
+    }
+
+    relink_to_vendor(&hoon_dir, &project_dir.join(VENDOR_DIR))?;
+    placer.save()?;
+    if write_lock {
+        let lock_path = project_dir.join("nockapp.lock");
+        lock_vendored(&NockAppLock::load(&lock_path)?, vendored).save(&lock_path)?;
+    }
+
+    say!();
+    say!(
+        "{} Installed {} vendored packages",
+        "✓".green(),
+        vendored.package.len()
+    );
+    output::emit(
+        "install-complete",
+        json!({ "packages": vendored.package.len(), "lockfile_updated": write_lock }),
+    );
+
+    Ok(())
+}
+
+fn lock_vendored(previous: &NockAppLock, vendored: &VendorManifest) -> NockAppLock {
+    previous.with_packages(
+        vendored
+            .package
+            .iter()
+            .map(|pkg| LockedPackage {
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                source: LockSource::Git {
+                    url: pkg.source.clone(),
+                    commit: pkg.commit.clone(),
+                    path: pkg.source_path.clone(),
+                },
+                checksum: Some(pkg.checksum.clone()),
+                license: pkg.license.clone(),
+                dependencies: pkg.dependencies.clone(),
+            })
+            .collect(),
+    )
+}
+
+/// Point the symlinks under hoon/ that lead into hoon/packages/ at the same files in vendor/
+///
+/// Packages are linked as if installed to hoon/packages/, so this is what makes a vendored
+/// project independent of its installed copies. Returns how many links were rewritten.
+fn relink_to_vendor(hoon_dir: &Path, vendor_dir: &Path) -> Result<usize> {
+    let packages_dir = hoon_dir.join("packages");
+    let mut rewritten = 0;
+    relink_dir(hoon_dir, &packages_dir, vendor_dir, &mut rewritten)?;
+    Ok(rewritten)
+}
+
+fn relink_dir(
+    dir: &Path,
+    packages_dir: &Path,
+    vendor_dir: &Path,
+    rewritten: &mut usize,
+) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path == packages_dir {
+            continue;
+        }
+
+        let metadata = fs::symlink_metadata(&path)?;
+        if metadata.is_dir() {
+            relink_dir(&path, packages_dir, vendor_dir, rewritten)?;
+            continue;
+        }
+        if !metadata.file_type().is_symlink() {
+            continue;
+        }
+
+        let link_dir = path
+            .parent()
+            .ok_or_else(|| anyhow!("{} has no parent directory", path.display()))?;
+        let target = normalize(&link_dir.join(fs::read_link(&path)?));
+        let Ok(rest) = target.strip_prefix(normalize(packages_dir)) else {
+            continue;
+        };
+
+        let new_target = relative_path(&normalize(link_dir), &normalize(&vendor_dir.join(rest)));
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove existing symlink {}", path.display()))?;
+        symlink(&new_target, &path)?;
+        *rewritten += 1;
+    }
+    Ok(())
+}
+
+/// Resolve `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Relative path from directory `from` to `to`, both normalized
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+fn symlink(target: &Path, link: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link).with_context(|| {
+            format!(
+                "Failed to create symlink {} -> {}",
+                link.display(),
+                target.display()
+            )
+        })?;
+    }
+
+    #[cfg(windows)]
+    {
+        std::os::windows::fs::symlink_file(target, link).with_context(|| {
+            format!(
+                "Failed to create symlink {} -> {}",
+                link.display(),
+                target.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relink_to_vendor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path();
+        let hoon = project.join("hoon");
+        let vendored = project.join("vendor/urbit-zuse--k409");
+        fs::create_dir_all(hoon.join("sys")).unwrap();
+        fs::create_dir_all(hoon.join("lib")).unwrap();
+        fs::create_dir_all(&vendored).unwrap();
+        fs::write(vendored.join("zuse.hoon"), "~").unwrap();
+        fs::write(hoon.join("lib/local.hoon"), "~").unwrap();
+
+        std::os::unix::fs::symlink(
+            "../packages/urbit-zuse--k409/zuse.hoon",
+            hoon.join("sys/zuse.hoon"),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("local.hoon", hoon.join("lib/other.hoon")).unwrap();
+
+        assert_eq!(relink_to_vendor(&hoon, &project.join("vendor")).unwrap(), 1);
+        assert_eq!(
+            fs::read_link(hoon.join("sys/zuse.hoon")).unwrap(),
+            PathBuf::from("../../vendor/urbit-zuse--k409/zuse.hoon")
+        );
+        assert_eq!(fs::read_to_string(hoon.join("sys/zuse.hoon")).unwrap(), "~");
+        // Links that do not lead into hoon/packages are left alone
+        assert_eq!(
+            fs::read_link(hoon.join("lib/other.hoon")).unwrap(),
+            PathBuf::from("local.hoon")
+        );
+    }
+}
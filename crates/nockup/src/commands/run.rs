@@ -19,7 +19,7 @@ pub async fn run(project: String, args: Vec<String>) -> Result<()> {
         return Err(anyhow::anyhow!("No Cargo.toml found in '{}'", project));
     }
 
-    println!("{} Running project '{}'...", "🔨".green(), project.cyan());
+    say!("{} Running project '{}'...", "🔨".green(), project.cyan());
 
     // Run cargo run in the project directory
     let mut command = Command::new("cargo");
@@ -41,7 +41,7 @@ pub async fn run(project: String, args: Vec<String>) -> Result<()> {
         .context("Failed to execute cargo run")?;
 
     if status.success() {
-        println!("{} Run completed successfully!", "✓".green());
+        say!("{} Run completed successfully!", "✓".green());
     } else {
         return Err(anyhow::anyhow!(
             "Run failed with exit code: {}",
@@ -0,0 +1,102 @@
+// src/commands/self_update/mod.rs
+pub mod rollback;
+pub mod uninstall;
+pub mod update;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::cli::SelfCommand;
+use crate::commands::common::get_cache_dir;
+
+pub async fn run(cmd: SelfCommand) -> Result<()> {
+    match cmd {
+        SelfCommand::Update {
+            insecure_skip_verify,
+        } => update::run(!insecure_skip_verify).await,
+        SelfCommand::Rollback => rollback::run().await,
+        SelfCommand::Uninstall { yes } => uninstall::run(yes).await,
+    }
+}
+
+/// The nockup binary that is running, which `self update` and `self rollback` replace
+fn current_binary() -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("Failed to locate the running nockup")?;
+    exe.canonicalize()
+        .with_context(|| format!("Failed to resolve {}", exe.display()))
+}
+
+/// Where the nockup replaced by the last `self update` or `self rollback` is kept
+fn previous_binary() -> Result<PathBuf> {
+    Ok(get_cache_dir()?.join("previous").join("nockup"))
+}
+
+/// Install `new` as `target`, keeping the binary it replaces as `previous`
+///
+/// Both are copied next to where they go first and then renamed into place, so neither path
+/// ever holds a partly written binary. `new` may be `previous`, which swaps the two.
+fn install_keeping_previous(new: &Path, target: &Path, previous: &Path) -> Result<()> {
+    let staged_new = stage(new, target, ".nockup.new")?;
+    let staged_old = stage(target, previous, ".nockup.old")?;
+    fs::rename(&staged_new, target)
+        .with_context(|| format!("Failed to replace {}", target.display()))?;
+    fs::rename(&staged_old, previous).with_context(|| {
+        format!(
+            "Failed to keep the previous nockup at {}",
+            previous.display()
+        )
+    })?;
+    Ok(())
+}
+
+/// Copy `source` to a file named `name` in the directory of `dest`, executable
+fn stage(source: &Path, dest: &Path, name: &str) -> Result<PathBuf> {
+    let staged = dest.with_file_name(name);
+    if let Some(parent) = staged.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(source, &staged).with_context(|| {
+        format!(
+            "Failed to copy {} to {}",
+            source.display(),
+            staged.display()
+        )
+    })?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(staged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_keeping_previous() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let target = tmp.path().join("bin/nockup");
+        let previous = tmp.path().join("previous/nockup");
+        let download = tmp.path().join("download");
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::write(&target, "v1").unwrap();
+        fs::write(&download, "v2").unwrap();
+
+        install_keeping_previous(&download, &target, &previous).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "v2");
+        assert_eq!(fs::read_to_string(&previous).unwrap(), "v1");
+
+        // Rolling back swaps them, so it can be undone the same way
+        install_keeping_previous(&previous, &target, &previous).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "v1");
+        assert_eq!(fs::read_to_string(&previous).unwrap(), "v2");
+        assert!(!target.with_file_name(".nockup.new").exists());
+        assert!(!previous.with_file_name(".nockup.old").exists());
+    }
+}
@@ -0,0 +1,35 @@
+// src/commands/self_update/rollback.rs
+use anyhow::Result;
+use colored::Colorize;
+
+use super::{current_binary, install_keeping_previous, previous_binary};
+
+/// Swap the running nockup with the one the last `self update` replaced
+///
+/// The running binary is kept in its place, so a second rollback undoes the first.
+pub async fn run() -> Result<()> {
+    let target = current_binary()?;
+    let previous = previous_binary()?;
+    if !previous.exists() {
+        anyhow::bail!(
+            "No previous nockup at {}; there is nothing to roll back until `nockup self update` \
+            replaces this one",
+            previous.display()
+        );
+    }
+
+    install_keeping_previous(&previous, &target, &previous)?;
+
+    say!(
+        "{} Rolled back nockup at {} to the previous version",
+        "✅".green(),
+        target.display()
+    );
+    say!(
+        "{} Run {} again to return to the version you rolled back from",
+        "→".cyan(),
+        "nockup self rollback".cyan()
+    );
+
+    Ok(())
+}
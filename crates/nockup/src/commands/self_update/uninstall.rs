@@ -0,0 +1,144 @@
+// src/commands/self_update/uninstall.rs
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use super::current_binary;
+use crate::commands::common::get_cache_dir;
+use crate::failure::Failure;
+
+/// Shell startup files that the installer and `nockup install` add PATH lines to
+pub(crate) const SHELL_RCS: [&str; 3] = [".bashrc", ".zshrc", ".profile"];
+
+/// Remove ~/.nockup, with the binaries, caches and config in it, and nockup's PATH lines
+///
+/// Without `yes`, only lists what would be removed.
+pub async fn run(yes: bool) -> Result<()> {
+    let cache_dir = get_cache_dir()?;
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+
+    let mut edited = Vec::new();
+    for rc in SHELL_RCS {
+        let rc_path = home.join(rc);
+        if !rc_path.is_file() {
+            continue;
+        }
+        let content = tokio::fs::read_to_string(&rc_path)
+            .await
+            .with_context(|| format!("Failed to read {}", rc_path.display()))?;
+        if let Some(stripped) = strip_path_lines(&content, &cache_dir.join("bin")) {
+            edited.push((rc_path, stripped));
+        }
+    }
+    // A nockup installed elsewhere, e.g. by cargo, is left to whatever installed it
+    let outside: Option<PathBuf> = current_binary()
+        .ok()
+        .filter(|exe| !exe.starts_with(&cache_dir));
+
+    if !yes {
+        say!("{} Uninstalling nockup would remove:", "🗑️".cyan());
+        say!("  {}", cache_dir.display());
+        for (rc_path, _) in &edited {
+            say!("  the nockup PATH lines in {}", rc_path.display());
+        }
+        return Err(Failure::Usage.wrap(anyhow::anyhow!(
+            "Run `nockup self uninstall --yes` to uninstall"
+        )));
+    }
+
+    say!("{} Uninstalling nockup...", "🗑️".cyan());
+    for (rc_path, stripped) in edited {
+        tokio::fs::write(&rc_path, stripped)
+            .await
+            .with_context(|| format!("Failed to write {}", rc_path.display()))?;
+        say!(
+            "  {} Removed the nockup PATH lines from {}",
+            "✓".green(),
+            rc_path.display()
+        );
+    }
+    if cache_dir.exists() {
+        tokio::fs::remove_dir_all(&cache_dir)
+            .await
+            .with_context(|| format!("Failed to remove {}", cache_dir.display()))?;
+        say!("  {} Removed {}", "✓".green(), cache_dir.display());
+    }
+
+    say!("{} nockup has been uninstalled", "✅".green());
+    if let Some(exe) = outside {
+        say!(
+            "{} This nockup, at {}, was not installed in {}; remove it the way you installed it",
+            "⚠".yellow(),
+            exe.display(),
+            cache_dir.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// `content` without the PATH lines nockup added, or None if it has none
+///
+/// Each is a `# Added by nockup` comment, followed by an `export PATH=` line naming `bin_dir`,
+/// after the blank line that was added with them.
+pub(crate) fn strip_path_lines(content: &str, bin_dir: &Path) -> Option<String> {
+    let bin_dir = bin_dir.to_string_lossy();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut kept: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let added = lines[i].trim().starts_with("# Added by nockup")
+            && lines.get(i + 1).is_some_and(|next| {
+                next.trim().starts_with("export PATH=") && next.contains(bin_dir.as_ref())
+            });
+        if added {
+            if kept.last().is_some_and(|last| last.trim().is_empty()) {
+                kept.pop();
+            }
+            i += 2;
+        } else {
+            kept.push(lines[i]);
+            i += 1;
+        }
+    }
+
+    if kept.len() == lines.len() {
+        return None;
+    }
+    let mut stripped = kept.join("\n");
+    if content.ends_with('\n') && !stripped.is_empty() {
+        stripped.push('\n');
+    }
+    Some(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_path_lines() {
+        let rc = "\
+alias ll='ls -l'
+
+# Added by nockup installer
+export PATH=\"/home/me/.nockup/bin:$PATH\"
+export EDITOR=vim
+
+# Added by nockup
+export PATH=\"/home/me/.nockup/bin:$PATH\"
+";
+        let bin_dir = Path::new("/home/me/.nockup/bin");
+        assert_eq!(
+            strip_path_lines(rc, bin_dir).unwrap(),
+            "alias ll='ls -l'\nexport EDITOR=vim\n"
+        );
+        assert_eq!(
+            strip_path_lines("export PATH=\"$HOME/bin:$PATH\"\n", bin_dir),
+            None
+        );
+        // Lines for another nockup home are left to it
+        assert_eq!(strip_path_lines(rc, Path::new("/srv/nockup/bin")), None);
+    }
+}
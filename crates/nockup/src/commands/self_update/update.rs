@@ -0,0 +1,52 @@
+// src/commands/self_update/update.rs
+use std::fs;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use super::{current_binary, install_keeping_previous, previous_binary};
+use crate::commands::common;
+
+/// Replace the running nockup with the latest build on the configured channel
+///
+/// The binary it replaces is kept for `nockup self rollback`. Unless `verify` is false, the
+/// channel manifest's signature and the download's checksums are checked first.
+pub async fn run(verify: bool) -> Result<()> {
+    let cache_dir = common::get_cache_dir()?;
+    let config = common::get_config()?;
+    let target = current_binary()?;
+
+    say!(
+        "{} Updating nockup at {}...",
+        "🔄".green(),
+        target.display()
+    );
+
+    common::download_toolchain_files(&cache_dir).await?;
+    let (manifest, architecture) = common::channel_manifest(&config, verify)?;
+    let extract_dir = common::fetch_binary(&manifest, &architecture, "nockup", verify).await?;
+    let download = extract_dir.join("nockup");
+
+    let unchanged = fs::read(&download).context("Failed to read the downloaded nockup")?
+        == fs::read(&target).with_context(|| format!("Failed to read {}", target.display()))?;
+    let result = if unchanged {
+        Ok(())
+    } else {
+        install_keeping_previous(&download, &target, &previous_binary()?)
+    };
+    fs::remove_dir_all(&extract_dir)?;
+    result?;
+
+    if unchanged {
+        say!("{} nockup is already up to date", "✅".green());
+    } else {
+        say!("{} Updated nockup at {}", "✅".green(), target.display());
+        say!(
+            "{} Run {} to go back to the version it replaced",
+            "→".cyan(),
+            "nockup self rollback".cyan()
+        );
+    }
+
+    Ok(())
+}
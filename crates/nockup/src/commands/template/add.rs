@@ -0,0 +1,98 @@
+// src/commands/template/add.rs
+use anyhow::Result;
+use colored::Colorize;
+
+use super::{GitTemplate, GIT_PREFIX};
+use crate::commands::common;
+
+/// Add the template in the git repository at `url` under `name`, or the repository's name
+///
+/// `url` may end in `#<ref>` to follow a branch, tag or commit. The template is cloned once to
+/// check it, and recorded in the `[templates]` table of config.toml.
+pub async fn run(url: &str, name: Option<String>, offline: bool) -> Result<()> {
+    let spec = if url.starts_with(GIT_PREFIX) {
+        url.to_string()
+    } else {
+        format!("{}{}", GIT_PREFIX, url)
+    };
+    let git = GitTemplate::parse(&spec).expect("spec starts with git:");
+    let name = match name {
+        Some(name) => name,
+        None => repository_name(&git.url),
+    };
+    // The name is used as `template` in nockapp.toml and as a key in config.toml
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!(
+            "Invalid template name '{}': use letters, digits, '-' and '_', or pass --name", name
+        );
+    }
+
+    let mut config = common::get_config()?;
+    let path = git.fetch(None, offline).await?;
+    say!("{} Fetched template to {}", "✓".green(), path.display());
+
+    config
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("Invalid config file"))?
+        .entry("templates")
+        .or_insert_with(|| toml::Value::Table(toml::map::Map::new()))
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("Invalid [templates] table in config file"))?
+        .insert(name.clone(), toml::Value::String(spec.clone()));
+    common::write_config(&config)?;
+
+    say!(
+        "{} Added template '{}' from {}",
+        "✅".green(),
+        name.cyan(),
+        spec
+    );
+    if common::get_cache_dir()?
+        .join("templates")
+        .join(&name)
+        .exists()
+    {
+        say!(
+            "{} It is used in place of the channel's template of the same name",
+            "⚠️".yellow()
+        );
+    }
+    say!(
+        "{} Set {} in the [package] table of nockapp.toml to use it",
+        "→".cyan(),
+        format!("template = \"{}\"", name).cyan()
+    );
+    Ok(())
+}
+
+/// The last component of a repository URL, without `.git`
+fn repository_name(url: &str) -> String {
+    let last = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or_default();
+    last.strip_suffix(".git").unwrap_or(last).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository_name() {
+        assert_eq!(
+            repository_name("https://github.com/acme/nockapp-template.git"),
+            "nockapp-template"
+        );
+        assert_eq!(repository_name("git@github.com:acme/grpc.git"), "grpc");
+        assert_eq!(
+            repository_name("https://example.com/templates/"),
+            "templates"
+        );
+    }
+}
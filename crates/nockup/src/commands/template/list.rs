@@ -0,0 +1,50 @@
+// src/commands/template/list.rs
+use anyhow::Result;
+use colored::Colorize;
+
+use super::added;
+use crate::commands::common;
+use crate::output;
+
+/// List the channel's templates and those added with `nockup template add`
+pub fn run() -> Result<()> {
+    let templates_dir = common::get_cache_dir()?.join("templates");
+    let mut channel_templates = Vec::new();
+    if templates_dir.exists() {
+        for entry in std::fs::read_dir(&templates_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.file_type()?.is_dir() && !name.starts_with('.') {
+                channel_templates.push(name);
+            }
+        }
+    }
+    channel_templates.sort();
+
+    say!("{} Channel templates:", "📂".cyan());
+    if channel_templates.is_empty() {
+        say!("  (none; run {})", "nockup update".cyan());
+    }
+    for name in &channel_templates {
+        say!("  {}", name);
+        output::emit(
+            "template",
+            serde_json::json!({ "name": name, "source": templates_dir.join(name) }),
+        );
+    }
+
+    let added = common::get_config()
+        .map(|config| added(&config))
+        .unwrap_or_default();
+    if !added.is_empty() {
+        say!("{} Added templates:", "📂".cyan());
+    }
+    for (name, spec) in &added {
+        say!("  {} {}", name, spec.dimmed());
+        output::emit(
+            "template",
+            serde_json::json!({ "name": name, "source": spec }),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+// src/commands/template/mod.rs
+pub mod add;
+pub mod list;
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::cache::PackageCache;
+use crate::cli::TemplateCommand;
+use crate::commands::common;
+use crate::git_auth::GitAuth;
+use crate::git_fetcher::{GitFetcher, GitSpec};
+use crate::network::NetworkConfig;
+
+/// How a template from a git repository is named: `git:<url>`, optionally with `#<ref>`
+const GIT_PREFIX: &str = "git:";
+
+pub async fn run(cmd: TemplateCommand, offline: bool) -> Result<()> {
+    match cmd {
+        TemplateCommand::Add { url, name } => add::run(&url, name, offline).await,
+        TemplateCommand::List => list::run(),
+    }
+}
+
+/// A template in a git repository, at a branch, tag or commit, or else its default branch
+#[derive(Debug, PartialEq, Eq)]
+pub struct GitTemplate {
+    pub url: String,
+    pub git_ref: Option<String>,
+}
+
+impl GitTemplate {
+    /// Parse `git:<url>[#<ref>]`, or None if `template` does not start with `git:`
+    pub fn parse(template: &str) -> Option<Self> {
+        let spec = template.strip_prefix(GIT_PREFIX)?;
+        let (url, git_ref) = match spec.rsplit_once('#') {
+            Some((url, git_ref)) if !git_ref.is_empty() => (url, Some(git_ref.to_string())),
+            Some((url, _)) => (url, None),
+            None => (spec, None),
+        };
+        Some(GitTemplate {
+            url: url.to_string(),
+            git_ref,
+        })
+    }
+
+    /// Clone the template into the template cache, returning where
+    ///
+    /// A `commit`, as pinned by `template_commit` in nockapp.toml, takes the place of the ref.
+    pub async fn fetch(&self, commit: Option<&str>, offline: bool) -> Result<PathBuf> {
+        GitFetcher::check_git_available().await?;
+        let fetcher = GitFetcher::new(PackageCache::new()?.templates_dir())
+            .with_auth(GitAuth::load()?)
+            .with_network(NetworkConfig::load()?)
+            .with_offline(offline);
+
+        let commit = match (commit, &self.git_ref) {
+            (Some(commit), _) => Some(commit.to_string()),
+            (None, Some(git_ref)) if is_commit(git_ref) => Some(git_ref.clone()),
+            // A branch or tag, which ls-remote matches by name
+            (None, Some(git_ref)) => Some(fetcher.resolve_ref(&self.url, git_ref).await?),
+            (None, None) => None,
+        };
+        let spec = GitSpec {
+            url: self.url.clone(),
+            commit,
+            tag: None,
+            branch: None,
+            path: None,
+            install_path: None,
+            file: None,
+        };
+        fetcher
+            .fetch(&spec)
+            .await
+            .with_context(|| format!("Failed to fetch template {}", self.url))
+    }
+}
+
+/// Whether `git_ref` is a commit hash rather than the name of a branch or tag
+fn is_commit(git_ref: &str) -> bool {
+    (7..=40).contains(&git_ref.len()) && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// The templates added with `nockup template add`, from the `[templates]` table of config.toml
+pub fn added(config: &toml::Value) -> Vec<(String, String)> {
+    config
+        .get("templates")
+        .and_then(|t| t.as_table())
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(name, spec)| Some((name.clone(), spec.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The directory to render `template` from
+///
+/// That is a `git:` template, or one added with `nockup template add`, cloned into the template
+/// cache; or else a template of the channel in ~/.nockup/templates, at `commit` if it is pinned.
+pub async fn resolve(template: &str, commit: Option<&str>, offline: bool) -> Result<PathBuf> {
+    let config = common::get_config().ok();
+    let added_spec = config.as_ref().and_then(|config| {
+        added(config)
+            .into_iter()
+            .find(|(name, _)| name == template)
+            .map(|(_, spec)| spec)
+    });
+    if let Some(git) = GitTemplate::parse(added_spec.as_deref().unwrap_or(template)) {
+        return git.fetch(commit, offline).await;
+    }
+
+    let templates_dir = common::get_cache_dir()?.join("templates");
+    let template_src = match commit {
+        Some(commit) => templates_dir.join(format!("{}-{}", template, commit)),
+        None => templates_dir.join(template),
+    };
+    if !template_src.exists() {
+        anyhow::bail!(
+            "Template '{}' not found in cache at {}.\n\
+             Run `nockup update`, check your template-commit hash, or use a git template \
+             (template = \"git:<url>#<ref>\").",
+            template,
+            template_src.display()
+        );
+    }
+    Ok(template_src)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            GitTemplate::parse("git:https://github.com/acme/nockapp-template.git#v1.2"),
+            Some(GitTemplate {
+                url: "https://github.com/acme/nockapp-template.git".to_string(),
+                git_ref: Some("v1.2".to_string()),
+            })
+        );
+        assert_eq!(
+            GitTemplate::parse("git:git@github.com:acme/template.git"),
+            Some(GitTemplate {
+                url: "git@github.com:acme/template.git".to_string(),
+                git_ref: None,
+            })
+        );
+        assert_eq!(GitTemplate::parse("basic"), None);
+
+        assert!(is_commit("3f2a9c1"));
+        assert!(!is_commit("main"));
+        assert!(!is_commit("cafe"));
+    }
+}
@@ -8,49 +8,49 @@ use crate::git_fetcher::{GitFetcher, GitSpec};
 use crate::resolver::VersionSpec;
 
 pub async fn run() -> Result<()> {
-    println!("{}", "=== Testing Phase 1 Infrastructure ===".cyan().bold());
-    println!();
+    say!("{}", "=== Testing Phase 1 Infrastructure ===".cyan().bold());
+    say!();
 
     // Initialize cache
-    println!("{} Initializing package cache...", "📦".green());
+    say!("{} Initializing package cache...", "📦".green());
     let cache = PackageCache::new()?;
-    println!(
+    say!(
         "  Cache directory: {}",
         cache.root().display().to_string().cyan()
     );
-    println!();
+    say!();
 
     // Initialize git fetcher
-    println!("{} Initializing Git fetcher...", "🔧".green());
+    say!("{} Initializing Git fetcher...", "🔧".green());
     let git_fetcher = GitFetcher::new(cache.git_dir());
-    println!();
+    say!();
 
     // Test 1: Parse version specs
-    println!("{}", "--- Test 1: Version Spec Parsing ---".yellow().bold());
+    say!("{}", "--- Test 1: Version Spec Parsing ---".yellow().bold());
     let specs = vec!["k409", "commit:abc123def", "tag:v1.2.3", "branch:main", "^1.2.0"];
 
     for spec_str in specs {
         match VersionSpec::parse(spec_str) {
             Ok(spec) => {
-                println!(
+                say!(
                     "  ✓ Parsed '{}' → {}",
                     spec_str.cyan(),
                     spec.to_canonical_string().green()
                 );
             }
             Err(e) => {
-                println!("  ✗ Failed to parse '{}': {}", spec_str.red(), e);
+                say!("  ✗ Failed to parse '{}': {}", spec_str.red(), e);
             }
         }
     }
-    println!();
+    say!();
 
     // Test 2: Fetch a real repository
-    println!(
+    say!(
         "{}",
         "--- Test 2: Git Repository Fetching ---".yellow().bold()
     );
-    println!("  Fetching github.com/urbit/urbit (may take a moment)...");
+    say!("  Fetching github.com/urbit/urbit (may take a moment)...");
 
     let spec = GitSpec {
         url: "https://github.com/urbit/urbit".to_string(),
@@ -64,7 +64,7 @@ pub async fn run() -> Result<()> {
 
     match git_fetcher.fetch(&spec).await {
         Ok(path) => {
-            println!(
+            say!(
                 "  {} Fetched to: {}",
                 "✓".green(),
                 path.display().to_string().cyan()
@@ -72,19 +72,19 @@ pub async fn run() -> Result<()> {
 
             // Check if path exists
             if path.exists() {
-                println!("  {} Repository is cached!", "✓".green());
+                say!("  {} Repository is cached!", "✓".green());
             }
         }
         Err(e) => {
-            println!("  {} Fetch failed: {}", "✗".red(), e);
-            println!("  (This is expected if git or network is unavailable)");
+            say!("  {} Fetch failed: {}", "✗".red(), e);
+            say!("  (This is expected if git or network is unavailable)");
         }
     }
-    println!();
+    say!();
 
     // Test 3: List tags from a repo
-    println!("{}", "--- Test 3: List Repository Tags ---".yellow().bold());
-    println!("  Fetching tags from github.com/urbit/urbit...");
+    say!("{}", "--- Test 3: List Repository Tags ---".yellow().bold());
+    say!("  Fetching tags from github.com/urbit/urbit...");
 
     match git_fetcher
         .list_tags("https://github.com/urbit/urbit")
@@ -92,53 +92,53 @@ pub async fn run() -> Result<()> {
     {
         Ok(tags) => {
             let display_count = 10.min(tags.len());
-            println!(
+            say!(
                 "  {} Found {} tags (showing first {}):",
                 "✓".green(),
                 tags.len(),
                 display_count
             );
             for tag in tags.iter().take(display_count) {
-                println!("    - {}", tag.cyan());
+                say!("    - {}", tag.cyan());
             }
         }
         Err(e) => {
-            println!("  {} Failed to list tags: {}", "✗".red(), e);
-            println!("  (This is expected if git or network is unavailable)");
+            say!("  {} Failed to list tags: {}", "✗".red(), e);
+            say!("  (This is expected if git or network is unavailable)");
         }
     }
-    println!();
+    say!();
 
     // Test 4: Cache operations
-    println!("{}", "--- Test 4: Cache Operations ---".yellow().bold());
+    say!("{}", "--- Test 4: Cache Operations ---".yellow().bold());
 
     match cache.stats().await {
         Ok(stats) => {
-            println!("  {} Cache Statistics:", "📊".cyan());
-            println!(
+            say!("  {} Cache Statistics:", "📊".cyan());
+            say!(
                 "    Total packages: {}",
                 stats.total_packages.to_string().green()
             );
-            println!(
+            say!(
                 "    Unique packages: {}",
                 stats.unique_packages.to_string().green()
             );
-            println!("    Total size: {:.2} MB", stats.total_size_mb());
+            say!("    Total size: {:.2} MB", stats.total_size_mb());
         }
         Err(e) => {
-            println!("  {} Failed to get cache stats: {}", "✗".red(), e);
+            say!("  {} Failed to get cache stats: {}", "✗".red(), e);
         }
     }
-    println!();
+    say!();
 
     // Test 5: Package spec parsing
-    println!("{}", "--- Test 5: Package Spec Parsing ---".yellow().bold());
+    say!("{}", "--- Test 5: Package Spec Parsing ---".yellow().bold());
     let package_specs = vec!["arvo@k414", "lagoon@^0.2.0", "sequent@commit:abc123"];
 
     for spec_str in package_specs {
         match crate::resolver::parse_package_spec(spec_str) {
             Ok((name, version)) => {
-                println!(
+                say!(
                     "  ✓ Parsed '{}' → name={}, version={}",
                     spec_str.cyan(),
                     name.green(),
@@ -146,26 +146,26 @@ pub async fn run() -> Result<()> {
                 );
             }
             Err(e) => {
-                println!("  ✗ Failed to parse '{}': {}", spec_str.red(), e);
+                say!("  ✗ Failed to parse '{}': {}", spec_str.red(), e);
             }
         }
     }
-    println!();
+    say!();
 
-    println!("{}", "=== Phase 1 Tests Complete ===".cyan().bold());
-    println!();
-    println!("The following modules are ready:");
-    println!(
+    say!("{}", "=== Phase 1 Tests Complete ===".cyan().bold());
+    say!();
+    say!("The following modules are ready:");
+    say!(
         "  {} GitFetcher - Fetch repos, resolve tags/branches",
         "✓".green()
     );
-    println!("  {} PackageCache - Store and manage packages", "✓".green());
-    println!(
+    say!("  {} PackageCache - Store and manage packages", "✓".green());
+    say!(
         "  {} VersionSpec Parser - Parse all version formats",
         "✓".green()
     );
-    println!();
-    println!("Next: Implement full dependency resolver in Phase 2");
+    say!();
+    say!("Next: Implement full dependency resolver in Phase 2");
 
     Ok(())
 }
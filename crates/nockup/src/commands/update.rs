@@ -3,24 +3,25 @@ use std::fs;
 use anyhow::{Context, Result};
 use colored::Colorize;
 
-use super::common;
+use super::{common, component};
 
-pub async fn run() -> Result<()> {
-    run_update(false).await
+pub async fn run(verify: bool) -> Result<()> {
+    run_update(false, verify).await
 }
 
 /// Run update with optional initial setup
 /// If `is_initial_install` is true, also creates cache structure, sets up config, and updates PATH
-pub async fn run_update(is_initial_install: bool) -> Result<()> {
+/// If `verify` is false, downloaded binaries are installed without checking signatures or checksums
+pub async fn run_update(is_initial_install: bool, verify: bool) -> Result<()> {
     let cache_dir = common::get_cache_dir()?;
 
     if is_initial_install {
-        println!("{} Setting up nockup cache directory...", "🚀".green());
+        say!("{} Setting up nockup cache directory...", "🚀".green());
     } else {
-        println!("{} Updating nockup...", "🔄".green());
+        say!("{} Updating nockup...", "🔄".green());
     }
 
-    println!(
+    say!(
         "{} Cache location: {}",
         "📁".blue(),
         cache_dir.display().to_string().cyan()
@@ -31,17 +32,11 @@ pub async fn run_update(is_initial_install: bool) -> Result<()> {
         create_cache_structure(&cache_dir).await?;
     }
 
-    // Download or update templates
-    common::download_templates(&cache_dir).await?;
-
-    // Download toolchain files
-    common::download_toolchain_files(&cache_dir).await?;
-
     // Set up or get config
     let config = if is_initial_install {
         let config_path = cache_dir.join("config.toml");
         let mut config = common::get_or_create_config()?;
-        println!("📝 Config installed at: {}", config_path.display());
+        say!("📝 Config installed at: {}", config_path.display());
         config["channel"] = toml::Value::String("stable".into());
         config["architecture"] = toml::Value::String(common::get_target_identifier());
         fs::write(&config_path, toml::to_string(&config)?)
@@ -51,11 +46,20 @@ pub async fn run_update(is_initial_install: bool) -> Result<()> {
         common::get_config()?
     };
 
+    // Download or update templates, unless they were removed as a component
+    let components = component::installed(&config, common::configured_channel(&config)?);
+    if components.contains(&"templates") {
+        common::download_templates(&cache_dir).await?;
+    }
+
+    // Download toolchain files
+    common::download_toolchain_files(&cache_dir).await?;
+
     // Write commit details to status file
     common::write_commit_details(&cache_dir).await?;
 
-    // Download binaries for current channel
-    common::download_binaries(&config).await?;
+    // Download nockup and the binary components of the current channel
+    common::download_binaries(&config, verify).await?;
 
     // Prepend cache bin directory to PATH (only for initial install)
     if is_initial_install {
@@ -63,19 +67,19 @@ pub async fn run_update(is_initial_install: bool) -> Result<()> {
     }
 
     if is_initial_install {
-        println!("{} Setup complete!", "✅".green());
-        println!(
+        say!("{} Setup complete!", "✅".green());
+        say!(
             "{} Templates are now available in: {}",
             "📂".blue(),
             cache_dir.join("templates").display().to_string().cyan()
         );
-        println!(
+        say!(
             "{} Binaries are now available in: {}",
             "🛠".blue(),
             cache_dir.join("bin").display().to_string().cyan()
         );
     } else {
-        println!("{} Update complete!", "✅".green());
+        say!("{} Update complete!", "✅".green());
     }
 
     Ok(())
@@ -93,12 +97,12 @@ async fn create_cache_structure(cache_dir: &std::path::Path) -> Result<()> {
         .await
         .context("Failed to create bin directory")?;
 
-    println!(
+    say!(
         "{} Created {}",
         "✓".green(),
         templates_dir.display().to_string().cyan()
     );
-    println!(
+    say!(
         "{} Created {}",
         "✓".green(),
         bin_dir.display().to_string().cyan()
@@ -127,7 +131,7 @@ async fn prepend_path_to_shell_rc(bin_dir: &std::path::Path) -> Result<()> {
                     .await
                     .context("Failed to write to shell RC file")?;
 
-                println!(
+                say!(
                     "{} Updated {}",
                     "✓".green(),
                     rc_path.display().to_string().cyan()
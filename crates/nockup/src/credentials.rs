@@ -0,0 +1,323 @@
+//! Tokens for private package registries, saved by `nockup login`.
+//!
+//! Kept per host in `~/.nockup/credentials.toml`, readable only by its owner:
+//!
+//! ```toml
+//! [hosts."registry.example.com"]
+//! token = "..."
+//!
+//! [hosts."hoon.internal.example.com"]
+//! keychain = true    # the token is in the OS keychain, under the service "nockup"
+//! ```
+//!
+//! Requests for registries and publishing to them send the token of their host as a bearer
+//! token.
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::common::get_cache_dir;
+
+const CREDENTIALS_FILE: &str = "credentials.toml";
+
+/// Service the OS keychain holds nockup's tokens under
+const KEYCHAIN_SERVICE: &str = "nockup";
+
+/// The contents of ~/.nockup/credentials.toml
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Credentials {
+    #[serde(default)]
+    hosts: BTreeMap<String, HostCredential>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct HostCredential {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+    // The token is in the OS keychain rather than this file
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    keychain: bool,
+}
+
+impl Credentials {
+    /// Load ~/.nockup/credentials.toml, empty if there is none
+    pub fn load() -> Result<Self> {
+        Self::load_from(&credentials_path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Save to ~/.nockup/credentials.toml, readable only by the user
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&credentials_path()?)
+    }
+
+    // Written to a fresh file that replaces the old one, as the mode only applies to a file
+    // being created: rewriting a credentials file someone else could read would leave it so.
+    fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp_name = path.as_os_str().to_owned();
+        tmp_name.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_name);
+        match std::fs::remove_file(&tmp_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(e).with_context(|| format!("Failed to remove {}", tmp_path.display()));
+            }
+            _ => {}
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&tmp_path)
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        file.write_all(toml::to_string_pretty(self)?.as_bytes())
+            .and_then(|()| file.sync_all())
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Remember `token` for `host`, in the OS keychain if `keychain` is set
+    pub fn set(&mut self, host: &str, token: &str, keychain: bool) -> Result<()> {
+        let credential = if keychain {
+            keychain_set(host, token)?;
+            HostCredential {
+                token: None,
+                keychain: true,
+            }
+        } else {
+            // A token moved out of the keychain should not linger there
+            if self.hosts.get(host).is_some_and(|c| c.keychain) {
+                keychain_delete(host)?;
+            }
+            HostCredential {
+                token: Some(token.to_string()),
+                keychain: false,
+            }
+        };
+        self.hosts.insert(host.to_string(), credential);
+        Ok(())
+    }
+
+    /// Forget the token for `host`, returning whether there was one
+    pub fn remove(&mut self, host: &str) -> Result<bool> {
+        match self.hosts.remove(host) {
+            Some(credential) => {
+                if credential.keychain {
+                    keychain_delete(host)?;
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// The token for the host of `url`, if one was saved
+    pub fn token_for(&self, url: &str) -> Result<Option<String>> {
+        let Some(host) = host_of(url) else {
+            return Ok(None);
+        };
+        match self.hosts.get(&host) {
+            Some(credential) if credential.keychain => keychain_get(&host).map(Some),
+            Some(credential) => Ok(credential.token.clone()),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The saved token for the host of `url`, if any
+pub fn token_for(url: &str) -> Result<Option<String>> {
+    Credentials::load()?.token_for(url)
+}
+
+/// The host (and port, if not the default) that credentials for `url` are kept under
+pub fn host_of(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
+
+fn credentials_path() -> Result<PathBuf> {
+    Ok(get_cache_dir()?.join(CREDENTIALS_FILE))
+}
+
+// The OS keychain, through macOS's `security` and the freedesktop Secret Service's `secret-tool`
+
+#[cfg(target_os = "macos")]
+fn keychain_set(host: &str, token: &str) -> Result<()> {
+    // `security` only takes the password as an argument, where any local user could read it
+    // from the process list, so the command goes to its interactive mode on stdin instead
+    if [token, host].iter().any(|s| s.contains(['"', '\\', '\n'])) {
+        anyhow::bail!(
+            "Tokens and hosts stored in the keychain cannot contain quotes, backslashes or newlines"
+        );
+    }
+    let command = format!(
+        "add-generic-password -U -s \"{}\" -a \"{}\" -w \"{}\"\n",
+        KEYCHAIN_SERVICE, host, token
+    );
+    keychain_run(Command::new("security").arg("-i"), Some(&command)).map(drop)
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_get(host: &str) -> Result<String> {
+    keychain_run(
+        Command::new("security")
+            .args(["find-generic-password", "-s", KEYCHAIN_SERVICE, "-a", host, "-w"]),
+        None,
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_delete(host: &str) -> Result<()> {
+    keychain_run(
+        Command::new("security")
+            .args(["delete-generic-password", "-s", KEYCHAIN_SERVICE, "-a", host]),
+        None,
+    )
+    .map(drop)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn keychain_set(host: &str, token: &str) -> Result<()> {
+    let label = format!("nockup registry token for {}", host);
+    keychain_run(
+        Command::new("secret-tool")
+            .args(["store", "--label", &label, "service", KEYCHAIN_SERVICE, "host", host]),
+        Some(token),
+    )
+    .map(drop)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn keychain_get(host: &str) -> Result<String> {
+    keychain_run(
+        Command::new("secret-tool").args(["lookup", "service", KEYCHAIN_SERVICE, "host", host]),
+        None,
+    )
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn keychain_delete(host: &str) -> Result<()> {
+    keychain_run(
+        Command::new("secret-tool").args(["clear", "service", KEYCHAIN_SERVICE, "host", host]),
+        None,
+    )
+    .map(drop)
+}
+
+#[cfg(unix)]
+fn keychain_run(command: &mut Command, input: Option<&str>) -> Result<String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}; is the OS keychain available?", program))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(not(unix))]
+fn keychain_set(_host: &str, _token: &str) -> Result<()> {
+    anyhow::bail!("The OS keychain is not supported on this platform; log in without --keychain")
+}
+
+#[cfg(not(unix))]
+fn keychain_get(_host: &str) -> Result<String> {
+    anyhow::bail!("The OS keychain is not supported on this platform")
+}
+
+#[cfg(not(unix))]
+fn keychain_delete(_host: &str) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials() {
+        assert_eq!(
+            host_of("https://registry.example.com/registry.toml").as_deref(),
+            Some("registry.example.com")
+        );
+        assert_eq!(
+            host_of("http://localhost:8080/publish").as_deref(),
+            Some("localhost:8080")
+        );
+        assert_eq!(host_of("/srv/hoon/registry.toml"), None);
+
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = tmp.path().join(CREDENTIALS_FILE);
+        // An existing file others can read is replaced, not rewritten in place
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::write(&path, "").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        let mut credentials = Credentials::load_from(&path).unwrap();
+        credentials
+            .set("registry.example.com", "secret", false)
+            .unwrap();
+        credentials.save_to(&path).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let mut credentials = Credentials::load_from(&path).unwrap();
+        assert_eq!(
+            credentials
+                .token_for("https://registry.example.com/registry.toml")
+                .unwrap()
+                .as_deref(),
+            Some("secret")
+        );
+        assert_eq!(
+            credentials
+                .token_for("https://other.example.com/registry.toml")
+                .unwrap(),
+            None
+        );
+        assert!(credentials.remove("registry.example.com").unwrap());
+        assert!(!credentials.remove("registry.example.com").unwrap());
+    }
+}
@@ -0,0 +1,129 @@
+// src/failure.rs
+//! Classes of failure, and the exit code each one gives
+//!
+//! Errors are tagged with a [`Failure`] where they arise; `main` turns the tag into the exit
+//! code. Errors without one exit with 1, except those from HTTP requests, which are network
+//! failures wherever they happen.
+use std::fmt;
+
+/// Why a command failed, for scripts that react to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// The command line asked for nothing, or for something contradictory
+    Usage,
+    /// The dependencies could not be resolved to a consistent set of versions
+    Resolution,
+    /// A remote could not be reached or refused the request
+    Network,
+    /// cargo or hoonc failed to build the project
+    Build,
+}
+
+impl Failure {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Failure::Usage => 2,
+            Failure::Resolution => 3,
+            Failure::Network => 4,
+            Failure::Build => 5,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Failure::Usage => "usage",
+            Failure::Resolution => "resolution",
+            Failure::Network => "network",
+            Failure::Build => "build",
+        }
+    }
+
+    /// Tag `error` with this class, unless it already has one
+    ///
+    /// The first class wins, so a network failure met while resolving stays a network failure.
+    pub fn wrap(self, error: anyhow::Error) -> anyhow::Error {
+        if of(&error).is_some() {
+            return error;
+        }
+        Classified {
+            failure: self,
+            error,
+        }
+        .into()
+    }
+}
+
+/// Tag the error of a `Result` with a class of failure
+pub trait Classify<T> {
+    fn classify(self, failure: Failure) -> anyhow::Result<T>;
+}
+
+impl<T> Classify<T> for anyhow::Result<T> {
+    fn classify(self, failure: Failure) -> anyhow::Result<T> {
+        self.map_err(|e| failure.wrap(e))
+    }
+}
+
+/// An error tagged with its class; it reads exactly like the error it wraps
+#[derive(Debug)]
+struct Classified {
+    failure: Failure,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Classified {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for Classified {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// The class of an error, from the first tag in its chain
+pub fn of(error: &anyhow::Error) -> Option<Failure> {
+    error.chain().find_map(|cause| {
+        if let Some(classified) = cause.downcast_ref::<Classified>() {
+            Some(classified.failure)
+        } else if cause.is::<reqwest::Error>() {
+            Some(Failure::Network)
+        } else {
+            None
+        }
+    })
+}
+
+/// The exit code for an error: its class's, or 1
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    of(error).map_or(1, Failure::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Context};
+
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(&anyhow!("disk full")), 1);
+
+        let network: anyhow::Result<()> = Err(Failure::Network.wrap(anyhow!("ls-remote failed")));
+        let error = network
+            .classify(Failure::Resolution)
+            .context("Failed to resolve dependency 'sequent'")
+            .unwrap_err();
+        assert_eq!(exit_code(&error), 4);
+        assert_eq!(
+            format!("{:#}", error),
+            "Failed to resolve dependency 'sequent': ls-remote failed"
+        );
+
+        let build = Failure::Build.wrap(anyhow!("hoonc failed").context("Failed to build"));
+        assert_eq!(of(&build), Some(Failure::Build));
+        assert_eq!(format!("{:#}", build), "Failed to build: hoonc failed");
+    }
+}
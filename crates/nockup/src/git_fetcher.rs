@@ -2,8 +2,13 @@ use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use anyhow::{Context, Result};
+use colored::Colorize;
 use tokio::process::Command;
 
+use crate::failure::Failure;
+use crate::git_auth::{redact_url, GitAuth};
+use crate::network::NetworkConfig;
+
 /// Specification for a Git repository to fetch
 #[derive(Debug, Clone)]
 pub struct GitSpec {
@@ -18,13 +23,55 @@ pub struct GitSpec {
 
 /// Handles Git repository fetching and management
 pub struct GitFetcher {
-    cache_dir: PathBuf, // ~/.nockup/cache/git/
+    cache_dir: PathBuf,     // ~/.nockup/cache/git/
+    offline: bool,          // Only use repositories already in cache_dir
+    auth: GitAuth,          // Credentials for private repositories
+    network: NetworkConfig, // Proxy to reach remotes through
+    full_clone: bool,       // Clone every blob of every file instead of only the requested path
 }
 
 impl GitFetcher {
     /// Create a new GitFetcher with the given cache directory
     pub fn new(cache_dir: PathBuf) -> Self {
-        Self { cache_dir }
+        Self {
+            cache_dir,
+            offline: false,
+            auth: GitAuth::default(),
+            network: NetworkConfig::default(),
+            full_clone: false,
+        }
+    }
+
+    /// Always clone whole repositories, for servers or setups where partial clones misbehave
+    pub fn with_full_clone(mut self, full_clone: bool) -> Self {
+        self.full_clone = full_clone;
+        self
+    }
+
+    /// Authenticate to remotes with the given credentials
+    pub fn with_auth(mut self, auth: GitAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Reach remotes through the proxy of the given network config
+    pub fn with_network(mut self, network: NetworkConfig) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// A git command that talks to the remote at `url`, with its credentials and proxy applied
+    fn remote_git(&self, url: &str) -> Command {
+        let mut command = Command::new("git");
+        command.envs(self.auth.env_for(url));
+        command.envs(self.network.git_env());
+        command
+    }
+
+    /// Refuse to use the network, serving only repositories already cloned into the cache
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
     }
 
     /// Fetch a repository according to the spec, returning the local path
@@ -37,8 +84,11 @@ impl GitFetcher {
 
         // Check if already cached
         if repo_path.exists() {
+            self.ensure_checked_out(spec, &repo_path).await?;
+            self.update_submodules(spec, &repo_path).await?;
             return Ok(repo_path);
         }
+        self.ensure_online(&format!("fetch {} at {}", spec.url, target_ref))?;
 
         // Clone the repository
         self.clone_repo(spec, &repo_path, &target_ref).await?;
@@ -46,22 +96,42 @@ impl GitFetcher {
         Ok(repo_path)
     }
 
+    /// Fetch a repository again, discarding any cached clone of it
+    pub async fn refetch(&self, spec: &GitSpec) -> Result<PathBuf> {
+        self.ensure_online(&format!("fetch {} again", spec.url))?;
+        let target_ref = self.determine_target_ref(spec).await?;
+        let repo_path = self.get_repo_cache_path(&spec.url, &target_ref);
+
+        if repo_path.exists() {
+            tokio::fs::remove_dir_all(&repo_path)
+                .await
+                .with_context(|| format!("Failed to remove {}", repo_path.display()))?;
+        }
+
+        self.clone_repo(spec, &repo_path, &target_ref).await?;
+
+        Ok(repo_path)
+    }
+
     /// Resolve a tag or branch to a commit hash
     pub async fn resolve_ref(&self, url: &str, ref_name: &str) -> Result<String> {
+        self.ensure_online(&format!("resolve {} in {}", ref_name, url))?;
+
         // Use git ls-remote to get commit hash without cloning
-        let output = Command::new("git")
+        let output = self
+            .remote_git(url)
             .args(["ls-remote", url, ref_name])
             .output()
             .await
             .context("Failed to run git ls-remote")?;
 
         if !output.status.success() {
-            anyhow::bail!(
+            return Err(Failure::Network.wrap(anyhow::anyhow!(
                 "Failed to resolve ref '{}' in {}: {}",
                 ref_name,
-                url,
+                redact_url(url),
                 String::from_utf8_lossy(&output.stderr)
-            );
+            )));
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -108,24 +178,31 @@ impl GitFetcher {
 
     /// Fetch a subdirectory from a repo using sparse checkout
     pub async fn fetch_subdir(&self, spec: &GitSpec, subdir: &str) -> Result<PathBuf> {
-        let target_ref = self.determine_target_ref(spec).await?;
-        let repo_path = self.get_repo_cache_path(&spec.url, &target_ref);
-
-        if repo_path.exists() {
-            return Ok(repo_path.join(subdir));
-        }
-
-        // Clone with sparse checkout
-        self.clone_sparse(spec, &repo_path, &target_ref, subdir)
-            .await?;
+        let spec = GitSpec {
+            path: Some(subdir.to_string()),
+            ..spec.clone()
+        };
+        let repo_path = self.fetch(&spec).await?;
 
         Ok(repo_path.join(subdir))
     }
 
     // Private helper methods
 
+    /// Fail if offline, naming what needed the network
+    fn ensure_online(&self, action: &str) -> Result<()> {
+        if self.offline {
+            anyhow::bail!(
+                "Cannot {} in offline mode, which only uses repositories cached in {}",
+                action,
+                self.cache_dir.display()
+            );
+        }
+        Ok(())
+    }
+
     /// Determine which ref to use (commit > tag > branch > default)
-    async fn determine_target_ref(&self, spec: &GitSpec) -> Result<String> {
+    pub async fn determine_target_ref(&self, spec: &GitSpec) -> Result<String> {
         if let Some(ref commit) = spec.commit {
             // If commit is specified, use it directly
             Ok(commit.clone())
@@ -145,7 +222,7 @@ impl GitFetcher {
     }
 
     /// Generate cache path from URL and commit hash
-    fn get_repo_cache_path(&self, url: &str, commit: &str) -> PathBuf {
+    pub fn get_repo_cache_path(&self, url: &str, commit: &str) -> PathBuf {
         // Hash the URL to create a safe directory name
         let url_hash = self.hash_url(url);
 
@@ -165,17 +242,39 @@ impl GitFetcher {
         format!("{:x}", hasher.finish())
     }
 
-    /// Clone a repository (full clone with depth=1 for efficiency)
+    /// Clone a repository at `commit`
+    ///
+    /// By default this is a partial clone (`--filter=blob:none`) that downloads file contents
+    /// only for the commit checked out, and, when the spec names a `path`, a sparse checkout of
+    /// just that directory and the files at the repository root. Should that fail, e.g. with a
+    /// git too old for sparse checkouts, the repository is cloned in full. Either way, the
+    /// submodules under the checkout are then initialized.
     async fn clone_repo(&self, spec: &GitSpec, target_path: &Path, commit: &str) -> Result<()> {
         // Create parent directory
         if let Some(parent) = target_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        // Clone with depth=1 for the specific commit (if possible)
+        if !self.full_clone {
+            match self.clone_partial(spec, target_path, commit).await {
+                Ok(()) => return self.update_submodules(spec, target_path).await,
+                Err(e) => {
+                    say!(
+                        "    {} Partial clone failed, falling back to a full clone: {}",
+                        "⚠".yellow(),
+                        e
+                    );
+                    if target_path.exists() {
+                        tokio::fs::remove_dir_all(target_path).await?;
+                    }
+                }
+            }
+        }
+
         // Note: Some git servers don't support fetching arbitrary commits with depth=1,
         // so we do a full clone and then checkout
-        let output = Command::new("git")
+        let output = self
+            .remote_git(&spec.url)
             .arg("clone")
             .arg(&spec.url)
             .arg(target_path.as_os_str())
@@ -186,85 +285,152 @@ impl GitFetcher {
             .context("Failed to clone repository")?;
 
         if !output.status.success() {
-            anyhow::bail!(
+            return Err(Failure::Network.wrap(anyhow::anyhow!(
                 "Failed to clone {}: {}",
-                spec.url,
+                redact_url(&spec.url),
                 String::from_utf8_lossy(&output.stderr)
-            );
+            )));
         }
 
         // Checkout the specific commit
         self.checkout_commit(target_path, commit).await?;
 
+        self.update_submodules(spec, target_path).await
+    }
+
+    /// Clone without file contents, then check out `commit`, sparsely if the spec has a `path`
+    async fn clone_partial(&self, spec: &GitSpec, target_path: &Path, commit: &str) -> Result<()> {
+        let target = target_path.to_string_lossy();
+        let mut clone = vec!["clone", "--filter=blob:none", "--no-checkout"];
+        if spec.path.is_some() {
+            clone.push("--sparse");
+        }
+        clone.extend([spec.url.as_str(), target.as_ref()]);
+        let parent = target_path.parent().unwrap_or(Path::new("."));
+        self.run_remote_git(&spec.url, parent, &clone).await?;
+
+        // Contents are downloaded as files are checked out, so these talk to the remote too
+        self.run_remote_git(&spec.url, target_path, &["checkout", commit])
+            .await?;
+        if let Some(ref path) = spec.path {
+            self.run_remote_git(&spec.url, target_path, &["sparse-checkout", "set", path])
+                .await?;
+        }
+
         Ok(())
     }
 
-    /// Clone with sparse checkout for a specific subdirectory
-    async fn clone_sparse(
-        &self,
-        spec: &GitSpec,
-        target_path: &Path,
-        commit: &str,
-        subdir: &str,
-    ) -> Result<()> {
-        // Create parent directory
-        if let Some(parent) = target_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+    /// Add the spec's `path` to a cached sparse checkout that does not include it yet
+    async fn ensure_checked_out(&self, spec: &GitSpec, repo_path: &Path) -> Result<()> {
+        let Some(ref path) = spec.path else {
+            return Ok(());
+        };
+        let sparse = repo_path.join(".git/info/sparse-checkout").exists();
+        if repo_path.join(path).exists() || !sparse {
+            return Ok(());
         }
 
-        // Initialize repo
-        Command::new("git")
-            .args(["init"])
-            .current_dir(target_path)
+        self.ensure_online(&format!("check out {} from {}", path, spec.url))?;
+        self.run_remote_git(&spec.url, repo_path, &["sparse-checkout", "add", path])
+            .await
+    }
+
+    /// Check out the submodules of a clone that are not yet, only those under the spec's `path`
+    ///
+    /// Submodules of local repositories may be local too, which git refuses to clone unless
+    /// told to, so that is allowed for them.
+    async fn update_submodules(&self, spec: &GitSpec, repo_path: &Path) -> Result<()> {
+        if !repo_path.join(".gitmodules").exists() {
+            return Ok(());
+        }
+        let mut pathspec = Vec::new();
+        if let Some(ref path) = spec.path {
+            pathspec.extend(["--", path.as_str()]);
+        }
+
+        // Uninitialized submodules are listed with a leading '-'
+        let status = Command::new("git")
+            .args(["submodule", "status"])
+            .args(&pathspec)
+            .current_dir(repo_path)
             .output()
-            .await?;
+            .await
+            .context("Failed to run git submodule status")?;
+        if status.status.success()
+            && !String::from_utf8_lossy(&status.stdout)
+                .lines()
+                .any(|line| line.starts_with('-'))
+        {
+            return Ok(());
+        }
+        self.ensure_online(&format!("check out the submodules of {}", spec.url))?;
 
-        // Configure sparse checkout
-        Command::new("git")
-            .args(["config", "core.sparseCheckout", "true"])
-            .current_dir(target_path)
+        let mut command = self.remote_git(&spec.url);
+        if is_local(&spec.url) {
+            command.args(["-c", "protocol.file.allow=always"]);
+        }
+        let output = command
+            .args(["submodule", "update", "--init", "--recursive"])
+            .args(&pathspec)
+            .current_dir(repo_path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
             .output()
-            .await?;
+            .await
+            .context("Failed to run git submodule update")?;
 
-        // Set sparse checkout paths
-        let sparse_file = target_path.join(".git/info/sparse-checkout");
-        tokio::fs::write(&sparse_file, format!("{}\n", subdir)).await?;
+        if !output.status.success() {
+            return Err(Failure::Network.wrap(anyhow::anyhow!(
+                "Failed to check out the submodules of {}: {}",
+                redact_url(&spec.url),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
 
-        // Add remote
-        Command::new("git")
-            .args(["remote", "add", "origin", &spec.url])
-            .current_dir(target_path)
-            .output()
-            .await?;
+        Ok(())
+    }
 
-        // Fetch and checkout
-        Command::new("git")
-            .args(["fetch", "--depth=1", "origin", commit])
-            .current_dir(target_path)
+    /// Run a git command in `dir` that may need to talk to the remote at `url`
+    async fn run_remote_git(&self, url: &str, dir: &Path, args: &[&str]) -> Result<()> {
+        let output = self
+            .remote_git(url)
+            .args(args)
+            .current_dir(dir)
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
             .output()
-            .await?;
+            .await
+            .with_context(|| format!("Failed to run git {}", args[0]))?;
 
-        self.checkout_commit(target_path, commit).await?;
+        if !output.status.success() {
+            return Err(Failure::Network.wrap(anyhow::anyhow!(
+                "git {} failed for {}: {}",
+                args[0],
+                redact_url(url),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
 
         Ok(())
     }
 
     /// List all tags in a remote repository
     pub async fn list_tags(&self, url: &str) -> Result<Vec<String>> {
-        let output = Command::new("git")
+        self.ensure_online(&format!("list tags of {}", url))?;
+
+        let output = self
+            .remote_git(url)
             .args(["ls-remote", "--tags", url])
             .output()
             .await
             .context("Failed to list tags")?;
 
         if !output.status.success() {
-            anyhow::bail!(
+            return Err(Failure::Network.wrap(anyhow::anyhow!(
                 "Failed to list tags for {}: {}",
-                url,
+                redact_url(url),
                 String::from_utf8_lossy(&output.stderr)
-            );
+            )));
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -297,6 +463,11 @@ impl GitFetcher {
     }
 }
 
+/// Whether `url` names a repository on this machine
+fn is_local(url: &str) -> bool {
+    url.starts_with("file://") || Path::new(url).is_absolute()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,4 +494,134 @@ mod tests {
         assert!(path.to_string_lossy().contains("/tmp/cache"));
         assert!(path.to_string_lossy().contains("abc123def456"));
     }
+
+    #[tokio::test]
+    async fn test_offline_fetch_uses_only_the_cache() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let fetcher = GitFetcher::new(tmp.path().to_path_buf()).with_offline(true);
+        let spec = GitSpec {
+            url: "https://github.com/urbit/urbit".to_string(),
+            commit: Some("abc123def456789".to_string()),
+            tag: None,
+            branch: None,
+            path: None,
+            install_path: None,
+            file: None,
+        };
+
+        let err = fetcher.fetch(&spec).await.unwrap_err().to_string();
+        assert!(err.contains("offline mode"), "{}", err);
+
+        let cached = fetcher.get_repo_cache_path(&spec.url, "abc123def456789");
+        std::fs::create_dir_all(&cached).unwrap();
+        assert_eq!(fetcher.fetch(&spec).await.unwrap(), cached);
+
+        let tagged = GitSpec {
+            commit: None,
+            tag: Some("v1.0".to_string()),
+            ..spec
+        };
+        let err = fetcher.fetch(&tagged).await.unwrap_err().to_string();
+        assert!(err.contains("offline mode"), "{}", err);
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("Failed to run git");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_checks_out_only_the_requested_path() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let origin = tmp.path().join("origin");
+        std::fs::create_dir_all(origin.join("lagoon/lib")).unwrap();
+        std::fs::create_dir_all(origin.join("math/lib")).unwrap();
+        std::fs::write(origin.join("hoon.toml"), "").unwrap();
+        std::fs::write(origin.join("lagoon/lib/lagoon.hoon"), "~").unwrap();
+        std::fs::write(origin.join("math/lib/math.hoon"), "~").unwrap();
+        git(&origin, &["init", "-q"]);
+        git(&origin, &["add", "."]);
+        git(&origin, &["commit", "-q", "-m", "init"]);
+        let commit = git(&origin, &["rev-parse", "HEAD"]);
+
+        let fetcher = GitFetcher::new(tmp.path().join("cache"));
+        let spec = GitSpec {
+            url: format!("file://{}", origin.display()),
+            commit: Some(commit),
+            tag: None,
+            branch: None,
+            path: Some("lagoon".to_string()),
+            install_path: None,
+            file: None,
+        };
+
+        let repo = fetcher.fetch(&spec).await.unwrap();
+        assert!(repo.join("lagoon/lib/lagoon.hoon").exists());
+        assert!(repo.join("hoon.toml").exists());
+        assert!(!repo.join("math").exists());
+
+        // Another path of the same commit is added to the cached checkout
+        let math = GitSpec {
+            path: Some("math".to_string()),
+            ..spec
+        };
+        assert_eq!(fetcher.fetch(&math).await.unwrap(), repo);
+        assert!(repo.join("math/lib/math.hoon").exists());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_checks_out_submodules() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let vendored = tmp.path().join("vendored");
+        std::fs::create_dir_all(&vendored).unwrap();
+        std::fs::write(vendored.join("ext.hoon"), "~").unwrap();
+        git(&vendored, &["init", "-q"]);
+        git(&vendored, &["add", "."]);
+        git(&vendored, &["commit", "-q", "-m", "init"]);
+
+        let origin = tmp.path().join("origin");
+        std::fs::create_dir_all(origin.join("lagoon/lib")).unwrap();
+        std::fs::write(origin.join("lagoon/lib/lagoon.hoon"), "~").unwrap();
+        git(&origin, &["init", "-q"]);
+        let vendored = vendored.to_string_lossy();
+        for path in ["lagoon/ext", "math/ext"] {
+            git(
+                &origin,
+                &["-c", "protocol.file.allow=always", "submodule", "add", "-q", &vendored, path],
+            );
+        }
+        git(&origin, &["add", "."]);
+        git(&origin, &["commit", "-q", "-m", "init"]);
+        let commit = git(&origin, &["rev-parse", "HEAD"]);
+
+        let fetcher = GitFetcher::new(tmp.path().join("cache"));
+        let spec = GitSpec {
+            url: format!("file://{}", origin.display()),
+            commit: Some(commit),
+            tag: None,
+            branch: None,
+            path: Some("lagoon".to_string()),
+            install_path: None,
+            file: None,
+        };
+
+        // Only the submodules under the path are checked out
+        let repo = fetcher.fetch(&spec).await.unwrap();
+        assert!(repo.join("lagoon/ext/ext.hoon").exists());
+        assert!(!repo.join("math/ext/ext.hoon").exists());
+
+        let whole = GitSpec { path: None, ..spec };
+        assert_eq!(fetcher.fetch(&whole).await.unwrap(), repo);
+        assert!(repo.join("math/ext/ext.hoon").exists());
+    }
 }
@@ -1,8 +1,16 @@
+#[macro_use]
+pub mod output;
+
 pub mod cache;
 pub mod cli;
 pub mod commands;
+pub mod credentials;
+pub mod failure;
+pub mod git_auth;
 pub mod git_fetcher;
 pub mod lib_manager;
 pub mod manifest;
+pub mod minisign;
+pub mod network;
 pub mod resolver;
 pub mod version;
@@ -7,6 +7,9 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
+use crate::commands::common::get_cache_dir;
+use crate::network::NetworkConfig;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LibrarySpec {
     pub url: String,
@@ -48,7 +51,7 @@ pub async fn process_libraries(project_dir: &Path, manifest: &ProjectManifest) -
             return Ok(());
         }
 
-        println!("{} Processing library dependencies...", "📚".cyan());
+        say!("{} Processing library dependencies...", "📚".cyan());
 
         let cache_dir = get_library_cache_dir()?;
         let project_lib_dir = project_dir.join("hoon").join("lib");
@@ -58,7 +61,7 @@ pub async fn process_libraries(project_dir: &Path, manifest: &ProjectManifest) -
             .context("Failed to create project library directory")?;
 
         for (lib_name, lib_spec) in libraries {
-            println!(
+            say!(
                 "  {} Fetching library '{}'...",
                 "⬇️".green(),
                 lib_name.cyan()
@@ -66,7 +69,7 @@ pub async fn process_libraries(project_dir: &Path, manifest: &ProjectManifest) -
 
             // Validate library spec
             if let Err(e) = validate_library_spec(lib_spec) {
-                println!("    ❌ Validation failed for '{}': {}", lib_name, e);
+                say!("    ❌ Validation failed for '{}': {}", lib_name, e);
                 return Err(e);
             }
 
@@ -74,10 +77,7 @@ pub async fn process_libraries(project_dir: &Path, manifest: &ProjectManifest) -
             let repo_dir = match fetch_library_repo(&cache_dir, lib_name, lib_spec).await {
                 Ok(dir) => dir,
                 Err(e) => {
-                    println!(
-                        "    ❌ Failed to fetch repository for '{}': {}",
-                        lib_name, e
-                    );
+                    say!("    ❌ Failed to fetch repository for '{}': {}", lib_name, e);
                     return Err(e);
                 }
             };
@@ -91,10 +91,7 @@ pub async fn process_libraries(project_dir: &Path, manifest: &ProjectManifest) -
                 let source_dir = match find_library_source_dir(&repo_dir, lib_spec) {
                     Ok(dir) => dir,
                     Err(e) => {
-                        println!(
-                            "    ❌ Failed to find source directory for '{}': {}",
-                            lib_name, e
-                        );
+                        say!("    ❌ Failed to find source directory for '{}': {}", lib_name, e);
                         return Err(e);
                     }
                 };
@@ -103,15 +100,15 @@ pub async fn process_libraries(project_dir: &Path, manifest: &ProjectManifest) -
                 if let Err(e) =
                     copy_library_files(&source_dir, &project_lib_dir, lib_name, lib_spec)
                 {
-                    println!("    ❌ Failed to copy files for '{}': {}", lib_name, e);
+                    say!("    ❌ Failed to copy files for '{}': {}", lib_name, e);
                     return Err(e);
                 }
             }
 
-            println!("    ✓ Installed library '{}'", lib_name);
+            say!("    ✓ Installed library '{}'", lib_name);
         }
 
-        println!("{} All libraries processed successfully!", "✓".green());
+        say!("{} All libraries processed successfully!", "✓".green());
     }
 
     Ok(())
@@ -151,10 +148,7 @@ fn validate_library_spec(spec: &LibrarySpec) -> Result<()> {
 }
 
 fn get_library_cache_dir() -> Result<PathBuf> {
-    let cache_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?
-        .join(".nockup")
-        .join("library_cache");
+    let cache_dir = get_cache_dir()?.join("library_cache");
 
     fs::create_dir_all(&cache_dir).context("Failed to create library cache directory")?;
 
@@ -182,10 +176,12 @@ async fn fetch_library_repo(
     }
 
     // Clone the repository
-    println!("    ⬇️ Cloning repository...");
+    say!("    ⬇️ Cloning repository...");
 
     let mut git_cmd = Command::new("git");
-    git_cmd.args(["clone", &spec.url]);
+    git_cmd
+        .args(["clone", &spec.url])
+        .envs(NetworkConfig::load()?.git_env());
 
     // If branch specified, clone that branch
     if let Some(branch) = &spec.branch {
@@ -329,7 +325,7 @@ fn copy_single_file(repo_dir: &Path, project_lib_dir: &Path, file_path: &str) ->
         )
     })?;
 
-    println!("      copy {}", file_path);
+    say!("      copy {}", file_path);
 
     Ok(())
 }
@@ -361,7 +357,7 @@ fn copy_top_level_library(src_dir: &Path, dest_dir: &Path, root_src: &Path) -> R
                 .with_context(|| format!("Failed to copy file '{}'", src_path.display()))?;
 
             let relative_src = src_path.strip_prefix(root_src).unwrap_or(&src_path);
-            println!("      copy {}", relative_src.display());
+            say!("      copy {}", relative_src.display());
         }
     }
 
@@ -391,7 +387,7 @@ fn copy_directory_contents(src_dir: &Path, dest_dir: &Path, root_src: &Path) ->
 
                 // Show relative path for cleaner output
                 let relative_src = src_path.strip_prefix(root_src).unwrap_or(&src_path);
-                println!("      copy {}", relative_src.display());
+                say!("      copy {}", relative_src.display());
             }
         }
     }
@@ -3,28 +3,58 @@ use std::process;
 use clap::Parser;
 use colored::Colorize;
 use nockup::cli::*;
-use nockup::{commands, version};
+use nockup::{commands, failure, output, version};
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let offline = cli.offline;
+    output::set_format(cli.format);
+    output::set_ci(cli.ci || output::ci_from_env());
+    if let Some(dir) = cli.cache_dir {
+        commands::common::set_cache_dir(dir);
+    }
 
     let result = match cli.command {
         // Hierarchical commands
-        Some(Commands::Project(cmd)) => commands::build::run(cmd).await,
-        Some(Commands::Package(cmd)) => commands::package::run(cmd).await,
+        Some(Commands::Project(cmd)) => commands::build::run(cmd, offline).await,
+        Some(Commands::Package(cmd)) => commands::package::run(cmd, offline).await,
         Some(Commands::Cache(cmd)) => commands::cache::run(cmd).await,
         Some(Commands::Channel(cmd)) => commands::channel::run(cmd).await,
+        Some(Commands::Component(ComponentCommand::Add { .. })) if offline => Err(anyhow::anyhow!(
+            "`nockup component add` downloads components and cannot run with --offline"
+        )),
+        Some(Commands::Component(cmd)) => commands::component::run(cmd).await,
+        Some(Commands::Template(cmd)) => commands::template::run(cmd, offline).await,
+        Some(Commands::Login { registry, keychain }) => commands::login::login(&registry, keychain),
+        Some(Commands::Logout { registry }) => commands::login::logout(&registry),
+        Some(Commands::Doctor) => commands::doctor::run(offline).await,
+        Some(Commands::SelfUpdate(SelfCommand::Update { .. })) if offline => Err(anyhow::anyhow!(
+            "`nockup self update` downloads nockup and cannot run with --offline"
+        )),
+        Some(Commands::SelfUpdate(cmd)) => commands::self_update::run(cmd).await,
 
         // Legacy flat commands (backward compatible)
         Some(Commands::Build { project }) => {
-            commands::build::run(ProjectCommand::Build {
-                project: Some(project),
-            })
+            commands::build::run(
+                ProjectCommand::Build {
+                    project: Some(project),
+                    force: false,
+                    locked: false,
+                    target: None,
+                    profile: "release".to_string(),
+                },
+                offline,
+            )
             .await
         }
         Some(Commands::Init { project }) => commands::init::run(project).await,
-        Some(Commands::Update) => commands::update::run().await,
+        Some(Commands::Update { .. }) if offline => Err(anyhow::anyhow!(
+            "`nockup update` downloads toolchains and cannot run with --offline"
+        )),
+        Some(Commands::Update {
+            insecure_skip_verify,
+        }) => commands::update::run(!insecure_skip_verify).await,
         // Some(Commands::Init { name: _ }) => {
         //     eprintln!("{}", "warning: `nockup init` is now `nockup package init`".yellow());
         //     commands::package::run(PackageCommand::Init{ name: name }).await
@@ -34,13 +64,27 @@ async fn main() {
                 "{}",
                 "warning: `nockup install` is now `nockup update`".yellow()
             );
-            commands::package::run(PackageCommand::Install).await
+            commands::package::run(
+                PackageCommand::Install {
+                    full_clone: false,
+                    locked: false,
+                    frozen: false,
+                },
+                offline,
+            )
+            .await
         }
         Some(Commands::Run { project, args }) => {
-            commands::build::run(ProjectCommand::Run {
-                project: Some(project),
-                args,
-            })
+            commands::build::run(
+                ProjectCommand::Run {
+                    project: Some(project),
+                    bin: None,
+                    profile: "release".to_string(),
+                    hot_reload: false,
+                    args,
+                },
+                offline,
+            )
             .await
         }
         Some(Commands::TestPhase1) => commands::test_phase1::run().await,
@@ -49,7 +93,15 @@ async fn main() {
     };
 
     if let Err(e) = result {
+        output::emit(
+            "error",
+            serde_json::json!({
+                "message": format!("{:#}", e),
+                "failure": failure::of(&e).map(failure::Failure::name),
+                "exit_code": failure::exit_code(&e),
+            }),
+        );
         eprintln!("Error: {}", e);
-        process::exit(1);
+        process::exit(failure::exit_code(&e));
     }
 }
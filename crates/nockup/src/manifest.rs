@@ -10,6 +10,15 @@ pub struct HoonPackage {
     pub package: PackageMeta,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dependencies: Option<BTreeMap<String, DependencySpec>>,
+    // Package registries for this project, in addition to those in ~/.nockup/config.toml
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registries: Option<BTreeMap<String, RegistrySpec>>,
+    // Replacement sources for packages anywhere in the dependency graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<BTreeMap<String, PatchSpec>>,
+    // Libraries developed alongside this project, installed from their directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<WorkspaceSpec>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -27,6 +36,53 @@ pub struct PackageMeta {
     pub template: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub template_commit: Option<String>,
+    // Binary that `nockup project run` runs in a project with several
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_bin: Option<String>,
+    // How package files are put into hoon/; overrides `install-mode` in ~/.nockup/config.toml
+    #[serde(
+        default,
+        rename = "install-mode",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub install_mode: Option<InstallMode>,
+    // Install dependencies found in the workspace from there; overrides ~/.nockup/config.toml
+    #[serde(
+        default,
+        rename = "prefer-local",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub prefer_local: Option<bool>,
+}
+
+// [workspace] members = ["libs/sequent"]: directories of libraries, relative to the manifest
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceSpec {
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+// install-mode: how an installed package's files are put into hoon/lib, hoon/sur and the like
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMode {
+    // Relative symlinks into hoon/packages/; the default, except on Windows
+    Symlink,
+    // Copies, for filesystems and platforms without symlinks; the default on Windows
+    Copy,
+    // Hard links to the files in hoon/packages/, on the same filesystem
+    Hardlink,
+}
+
+impl Default for InstallMode {
+    fn default() -> Self {
+        // Windows only allows symlinks with developer mode or as administrator
+        if cfg!(windows) {
+            InstallMode::Copy
+        } else {
+            InstallMode::Symlink
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -42,6 +98,18 @@ pub struct NockAppManifest {
     #[serde(default)]
     pub dependencies: BTreeMap<String, DependencySpec>,
 
+    // Hoon kernel of each Rust binary; without any, kernels are matched to binaries by name
+    #[serde(default, rename = "app", skip_serializing_if = "Vec::is_empty")]
+    pub apps: Vec<AppSpec>,
+
+    // Build profiles for `nockup project build --profile`, adding to the built-in dev and release
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profile: BTreeMap<String, ProfileSpec>,
+
+    // Commands run before and after `nockup project build`, from the project directory
+    #[serde(default, skip_serializing_if = "HooksSpec::is_empty")]
+    pub hooks: HooksSpec,
+
     // Optional local section (rare)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub build: Option<String>,
@@ -64,6 +132,53 @@ impl NockAppManifest {
     }
 }
 
+// [[app]] entry: { bin = "listen", entry = "hoon/app/listener.hoon", jam = "listen.jam" }
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AppSpec {
+    // A [[bin]] of Cargo.toml; may be left out in a project with one binary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin: Option<String>,
+    // The Hoon kernel, relative to the project, under hoon/
+    pub entry: String,
+    // Jam to build it into, relative to the project; out.jam for a lone app, else <bin>.jam
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jam: Option<String>,
+}
+
+// [profile.<name>] entry: { cargo_profile = "dev", hoonc_flags = ["--arbitrary"], features = ["x"] }
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSpec {
+    // Cargo profile to build with; defaults to the profile's own name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_profile: Option<String>,
+    // Extra arguments to hoonc
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hoonc_flags: Vec<String>,
+    // Cargo features to enable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+}
+
+// [hooks] section: { pre-build = ["./gen-hoon.sh"], post-build = ["cp $JAM_PATH dist/"] }
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HooksSpec {
+    // Run before cargo, so they may generate Hoon sources
+    #[serde(default, rename = "pre-build", skip_serializing_if = "Vec::is_empty")]
+    pub pre_build: Vec<String>,
+    // Run once every jam is built
+    #[serde(default, rename = "post-build", skip_serializing_if = "Vec::is_empty")]
+    pub post_build: Vec<String>,
+}
+
+impl HooksSpec {
+    pub fn is_empty(&self) -> bool {
+        self.pre_build.is_empty() && self.post_build.is_empty()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum DependencySpec {
@@ -86,21 +201,89 @@ pub enum DependencySpec {
     },
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum RegistrySpec {
+    // "https://example.com/registry.toml"
+    Url(String),
+    // { url = "...", priority = 10, mirrors = ["..."] }
+    Full {
+        url: String,
+        priority: Option<i64>,
+        mirrors: Option<Vec<String>>,
+    },
+}
+
+// [patch] entry: { git = "https://github.com/me/fork", branch = "fix" } or { path = "../fork" }
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PatchSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    // Local git repository, relative to the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+/// Format of the nockapp.lock this nockup writes; lockfiles without a `version` are format 1
+pub const LOCK_VERSION: u32 = 2;
+
 // nockapp.lock format – always exact commit hashes
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NockAppLock {
+    // Lockfile format, LOCK_VERSION once loaded
+    #[serde(default = "lock_v1")]
+    pub version: u32,
+    // nockup version that wrote the lockfile, e.g. "1.0.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nockup: Option<String>,
+    // Resolution algorithm it was resolved with (resolver::ALGORITHM)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolver: Option<String>,
+    // Versions of hoon and hoonc the project was last built with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<LockedToolchain>,
+    #[serde(default)]
     pub package: Vec<LockedPackage>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedToolchain {
+    // As reported by `hoon --version`, e.g. "1.0.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hoon: Option<String>,
+    // As reported by `hoonc --version`, e.g. "0.2.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hoonc: Option<String>,
+}
+
+fn lock_v1() -> u32 {
+    1
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LockedPackage {
     pub name: String,
     // k414", "commit:abc123", "^1.0", etc.
     pub version: String,
     pub source: LockSource,
+    // "sha256:<hex>" digest of the installed package contents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    // License declared in the package's hoon.toml, e.g. "MIT"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    // What the package asked for of each of its dependencies, e.g. { lagoon = "^1.0.0" }
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum LockSource {
     #[serde(rename = "git")]
@@ -131,13 +314,48 @@ impl HoonPackage {
 }
 
 impl NockAppLock {
+    /// A lockfile of these packages, written by this nockup
+    pub fn new(package: Vec<LockedPackage>) -> Self {
+        NockAppLock {
+            version: LOCK_VERSION,
+            nockup: Some(env!("CARGO_PKG_VERSION").to_string()),
+            resolver: Some(crate::resolver::ALGORITHM.to_string()),
+            toolchain: None,
+            package,
+        }
+    }
+
+    /// Load nockapp.lock, migrating it to the current format; empty if there is none
+    ///
+    /// Older lockfiles are rewritten in the current format the next time they are saved.
     pub fn load(path: &Path) -> Result<Self> {
-        if path.exists() {
-            let content = std::fs::read_to_string(path)?;
-            Ok(toml::from_str(&content)?)
-        } else {
-            Ok(NockAppLock { package: vec![] })
+        if !path.exists() {
+            return Ok(NockAppLock::new(vec![]));
         }
+        let content = std::fs::read_to_string(path)?;
+        let lock: NockAppLock = toml::from_str(&content)?;
+        lock.migrate()
+    }
+
+    /// Bring a lockfile of any known format up to LOCK_VERSION
+    ///
+    /// Format 1 had no header, package dependencies or required checksums; what it lacks is
+    /// left unknown until the packages are installed again.
+    fn migrate(mut self) -> Result<Self> {
+        if self.version > LOCK_VERSION {
+            anyhow::bail!(
+                "nockapp.lock has format {}, but this nockup reads up to format {}{}; \
+                upgrade nockup to use it",
+                self.version,
+                LOCK_VERSION,
+                self.nockup
+                    .as_deref()
+                    .map(|v| format!(" (it was written by nockup {})", v))
+                    .unwrap_or_default()
+            );
+        }
+        self.version = LOCK_VERSION;
+        Ok(self)
     }
 
     pub fn save(&self, path: &Path) -> Result<()> {
@@ -145,4 +363,136 @@ impl NockAppLock {
         std::fs::write(path, content)?;
         Ok(())
     }
+
+    /// These packages in place of the locked ones, keeping the toolchain they were built with
+    pub fn with_packages(&self, package: Vec<LockedPackage>) -> Self {
+        NockAppLock {
+            toolchain: self.toolchain.clone(),
+            ..NockAppLock::new(package)
+        }
+    }
+
+    /// The recorded checksum of a package, if it is locked at `commit`
+    pub fn checksum(&self, name: &str, commit: &str) -> Option<&str> {
+        self.package
+            .iter()
+            .find(|p| {
+                p.name == name
+                    && matches!(&p.source, LockSource::Git { commit: c, .. } if c == commit)
+            })
+            .and_then(|p| p.checksum.as_deref())
+    }
+}
+
+// vendor.toml format – packages copied into the project's vendor/ directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VendorManifest {
+    #[serde(default)]
+    pub package: Vec<VendoredPackage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VendoredPackage {
+    pub name: String,
+    pub version: String,
+    pub commit: String,
+    pub source: String,
+    // Directory holding the package, relative to the project (e.g. "vendor/urbit-zuse--k409")
+    pub path: String,
+    // "sha256:<hex>" digest of the vendored contents
+    pub checksum: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    // As in nockapp.lock
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, String>,
+}
+
+impl VendorManifest {
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(toml::from_str(&content)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Dependencies of `manifest` that are not vendored
+    pub fn missing<'a>(&self, manifest: &'a HoonPackage) -> Vec<&'a str> {
+        manifest
+            .dependencies
+            .iter()
+            .flatten()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| !self.package.iter().any(|p| p.name == *name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_migration() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = tmp.path().join("nockapp.lock");
+        std::fs::write(
+            &path,
+            r#"
+            [[package]]
+            name = "sequent"
+            version = "k409"
+            source = { type = "git", url = "https://github.com/example/sequent", commit = "abc" }
+            "#,
+        )
+        .unwrap();
+
+        // A lockfile without a header is format 1, and saved in the current format
+        let lock = NockAppLock::load(&path).unwrap();
+        assert_eq!(lock.version, LOCK_VERSION);
+        assert_eq!(lock.nockup, None);
+        assert_eq!(lock.package[0].name, "sequent");
+        assert!(lock.package[0].dependencies.is_empty());
+
+        let mut lock = NockAppLock::new(lock.package);
+        lock.toolchain = Some(LockedToolchain {
+            hoon: None,
+            hoonc: Some("0.2.0".to_string()),
+        });
+        lock.package[0]
+            .dependencies
+            .insert("lagoon".to_string(), "^1.0.0".to_string());
+        lock.save(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with(&format!("version = {}\n", LOCK_VERSION)));
+        let reloaded = NockAppLock::load(&path).unwrap();
+        assert_eq!(reloaded.package, lock.package);
+        assert!(
+            saved.contains("[toolchain]\nhoonc = \"0.2.0\"\n"),
+            "{}",
+            saved
+        );
+        assert_eq!(reloaded.with_packages(vec![]).toolchain, lock.toolchain);
+        assert_eq!(
+            reloaded.resolver.as_deref(),
+            Some(crate::resolver::ALGORITHM)
+        );
+
+        std::fs::write(&path, "version = 99\nnockup = \"9.0.0\"\n").unwrap();
+        let err = NockAppLock::load(&path).unwrap_err().to_string();
+        assert!(err.contains("format 99"), "{}", err);
+    }
 }
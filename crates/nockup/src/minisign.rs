@@ -0,0 +1,170 @@
+// src/minisign.rs
+//! Verification of minisign signatures, which sign the channel manifests of releases
+//!
+//! A minisign signature file has four lines: an untrusted comment, the base64 of the algorithm,
+//! key id and Ed25519 signature of the file, a trusted comment, and the base64 of a signature
+//! over the file's signature and the trusted comment. Files signed with the `ED` algorithm are
+//! hashed with BLAKE2b-512 first; legacy `Ed` signatures sign the file itself.
+use anyhow::{Context, Result};
+use base64::Engine;
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+const UNTRUSTED_PREFIX: &str = "untrusted comment:";
+const TRUSTED_PREFIX: &str = "trusted comment: ";
+
+/// A minisign public key
+#[derive(Debug)]
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+
+impl PublicKey {
+    /// Parse a public key, either a whole `minisign.pub` file or just its base64 line
+    pub fn parse(text: &str) -> Result<Self> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .rfind(|l| !l.is_empty() && !l.starts_with(UNTRUSTED_PREFIX))
+            .ok_or_else(|| anyhow::anyhow!("Empty minisign public key"))?;
+        let bytes = decode(line).context("Invalid minisign public key")?;
+        if bytes.len() != 42 || &bytes[..2] != b"Ed" {
+            anyhow::bail!("Invalid minisign public key: expected an Ed25519 key");
+        }
+        let key: [u8; 32] = bytes[10..].try_into()?;
+        Ok(PublicKey {
+            key_id: bytes[2..10].try_into()?,
+            key: VerifyingKey::from_bytes(&key).context("Invalid minisign public key")?,
+        })
+    }
+
+    /// Check that `signature`, the text of a `.minisig` file, signs `data` with this key
+    ///
+    /// Returns the signature's trusted comment.
+    pub fn verify(&self, data: &[u8], signature: &str) -> Result<String> {
+        let mut lines = signature
+            .lines()
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty());
+        if !lines
+            .next()
+            .is_some_and(|l| l.starts_with(UNTRUSTED_PREFIX))
+        {
+            anyhow::bail!("Malformed minisign signature: missing its untrusted comment");
+        }
+        let sig_line = lines
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Malformed minisign signature: missing signature"))?;
+        let trusted_comment = lines
+            .next()
+            .and_then(|l| l.strip_prefix(TRUSTED_PREFIX))
+            .ok_or_else(|| {
+                anyhow::anyhow!("Malformed minisign signature: missing trusted comment")
+            })?;
+        let global_line = lines.next().ok_or_else(|| {
+            anyhow::anyhow!("Malformed minisign signature: missing global signature")
+        })?;
+
+        let sig = decode(sig_line).context("Malformed minisign signature")?;
+        if sig.len() != 74 {
+            anyhow::bail!("Malformed minisign signature: wrong length");
+        }
+        if sig[2..10] != self.key_id {
+            anyhow::bail!(
+                "Signed with key {}, not the trusted key {}",
+                key_id_hex(&sig[2..10]),
+                key_id_hex(&self.key_id)
+            );
+        }
+        let signature = Signature::from_slice(&sig[10..])?;
+        let verified = match &sig[..2] {
+            b"ED" => self.key.verify(&Blake2b512::digest(data), &signature),
+            b"Ed" => self.key.verify(data, &signature),
+            _ => anyhow::bail!("Unsupported minisign signature algorithm"),
+        };
+        verified.map_err(|_| anyhow::anyhow!("Signature does not match the signed file"))?;
+
+        // The global signature covers the trusted comment, so it cannot be swapped
+        let global = Signature::from_slice(
+            &decode(global_line).context("Malformed minisign global signature")?,
+        )?;
+        let mut signed = sig[10..].to_vec();
+        signed.extend_from_slice(trusted_comment.as_bytes());
+        self.key
+            .verify(&signed, &global)
+            .map_err(|_| anyhow::anyhow!("Trusted comment does not match its signature"))?;
+
+        Ok(trusted_comment.to_string())
+    }
+}
+
+fn decode(line: &str) -> Result<Vec<u8>> {
+    Ok(base64::engine::general_purpose::STANDARD.decode(line.trim())?)
+}
+
+/// Key ids are shown as minisign shows them, little-endian
+fn key_id_hex(id: &[u8]) -> String {
+    id.iter().rev().map(|b| format!("{:02X}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::*;
+
+    const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn public_key(signing: &SigningKey) -> String {
+        let mut bytes = b"Ed".to_vec();
+        bytes.extend_from_slice(&KEY_ID);
+        bytes.extend_from_slice(signing.verifying_key().as_bytes());
+        format!(
+            "untrusted comment: minisign public key\n{}\n",
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        )
+    }
+
+    /// Sign `data` as `minisign -S` does
+    fn sign(signing: &SigningKey, data: &[u8], trusted_comment: &str) -> String {
+        let signature = signing.sign(&Blake2b512::digest(data)).to_bytes();
+        let mut sig = b"ED".to_vec();
+        sig.extend_from_slice(&KEY_ID);
+        sig.extend_from_slice(&signature);
+        let mut signed = signature.to_vec();
+        signed.extend_from_slice(trusted_comment.as_bytes());
+        let global = signing.sign(&signed).to_bytes();
+        let b64 = base64::engine::general_purpose::STANDARD;
+        format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+            b64.encode(sig),
+            trusted_comment,
+            b64.encode(global)
+        )
+    }
+
+    #[test]
+    fn test_verify() {
+        let signing = SigningKey::from_bytes(&[7; 32]);
+        let key = PublicKey::parse(&public_key(&signing)).unwrap();
+        let manifest = b"[pkg.nockup]\nversion = \"1.0.0\"\n";
+        let signature = sign(&signing, manifest, "timestamp:1760000000");
+
+        assert_eq!(
+            key.verify(manifest, &signature).unwrap(),
+            "timestamp:1760000000"
+        );
+        assert!(key
+            .verify(b"[pkg.nockup]\nversion = \"6.6.6\"\n", &signature)
+            .is_err());
+
+        // The trusted comment cannot be changed without the key
+        let altered = signature.replace("timestamp:1760000000", "timestamp:1770000000");
+        assert!(key.verify(manifest, &altered).is_err());
+
+        // Nor can another key sign for this one
+        let other = SigningKey::from_bytes(&[9; 32]);
+        assert!(key.verify(manifest, &sign(&other, manifest, "x")).is_err());
+    }
+}
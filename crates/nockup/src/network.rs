@@ -0,0 +1,145 @@
+//! Proxies for downloads and for the git nockup runs.
+//!
+//! Both honor `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` from the environment. A
+//! proxy configured in `~/.nockup/config.toml` takes precedence over them:
+//!
+//! ```toml
+//! [network]
+//! proxy = "socks5h://proxy.example.com:1080"   # or http://, https://, socks5://
+//! no_proxy = "localhost,.internal.example.com" # hosts reached directly
+//! ```
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::commands::common::get_cache_dir;
+
+/// Proxy URL schemes that both reqwest and git's curl understand
+const PROXY_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+
+/// The `[network]` table of ~/.nockup/config.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NetworkConfig {
+    #[serde(default)]
+    pub proxy: Option<String>,
+    // Comma-separated hosts, domains and IP ranges to reach without the proxy
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+}
+
+impl NetworkConfig {
+    /// Load the `[network]` table of ~/.nockup/config.toml
+    pub fn load() -> Result<Self> {
+        let config_path = get_cache_dir()?.join("config.toml");
+        if !config_path.exists() {
+            return Ok(Self::default());
+        }
+        let contents =
+            std::fs::read_to_string(&config_path).context("Failed to read config file")?;
+        #[derive(Deserialize)]
+        struct ConfigFile {
+            #[serde(default)]
+            network: NetworkConfig,
+        }
+        Ok(toml::from_str::<ConfigFile>(&contents)
+            .context("Failed to parse [network] section of config file")?
+            .network)
+    }
+
+    /// An HTTP client that goes through the configured proxy, or else the environment's
+    pub fn client(&self) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = self.reqwest_proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        builder.build().context("Failed to create HTTP client")
+    }
+
+    /// A blocking HTTP client that goes through the configured proxy, or else the environment's
+    pub fn blocking_client(&self) -> Result<reqwest::blocking::Client> {
+        let mut builder = reqwest::blocking::Client::builder();
+        if let Some(proxy) = self.reqwest_proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        builder.build().context("Failed to create HTTP client")
+    }
+
+    /// Environment for a git command, sending it through the configured proxy
+    ///
+    /// git's curl reads the lowercase variables before the uppercase ones, so these override
+    /// the environment's. Without a configured proxy, git finds the environment's itself.
+    pub fn git_env(&self) -> Vec<(String, String)> {
+        let mut env = Vec::new();
+        if let Some(proxy) = &self.proxy {
+            for var in ["http_proxy", "https_proxy", "all_proxy"] {
+                env.push((var.to_string(), proxy.clone()));
+            }
+            if let Some(no_proxy) = &self.no_proxy {
+                env.push(("no_proxy".to_string(), no_proxy.clone()));
+            }
+        }
+        env
+    }
+
+    fn reqwest_proxy(&self) -> Result<Option<reqwest::Proxy>> {
+        let Some(url) = &self.proxy else {
+            return Ok(None);
+        };
+        let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+        if !scheme.is_some_and(|s| PROXY_SCHEMES.contains(&s)) {
+            anyhow::bail!(
+                "Invalid [network] proxy '{}': expected a URL starting with one of {}",
+                url,
+                PROXY_SCHEMES.map(|s| format!("{}://", s)).join(", ")
+            );
+        }
+        // A configured proxy replaces the environment's, but not its NO_PROXY
+        let no_proxy = match &self.no_proxy {
+            Some(no_proxy) => reqwest::NoProxy::from_string(no_proxy),
+            None => reqwest::NoProxy::from_env(),
+        };
+        let proxy = reqwest::Proxy::all(url)
+            .with_context(|| format!("Invalid [network] proxy '{}'", url))?
+            .no_proxy(no_proxy);
+        Ok(Some(proxy))
+    }
+}
+
+/// An HTTP client for nockup's downloads, through the proxy of ~/.nockup/config.toml if any
+pub fn client() -> Result<reqwest::Client> {
+    NetworkConfig::load()?.client()
+}
+
+/// A blocking HTTP client for nockup's downloads, through the proxy of ~/.nockup/config.toml
+pub fn blocking_client() -> Result<reqwest::blocking::Client> {
+    NetworkConfig::load()?.blocking_client()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy() {
+        let config: NetworkConfig =
+            toml::from_str("proxy = \"socks5h://proxy.internal:1080\"\nno_proxy = \"localhost\"\n")
+                .unwrap();
+        assert!(config.client().is_ok());
+        let env = config.git_env();
+        assert!(env.contains(&(
+            "https_proxy".to_string(),
+            "socks5h://proxy.internal:1080".to_string()
+        )));
+        assert!(env.contains(&("no_proxy".to_string(), "localhost".to_string())));
+
+        assert!(NetworkConfig::default().git_env().is_empty());
+        assert!(NetworkConfig::default().client().is_ok());
+
+        for url in ["proxy.internal:3128", "ftp://proxy.internal"] {
+            let config = NetworkConfig {
+                proxy: Some(url.to_string()),
+                no_proxy: None,
+            };
+            assert!(config.client().is_err(), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// src/output.rs
+//! How nockup reports to whoever runs it
+//!
+//! As text, commands print progress for people to stdout. With `--format json`, stdout carries
+//! only events, one JSON object per line with an `event` field naming it, for tools that wrap
+//! nockup; the text goes to stderr, uncolored.
+//!
+//! In CI mode (`--ci`, or `CI` set in the environment) the text is plain: no colors, no emoji
+//! and no blank lines, one line per step.
+use std::fmt;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+static FORMAT: OnceCell<OutputFormat> = OnceCell::new();
+static CI: AtomicBool = AtomicBool::new(false);
+
+/// Choose the output format, once, before any command runs
+pub fn set_format(format: OutputFormat) {
+    if format == OutputFormat::Json {
+        colored::control::set_override(false);
+    }
+    let _ = FORMAT.set(format);
+}
+
+pub fn is_json() -> bool {
+    FORMAT.get() == Some(&OutputFormat::Json)
+}
+
+/// Turn CI mode on or off, before any command runs
+pub fn set_ci(ci: bool) {
+    if ci {
+        colored::control::set_override(false);
+    }
+    CI.store(ci, Ordering::Relaxed);
+}
+
+pub fn is_ci() -> bool {
+    CI.load(Ordering::Relaxed)
+}
+
+/// Whether the environment is a CI system, which sets `CI` to anything but `0` or `false`
+pub fn ci_from_env() -> bool {
+    std::env::var("CI").is_ok_and(|v| !v.is_empty() && v != "0" && !v.eq_ignore_ascii_case("false"))
+}
+
+/// Print a line for people: to stdout as text, or to stderr with `--format json`
+#[macro_export]
+macro_rules! say {
+    () => {
+        $crate::output::say(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::say(format_args!($($arg)*))
+    };
+}
+
+#[doc(hidden)]
+pub fn say(args: fmt::Arguments) {
+    let line = if is_ci() {
+        let line = plain(&args.to_string());
+        if line.trim().is_empty() {
+            return;
+        }
+        line
+    } else {
+        args.to_string()
+    };
+    if is_json() {
+        eprintln!("{}", line)
+    } else {
+        println!("{}", line)
+    }
+}
+
+/// `line` without the emoji or symbol, such as ✓ or →, that marks what kind of line it is
+fn plain(line: &str) -> String {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+    let rest = text.trim_start_matches(is_marker);
+    if rest.len() == text.len() {
+        return line.to_string();
+    }
+    format!("{}{}", indent, rest.trim_start())
+}
+
+fn is_marker(c: char) -> bool {
+    matches!(c,
+        '\u{2190}'..='\u{24FF}'       // Arrows and technical symbols
+        | '\u{25A0}'..='\u{2BFF}'     // Shapes, dingbats and symbols, but not box drawing
+        | '\u{1F000}'..='\u{1FAFF}'   // Emoji
+        | '\u{FE0F}' | '\u{200D}'     // Emoji presentation selector and joiner
+    )
+}
+
+#[derive(Serialize)]
+struct Event<'a, T: Serialize> {
+    event: &'a str,
+    #[serde(flatten)]
+    data: T,
+}
+
+/// Print an event to stdout with `--format json`; as text, events are not printed
+///
+/// `data` must serialize to a JSON object, whose fields are added to the event's.
+pub fn emit<T: Serialize>(event: &str, data: T) {
+    if is_json() {
+        match event_line(event, data) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("Failed to serialize {} event: {}", event, e),
+        }
+    }
+}
+
+fn event_line<T: Serialize>(event: &str, data: T) -> serde_json::Result<String> {
+    serde_json::to_string(&Event { event, data })
+}
+
+/// Where the stdout of tools such as cargo and hoonc goes, so it stays out of the events
+pub fn child_stdout() -> Stdio {
+    if is_json() {
+        std::io::stderr().into()
+    } else {
+        Stdio::inherit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(plain("  ✓ Installed sequent"), "  Installed sequent");
+        assert_eq!(
+            plain("🗑️ Clearing nockup cache..."),
+            "Clearing nockup cache..."
+        );
+        assert_eq!(plain("  → 3 packages, 1.2 MB"), "  3 packages, 1.2 MB");
+        assert_eq!(
+            plain("  sequent v1.0.0 → v1.1.0"),
+            "  sequent v1.0.0 → v1.1.0"
+        );
+        assert_eq!(plain("│   └── lagoon latest"), "│   └── lagoon latest");
+    }
+
+    #[test]
+    fn test_event_line() {
+        #[derive(Serialize)]
+        struct Cleared {
+            cache: &'static str,
+            freed_bytes: u64,
+        }
+
+        assert_eq!(
+            event_line(
+                "cache-cleared",
+                Cleared {
+                    cache: "git",
+                    freed_bytes: 1024
+                }
+            )
+            .unwrap(),
+            r#"{"event":"cache-cleared","cache":"git","freed_bytes":1024}"#
+        );
+        assert_eq!(
+            event_line("channel", serde_json::json!({ "channel": "stable" })).unwrap(),
+            r#"{"event":"channel","channel":"stable"}"#
+        );
+    }
+}
@@ -1,116 +1,561 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use colored::Colorize;
 
 use crate::cache::PackageCache;
+use crate::failure::{Classify, Failure};
+use crate::git_auth::GitAuth;
 use crate::git_fetcher::{GitFetcher, GitSpec};
-use crate::manifest::{DependencySpec, HoonPackage};
-use crate::resolver::types::{ResolvedGraph, ResolvedPackage};
-use crate::resolver::{registry, VersionSpec};
+use crate::manifest::{DependencySpec, HoonPackage, LockSource, NockAppLock, PatchSpec};
+use crate::network::NetworkConfig;
+use crate::resolver::local::LocalPackage;
+use crate::resolver::registry::{Deprecated, RegistrySource, Yanked};
+use crate::resolver::types::{DependencyRequest, ResolvedGraph, ResolvedPackage, VersionConflict};
+use crate::resolver::{kelvin_tags, registry, VersionSpec};
 
 /// Main dependency resolver
 pub struct Resolver {
     cache: PackageCache,
     git_fetcher: GitFetcher,
+    offline: bool,                        // Resolve only from the package cache
+    registries: Vec<RegistrySource>,      // Searched in order for packages named without a git URL
+    patches: HashMap<String, PatchSpec>,  // Replacement sources, by package name
+    locked: HashMap<String, String>,      // Locked commits, kept even if yanked, by package name
+    local: HashMap<String, LocalPackage>, // Workspace libraries used in place of their sources
 }
 
 impl Resolver {
     /// Create a new resolver
-    pub fn new() -> Result<Self> {
+    ///
+    /// An offline resolver never uses the network: packages, the registry and git repositories
+    /// all come from ~/.nockup/cache.
+    pub fn new(offline: bool) -> Result<Self> {
         let cache = PackageCache::new()?;
-        let git_fetcher = GitFetcher::new(cache.git_dir());
+        let git_fetcher = GitFetcher::new(cache.git_dir())
+            .with_offline(offline)
+            .with_auth(GitAuth::load()?)
+            .with_network(NetworkConfig::load()?);
 
-        Ok(Self { cache, git_fetcher })
+        Ok(Self {
+            cache,
+            git_fetcher,
+            offline,
+            registries: registry::registry_sources(None)?,
+            patches: HashMap::new(),
+            locked: HashMap::new(),
+            local: HashMap::new(),
+        })
+    }
+
+    /// Look packages up in these registries, e.g. including those of a project manifest
+    pub fn with_registries(mut self, registries: Vec<RegistrySource>) -> Self {
+        self.registries = registries;
+        self
+    }
+
+    /// Fetch these packages from other sources, wherever they appear in the graph
+    ///
+    /// Local `path` patches are taken relative to `base_dir`, the directory of the manifest.
+    pub fn with_patches(mut self, patches: BTreeMap<String, PatchSpec>, base_dir: &Path) -> Self {
+        self.patches = patches
+            .into_iter()
+            .map(|(name, mut patch)| {
+                if patch.git.is_none() {
+                    if let Some(path) = patch.path.take() {
+                        patch.git = Some(base_dir.join(path).to_string_lossy().into_owned());
+                    }
+                }
+                (name, patch)
+            })
+            .collect();
+        self
+    }
+
+    /// Keep the commits of this lock if they are resolved again, even once yanked
+    pub fn with_lock(mut self, lock: &NockAppLock) -> Self {
+        self.locked = lock
+            .package
+            .iter()
+            .filter_map(|locked| match &locked.source {
+                LockSource::Git { commit, .. } => Some((locked.name.clone(), commit.clone())),
+                LockSource::Path { .. } => None,
+            })
+            .collect();
+        self
+    }
+
+    /// Install these libraries of the project's workspace from their directories
+    ///
+    /// Each is used wherever it is asked for at a version it has, unless it is patched.
+    pub fn with_local_packages(mut self, packages: HashMap<String, LocalPackage>) -> Self {
+        self.local = packages;
+        self
+    }
+
+    /// Clone whole repositories instead of only the paths packages use
+    pub fn with_full_clone(mut self, full_clone: bool) -> Self {
+        self.git_fetcher = self.git_fetcher.with_full_clone(full_clone);
+        self
     }
 
     /// Resolve all dependencies in a manifest
     pub async fn resolve(&self, manifest: &HoonPackage) -> Result<ResolvedGraph> {
-        println!("{} Resolving dependencies...", "📦".cyan());
+        say!("{} Resolving dependencies...", "📦".cyan());
 
         let mut graph = ResolvedGraph::new();
-        let mut visited = std::collections::HashSet::new();
         let mut to_resolve = Vec::new();
+        let mut missing = Vec::new();
+        // Every requirement on each package so far, and the spec it was last resolved for
+        let mut requirements: HashMap<String, Vec<Requirement>> = HashMap::new();
+        let mut resolved_for: HashMap<String, VersionSpec> = HashMap::new();
 
         // Get dependencies from manifest
         let dependencies = match manifest.dependencies.as_ref() {
             Some(deps) if !deps.is_empty() => deps,
             _ => {
-                println!("  No dependencies to resolve");
+                say!("  No dependencies to resolve");
                 return Ok(graph);
             }
         };
 
-        // Queue initial dependencies
+        // Queue initial dependencies, with the package that asked for them (None for the manifest)
         for (name, spec) in dependencies {
-            to_resolve.push((name.clone(), spec.clone()));
+            to_resolve.push((name.clone(), spec.clone(), None));
         }
 
+        let (yanked, deprecated) = registry::notices(&self.registries, self.offline).await;
+        let mut noticed = HashSet::new();
+
         // Resolve dependencies recursively
-        while let Some((name, spec)) = to_resolve.pop() {
-            // Skip if already resolved
-            if visited.contains(&name) {
-                continue;
+        while let Some((name, spec, dependent)) = to_resolve.pop() {
+            let version = self
+                .spec_to_version_spec(&spec)
+                .with_context(|| format!("Invalid version for dependency '{}'", name))
+                .classify(Failure::Resolution)?;
+            graph.add_request(dependent.as_deref(), &name, &version.to_canonical_string());
+
+            let reqs = requirements.entry(name.clone()).or_default();
+            reqs.push(Requirement {
+                dependent,
+                spec,
+                version,
+            });
+            let versions: Vec<VersionSpec> = reqs.iter().map(|r| r.version.clone()).collect();
+            let Some(unified) = VersionSpec::unify(&versions) else {
+                return Err(Failure::Resolution.wrap(conflict(&name, reqs, Vec::new()).into()));
+            };
+
+            // Skip if already resolved for a spec every requirement accepts
+            match resolved_for.get(&name) {
+                Some(previous) if *previous == unified => continue,
+                Some(_) => {
+                    say!(
+                        "  {} Re-resolving {} for {}...",
+                        "↻".cyan(),
+                        name.yellow(),
+                        unified.to_canonical_string().cyan()
+                    );
+                }
+                None => say!("  {} Resolving {}...", "→".cyan(), name.yellow()),
             }
-            visited.insert(name.clone());
-
-            println!("  {} Resolving {}...", "→".cyan(), name.yellow());
-
-            // Check cache first
-            if let Some(cached) = self.check_cache(&name, &spec).await? {
-                println!("    {} Found in cache", "✓".green());
-                graph.add_package(cached);
-
-                // Queue transitive dependencies
-                let deps = registry::get_dependencies(&name).await;
-                for dep in deps {
-                    if !visited.contains(&dep) {
-                        // Use "latest" for transitive dependencies
-                        to_resolve
-                            .push((dep.clone(), DependencySpec::Simple("latest".to_string())));
-                    }
+            let spec = combined_spec(reqs, &unified);
+
+            // What the version being replaced asked for no longer applies
+            if resolved_for.insert(name.clone(), unified.clone()).is_some() {
+                graph.packages.remove(&name);
+                graph
+                    .requests
+                    .retain(|r| r.dependent.as_deref() != Some(name.as_str()));
+                for reqs in requirements.values_mut() {
+                    reqs.retain(|r| r.dependent.as_deref() != Some(name.as_str()));
                 }
+            }
+
+            // Check cache first; a cached copy since yanked is resolved again, if possible
+            let local = self.local_for(&name, &unified);
+            let cached = match local {
+                Some(_) => None,
+                None => self.check_cache(&name, &spec).await?.filter(|cached| {
+                    self.offline || yank_of(cached, &yanked).is_none() || self.is_locked(cached)
+                }),
+            };
+            let resolved = if let Some(local) = local {
+                say!(
+                    "    {} Using {} from the workspace",
+                    "✓".green(),
+                    local.path.cyan()
+                );
+                local.resolve().classify(Failure::Resolution)?
+            } else if let Some(cached) = cached {
+                say!("    {} Found in cache", "✓".green());
+                cached
+            } else if self.offline {
+                say!("    {} Not in cache", "✗".red());
+                missing.push(format!("{}@{}", name, unified.to_canonical_string()));
                 continue;
+            } else {
+                // Resolve from source
+                match self.resolve_dependency(&name, &spec, &yanked).await {
+                    Ok(resolved) => resolved,
+                    // No tag meets the requirements; report who asked for what
+                    Err(e) => match e.downcast::<VersionConflict>() {
+                        Ok(found) => {
+                            return Err(Failure::Resolution.wrap(
+                                conflict(&name, &requirements[&name], found.available).into(),
+                            ))
+                        }
+                        Err(e) => {
+                            return Err(Failure::Resolution.wrap(
+                                e.context(format!("Failed to resolve dependency '{}'", name)),
+                            ))
+                        }
+                    },
+                }
+            };
+
+            if let Some(yank) = yank_of(&resolved, &yanked).filter(|_| local.is_none()) {
+                if !self.is_locked(&resolved) {
+                    return Err(Failure::Resolution.wrap(yanked_error(&resolved, yank)));
+                }
+                say!(
+                    "    {} This version has been yanked; keeping it as nockapp.lock has it",
+                    "⚠".yellow()
+                );
+            }
+            if noticed.insert(name.clone()) {
+                print_deprecation(&name, &deprecated);
             }
 
-            // Resolve from source
-            let resolved = self
-                .resolve_dependency(&name, &spec)
-                .await
-                .with_context(|| format!("Failed to resolve dependency '{}'", name))?;
+            // Queue transitive dependencies: those the package's hoon.toml asks for, then any
+            // others the registry lists, at "latest"
+            let mut deps: Vec<(String, DependencySpec)> = resolved
+                .dependencies
+                .iter()
+                .map(|(dep, dep_spec)| (dep.clone(), dep_spec.clone()))
+                .collect();
+            deps.sort_by(|a, b| a.0.cmp(&b.0));
+            for dep in registry::get_dependencies(&name, &self.registries, self.offline).await {
+                if !resolved.dependencies.contains_key(&dep) {
+                    deps.push((dep, DependencySpec::Simple("latest".to_string())));
+                }
+            }
 
             graph.add_package(resolved);
+            for (dep, dep_spec) in deps {
+                to_resolve.push((dep, dep_spec, Some(name.clone())));
+            }
+        }
+
+        if !missing.is_empty() {
+            missing.sort();
+            return Err(Failure::Resolution.wrap(anyhow::anyhow!(
+                "Offline mode: {} packages are not in the cache at {}:\n  - {}\n\
+                Run `nockup package install` with network access to cache them.",
+                missing.len(),
+                self.cache.packages_dir().display(),
+                missing.join("\n  - ")
+            )));
+        }
+
+        // Compute installation order (topological sort)
+        graph
+            .compute_install_order()
+            .classify(Failure::Resolution)?;
+
+        say!("{} Resolved {} packages", "✓".green(), graph.packages.len());
+
+        Ok(graph)
+    }
+
+    /// Build the graph recorded in nockapp.lock, at its locked commits, without resolving
+    ///
+    /// Every dependency of the manifest must be locked at a version its spec accepts. Packages
+    /// missing from the cache are fetched at their locked commit.
+    pub async fn resolve_locked(
+        &self,
+        manifest: &HoonPackage,
+        lock: &NockAppLock,
+    ) -> Result<ResolvedGraph> {
+        say!("{} Using the versions in nockapp.lock...", "🔒".cyan());
+
+        let dependencies = manifest.dependencies.clone().unwrap_or_default();
+        let problems = lock_mismatches(&dependencies, lock)?;
+        if !problems.is_empty() {
+            return Err(Failure::Resolution.wrap(anyhow::anyhow!(
+                "nockapp.lock does not match nockapp.toml:\n  - {}\n\
+                Run `nockup package install` without --frozen to update it.",
+                problems.join("\n  - ")
+            )));
+        }
+
+        let (_, deprecated) = registry::notices(&self.registries, self.offline).await;
+        let mut graph = ResolvedGraph::new();
+        let mut missing = Vec::new();
+        for (name, spec) in &dependencies {
+            let wanted = self.spec_to_version_spec(spec)?;
+            graph.add_request(None, name, &wanted.to_canonical_string());
+        }
+        for locked in &lock.package {
+            let LockSource::Git { url, commit, path } = &locked.source else {
+                // Workspace libraries are locked to their directories, and installed from there
+                let local = self.local.get(&locked.name).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "'{}' is locked to a local path, but is not a library of this workspace",
+                        locked.name
+                    )
+                })?;
+                say!(
+                    "  {} {} from {}",
+                    "→".cyan(),
+                    locked.name.yellow(),
+                    local.path.cyan()
+                );
+                for (dep, version) in &locked.dependencies {
+                    graph.add_request(Some(&locked.name), dep, version);
+                }
+                graph.install_order.push(locked.name.clone());
+                graph.add_package(local.resolve()?);
+                continue;
+            };
+            let version_spec = VersionSpec::parse(&locked.version)?;
+
+            // Registry packages may be installed to a path of their own; manifest files are kept
+            let spec = dependencies.get(&locked.name);
+            let install_path = match spec {
+                Some(DependencySpec::Full { .. }) => None,
+                _ => registry::lookup(&locked.name, &self.registries, self.offline)
+                    .await
+                    .and_then(|entry| registry::to_git_spec(&entry, None, None).install_path),
+            };
+            let source_files = match spec {
+                Some(DependencySpec::Full {
+                    files: Some(files), ..
+                }) => Some(files.iter().map(|f| format!("{}.hoon", f)).collect()),
+                _ => None,
+            };
+
+            let pkg = ResolvedPackage {
+                name: locked.name.clone(),
+                version_spec,
+                commit: commit.clone(),
+                source_url: url.clone(),
+                source_path: path.clone(),
+                install_path,
+                source_files,
+                // As asked for when locked; lockfiles from before they were recorded have none
+                dependencies: locked
+                    .dependencies
+                    .iter()
+                    .map(|(dep, version)| (dep.clone(), DependencySpec::Simple(version.clone())))
+                    .collect(),
+                license: locked.license.clone(),
+                local: None,
+            };
+            for (dep, version) in &locked.dependencies {
+                graph.add_request(Some(&locked.name), dep, version);
+            }
+
+            // Wildcard versions are cached by commit, as in resolve_dependency
+            let version_str = pkg.version_spec.to_canonical_string();
+            let cache_version = if version_str == "*" {
+                format!("commit:{}", commit)
+            } else {
+                version_str
+            };
 
-            // Queue transitive dependencies
-            let deps = registry::get_dependencies(&name).await;
-            for dep in deps {
-                if !visited.contains(&dep) {
-                    // Use "latest" for transitive dependencies
-                    to_resolve.push((dep.clone(), DependencySpec::Simple("latest".to_string())));
+            say!(
+                "  {} {}@{}",
+                "→".cyan(),
+                pkg.name.yellow(),
+                locked.version.cyan()
+            );
+            print_deprecation(&pkg.name, &deprecated);
+            let cached = self.cache.find_cached(&pkg.name, &cache_version).await?;
+            if cached.is_none_or(|c| c.commit != *commit) {
+                if self.offline {
+                    say!("    {} Not in cache", "✗".red());
+                    missing.push(format!("{}@{}", pkg.name, locked.version));
+                    continue;
                 }
+                self.fetch_locked(&pkg, &cache_version).await?;
             }
+
+            graph.install_order.push(pkg.name.clone());
+            graph.add_package(pkg);
         }
 
-        // Compute installation order (topological sort)
-        graph.compute_install_order()?;
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Offline mode: {} locked packages are not in the cache at {}:\n  - {}\n\
+                Run `nockup package install --frozen` with network access to cache them.",
+                missing.len(),
+                self.cache.packages_dir().display(),
+                missing.join("\n  - ")
+            );
+        }
 
-        println!("{} Resolved {} packages", "✓".green(), graph.packages.len());
+        say!("{} Locked {} packages", "✓".green(), graph.packages.len());
 
         Ok(graph)
     }
 
+    /// Fetch a locked package at its commit into the package cache
+    async fn fetch_locked(&self, pkg: &ResolvedPackage, cache_version: &str) -> Result<()> {
+        let git_spec = GitSpec {
+            url: pkg.source_url.clone(),
+            commit: Some(pkg.commit.clone()),
+            tag: None,
+            branch: None,
+            path: pkg.source_path.clone(),
+            install_path: pkg.install_path.clone(),
+            file: None,
+        };
+
+        say!(
+            "    {} Fetching from {}...",
+            "⬇".cyan(),
+            git_spec.url.cyan()
+        );
+        let repo_path = self
+            .git_fetcher
+            .fetch(&git_spec)
+            .await
+            .context("Failed to fetch git repository")?;
+
+        let source_dir = match git_spec.path {
+            Some(ref subpath) => repo_path.join(subpath),
+            None => repo_path,
+        };
+        if !source_dir.exists() {
+            anyhow::bail!(
+                "Source path {} does not exist in repository",
+                source_dir.display()
+            );
+        }
+
+        // Replace any copy cached for the same version at another commit
+        self.cache.evict(&pkg.name, cache_version).await?;
+        self.cache
+            .cache_package(
+                &pkg.name, cache_version, &pkg.commit, &pkg.source_url, &source_dir,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Fetch a resolved package again at its exact commit, replacing its cached copy
+    pub async fn refetch(&self, pkg: &ResolvedPackage, cache_version: &str) -> Result<PathBuf> {
+        let git_spec = GitSpec {
+            url: pkg.source_url.clone(),
+            commit: Some(pkg.commit.clone()),
+            tag: None,
+            branch: None,
+            path: pkg.source_path.clone(),
+            install_path: pkg.install_path.clone(),
+            file: None,
+        };
+
+        say!(
+            "    {} Fetching from {}...",
+            "⬇".cyan(),
+            git_spec.url.cyan()
+        );
+        let repo_path = self
+            .git_fetcher
+            .refetch(&git_spec)
+            .await
+            .context("Failed to fetch git repository")?;
+
+        let source_dir = match git_spec.path {
+            Some(ref subpath) => repo_path.join(subpath),
+            None => repo_path,
+        };
+
+        self.cache.evict(&pkg.name, cache_version).await?;
+        self.cache
+            .cache_package(
+                &pkg.name, cache_version, &pkg.commit, &pkg.source_url, &source_dir,
+            )
+            .await
+    }
+
+    /// The workspace library to use for `name`, if any, when `wanted` is asked of it
+    fn local_for(&self, name: &str, wanted: &VersionSpec) -> Option<&LocalPackage> {
+        let local = self.local.get(name)?;
+        if self.patches.contains_key(name) {
+            return None;
+        }
+        if !local.satisfies(wanted) {
+            say!(
+                "    {} {} in the workspace is not {}, resolving from its source",
+                "⚠".yellow(),
+                local.path,
+                wanted.to_canonical_string()
+            );
+            return None;
+        }
+        Some(local)
+    }
+
     /// Resolve a single dependency
     async fn resolve_dependency(
         &self,
         name: &str,
         spec: &DependencySpec,
+        yanked: &[Yanked],
     ) -> Result<ResolvedPackage> {
         // Convert DependencySpec to GitSpec
-        let git_spec = self.dep_spec_to_git_spec(spec, name).await?;
+        let mut git_spec = self.dep_spec_to_git_spec(spec, name).await?;
+
+        // A semver requirement resolves to the highest tag meeting it; a patch names its own ref
+        let patched = self.patches.contains_key(name);
+        if let VersionSpec::Semver(req) = self.spec_to_version_spec(spec)? {
+            if !req.comparators.is_empty()
+                && !patched
+                && git_spec.commit.is_none()
+                && git_spec.tag.is_none()
+            {
+                git_spec.tag = Some(self.select_tag(name, &git_spec.url, &req, yanked).await?);
+            }
+        }
+
+        // A kelvin resolves to its tag, and a kelvin range to the newest kelvin tag in it. A
+        // registry package wanted at `latest` takes its newest kelvin tag, if it has any, rather
+        // than the head of its default branch.
+        let version_spec = self.spec_to_version_spec(spec)?;
+        if !patched && git_spec.commit.is_none() && git_spec.tag.is_none() {
+            match version_spec {
+                VersionSpec::Kelvin(_) | VersionSpec::KelvinRange(_) => {
+                    let tag = self
+                        .select_kelvin_tag(name, &git_spec.url, &version_spec, yanked)
+                        .await?;
+                    git_spec.tag = Some(tag.ok_or_else(|| VersionConflict {
+                        package: name.to_string(),
+                        requirements: vec![DependencyRequest {
+                            dependent: None,
+                            name: name.to_string(),
+                            version: version_spec.to_canonical_string(),
+                        }],
+                        available: Vec::new(),
+                    })?);
+                }
+                VersionSpec::Semver(_)
+                    if version_spec.is_any()
+                        && git_spec.branch.is_none()
+                        && !matches!(spec, DependencySpec::Full { .. }) =>
+                {
+                    git_spec.tag = self
+                        .select_kelvin_tag(name, &git_spec.url, &version_spec, yanked)
+                        .await?;
+                }
+                _ => {}
+            }
+        }
 
         // Fetch the repository
-        println!(
+        say!(
             "    {} Fetching from {}...",
             "⬇".cyan(),
             git_spec.url.cyan()
@@ -124,7 +569,7 @@ impl Resolver {
         // Determine exact commit
         let commit = self.get_exact_commit(&git_spec).await?;
 
-        println!(
+        say!(
             "    {} Commit: {}",
             "→".cyan(),
             commit.chars().take(12).collect::<String>().yellow()
@@ -148,21 +593,34 @@ impl Resolver {
         // Validate all requested source files exist
         let source_files = self.validate_source_files(&source_dir, spec)?;
 
-        // Check for transitive dependencies (look for hoon.toml in fetched repo)
-        let transitive_deps = self
-            .load_transitive_deps(repo_path.as_path(), &git_spec)
+        // Check for transitive dependencies and a license (look for hoon.toml in fetched repo)
+        let package_manifest = self
+            .load_package_manifest(repo_path.as_path(), &git_spec)
             .await?;
+        let license = package_manifest
+            .as_ref()
+            .and_then(|pkg| pkg.package.license.clone());
+        let transitive_deps: HashMap<String, DependencySpec> = package_manifest
+            .and_then(|pkg| pkg.dependencies)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
 
         if !transitive_deps.is_empty() {
-            println!(
+            say!(
                 "    {} Found {} transitive dependencies",
                 "→".cyan(),
                 transitive_deps.len()
             );
         }
 
-        // Cache the package (always cache the full source directory)
-        let version_spec = self.spec_to_version_spec(spec)?;
+        // Cache the package (always cache the full source directory). A patched package is pinned
+        // to the commit fetched, so it is never confused with the package it replaces.
+        let version_spec = if patched {
+            VersionSpec::Commit(commit.clone())
+        } else {
+            version_spec
+        };
         let version_str = version_spec.to_canonical_string();
 
         // For wildcard versions ("*" or "latest"), cache using the commit hash instead
@@ -173,7 +631,7 @@ impl Resolver {
             version_str.clone()
         };
 
-        println!("    {} Caching to packages cache...", "💾".cyan());
+        say!("    {} Caching to packages cache...", "💾".cyan());
 
         self.cache
             .cache_package(
@@ -194,6 +652,8 @@ impl Resolver {
                 Some(source_files)
             },
             dependencies: transitive_deps,
+            license,
+            local: None,
         })
     }
 
@@ -206,7 +666,28 @@ impl Resolver {
         let version_spec = self.spec_to_version_spec(spec)?;
         let version_str = version_spec.to_canonical_string();
 
-        if let Some(cached) = self.cache.find_cached(name, &version_str).await? {
+        // Wildcard versions are cached by commit; online they are resolved again to pick up
+        // new commits, but offline the last commit fetched is the best available. The same goes
+        // for patched packages, from the patch's source.
+        let patch_url = self
+            .patches
+            .get(name)
+            .and_then(|patch| patch.git.as_deref());
+        let cached = match patch_url {
+            Some(url) if self.offline => self.cache.find_latest_commit_from(name, url).await?,
+            Some(_) => None,
+            None if self.offline && version_str == "*" => {
+                self.cache.find_latest_commit(name).await?
+            }
+            None => self.cache.find_cached(name, &version_str).await?,
+        };
+
+        if let Some(cached) = cached {
+            let version_spec = match patch_url {
+                Some(_) => VersionSpec::Commit(cached.commit.clone()),
+                None => version_spec,
+            };
+
             // Reconstruct the GitSpec to get source_path and source_files
             let git_spec = self.dep_spec_to_git_spec(spec, name).await?;
 
@@ -218,6 +699,19 @@ impl Resolver {
                 _ => None,
             };
 
+            // The package's own requirements and license, from the hoon.toml cached with it
+            let manifest_path = self
+                .cache
+                .package_path(name, &cached.version_spec)
+                .join("hoon.toml");
+            let (dependencies, license) = match HoonPackage::load(&manifest_path)? {
+                Some(pkg) => (
+                    pkg.dependencies.unwrap_or_default().into_iter().collect(),
+                    pkg.package.license,
+                ),
+                None => (HashMap::new(), None),
+            };
+
             return Ok(Some(ResolvedPackage {
                 name: name.to_string(),
                 version_spec,
@@ -226,19 +720,52 @@ impl Resolver {
                 source_path: git_spec.path,
                 install_path: git_spec.install_path,
                 source_files,
-                dependencies: HashMap::new(), // TODO: Store in cache metadata
+                dependencies,
+                license,
+                local: None,
             }));
         }
 
         Ok(None)
     }
 
-    /// Convert DependencySpec to GitSpec
+    /// Convert DependencySpec to GitSpec, fetching patched packages from their patch
+    ///
+    /// A patch replaces the repository and ref, but keeps the path within the repository that
+    /// the package would otherwise be fetched from, so a fork is laid out like its upstream.
     async fn dep_spec_to_git_spec(&self, spec: &DependencySpec, name: &str) -> Result<GitSpec> {
+        let Some(patch) = self.patches.get(name) else {
+            return self.source_git_spec(spec, name).await;
+        };
+        if patch.path.is_some() {
+            anyhow::bail!("Patch for '{}' sets both 'git' and 'path'", name);
+        }
+        let url = patch
+            .git
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Patch for '{}' needs a 'git' URL or a 'path'", name))?;
+
+        // The package need not exist upstream, e.g. if the patch adds it to the registry's
+        let upstream = self.source_git_spec(spec, name).await.ok();
+        say!("    {} Patched to {}", "⤷".cyan(), url.cyan());
+
+        Ok(GitSpec {
+            url,
+            commit: patch.commit.clone(),
+            tag: patch.tag.clone(),
+            branch: patch.branch.clone(),
+            path: upstream.as_ref().and_then(|u| u.path.clone()),
+            install_path: upstream.and_then(|u| u.install_path),
+            file: None,
+        })
+    }
+
+    /// Convert DependencySpec to GitSpec, from the manifest or the registries
+    async fn source_git_spec(&self, spec: &DependencySpec, name: &str) -> Result<GitSpec> {
         match spec {
             DependencySpec::Simple(version) => {
                 // Try to look up in registry
-                if let Some(entry) = registry::lookup(name).await {
+                if let Some(entry) = registry::lookup(name, &self.registries, self.offline).await {
                     // Parse the version spec to extract tag/branch/commit
                     let version_spec = VersionSpec::parse(version)?;
                     let (tag, branch) = match version_spec {
@@ -249,7 +776,10 @@ impl Resolver {
                             // "latest" or "*" means use the default branch
                             (None, None)
                         }
-                        VersionSpec::Semver(_) => (Some(version.clone()), None),
+                        VersionSpec::Semver(_) | VersionSpec::KelvinRange(_) => {
+                            // The tag meeting the requirement is picked by resolve_dependency
+                            (None, None)
+                        }
                         VersionSpec::Commit(_) => {
                             // For commits, we'll let get_exact_commit handle it
                             (None, None)
@@ -266,7 +796,7 @@ impl Resolver {
             }
             DependencySpec::Version { version } => {
                 // Try to look up in registry
-                if let Some(entry) = registry::lookup(name).await {
+                if let Some(entry) = registry::lookup(name, &self.registries, self.offline).await {
                     let version_spec = VersionSpec::parse(version)?;
                     let (tag, branch) = match version_spec {
                         VersionSpec::Kelvin(k) => (Some(format!("{}k", k)), None),
@@ -276,8 +806,9 @@ impl Resolver {
                             // "latest" or "*" means use the default branch
                             (None, None)
                         }
-                        VersionSpec::Semver(_) => (Some(version.clone()), None),
-                        VersionSpec::Commit(_) => (None, None),
+                        VersionSpec::Semver(_)
+                        | VersionSpec::KelvinRange(_)
+                        | VersionSpec::Commit(_) => (None, None),
                     };
                     Ok(registry::to_git_spec(&entry, tag, branch))
                 } else {
@@ -313,6 +844,116 @@ impl Resolver {
         }
     }
 
+    /// The highest tag of the repository at `url` that is a version meeting `req`
+    ///
+    /// Yanked tags are passed over, unless nockapp.lock has the package at the tag's commit.
+    async fn select_tag(
+        &self,
+        name: &str,
+        url: &str,
+        req: &semver::VersionReq,
+        yanked: &[Yanked],
+    ) -> Result<String> {
+        let mut versions: Vec<(semver::Version, String)> = self
+            .git_fetcher
+            .list_tags(url)
+            .await?
+            .into_iter()
+            .filter_map(|tag| {
+                let version = semver::Version::parse(tag.trim_start_matches('v')).ok()?;
+                Some((version, tag))
+            })
+            .collect();
+        versions.sort();
+
+        let mut selected = None;
+        for (version, tag) in versions.iter().rev() {
+            if !req.matches(version) {
+                continue;
+            }
+            if self.skip_yanked(name, url, tag, yanked).await? {
+                continue;
+            }
+            selected = Some((version, tag));
+            break;
+        }
+
+        match selected {
+            Some((version, tag)) => {
+                say!(
+                    "    {} Selected {} for {}",
+                    "→".cyan(),
+                    version.to_string().cyan(),
+                    req
+                );
+                Ok(tag.clone())
+            }
+            None => Err(VersionConflict {
+                package: name.to_string(),
+                requirements: vec![DependencyRequest {
+                    dependent: None,
+                    name: name.to_string(),
+                    version: req.to_string(),
+                }],
+                available: versions.into_iter().map(|(_, tag)| tag).collect(),
+            }
+            .into()),
+        }
+    }
+
+    /// The newest kelvin tag of the repository at `url` that `spec` accepts, if there is one
+    ///
+    /// Kelvins count down, so the newest is the lowest. `*` accepts every kelvin. Yanked tags
+    /// are passed over as in `select_tag`.
+    async fn select_kelvin_tag(
+        &self,
+        name: &str,
+        url: &str,
+        spec: &VersionSpec,
+        yanked: &[Yanked],
+    ) -> Result<Option<String>> {
+        let tags = self.git_fetcher.list_tags(url).await?;
+        for (kelvin, tag) in kelvin_tags(&tags, spec) {
+            if self.skip_yanked(name, url, &tag, yanked).await? {
+                continue;
+            }
+            say!(
+                "    {} Selected k{} for {}",
+                "→".cyan(),
+                kelvin.to_string().cyan(),
+                spec.to_canonical_string()
+            );
+            return Ok(Some(tag));
+        }
+        Ok(None)
+    }
+
+    /// Whether `tag` has been yanked, and nockapp.lock does not have the package at its commit
+    async fn skip_yanked(
+        &self,
+        name: &str,
+        url: &str,
+        tag: &str,
+        yanked: &[Yanked],
+    ) -> Result<bool> {
+        if !yanked.iter().any(|yank| yank.affects_tag(name, tag)) {
+            return Ok(false);
+        }
+        let locked = match self.locked.get(name) {
+            Some(commit) => self.git_fetcher.resolve_tag(url, tag).await? == *commit,
+            None => false,
+        };
+        if !locked {
+            say!("    {} Skipping {}, which has been yanked", "→".cyan(), tag);
+        }
+        Ok(!locked)
+    }
+
+    /// Whether nockapp.lock has a resolved package at the same commit
+    fn is_locked(&self, pkg: &ResolvedPackage) -> bool {
+        self.locked.get(&pkg.name) == Some(&pkg.commit)
+    }
+
     /// Get exact commit hash for a GitSpec
     async fn get_exact_commit(&self, spec: &GitSpec) -> Result<String> {
         if let Some(ref commit) = spec.commit {
@@ -337,12 +978,12 @@ impl Resolver {
         }
     }
 
-    /// Load transitive dependencies from a fetched package
-    async fn load_transitive_deps(
+    /// Load the hoon.toml of a fetched package, if it has one
+    async fn load_package_manifest(
         &self,
         repo_path: &Path,
         git_spec: &GitSpec,
-    ) -> Result<HashMap<String, DependencySpec>> {
+    ) -> Result<Option<HoonPackage>> {
         // Check for hoon.toml in the fetched repo
         let manifest_path = if let Some(ref subdir) = git_spec.path {
             repo_path.join(subdir).join("hoon.toml")
@@ -350,16 +991,8 @@ impl Resolver {
             repo_path.join("hoon.toml")
         };
 
-        if !manifest_path.exists() {
-            // No transitive dependencies
-            return Ok(HashMap::new());
-        }
-
-        // Load and parse manifest
-        match HoonPackage::load(&manifest_path)? {
-            Some(pkg) => Ok(pkg.dependencies.unwrap_or_default().into_iter().collect()),
-            None => Ok(HashMap::new()),
-        }
+        // Load and parse manifest; without one there are no transitive dependencies
+        HoonPackage::load(&manifest_path)
     }
 
     /// Validate that all requested source files exist and return the list
@@ -394,36 +1027,229 @@ impl Resolver {
 
     /// Convert DependencySpec to VersionSpec for caching
     fn spec_to_version_spec(&self, spec: &DependencySpec) -> Result<VersionSpec> {
-        match spec {
-            DependencySpec::Simple(s) => VersionSpec::parse(s),
-            DependencySpec::Version { version } => VersionSpec::parse(version),
-            DependencySpec::Full {
-                version,
-                commit,
-                tag,
-                branch,
-                kelvin,
-                ..
-            } => {
-                // Priority: commit > tag > kelvin > branch > version
-                if let Some(c) = commit {
-                    return Ok(VersionSpec::Commit(c.clone()));
-                }
-                if let Some(t) = tag {
-                    return Ok(VersionSpec::Tag(t.clone()));
-                }
-                if let Some(k) = kelvin {
-                    return VersionSpec::parse(k);
-                }
-                if let Some(b) = branch {
-                    return Ok(VersionSpec::Branch(b.clone()));
-                }
-                if let Some(v) = version {
-                    return VersionSpec::parse(v);
+        VersionSpec::from_dependency_spec(spec)
+    }
+}
+
+/// How nockapp.lock fails to satisfy the dependencies of nockapp.toml, one line per dependency
+///
+/// Every dependency must be locked at a version its spec accepts. The lock's other packages are
+/// dependencies of dependencies, and are not checked.
+pub fn lock_mismatches(
+    dependencies: &BTreeMap<String, DependencySpec>,
+    lock: &NockAppLock,
+) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+    for (name, spec) in dependencies {
+        let wanted = VersionSpec::from_dependency_spec(spec)?;
+        match lock.package.iter().find(|p| p.name == *name) {
+            None => problems.push(format!("'{}' is not in nockapp.lock", name)),
+            Some(locked) => {
+                let locked_spec = VersionSpec::parse(&locked.version)?;
+                let accepted = wanted.is_any()
+                    || VersionSpec::unify(&[wanted.clone(), locked_spec.clone()])
+                        == Some(locked_spec);
+                if !accepted {
+                    problems.push(format!(
+                        "'{}' is locked at {}, but nockapp.toml asks for {}",
+                        name,
+                        locked.version,
+                        wanted.to_canonical_string()
+                    ));
                 }
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// One package's requirement on another
+struct Requirement {
+    dependent: Option<String>, // None for the manifest
+    spec: DependencySpec,
+    version: VersionSpec,
+}
+
+fn conflict(name: &str, requirements: &[Requirement], available: Vec<String>) -> VersionConflict {
+    VersionConflict {
+        package: name.to_string(),
+        requirements: requirements
+            .iter()
+            .map(|r| DependencyRequest {
+                dependent: r.dependent.clone(),
+                name: name.to_string(),
+                version: r.version.to_canonical_string(),
+            })
+            .collect(),
+        available,
+    }
+}
 
-                anyhow::bail!("DependencySpec has no version information")
+/// The yank that applies to a resolved package, if it has been yanked
+fn yank_of<'a>(pkg: &ResolvedPackage, yanked: &'a [Yanked]) -> Option<&'a Yanked> {
+    let version = pkg.version_spec.to_canonical_string();
+    yanked
+        .iter()
+        .find(|yank| yank.affects(&pkg.name, &version, &pkg.commit))
+}
+
+fn yanked_error(pkg: &ResolvedPackage, yank: &Yanked) -> anyhow::Error {
+    let mut message = format!(
+        "'{}' {} ({}) has been yanked",
+        pkg.name,
+        pkg.version_spec.to_canonical_string(),
+        pkg.commit.chars().take(12).collect::<String>()
+    );
+    if let Some(reason) = &yank.reason {
+        message.push_str(&format!(": {}", reason));
+    }
+    match &yank.upgrade {
+        Some(upgrade) => message.push_str(&format!(
+            "\nUse another version, e.g. `nockup package add {} --version {}`",
+            pkg.name, upgrade
+        )),
+        None => message.push_str("\nAsk for another version of it in nockapp.toml"),
+    }
+    anyhow::anyhow!(message)
+}
+
+/// Warn if a registry has deprecated a package, naming its replacement
+fn print_deprecation(name: &str, deprecated: &[Deprecated]) {
+    let Some(deprecation) = deprecated.iter().find(|d| d.package == name) else {
+        return;
+    };
+    match &deprecation.reason {
+        Some(reason) => say!(
+            "    {} {} is deprecated: {}",
+            "⚠".yellow(),
+            name.yellow(),
+            reason
+        ),
+        None => say!("    {} {} is deprecated", "⚠".yellow(), name.yellow()),
+    }
+    if let Some(replacement) = &deprecation.replacement {
+        say!(
+            "    {} {}",
+            "replacement:".dimmed(),
+            format!("nockup package add {}", replacement).cyan()
+        );
+    }
+}
+
+/// The spec to resolve a package for, given every requirement on it
+///
+/// Where the package comes from is taken from the requirements that give a git URL, the
+/// manifest's first; the version is the one unified from all of them.
+fn combined_spec(requirements: &[Requirement], unified: &VersionSpec) -> DependencySpec {
+    let source = requirements
+        .iter()
+        .filter(|r| matches!(r.spec, DependencySpec::Full { git: Some(_), .. }))
+        .min_by_key(|r| r.dependent.is_some());
+
+    match source.map(|r| &r.spec) {
+        Some(DependencySpec::Full {
+            git, path, files, ..
+        }) => {
+            let mut spec = unified.to_dependency_spec(git.clone());
+            if let DependencySpec::Full {
+                path: p, files: f, ..
+            } = &mut spec
+            {
+                *p = path.clone();
+                *f = files.clone();
             }
+            spec
         }
+        _ => DependencySpec::Simple(unified.to_canonical_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirement(dependent: Option<&str>, spec: DependencySpec) -> Requirement {
+        let version = match &spec {
+            DependencySpec::Simple(v) => VersionSpec::parse(v).unwrap(),
+            _ => VersionSpec::parse("*").unwrap(),
+        };
+        Requirement {
+            dependent: dependent.map(str::to_string),
+            spec,
+            version,
+        }
+    }
+
+    #[test]
+    fn test_combined_spec_and_conflict() {
+        let git = VersionSpec::Semver(semver::VersionReq::STAR)
+            .to_dependency_spec(Some("https://github.com/example/lib".to_string()));
+        let requirements = vec![
+            requirement(Some("other"), DependencySpec::Simple("^1.2.0".to_string())),
+            requirement(None, git),
+        ];
+        let unified = VersionSpec::parse("^1.2.0").unwrap();
+
+        // The git URL is kept and the version replaced by the unified one
+        match combined_spec(&requirements, &unified) {
+            DependencySpec::Full { git, version, .. } => {
+                assert_eq!(git.as_deref(), Some("https://github.com/example/lib"));
+                assert_eq!(version.as_deref(), Some("^1.2.0"));
+            }
+            other => panic!("expected a full spec, got {:?}", other),
+        }
+        assert!(matches!(
+            combined_spec(&requirements[..1], &unified),
+            DependencySpec::Simple(v) if v == "^1.2.0"
+        ));
+
+        let message = conflict("lib", &requirements, vec!["v1.0.0".to_string()]).to_string();
+        assert_eq!(
+            message,
+            "No version of 'lib' satisfies every requirement on it:\n  \
+             - ^1.2.0 (required by other)\n  \
+             - * (required by nockapp.toml)\n\
+             Available versions: v1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_yank_of() {
+        let feed: registry::RegistryToml = toml::from_str(
+            r#"
+            [[yanked]]
+            package = "sequent"
+            versions = ["<1.2.3"]
+            commits = ["4f2c9a1"]
+            reason = "Unchecked subtraction"
+            upgrade = "^1.2.3"
+            "#,
+        )
+        .unwrap();
+        let pkg = |version: &str, commit: &str| ResolvedPackage {
+            name: "sequent".to_string(),
+            version_spec: VersionSpec::parse(version).unwrap(),
+            commit: commit.to_string(),
+            source_url: "https://github.com/example/sequent".to_string(),
+            source_path: None,
+            install_path: None,
+            source_files: None,
+            dependencies: HashMap::new(),
+            license: None,
+            local: None,
+        };
+
+        assert!(feed.yanked[0].affects_tag("sequent", "v1.2.0"));
+        assert!(!feed.yanked[0].affects_tag("sequent", "v1.2.3"));
+        assert!(yank_of(&pkg("tag:v1.0.0", "0123abc"), &feed.yanked).is_some());
+        assert!(yank_of(&pkg("^1.0.0", "4f2c9a1e"), &feed.yanked).is_some());
+        assert!(yank_of(&pkg("tag:v1.2.3", "0123abc"), &feed.yanked).is_none());
+
+        let message = yanked_error(&pkg("^1.0.0", "4f2c9a1e"), &feed.yanked[0]).to_string();
+        assert_eq!(
+            message,
+            "'sequent' ^1.0.0 (4f2c9a1e) has been yanked: Unchecked subtraction\n\
+             Use another version, e.g. `nockup package add sequent --version ^1.2.3`"
+        );
     }
 }
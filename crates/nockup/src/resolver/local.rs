@@ -0,0 +1,198 @@
+//! Libraries of the workspace a project is developed in, installed from their directories
+//!
+//! With `prefer-local = true`, a dependency named like one of these is not fetched from git. The
+//! workspace is the `members` of the project's `[workspace]`, then the directories beside the
+//! project's, each holding a library with a hoon.toml.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::{ResolvedPackage, VersionSpec};
+use crate::cache::content_hash;
+use crate::commands::common;
+use crate::manifest::{HoonPackage, PackageMeta};
+
+/// A library of the workspace
+#[derive(Debug)]
+pub struct LocalPackage {
+    pub path: String, // Relative to the project's manifest, as nockapp.lock records it
+    pub dir: PathBuf,
+    pub manifest: HoonPackage,
+}
+
+impl LocalPackage {
+    pub fn name(&self) -> &str {
+        &self.manifest.package.name
+    }
+
+    /// Whether this library's version is one that `wanted` accepts
+    ///
+    /// Libraries without a version only stand in for dependencies that take any version.
+    pub fn satisfies(&self, wanted: &VersionSpec) -> bool {
+        wanted.is_any()
+            || self
+                .manifest
+                .package
+                .version
+                .as_deref()
+                .is_some_and(|version| wanted.matches(version))
+    }
+
+    /// The library as a resolved package, at the current contents of its directory
+    pub fn resolve(&self) -> Result<ResolvedPackage> {
+        let version_spec = self
+            .manifest
+            .package
+            .version
+            .as_deref()
+            .and_then(|version| VersionSpec::parse(version).ok())
+            .unwrap_or(VersionSpec::Semver(semver::VersionReq::STAR));
+        Ok(ResolvedPackage {
+            name: self.name().to_string(),
+            version_spec,
+            // Workspace libraries have no commit of their own; their contents stand in for one
+            commit: content_hash(&self.dir)?,
+            source_url: self.dir.to_string_lossy().into_owned(),
+            source_path: None,
+            install_path: None,
+            source_files: None,
+            dependencies: self
+                .manifest
+                .dependencies
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            license: self.manifest.package.license.clone(),
+            local: Some(self.path.clone()),
+        })
+    }
+}
+
+/// Whether a project installs workspace libraries from their directories
+///
+/// Set by `prefer-local` in the project's nockapp.toml, else in ~/.nockup/config.toml.
+pub fn prefer_local(package: &PackageMeta) -> bool {
+    package.prefer_local.unwrap_or_else(|| {
+        common::get_config()
+            .ok()
+            .and_then(|config| config.get("prefer-local").and_then(|v| v.as_bool()))
+            .unwrap_or(false)
+    })
+}
+
+/// The libraries of the workspace of the project whose manifest is in `base_dir`, by name
+///
+/// Empty unless the project prefers local libraries. A member comes before a sibling of the
+/// same name.
+pub fn local_packages(
+    manifest: &HoonPackage,
+    base_dir: &Path,
+) -> Result<HashMap<String, LocalPackage>> {
+    let mut packages = HashMap::new();
+    if !prefer_local(&manifest.package) {
+        return Ok(packages);
+    }
+
+    let mut candidates: Vec<(String, bool)> = manifest
+        .workspace
+        .iter()
+        .flat_map(|workspace| &workspace.members)
+        .map(|member| (member.trim_end_matches('/').to_string(), true))
+        .collect();
+    if let (Some(parent), Some(own_name)) = (base_dir.parent(), base_dir.file_name()) {
+        let mut siblings: Vec<String> = std::fs::read_dir(parent)
+            .with_context(|| format!("Failed to read {}", parent.display()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir() && entry.file_name() != own_name)
+            .map(|entry| format!("../{}", entry.file_name().to_string_lossy()))
+            .collect();
+        siblings.sort();
+        candidates.extend(siblings.into_iter().map(|sibling| (sibling, false)));
+    }
+
+    for (path, member) in candidates {
+        let dir = base_dir.join(&path);
+        let Some(library) = HoonPackage::load(&dir.join("hoon.toml"))
+            .with_context(|| format!("Failed to parse {}/hoon.toml", path))?
+        else {
+            if member {
+                anyhow::bail!("Workspace member '{}' has no hoon.toml", path);
+            }
+            continue;
+        };
+        if library.package.name == manifest.package.name {
+            continue;
+        }
+        packages
+            .entry(library.package.name.clone())
+            .or_insert(LocalPackage {
+                path,
+                dir,
+                manifest: library,
+            });
+    }
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::WorkspaceSpec;
+
+    fn library(dir: &Path, name: &str, version: Option<&str>) {
+        std::fs::create_dir_all(dir.join("src/lib")).unwrap();
+        let version = version
+            .map(|v| format!("version = \"{}\"\n", v))
+            .unwrap_or_default();
+        std::fs::write(
+            dir.join("hoon.toml"),
+            format!("[package]\nname = \"{}\"\n{}", name, version),
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/lib").join(format!("{}.hoon", name)), "~").unwrap();
+    }
+
+    #[test]
+    fn test_local_packages() {
+        let tmp = tempfile::tempdir().expect("Failed to create temp dir");
+        let app = tmp.path().join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        library(&tmp.path().join("sequent"), "sequent", Some("1.2.0"));
+        library(&tmp.path().join("loose"), "loose", None);
+        library(&app.join("libs/util"), "util", Some("0.1.0"));
+        std::fs::create_dir_all(tmp.path().join("docs")).unwrap();
+
+        let mut manifest = HoonPackage::default();
+        manifest.package.name = "app".to_string();
+        manifest.workspace = Some(WorkspaceSpec {
+            members: vec!["libs/util".to_string()],
+        });
+        manifest.package.prefer_local = Some(false);
+        assert!(local_packages(&manifest, &app).unwrap().is_empty());
+
+        manifest.package.prefer_local = Some(true);
+        let packages = local_packages(&manifest, &app).unwrap();
+        let mut names: Vec<&str> = packages.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, ["loose", "sequent", "util"]);
+        assert_eq!(packages["sequent"].path, "../sequent");
+        assert_eq!(packages["util"].path, "libs/util");
+
+        let sequent = &packages["sequent"];
+        assert!(sequent.satisfies(&VersionSpec::parse("^1.0.0").unwrap()));
+        assert!(!sequent.satisfies(&VersionSpec::parse("^2.0.0").unwrap()));
+        assert!(!packages["loose"].satisfies(&VersionSpec::parse("^1.0.0").unwrap()));
+        assert!(packages["loose"].satisfies(&VersionSpec::parse("latest").unwrap()));
+
+        let resolved = sequent.resolve().unwrap();
+        assert_eq!(resolved.local.as_deref(), Some("../sequent"));
+        assert!(resolved.commit.starts_with("sha256:"));
+
+        manifest.workspace = Some(WorkspaceSpec {
+            members: vec!["libs/missing".to_string()],
+        });
+        assert!(local_packages(&manifest, &app).is_err());
+    }
+}
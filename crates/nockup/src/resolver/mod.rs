@@ -1,8 +1,15 @@
 mod engine;
+pub mod local;
 pub mod registry;
 pub mod spec_parser;
 pub mod types;
 
-pub use engine::Resolver;
-pub use spec_parser::{parse_package_spec, VersionSpec};
-pub use types::{ResolvedGraph, ResolvedPackage};
+pub use engine::{lock_mismatches, Resolver};
+
+/// How dependencies are resolved, recorded in nockapp.lock
+///
+/// Requirements on each package are unified, and semver requirements take the highest
+/// matching tag that has not been yanked. Changes whenever a manifest could resolve differently.
+pub const ALGORITHM: &str = "unify-1";
+pub use spec_parser::{kelvin_of, kelvin_tags, parse_package_spec, VersionSpec};
+pub use types::{DependencyRequest, ResolvedGraph, ResolvedPackage, VersionConflict};
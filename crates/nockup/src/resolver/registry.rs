@@ -1,13 +1,22 @@
 /// Package registry system using typhoon registry format
-/// Fetches registry from https://github.com/sigilante/typhoon
-use std::collections::HashMap;
+/// Fetches registry from https://github.com/sigilante/typhoon, and any registries configured in
+/// ~/.nockup/config.toml or the project manifest
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
+use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
 use once_cell::sync::Lazy;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::cache::PackageCache;
+use crate::commands::common::get_cache_dir;
 use crate::git_fetcher::GitSpec;
+use crate::manifest::{HoonPackage, RegistrySpec};
+use crate::resolver::VersionSpec;
+use crate::{credentials, network};
 
 #[derive(Debug, Clone)]
 pub struct RegistryEntry {
@@ -18,17 +27,23 @@ pub struct RegistryEntry {
 }
 
 /// Typhoon registry TOML format structures
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegistryToml {
     #[serde(default)]
     pub workspace: HashMap<String, Workspace>,
     #[serde(default)]
     pub package: Vec<Package>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub alias: Vec<Alias>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advisory: Vec<Advisory>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub yanked: Vec<Yanked>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deprecated: Vec<Deprecated>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Workspace {
     pub git_url: String,
     #[serde(rename = "ref")]
@@ -37,22 +52,105 @@ pub struct Workspace {
     pub root_path: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Package {
     pub name: String,
     pub workspace: String,
     pub path: String,
     pub file: String,
+    // Every .hoon file in the package, relative to path (published packages only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
     #[serde(default)]
     pub dependencies: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Alias {
     pub name: String,
     pub target: String,
 }
 
+/// A security advisory against some versions of a package
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Advisory {
+    pub id: String, // e.g. "NOCK-2025-0001"
+    pub package: String,
+    pub title: String,
+    pub url: Option<String>,
+    #[serde(default)]
+    pub versions: Vec<String>, // Affected versions or requirements, e.g. "k409" or "<1.2.3"
+    #[serde(default)]
+    pub commits: Vec<String>, // Affected commits, in full or abbreviated
+    pub patched: Option<String>, // Version to upgrade to
+}
+
+/// Versions of a package withdrawn from use
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Yanked {
+    pub package: String,
+    #[serde(default)]
+    pub versions: Vec<String>,
+    #[serde(default)]
+    pub commits: Vec<String>,
+    pub reason: Option<String>,
+    pub upgrade: Option<String>, // Version to use instead
+}
+
+/// A package that is no longer maintained, and what to use in its place
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Deprecated {
+    pub package: String,
+    pub reason: Option<String>,
+    pub replacement: Option<String>, // Package to use instead
+}
+
+impl Advisory {
+    /// Whether a package locked at `version` and `commit` is affected
+    pub fn affects(&self, name: &str, version: &str, commit: &str) -> bool {
+        self.package == name && affects(&self.versions, &self.commits, version, commit)
+    }
+}
+
+impl Yanked {
+    /// Whether a package locked at `version` and `commit` has been yanked
+    pub fn affects(&self, name: &str, version: &str, commit: &str) -> bool {
+        self.package == name && affects(&self.versions, &self.commits, version, commit)
+    }
+
+    /// Whether a tag of a package has been yanked, by version
+    pub fn affects_tag(&self, name: &str, tag: &str) -> bool {
+        self.package == name && affects(&self.versions, &[], &format!("tag:{}", tag), "")
+    }
+}
+
+/// Whether a locked version and commit fall among the listed versions or commits
+///
+/// A listed version matches the locked one if they are the same spec, or if it is a semver
+/// requirement that a locked tag meets.
+fn affects(versions: &[String], commits: &[String], version: &str, commit: &str) -> bool {
+    if commits
+        .iter()
+        .any(|c| VersionSpec::Commit(c.clone()).matches(commit))
+    {
+        return true;
+    }
+
+    let Ok(locked) = VersionSpec::parse(version) else {
+        return false;
+    };
+    versions
+        .iter()
+        .any(|listed| match VersionSpec::parse(listed) {
+            Ok(listed) if listed == locked => true,
+            Ok(listed @ VersionSpec::Semver(_)) => match &locked {
+                VersionSpec::Tag(tag) => !listed.is_any() && listed.matches(tag),
+                _ => false,
+            },
+            _ => false,
+        })
+}
+
 /// Well-known packages registry
 static REGISTRY: Lazy<HashMap<&'static str, RegistryEntry>> = Lazy::new(|| {
     let mut m = HashMap::new();
@@ -196,55 +294,321 @@ static REGISTRY: Lazy<HashMap<&'static str, RegistryEntry>> = Lazy::new(|| {
     m
 });
 
-/// Cached online registry
-static ONLINE_REGISTRY: Lazy<RwLock<Option<RegistryToml>>> = Lazy::new(|| RwLock::new(None));
+/// Name of the default registry, https://github.com/sigilante/typhoon
+pub const DEFAULT_REGISTRY: &str = "typhoon";
 
-const REGISTRY_URL: &str =
+const DEFAULT_REGISTRY_URL: &str =
     "https://raw.githubusercontent.com/sigilante/typhoon/master/registry.toml";
 
-/// Fetch and parse the online registry (blocking - use spawn_blocking in async context)
-fn fetch_registry_sync() -> Result<RegistryToml> {
-    let response =
-        reqwest::blocking::get(REGISTRY_URL).context("Failed to fetch registry from GitHub")?;
+/// Copies of the default registry, tried in order when GitHub can't be reached
+const DEFAULT_REGISTRY_MIRRORS: &[&str] =
+    &["https://cdn.jsdelivr.net/gh/sigilante/typhoon@master/registry.toml"];
 
-    let content = response
-        .text()
-        .context("Failed to read registry response")?;
+/// How long a fetched registry is used before it is fetched again, unless `registry_ttl`
+/// (in seconds) is set in ~/.nockup/config.toml
+const DEFAULT_REGISTRY_TTL: Duration = Duration::from_secs(60 * 60);
 
-    let registry: RegistryToml =
-        toml::from_str(&content).context("Failed to parse registry TOML")?;
+/// Priority of the default registry; registries with a lower priority are searched first
+const DEFAULT_REGISTRY_PRIORITY: i64 = 100;
 
-    Ok(registry)
+/// Priority of configured registries that give none, so they come before the default
+const CUSTOM_REGISTRY_PRIORITY: i64 = 0;
+
+/// Environment variable naming the registry endpoint packages are published to
+pub const PUBLISH_URL_ENV: &str = "NOCKUP_REGISTRY_URL";
+
+/// Environment variable holding the bearer token sent when publishing
+pub const PUBLISH_TOKEN_ENV: &str = "NOCKUP_REGISTRY_TOKEN";
+
+/// A registry that packages are looked up in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrySource {
+    pub name: String,
+    pub url: String,          // http(s) URL, or path of a local registry file
+    pub mirrors: Vec<String>, // Tried in order when url fails
+    pub priority: i64,
+    pub ttl: Duration, // How long a fetched copy is used before fetching again
+}
+
+impl RegistrySource {
+    fn new(name: &str, spec: &RegistrySpec, ttl: Duration) -> Self {
+        let (url, priority, mirrors) = match spec {
+            RegistrySpec::Url(url) => (url.clone(), None, Vec::new()),
+            RegistrySpec::Full {
+                url,
+                priority,
+                mirrors,
+            } => (url.clone(), *priority, mirrors.clone().unwrap_or_default()),
+        };
+        let default_priority = if name == DEFAULT_REGISTRY {
+            DEFAULT_REGISTRY_PRIORITY
+        } else {
+            CUSTOM_REGISTRY_PRIORITY
+        };
+        Self {
+            name: name.to_string(),
+            url,
+            mirrors,
+            priority: priority.unwrap_or(default_priority),
+            ttl,
+        }
+    }
+
+    fn is_remote(&self) -> bool {
+        is_remote_url(&self.url)
+    }
+}
+
+fn is_remote_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// The registries to search, in the order to search them
+///
+/// The default registry is joined by the `[registries]` of ~/.nockup/config.toml and then of
+/// the project manifest, each replacing a registry of the same name. Registries are searched
+/// by ascending priority, then by name.
+pub fn registry_sources(manifest: Option<&HoonPackage>) -> Result<Vec<RegistrySource>> {
+    let config = registry_config()?;
+    let mut specs = BTreeMap::from([(
+        DEFAULT_REGISTRY.to_string(),
+        RegistrySpec::Full {
+            url: DEFAULT_REGISTRY_URL.to_string(),
+            priority: None,
+            mirrors: Some(
+                DEFAULT_REGISTRY_MIRRORS
+                    .iter()
+                    .map(|m| m.to_string())
+                    .collect(),
+            ),
+        },
+    )]);
+    specs.extend(config.registries);
+    if let Some(registries) = manifest.and_then(|m| m.registries.as_ref()) {
+        specs.extend(registries.clone());
+    }
+    let ttl = config
+        .registry_ttl
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_REGISTRY_TTL);
+    Ok(order_sources(&specs, ttl))
+}
+
+fn order_sources(specs: &BTreeMap<String, RegistrySpec>, ttl: Duration) -> Vec<RegistrySource> {
+    let mut sources: Vec<RegistrySource> = specs
+        .iter()
+        .map(|(name, spec)| RegistrySource::new(name, spec, ttl))
+        .collect();
+    sources.sort_by(|a, b| {
+        a.priority
+            .cmp(&b.priority)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    sources
+}
+
+/// Registry settings of ~/.nockup/config.toml
+#[derive(Debug, Default, Deserialize)]
+struct RegistryConfig {
+    #[serde(default)]
+    registries: BTreeMap<String, RegistrySpec>,
+    registry_ttl: Option<u64>,
+}
+
+fn registry_config() -> Result<RegistryConfig> {
+    let config_path = get_cache_dir()?.join("config.toml");
+    if !config_path.exists() {
+        return Ok(RegistryConfig::default());
+    }
+
+    let contents = std::fs::read_to_string(&config_path).context("Failed to read config file")?;
+    toml::from_str(&contents).context("Failed to parse registry settings of config file")
+}
+
+/// Registries fetched so far, by URL
+static ONLINE_REGISTRIES: Lazy<RwLock<HashMap<String, RegistryToml>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Read a registry from its URL or, failing that, each of its mirrors (blocking - use
+/// spawn_blocking in async context); returns the registry and its TOML
+fn fetch_registry_sync(source: &RegistrySource) -> Result<(RegistryToml, String)> {
+    let mut errors = Vec::new();
+    for url in std::iter::once(&source.url).chain(&source.mirrors) {
+        match read_registry_url(url) {
+            Ok(fetched) => return Ok(fetched),
+            Err(e) => errors.push(format!("{}: {:#}", url, e)),
+        }
+    }
+    anyhow::bail!(
+        "Failed to fetch registry '{}':\n  - {}",
+        source.name,
+        errors.join("\n  - ")
+    )
+}
+
+fn read_registry_url(url: &str) -> Result<(RegistryToml, String)> {
+    let content = if is_remote_url(url) {
+        let mut request = network::blocking_client()?.get(url);
+        if let Some(token) = credentials::token_for(url)? {
+            request = request.bearer_auth(token);
+        }
+        request
+            .send()
+            .and_then(|response| response.error_for_status())
+            .context("Failed to fetch registry")?
+            .text()
+            .context("Failed to read registry response")?
+    } else {
+        let path = url.strip_prefix("file://").unwrap_or(url);
+        std::fs::read_to_string(path).context("Failed to read registry file")?
+    };
+    let registry = toml::from_str(&content).context("Failed to parse registry TOML")?;
+    Ok((registry, content))
+}
+
+/// The copy of the last fetch of a registry
+fn registry_cache_file(source: &RegistrySource) -> Result<PathBuf> {
+    let file_name = format!("{}.toml", source.name.replace(['/', '\\'], "-"));
+    Ok(PackageCache::new()?.registry_dir().join(file_name))
+}
+
+/// Read a cached registry, and how long ago it was fetched
+fn read_cached_registry(path: &Path) -> Result<(RegistryToml, Duration)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("No cached registry at {}", path.display()))?;
+    let age = std::fs::metadata(path)?
+        .modified()?
+        .elapsed()
+        .unwrap_or_default();
+    let registry = toml::from_str(&content).context("Failed to parse cached registry TOML")?;
+    Ok((registry, age))
+}
+
+/// Drop every fetched registry, so the next lookup fetches them again
+fn clear_cached_registries() {
+    if let Ok(mut cache) = ONLINE_REGISTRIES.write() {
+        cache.clear();
+    }
+    if let Ok(cache) = PackageCache::new() {
+        if let Ok(entries) = std::fs::read_dir(cache.registry_dir()) {
+            for entry in entries.flatten() {
+                if entry.path().extension().is_some_and(|ext| ext == "toml") {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+    }
 }
 
-/// Get the online registry (with caching) - async wrapper around blocking fetch
-async fn get_online_registry() -> Result<RegistryToml> {
+/// Load a remote registry: from ~/.nockup/cache/registry if fetched within its TTL, else from
+/// its URL or mirrors, else from the stale cached copy with a warning (blocking)
+fn load_remote_registry(source: &RegistrySource, offline: bool) -> Result<RegistryToml> {
+    let cache_file = registry_cache_file(source)?;
+    let cached = read_cached_registry(&cache_file);
+
+    if offline {
+        return cached
+            .map(|(registry, _)| registry)
+            .with_context(|| format!("Registry '{}' has not been fetched yet", source.name));
+    }
+    if let Ok((ref registry, age)) = cached {
+        if age < source.ttl {
+            return Ok(registry.clone());
+        }
+    }
+
+    match fetch_registry_sync(source) {
+        Ok((registry, content)) => {
+            // Keep a copy for later runs and offline mode; failing to is not fatal
+            let _ = std::fs::write(&cache_file, content);
+            Ok(registry)
+        }
+        Err(e) => match cached {
+            Ok((registry, age)) => {
+                say!(
+                    "{} Registry '{}' is unreachable, using the copy fetched {} minutes ago",
+                    "⚠".yellow(),
+                    source.name,
+                    age.as_secs() / 60
+                );
+                Ok(registry)
+            }
+            Err(_) => Err(e),
+        },
+    }
+}
+
+/// Get a registry (with caching) - async wrapper around blocking fetch
+///
+/// Remote registries are kept in ~/.nockup/cache/registry; see `load_remote_registry`.
+async fn get_online_registry(source: &RegistrySource, offline: bool) -> Result<RegistryToml> {
     // Try to read from cache first
     {
-        let cache = ONLINE_REGISTRY
+        let cache = ONLINE_REGISTRIES
             .read()
             .map_err(|err| anyhow!("Failed to read registry cache: {err}"))?;
-        if let Some(ref registry) = *cache {
+        if let Some(registry) = cache.get(&source.url) {
             return Ok(registry.clone());
         }
     }
 
     // Fetch and cache (spawn blocking task to avoid blocking async runtime)
-    let registry = tokio::task::spawn_blocking(fetch_registry_sync)
+    let registry = {
+        let source = source.clone();
+        tokio::task::spawn_blocking(move || {
+            if source.is_remote() {
+                load_remote_registry(&source, offline)
+            } else {
+                fetch_registry_sync(&source).map(|(registry, _)| registry)
+            }
+        })
         .await
-        .context("Failed to spawn blocking task")?
-        .context("Failed to fetch registry")?;
+        .context("Failed to spawn blocking task")??
+    };
 
     {
-        let mut cache = ONLINE_REGISTRY
+        let mut cache = ONLINE_REGISTRIES
             .write()
             .map_err(|err| anyhow!("Failed to write registry cache: {err}"))?;
-        *cache = Some(registry.clone());
+        cache.insert(source.url.clone(), registry.clone());
     }
 
     Ok(registry)
 }
 
+/// Every registry that can be reached, in search order; the others are skipped
+async fn get_registries(
+    sources: &[RegistrySource],
+    offline: bool,
+) -> Vec<(&RegistrySource, RegistryToml)> {
+    let mut registries = Vec::new();
+    for source in sources {
+        if let Ok(registry) = get_online_registry(source, offline).await {
+            registries.push((source, registry));
+        }
+    }
+    registries
+}
+
+/// Like get_registries, but failing if there are registries and none can be reached
+async fn get_readable_registries(
+    sources: &[RegistrySource],
+    offline: bool,
+) -> Result<Vec<(&RegistrySource, RegistryToml)>> {
+    let registries = get_registries(sources, offline).await;
+    if registries.is_empty() && !sources.is_empty() {
+        anyhow::bail!(
+            "None of the registries could be read: {}",
+            sources
+                .iter()
+                .map(|source| source.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    Ok(registries)
+}
+
 /// Resolve an alias to its target package name
 fn resolve_alias(name: &str, registry: &RegistryToml) -> String {
     for alias in &registry.alias {
@@ -255,28 +619,39 @@ fn resolve_alias(name: &str, registry: &RegistryToml) -> String {
     name.to_string()
 }
 
-/// Look up a package in the registry (tries online registry first, falls back to hardcoded)
-pub async fn lookup(name: &str) -> Option<RegistryEntry> {
-    // Try online registry first
-    if let Ok(registry) = get_online_registry().await {
-        // Resolve aliases
-        let resolved_name = resolve_alias(name, &registry);
-
-        // Find the package
-        if let Some(package) = registry.package.iter().find(|p| p.name == resolved_name) {
-            // Look up workspace info
-            if let Some(workspace) = registry.workspace.get(&package.workspace) {
-                // Concatenate root_path + path to get full repository path for fetching
-                // e.g., root_path="pkg/arvo", path="sys" -> fetch from "pkg/arvo/sys"
-                // But install_path is just "sys" (the package path)
-                let entry = RegistryEntry {
-                    git_url: workspace.git_url.clone(),
-                    path: Some(format!("{}/{}", workspace.root_path, package.path)),
-                    install_path: Some(package.path.clone()),
-                    file: Some(package.file.clone()),
-                };
-                return Some(entry);
-            }
+/// Find a package in a registry by name or alias
+fn find_package<'a>(name: &str, registry: &'a RegistryToml) -> Option<&'a Package> {
+    let resolved_name = resolve_alias(name, registry);
+    registry.package.iter().find(|p| p.name == resolved_name)
+}
+
+/// Look up a package in the registries, in order, falling back to the hardcoded registry
+pub async fn lookup(
+    name: &str,
+    sources: &[RegistrySource],
+    offline: bool,
+) -> Option<RegistryEntry> {
+    for (_, registry) in get_registries(sources, offline).await {
+        let Some(package) = find_package(name, &registry) else {
+            continue;
+        };
+        // Look up workspace info
+        if let Some(workspace) = registry.workspace.get(&package.workspace) {
+            // Concatenate root_path + path to get full repository path for fetching
+            // e.g., root_path="pkg/arvo", path="sys" -> fetch from "pkg/arvo/sys"
+            // But install_path is just "sys" (the package path)
+            // Published workspaces at the root of their repository have an empty root_path
+            let path = if workspace.root_path.is_empty() {
+                package.path.clone()
+            } else {
+                format!("{}/{}", workspace.root_path, package.path)
+            };
+            return Some(RegistryEntry {
+                git_url: workspace.git_url.clone(),
+                path: Some(path),
+                install_path: Some(package.path.clone()),
+                file: Some(package.file.clone()),
+            });
         }
     }
 
@@ -284,15 +659,14 @@ pub async fn lookup(name: &str) -> Option<RegistryEntry> {
     REGISTRY.get(name).cloned()
 }
 
-/// Get the dependencies of a package from the registry
-pub async fn get_dependencies(name: &str) -> Vec<String> {
-    // Try online registry first
-    if let Ok(registry) = get_online_registry().await {
-        // Resolve aliases
-        let resolved_name = resolve_alias(name, &registry);
-
-        // Find the package
-        if let Some(package) = registry.package.iter().find(|p| p.name == resolved_name) {
+/// Get the dependencies of a package from the first registry that has it
+pub async fn get_dependencies(
+    name: &str,
+    sources: &[RegistrySource],
+    offline: bool,
+) -> Vec<String> {
+    for (_, registry) in get_registries(sources, offline).await {
+        if let Some(package) = find_package(name, &registry) {
             return package.dependencies.clone();
         }
     }
@@ -301,6 +675,159 @@ pub async fn get_dependencies(name: &str) -> Vec<String> {
     Vec::new()
 }
 
+/// A registry package matching a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub name: String,
+    pub registry: String, // Registry the package was found in
+    pub aliases: Vec<String>,
+    pub description: Option<String>,
+    pub git_url: String,
+    pub dependencies: Vec<String>,
+}
+
+/// Search the registries' package names, aliases and descriptions; an empty query matches all
+///
+/// Packages only in the built-in registry are included too. Results whose name matches come
+/// first, then those matching by alias, then by description. A package in several registries
+/// is reported from the first one searched.
+pub async fn search(
+    query: &str,
+    sources: &[RegistrySource],
+    offline: bool,
+) -> Result<Vec<SearchResult>> {
+    let registries = get_readable_registries(sources, offline).await?;
+    let registries: Vec<(&str, &RegistryToml)> = registries
+        .iter()
+        .map(|(source, registry)| (source.name.as_str(), registry))
+        .collect();
+    Ok(search_registries(&registries, query))
+}
+
+fn search_registries(registries: &[(&str, &RegistryToml)], query: &str) -> Vec<SearchResult> {
+    let query = query.to_lowercase();
+    let matches = |text: &str| text.to_lowercase().contains(&query);
+
+    let mut seen = std::collections::HashSet::new();
+    let mut results: Vec<(u8, SearchResult)> = Vec::new();
+    for (registry_name, registry) in registries {
+        for package in &registry.package {
+            if !seen.insert(package.name.clone()) {
+                continue;
+            }
+            let workspace = registry.workspace.get(&package.workspace);
+            let aliases: Vec<String> = registry
+                .alias
+                .iter()
+                .filter(|alias| alias.target == package.name)
+                .map(|alias| alias.name.clone())
+                .collect();
+            let description = workspace.and_then(|w| w.description.clone());
+
+            let rank = if package.name.to_lowercase() == query {
+                0
+            } else if matches(&package.name) {
+                1
+            } else if aliases.iter().any(|alias| matches(alias)) {
+                2
+            } else if description.as_deref().is_some_and(matches) {
+                3
+            } else {
+                continue;
+            };
+
+            results.push((
+                rank,
+                SearchResult {
+                    name: package.name.clone(),
+                    registry: registry_name.to_string(),
+                    aliases,
+                    description,
+                    git_url: workspace.map(|w| w.git_url.clone()).unwrap_or_default(),
+                    dependencies: package.dependencies.clone(),
+                },
+            ));
+        }
+    }
+
+    for (name, entry) in REGISTRY.iter() {
+        if !matches(name) || seen.contains(*name) {
+            continue;
+        }
+        let rank = if name.to_lowercase() == query { 0 } else { 1 };
+        results.push((
+            rank,
+            SearchResult {
+                name: name.to_string(),
+                registry: "built-in".to_string(),
+                aliases: Vec::new(),
+                description: None,
+                git_url: entry.git_url.clone(),
+                dependencies: Vec::new(),
+            },
+        ));
+    }
+
+    results.sort_by(|(rank_a, a), (rank_b, b)| rank_a.cmp(rank_b).then(a.name.cmp(&b.name)));
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// The advisories and yanked versions listed by every registry that can be reached
+pub async fn audit_feed(
+    sources: &[RegistrySource],
+    offline: bool,
+) -> Result<(Vec<Advisory>, Vec<Yanked>)> {
+    let mut advisories = Vec::new();
+    let mut yanked = Vec::new();
+    for (_, registry) in get_readable_registries(sources, offline).await? {
+        advisories.extend(registry.advisory);
+        yanked.extend(registry.yanked);
+    }
+    Ok((advisories, yanked))
+}
+
+/// The yanked versions and deprecated packages listed by the registries that can be reached
+pub async fn notices(sources: &[RegistrySource], offline: bool) -> (Vec<Yanked>, Vec<Deprecated>) {
+    let mut yanked = Vec::new();
+    let mut deprecated = Vec::new();
+    for (_, registry) in get_registries(sources, offline).await {
+        yanked.extend(registry.yanked);
+        deprecated.extend(registry.deprecated);
+    }
+    (yanked, deprecated)
+}
+
+/// Push a registry entry to the registry at `endpoint`
+pub async fn publish(endpoint: &str, token: Option<&str>, entry: &RegistryToml) -> Result<()> {
+    let body = toml::to_string_pretty(entry).context("Failed to serialize registry entry")?;
+    let mut request = network::client()?
+        .post(endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "application/toml")
+        .body(body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach registry at {}", endpoint))?;
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        anyhow::bail!(
+            "Registry rejected the package ({}): {}",
+            status,
+            message.trim()
+        );
+    }
+
+    // The next lookup should see the new entry
+    clear_cached_registries();
+
+    Ok(())
+}
+
 /// Convert a registry entry to a GitSpec with version info
 pub fn to_git_spec(entry: &RegistryEntry, tag: Option<String>, branch: Option<String>) -> GitSpec {
     GitSpec {
@@ -313,3 +840,140 @@ pub fn to_git_spec(entry: &RegistryEntry, tag: Option<String>, branch: Option<St
         file: entry.file.clone(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_registries() {
+        let registry: RegistryToml = toml::from_str(
+            r#"
+            [workspace.numerics]
+            git_url = "https://github.com/urbit/numerics"
+            ref = "main"
+            description = "Numerical computing for Hoon"
+            root_path = ""
+
+            [[package]]
+            name = "lagoon"
+            workspace = "numerics"
+            path = "lagoon/desk/lib"
+            file = "lagoon.hoon"
+
+            [[package]]
+            name = "math-extra"
+            workspace = "numerics"
+            path = "libmath/desk/lib"
+            file = "math.hoon"
+            dependencies = ["lagoon"]
+
+            [[alias]]
+            name = "linear-algebra"
+            target = "lagoon"
+            "#,
+        )
+        .unwrap();
+
+        let internal: RegistryToml = toml::from_str(
+            r#"
+            [workspace.tools]
+            git_url = "https://git.example.com/tools"
+            ref = "main"
+            root_path = ""
+
+            [[package]]
+            name = "lagoon"
+            workspace = "tools"
+            path = "lib"
+            file = "lagoon.hoon"
+            "#,
+        )
+        .unwrap();
+        let registries = [("typhoon", &registry)];
+
+        let names = |query: &str| -> Vec<String> {
+            search_registries(&registries, query)
+                .into_iter()
+                .map(|result| result.name)
+                .collect()
+        };
+
+        // Name matches come first, exact ones before partial ones; built-in packages are included
+        assert_eq!(names("math"), vec!["math", "math-extra"]);
+        assert_eq!(names("LINEAR"), vec!["lagoon"]);
+        assert_eq!(names("numerical"), vec!["lagoon", "math-extra"]);
+        assert!(names("nonexistent").is_empty());
+
+        let lagoon = &search_registries(&registries, "lagoon")[0];
+        assert_eq!(lagoon.aliases, vec!["linear-algebra"]);
+        assert_eq!(lagoon.git_url, "https://github.com/urbit/numerics");
+        assert_eq!(
+            lagoon.description.as_deref(),
+            Some("Numerical computing for Hoon")
+        );
+
+        // A package in several registries comes from the first one searched
+        let results =
+            search_registries(&[("internal", &internal), ("typhoon", &registry)], "lagoon");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].registry, "internal");
+        assert_eq!(results[0].git_url, "https://git.example.com/tools");
+    }
+
+    #[test]
+    fn test_order_sources() {
+        let specs: BTreeMap<String, RegistrySpec> = toml::from_str(
+            r#"
+            typhoon = "https://example.com/typhoon.toml"
+            internal = "https://registry.example.com/registry.toml"
+            mirror = { url = "/srv/registry.toml", priority = 200 }
+            "#,
+        )
+        .unwrap();
+
+        let sources = order_sources(&specs, DEFAULT_REGISTRY_TTL);
+        let names: Vec<&str> = sources.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["internal", "typhoon", "mirror"]);
+        assert_eq!(sources[1].url, "https://example.com/typhoon.toml");
+        assert_eq!(sources[1].priority, DEFAULT_REGISTRY_PRIORITY);
+        assert!(!sources[2].is_remote());
+    }
+
+    #[test]
+    fn test_fetch_falls_back_to_mirrors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mirror = tmp.path().join("mirror.toml");
+        std::fs::write(
+            &mirror,
+            r#"
+            [[package]]
+            name = "lagoon"
+            workspace = "numerics"
+            path = "lib"
+            file = "lagoon.hoon"
+            "#,
+        )
+        .unwrap();
+
+        let source = RegistrySource {
+            name: "internal".to_string(),
+            url: tmp.path().join("missing.toml").display().to_string(),
+            mirrors: vec![mirror.display().to_string()],
+            priority: 0,
+            ttl: DEFAULT_REGISTRY_TTL,
+        };
+        let (registry, _) = fetch_registry_sync(&source).unwrap();
+        assert_eq!(registry.package[0].name, "lagoon");
+
+        let source = RegistrySource {
+            mirrors: Vec::new(),
+            ..source
+        };
+        let err = format!("{:#}", fetch_registry_sync(&source).unwrap_err());
+        assert!(err.contains("missing.toml"), "{}", err);
+
+        let (_, age) = read_cached_registry(&mirror).unwrap();
+        assert!(age < DEFAULT_REGISTRY_TTL);
+    }
+}
@@ -9,6 +9,9 @@ pub enum VersionSpec {
     /// Kelvin version (e.g., @k414)
     Kelvin(u32),
 
+    /// Kelvin version or any newer one, which has a lower number (e.g., ^k414 accepts k413)
+    KelvinRange(u32),
+
     /// Exact commit hash (e.g., @commit:abc123def)
     Commit(String),
 
@@ -27,6 +30,7 @@ impl VersionSpec {
     ///
     /// Supported formats:
     /// - `@k414` or `k414` → Kelvin(414)
+    /// - `^k414` → KelvinRange(414)
     /// - `@commit:abc123` or `commit:abc123` → Commit("abc123")
     /// - `@tag:v1.2.3` or `tag:v1.2.3` → Tag("v1.2.3")
     /// - `@branch:main` or `branch:main` → Branch("main")
@@ -43,10 +47,16 @@ impl VersionSpec {
 
         // Try kelvin format (with optional ^ prefix for minimum version)
         // ^k409 or k409
-        let kelvin_input = input.strip_prefix('^').unwrap_or(input);
+        let (kelvin_input, range) = match input.strip_prefix('^') {
+            Some(rest) => (rest, true),
+            None => (input, false),
+        };
         if let Some(kelvin_str) = kelvin_input.strip_prefix('k') {
             if let Ok(kelvin) = kelvin_str.parse::<u32>() {
-                return Ok(VersionSpec::Kelvin(kelvin));
+                return Ok(match range {
+                    true => VersionSpec::KelvinRange(kelvin),
+                    false => VersionSpec::Kelvin(kelvin),
+                });
             }
         }
 
@@ -81,12 +91,11 @@ impl VersionSpec {
         match self {
             VersionSpec::Kelvin(k) => {
                 // Check if version is k<number> matching our kelvin
-                version
-                    .trim_start_matches('@')
-                    .strip_prefix('k')
-                    .and_then(|s| s.parse::<u32>().ok())
-                    .map(|v| v == *k)
-                    .unwrap_or(false)
+                kelvin_of(version) == Some(*k)
+            }
+            VersionSpec::KelvinRange(k) => {
+                // Kelvins count down, so newer versions have lower numbers
+                kelvin_of(version).is_some_and(|v| v <= *k)
             }
             VersionSpec::Commit(c) => {
                 // Match exact commit or prefix
@@ -114,7 +123,7 @@ impl VersionSpec {
     /// Convert to a DependencySpec for use in manifests
     pub fn to_dependency_spec(&self, git_url: Option<String>) -> DependencySpec {
         match self {
-            VersionSpec::Kelvin(k) => DependencySpec::Full {
+            VersionSpec::Kelvin(_) | VersionSpec::KelvinRange(_) => DependencySpec::Full {
                 version: None,
                 git: git_url,
                 commit: None,
@@ -122,7 +131,7 @@ impl VersionSpec {
                 branch: None,
                 path: None,
                 files: None,
-                kelvin: Some(format!("k{}", k)),
+                kelvin: Some(self.to_canonical_string()),
             },
             VersionSpec::Commit(c) => DependencySpec::Full {
                 version: None,
@@ -167,10 +176,47 @@ impl VersionSpec {
         }
     }
 
+    /// The version a manifest dependency asks for
+    ///
+    /// A full spec's commit takes priority, then its tag, kelvin, branch and version.
+    pub fn from_dependency_spec(spec: &DependencySpec) -> Result<Self> {
+        match spec {
+            DependencySpec::Simple(s) => VersionSpec::parse(s),
+            DependencySpec::Version { version } => VersionSpec::parse(version),
+            DependencySpec::Full {
+                version,
+                commit,
+                tag,
+                branch,
+                kelvin,
+                ..
+            } => {
+                if let Some(c) = commit {
+                    return Ok(VersionSpec::Commit(c.clone()));
+                }
+                if let Some(t) = tag {
+                    return Ok(VersionSpec::Tag(t.clone()));
+                }
+                if let Some(k) = kelvin {
+                    return VersionSpec::parse(k);
+                }
+                if let Some(b) = branch {
+                    return Ok(VersionSpec::Branch(b.clone()));
+                }
+                if let Some(v) = version {
+                    return VersionSpec::parse(v);
+                }
+
+                anyhow::bail!("DependencySpec has no version information")
+            }
+        }
+    }
+
     /// Get a canonical string representation
     pub fn to_canonical_string(&self) -> String {
         match self {
             VersionSpec::Kelvin(k) => format!("k{}", k),
+            VersionSpec::KelvinRange(k) => format!("^k{}", k),
             VersionSpec::Commit(c) => format!("commit:{}", c),
             VersionSpec::Tag(t) => format!("tag:{}", t),
             VersionSpec::Branch(b) => format!("branch:{}", b),
@@ -182,6 +228,101 @@ impl VersionSpec {
     pub fn is_exact(&self) -> bool {
         matches!(self, VersionSpec::Commit(_) | VersionSpec::Tag(_))
     }
+
+    /// Check if this is `*` (or `latest`), which every version satisfies
+    pub fn is_any(&self) -> bool {
+        matches!(self, VersionSpec::Semver(req) if req.comparators.is_empty())
+    }
+
+    /// Combine the requirements of several dependents into one spec they all accept
+    ///
+    /// Semver requirements are intersected, and `*` accepts anything. A kelvin, commit, tag, or
+    /// branch pins the package, so every other spec must name the same pin or, for a tag that
+    /// is a semver version, be a requirement the tag meets. Kelvin ranges narrow to the newest
+    /// kelvin they all accept. Returns None if the specs conflict.
+    pub fn unify(specs: &[VersionSpec]) -> Option<VersionSpec> {
+        let mut pin: Option<&VersionSpec> = None;
+        let mut comparators = Vec::new();
+
+        for spec in specs {
+            match spec {
+                VersionSpec::Semver(req) => {
+                    for comparator in &req.comparators {
+                        if !comparators.contains(comparator) {
+                            comparators.push(comparator.clone());
+                        }
+                    }
+                }
+                other => match pin {
+                    None => pin = Some(other),
+                    Some(existing) if same_pin(existing, other) => {
+                        // Keep the longer of two commit prefixes
+                        if let (VersionSpec::Commit(a), VersionSpec::Commit(b)) = (existing, other)
+                        {
+                            if b.len() > a.len() {
+                                pin = Some(other);
+                            }
+                        }
+                    }
+                    Some(existing) => match narrower_kelvin(existing, other) {
+                        Some(narrower) => pin = Some(narrower),
+                        None => return None,
+                    },
+                },
+            }
+        }
+
+        let req = VersionReq { comparators };
+        match pin {
+            None => Some(VersionSpec::Semver(req)),
+            Some(pin) if req.comparators.is_empty() => Some(pin.clone()),
+            Some(VersionSpec::Tag(tag)) => {
+                let version = semver::Version::parse(tag.trim_start_matches('v')).ok()?;
+                req.matches(&version).then(|| VersionSpec::Tag(tag.clone()))
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+/// Whether two pinned specs name the same version
+fn same_pin(a: &VersionSpec, b: &VersionSpec) -> bool {
+    match (a, b) {
+        (VersionSpec::Commit(a), VersionSpec::Commit(b)) => a.starts_with(b) || b.starts_with(a),
+        _ => a == b,
+    }
+}
+
+/// The one of two kelvin specs that accepts only kelvins both accept, if either does
+fn narrower_kelvin<'a>(a: &'a VersionSpec, b: &'a VersionSpec) -> Option<&'a VersionSpec> {
+    match (a, b) {
+        (VersionSpec::KelvinRange(x), VersionSpec::KelvinRange(y)) => {
+            Some(if x <= y { a } else { b })
+        }
+        (VersionSpec::KelvinRange(x), VersionSpec::Kelvin(k)) if k <= x => Some(b),
+        (VersionSpec::Kelvin(k), VersionSpec::KelvinRange(x)) if k <= x => Some(a),
+        _ => None,
+    }
+}
+
+/// Kelvin number of a version or tag such as `k409`, `@k409` or `409k`
+pub fn kelvin_of(version: &str) -> Option<u32> {
+    let version = version.trim_start_matches('@');
+    version
+        .strip_suffix('k')
+        .or_else(|| version.strip_prefix('k'))
+        .and_then(|n| n.parse().ok())
+}
+
+/// The tags that are kelvins `spec` accepts, or every kelvin if it is `*`, newest first
+pub fn kelvin_tags(tags: &[String], spec: &VersionSpec) -> Vec<(u32, String)> {
+    let mut kelvins: Vec<(u32, String)> = tags
+        .iter()
+        .filter(|tag| spec.is_any() || spec.matches(tag))
+        .filter_map(|tag| Some((kelvin_of(tag)?, tag.clone())))
+        .collect();
+    kelvins.sort();
+    kelvins
 }
 
 /// Parse a package spec in the form "name@version"
@@ -211,6 +352,10 @@ mod tests {
 
         let spec = VersionSpec::parse("k417").unwrap();
         assert_eq!(spec, VersionSpec::Kelvin(417));
+
+        let spec = VersionSpec::parse("^k412").unwrap();
+        assert_eq!(spec, VersionSpec::KelvinRange(412));
+        assert_eq!(spec.to_canonical_string(), "^k412");
     }
 
     #[test]
@@ -263,6 +408,14 @@ mod tests {
         assert!(spec.matches("@k414"));
         assert!(!spec.matches("k415"));
         assert!(!spec.matches("414"));
+        assert!(spec.matches("414k"));
+
+        // Newer kelvins have lower numbers
+        let spec = VersionSpec::KelvinRange(412);
+        assert!(spec.matches("k412"));
+        assert!(spec.matches("408k"));
+        assert!(!spec.matches("k413"));
+        assert!(!spec.matches("1.2.0"));
     }
 
     #[test]
@@ -312,6 +465,69 @@ mod tests {
         assert_eq!(version, VersionSpec::Commit("abc123".to_string()));
     }
 
+    #[test]
+    fn test_unify() {
+        let parse = |specs: &[&str]| -> Vec<VersionSpec> {
+            specs
+                .iter()
+                .map(|s| VersionSpec::parse(s).unwrap())
+                .collect()
+        };
+
+        // Semver requirements are intersected; `*` adds nothing
+        let unified = VersionSpec::unify(&parse(&["^1.2.0", "*", "<1.5.0", "^1.2.0"])).unwrap();
+        assert_eq!(unified.to_canonical_string(), "^1.2.0, <1.5.0");
+        assert!(unified.matches("1.4.9"));
+        assert!(!unified.matches("1.5.0"));
+        assert!(VersionSpec::unify(&parse(&["*", "latest"]))
+            .unwrap()
+            .is_any());
+
+        // Pins must agree with each other and with the semver requirements
+        assert_eq!(
+            VersionSpec::unify(&parse(&["k409", "*", "@k409"])),
+            Some(VersionSpec::Kelvin(409))
+        );
+        assert_eq!(VersionSpec::unify(&parse(&["k409", "k410"])), None);
+        assert_eq!(
+            VersionSpec::unify(&parse(&["^k412", "^k410", "*"])),
+            Some(VersionSpec::KelvinRange(410))
+        );
+        assert_eq!(
+            VersionSpec::unify(&parse(&["^k412", "k411"])),
+            Some(VersionSpec::Kelvin(411))
+        );
+        assert_eq!(VersionSpec::unify(&parse(&["^k412", "k413"])), None);
+        assert_eq!(
+            VersionSpec::unify(&parse(&["commit:abc123", "commit:abc123def"])),
+            Some(VersionSpec::Commit("abc123def".to_string()))
+        );
+        assert_eq!(
+            VersionSpec::unify(&parse(&["tag:v1.3.0", "^1.2.0"])),
+            Some(VersionSpec::Tag("v1.3.0".to_string()))
+        );
+        assert_eq!(VersionSpec::unify(&parse(&["tag:v2.0.0", "^1.2.0"])), None);
+        assert_eq!(VersionSpec::unify(&parse(&["branch:main", "^1.2.0"])), None);
+    }
+
+    #[test]
+    fn test_kelvin_tags() {
+        let tags: Vec<String> = ["k414", "412k", "409k", "v1.2.0", "main"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let newest = |spec: &str| {
+            kelvin_tags(&tags, &VersionSpec::parse(spec).unwrap())
+                .into_iter()
+                .map(|(_, tag)| tag)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(newest("latest"), ["409k", "412k", "k414"]);
+        assert_eq!(newest("^k413"), ["409k", "412k"]);
+        assert_eq!(newest("k414"), ["k414"]);
+        assert!(newest("^k400").is_empty());
+    }
+
     #[test]
     fn test_to_canonical_string() {
         assert_eq!(VersionSpec::Kelvin(414).to_canonical_string(), "k414");
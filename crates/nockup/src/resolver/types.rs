@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::manifest::DependencySpec;
 use crate::resolver::VersionSpec;
@@ -14,6 +14,44 @@ pub struct ResolvedPackage {
     pub install_path: Option<String>, // Subdir to install to (e.g., "sys")
     pub source_files: Option<Vec<String>>, // Specific files to extract (if any)
     pub dependencies: HashMap<String, DependencySpec>, // Transitive deps
+    pub license: Option<String>,     // From the package's hoon.toml
+    pub local: Option<String>,       // Directory of a workspace package, relative to the manifest
+}
+
+/// Requirements on a package that no single version satisfies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionConflict {
+    pub package: String,
+    pub requirements: Vec<DependencyRequest>,
+    pub available: Vec<String>, // Versions that were considered, when known
+}
+
+impl std::fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No version of '{}' satisfies every requirement on it:",
+            self.package
+        )?;
+        for request in &self.requirements {
+            let by = request.dependent.as_deref().unwrap_or("nockapp.toml");
+            write!(f, "\n  - {} (required by {})", request.version, by)?;
+        }
+        if !self.available.is_empty() {
+            write!(f, "\nAvailable versions: {}", self.available.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VersionConflict {}
+
+/// A package asking for another while the graph was resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyRequest {
+    pub dependent: Option<String>, // None when requested by the manifest itself
+    pub name: String,
+    pub version: String, // Canonical form of the requested version spec
 }
 
 /// A resolved dependency graph
@@ -21,6 +59,7 @@ pub struct ResolvedPackage {
 pub struct ResolvedGraph {
     pub packages: HashMap<String, ResolvedPackage>,
     pub install_order: Vec<String>, // Topological sort for installation
+    pub requests: Vec<DependencyRequest>, // Every request, including for already resolved packages
 }
 
 impl ResolvedGraph {
@@ -29,6 +68,7 @@ impl ResolvedGraph {
         Self {
             packages: HashMap::new(),
             install_order: Vec::new(),
+            requests: Vec::new(),
         }
     }
 
@@ -37,6 +77,57 @@ impl ResolvedGraph {
         self.packages.insert(package.name.clone(), package);
     }
 
+    /// Record that `dependent` (or the manifest, if None) asked for `name` at `version`
+    pub fn add_request(&mut self, dependent: Option<&str>, name: &str, version: &str) {
+        self.requests.push(DependencyRequest {
+            dependent: dependent.map(str::to_string),
+            name: name.to_string(),
+            version: version.to_string(),
+        });
+    }
+
+    /// Packages the manifest depends on directly, sorted
+    pub fn direct_dependencies(&self) -> Vec<&str> {
+        self.dependencies_of(None)
+    }
+
+    /// Packages that `dependent` (or the manifest, if None) asked for, sorted
+    pub fn dependencies_of(&self, dependent: Option<&str>) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .requests
+            .iter()
+            .filter(|r| r.dependent.as_deref() == dependent)
+            .map(|r| r.name.as_str())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// What `dependent` asked for of each of its dependencies, by name
+    pub fn constraints_of(&self, dependent: &str) -> BTreeMap<String, String> {
+        self.requests
+            .iter()
+            .filter(|r| r.dependent.as_deref() == Some(dependent))
+            .map(|r| (r.name.clone(), r.version.clone()))
+            .collect()
+    }
+
+    /// Packages that were asked for at more than one version, with each distinct version
+    pub fn duplicates(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut versions: BTreeMap<&str, Vec<&str>> = Default::default();
+        for request in &self.requests {
+            let seen = versions.entry(request.name.as_str()).or_default();
+            if !seen.contains(&request.version.as_str()) {
+                seen.push(request.version.as_str());
+            }
+        }
+        versions
+            .into_iter()
+            .filter(|(_, versions)| versions.len() > 1)
+            .collect()
+    }
+
     /// Compute topological installation order
     /// Simple approach: no cycles allowed, packages with no deps come first
     pub fn compute_install_order(&mut self) -> anyhow::Result<()> {
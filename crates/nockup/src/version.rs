@@ -1,5 +1,3 @@
-use std::path::PathBuf;
-
 use anyhow::{Context, Result};
 use colored::Colorize;
 use tokio::process::Command as TokioCommand;
@@ -8,28 +6,28 @@ use crate::commands::common;
 
 pub async fn show_version_info() -> Result<()> {
     // Show nockup version
-    println!("nockup version {}", env!("FULL_VERSION"));
+    say!("nockup version {}", env!("FULL_VERSION"));
 
     // Get hoon version
     match get_binary_version("hoon").await {
-        Ok(version) => println!("hoon   version {}", version),
-        Err(_) => println!("hoon   {}", "not found".red()),
+        Ok(version) => say!("hoon   version {}", version),
+        Err(_) => say!("hoon   {}", "not found".red()),
     }
 
     // Get hoonc version
     match get_binary_version("hoonc").await {
-        Ok(version) => println!("hoonc  version {}", version),
-        Err(_) => println!("hoonc  {}", "not found".red()),
+        Ok(version) => say!("hoonc  version {}", version),
+        Err(_) => say!("hoonc  {}", "not found".red()),
     }
 
     // Get current channel and architecture
     // The channel is in the TOML file at ~/.nockup/config.toml
     let config = get_config()?;
-    println!(
+    say!(
         "current channel {}",
         config["channel"].as_str().unwrap_or("stable")
     );
-    println!(
+    say!(
         "current architecture {}",
         config["architecture"].as_str().unwrap_or("unknown")
     );
@@ -103,13 +101,8 @@ fn extract_version_string(version_line: &str) -> String {
     version_line.to_string()
 }
 
-fn get_cache_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-    Ok(home.join(".nockup"))
-}
-
 fn get_config() -> Result<toml::Value> {
-    let cache_dir = get_cache_dir()?;
+    let cache_dir = common::get_cache_dir()?;
     let config_path = cache_dir.join("config.toml");
     if !config_path.exists() {
         let mut table = toml::map::Map::new();
@@ -20,7 +20,6 @@
         parsedSystem = pkgs.lib.systems.parse.mkSystemFromString system;
       in {
         devShells.default = pkgs.mkShell {
-          LIBCLANG_PATH = "${pkgs.llvmPackages.libclang.lib}/lib";
           buildInputs = [
             (fenix.packages.${system}.complete.withComponents [
               "cargo"
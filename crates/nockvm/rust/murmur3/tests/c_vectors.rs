@@ -0,0 +1,159 @@
+// Copyright (c) 2020 Stu Small
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Output of the reference C implementation (Austin Appleby's `MurmurHash3.cpp`), for every
+//! input length up to two 128-bit blocks plus a tail, so each tail case of each variant is
+//! covered. The 128-bit hashes are the C output bytes read as a little-endian `u128`.
+
+extern crate murmur3;
+
+use std::io::Cursor;
+
+use murmur3::{murmur3_32, murmur3_32_of_slice, murmur3_x64_128, murmur3_x86_128};
+
+/// Byte `i` of every input is `i * 37 + 11`.
+fn input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 37 + 11) as u8).collect()
+}
+
+// (seed, input length, x86_32, x86_128, x64_128)
+#[rustfmt::skip]
+const C_VECTORS: &[(u32, usize, u32, u128, u128)] = &[
+    (0x00000000,  0, 0x00000000, 0x00000000000000000000000000000000, 0x00000000000000000000000000000000),
+    (0x00000000,  1, 0x4d79446c, 0x825db56b825db56b825db56b1a2a893c, 0x7a434b816c4508dc932fc7cce617f1e7),
+    (0x00000000,  2, 0x6bfe18a9, 0x946637e0946637e0946637e0e25b086a, 0x8549c3b277c47536cff7e5ae5471b39d),
+    (0x00000000,  3, 0x389b84e7, 0x814fe1d3814fe1d3814fe1d3ef5dd547, 0x0cdcf4fb816ab2436afac0b8de81a4b1),
+    (0x00000000,  4, 0x1939e63d, 0xbffa0a8ebffa0a8ebffa0a8ed021ef70, 0x8f4cf22ad9a420cdd3a4eed1b81f9f72),
+    (0x00000000,  5, 0xa4811fac, 0x253eef03253eef03fcb58997a1bdd9f5, 0xad93f9759963f50bb0330f40087f990b),
+    (0x00000000,  6, 0x2f82c86d, 0xcf86f34ecf86f34e533a1fa21104c619, 0x4b5791ad3829937e32d1f10b04077a12),
+    (0x00000000,  7, 0x0ea050dd, 0xf2c048def2c048de0cf7684efefb3e3f, 0xa08f38dfaa7ff6f98340cc686662983b),
+    (0x00000000,  8, 0x3ae449f2, 0x40e1633840e16338f89a9067fc0141d5, 0xd02221832d7af9a1f0b144007f89ced7),
+    (0x00000000,  9, 0x593f000d, 0xc0f0e315dd382ad90f9dc92c37dd5f38, 0xcbde24182efe09a9dbb3088eaec8a0b1),
+    (0x00000000, 10, 0x868522c3, 0x351e03687f068c228b31ff12be719708, 0x7a1ac716596298b07fc82ef8fd4f371e),
+    (0x00000000, 11, 0xd3ece69e, 0x2864d73b5ceb057c02ff2c68b7d8ddb0, 0xc5c7129b727fc1d6ce105a4d0920d564),
+    (0x00000000, 12, 0x7d0fe536, 0x04feaac2427c9e8a92dde2a26bbe8b8b, 0xde304d589b0ddbf811eea59fafc3bbb8),
+    (0x00000000, 13, 0x6fa10f80, 0x1445e0c33bab087f107c882ccbedda29, 0xf34ba40d12f9b27e3c0bc0c7790cc4e7),
+    (0x00000000, 14, 0xb7b2cf95, 0xf5d7806d08a8894d263b29673bbe5d42, 0x27302e3cea75fb094af05278beeea171),
+    (0x00000000, 15, 0x6b580432, 0x7162791c03559fa988c1111ce834163d, 0x49ca338fe7701fac2906f047b67f83ff),
+    (0x00000000, 16, 0xe6271c29, 0xb35623e7adf04182a8c41845aa3a2073, 0x885aae87bb6c5ff7da9c66580c5ef0fb),
+    (0x00000000, 17, 0xc792fd21, 0xaee0538b9ede0bb0fbe3511fc5078dc0, 0xe36790301698fce078b8ee9a775e07d1),
+    (0x00000000, 18, 0x35176c7f, 0x8963f551b06f4446ee0152e812d11e6b, 0x16d1b21eec8d67ac5022ece9b605f7d8),
+    (0x00000000, 19, 0x2d6746be, 0xe45e6b2cc698fa969caa96da6bbd364a, 0x3d2b31d643027caf40591a6345e2fd5e),
+    (0x00000000, 20, 0x815bf850, 0x588fb5ac5980334e6923e823ffc8ded8, 0xa65eec4ab46824eef85aeb21888559f3),
+    (0x00000000, 21, 0x54ec73ca, 0x0506c6ac14fad583f185eceb86e144ef, 0x93818c98df190fa82c128c13418db6f3),
+    (0x00000000, 22, 0x1195dfbd, 0x2c87f3ecf0f71505b51e8a79f41f1808, 0x6dfadfaec1dcf534c0ffc3389f035072),
+    (0x00000000, 23, 0x7ade4401, 0x733ffacbafbf75672e4e5b236ae40f03, 0x5ad5c324b9edfa9901b7d4fb84dc53aa),
+    (0x00000000, 24, 0x50d92d4b, 0x132437fcfad5bd9a2e023166f7b3f9b9, 0xe8f405aaf3f01b038372e2feeb61eb1c),
+    (0x00000000, 25, 0x37925386, 0x35c6a200ccf9cd0bac5eea061540ebc6, 0x704bb843bd5ff913ed9e2943729d5da2),
+    (0x00000000, 26, 0x27f05464, 0xa619a4d590e992cf14a8f1584803fedf, 0x628fa90e86818db59fcd422e337cdec4),
+    (0x00000000, 27, 0x8eaaae9b, 0x815549ff8270ee5ab957a1597cf3d5e7, 0xe3a5ff0398ea8b76454a6a1202a0fd74),
+    (0x00000000, 28, 0x71f441a9, 0xa223e560e76e464d3c5eb4493b5c91fe, 0x82b2e058cfa3ecdac2ea419f1d4c02de),
+    (0x00000000, 29, 0xb79c39b0, 0x7b37996e271af28f893e88f3f569edea, 0xbef6e3ba00dbfc062ab2e5425a3cf241),
+    (0x00000000, 30, 0xad8cc658, 0x61162aa13da5cdaf42517092db7fd7ec, 0x3e2a6a483ba0dd7cfde530955c73468b),
+    (0x00000000, 31, 0xa5df4a5b, 0xaebc2f18c4d9c5a264e569c4661fa416, 0xcb6926489e7b3763b1ca061ed4c5532f),
+    (0x00000000, 32, 0x76cd521c, 0xabafe93f5d53b314e3dfb338ce5ff737, 0x3a5200924dcdd6ffaf7374eb8efe799b),
+    (0x00000000, 33, 0xdf59faad, 0x887e3f9d8b5892a528758d8ff2ce5217, 0x15c06fbdb5df8af908b88a88c3099ba9),
+    (0xcafebabe,  0, 0x79ff04e8, 0x1a9dfe081a9dfe081a9dfe08bc944223, 0x948217dd16ff5b47c0ffc9af09724c3d),
+    (0xcafebabe,  1, 0x2aeafa96, 0x79f9678d79f9678d79f9678d33410432, 0x719e5d6e092be3e3757c1868a4966fe9),
+    (0xcafebabe,  2, 0x84811949, 0xef294075ef294075ef2940759f6ab322, 0xe13bfe79e3440eb9da9843bf04ea73f4),
+    (0xcafebabe,  3, 0xe09ef260, 0x5a813a085a813a085a813a08d6228647, 0x58b3f98a257db9196eb420a580be5714),
+    (0xcafebabe,  4, 0xa3204568, 0x6dd5e4956dd5e4956dd5e4952f81a3e7, 0x33f348655b205d93bb91cf812881425e),
+    (0xcafebabe,  5, 0x51a77906, 0xba69c149ba69c149360247a33e06a994, 0x3d96e8d3e30f781144d023ebcfc14ee1),
+    (0xcafebabe,  6, 0xfb803bfc, 0x4d90efef4d90efef5cae59cf956a6e4a, 0x9241c054d68b2f37216e981896fadc08),
+    (0xcafebabe,  7, 0xb331f956, 0x3e2f4b5c3e2f4b5cd0eb64f00acf6c0f, 0x71e27d2b580d31dfae3b7f695c2def03),
+    (0xcafebabe,  8, 0xdb1950fc, 0x199be78e199be78ec280ad98a18dbe7e, 0x792a0ee563086fcbca3fd18c3ca3f65d),
+    (0xcafebabe,  9, 0x3582c14e, 0xc21e7bcac25d3b4ff87caa75f9c467e7, 0xa317564bb9f4c1b4cd368800d8007618),
+    (0xcafebabe, 10, 0x81111d35, 0x905baced1935c1c3c5af46ffbbb19caa, 0x28e2d628eaeda384dfb0c535949c27cf),
+    (0xcafebabe, 11, 0x3960e589, 0xe09d4efa93196b8e02d019f72f71d478, 0x3e83a9686657f38fb50a757e0de59d67),
+    (0xcafebabe, 12, 0x9de1aa20, 0x45c67cdfda82820ddb001c6261a93eb4, 0xaf8b19109fadee7d59f01c8d0923db6f),
+    (0xcafebabe, 13, 0xf26554a5, 0x2f2c82f2bf1bef09cd2098dd7f6cd909, 0xc94941e4a677a7d850332bcc2076b9d5),
+    (0xcafebabe, 14, 0x6e426d86, 0xffac244bc4a87d3241729cfab744b82c, 0xf2d903f634c736f2c276e40dac1ace3a),
+    (0xcafebabe, 15, 0x5b74e055, 0x832c5dfe6e41255b5ff4490566105cec, 0x843e1e86d00bf80e0ca233dec4f5cb3c),
+    (0xcafebabe, 16, 0x6644b55e, 0xc22df7401ba080853d91e2f2e8d76091, 0x71dbe9e03caf92ff0a1d11b8f5d9e5b1),
+    (0xcafebabe, 17, 0xfa9b85ff, 0xdf918cf59737d2b8019bd46db2943dee, 0x9b29956b1407ce649699d7a88f9f8434),
+    (0xcafebabe, 18, 0x6aecab9a, 0x6063400c41485572501c7bcfd9f49e75, 0x43851492ad025c5614826fc5b65c5522),
+    (0xcafebabe, 19, 0xdad55cf7, 0x91191389858949dd3c04119a408745d1, 0x47884d05855271465a6a0c3342884394),
+    (0xcafebabe, 20, 0x123b6172, 0x6845f1d75cdd27b396bffd6f65befeba, 0x92204d60decb6df4ccd2bcbc2e2308c5),
+    (0xcafebabe, 21, 0x07304530, 0xc5ebcc96e52433bcebff1f2dd9cffd46, 0x964a1863e5abc1a53d3214e0177e3c89),
+    (0xcafebabe, 22, 0xe6780adc, 0xca0068691af1e1454b6f796f70079fb0, 0xc123f38e81683186e27ca31cc6501c87),
+    (0xcafebabe, 23, 0xfb93bcf3, 0x3b57ed1e7f6094f32c5c2cf89a2c7676, 0x2c32e46e0b060bd4b4a07f44af652bde),
+    (0xcafebabe, 24, 0x02988b0b, 0xa7304d74c633239930193f364cc56f7a, 0xb5325f6e070a78abe85e232b364a4bbb),
+    (0xcafebabe, 25, 0x10902001, 0xfcc311bfa3948684681d3e47467c0926, 0x474cb1d44aa90b5e6f00c77fc32fa0b6),
+    (0xcafebabe, 26, 0x99fd5811, 0x1e79f9cf5aae6c5f0d74383851385c80, 0x470d85c2dceb650e29641a09b2a6283a),
+    (0xcafebabe, 27, 0x42d1453c, 0xd127818fedeef8fe737a99c0462a4694, 0x410f8ab1be8815522a5caf1a9791f443),
+    (0xcafebabe, 28, 0xb03a54c8, 0x39412ef5f8d17a98309fa174f23d105f, 0x119c494c51b4ae728ba6bef41a99d57d),
+    (0xcafebabe, 29, 0xdccb9278, 0xc0fc1781fd1cbc27b9ca256c4ea260b5, 0xb1cc62ef59b84999c19221c12a369767),
+    (0xcafebabe, 30, 0xae40afd1, 0xe3db5ce12da7e6cff5fc05bce913ea6f, 0x713bc2d5a6fa2b0b892da538246d9bf3),
+    (0xcafebabe, 31, 0xf79ba4b8, 0x3543702485dc22ae31ce6e310e745352, 0xcc3f648100356a1118c6e6cbb1964abc),
+    (0xcafebabe, 32, 0x12b326cd, 0x2c4cbf911bc6d19e9d7a5c6a49124231, 0x8046f4e67408aea655ccf309015e2272),
+    (0xcafebabe, 33, 0x696dbf38, 0x7b614ada21e8041e8210e7624a749e69, 0x2a1c22e4635ea84e4693dd096fe2af01),
+    (0xffffffff,  0, 0x81f16f39, 0x989d49f7989d49f7989d49f7051e08a9, 0x857421121ee6446b6af1df4d9d3bc9ec),
+    (0xffffffff,  1, 0x0356425b, 0x35a7c4a835a7c4a835a7c4a8a283d4d2, 0x6a8ae02950aa52bb024d662fed410084),
+    (0xffffffff,  2, 0x7b9984e6, 0x93579f6c93579f6c93579f6c85ce7c32, 0x92304e5f7e00c12c07b1a56b2afdbb98),
+    (0xffffffff,  3, 0x9e95b6a1, 0x09e2f6d909e2f6d909e2f6d964b213d5, 0xd3bdde5dc24a25abd99be2e0648daefb),
+    (0xffffffff,  4, 0xde692f70, 0x7ffb6f2c7ffb6f2c7ffb6f2c075378c1, 0xbaf33268b4dcabb1a35273ab3e09778a),
+    (0xffffffff,  5, 0xf83f2e0e, 0x81e57c8381e57c833bf6b803b582720e, 0x2c42deb23cd6c50d834cc26af69ee11d),
+    (0xffffffff,  6, 0x8b517ed4, 0x6fcd95926fcd9592b3dda21e1b8c9b49, 0x18650e98318e9c3e84176124dbbfa4fd),
+    (0xffffffff,  7, 0x5f39ccf9, 0xe56b614de56b614df57b025cd6f4fc90, 0xfb75332becb25c42ccfe9d24279074cf),
+    (0xffffffff,  8, 0x828dc9ac, 0x02ddd25802ddd2583f301e1436489ef7, 0xde5cdc6b46663208e8c3a5e74b3af919),
+    (0xffffffff,  9, 0x2b9178ff, 0x21852bd952b9d9a1b7e691409f5cadd2, 0x2377ae7b2b2f2cb154347eb9f0ce0718),
+    (0xffffffff, 10, 0xecd2a97c, 0xf903b7e3d56182217938f1b5a989ed70, 0xe90bed7f9c8a25f4d2755135798d8884),
+    (0xffffffff, 11, 0xb0630709, 0x79d3294c49d44b2d390c8245849cd42d, 0x878f6b0f53078e293eb3c72690dc5b64),
+    (0xffffffff, 12, 0x5efde262, 0xaadc79ac30f7f9a645273c53eca3fcc3, 0xb3008838754170e9e9c0db8c70aa3cf5),
+    (0xffffffff, 13, 0x0b0191f0, 0x204dc772583f90d86a2f8f35246c276c, 0xd5631a18781f89d99eb97ef817dbc22d),
+    (0xffffffff, 14, 0x7e579c8c, 0xfd334866ad2f1a1dc5fe55dabf4fb7be, 0x7466a9c532746d10325f19a206b00af2),
+    (0xffffffff, 15, 0x05afc79d, 0x75e76f33b4562dd2f3a20b396fb33264, 0xe74ecfae6c9f0abc797ef50a52c26dd6),
+    (0xffffffff, 16, 0x15a4ed7f, 0xdd3a05640753cf9cd10904ea4c0140c2, 0xbf93c8eb269488d506d3fc3376fe6236),
+    (0xffffffff, 17, 0xe761a468, 0x621d97f683118481d5c41177fbf35ca8, 0x7a8e295552d2d6492fdd79fef11a1539),
+    (0xffffffff, 18, 0x432b0f21, 0x3fa0e4b08da670d0763e623413d13bcb, 0x353281a4eeed7a6b91d73f4d12f51119),
+    (0xffffffff, 19, 0x19942cca, 0x4a150321df6e94ffe53c8da287abfcc8, 0x7c8150972f8bf46727a07008bfacc47a),
+    (0xffffffff, 20, 0xc0b516d7, 0x92e6e4ddc1c32db2983c246291db67ca, 0x30b2fda598ec61d787c7ae3dda45e763),
+    (0xffffffff, 21, 0x88b7893f, 0xa558c97d95ca48fab322e7a5d858cca2, 0x0dcddb5d157a685a1c0a5816edf60077),
+    (0xffffffff, 22, 0x08f6d115, 0x85e29baeb2f2ff39566ebaec9fb282d3, 0xa6af42a34654065b65ec43b85c96c888),
+    (0xffffffff, 23, 0x2cdfe72c, 0xf59311b8b3fcb007bace1676d979dca2, 0xe755abae7470dfbd347f90bb4b2e746e),
+    (0xffffffff, 24, 0x698d846e, 0x581bd608fc804d9ac958ac84c646a65b, 0x4dd50898f2c73732f628164893248027),
+    (0xffffffff, 25, 0x16998618, 0x99cbd11fcca7aee7491ad6b1949e633d, 0x7c5dd13ea8040e447b0e613c401d4ae5),
+    (0xffffffff, 26, 0x7eff92f8, 0x0a53bff7b434d1e0f1d595f7ba0b0395, 0x8f46eaa4e6cb729d7a4518260a9767c1),
+    (0xffffffff, 27, 0xfee00728, 0x1e1a5ff1428cfa84e260da2ec53515dc, 0x1821636211e76ed84a0bf3238ed6196c),
+    (0xffffffff, 28, 0x1f20ad9f, 0xc27ac5f9d71d27d96022ce125dcd3dc1, 0xc7ddb773e2723a784ac0a9a3e3411d28),
+    (0xffffffff, 29, 0xea533633, 0x50c4edb7b53e09f7e800417fa9666030, 0x96881e1660f04d2628b223101a297636),
+    (0xffffffff, 30, 0x15e5b4f2, 0x6af9af396ef1c970dee7fbea1dbc240c, 0xe5de5920357aac43dfbf5079bad2fa15),
+    (0xffffffff, 31, 0xbe668443, 0x049f0250b4ee700ce6e011d105f4a454, 0x43b63a887cd5796b2f3a479daf996e67),
+    (0xffffffff, 32, 0x2da72643, 0x4a21893bf65218677df3757d378ca8ac, 0x59347ac728b88fbb20f39e2335a3cc0d),
+    (0xffffffff, 33, 0x44721047, 0x484dad64bb64a63b8f7a9d2d04d59bd3, 0x221d723be19fac3336697d1c1a3ac3af),
+];
+
+#[test]
+fn matches_c_murmur3_32() {
+    for &(seed, len, expected, _, _) in C_VECTORS {
+        let xs = input(len);
+        let hash = murmur3_32(&mut Cursor::new(&xs), seed).expect("read from a cursor");
+        assert_eq!(hash, expected, "seed {seed:#x}, length {len}");
+        assert_eq!(
+            murmur3_32_of_slice(&xs, seed),
+            expected,
+            "seed {seed:#x}, length {len}"
+        );
+    }
+}
+
+#[test]
+fn matches_c_murmur3_x86_128() {
+    for &(seed, len, _, expected, _) in C_VECTORS {
+        let hash = murmur3_x86_128(&mut Cursor::new(input(len)), seed).expect("read from a cursor");
+        assert_eq!(hash, expected, "seed {seed:#x}, length {len}");
+    }
+}
+
+#[test]
+fn matches_c_murmur3_x64_128() {
+    for &(seed, len, _, _, expected) in C_VECTORS {
+        let hash = murmur3_x64_128(&mut Cursor::new(input(len)), seed).expect("read from a cursor");
+        assert_eq!(hash, expected, "seed {seed:#x}, length {len}");
+    }
+}
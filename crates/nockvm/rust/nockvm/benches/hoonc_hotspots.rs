@@ -9,6 +9,7 @@ use nockvm::jets::cold::Cold;
 use nockvm::jets::hot::{Hot, URBIT_HOT_STATE};
 use nockvm::jets::warm::Warm;
 use nockvm::mem::NockStack;
+use nockvm::meter::{Meter, MeterLimits};
 use nockvm::noun::{self, Noun, D, T};
 use nockvm::serialization::{cue, jam};
 use nockvm::unifying_equality::unifying_equality;
@@ -42,6 +43,7 @@ fn bench_context() -> Context {
         trace_info: None,
         running_status: cancel,
         test_jets,
+        meter: None,
     }
 }
 
@@ -188,6 +190,50 @@ fn bench_interpret_hint_case(c: &mut Criterion) {
     });
 }
 
+/// A subject and formula counting up to `n` in a tail-recursive Nock 2 loop: the formula is
+/// `[6 [5 [0 6] [0 7]] [0 7] [2 [[0 2] [0 6] [4 0 7]] [0 2]]]`, run against `[formula n 0]`.
+fn build_counting_loop(stack: &mut NockStack, n: u64) -> (Noun, Noun) {
+    let axis2 = T(stack, &[D(0), D(2)]);
+    let axis6 = T(stack, &[D(0), D(6)]);
+    let axis7 = T(stack, &[D(0), D(7)]);
+    let inc = T(stack, &[D(4), axis7]);
+    let next = T(stack, &[axis2, axis6, inc]);
+    let recur = T(stack, &[D(2), next, axis2]);
+    let done = T(stack, &[D(5), axis6, axis7]);
+    let form = T(stack, &[D(6), done, axis7, recur]);
+    let subj = T(stack, &[form, D(n), D(0)]);
+    (subj, form)
+}
+
+/// The interpreter loop with no meter set, against the same loop metered. Metering off should
+/// cost no more than the unmetered loop did before metering existed: a never-taken branch.
+fn bench_interpret_metering(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interpret_metering");
+    for metered in [false, true] {
+        let name = if metered { "metered" } else { "unmetered" };
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    let mut ctx = bench_context();
+                    let (subj, form) = build_counting_loop(&mut ctx.stack, 100_000);
+                    if metered {
+                        let mut meter = Meter::new(MeterLimits::default());
+                        meter.start(&mut ctx.stack);
+                        ctx.meter = Some(meter);
+                    }
+                    (ctx, subj, form)
+                },
+                |(mut ctx, subj, form)| {
+                    let outcome = interpret(&mut ctx, subj, form);
+                    black_box(&outcome);
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
 fn build_balanced_tree(stack: &mut NockStack, depth: u8, seed: u64) -> Noun {
     if depth == 0 {
         D(seed & 0xffff_ffff)
@@ -330,6 +376,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     bench_noun_preserve(c);
     bench_unifying_equality(c);
     bench_interpret_hint_case(c);
+    bench_interpret_metering(c);
     bench_warm_lookup(c);
     bench_cache_churn(c);
     bench_cue_jam_roundtrip(c);
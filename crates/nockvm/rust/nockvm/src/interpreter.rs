@@ -16,6 +16,7 @@ use crate::jets::list::util::weld;
 use crate::jets::warm::Warm;
 use crate::jets::{cold, JetErr};
 use crate::mem::{NockStack, Preserve};
+use crate::meter::Meter;
 use crate::noun::{Atom, Cell, IndirectAtom, Noun, Slots, D, T};
 use crate::trace::{write_nock_trace, TraceInfo, TraceStack};
 use crate::unifying_equality::unifying_equality;
@@ -437,6 +438,8 @@ pub struct Context {
     pub trace_info: Option<TraceInfo>,
     pub running_status: Arc<AtomicIsize>,
     pub test_jets: Hamt<()>,
+    /// Budget for the event being run, if it is metered
+    pub meter: Option<Meter>,
 }
 
 #[derive(Debug, Clone)]
@@ -671,6 +674,11 @@ pub fn interpret(context: &mut Context, mut subject: Noun, formula: Noun) -> Res
         try_or_bail!(push_formula(&mut context.stack, formula, true));
 
         loop {
+            if let Some(meter) = context.meter.as_mut() {
+                if let Err(mote) = meter.tick(&context.stack) {
+                    break Err(Error::NonDeterministic(mote, D(0)));
+                }
+            }
             let work_ptr = context.stack.top::<NockWork>();
             match &mut *work_ptr {
                 NockWork::Work0(zero) => {
@@ -368,6 +368,7 @@ pub mod util {
                 trace_info: None,
                 running_status: cancel,
                 test_jets,
+                meter: None,
             }
         }
 
@@ -13,6 +13,7 @@ pub mod hamt;
 pub mod interpreter;
 pub mod jets;
 pub mod mem;
+pub mod meter;
 pub mod mug;
 pub mod noun;
 pub mod serialization;
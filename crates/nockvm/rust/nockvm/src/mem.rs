@@ -64,6 +64,19 @@ pub enum AllocationError {
     // NoSlotsAvailable,
 }
 
+impl AllocationError {
+    /// Whether this is an out-of-memory error raised outside of a pre-copy phase. In that case
+    /// nothing has been moved yet, so nouns in the frames below the failing computation are intact
+    /// and can still be copied off the stack before it is dropped, e.g. to retry the computation
+    /// on a larger one.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            AllocationError::OutOfMemory(OutOfMemoryError(state, alloc)) if !state.pc && !alloc.pc
+        )
+    }
+}
+
 impl From<AllocationError> for std::io::Error {
     fn from(_e: AllocationError) -> std::io::Error {
         std::io::ErrorKind::OutOfMemory.into()
@@ -243,6 +256,8 @@ pub struct NockStack {
     alloc_offset: usize,
     /// The least amount of space between the stack and alloc pointers since last reset
     least_space: usize,
+    /// Like `least_space`, but reset at the start of each metered event
+    event_least_space: usize,
     /// The underlying memory allocation which must be kept alive
     memory: Memory,
     /// Whether or not [`Self::pre_copy()`] has been called on the current stack frame.
@@ -348,6 +363,7 @@ impl NockStack {
                 stack_offset,
                 alloc_offset,
                 least_space,
+                event_least_space: least_space,
                 memory,
                 pc: false,
             },
@@ -641,6 +657,7 @@ impl NockStack {
                     Some(size),
                 )),
             };
+            self.event_least_space = self.least_space.min(self.event_least_space);
             self.pc = false;
 
             assert!(!self.is_west());
@@ -680,6 +697,7 @@ impl NockStack {
                     Some(size),
                 )),
             };
+            self.event_least_space = self.least_space.min(self.event_least_space);
             self.pc = false;
 
             assert!(self.is_west());
@@ -697,6 +715,7 @@ impl NockStack {
             .alloc_offset
             .checked_sub(self.frame_offset)
             .expect("Resetting a stack too small (should never happen)");
+        self.event_least_space = self.least_space.min(self.event_least_space);
         self.pc = false;
 
         unsafe {
@@ -765,7 +784,7 @@ impl NockStack {
     }
 
     /** Size **in 64-bit words** of this NockStack */
-    pub(crate) fn size(&self) -> usize {
+    pub fn size(&self) -> usize {
         self.size
     }
 
@@ -774,6 +793,21 @@ impl NockStack {
         self.least_space
     }
 
+    /** Get the low-water-mark for space since the last [`Self::reset_event_least_space`] */
+    pub fn event_least_space(&self) -> usize {
+        self.event_least_space
+    }
+
+    /** Reset the per-event low-water-mark for space to the space free right now, leaving
+     * [`Self::least_space`] alone */
+    pub fn reset_event_least_space(&mut self) {
+        self.event_least_space = if self.is_west() {
+            self.alloc_offset.saturating_sub(self.stack_offset)
+        } else {
+            self.stack_offset.saturating_sub(self.alloc_offset)
+        };
+    }
+
     /** Check to see if an allocation is in frame */
     #[inline]
     pub(crate) unsafe fn is_in_frame<T>(&self, ptr: *const T) -> bool {
@@ -1061,6 +1095,7 @@ impl NockStack {
             ),
         };
         self.least_space = new_space.min(self.least_space);
+        self.event_least_space = new_space.min(self.event_least_space);
 
         // Derive pointer from the new offset
         let alloc_ptr = self.derive_ptr(new_alloc_offset);
@@ -1096,6 +1131,7 @@ impl NockStack {
             ),
         };
         self.least_space = new_space.min(self.least_space);
+        self.event_least_space = new_space.min(self.event_least_space);
 
         // Check that the new offset is within bounds
         if new_alloc_offset > self.size {
@@ -1437,6 +1473,25 @@ impl NockStack {
         self.pc = false;
     }
 
+    /// Run `f` against `owner` in a new frame on the stack `stack` finds in it, then pop the frame,
+    /// throwing away everything allocated in it. The frame is left in place if `f` panics.
+    ///
+    /// # Safety
+    ///
+    /// Once `f` returns, nothing reachable may point into the popped frame: not its result, and
+    /// not anything it stored in `owner` or elsewhere. Results must be copied off the stack and
+    /// any state `f` replaced must be restored by the caller.
+    pub unsafe fn with_frame<O: ?Sized, T>(
+        owner: &mut O,
+        stack: impl Fn(&mut O) -> &mut NockStack,
+        f: impl FnOnce(&mut O) -> T,
+    ) -> T {
+        stack(owner).frame_push(0);
+        let res = f(owner);
+        stack(owner).frame_pop();
+        res
+    }
+
     pub unsafe fn preserve<T: Preserve>(&mut self, x: &mut T) {
         x.preserve(self)
     }
@@ -2200,6 +2255,43 @@ mod test {
         assert!(frame_push_res.is_ok());
     }
 
+    #[test]
+    fn test_out_of_memory_is_recoverable() {
+        let mut stack = make_test_stack(512);
+        let err = catch_unwind(AssertUnwindSafe(|| stack.frame_push(504)))
+            .expect_err("Expected alloc error");
+        let err = err
+            .downcast_ref::<AllocationError>()
+            .expect("Expected alloc error");
+        assert!(err.is_recoverable());
+        assert_eq!(stack.size(), 512);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_deep_recursion_is_recoverable() {
+        use crate::interpreter::interpret;
+        use crate::jets::util::test::init_context;
+        use crate::noun::T;
+
+        // *[f f] with f = [[2 [0 1] [0 1]] [1 0]] autocons onto itself forever, and since the
+        // recursive call is in the head it can never be a tail call.
+        let mut context = init_context();
+        let stack = &mut context.stack;
+        let whole = T(stack, &[D(0), D(1)]);
+        let recur = T(stack, &[D(2), whole, whole]);
+        let konst = T(stack, &[D(1), D(0)]);
+        let formula = T(stack, &[recur, konst]);
+        let err = catch_unwind(AssertUnwindSafe(|| {
+            interpret(&mut context, formula, formula)
+        }))
+        .expect_err("Expected alloc error");
+        let err = err
+            .downcast_ref::<AllocationError>()
+            .expect("Expected alloc error");
+        assert!(err.is_recoverable());
+    }
+
     // cargo test -p nockvm test_stack_push -- --nocapture
     #[test]
     fn test_stack_push() {
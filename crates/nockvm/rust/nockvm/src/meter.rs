@@ -0,0 +1,235 @@
+//! Per-event budgets on Nock steps and allocation.
+//!
+//! A [`Meter`] set on the interpreter [`Context`](crate::interpreter::Context) counts every step
+//! the interpreter takes, across nested calls and virtualized Nock, and bails once the event it
+//! was [started](Meter::start) for goes over its [`MeterLimits`]: with `%intr` for too many steps
+//! or a [`MeterHook`] refusing to continue, and with `%meme` for too much allocation. Both are
+//! nondeterministic, so Nock can't catch them with `+mule`.
+
+use crate::interpreter::Mote;
+use crate::mem::NockStack;
+
+/// Steps between checks of the allocation limit and the hook.
+pub const METER_CHECK_INTERVAL: u64 = 1 << 16;
+
+/// Limits on what a single event may consume, `None` meaning unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MeterLimits {
+    /// Nock steps taken by the interpreter
+    pub steps: Option<u64>,
+    /// Words allocated on the [`NockStack`] at any one time
+    pub alloc_words: Option<usize>,
+}
+
+/// What an event has consumed so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MeterUsage {
+    pub steps: u64,
+    /// The most words the event has had allocated at once. Only measured every
+    /// [`METER_CHECK_INTERVAL`] steps while the event runs.
+    pub alloc_words: usize,
+}
+
+impl MeterUsage {
+    /// The usage of running `self` and then `other` as one event.
+    pub fn combine(self, other: MeterUsage) -> MeterUsage {
+        MeterUsage {
+            steps: self.steps + other.steps,
+            alloc_words: self.alloc_words.max(other.alloc_words),
+        }
+    }
+}
+
+/// Called every [`METER_CHECK_INTERVAL`] steps with the event's usage so far.
+pub trait MeterHook: Send {
+    /// Whether the event may keep running. Returning `false` interrupts it.
+    fn check(&mut self, usage: &MeterUsage) -> bool;
+}
+
+pub struct Meter {
+    limits: MeterLimits,
+    usage: MeterUsage,
+    /// Free space on the stack when the event started
+    start_space: usize,
+    hook: Option<Box<dyn MeterHook>>,
+}
+
+impl Meter {
+    pub fn new(limits: MeterLimits) -> Self {
+        Self {
+            limits,
+            usage: MeterUsage::default(),
+            start_space: 0,
+            hook: None,
+        }
+    }
+
+    pub fn with_hook(mut self, hook: impl MeterHook + 'static) -> Self {
+        self.hook = Some(Box::new(hook));
+        self
+    }
+
+    pub fn limits(&self) -> MeterLimits {
+        self.limits
+    }
+
+    /// Start metering a new event on `stack`, clearing the last event's usage.
+    pub fn start(&mut self, stack: &mut NockStack) {
+        stack.reset_event_least_space();
+        self.usage = MeterUsage::default();
+        self.start_space = stack.event_least_space();
+    }
+
+    /// Stop metering the current event, returning what it consumed.
+    pub fn finish(&mut self, stack: &NockStack) -> MeterUsage {
+        self.measure_alloc(stack);
+        self.usage
+    }
+
+    pub fn usage(&self) -> MeterUsage {
+        self.usage
+    }
+
+    /// Count one interpreter step, failing with the mote to bail with if the event is over budget.
+    #[inline(always)]
+    pub(crate) fn tick(&mut self, stack: &NockStack) -> Result<(), Mote> {
+        self.usage.steps += 1;
+        if self
+            .limits
+            .steps
+            .is_some_and(|steps| self.usage.steps > steps)
+        {
+            return Err(Mote::Intr);
+        }
+        if self.usage.steps % METER_CHECK_INTERVAL == 0 {
+            self.check(stack)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cold]
+    fn check(&mut self, stack: &NockStack) -> Result<(), Mote> {
+        self.measure_alloc(stack);
+        if self
+            .limits
+            .alloc_words
+            .is_some_and(|words| self.usage.alloc_words > words)
+        {
+            return Err(Mote::Meme);
+        }
+        if let Some(hook) = &mut self.hook {
+            if !hook.check(&self.usage) {
+                return Err(Mote::Intr);
+            }
+        }
+        Ok(())
+    }
+
+    fn measure_alloc(&mut self, stack: &NockStack) {
+        let alloc_words = self.start_space.saturating_sub(stack.event_least_space());
+        self.usage.alloc_words = self.usage.alloc_words.max(alloc_words);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{interpret, Context, Error};
+    use crate::jets::util::test::init_context;
+    use crate::noun::{IndirectAtom, Noun, D, T};
+
+    struct Refuse;
+
+    impl MeterHook for Refuse {
+        fn check(&mut self, _usage: &MeterUsage) -> bool {
+            false
+        }
+    }
+
+    fn metered(mut meter: Meter) -> Context {
+        let mut context = init_context();
+        meter.start(&mut context.stack);
+        context.meter = Some(meter);
+        context
+    }
+
+    /// A subject and formula that loop forever in tail position, consing a cell onto the
+    /// subject each time around: `f` is `[2 [[0 2] [0 1]] [0 2]]` run against `[f 0]`.
+    fn consing_loop(context: &mut Context) -> (Noun, Noun) {
+        let stack = &mut context.stack;
+        let head = T(stack, &[D(0), D(2)]);
+        let whole = T(stack, &[D(0), D(1)]);
+        let cons = T(stack, &[head, whole]);
+        let formula = T(stack, &[D(2), cons, head]);
+        let subject = T(stack, &[formula, D(0)]);
+        (subject, formula)
+    }
+
+    fn usage(context: &mut Context) -> MeterUsage {
+        let meter = context.meter.as_mut().expect("meter is set");
+        meter.finish(&context.stack)
+    }
+
+    #[test]
+    fn counts_steps() {
+        let mut context = metered(Meter::new(MeterLimits::default()));
+        let formula = T(&mut context.stack, &[D(4), D(0), D(1)]);
+        interpret(&mut context, D(41), formula).expect("increment should succeed");
+        assert!(usage(&mut context).steps > 0);
+    }
+
+    #[test]
+    fn start_leaves_all_time_low_water_mark() {
+        let mut context = init_context();
+        let stack = &mut context.stack;
+        stack.frame_push(0);
+        unsafe {
+            IndirectAtom::new_raw_mut_zeroed(stack, 1000);
+            stack.frame_pop();
+        }
+        let least_space = stack.least_space();
+
+        let mut meter = Meter::new(MeterLimits::default());
+        meter.start(stack);
+        assert_eq!(stack.least_space(), least_space);
+        assert!(stack.event_least_space() >= least_space + 1000);
+
+        unsafe { IndirectAtom::new_raw_mut_zeroed(stack, 10) };
+        assert_eq!(stack.least_space(), least_space);
+        assert!(meter.finish(stack).alloc_words >= 10);
+    }
+
+    #[test]
+    fn step_limit_interrupts() {
+        let mut context = metered(Meter::new(MeterLimits {
+            steps: Some(1000),
+            alloc_words: None,
+        }));
+        let (subject, formula) = consing_loop(&mut context);
+        let res = interpret(&mut context, subject, formula);
+        assert!(matches!(res, Err(Error::NonDeterministic(Mote::Intr, _))));
+        assert_eq!(usage(&mut context).steps, 1001);
+    }
+
+    #[test]
+    fn alloc_limit_bails_with_meme() {
+        let mut context = metered(Meter::new(MeterLimits {
+            steps: None,
+            alloc_words: Some(1000),
+        }));
+        let (subject, formula) = consing_loop(&mut context);
+        let res = interpret(&mut context, subject, formula);
+        assert!(matches!(res, Err(Error::NonDeterministic(Mote::Meme, _))));
+        assert!(usage(&mut context).alloc_words > 1000);
+    }
+
+    #[test]
+    fn hook_can_interrupt() {
+        let mut context = metered(Meter::new(MeterLimits::default()).with_hook(Refuse));
+        let (subject, formula) = consing_loop(&mut context);
+        let res = interpret(&mut context, subject, formula);
+        assert!(matches!(res, Err(Error::NonDeterministic(Mote::Intr, _))));
+        assert_eq!(usage(&mut context).steps, METER_CHECK_INTERVAL);
+    }
+}
@@ -1,10 +1,12 @@
+use std::io::{BufReader, Read, Write};
+
 use bitvec::prelude::{BitSlice, Lsb0};
 use either::Either::{Left, Right};
 
 use crate::hamt::MutHamt;
 use crate::interpreter::Error::{self, *};
 use crate::interpreter::Mote::*;
-use crate::mem::NockStack;
+use crate::mem::{NockStack, Preserve};
 use crate::noun::{Atom, Cell, DirectAtom, IndirectAtom, Noun, D};
 
 crate::gdb!();
@@ -439,6 +441,387 @@ fn mat(traversal: &mut NockStack, state: &mut JamState, atom: Atom) -> Result<()
     }
 }
 
+/// Errors from streaming [`cue_from_reader`].
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("jam ended early")]
+    Truncated,
+    #[error("invalid jam: {0}")]
+    Cue(Error),
+}
+
+impl From<Error> for StreamError {
+    fn from(err: Error) -> Self {
+        StreamError::Cue(err)
+    }
+}
+
+impl Preserve for StreamError {
+    unsafe fn preserve(&mut self, stack: &mut NockStack) {
+        if let StreamError::Cue(err) = self {
+            err.preserve(stack);
+        }
+    }
+
+    unsafe fn assert_in_stack(&self, stack: &NockStack) {
+        if let StreamError::Cue(err) = self {
+            err.assert_in_stack(stack);
+        }
+    }
+}
+
+/// Bits buffered least-significant first and written out a word at a time, producing the same
+/// bytes as the little-endian encoding of the atom `jam` would return.
+struct BitWriter<W: Write> {
+    writer: W,
+    word: u64,
+    bits: usize,
+    written: usize,
+}
+
+impl<W: Write> BitWriter<W> {
+    fn new(writer: W) -> Self {
+        BitWriter {
+            writer,
+            word: 0,
+            bits: 0,
+            written: 0,
+        }
+    }
+
+    /// Cursor position in bits, which is what backreferences point at
+    fn cursor(&self) -> usize {
+        self.written * 8 + self.bits
+    }
+
+    /// Write the low `n` bits of `value`, `n <= 64`
+    fn push(&mut self, value: u64, n: usize) -> std::io::Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+        let value = if n < 64 {
+            value & ((1 << n) - 1)
+        } else {
+            value
+        };
+        self.word |= value << self.bits;
+        if self.bits + n < 64 {
+            self.bits += n;
+            return Ok(());
+        }
+        self.writer.write_all(&self.word.to_le_bytes())?;
+        self.written += 8;
+        self.word = if self.bits == 0 {
+            0
+        } else {
+            value >> (64 - self.bits)
+        };
+        self.bits = self.bits + n - 64;
+        Ok(())
+    }
+
+    /// Write the low `n` bits of `atom`
+    fn push_atom(&mut self, atom: Atom, n: usize) -> std::io::Result<()> {
+        let direct;
+        let indirect;
+        let words = match atom.as_either() {
+            Left(d) => {
+                direct = [d.data()];
+                &direct[..]
+            }
+            Right(i) => {
+                indirect = i;
+                indirect.as_slice()
+            }
+        };
+        let mut remaining = n;
+        for word in words {
+            if remaining == 0 {
+                break;
+            }
+            let take = remaining.min(64);
+            self.push(*word, take)?;
+            remaining -= take;
+        }
+        Ok(())
+    }
+
+    /// Length-encode `atom`, as `mat` does
+    fn mat(&mut self, atom: Atom) -> std::io::Result<()> {
+        let b_atom_size = met0_usize(atom);
+        if b_atom_size == 0 {
+            return self.push(1, 1);
+        }
+        let c_b_size = met0_u64_to_usize(b_atom_size as u64);
+        self.push(0, c_b_size)?;
+        self.push(1, 1)?;
+        self.push(b_atom_size as u64, c_b_size - 1)?;
+        self.push_atom(atom, b_atom_size)
+    }
+
+    /// Write out any partial word and flush, returning the number of bytes written
+    fn finish(mut self) -> std::io::Result<usize> {
+        let tail = self.bits.div_ceil(8);
+        self.writer.write_all(&self.word.to_le_bytes()[..tail])?;
+        self.writer.flush()?;
+        Ok(self.written + tail)
+    }
+}
+
+/// Serialize a noun straight into `writer`, without building the jammed atom in memory
+///
+/// The bytes written are exactly those of the atom [`jam`] returns, least-significant byte first.
+/// Only the backreference table is kept on the stack, so this is the way to write out state nouns
+/// too large to hold twice. Returns the number of bytes written.
+pub fn jam_to_writer<W: Write>(
+    stack: &mut NockStack,
+    noun: Noun,
+    writer: W,
+) -> std::io::Result<usize> {
+    let backref_map = MutHamt::new(stack);
+    let mut out = BitWriter::new(writer);
+    stack.frame_push(0);
+    unsafe {
+        *(stack.push::<Noun>()) = noun;
+    };
+    let res = jam_stream_loop(stack, backref_map, &mut out);
+    unsafe {
+        stack.frame_pop();
+    }
+    res?;
+    out.finish()
+}
+
+fn jam_stream_loop<W: Write>(
+    stack: &mut NockStack,
+    backref_map: MutHamt<u64>,
+    out: &mut BitWriter<W>,
+) -> std::io::Result<()> {
+    while !stack.stack_is_empty() {
+        let mut noun = unsafe { *(stack.top::<Noun>()) };
+        unsafe {
+            stack.pop::<Noun>();
+        };
+        if let Some(backref) = backref_map.lookup(stack, &mut noun) {
+            match noun.as_either_atom_cell() {
+                Left(atom) if met0_usize(atom) <= met0_u64_to_usize(backref) => {
+                    out.push(0, 1)?;
+                    out.mat(atom)?;
+                }
+                _ => {
+                    out.push(0b11, 2)?;
+                    let backref_atom = Atom::new(stack, backref);
+                    out.mat(backref_atom)?;
+                }
+            }
+            continue;
+        }
+        backref_map.insert(stack, &mut noun, out.cursor() as u64);
+        match noun.as_either_atom_cell() {
+            Left(atom) => {
+                out.push(0, 1)?;
+                out.mat(atom)?;
+            }
+            Right(cell) => {
+                out.push(0b01, 2)?;
+                unsafe {
+                    *(stack.push::<Noun>()) = cell.tail();
+                    *(stack.push::<Noun>()) = cell.head();
+                };
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bits read least-significant first from a byte stream. Reading past the end of the stream fails
+/// with [`StreamError::Truncated`]: a jam always ends on a 1 bit, so a complete one never needs
+/// the zero bits above its last byte.
+struct BitReader<R: Read> {
+    reader: BufReader<R>,
+    word: u64,
+    bits: usize,
+    eof: bool,
+    cursor: usize,
+}
+
+impl<R: Read> BitReader<R> {
+    fn new(reader: R) -> Self {
+        BitReader {
+            reader: BufReader::new(reader),
+            word: 0,
+            bits: 0,
+            eof: false,
+            cursor: 0,
+        }
+    }
+
+    /// Top the buffered word up to at least 57 bits, unless the stream has ended
+    fn refill(&mut self) -> std::io::Result<()> {
+        while self.bits <= 56 && !self.eof {
+            let mut byte = [0u8; 1];
+            match self.reader.read(&mut byte) {
+                Ok(0) => self.eof = true,
+                Ok(_) => {
+                    self.word |= (byte[0] as u64) << self.bits;
+                    self.bits += 8;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn consume(&mut self, n: usize) {
+        if n >= self.bits {
+            self.word = 0;
+            self.bits = 0;
+        } else {
+            self.word >>= n;
+            self.bits -= n;
+        }
+        self.cursor += n;
+    }
+
+    /// Read `n <= 64` bits, failing if fewer than `n` remain
+    fn read(&mut self, n: usize) -> Result<u64, StreamError> {
+        if n > 32 {
+            let low = self.read(32)?;
+            let high = self.read(n - 32)?;
+            return Ok(low | (high << 32));
+        }
+        if n == 0 {
+            return Ok(0);
+        }
+        self.refill()?;
+        if self.bits < n {
+            return Err(StreamError::Truncated);
+        }
+        let value = self.word & ((1 << n) - 1);
+        self.consume(n);
+        Ok(value)
+    }
+
+    /// Count the 0 bits before the next 1 bit and skip past both, failing if the stream ends first
+    fn zeros_then_one(&mut self) -> Result<usize, StreamError> {
+        let mut zeros = 0;
+        loop {
+            self.refill()?;
+            if self.bits == 0 {
+                return Err(StreamError::Truncated);
+            }
+            let run = (self.word.trailing_zeros() as usize).min(self.bits);
+            if run < self.bits {
+                self.consume(run + 1);
+                return Ok(zeros + run);
+            }
+            zeros += run;
+            self.consume(run);
+        }
+    }
+
+    /// Stream counterpart of `get_size`
+    fn size(&mut self) -> Result<usize, StreamError> {
+        let bitsize = self.zeros_then_one()?;
+        if bitsize == 0 {
+            Ok(0)
+        } else if bitsize > 64 {
+            Err(NonDeterministic(Fail, D(0)).into())
+        } else {
+            let size = self.read(bitsize - 1)? as usize;
+            Ok(size + (1 << (bitsize - 1)))
+        }
+    }
+
+    /// Stream counterpart of `rub_atom`
+    fn atom(&mut self, stack: &mut NockStack) -> Result<Atom, StreamError> {
+        let size = self.size()?;
+        if size < 64 {
+            let value = self.read(size)?;
+            return Ok(unsafe { DirectAtom::new_unchecked(value).as_atom() });
+        }
+        let wordsize = (size + 63) >> 6;
+        let (mut atom, buffer) = unsafe { IndirectAtom::new_raw_mut(stack, wordsize) };
+        let mut remaining = size;
+        for i in 0..wordsize {
+            let take = remaining.min(64);
+            unsafe { *buffer.add(i) = self.read(take)? };
+            remaining -= take;
+        }
+        Ok(unsafe { atom.normalize_as_atom() })
+    }
+
+    /// Stream counterpart of `rub_backref`
+    fn backref(&mut self) -> Result<u64, StreamError> {
+        let size = self.size()?;
+        if size <= 64 {
+            Ok(self.read(size)?)
+        } else {
+            Err(NonDeterministic(Fail, D(0)).into())
+        }
+    }
+}
+
+/// Deserialize a noun read from `reader`, without first reading the whole jam into memory
+///
+/// Accepts the bytes [`jam_to_writer`] produces (equivalently, the little-endian bytes of a jammed
+/// atom) and builds the noun on `stack`, as [`cue`] does.
+pub fn cue_from_reader<R: Read>(stack: &mut NockStack, reader: R) -> Result<Noun, StreamError> {
+    let backref_map = MutHamt::<Noun>::new(stack);
+    let mut result = D(0);
+    let mut input = BitReader::new(reader);
+
+    unsafe {
+        stack.with_frame(0, |stack: &mut NockStack| {
+            *(stack.push::<CueStackEntry>()) =
+                CueStackEntry::DestinationPointer(&mut result as *mut Noun);
+            loop {
+                if stack.stack_is_empty() {
+                    break Ok(result);
+                }
+                let stack_entry = *stack.top::<CueStackEntry>();
+                stack.pop::<CueStackEntry>();
+                match stack_entry {
+                    CueStackEntry::DestinationPointer(dest_ptr) => {
+                        let position = input.cursor as u64;
+                        if input.read(1)? == 0 {
+                            // 0 tag: atom
+                            *dest_ptr = input.atom(stack)?.as_noun();
+                            let mut backref_atom = Atom::new(stack, position).as_noun();
+                            backref_map.insert(stack, &mut backref_atom, *dest_ptr);
+                        } else if input.read(1)? == 1 {
+                            // 11 tag: backref
+                            let mut backref_noun = Atom::new(stack, input.backref()?).as_noun();
+                            *dest_ptr = backref_map
+                                .lookup(stack, &mut backref_noun)
+                                .ok_or(Deterministic(Exit, D(0)))?;
+                        } else {
+                            // 10 tag: cell
+                            let (cell, cell_mem_ptr) = Cell::new_raw_mut(stack);
+                            *dest_ptr = cell.as_noun();
+                            let mut backref_atom = Atom::new(stack, position).as_noun();
+                            backref_map.insert(stack, &mut backref_atom, *dest_ptr);
+                            *(stack.push()) =
+                                CueStackEntry::BackRef(position, dest_ptr as *const Noun);
+                            *(stack.push()) =
+                                CueStackEntry::DestinationPointer(&mut (*cell_mem_ptr).tail);
+                            *(stack.push()) =
+                                CueStackEntry::DestinationPointer(&mut (*cell_mem_ptr).head);
+                        }
+                    }
+                    CueStackEntry::BackRef(backref, noun_ptr) => {
+                        let mut backref_atom = Atom::new(stack, backref).as_noun();
+                        backref_map.insert(stack, &mut backref_atom, *noun_ptr)
+                    }
+                }
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -449,7 +832,7 @@ mod tests {
     use super::*;
     use crate::jets::util::test::assert_noun_eq;
     use crate::mem::NockStack;
-    use crate::noun::{Atom, Cell, CellMemory, Noun};
+    use crate::noun::{Atom, Cell, CellMemory, Noun, T};
     fn setup_stack() -> NockStack {
         NockStack::new(1 << 30, 0)
     }
@@ -627,6 +1010,59 @@ mod tests {
         assert_noun_eq(&mut stack, cued, original);
     }
 
+    fn jammed_bytes(atom: Atom) -> Vec<u8> {
+        let mut bytes = atom.as_ne_bytes().to_vec();
+        while bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        bytes
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_stream_jam_matches_jam() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut stack = setup_stack();
+        let (original, _) = generate_deeply_nested_noun(&mut stack, 7, &mut rng);
+        let large = Atom::new(&mut stack, u64::MAX).as_noun();
+        let shared = T(&mut stack, &[original, large, original, D(0)]);
+
+        for noun in [D(0), D(1), large, shared] {
+            let jammed = jam(&mut stack, noun);
+            let mut streamed = Vec::new();
+            let written = jam_to_writer(&mut stack, noun, &mut streamed).expect("write jam");
+            assert_eq!(written, streamed.len());
+            assert_eq!(streamed, jammed_bytes(jammed));
+
+            let cued = cue_from_reader(&mut stack, &streamed[..]).expect("cue from reader");
+            assert_noun_eq(&mut stack, cued, noun);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_stream_cue_truncated_input() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut stack = setup_stack();
+        let (nested, _) = generate_deeply_nested_noun(&mut stack, 5, &mut rng);
+        let large = Atom::new(&mut stack, u64::MAX).as_noun();
+        let wide = Atom::new(&mut stack, 1 << 40).as_noun();
+        let shared = T(&mut stack, &[nested, large, nested, wide]);
+
+        for noun in [D(1), large, T(&mut stack, &[D(1), D(2), D(3)]), shared] {
+            let mut streamed = Vec::new();
+            jam_to_writer(&mut stack, noun, &mut streamed).expect("write jam");
+            for cut in 0..streamed.len() {
+                let result = cue_from_reader(&mut stack, &streamed[..cut]);
+                assert!(
+                    matches!(result, Err(StreamError::Truncated)),
+                    "cut at byte {cut} of {}",
+                    streamed.len()
+                );
+            }
+        }
+    }
+
     // FIXME: why is bits unused?
     #[allow(clippy::only_used_in_recursion)]
     fn generate_random_noun(stack: &mut NockStack, bits: usize, rng: &mut StdRng) -> (Noun, usize) {
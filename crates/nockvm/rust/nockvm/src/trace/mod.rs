@@ -20,6 +20,9 @@ pub use tracing_backend::*;
 mod filter;
 pub use filter::*;
 
+mod profile;
+pub use profile::*;
+
 crate::gdb!();
 
 pub trait TraceBackend: Send {
@@ -0,0 +1,322 @@
+//! Sampling profiler over fast-hinted arms, written as folded stacks.
+//!
+//! The backend keeps the stack of jetted/fast-hinted arms the interpreter is currently inside and
+//! takes a sample every `interval` of wall-clock time, attributing it to that stack. Samples are
+//! grouped under the serf event that produced them (`boot`, `peek`, a poke's wire, ...) and written
+//! one stack per line as `event;outer/arm;inner/arm count`, the format `inferno-flamegraph` and
+//! `flamegraph.pl` read.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::*;
+
+/// How often the folded stacks are rewritten while the profiler runs.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy)]
+struct ProfileFrame {
+    /// Depth of the profiler's stack before this arm was entered
+    depth: usize,
+}
+
+pub struct ProfileBackend {
+    output: PathBuf,
+    interval: Duration,
+    labels: Vec<Box<str>>,
+    label_ids: HashMap<Box<str>, u32>,
+    /// Label ids by the encoded cold-state path they were made from
+    path_ids: HashMap<Box<[u8]>, u32>,
+    /// Scratch buffer for encoding a path before looking it up
+    path_key: Vec<u8>,
+    stack: Vec<u32>,
+    /// Samples for the event in progress, keyed by arm stack
+    pending: HashMap<Vec<u32>, u64>,
+    pending_total: u64,
+    /// Samples for finished events, keyed by event label and then arm stack
+    samples: HashMap<Vec<u32>, u64>,
+    last: Option<Instant>,
+    carry: Duration,
+    last_flush: Instant,
+}
+
+impl ProfileBackend {
+    pub fn new(output: impl Into<PathBuf>, interval: Duration) -> Self {
+        Self {
+            output: output.into(),
+            interval: interval.max(Duration::from_micros(1)),
+            labels: Vec::new(),
+            label_ids: HashMap::new(),
+            path_ids: HashMap::new(),
+            path_key: Vec::new(),
+            stack: Vec::new(),
+            pending: HashMap::new(),
+            pending_total: 0,
+            samples: HashMap::new(),
+            last: None,
+            carry: Duration::ZERO,
+            last_flush: Instant::now(),
+        }
+    }
+
+    fn intern(&mut self, label: &str) -> u32 {
+        if let Some(id) = self.label_ids.get(label) {
+            return *id;
+        }
+        let id = self.labels.len() as u32;
+        self.labels.push(label.into());
+        self.label_ids.insert(label.into(), id);
+        id
+    }
+
+    /// Attribute the samples elapsed since the last event to the current stack.
+    fn tick(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last.replace(now) {
+            let elapsed = now - last + self.carry;
+            let samples = (elapsed.as_nanos() / self.interval.as_nanos()) as u64;
+            self.carry =
+                Duration::from_nanos((elapsed.as_nanos() % self.interval.as_nanos()) as u64);
+            if samples > 0 {
+                *self.pending.entry(self.stack.clone()).or_default() += samples;
+                self.pending_total += samples;
+            }
+        }
+    }
+
+    /// Write every finished event's samples to the output file, replacing it.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.last_flush = Instant::now();
+        let mut lines: Vec<(String, u64)> = self
+            .samples
+            .iter()
+            .map(|(stack, count)| {
+                let frames: Vec<&str> =
+                    stack.iter().map(|id| &*self.labels[*id as usize]).collect();
+                (frames.join(";"), *count)
+            })
+            .collect();
+        lines.sort_unstable();
+
+        let temp = self.output.with_extension("tmp");
+        let mut out = BufWriter::new(File::create(&temp)?);
+        for (stack, count) in lines {
+            writeln!(out, "{stack} {count}")?;
+        }
+        out.flush()?;
+        drop(out);
+        std::fs::rename(&temp, &self.output)
+    }
+}
+
+/// Encode a cold-state path into `out` so that two paths encode alike only if they are equal.
+///
+/// Each element is written as a tag and its atoms length-prefixed, without allocating on the
+/// NockStack, so it can key the label cache on every traced arm.
+fn encode_path(path: Noun, out: &mut Vec<u8>) {
+    fn atom(a: Atom, out: &mut Vec<u8>) {
+        let bytes = &a.as_ne_bytes()[..met3_usize(a)];
+        out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        out.extend_from_slice(bytes);
+    }
+
+    out.clear();
+    let mut cursor = path;
+    while let Ok(c) = cursor.as_cell() {
+        match c.head().as_either_atom_cell() {
+            Left(a) => {
+                out.push(0);
+                atom(a, out);
+            }
+            Right(ch) => match (ch.head().as_atom(), ch.tail().as_atom()) {
+                (Ok(nm), Ok(kv)) => {
+                    out.push(1);
+                    atom(nm, out);
+                    atom(kv, out);
+                }
+                // path_to_cord renders nothing for these, so they all share a label
+                _ => out.push(2),
+            },
+        }
+        cursor = c.tail();
+    }
+}
+
+impl Drop for ProfileBackend {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!(
+                "nock profiler: failed to write {}: {}",
+                self.output.display(),
+                e
+            );
+        }
+    }
+}
+
+impl TraceBackend for ProfileBackend {
+    fn append_trace(&mut self, stack: &mut NockStack, path: Noun) {
+        self.tick();
+        encode_path(path, &mut self.path_key);
+        let id = match self.path_ids.get(self.path_key.as_slice()) {
+            Some(id) => *id,
+            None => {
+                let cord = path_to_cord(stack, path);
+                let label = std::str::from_utf8(cord.as_ne_bytes())
+                    .unwrap_or("?")
+                    .trim_end_matches('\0')
+                    .to_owned();
+                let id = self.intern(&label);
+                self.path_ids.insert(self.path_key.as_slice().into(), id);
+                id
+            }
+        };
+
+        TraceStack::push_on_stack(
+            stack,
+            ProfileFrame {
+                depth: self.stack.len(),
+            },
+        );
+        self.stack.push(id);
+    }
+
+    unsafe fn write_nock_trace(
+        &mut self,
+        _: &mut NockStack,
+        trace_stack: *const TraceStack,
+    ) -> Result<(), Error> {
+        let mut trace_stack = trace_stack as *const TraceStack<ProfileFrame>;
+        if trace_stack.is_null() {
+            return Ok(());
+        }
+        self.tick();
+
+        // The frame's entries are chained newest first; the oldest records how deep the stack
+        // was when the frame began. Truncating to it also drops entries left behind by inner
+        // frames that bailed out instead of returning.
+        while !(*trace_stack).next.is_null() {
+            trace_stack = (*trace_stack).next;
+        }
+        self.stack.truncate((*trace_stack).data.depth);
+        Ok(())
+    }
+
+    fn write_serf_trace(&mut self, name: &str, start: Instant) -> Result<(), Error> {
+        self.tick();
+        // Time in the event before its first traced arm counts towards the event itself.
+        let total = (start.elapsed().as_nanos() / self.interval.as_nanos()) as u64;
+        let untraced = total.saturating_sub(self.pending_total);
+        if untraced > 0 {
+            *self.pending.entry(Vec::new()).or_default() += untraced;
+        }
+
+        let event = self.intern(name);
+        for (stack, count) in std::mem::take(&mut self.pending) {
+            let mut key = Vec::with_capacity(stack.len() + 1);
+            key.push(event);
+            key.extend(stack);
+            *self.samples.entry(key).or_default() += count;
+        }
+        self.pending_total = 0;
+        self.stack.clear();
+        self.last = None;
+        self.carry = Duration::ZERO;
+
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::interpret;
+    use crate::jets::util::test::init_context;
+    use crate::noun::{D, T};
+
+    /// A formula that builds a core registered as `/loop/root` and runs its arm, which counts up
+    /// to `n` with Nock 2 so the count itself isn't traced.
+    fn traced_loop(stack: &mut NockStack, n: u64) -> Noun {
+        let axis = |stack: &mut NockStack, a: u64| T(stack, &[D(0), D(a)]);
+        let constant = |stack: &mut NockStack, c: Noun| T(stack, &[D(1), c]);
+        let fast = |stack: &mut NockStack, name: u64, parent: Noun| {
+            let clue = T(stack, &[D(name), parent, D(0)]);
+            T(stack, &[D(tas!(b"fast")), D(1), clue])
+        };
+
+        // [11 [%fast 1 %root [1 0] 0] [1 7 0]]
+        let root_parent = constant(stack, D(0));
+        let root_hint = fast(stack, tas!(b"root"), root_parent);
+        let root = T(stack, &[D(11), root_hint, D(1), D(7), D(0)]);
+
+        // [6 [5 [0 6] [0 7]] [0 7] [2 [[0 2] [0 6] [4 0 7]] [0 2]]] against [count n i]
+        let (a2, a6, a7) = (axis(stack, 2), axis(stack, 6), axis(stack, 7));
+        let inc = T(stack, &[D(4), a7]);
+        let next = T(stack, &[a2, a6, inc]);
+        let recur = T(stack, &[D(2), next, a2]);
+        let done = T(stack, &[D(5), a6, a7]);
+        let count = T(stack, &[D(6), done, a7, recur]);
+
+        // [2 [[1 count] [0 6] [1 0]] [1 count]] against the core [battery n root]
+        let count_const = constant(stack, count);
+        let zero = constant(stack, D(0));
+        let start = T(stack, &[count_const, a6, zero]);
+        let battery = T(stack, &[D(2), start, count_const]);
+
+        // [9 2 [11 [%fast 1 %loop [0 7] 0] [1 battery] [1 n] root]]
+        let core_hint = fast(stack, tas!(b"loop"), a7);
+        let battery_const = constant(stack, battery);
+        let n_const = constant(stack, D(n));
+        let core = T(stack, &[D(11), core_hint, battery_const, n_const, root]);
+        T(stack, &[D(9), D(2), core])
+    }
+
+    #[test]
+    fn profiles_fast_hinted_arm() {
+        let output =
+            std::env::temp_dir().join(format!("nockvm-profile-{}.folded", std::process::id()));
+        let interval = Duration::from_micros(1);
+
+        let mut context = init_context();
+        context.trace_info = Some(TraceInfo {
+            backend: Box::new(ProfileBackend::new(&output, interval)),
+            filter: None,
+        });
+        let formula = traced_loop(&mut context.stack, 20_000);
+
+        let start = Instant::now();
+        let res = interpret(&mut context, D(0), formula).expect("loop should succeed");
+        assert!(unsafe { res.raw_equals(&D(20_000)) });
+        let info = context.trace_info.as_mut().expect("trace info is set");
+        write_serf_trace(info, "poke", start).expect("event should be recorded");
+        let elapsed = start.elapsed();
+        // Dropping the backend writes the folded stacks.
+        context.trace_info = None;
+
+        let folded = std::fs::read_to_string(&output).expect("profile should be written");
+        std::fs::remove_file(&output).ok();
+        let lines: HashMap<&str, u64> = folded
+            .lines()
+            .map(|line| {
+                let (stack, count) = line.rsplit_once(' ').expect("line should have a count");
+                (stack, count.parse().expect("count should be a number"))
+            })
+            .collect();
+
+        assert!(
+            lines
+                .keys()
+                .all(|stack| *stack == "poke" || *stack == "poke;/loop/root"),
+            "unexpected stacks: {folded}"
+        );
+        assert!(lines.get("poke;/loop/root").copied().unwrap_or(0) > 0);
+        let total: u64 = lines.values().sum();
+        assert!(total as u128 <= elapsed.as_nanos() / interval.as_nanos());
+    }
+}
@@ -0,0 +1,322 @@
+//! Benchmarks for the zkvm jets: field arithmetic, NTTs across sizes, Tip5 hashing, and end-to-end
+//! proof verification.
+//!
+//! The verification benchmark needs the proof library from `make assets/verify-bench.jam`, passed
+//! in `ZKVM_BENCH_VERIFY_JAM`; without it only the micro-benchmarks run. `make bench-jets` runs the
+//! suite, `make bench-jets-baseline` records the results as the stored baseline, and
+//! `make bench-jets-check` fails if a benchmark has slowed down against it.
+
+use std::sync::atomic::AtomicIsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use nockvm::ext::NounExt;
+use nockvm::hamt::Hamt;
+use nockvm::interpreter::{interpret, Context, NockCancelToken, Slogger};
+use nockvm::jets::cold::Cold;
+use nockvm::jets::hot::{Hot, HotEntry, URBIT_HOT_STATE};
+use nockvm::jets::util::slam;
+use nockvm::jets::warm::Warm;
+use nockvm::jets::Jet;
+use nockvm::mem::NockStack;
+use nockvm::noun::{Atom, Noun, D, T, YES};
+use nockvm::unifying_equality::unifying_equality;
+use quickcheck::{Arbitrary, Gen};
+use zkvm_jetpack::form::belt::{bpow, Belt};
+use zkvm_jetpack::form::felt::{finv_, fmul_, Felt};
+use zkvm_jetpack::form::handle::{finalize_poly, new_handle_mut_slice};
+use zkvm_jetpack::form::poly::Element;
+use zkvm_jetpack::form::tip5;
+use zkvm_jetpack::hot::produce_prover_hot_state;
+use zkvm_jetpack::jets::base_jets::bmul_jet;
+use zkvm_jetpack::jets::bp_jets::bp_ntt_jet;
+use zkvm_jetpack::jets::fext_jets::{finv_jet, fmul_jet};
+use zkvm_jetpack::jets::fpntt_jets::{felt_as_noun, fp_ntt_jet};
+use zkvm_jetpack::jets::tip5_jets::hash_varlen_jet;
+use zkvm_jetpack::utils::vec_to_hoon_list;
+
+const VERIFY_JAM_ENV: &str = "ZKVM_BENCH_VERIFY_JAM";
+
+const BP_NTT_LOG_SIZES: &[u32] = &[10, 12, 14, 16];
+const FP_NTT_LOG_SIZES: &[u32] = &[10, 12, 14];
+const HASH_LENS: &[usize] = &[10, 100, 1000];
+
+struct BenchSlogger;
+
+impl Slogger for BenchSlogger {
+    fn flog(&mut self, _stack: &mut NockStack, _cord: Noun) {}
+
+    fn slog(&mut self, _stack: &mut NockStack, _pri: u64, _noun: Noun) {}
+}
+
+fn bench_context_with(stack_words: usize, hot_state: &[HotEntry]) -> Context {
+    let mut stack = NockStack::new(stack_words, 0);
+    let cold = Cold::new(&mut stack);
+    let warm = Warm::new(&mut stack);
+    let hot = Hot::init(&mut stack, hot_state);
+    let cache = Hamt::<Noun>::new(&mut stack);
+    let slogger = std::boxed::Box::pin(BenchSlogger);
+    let cancel = Arc::new(AtomicIsize::new(NockCancelToken::RUNNING_IDLE));
+    let test_jets = Hamt::<()>::new(&mut stack);
+
+    Context {
+        stack,
+        slogger,
+        cold,
+        warm,
+        hot,
+        cache,
+        scry_stack: D(0),
+        trace_info: None,
+        running_status: cancel,
+        test_jets,
+        meter: None,
+    }
+}
+
+fn bench_context() -> Context {
+    bench_context_with(8 << 20, URBIT_HOT_STATE)
+}
+
+/// Random field elements from a fixed seed size, so every run sees inputs of the same shape.
+fn belts(len: usize) -> Vec<Belt> {
+    let mut gen = Gen::new(64);
+    (0..len).map(|_| Belt::arbitrary(&mut gen)).collect()
+}
+
+fn felts(len: usize) -> Vec<Felt> {
+    belts(3 * len)
+        .chunks_exact(3)
+        .map(|chunk| Felt([chunk[0], chunk[1], chunk[2]]))
+        .collect()
+}
+
+fn poly_noun<T: Element + Copy>(stack: &mut NockStack, elements: &[T]) -> Noun {
+    let (atom, slice): (_, &mut [T]) = new_handle_mut_slice(stack, Some(elements.len()));
+    slice.copy_from_slice(elements);
+    finalize_poly(stack, Some(elements.len()), atom)
+}
+
+/// A context and a gate subject `[battery sample payload]` for calling a jet directly.
+fn jet_input(sample: impl FnOnce(&mut Context) -> Noun) -> (Context, Noun) {
+    let mut context = bench_context();
+    let sample = sample(&mut context);
+    let subject = T(&mut context.stack, &[D(0), sample, D(0)]);
+    (context, subject)
+}
+
+fn run_jet(jet: Jet, (mut context, subject): (Context, Noun)) {
+    black_box(jet(&mut context, subject).expect("jet should succeed"));
+}
+
+fn bench_field(c: &mut Criterion) {
+    let mut group = c.benchmark_group("field");
+    let (a, b) = match belts(2)[..] {
+        [a, b] => (a, b),
+        _ => unreachable!(),
+    };
+    group.bench_function("bmul", |bench| bench.iter(|| black_box(a) * black_box(b)));
+    group.bench_function("binv", |bench| bench.iter(|| black_box(a).inv()));
+    group.bench_function("bpow", |bench| {
+        bench.iter(|| bpow(black_box(a.0), black_box(u64::MAX)))
+    });
+    let (x, y) = match felts(2)[..] {
+        [x, y] => (x, y),
+        _ => unreachable!(),
+    };
+    group.bench_function("fmul", |bench| {
+        bench.iter(|| fmul_(black_box(&x), black_box(&y)))
+    });
+    group.bench_function("finv", |bench| bench.iter(|| finv_(black_box(&x))));
+
+    group.bench_function("bmul_jet", |bench| {
+        bench.iter_batched(
+            || {
+                jet_input(|context| {
+                    let a = Atom::new(&mut context.stack, a.0).as_noun();
+                    let b = Atom::new(&mut context.stack, b.0).as_noun();
+                    T(&mut context.stack, &[a, b])
+                })
+            },
+            |input| run_jet(bmul_jet, input),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("fmul_jet", |bench| {
+        bench.iter_batched(
+            || {
+                jet_input(|context| {
+                    let x = felt_as_noun(context, x).expect("felt noun");
+                    let y = felt_as_noun(context, y).expect("felt noun");
+                    T(&mut context.stack, &[x, y])
+                })
+            },
+            |input| run_jet(fmul_jet, input),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("finv_jet", |bench| {
+        bench.iter_batched(
+            || jet_input(|context| felt_as_noun(context, x).expect("felt noun")),
+            |input| run_jet(finv_jet, input),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_ntt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ntt");
+    for &log in BP_NTT_LOG_SIZES {
+        let n = 1usize << log;
+        let coeffs = belts(n);
+        let root = Belt(n as u64).ordered_root().expect("ordered root");
+        group.bench_with_input(BenchmarkId::new("bp_ntt", n), &coeffs, |bench, coeffs| {
+            bench.iter_batched(
+                || {
+                    jet_input(|context| {
+                        let bp = poly_noun(&mut context.stack, coeffs);
+                        let root = Atom::new(&mut context.stack, root.0).as_noun();
+                        T(&mut context.stack, &[bp, root])
+                    })
+                },
+                |input| run_jet(bp_ntt_jet, input),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    for &log in FP_NTT_LOG_SIZES {
+        let n = 1usize << log;
+        let coeffs = felts(n);
+        let root = Felt::ordered_root(n as u64).expect("ordered root");
+        group.bench_with_input(BenchmarkId::new("fp_ntt", n), &coeffs, |bench, coeffs| {
+            bench.iter_batched(
+                || {
+                    jet_input(|context| {
+                        let fp = poly_noun(&mut context.stack, coeffs);
+                        let root = felt_as_noun(context, root).expect("felt noun");
+                        T(&mut context.stack, &[fp, root])
+                    })
+                },
+                |input| run_jet(fp_ntt_jet, input),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash");
+    group.bench_function("permute", |bench| {
+        let mut sponge = [0u64; 16];
+        for (slot, belt) in sponge.iter_mut().zip(belts(16)) {
+            *slot = belt.0;
+        }
+        bench.iter(|| tip5::permute(black_box(&mut sponge)))
+    });
+    group.bench_function("hash_10", |bench| {
+        let input = belts(10);
+        bench.iter(|| tip5::hash::hash_10(&mut black_box(input.clone())))
+    });
+    for &len in HASH_LENS {
+        let input = belts(len);
+        group.bench_with_input(
+            BenchmarkId::new("hash_varlen", len),
+            &input,
+            |bench, input| bench.iter(|| tip5::hash::hash_varlen(&mut black_box(input.clone()))),
+        );
+        let raw: Vec<u64> = input.iter().map(|belt| belt.0).collect();
+        group.bench_with_input(
+            BenchmarkId::new("hash_varlen_jet", len),
+            &raw,
+            |bench, raw| {
+                bench.iter_batched(
+                    || jet_input(|context| vec_to_hoon_list(&mut context.stack, raw)),
+                    |input| run_jet(hash_varlen_jet, input),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+/// The booted verifier gate and the proof to verify, living on `context`'s stack.
+struct VerifyBench {
+    context: Context,
+    gate: Noun,
+    sample: Noun,
+}
+
+impl VerifyBench {
+    fn boot(jam: &[u8]) -> Self {
+        let hot_state = produce_prover_hot_state();
+        let mut context = bench_context_with(1 << 30, &[URBIT_HOT_STATE, &hot_state].concat());
+        let trap =
+            Noun::cue_bytes_slice(&mut context.stack, jam).expect("verify-bench jam should cue");
+        let kick = T(&mut context.stack, &[D(9), D(2), D(0), D(1)]);
+        let booted = interpret(&mut context, trap, kick).expect("verify-bench should boot");
+        let booted = booted
+            .as_cell()
+            .expect("verify-bench is a [gate sample] cell");
+        Self {
+            context,
+            gate: booted.head(),
+            sample: booted.tail(),
+        }
+    }
+
+    fn verify(&mut self) {
+        let mut res = slam(&mut self.context, self.gate, self.sample).expect("verify should run");
+        let mut yes = YES;
+        assert!(
+            unsafe { unifying_equality(&mut self.context.stack, &mut res, &mut yes) },
+            "proof should verify"
+        );
+    }
+
+    /// Drop everything the last verification allocated, keeping the gate, proof and jet state.
+    fn reset(&mut self) {
+        let stack = &mut self.context.stack;
+        self.context.cache = Hamt::new(stack);
+        unsafe {
+            stack.preserve(&mut self.context.warm);
+            stack.preserve(&mut self.context.test_jets);
+            stack.preserve(&mut self.context.hot);
+            stack.preserve(&mut self.context.cache);
+            stack.preserve(&mut self.context.cold);
+            stack.preserve(&mut self.gate);
+            stack.preserve(&mut self.sample);
+            stack.flip_top_frame(0);
+        }
+    }
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let Ok(path) = std::env::var(VERIFY_JAM_ENV) else {
+        eprintln!("{VERIFY_JAM_ENV} not set, skipping the proof verification benchmark");
+        return;
+    };
+    let jam = std::fs::read(&path).unwrap_or_else(|err| panic!("reading {path}: {err}"));
+    let mut bench = VerifyBench::boot(&jam);
+    let mut group = c.benchmark_group("verify");
+    group.sample_size(10);
+    group.bench_function("proof", |b| {
+        b.iter_custom(|iters| {
+            let mut total = Duration::ZERO;
+            for _ in 0..iters {
+                let start = Instant::now();
+                bench.verify();
+                total += start.elapsed();
+                bench.reset();
+            }
+            total
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(jets, bench_field, bench_ntt, bench_hash, bench_verify);
+criterion_main!(jets);
@@ -1,10 +1,11 @@
 use crate::form::belt::*;
-use crate::form::bpoly::{bp_fft, bpoly_zero_extend};
+use crate::form::bpoly::{bp_ntt_with_twiddles, bpoly_zero_extend};
 use crate::form::felt::{fpow, Felt};
 use crate::form::fpoly::*;
 use crate::form::mary::{snag_as_bpoly, MarySlice};
 use crate::form::poly::*;
 use crate::form::structs::HoonList;
+use crate::twiddles::bp_twiddles;
 
 pub fn precompute_ntts(
     polys: MarySlice,
@@ -12,13 +13,18 @@ pub fn precompute_ntts(
     max_ntt_len: usize,
     res: &mut [Belt],
 ) -> Result<(), FieldError> {
+    if polys.len == 0 {
+        return Ok(());
+    }
     let new_len = height * max_ntt_len;
+    let root = Belt(new_len as u64).ordered_root()?;
+    let twiddles = bp_twiddles(new_len, &root);
 
     for i in 0..polys.len as usize {
         let bp = snag_as_bpoly(polys, i);
         let mut extended = vec![Belt::zero(); new_len];
         bpoly_zero_extend(bp, &mut extended);
-        let fft = bp_fft(&extended)?;
+        let fft = bp_ntt_with_twiddles(&extended, &twiddles);
         res[i * new_len..(i + 1) * new_len].copy_from_slice(&fft);
     }
     Ok(())
@@ -0,0 +1,488 @@
+//! Differential fuzzing of jets against the Hoon they replace.
+//!
+//! [`JetFuzzer`] boots the gate library in `hoon/apps/jet-fuzz/jet-fuzz.hoon`, compiled with
+//! `make assets/jet-fuzz.jam`, and for each [`FuzzTarget`] feeds the same random samples to the
+//! Rust jet and to the gate's own formula. Only the standard-library jets are installed in the
+//! fuzzer's context, so the Nock side never reaches back into the jets under test. A jet and its
+//! Hoon agree when they return equal nouns or both crash; a punt is not a disagreement, since the
+//! runtime would fall back to the Nock anyway.
+//!
+//! The `jet_fuzz` integration test drives every target; see the `fuzz-jets` make target.
+
+use std::pin::Pin;
+use std::sync::atomic::AtomicIsize;
+use std::sync::Arc;
+
+use nockvm::ext::NounExt;
+use nockvm::hamt::Hamt;
+use nockvm::interpreter::{interpret, Context, Error as NockError, NockCancelToken, Slogger};
+use nockvm::jets::cold::Cold;
+use nockvm::jets::hot::{Hot, URBIT_HOT_STATE};
+use nockvm::jets::warm::Warm;
+use nockvm::jets::{Jet, JetErr};
+use nockvm::mem::NockStack;
+use nockvm::noun::{Atom, Noun, D, T};
+use nockvm::unifying_equality::unifying_equality;
+use quickcheck::{Arbitrary, Gen};
+use thiserror::Error;
+
+use crate::form::belt::{Belt, PRIME};
+use crate::form::felt::Felt;
+use crate::form::handle::{finalize_poly, new_handle_mut_slice};
+use crate::jets::base_jets::*;
+use crate::jets::bp_jets::*;
+use crate::jets::fext_jets::*;
+use crate::jets::fpntt_jets::felt_as_noun;
+use crate::jets::shape_jets::*;
+use crate::jets::tip5_jets::*;
+use crate::utils::vec_to_hoon_list;
+
+pub const FUZZ_JAM_ENV: &str = "ZKVM_JET_FUZZ_JAM";
+
+/// Words of NockStack per fuzzer. Nothing is freed between cases, so this bounds how many cases a
+/// single [`JetFuzzer`] can run.
+pub const FUZZ_STACK_WORDS: usize = 1 << 29;
+
+/// Rendered nouns in reports are cut off after this many characters.
+const MAX_RENDERED_LEN: usize = 2048;
+
+#[derive(Debug, Error)]
+pub enum FuzzError {
+    #[error("jet-fuzz jam is not a valid jam")]
+    Cue,
+    #[error("jet-fuzz library failed to boot: {0}")]
+    Boot(String),
+    #[error("jet-fuzz library has no gate named %{0}")]
+    MissingGate(&'static str),
+    #[error("%{target} hit a nondeterministic error: {error}")]
+    Nondeterministic { target: &'static str, error: String },
+    #[error(
+        "%{target} disagrees with its Hoon on sample {sample}: jet gave {jet}, Nock gave {nock}"
+    )]
+    Mismatch {
+        target: &'static str,
+        sample: String,
+        jet: String,
+        nock: String,
+    },
+}
+
+/// A jetted gate, the jet that replaces it, and how to make samples for it.
+pub struct FuzzTarget {
+    /// The gate's name in `jet-fuzz.hoon`.
+    pub name: &'static str,
+    pub jet: Jet,
+    pub sample: fn(&mut Gen, &mut Context) -> Noun,
+}
+
+/// How the cases for one target turned out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FuzzReport {
+    /// Jet and Nock returned the same noun.
+    pub agreed: usize,
+    /// Jet and Nock both crashed.
+    pub crashed: usize,
+    /// The jet punted, so there was nothing to compare.
+    pub punted: usize,
+}
+
+struct QuietSlogger;
+
+impl Slogger for QuietSlogger {
+    fn slog(&mut self, _stack: &mut NockStack, _pri: u64, _tank: Noun) {}
+    fn flog(&mut self, _stack: &mut NockStack, _cord: Noun) {}
+}
+
+fn fuzz_context(stack_words: usize) -> Context {
+    let mut stack = NockStack::new(stack_words, 0);
+    let cold = Cold::new(&mut stack);
+    let warm = Warm::new(&mut stack);
+    let hot = Hot::init(&mut stack, URBIT_HOT_STATE);
+    let cache = Hamt::<Noun>::new(&mut stack);
+    let test_jets = Hamt::<()>::new(&mut stack);
+    Context {
+        stack,
+        slogger: Pin::new(Box::new(QuietSlogger)),
+        cold,
+        warm,
+        hot,
+        cache,
+        scry_stack: D(0),
+        trace_info: None,
+        running_status: Arc::new(AtomicIsize::new(NockCancelToken::RUNNING_IDLE)),
+        test_jets,
+        meter: None,
+    }
+}
+
+fn render(noun: Noun) -> String {
+    let mut rendered = format!("{noun:?}");
+    if rendered.len() > MAX_RENDERED_LEN {
+        let mut end = MAX_RENDERED_LEN;
+        while !rendered.is_char_boundary(end) {
+            end -= 1;
+        }
+        rendered.truncate(end);
+        rendered.push_str("...");
+    }
+    rendered
+}
+
+pub struct JetFuzzer {
+    context: Context,
+    /// `(list [@tas *])` of named gates from `jet-fuzz.hoon`.
+    gates: Noun,
+}
+
+impl JetFuzzer {
+    /// Boot the jammed gate library with a [`FUZZ_STACK_WORDS`] stack.
+    pub fn boot(jam: &[u8]) -> Result<Self, FuzzError> {
+        Self::boot_with_stack(jam, FUZZ_STACK_WORDS)
+    }
+
+    pub fn boot_with_stack(jam: &[u8], stack_words: usize) -> Result<Self, FuzzError> {
+        let mut context = fuzz_context(stack_words);
+        let trap = Noun::cue_bytes_slice(&mut context.stack, jam).map_err(|_| FuzzError::Cue)?;
+        let kick = T(&mut context.stack, &[D(9), D(2), D(0), D(1)]);
+        let gates =
+            interpret(&mut context, trap, kick).map_err(|err| FuzzError::Boot(err.to_string()))?;
+        Ok(Self { context, gates })
+    }
+
+    fn gate(&self, name: &'static str) -> Result<Noun, FuzzError> {
+        self.gates
+            .list_iter()
+            .filter_map(|entry| entry.as_cell().ok())
+            .find(|entry| entry.head().eq_bytes(name))
+            .map(|entry| entry.tail())
+            .ok_or(FuzzError::MissingGate(name))
+    }
+
+    /// Run one sample through `target`'s jet and its Hoon.
+    pub fn check(&mut self, target: &FuzzTarget, sample: Noun) -> Result<FuzzReport, FuzzError> {
+        let gate = self
+            .gate(target.name)?
+            .as_cell()
+            .map_err(|_| FuzzError::MissingGate(target.name))?;
+        let payload = gate
+            .tail()
+            .as_cell()
+            .map_err(|_| FuzzError::MissingGate(target.name))?;
+        let core = T(
+            &mut self.context.stack,
+            &[gate.head(), sample, payload.tail()],
+        );
+
+        let nondeterministic = |error: String| FuzzError::Nondeterministic {
+            target: target.name,
+            error,
+        };
+        let jet = match (target.jet)(&mut self.context, core) {
+            Err(JetErr::Punt) => {
+                return Ok(FuzzReport {
+                    punted: 1,
+                    ..Default::default()
+                })
+            }
+            Err(JetErr::Fail(err @ NockError::NonDeterministic(..))) => {
+                return Err(nondeterministic(err.to_string()))
+            }
+            Ok(res) => Some(res),
+            Err(JetErr::Fail(_)) => None,
+        };
+        let nock = match interpret(&mut self.context, core, gate.head()) {
+            Err(err @ NockError::NonDeterministic(..)) => {
+                return Err(nondeterministic(err.to_string()))
+            }
+            Ok(res) => Some(res),
+            Err(_) => None,
+        };
+
+        match (jet, nock) {
+            (Some(mut jet), Some(mut nock)) => {
+                if unsafe { unifying_equality(&mut self.context.stack, &mut jet, &mut nock) } {
+                    Ok(FuzzReport {
+                        agreed: 1,
+                        ..Default::default()
+                    })
+                } else {
+                    Err(FuzzError::Mismatch {
+                        target: target.name,
+                        sample: render(sample),
+                        jet: render(jet),
+                        nock: render(nock),
+                    })
+                }
+            }
+            (None, None) => Ok(FuzzReport {
+                crashed: 1,
+                ..Default::default()
+            }),
+            (jet, nock) => Err(FuzzError::Mismatch {
+                target: target.name,
+                sample: render(sample),
+                jet: jet.map_or_else(|| "a crash".to_string(), render),
+                nock: nock.map_or_else(|| "a crash".to_string(), render),
+            }),
+        }
+    }
+
+    /// Run `cases` random samples through `target`, stopping at the first disagreement.
+    pub fn run(
+        &mut self,
+        target: &FuzzTarget,
+        gen: &mut Gen,
+        cases: usize,
+    ) -> Result<FuzzReport, FuzzError> {
+        let mut report = FuzzReport::default();
+        for _ in 0..cases {
+            let sample = (target.sample)(gen, &mut self.context);
+            let case = self.check(target, sample)?;
+            report.agreed += case.agreed;
+            report.crashed += case.crashed;
+            report.punted += case.punted;
+        }
+        Ok(report)
+    }
+}
+
+/// A belt, or every so often one of the values at the edges of (and just past) the field.
+fn belt(g: &mut Gen) -> u64 {
+    if u8::arbitrary(g) % 16 == 0 {
+        *g.choose(&[0, 1, PRIME - 1, PRIME, u64::MAX])
+            .expect("nonempty")
+    } else {
+        Belt::arbitrary(g).0
+    }
+}
+
+fn belt_noun(g: &mut Gen, context: &mut Context) -> Noun {
+    let belt = belt(g);
+    Atom::new(&mut context.stack, belt).as_noun()
+}
+
+fn felt_noun(g: &mut Gen, context: &mut Context) -> Noun {
+    let felt = Felt([Belt(belt(g)), Belt(belt(g)), Belt(belt(g))]);
+    felt_as_noun(context, felt).expect("felt noun")
+}
+
+fn bpoly_noun(context: &mut Context, belts: &[u64]) -> Noun {
+    let (atom, slice): (_, &mut [Belt]) =
+        new_handle_mut_slice(&mut context.stack, Some(belts.len()));
+    for (dst, src) in slice.iter_mut().zip(belts) {
+        *dst = Belt(*src);
+    }
+    finalize_poly(&mut context.stack, Some(belts.len()), atom)
+}
+
+fn bpoly_len(g: &mut Gen) -> usize {
+    1 + usize::arbitrary(g) % g.size().max(1)
+}
+
+fn random_bpoly(g: &mut Gen, context: &mut Context, len: usize) -> Noun {
+    let belts: Vec<u64> = (0..len).map(|_| belt(g)).collect();
+    bpoly_noun(context, &belts)
+}
+
+fn small_noun(g: &mut Gen, context: &mut Context, depth: usize) -> Noun {
+    if depth == 0 || bool::arbitrary(g) {
+        return D(u64::arbitrary(g) % 256);
+    }
+    let head = small_noun(g, context, depth - 1);
+    let tail = small_noun(g, context, depth - 1);
+    T(&mut context.stack, &[head, tail])
+}
+
+fn two_belts(g: &mut Gen, context: &mut Context) -> Noun {
+    let a = belt_noun(g, context);
+    let b = belt_noun(g, context);
+    T(&mut context.stack, &[a, b])
+}
+
+fn belt_and_exponent(g: &mut Gen, context: &mut Context) -> Noun {
+    let x = belt_noun(g, context);
+    let n = Atom::new(&mut context.stack, u64::arbitrary(g)).as_noun();
+    T(&mut context.stack, &[x, n])
+}
+
+fn any_u64(g: &mut Gen, context: &mut Context) -> Noun {
+    let a = if bool::arbitrary(g) {
+        belt(g)
+    } else {
+        u64::arbitrary(g)
+    };
+    Atom::new(&mut context.stack, a).as_noun()
+}
+
+fn two_felts(g: &mut Gen, context: &mut Context) -> Noun {
+    let a = felt_noun(g, context);
+    let b = felt_noun(g, context);
+    T(&mut context.stack, &[a, b])
+}
+
+fn one_bpoly(g: &mut Gen, context: &mut Context) -> Noun {
+    let len = bpoly_len(g);
+    random_bpoly(g, context, len)
+}
+
+fn two_bpolys(g: &mut Gen, context: &mut Context) -> Noun {
+    let p = one_bpoly(g, context);
+    let q = one_bpoly(g, context);
+    T(&mut context.stack, &[p, q])
+}
+
+fn two_bpolys_same_len(g: &mut Gen, context: &mut Context) -> Noun {
+    let len = bpoly_len(g);
+    let p = random_bpoly(g, context, len);
+    let q = random_bpoly(g, context, len);
+    T(&mut context.stack, &[p, q])
+}
+
+fn belt_and_bpoly(g: &mut Gen, context: &mut Context) -> Noun {
+    let c = belt_noun(g, context);
+    let bp = one_bpoly(g, context);
+    T(&mut context.stack, &[c, bp])
+}
+
+fn bpoly_and_belt(g: &mut Gen, context: &mut Context) -> Noun {
+    let bp = one_bpoly(g, context);
+    let x = belt_noun(g, context);
+    T(&mut context.stack, &[bp, x])
+}
+
+fn bpoly_and_root(g: &mut Gen, context: &mut Context) -> Noun {
+    let len = 1usize << (usize::arbitrary(g) % 7);
+    let bp = random_bpoly(g, context, len);
+    let root = Belt(len as u64).ordered_root().expect("ordered root").0;
+    let root = Atom::new(&mut context.stack, root).as_noun();
+    T(&mut context.stack, &[bp, root])
+}
+
+fn belt_list(g: &mut Gen, context: &mut Context) -> Noun {
+    let len = usize::arbitrary(g) % (g.size() + 1);
+    let belts: Vec<u64> = (0..len).map(|_| belt(g)).collect();
+    vec_to_hoon_list(&mut context.stack, &belts)
+}
+
+fn any_noun(g: &mut Gen, context: &mut Context) -> Noun {
+    small_noun(g, context, 6)
+}
+
+/// Every target the `jet_fuzz` test runs.
+pub const FUZZ_TARGETS: &[FuzzTarget] = &[
+    FuzzTarget {
+        name: "badd",
+        jet: badd_jet,
+        sample: two_belts,
+    },
+    FuzzTarget {
+        name: "bsub",
+        jet: bsub_jet,
+        sample: two_belts,
+    },
+    FuzzTarget {
+        name: "bmul",
+        jet: bmul_jet,
+        sample: two_belts,
+    },
+    FuzzTarget {
+        name: "bneg",
+        jet: bneg_jet,
+        sample: belt_noun,
+    },
+    FuzzTarget {
+        name: "bpow",
+        jet: bpow_jet,
+        sample: belt_and_exponent,
+    },
+    FuzzTarget {
+        name: "based",
+        jet: based_jet,
+        sample: any_u64,
+    },
+    FuzzTarget {
+        name: "fadd",
+        jet: fadd_jet,
+        sample: two_felts,
+    },
+    FuzzTarget {
+        name: "fsub",
+        jet: fsub_jet,
+        sample: two_felts,
+    },
+    FuzzTarget {
+        name: "fneg",
+        jet: fneg_jet,
+        sample: felt_noun,
+    },
+    FuzzTarget {
+        name: "fmul",
+        jet: fmul_jet,
+        sample: two_felts,
+    },
+    FuzzTarget {
+        name: "finv",
+        jet: finv_jet,
+        sample: felt_noun,
+    },
+    FuzzTarget {
+        name: "fdiv",
+        jet: fdiv_jet,
+        sample: two_felts,
+    },
+    FuzzTarget {
+        name: "bpadd",
+        jet: bpadd_jet,
+        sample: two_bpolys,
+    },
+    FuzzTarget {
+        name: "bpsub",
+        jet: bpsub_jet,
+        sample: two_bpolys,
+    },
+    FuzzTarget {
+        name: "bpneg",
+        jet: bpneg_jet,
+        sample: one_bpoly,
+    },
+    FuzzTarget {
+        name: "bpscal",
+        jet: bpscal_jet,
+        sample: belt_and_bpoly,
+    },
+    FuzzTarget {
+        name: "bpmul",
+        jet: bpmul_jet,
+        sample: two_bpolys,
+    },
+    FuzzTarget {
+        name: "bp-hadamard",
+        jet: bp_hadamard_jet,
+        sample: two_bpolys_same_len,
+    },
+    FuzzTarget {
+        name: "bpeval",
+        jet: bpeval_jet,
+        sample: bpoly_and_belt,
+    },
+    FuzzTarget {
+        name: "bp-ntt",
+        jet: bp_ntt_jet,
+        sample: bpoly_and_root,
+    },
+    FuzzTarget {
+        name: "hash-varlen",
+        jet: hash_varlen_jet,
+        sample: belt_list,
+    },
+    FuzzTarget {
+        name: "leaf-sequence",
+        jet: leaf_sequence_jet,
+        sample: any_noun,
+    },
+    FuzzTarget {
+        name: "dyck",
+        jet: dyck_jet,
+        sample: any_noun,
+    },
+];
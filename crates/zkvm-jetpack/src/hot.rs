@@ -18,14 +18,28 @@ use crate::jets::tip5_jets::*;
 use crate::jets::tip5_sponge::*;
 use crate::jets::trace_gen_jets::*;
 use crate::jets::verifier_jets::*;
+use crate::{instrument, registry};
 
+/// The built-in jets plus any registered through [`crate::registry`].
 pub fn produce_prover_hot_state() -> Vec<HotEntry> {
+    let mut jets = builtin_hot_state();
+    jets.extend(registry::registered_hot_entries());
+
+    if instrument::enabled() {
+        instrument::instrument(jets)
+    } else {
+        jets
+    }
+}
+
+pub(crate) fn builtin_hot_state() -> Vec<HotEntry> {
     let mut jets: Vec<HotEntry> = Vec::new();
     jets.extend(BASE_FIELD_JETS);
     jets.extend(BASE_POLY_JETS);
     jets.extend(CURVE_JETS);
     jets.extend(ZTD_JETS);
     jets.extend(KEYGEN_JETS);
+    jets.extend(BLAKE3_JETS);
     jets.extend(XTRA_JETS);
     jets.extend(EXTENSION_FIELD_JETS);
     jets.extend(ZKVM_TABLE_JETS_V2);
@@ -154,6 +168,32 @@ pub const XTRA_JETS: &[HotEntry] = &[
         1,
         evaluate_deep_jet,
     ),
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"ext-field"),
+            Left(b"misc-lib"),
+            Left(b"proof-lib"),
+            Left(b"utils"),
+            Left(b"fri"),
+            Left(b"table-lib"),
+            Left(b"stark-core"),
+            Left(b"fock-core"),
+            Left(b"pow"),
+            Left(b"stark-engine"),
+            Left(b"stark-verifier"),
+            Left(b"verify-door"),
+            Left(b"verify-merk-proofs"),
+        ],
+        1,
+        verify_merk_proofs_jet,
+    ),
     (
         &[
             K_138,
@@ -334,6 +374,23 @@ pub const XTRA_JETS: &[HotEntry] = &[
         1,
         bp_build_merk_heap_jet,
     ),
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"ext-field"),
+            Left(b"misc-lib"),
+            Left(b"merkle"),
+            Left(b"verify-merk-batch"),
+        ],
+        1,
+        verify_merk_batch_jet,
+    ),
     (
         &[
             K_138,
@@ -395,6 +452,26 @@ pub const XTRA_JETS: &[HotEntry] = &[
         1,
         compute_deep_jet,
     ),
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"ext-field"),
+            Left(b"misc-lib"),
+            Left(b"proof-lib"),
+            Left(b"utils"),
+            Left(b"fri"),
+            Left(b"fri-door"),
+            Left(b"check-folds"),
+        ],
+        1,
+        check_folds_jet,
+    ),
     (
         &[
             K_138,
@@ -910,6 +987,20 @@ pub const BASE_POLY_JETS: &[HotEntry] = &[
         1,
         bpdvr_jet,
     ),
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"bpeval"),
+        ],
+        1,
+        bpeval_jet,
+    ),
 ];
 
 pub const ZTD_JETS: &[HotEntry] = &[
@@ -1286,6 +1377,92 @@ pub const KEYGEN_JETS: &[HotEntry] = &[(
     argon2_jet,
 )];
 
+pub const BLAKE3_JETS: &[HotEntry] = &[
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"ext-field"),
+            Left(b"misc-lib"),
+            Left(b"proof-lib"),
+            Left(b"utils"),
+            Left(b"fri"),
+            Left(b"table-lib"),
+            Left(b"stark-core"),
+            Left(b"fock-core"),
+            Left(b"pow"),
+            Left(b"stark-engine"),
+            Left(b"zose"),
+            Left(b"blake"),
+            Left(b"blake3-impl"),
+            Left(b"blake3"),
+            Left(b"hash"),
+        ],
+        1,
+        blake3_hash_jet,
+    ),
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"ext-field"),
+            Left(b"misc-lib"),
+            Left(b"proof-lib"),
+            Left(b"utils"),
+            Left(b"fri"),
+            Left(b"table-lib"),
+            Left(b"stark-core"),
+            Left(b"fock-core"),
+            Left(b"pow"),
+            Left(b"stark-engine"),
+            Left(b"zose"),
+            Left(b"blake"),
+            Left(b"blake3-impl"),
+            Left(b"blake3"),
+            Left(b"chunk-output"),
+        ],
+        1,
+        blake3_chunk_output_jet,
+    ),
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"ext-field"),
+            Left(b"misc-lib"),
+            Left(b"proof-lib"),
+            Left(b"utils"),
+            Left(b"fri"),
+            Left(b"table-lib"),
+            Left(b"stark-core"),
+            Left(b"fock-core"),
+            Left(b"pow"),
+            Left(b"stark-engine"),
+            Left(b"zose"),
+            Left(b"blake"),
+            Left(b"blake3-impl"),
+            Left(b"compress"),
+        ],
+        1,
+        blake3_compress_jet,
+    ),
+];
+
 pub const CURVE_JETS: &[HotEntry] = &[
     (
         &[
@@ -1325,6 +1502,25 @@ pub const CURVE_JETS: &[HotEntry] = &[
         1,
         batch_verify_affine_jet,
     ),
+    (
+        &[
+            K_138,
+            Left(b"one"),
+            Left(b"two"),
+            Left(b"tri"),
+            Left(b"qua"),
+            Left(b"pen"),
+            Left(b"zeke"),
+            Left(b"ext-field"),
+            Left(b"misc-lib"),
+            Left(b"cheetah"),
+            Left(b"belt-schnorr"),
+            Left(b"affine"),
+            Left(b"batch-verify"),
+        ],
+        1,
+        belt_schnorr_batch_verify_jet,
+    ),
 ];
 
 pub const CUSTOM_LIST_JETS: &[HotEntry] = &[
@@ -0,0 +1,248 @@
+//! Opt-in per-jet call counters and timings.
+//!
+//! Setting `ZKVM_JET_STATS` (to anything but `0`) makes [`crate::hot::produce_prover_hot_state`]
+//! wrap every jet in a trampoline that counts calls, punts back to Nock, and errors, and adds up
+//! the wall time spent inside the jet. [`snapshot`] reads the counters, e.g. for the private gRPC
+//! `JetStats` call, and [`log_report`] writes them to the log. Without the variable the hot state
+//! is returned untouched and none of this is on the call path.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use either::Either::{Left, Right};
+use nockvm::interpreter::Context;
+use nockvm::jets::hot::HotEntry;
+use nockvm::jets::{Jet, JetErr, Result};
+use nockvm::noun::Noun;
+use tracing::{info, warn};
+
+pub const JET_STATS_ENV: &str = "ZKVM_JET_STATS";
+
+/// Jets registered past this many are left uninstrumented.
+pub const MAX_INSTRUMENTED_JETS: usize = 512;
+
+/// Whether `ZKVM_JET_STATS` asks for instrumentation.
+pub fn enabled() -> bool {
+    std::env::var(JET_STATS_ENV).is_ok_and(|value| value != "0")
+}
+
+struct Slot {
+    path: String,
+    jet: Jet,
+    calls: AtomicU64,
+    punts: AtomicU64,
+    errors: AtomicU64,
+    nanos: AtomicU64,
+}
+
+static SLOTS: [OnceLock<Slot>; MAX_INSTRUMENTED_JETS] =
+    [const { OnceLock::new() }; MAX_INSTRUMENTED_JETS];
+static REGISTERED: AtomicUsize = AtomicUsize::new(0);
+static REGISTER_LOCK: Mutex<()> = Mutex::new(());
+
+fn trampoline<const I: usize>(context: &mut Context, subject: Noun) -> Result {
+    let slot = SLOTS[I]
+        .get()
+        .expect("trampoline dispatched before its slot was registered");
+    let start = Instant::now();
+    let res = (slot.jet)(context, subject);
+    slot.nanos
+        .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    let counter = match &res {
+        Ok(_) => &slot.calls,
+        Err(JetErr::Punt) => &slot.punts,
+        Err(_) => &slot.errors,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    res
+}
+
+macro_rules! trampolines {
+    ($($row:literal)*) => {
+        [$(
+            trampoline::<{ $row * 16 }>, trampoline::<{ $row * 16 + 1 }>,
+            trampoline::<{ $row * 16 + 2 }>, trampoline::<{ $row * 16 + 3 }>,
+            trampoline::<{ $row * 16 + 4 }>, trampoline::<{ $row * 16 + 5 }>,
+            trampoline::<{ $row * 16 + 6 }>, trampoline::<{ $row * 16 + 7 }>,
+            trampoline::<{ $row * 16 + 8 }>, trampoline::<{ $row * 16 + 9 }>,
+            trampoline::<{ $row * 16 + 10 }>, trampoline::<{ $row * 16 + 11 }>,
+            trampoline::<{ $row * 16 + 12 }>, trampoline::<{ $row * 16 + 13 }>,
+            trampoline::<{ $row * 16 + 14 }>, trampoline::<{ $row * 16 + 15 }>,
+        )*]
+    };
+}
+
+static TRAMPOLINES: [Jet; MAX_INSTRUMENTED_JETS] = trampolines!(
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+);
+
+/// A hot-state path as `/`-joined segments, with versioned roots written as e.g. `k138`.
+pub(crate) fn path_name(path: &[either::Either<&[u8], (u64, u64)>]) -> String {
+    path.iter()
+        .map(|segment| match segment {
+            Left(name) => String::from_utf8_lossy(name).into_owned(),
+            Right((tag, version)) => {
+                let tag = tag.to_le_bytes();
+                let len = tag.iter().position(|b| *b == 0).unwrap_or(tag.len());
+                format!("{}{}", String::from_utf8_lossy(&tag[..len]), version)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Replace each entry's jet with a counting trampoline. Registering the same path and jet again,
+/// e.g. from a second call to `produce_prover_hot_state`, reuses its counters.
+pub fn instrument(entries: Vec<HotEntry>) -> Vec<HotEntry> {
+    let _guard = REGISTER_LOCK.lock().expect("jet stats registry poisoned");
+    let mut overflow = 0;
+    let instrumented = entries
+        .into_iter()
+        .map(|(path, axis, jet)| {
+            let name = path_name(path);
+            let registered = REGISTERED.load(Ordering::Acquire);
+            let existing = (0..registered).find(|&i| {
+                let slot = SLOTS[i].get().expect("registered slot");
+                slot.path == name && std::ptr::fn_addr_eq(slot.jet, jet)
+            });
+            let index = match existing {
+                Some(index) => index,
+                None if registered < MAX_INSTRUMENTED_JETS => {
+                    let slot = Slot {
+                        path: name,
+                        jet,
+                        calls: AtomicU64::new(0),
+                        punts: AtomicU64::new(0),
+                        errors: AtomicU64::new(0),
+                        nanos: AtomicU64::new(0),
+                    };
+                    if SLOTS[registered].set(slot).is_err() {
+                        unreachable!("slot {registered} registered twice");
+                    }
+                    REGISTERED.store(registered + 1, Ordering::Release);
+                    registered
+                }
+                None => {
+                    overflow += 1;
+                    return (path, axis, jet);
+                }
+            };
+            (path, axis, TRAMPOLINES[index])
+        })
+        .collect();
+    if overflow > 0 {
+        warn!("{overflow} jets exceed the instrumentation limit of {MAX_INSTRUMENTED_JETS} and are not counted");
+    }
+    instrumented
+}
+
+/// Counters for one jet since it was registered or last [`reset`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JetStats {
+    pub path: String,
+    /// Calls that returned a result.
+    pub calls: u64,
+    /// Calls that punted and fell back to Nock.
+    pub punts: u64,
+    /// Calls that failed with a non-punt error.
+    pub errors: u64,
+    /// Wall time spent inside the jet, across all outcomes.
+    pub total: Duration,
+}
+
+/// Every registered jet that has been invoked at least once, by descending total time.
+pub fn snapshot() -> Vec<JetStats> {
+    let mut stats: Vec<JetStats> = SLOTS[..REGISTERED.load(Ordering::Acquire)]
+        .iter()
+        .filter_map(OnceLock::get)
+        .map(|slot| JetStats {
+            path: slot.path.clone(),
+            calls: slot.calls.load(Ordering::Relaxed),
+            punts: slot.punts.load(Ordering::Relaxed),
+            errors: slot.errors.load(Ordering::Relaxed),
+            total: Duration::from_nanos(slot.nanos.load(Ordering::Relaxed)),
+        })
+        .filter(|stats| stats.calls + stats.punts + stats.errors > 0)
+        .collect();
+    stats.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.path.cmp(&b.path)));
+    stats
+}
+
+/// Zero every counter.
+pub fn reset() {
+    for slot in SLOTS[..REGISTERED.load(Ordering::Acquire)]
+        .iter()
+        .filter_map(OnceLock::get)
+    {
+        slot.calls.store(0, Ordering::Relaxed);
+        slot.punts.store(0, Ordering::Relaxed);
+        slot.errors.store(0, Ordering::Relaxed);
+        slot.nanos.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Log the current [`snapshot`], one line per jet.
+pub fn log_report() {
+    let stats = snapshot();
+    info!("jet stats: {} jets invoked", stats.len());
+    for jet in stats {
+        info!(
+            "jet stats: {:>10} calls {:>8} punts {:>6} errors {:>12.3?} {}",
+            jet.calls, jet.punts, jet.errors, jet.total, jet.path
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nockvm::jets::util::test::init_context;
+    use nockvm::noun::D;
+
+    use super::*;
+
+    fn answer(_context: &mut Context, _subject: Noun) -> Result {
+        Ok(D(42))
+    }
+
+    fn punt(_context: &mut Context, _subject: Noun) -> Result {
+        Err(JetErr::Punt)
+    }
+
+    #[test]
+    fn counts_calls_and_punts() {
+        let entries: Vec<HotEntry> = vec![
+            (&[Left(b"instrument-test"), Left(b"answer")], 1, answer),
+            (&[Left(b"instrument-test"), Left(b"punt")], 1, punt),
+        ];
+        let wrapped = instrument(entries.clone());
+        // Registering again must reuse the same trampolines.
+        let again = instrument(entries);
+        for (a, b) in wrapped.iter().zip(&again) {
+            assert!(std::ptr::fn_addr_eq(a.2, b.2));
+        }
+
+        let mut context = init_context();
+        for _ in 0..3 {
+            let res = (wrapped[0].2)(&mut context, D(0)).expect("jet should succeed");
+            assert_eq!(res.as_direct().expect("direct").data(), 42);
+        }
+        assert!(matches!(
+            (wrapped[1].2)(&mut context, D(0)),
+            Err(JetErr::Punt)
+        ));
+
+        let stats = snapshot();
+        let find = |path: &str| {
+            stats
+                .iter()
+                .find(|stats| stats.path == path)
+                .cloned()
+                .expect("jet should be reported")
+        };
+        let answer = find("instrument-test/answer");
+        assert_eq!((answer.calls, answer.punts, answer.errors), (3, 0, 0));
+        let punted = find("instrument-test/punt");
+        assert_eq!((punted.calls, punted.punts, punted.errors), (0, 1, 0));
+    }
+}
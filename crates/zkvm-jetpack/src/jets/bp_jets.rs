@@ -15,6 +15,7 @@ use crate::form::poly::*;
 use crate::form::structs::HoonList;
 use crate::jets::fpntt_jets::{felt_as_noun, felt_from_u64s};
 use crate::jets::mary_jets::{mary_to_list_fields, snag_one_fields};
+use crate::twiddles::bp_twiddles;
 use crate::utils::is_hoon_list_end;
 
 pub fn bpoly_to_list_jet(context: &mut Context, subject: Noun) -> Result {
@@ -169,7 +170,8 @@ pub fn bp_ntt_jet(context: &mut Context, subject: Noun) -> Result {
         return Err(BAIL_FAIL);
     };
     let root_64 = root_atom.as_u64()?;
-    let returned_bpoly = bp_ntt(bp_poly.0, &Belt(root_64));
+    let twiddles = bp_twiddles(bp_poly.len(), &Belt(root_64));
+    let returned_bpoly = bp_ntt_with_twiddles(bp_poly.0, &twiddles);
     // TODO: preallocate and pass res buffer into bp_ntt?
     let (res_atom, res_poly): (IndirectAtom, &mut [Belt]) =
         new_handle_mut_slice(&mut context.stack, Some(returned_bpoly.len() as usize));
@@ -186,7 +188,9 @@ pub fn bp_fft_jet(context: &mut Context, subject: Noun) -> Result {
     let Ok(p_poly) = BPolySlice::try_from(p) else {
         return Err(BAIL_FAIL);
     };
-    let returned_bpoly = bp_fft(p_poly.0)?;
+    let root = Belt(p_poly.len() as u64).ordered_root()?;
+    let twiddles = bp_twiddles(p_poly.len(), &root);
+    let returned_bpoly = bp_ntt_with_twiddles(p_poly.0, &twiddles);
     let (res_atom, res_poly): (IndirectAtom, &mut [Belt]) =
         new_handle_mut_slice(&mut context.stack, Some(returned_bpoly.len() as usize));
 
@@ -227,7 +231,8 @@ pub fn bp_coseword_jet(context: &mut Context, subject: Noun) -> Result {
     };
     let order_32: u32 = order_atom.as_u32()?;
     let root = Belt(order_32 as u64).ordered_root()?;
-    let returned_bpoly = bp_coseword(p_poly.0, &offset_belt, order_32, &root);
+    let twiddles = bp_twiddles(order_32 as usize, &root);
+    let returned_bpoly = bp_coseword_with_twiddles(p_poly.0, &offset_belt, order_32, &twiddles);
     let (res, res_poly): (IndirectAtom, &mut [Belt]) =
         new_handle_mut_slice(&mut context.stack, Some(returned_bpoly.len() as usize));
     res_poly.copy_from_slice(&returned_bpoly);
@@ -327,6 +332,20 @@ pub fn bpeval_lift_jet(context: &mut Context, subject: Noun) -> Result {
     }
 }
 
+// bpeval: evaluate a bpoly at a belt
+pub fn bpeval_jet(context: &mut Context, subject: Noun) -> Result {
+    let sam = slot(subject, 6)?;
+    let bp = slot(sam, 2)?;
+    let x = slot(sam, 3)?;
+
+    let (Ok(bp_poly), Ok(x)) = (BPolySlice::try_from(bp), x.as_belt()) else {
+        debug!("bp was not a bpoly or x was not a belt");
+        return Err(BAIL_FAIL);
+    };
+
+    Ok(Atom::new(&mut context.stack, bpeval(bp_poly.0, x).0).as_noun())
+}
+
 pub fn bpdvr_jet(context: &mut Context, subject: Noun) -> Result {
     let sam = slot(subject, 6)?;
     let ba = slot(sam, 2)?;
@@ -5,7 +5,7 @@ use nockvm::jets::util::BAIL_FAIL;
 use nockvm::jets::JetErr;
 use nockvm::noun::{Noun, Slots};
 use noun_serde::{NounDecode, NounEncode};
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use crate::form::belt::*;
 use crate::form::crypto::cheetah::*;
@@ -118,24 +118,143 @@ pub fn verify_affine(
         return Err(BAIL_FAIL);
     }
 
+    Ok(schnorr_challenge(&sum, &pubkey, m) == *chal)
+}
+
+fn schnorr_challenge(scalar: &CheetahPoint, pubkey: &CheetahPoint, m: &[Belt]) -> UBig {
     let mut hashable = vec![Belt(0); 6 * 4 + 5];
-    hashable[0..6].copy_from_slice(&sum.x.0);
-    hashable[6..12].copy_from_slice(&sum.y.0);
+    hashable[0..6].copy_from_slice(&scalar.x.0);
+    hashable[6..12].copy_from_slice(&scalar.y.0);
     hashable[12..18].copy_from_slice(&pubkey.x.0);
     hashable[18..24].copy_from_slice(&pubkey.y.0);
     hashable[24..].copy_from_slice(m);
 
     let hash = tip5::hash::hash_varlen(&mut hashable);
-    let truncated_hash = trunc_g_order(&hash);
+    trunc_g_order(&hash)
+}
 
-    Ok(truncated_hash == *chal)
+/// `t8-to-atom:belt-schnorr`: `(rap 5 (leaf-sequence:shape t))` over an 8-tuple. Each element
+/// takes as many 32-bit blocks as it needs, so zeros contribute nothing and oversized elements
+/// push the rest further up, exactly as in Hoon.
+fn t8_to_ubig(context: &mut Context, t8: Noun) -> Result<UBig, JetErr> {
+    let mut res = UBig::from(0u8);
+    let mut shift = 0;
+    let mut rest = t8;
+    for i in 0..8 {
+        let leaf = if i < 7 {
+            let cell = rest.as_cell().map_err(|_| JetErr::Punt)?;
+            rest = cell.tail();
+            cell.head()
+        } else {
+            rest
+        };
+        let leaf = leaf
+            .as_atom()
+            .map_err(|_| JetErr::Punt)?
+            .as_ubig(&mut context.stack);
+        let blocks = leaf.bit_len().div_ceil(32);
+        res |= leaf << shift;
+        shift += 32 * blocks;
+    }
+    Ok(res)
+}
+
+enum BeltSchnorrCheck {
+    Valid,
+    Invalid,
+    /// Inputs the batched arithmetic can't vouch for, left to the Hoon.
+    Unsupported,
+}
+
+/// `batch-verify:affine:belt-schnorr`, the check block validation runs over every spend.
+///
+/// Rather than verifying each signature on its own, the whole batch shares one table of powers
+/// of the generator, does its scalar multiplications in Jacobian coordinates, and converts the
+/// commitments back to affine with a single field inversion. Like `levy`, the first signature
+/// that fails decides the result; if an earlier one is malformed or off the curve the jet punts
+/// so the Hoon can decide what that means.
+pub fn belt_schnorr_batch_verify_jet(context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
+    let list = subject.slot(6)?;
+    let mut args = Vec::new();
+    for arg in list.list_iter() {
+        let pubkey = CheetahPoint::from_noun(&arg.slot(2)?).map_err(|_| JetErr::Punt)?;
+        let m = <[Belt; 5]>::from_noun(&arg.slot(6)?).map_err(|_| JetErr::Punt)?;
+        let chal = t8_to_ubig(context, arg.slot(14)?)?;
+        let sig = t8_to_ubig(context, arg.slot(15)?)?;
+        args.push(ValidateArgs {
+            pubkey,
+            m,
+            chal,
+            sig,
+        });
+    }
+    let valid = belt_schnorr_batch_verify(&args)?;
+    Ok(valid.to_noun(&mut context.stack))
+}
+
+pub(crate) fn belt_schnorr_batch_verify(args: &[ValidateArgs]) -> Result<bool, JetErr> {
+    let zero = UBig::from(0u8);
+    let in_range = |n: &UBig| *n > zero && *n < *G_ORDER;
+    let checkable: Vec<bool> = args
+        .iter()
+        .map(|arg| {
+            in_range(&arg.chal)
+                && in_range(&arg.sig)
+                && ch_on_curve(&arg.pubkey)
+                && arg.m.iter().all(|belt| based_check(belt.0))
+        })
+        .collect();
+
+    let scalars: Vec<JacobianPoint> = args
+        .par_iter()
+        .zip(checkable.par_iter())
+        .map(|(arg, checkable)| {
+            if !checkable {
+                return JacobianPoint::IDENTITY;
+            }
+            ch_gen_scal(&arg.sig).add(&JacobianPoint::scal(&arg.chal, &arg.pubkey).neg())
+        })
+        .collect();
+    let scalars = ch_batch_to_affine(&scalars)?;
+
+    let checks: Vec<BeltSchnorrCheck> = args
+        .par_iter()
+        .zip(checkable.par_iter().zip(scalars.par_iter()))
+        .map(|(arg, (checkable, scalar))| {
+            if !checkable {
+                // Out-of-range scalars are a plain rejection; anything else is left to the Hoon.
+                return if in_range(&arg.chal) && in_range(&arg.sig) {
+                    BeltSchnorrCheck::Unsupported
+                } else {
+                    BeltSchnorrCheck::Invalid
+                };
+            }
+            if scalar.inf {
+                return BeltSchnorrCheck::Unsupported;
+            }
+            if schnorr_challenge(scalar, &arg.pubkey, &arg.m) == arg.chal {
+                BeltSchnorrCheck::Valid
+            } else {
+                BeltSchnorrCheck::Invalid
+            }
+        })
+        .collect();
+
+    for check in checks {
+        match check {
+            BeltSchnorrCheck::Valid => {}
+            BeltSchnorrCheck::Invalid => return Ok(false),
+            BeltSchnorrCheck::Unsupported => return Err(JetErr::Punt),
+        }
+    }
+    Ok(true)
 }
 
 #[cfg(test)]
 mod tests {
     use ibig::UBig;
     use nockvm::jets::util::test::{assert_jet, init_context, A};
-    use nockvm::noun::{Atom, D, T, YES};
+    use nockvm::noun::{Atom, D, NO, T, YES};
     use noun_serde::NounEncode;
 
     use super::*;
@@ -457,4 +576,119 @@ mod tests {
         assert_jet(&mut context, batch_verify_affine_jet, sample, YES);
         Ok(())
     }
+
+    /// `atom-to-t8:belt-schnorr`: the 32-bit blocks of `n`, least significant first.
+    fn t8(context: &mut Context, n: &UBig) -> Noun {
+        let blocks: Vec<Noun> = (0..8)
+            .map(|i| {
+                let block = (n >> (32 * i)) & UBig::from(u32::MAX);
+                D(u64::try_from(&block).expect("block fits in 32 bits"))
+            })
+            .collect();
+        T(&mut context.stack, &blocks)
+    }
+
+    #[test]
+    fn test_belt_schnorr_batch_verify() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = init_context();
+        let sparse = (
+            CheetahPoint {
+                x: F6lt([
+                    Belt(5226170347725594598),
+                    Belt(10326968723909427995),
+                    Belt(9909287574944299757),
+                    Belt(3389312162809687369),
+                    Belt(6741939401364684801),
+                    Belt(1215336833048603318),
+                ]),
+                y: F6lt([
+                    Belt(4761860904395420101),
+                    Belt(8266056389007434480),
+                    Belt(9911285737560359492),
+                    Belt(14968168698225451681),
+                    Belt(5907552010793110532),
+                    Belt(781863599964220501),
+                ]),
+                inf: false,
+            },
+            [Belt(1), Belt(2), Belt(3), Belt(4), Belt(5)],
+            UBig::from_str_radix(
+                "6ed772faeda592c3d5c570169acb19e5e979ea9975409bfa28d874a88c34fba", 16,
+            )?,
+            UBig::from_str_radix(
+                "64483168448a47664e22ba6c4a571eb0dd64dc5ee95b550c66b5227791278589", 16,
+            )?,
+        );
+        let dense = (
+            CheetahPoint {
+                x: A_GEN.x,
+                y: F6lt([
+                    Belt(3062714866612034253),
+                    Belt(15671931273416742386),
+                    Belt(4071440668668521568),
+                    Belt(7738250649524482367),
+                    Belt(5259065445844042557),
+                    Belt(8456011930642078370),
+                ]),
+                inf: false,
+            },
+            [Belt(8), Belt(9), Belt(10), Belt(11), Belt(12)],
+            UBig::from_str_radix(
+                "6f3cd43cd8709f4368aed04cd84292ab1c380cb645aaa7d010669d70375cbe88", 16,
+            )?,
+            UBig::from_str_radix(
+                "5197ab182e307a350b5cf3606d6e99a6f35b0d382c8330dde6e51fb6ef8ebb8c", 16,
+            )?,
+        );
+        let off_curve = CheetahPoint {
+            x: sparse.0.x,
+            y: F6_ONE,
+            inf: false,
+        };
+
+        let arg =
+            |context: &mut Context,
+             (pubkey, m, chal, sig): &(CheetahPoint, [Belt; 5], UBig, UBig)| {
+                let pubkey = pubkey.to_noun(&mut context.stack);
+                let m = m.to_noun(&mut context.stack);
+                let chal = t8(context, chal);
+                let sig = t8(context, sig);
+                T(&mut context.stack, &[pubkey, m, chal, sig])
+            };
+        let good = [arg(&mut context, &sparse), arg(&mut context, &dense)];
+        let forged = arg(
+            &mut context,
+            &(
+                dense.0,
+                dense.1,
+                dense.2.clone(),
+                &dense.3 + UBig::from(1u8),
+            ),
+        );
+        let zero_chal = arg(
+            &mut context,
+            &(off_curve, dense.1, UBig::from(0u8), dense.3.clone()),
+        );
+        let unchecked = arg(
+            &mut context,
+            &(off_curve, dense.1, dense.2.clone(), dense.3.clone()),
+        );
+
+        let sample = T(&mut context.stack, &[good[0], good[1], good[0], D(0)]);
+        assert_jet(&mut context, belt_schnorr_batch_verify_jet, sample, YES);
+        let sample = T(&mut context.stack, &[good[0], forged, good[1], D(0)]);
+        assert_jet(&mut context, belt_schnorr_batch_verify_jet, sample, NO);
+        // A rejection before an input the jet can't handle still decides the batch...
+        let sample = T(&mut context.stack, &[zero_chal, unchecked, D(0)]);
+        assert_jet(&mut context, belt_schnorr_batch_verify_jet, sample, NO);
+        // ...but an off-curve pubkey ahead of it is left to the Hoon.
+        let sample = T(&mut context.stack, &[unchecked, forged, D(0)]);
+        let subject = T(&mut context.stack, &[D(0), sample, D(0)]);
+        assert!(matches!(
+            belt_schnorr_batch_verify_jet(&mut context, subject),
+            Err(JetErr::Punt)
+        ));
+        assert_jet(&mut context, belt_schnorr_batch_verify_jet, D(0), YES);
+        Ok(())
+    }
 }
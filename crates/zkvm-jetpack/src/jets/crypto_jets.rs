@@ -5,9 +5,13 @@ use nockvm::interpreter::Context;
 use nockvm::jets::cold::Nounable;
 use nockvm::jets::util::{slot, BAIL_EXIT};
 use nockvm::jets::JetErr;
-use nockvm::noun::{Atom, Noun};
+use nockvm::mem::NockStack;
+use nockvm::noun::{Atom, Noun, D, T};
 
 use crate::form::crypto::argon2::{argon2_hook, Argon2Args};
+use crate::form::crypto::blake3::{
+    self, words_from_le_bytes, words_to_le_bytes, Output, BLOCK_LEN,
+};
 
 pub fn argon2_jet(context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
     let parent_core = slot(subject, 7)?;
@@ -30,13 +34,130 @@ pub fn argon2_jet(context: &mut Context, subject: Noun) -> Result<Noun, JetErr>
     Ok(res_noun)
 }
 
+/// `++hash:blake3`: one-shot hashing of a whole message under the door's `cv` and `flags`.
+pub fn blake3_hash_jet(context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
+    let sam = slot(subject, 6)?;
+    let out = slot(sam, 2)?
+        .as_atom()?
+        .as_u64()
+        .map_err(|_| JetErr::Punt)?;
+    let out = usize::try_from(out).map_err(|_| JetErr::Punt)?;
+    let msg = octs_bytes(slot(sam, 3)?)?;
+    let (cv, flags) = blake3_door(subject)?;
+
+    let mut res = vec![0; out];
+    blake3::hash(words_from_le_bytes(cv.as_ne_bytes()), flags, &msg, &mut res);
+    Ok(le_bytes_to_noun(&mut context.stack, &res))
+}
+
+/// `++chunk-output:blake3`: absorb one chunk, returning the `$output` for its final block.
+///
+/// Kernels hashing a message incrementally call this once per chunk and merge the results with
+/// `++compress`, so the noun produced must match the Hoon exactly, including the door's `cv`
+/// being passed through untouched when the chunk fits in a single block.
+pub fn blake3_chunk_output_jet(context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
+    let sam = slot(subject, 6)?;
+    let counter = slot(sam, 2)?;
+    let chunk = octs_bytes(slot(sam, 3)?)?;
+    let (cv, flags) = blake3_door(subject)?;
+
+    let output = blake3::chunk_output(
+        words_from_le_bytes(cv.as_ne_bytes()),
+        flags,
+        atom_low_u64(counter.as_atom()?),
+        &chunk,
+    );
+    let last_block = chunk.len().saturating_sub(1) / BLOCK_LEN * BLOCK_LEN;
+    let stack = &mut context.stack;
+    let cv = if last_block == 0 {
+        cv.as_noun()
+    } else {
+        le_bytes_to_noun(stack, &words_to_le_bytes(&output.cv))
+    };
+    let block = le_bytes_to_noun(stack, &chunk[last_block..]);
+    Ok(T(
+        stack,
+        &[cv, counter, block, D(output.block_len as u64), D(output.flags as u64)],
+    ))
+}
+
+/// `++compress:blake3-impl`: one compression, returning the full 16-word state.
+pub fn blake3_compress_jet(context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
+    let sam = slot(subject, 6)?;
+    let word = |axis: u64| -> Result<[u32; 1], JetErr> {
+        Ok(words_from_le_bytes(
+            slot(sam, axis)?.as_atom()?.as_ne_bytes(),
+        ))
+    };
+    let output = Output {
+        cv: words_from_le_bytes(slot(sam, 2)?.as_atom()?.as_ne_bytes()),
+        counter: atom_low_u64(slot(sam, 6)?.as_atom()?),
+        block: words_from_le_bytes(slot(sam, 14)?.as_atom()?.as_ne_bytes()),
+        block_len: word(30)?[0],
+        flags: word(31)?[0],
+    };
+    Ok(le_bytes_to_noun(
+        &mut context.stack,
+        &words_to_le_bytes(&output.compress()),
+    ))
+}
+
+/// The `[cv flags]` sample of the `%blake3` door a gate was pulled from.
+fn blake3_door(subject: Noun) -> Result<(Atom, u32), JetErr> {
+    let door = slot(subject, 7)?;
+    let params = slot(door, 6)?;
+    let cv = slot(params, 2)?.as_atom()?;
+    let flags = slot(params, 3)?
+        .as_atom()?
+        .as_u64()
+        .map_err(|_| JetErr::Punt)?;
+    let flags = u32::try_from(flags).map_err(|_| JetErr::Punt)?;
+    Ok((cv, flags))
+}
+
+/// The `p.octs` little-endian bytes of `q.octs`.
+///
+/// The Hoon splits `q` with `rsh`/`end` without masking, so data past `p` bytes would leak into
+/// the last block; punt on such inputs rather than reproduce that.
+fn octs_bytes(octs: Noun) -> Result<Vec<u8>, JetErr> {
+    let len = slot(octs, 2)?
+        .as_atom()?
+        .as_u64()
+        .map_err(|_| JetErr::Punt)?;
+    let len = usize::try_from(len).map_err(|_| JetErr::Punt)?;
+    let dat = slot(octs, 3)?.as_atom()?;
+    let bytes = dat.as_ne_bytes();
+    let significant = bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |i| i + 1);
+    if significant > len {
+        return Err(JetErr::Punt);
+    }
+    let mut res = vec![0; len];
+    res[..significant].copy_from_slice(&bytes[..significant]);
+    Ok(res)
+}
+
+fn atom_low_u64(atom: Atom) -> u64 {
+    let [lo, hi] = words_from_le_bytes(atom.as_ne_bytes());
+    (lo as u64) | ((hi as u64) << 32)
+}
+
+fn le_bytes_to_noun(stack: &mut NockStack, bytes: &[u8]) -> Noun {
+    if bytes.iter().all(|byte| *byte == 0) {
+        D(0)
+    } else {
+        Atom::from_bytes(stack, bytes).as_noun()
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use hex_literal::hex;
     use ibig::UBig;
     use nockvm::ext::make_tas;
-    use nockvm::jets::util::test::{assert_jet_door, init_context};
-    use nockvm::noun::{D, T};
+    use nockvm::jets::util::test::{assert_jet_door, assert_noun_eq, init_context};
 
     use super::*;
 
@@ -140,4 +261,156 @@ pub mod test {
             &mut context, argon2_jet, inner, parent_context, expected_bytes,
         );
     }
+
+    fn octs(context: &mut Context, bytes: &[u8]) -> Noun {
+        let dat = le_bytes_to_noun(&mut context.stack, bytes);
+        T(&mut context.stack, &[D(bytes.len() as u64), dat])
+    }
+
+    /// A `%blake3` door parameterized with `cv` and `flags`, as `assert_jet_door` expects it.
+    fn blake3_door_noun(context: &mut Context, cv: &[u32; 8], flags: u32) -> Noun {
+        let cv = le_bytes_to_noun(&mut context.stack, &words_to_le_bytes(cv));
+        let params = T(&mut context.stack, &[cv, D(flags as u64)]);
+        T(&mut context.stack, &[D(0), params, D(0)])
+    }
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Key of the official BLAKE3 test vectors, whose inputs are the bytes `i % 251`.
+    const VECTOR_KEY: &[u8; 32] = b"whats the Elvish word for friend";
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_blake3_hash_jet() {
+        let mut context = init_context();
+        let door = blake3_door_noun(&mut context, &blake3::IV, 0);
+        let sam = T(&mut context.stack, &[D(32), D(0), D(0)]);
+        let expected = hex!("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262");
+        let expected = le_bytes_to_noun(&mut context.stack, &expected);
+        assert_jet_door(&mut context, blake3_hash_jet, sam, door, expected);
+
+        // Outputs of the BLAKE3 reference C implementation, spanning several chunks and
+        // extended output lengths.
+        let key = words_from_le_bytes(VECTOR_KEY);
+        let cases: [([u32; 8], u32, usize, &[u8]); 5] = [
+            (
+                blake3::IV,
+                0,
+                3,
+                &hex!("e1be4d7a8ab5560aa4199eea339849ba8e293d55ca0a81006726d184519e647f"),
+            ),
+            (
+                blake3::IV,
+                0,
+                1025,
+                &hex!("d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"),
+            ),
+            (
+                blake3::IV,
+                0,
+                4097,
+                &hex!(
+                    "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb995"
+                    "05f91b0b5600a11251652eacfa9497b31cd3c409ce2e45cfe6c0a016967316c4"
+                    "26bd26f619eab5d70af9a418b845c608840390f361630bd497b1ab4401931635"
+                    "7c61dbe0"
+                ),
+            ),
+            (
+                key,
+                blake3::KEYED_HASH,
+                3,
+                &hex!("39e67b76b5a007d4921969779fe666da67b5213b096084ab674742f0d5ec62b9"),
+            ),
+            (
+                key,
+                blake3::KEYED_HASH,
+                1025,
+                &hex!("357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69"),
+            ),
+        ];
+        for (cv, flags, len, res) in cases {
+            let door = blake3_door_noun(&mut context, &cv, flags);
+            let msg_noun = octs(&mut context, &message(len));
+            let sam = T(&mut context.stack, &[D(res.len() as u64), msg_noun]);
+            let expected = le_bytes_to_noun(&mut context.stack, res);
+            assert_jet_door(&mut context, blake3_hash_jet, sam, door, expected);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_blake3_chunked_matches_one_shot() {
+        let mut context = init_context();
+        let msg = message(200);
+        let door = blake3_door_noun(&mut context, &blake3::IV, 0);
+        let chunk = octs(&mut context, &msg);
+        let sam = T(&mut context.stack, &[D(0), chunk]);
+        let subject = T(&mut context.stack, &[D(0), sam, door]);
+        let chunk_output =
+            blake3_chunk_output_jet(&mut context, subject).expect("chunk-output jet");
+
+        // Everything but the chaining value is fixed by the chunk layout: the last of four
+        // blocks, eight bytes long, with only the chunk-end flag. The chaining value is checked
+        // against the reference output below.
+        let cv = slot(chunk_output, 2).expect("cv");
+        let block = le_bytes_to_noun(&mut context.stack, &msg[192..]);
+        let expected = T(
+            &mut context.stack,
+            &[cv, D(0), block, D(8), D(blake3::CHUNK_END as u64)],
+        );
+        assert_noun_eq(&mut context.stack, chunk_output, expected);
+
+        // A single chunk is its own root: compressing its output with the root flag set must
+        // reproduce the first block of extended output from the reference implementation.
+        let root = T(
+            &mut context.stack,
+            &[
+                slot(chunk_output, 2).expect("cv"),
+                slot(chunk_output, 6).expect("counter"),
+                slot(chunk_output, 14).expect("block"),
+                slot(chunk_output, 30).expect("blocklen"),
+                D((blake3::CHUNK_END | blake3::ROOT) as u64),
+            ],
+        );
+        let xof = hex!(
+            "f9c991a91ce818ab00f3bf22cef993a2f8d9ab0206f2b9efcef063bb19046966"
+            "2cd15ef78d700c7a3583675e3424aab574d353b72e026ad6a68ed7b1188212f3"
+        );
+        let expected = le_bytes_to_noun(&mut context.stack, &xof);
+        assert_jet_door(&mut context, blake3_compress_jet, root, D(0), expected);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_blake3_single_block_chunk_keeps_door_cv() {
+        let mut context = init_context();
+        let door = blake3_door_noun(&mut context, &blake3::IV, blake3::KEYED_HASH);
+        let chunk = octs(&mut context, b"abc");
+        let sam = T(&mut context.stack, &[D(7), chunk]);
+        let cv = slot(door, 12).expect("door cv");
+        let block = le_bytes_to_noun(&mut context.stack, b"abc");
+        let flags = blake3::KEYED_HASH | blake3::CHUNK_START | blake3::CHUNK_END;
+        let expected = T(
+            &mut context.stack,
+            &[cv, D(7), block, D(3), D(flags as u64)],
+        );
+        assert_jet_door(&mut context, blake3_chunk_output_jet, sam, door, expected);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_blake3_punts_on_oversized_octs() {
+        let mut context = init_context();
+        let door = blake3_door_noun(&mut context, &blake3::IV, 0);
+        let msg = T(&mut context.stack, &[D(1), D(0x0102)]);
+        let sam = T(&mut context.stack, &[D(32), msg]);
+        let subject = T(&mut context.stack, &[D(0), sam, door]);
+        assert!(matches!(
+            blake3_hash_jet(&mut context, subject),
+            Err(JetErr::Punt)
+        ));
+    }
 }
@@ -5,11 +5,12 @@ use nockvm::noun::{Cell, IndirectAtom, Noun, D};
 use tracing::debug;
 
 use crate::form::felt::Felt;
-use crate::form::fpoly::{fp_coseword, fpeval, lift_to_fpoly};
+use crate::form::fpoly::{fp_coseword_with_twiddles, fpeval, lift_to_fpoly};
 use crate::form::handle::{finalize_poly, new_handle_mut_felt, new_handle_mut_slice};
 use crate::form::noun_ext::{AtomMathExt, NounMathExt};
 use crate::form::poly::FPolySlice;
 use crate::form::structs::HoonList;
+use crate::twiddles::fp_twiddles;
 
 pub fn fp_coseword_jet(context: &mut Context, subject: Noun) -> Result {
     let sam = slot(subject, 6)?;
@@ -25,7 +26,8 @@ pub fn fp_coseword_jet(context: &mut Context, subject: Noun) -> Result {
     };
     let order_32: u32 = order_atom.as_u32()?;
     let root = Felt::ordered_root(order_32 as u64)?;
-    let returned_fpoly = fp_coseword(p_poly.0, offset_felt, order_32, &root);
+    let twiddles = fp_twiddles(order_32 as usize, &root);
+    let returned_fpoly = fp_coseword_with_twiddles(p_poly.0, offset_felt, order_32, &twiddles);
 
     let (res, res_poly): (IndirectAtom, &mut [Felt]) =
         new_handle_mut_slice(&mut context.stack, Some(returned_fpoly.len() as usize));
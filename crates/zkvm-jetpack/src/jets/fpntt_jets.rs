@@ -7,10 +7,11 @@ use nockvm::noun::{Atom, IndirectAtom, Noun};
 use crate::based;
 use crate::form::belt::*;
 use crate::form::felt::Felt;
-use crate::form::fpoly::fp_ntt;
+use crate::form::fpoly::fp_ntt_with_twiddles;
 use crate::form::handle::{finalize_poly, new_handle_mut_slice};
 use crate::form::noun_ext::NounMathExt;
 use crate::form::poly::*;
+use crate::twiddles::fp_twiddles;
 use crate::utils::hoon_list_to_vecbelt;
 
 const DEG: u64 = 3; // field extension degree
@@ -68,7 +69,8 @@ pub fn fp_ntt_jet(context: &mut Context, subject: Noun) -> Result<Noun, JetErr>
         return Err(BAIL_FAIL);
     };
 
-    let returned_fpoly = fp_ntt(fp.0, root);
+    let twiddles = fp_twiddles(fp.len(), root);
+    let returned_fpoly = fp_ntt_with_twiddles(fp.0, &twiddles);
     let (res_atom, res_poly): (IndirectAtom, &mut [Felt]) =
         new_handle_mut_slice(&mut context.stack, Some(returned_fpoly.len() as usize));
     res_poly.copy_from_slice(&returned_fpoly[..]);
@@ -143,22 +143,18 @@ pub fn hash_pairs(stack: &mut NockStack, lis_noun: Noun) -> Result<Noun, JetErr>
     let lent_lis = lis.len();
     assert!(lent_lis > 0);
 
-    let mut res: Vec<Noun> = Vec::new();
-
-    for i in 0..lent_lis / 2 {
-        let b = i * 2;
-        if (b + 1) == lent_lis {
-            res.push(lis[b]);
-        } else {
-            let b0 = hoon_list_to_vecbelt(lis[b])?;
-            let mut b1 = hoon_list_to_vecbelt(lis[b + 1])?;
-            let mut pair = b0;
-            pair.append(&mut b1);
-            let digest = tip5::hash::hash_10(&mut pair);
-            let digest_noun = vec_to_hoon_list(stack, &digest);
-            res.push(digest_noun);
-        }
-    }
+    let pairs = lis
+        .chunks_exact(2)
+        .map(|pair| {
+            let mut input = hoon_list_to_vecbelt(pair[0])?;
+            input.append(&mut hoon_list_to_vecbelt(pair[1])?);
+            <[Belt; tip5::RATE]>::try_from(input).map_err(|_| BAIL_FAIL)
+        })
+        .collect::<Result<Vec<_>, JetErr>>()?;
+    let res: Vec<Noun> = tip5::hash::hash_10_batch(&pairs)
+        .iter()
+        .map(|digest| vec_to_hoon_list(stack, digest))
+        .collect();
 
     Ok(vecnoun_to_hoon_list(stack, res.as_slice()))
 }
@@ -1,14 +1,23 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
 use nockvm::interpreter::Context;
 use nockvm::jets::util::{slot, BAIL_FAIL};
 use nockvm::jets::JetErr;
 use nockvm::mem::NockStack;
-use nockvm::noun::{Atom, Cell, IndirectAtom, Noun};
+use nockvm::noun::{Atom, Cell, IndirectAtom, Noun, NO, YES};
 use nockvm_macros::tas;
+use noun_serde::NounDecode;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rayon::slice::ParallelSlice;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use tracing::debug;
 
-use crate::form::belt::{bpow, Belt};
+use crate::form::belt::{based_check, binv, bpow, Belt};
 use crate::form::felt::*;
+use crate::form::fpoly::{fp_ntt, fpeval, fpscal_};
 use crate::form::handle::new_handle_mut_felt;
+use crate::form::math::tip5;
 use crate::form::noun_ext::{AtomMathExt, NounMathExt};
 use crate::form::poly::{BPolySlice, Element, FPolySlice, Poly, PolySlice};
 use crate::form::structs::{HoonList, HoonMapIter};
@@ -509,3 +518,464 @@ pub fn mpeval_ultra_felt(
         }
     }
 }
+
+static VERIFIER_POOL: OnceLock<ThreadPool> = OnceLock::new();
+
+/// Size the thread pool used by the parallel verification jets.
+///
+/// Must be called before the first proof is verified; returns `false` if the pool was already
+/// built, in which case the existing size stays in effect. Without a call the pool takes rayon's
+/// default of one thread per core.
+pub fn init_verifier_pool(num_threads: usize) -> bool {
+    let mut initialized = false;
+    VERIFIER_POOL.get_or_init(|| {
+        initialized = true;
+        build_verifier_pool(num_threads)
+    });
+    initialized
+}
+
+fn verifier_pool() -> &'static ThreadPool {
+    VERIFIER_POOL.get_or_init(|| build_verifier_pool(0))
+}
+
+fn build_verifier_pool(num_threads: usize) -> ThreadPool {
+    ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .thread_name(|i| format!("stark-verify-{i}"))
+        .build()
+        .unwrap_or_else(|err| {
+            panic!(
+                "Panicked with {err:?} at {}:{} (git sha: {:?})",
+                file!(),
+                line!(),
+                option_env!("GIT_SHA")
+            )
+        })
+}
+
+type Digest = [u64; 5];
+
+/// `$merk-data:merkle` lifted off the stack so it can be checked on any thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkData {
+    pub leaf: Digest,
+    pub axis: u64,
+    pub root: Digest,
+    pub path: Vec<Digest>,
+}
+
+/// Decode a digest the way `hash-ten-cell` would accept it, punting on unbased belts.
+fn based_digest(noun: Noun) -> Result<Digest, JetErr> {
+    let digest = Digest::from_noun(&noun).map_err(|_| JetErr::Punt)?;
+    if digest.iter().all(|belt| based_check(*belt)) {
+        Ok(digest)
+    } else {
+        Err(JetErr::Punt)
+    }
+}
+
+fn merk_axis(noun: Noun) -> Result<u64, JetErr> {
+    noun.as_atom()?.as_u64().map_err(|_| JetErr::Punt)
+}
+
+fn merk_path(noun: Noun) -> Result<Vec<Digest>, JetErr> {
+    HoonList::try_from(noun)?.map(based_digest).collect()
+}
+
+impl TryFrom<Noun> for MerkData {
+    type Error = JetErr;
+
+    /// Punts on anything the jet cannot mirror exactly: axes wider than 64 bits, and digests
+    /// with unbased belts, which crash `hash-ten-cell` in Hoon.
+    fn try_from(noun: Noun) -> Result<Self, Self::Error> {
+        Ok(MerkData {
+            leaf: based_digest(slot(noun, 2)?)?,
+            axis: merk_axis(slot(noun, 6)?)?,
+            root: based_digest(slot(noun, 14)?)?,
+            path: merk_path(slot(noun, 15)?)?,
+        })
+    }
+}
+
+fn hash_ten_cell(left: &Digest, right: &Digest) -> Digest {
+    let mut input: Vec<Belt> = left.iter().chain(right).map(|x| Belt(*x)).collect();
+    tip5::hash::hash_10(&mut input)
+}
+
+impl MerkData {
+    /// `++verify-merk-proof:merkle`
+    pub fn verify(&self) -> bool {
+        self.verify_with(hash_ten_cell)
+    }
+
+    /// [`Self::verify`] with `hash` standing in for `hash-ten-cell`.
+    fn verify_with(&self, mut hash: impl FnMut(&Digest, &Digest) -> Digest) -> bool {
+        let mut axis = self.axis;
+        let mut leaf = self.leaf;
+        let mut path = self.path.iter();
+        if axis == 0 {
+            return false;
+        }
+        loop {
+            if axis == 1 {
+                return self.root == leaf && path.next().is_none();
+            }
+            let Some(sib) = path.next() else {
+                return false;
+            };
+            match axis {
+                2 => return self.root == hash(&leaf, sib) && path.next().is_none(),
+                3 => return self.root == hash(sib, &leaf) && path.next().is_none(),
+                _ if axis.is_multiple_of(2) => leaf = hash(&leaf, sib),
+                _ => leaf = hash(sib, &leaf),
+            }
+            axis /= 2;
+        }
+    }
+}
+
+/// Check every proof on the verifier pool, stopping early once any fails.
+pub fn verify_merk_proofs(proofs: &[MerkData]) -> bool {
+    verifier_pool().install(|| proofs.par_iter().all(MerkData::verify))
+}
+
+/// `++verify-merk-proofs`
+///
+/// The Hoon walks the proofs in an `eny`-shuffled order so an attacker can't make every node fail
+/// late on the same expensive proof. Checking them all concurrently with early exit gives the same
+/// answer and the same protection, so `eny` is unused here.
+pub fn verify_merk_proofs_jet(_context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
+    let sam = slot(subject, 6)?;
+    let proofs = HoonList::try_from(slot(sam, 2)?)?
+        .map(MerkData::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(if verify_merk_proofs(&proofs) { YES } else { NO })
+}
+
+/// Check many openings of one tree. Openings are sorted by axis and split across the verifier
+/// pool; within each share, a node computed for one opening is reused by any other opening that
+/// hashes the same pair of children, so neighbouring leaves only pay for the part of their paths
+/// that differs.
+pub fn verify_merk_batch(proofs: &mut [MerkData]) -> bool {
+    proofs.sort_unstable_by_key(|proof| proof.axis);
+    verifier_pool().install(|| {
+        let share = proofs.len().div_ceil(rayon::current_num_threads()).max(1);
+        proofs.par_chunks(share).all(|share| {
+            let mut nodes: HashMap<(Digest, Digest), Digest> = HashMap::new();
+            share.iter().all(|proof| {
+                proof.verify_with(|left, right| {
+                    *nodes
+                        .entry((*left, *right))
+                        .or_insert_with(|| hash_ten_cell(left, right))
+                })
+            })
+        })
+    })
+}
+
+/// `++verify-merk-batch:merkle`
+pub fn verify_merk_batch_jet(_context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
+    let sam = slot(subject, 6)?;
+    let root = based_digest(slot(sam, 2)?)?;
+    let mut proofs = HoonList::try_from(slot(sam, 3)?)?
+        .map(|opening| {
+            Ok(MerkData {
+                leaf: based_digest(slot(opening, 2)?)?,
+                axis: merk_axis(slot(opening, 6)?)?,
+                root,
+                path: merk_path(slot(opening, 7)?)?,
+            })
+        })
+        .collect::<Result<Vec<_>, JetErr>>()?;
+    Ok(if verify_merk_batch(&mut proofs) {
+        YES
+    } else {
+        NO
+    })
+}
+
+/// One round of `++check-folds:fri-door` lifted off the stack so its queries can be checked on
+/// any thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FriFolds {
+    pub prev_indices: Vec<u64>,
+    pub prev_cosets: HashMap<u64, Vec<Felt>>,
+    pub cosets: HashMap<u64, Vec<Felt>>,
+    /// The codeword sent in the clear, when this is the last round.
+    pub last_codeword: Option<Vec<Felt>>,
+    pub alpha: Felt,
+    pub round_offset: Felt,
+    pub omega: Felt,
+    pub prev_len: u64,
+    pub new_len: u64,
+    pub folding_deg: u64,
+}
+
+fn fri_index(noun: Noun) -> Result<u64, JetErr> {
+    noun.as_atom()?.as_u64().map_err(|_| JetErr::Punt)
+}
+
+fn fri_felt(noun: Noun) -> Result<Felt, JetErr> {
+    noun.as_felt().copied().map_err(|_| JetErr::Punt)
+}
+
+fn fri_fpoly(noun: Noun) -> Result<Vec<Felt>, JetErr> {
+    Ok(FPolySlice::try_from(noun)
+        .map_err(|_| JetErr::Punt)?
+        .0
+        .to_vec())
+}
+
+fn fri_cosets(noun: Noun) -> Result<HashMap<u64, Vec<Felt>>, JetErr> {
+    HoonMapIter::from(noun)
+        .map(|node| {
+            let [idx, coset] = node.uncell()?;
+            Ok((fri_index(idx)?, fri_fpoly(coset)?))
+        })
+        .collect()
+}
+
+/// `++fp-ifft`, or `None` where the Hoon would crash.
+fn fp_ifft(p: &[Felt]) -> Option<Vec<Felt>> {
+    let len = p.len() as u64;
+    if len == 0 {
+        return None;
+    }
+    let root = Belt(len).ordered_root().ok()?;
+    let inverse_root = Felt::lift(Belt(binv(root.0)));
+    Some(fpscal_(
+        &Felt::lift(Belt(binv(len))),
+        &fp_ntt(p, &inverse_root),
+    ))
+}
+
+/// `++levy` over per-query results, where `None` stands for a query that crashes.
+fn levy(results: impl IntoIterator<Item = Option<bool>>) -> Option<bool> {
+    for result in results {
+        if !result? {
+            return Some(false);
+        }
+    }
+    Some(true)
+}
+
+impl FriFolds {
+    /// Reads the sample of `++check-folds` and `folding-deg` from the `fri-input` of its door.
+    pub fn from_subject(subject: Noun) -> Result<Self, JetErr> {
+        let sam = slot(subject, 6)?;
+        let last_codeword = slot(sam, 30)?;
+        Ok(FriFolds {
+            prev_indices: HoonList::try_from(slot(sam, 2)?)?
+                .map(fri_index)
+                .collect::<Result<_, _>>()?,
+            prev_cosets: fri_cosets(slot(sam, 6)?)?,
+            cosets: fri_cosets(slot(sam, 14)?)?,
+            last_codeword: if last_codeword.is_atom() {
+                None
+            } else {
+                Some(fri_fpoly(slot(last_codeword, 3)?)?)
+            },
+            alpha: fri_felt(slot(sam, 62)?)?,
+            round_offset: fri_felt(slot(sam, 126)?)?,
+            omega: fri_felt(slot(sam, 254)?)?,
+            prev_len: fri_index(slot(sam, 510)?)?,
+            new_len: fri_index(slot(sam, 511)?)?,
+            folding_deg: fri_index(slot(slot(subject, 30)?, 63)?)?,
+        })
+    }
+
+    /// Fold the coset `prev_idx` opens and compare it with the next codeword, or `None` where the
+    /// Hoon would crash.
+    fn check_query(&self, prev_idx: u64) -> Option<bool> {
+        let prev_coset_idx = prev_idx.checked_rem(self.prev_len.checked_div(self.folding_deg)?)?;
+        let coeffs = fp_ifft(self.prev_cosets.get(&prev_coset_idx)?)?;
+        let mut denominator = Felt::zero();
+        fmul(
+            &self.round_offset,
+            &fpow_(&self.omega, prev_coset_idx),
+            &mut denominator,
+        );
+        if denominator.is_zero() {
+            return None;
+        }
+        let mut eval_point = Felt::zero();
+        fdiv(&self.alpha, &denominator, &mut eval_point);
+        let folded = fpeval(&coeffs, eval_point);
+
+        let new_cosets_len = self.new_len.checked_div(self.folding_deg)?;
+        let new_coset_idx = prev_coset_idx.checked_rem(new_cosets_len)?;
+        let new_codeword_val = match &self.last_codeword {
+            Some(last_codeword) => *last_codeword.get(prev_coset_idx as usize)?,
+            None => {
+                let entry = prev_coset_idx / new_cosets_len;
+                *self.cosets.get(&new_coset_idx)?.get(entry as usize)?
+            }
+        };
+        Some(folded == new_codeword_val)
+    }
+
+    /// Check every query on the verifier pool. Results are combined in query order, so a crash
+    /// after the first bad fold still answers `Some(false)`, as `++levy` would.
+    pub fn check(&self) -> Option<bool> {
+        let results: Vec<Option<bool>> = verifier_pool().install(|| {
+            self.prev_indices
+                .par_iter()
+                .map(|prev_idx| self.check_query(*prev_idx))
+                .collect()
+        });
+        levy(results)
+    }
+
+    /// [`Self::check`] one query at a time on the calling thread.
+    pub fn check_serial(&self) -> Option<bool> {
+        levy(
+            self.prev_indices
+                .iter()
+                .map(|prev_idx| self.check_query(*prev_idx)),
+        )
+    }
+}
+
+/// `++check-folds:fri-door`
+///
+/// Punts wherever the Hoon would crash, so the crash comes from the Nock itself.
+pub fn check_folds_jet(_context: &mut Context, subject: Noun) -> Result<Noun, JetErr> {
+    match FriFolds::from_subject(subject)?.check() {
+        Some(true) => Ok(YES),
+        Some(false) => Ok(NO),
+        None => Err(JetErr::Punt),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn leaf(i: u64) -> Digest {
+        [i, i + 1, i + 2, i + 3, i + 4]
+    }
+
+    /// Proofs for every leaf of a four-leaf tree, whose leaves sit at axes 4 through 7.
+    fn four_leaf_proofs() -> Vec<MerkData> {
+        let leaves: Vec<Digest> = (0..4).map(leaf).collect();
+        let left = hash_ten_cell(&leaves[0], &leaves[1]);
+        let right = hash_ten_cell(&leaves[2], &leaves[3]);
+        let root = hash_ten_cell(&left, &right);
+        (0..4)
+            .map(|i| MerkData {
+                leaf: leaves[i],
+                axis: 4 + i as u64,
+                root,
+                path: vec![leaves[i ^ 1], if i < 2 { right } else { left }],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn verifies_valid_proofs() {
+        let proofs = four_leaf_proofs();
+        assert!(proofs.iter().all(MerkData::verify));
+        assert!(verify_merk_proofs(&proofs));
+    }
+
+    #[test]
+    fn rejects_bad_proofs() {
+        let mut proofs = four_leaf_proofs();
+        proofs[2].axis = 5;
+        assert!(!proofs[2].verify());
+        assert!(!verify_merk_proofs(&proofs));
+
+        let mut proof = four_leaf_proofs().remove(0);
+        proof.path.push(leaf(9));
+        assert!(!proof.verify());
+
+        let mut proof = four_leaf_proofs().remove(0);
+        proof.axis = 0;
+        assert!(!proof.verify());
+    }
+
+    /// A round of eight cosets of eight folding into a single coset of the next codeword, with
+    /// every coset opened once.
+    fn fri_round(last: bool) -> FriFolds {
+        let felt = |i: u64| Felt([Belt(3 * i + 1), Belt(i * i + 5), Belt(7 * i)]);
+        let prev_cosets: HashMap<u64, Vec<Felt>> = (0..8)
+            .map(|c| (c, (0..8).map(|i| felt(8 * c + i)).collect()))
+            .collect();
+        let mut folds = FriFolds {
+            prev_indices: vec![3, 12, 21, 30, 39, 46, 53, 56],
+            prev_cosets,
+            cosets: HashMap::new(),
+            last_codeword: None,
+            alpha: felt(99),
+            round_offset: Felt::lift(Belt(7)),
+            omega: Felt::lift(Belt(64).ordered_root().expect("ordered root")),
+            prev_len: 64,
+            new_len: 8,
+            folding_deg: 8,
+        };
+        let next: Vec<Felt> = (0..8)
+            .map(|c| {
+                let coeffs = fp_ifft(&folds.prev_cosets[&c]).expect("ifft");
+                let mut denominator = Felt::zero();
+                fmul(
+                    &folds.round_offset,
+                    &fpow_(&folds.omega, c),
+                    &mut denominator,
+                );
+                let mut eval_point = Felt::zero();
+                fdiv(&folds.alpha, &denominator, &mut eval_point);
+                fpeval(&coeffs, eval_point)
+            })
+            .collect();
+        if last {
+            folds.last_codeword = Some(next);
+        } else {
+            folds.cosets.insert(0, next);
+        }
+        folds
+    }
+
+    #[test]
+    fn parallel_fold_check_matches_serial() {
+        for last in [false, true] {
+            let folds = fri_round(last);
+            assert_eq!(folds.check_serial(), Some(true));
+            assert_eq!(folds.check(), folds.check_serial());
+
+            // A bad fold anywhere fails the round.
+            let mut bad = folds.clone();
+            match &mut bad.last_codeword {
+                Some(codeword) => codeword[5] = Felt::one(),
+                None => bad.cosets.get_mut(&0).expect("coset")[5] = Felt::one(),
+            }
+            assert_eq!(bad.check_serial(), Some(false));
+            assert_eq!(bad.check(), bad.check_serial());
+
+            // A missing opening crashes the Hoon, unless a bad fold comes first.
+            let mut missing = folds.clone();
+            missing.prev_cosets.remove(&6);
+            assert_eq!(missing.check_serial(), None);
+            assert_eq!(missing.check(), missing.check_serial());
+            let mut bad_then_missing = bad.clone();
+            bad_then_missing.prev_cosets.remove(&6);
+            assert_eq!(bad_then_missing.check_serial(), Some(false));
+            assert_eq!(bad_then_missing.check(), bad_then_missing.check_serial());
+        }
+    }
+
+    #[test]
+    fn batch_matches_individual_checks() {
+        let mut proofs = four_leaf_proofs();
+        proofs.extend(four_leaf_proofs());
+        assert!(verify_merk_batch(&mut proofs));
+
+        // A forged sibling must still fail even though its parent was already computed once.
+        let mut proofs = four_leaf_proofs();
+        proofs[1].path[1] = leaf(9);
+        assert!(!proofs[1].verify());
+        assert!(!verify_merk_batch(&mut proofs));
+
+        assert!(verify_merk_batch(&mut []));
+    }
+}
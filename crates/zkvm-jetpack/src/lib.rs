@@ -3,7 +3,11 @@
 #![cfg_attr(test, allow(clippy::unwrap_used))]
 
 pub mod form;
+pub mod fuzz;
 pub mod hot;
+pub mod instrument;
 pub mod jets;
+pub mod registry;
+pub mod twiddles;
 pub mod utils;
 pub use nockchain_math::based;
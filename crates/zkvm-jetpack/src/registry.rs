@@ -0,0 +1,176 @@
+//! Jets contributed by host applications.
+//!
+//! A NockApp that ships its own accelerators registers them here before booting its kernel, and
+//! [`crate::hot::produce_prover_hot_state`] appends them to the built-in jets. Registration only
+//! affects hot states produced afterwards; a kernel already running keeps the jets it booted with.
+
+use std::sync::Mutex;
+
+use either::Either::{self, Left};
+use nockvm::jets::hot::{HotEntry, K_138};
+use nockvm::jets::Jet;
+use thiserror::Error;
+
+use crate::instrument::path_name;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum JetRegistrationError {
+    #[error("jet path is empty")]
+    EmptyPath,
+    #[error("jet path segment {0:?} is not a valid @tas")]
+    InvalidSegment(String),
+    #[error("jet axis must be at least 1")]
+    InvalidAxis,
+    #[error("a jet is already registered at {path} (axis {axis})")]
+    Duplicate { path: String, axis: u64 },
+}
+
+static REGISTERED: Mutex<Vec<HotEntry>> = Mutex::new(Vec::new());
+
+/// A hot-state path that need not be `'static` yet.
+type Path<'a> = [Either<&'a [u8], (u64, u64)>];
+
+fn valid_tas(segment: &str) -> bool {
+    let mut chars = segment.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a == b)
+}
+
+/// Register a jet for the arm at `axis` of the core named by `path`, relative to the `%k.138`
+/// root, e.g. `&["one", "two", "tri", "qua", "pen", "my-lib", "my-arm"]`.
+///
+/// Once the registration is known to succeed, the path is leaked so it can live in the `'static`
+/// hot state, which is fine for the handful of registrations an application makes at startup.
+pub fn register_jet(path: &[&str], axis: u64, jet: Jet) -> Result<(), JetRegistrationError> {
+    if path.is_empty() {
+        return Err(JetRegistrationError::EmptyPath);
+    }
+    if let Some(segment) = path.iter().find(|segment| !valid_tas(segment)) {
+        return Err(JetRegistrationError::InvalidSegment(segment.to_string()));
+    }
+    let full: Vec<Either<&[u8], (u64, u64)>> = std::iter::once(K_138)
+        .chain(path.iter().map(|segment| Left(segment.as_bytes())))
+        .collect();
+
+    let mut registered = REGISTERED.lock().expect("jet registry poisoned");
+    check_entries(&registered, &[(&full, axis)])?;
+    let full: Vec<Either<&'static [u8], (u64, u64)>> = std::iter::once(K_138)
+        .chain(
+            path.iter()
+                .map(|segment| Left(&*Box::leak(segment.as_bytes().into()))),
+        )
+        .collect();
+    registered.push((Box::leak(full.into_boxed_slice()), axis, jet));
+    Ok(())
+}
+
+/// Register fully specified hot-state entries, for applications that keep their jets in a
+/// `&[HotEntry]` table like the ones in [`crate::hot`].
+///
+/// Either every entry is registered or, if any of them collides with a built-in or previously
+/// registered jet, none is.
+pub fn register_hot_entries(entries: &[HotEntry]) -> Result<(), JetRegistrationError> {
+    let mut registered = REGISTERED.lock().expect("jet registry poisoned");
+    let paths: Vec<(&Path, u64)> = entries
+        .iter()
+        .map(|(path, axis, _)| (*path, *axis))
+        .collect();
+    check_entries(&registered, &paths)?;
+    registered.extend_from_slice(entries);
+    Ok(())
+}
+
+/// Check that `entries` can be added alongside the built-in jets and `registered`.
+fn check_entries(
+    registered: &[HotEntry],
+    entries: &[(&Path, u64)],
+) -> Result<(), JetRegistrationError> {
+    let builtin = crate::hot::builtin_hot_state();
+    for (i, (path, axis)) in entries.iter().enumerate() {
+        if path.is_empty() {
+            return Err(JetRegistrationError::EmptyPath);
+        }
+        if *axis == 0 {
+            return Err(JetRegistrationError::InvalidAxis);
+        }
+        let taken = builtin
+            .iter()
+            .chain(registered)
+            .map(|(other, other_axis, _)| (*other, *other_axis))
+            .chain(entries[..i].iter().copied())
+            .any(|(other, other_axis)| other_axis == *axis && same_path(path, other));
+        if taken {
+            return Err(JetRegistrationError::Duplicate {
+                path: path_name(path),
+                axis: *axis,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Every entry registered so far, in registration order.
+pub fn registered_hot_entries() -> Vec<HotEntry> {
+    REGISTERED.lock().expect("jet registry poisoned").clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use nockvm::interpreter::Context;
+    use nockvm::jets::Result;
+    use nockvm::noun::{Noun, D};
+
+    use super::*;
+
+    fn plugin(_context: &mut Context, _subject: Noun) -> Result {
+        Ok(D(0))
+    }
+
+    #[test]
+    fn registers_and_rejects_duplicates() {
+        let path = ["one", "two", "registry-test", "plugin"];
+        register_jet(&path, 1, plugin).expect("first registration");
+        assert_eq!(
+            register_jet(&path, 1, plugin),
+            Err(JetRegistrationError::Duplicate {
+                path: "k138/one/two/registry-test/plugin".to_string(),
+                axis: 1,
+            })
+        );
+        // A different arm of the same core is fine.
+        register_jet(&path, 2, plugin).expect("second axis");
+
+        let hot_state = crate::hot::produce_prover_hot_state();
+        let ours: Vec<u64> = hot_state
+            .iter()
+            .filter(|(p, _, _)| path_name(p) == "k138/one/two/registry-test/plugin")
+            .map(|(_, axis, _)| *axis)
+            .collect();
+        assert_eq!(ours, vec![1, 2]);
+    }
+
+    #[test]
+    fn rejects_invalid_paths_and_builtins() {
+        assert_eq!(
+            register_jet(&[], 1, plugin),
+            Err(JetRegistrationError::EmptyPath)
+        );
+        assert_eq!(
+            register_jet(&["one", "Bad"], 1, plugin),
+            Err(JetRegistrationError::InvalidSegment("Bad".to_string()))
+        );
+        assert_eq!(
+            register_jet(&["one", "registry-test", "axis"], 0, plugin),
+            Err(JetRegistrationError::InvalidAxis)
+        );
+        let (path, axis, _) = crate::hot::BASE_FIELD_JETS[0];
+        assert!(matches!(
+            register_hot_entries(&[(path, axis, plugin)]),
+            Err(JetRegistrationError::Duplicate { .. })
+        ));
+    }
+}
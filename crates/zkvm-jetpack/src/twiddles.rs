@@ -0,0 +1,237 @@
+//! Process-wide cache of NTT twiddle tables.
+//!
+//! Proving and verification run the same few transform sizes over and over, always with the
+//! domain's canonical root, so the per-layer twiddle factors are computed once per
+//! `(size, root)` and shared between jet calls. The cache holds at most
+//! [`twiddle_cache_capacity`] bytes (`ZKVM_TWIDDLE_CACHE_MB`, default 256) and evicts the least
+//! recently used tables past that; a table that would not fit on its own is computed and not
+//! cached.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::form::belt::Belt;
+use crate::form::bpoly::bp_ntt_twiddles;
+use crate::form::felt::Felt;
+use crate::form::fpoly::fp_ntt_twiddles;
+
+pub const TWIDDLE_CACHE_ENV: &str = "ZKVM_TWIDDLE_CACHE_MB";
+const DEFAULT_CAPACITY_MB: usize = 256;
+
+/// Tables shorter than this are cheaper to recompute than to look up.
+const MIN_CACHED_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Base { n: usize, root: u64 },
+    Ext { n: usize, root: [u64; 3] },
+}
+
+#[derive(Clone)]
+enum Table {
+    Base(Arc<[Belt]>),
+    Ext(Arc<[Felt]>),
+}
+
+impl Table {
+    fn bytes(&self) -> usize {
+        match self {
+            Table::Base(table) => std::mem::size_of_val(&**table),
+            Table::Ext(table) => std::mem::size_of_val(&**table),
+        }
+    }
+}
+
+struct Entry {
+    table: Table,
+    last_used: u64,
+}
+
+/// Counters for the cache as a whole.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TwiddleCacheStats {
+    pub entries: usize,
+    pub bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+struct TwiddleCache {
+    entries: HashMap<Key, Entry>,
+    capacity: usize,
+    clock: u64,
+    stats: TwiddleCacheStats,
+}
+
+impl TwiddleCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+            clock: 0,
+            stats: TwiddleCacheStats::default(),
+        }
+    }
+
+    fn get(&mut self, key: &Key) -> Option<Table> {
+        self.clock += 1;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = self.clock;
+                self.stats.hits += 1;
+                Some(entry.table.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: Key, table: Table) {
+        let bytes = table.bytes();
+        if bytes > self.capacity {
+            return;
+        }
+        while self.stats.bytes + bytes > self.capacity {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+            let evicted = self.entries.remove(&oldest).expect("oldest entry exists");
+            self.stats.bytes -= evicted.table.bytes();
+            self.stats.entries -= 1;
+            self.stats.evictions += 1;
+        }
+        let previous = self.entries.insert(
+            key,
+            Entry {
+                table,
+                last_used: self.clock,
+            },
+        );
+        // Two threads can miss on the same key and both insert; keep the byte count honest.
+        match previous {
+            Some(previous) => self.stats.bytes -= previous.table.bytes(),
+            None => self.stats.entries += 1,
+        }
+        self.stats.bytes += bytes;
+    }
+}
+
+/// The configured cap in bytes.
+pub fn twiddle_cache_capacity() -> usize {
+    std::env::var(TWIDDLE_CACHE_ENV)
+        .ok()
+        .and_then(|mb| mb.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_CAPACITY_MB)
+        .saturating_mul(1 << 20)
+}
+
+static CACHE: Lazy<Mutex<TwiddleCache>> =
+    Lazy::new(|| Mutex::new(TwiddleCache::new(twiddle_cache_capacity())));
+
+fn cached(key: Key, compute: impl FnOnce() -> Table) -> Table {
+    if let Some(table) = CACHE.lock().expect("twiddle cache poisoned").get(&key) {
+        return table;
+    }
+    // Compute outside the lock so a large table doesn't stall every other NTT.
+    let table = compute();
+    CACHE
+        .lock()
+        .expect("twiddle cache poisoned")
+        .insert(key, table.clone());
+    table
+}
+
+/// Twiddles for a base-field NTT of length `n` with `root`, from the cache when possible.
+pub fn bp_twiddles(n: usize, root: &Belt) -> Arc<[Belt]> {
+    if n < MIN_CACHED_LEN {
+        return bp_ntt_twiddles(n, root).into();
+    }
+    let key = Key::Base { n, root: root.0 };
+    match cached(key, || Table::Base(bp_ntt_twiddles(n, root).into())) {
+        Table::Base(table) => table,
+        Table::Ext(_) => unreachable!("base-field key holds an extension-field table"),
+    }
+}
+
+/// Twiddles for an extension-field NTT of length `n` with `root`, from the cache when possible.
+pub fn fp_twiddles(n: usize, root: &Felt) -> Arc<[Felt]> {
+    if n < MIN_CACHED_LEN {
+        return fp_ntt_twiddles(n, root).into();
+    }
+    let key = Key::Ext {
+        n,
+        root: root.0.map(|belt| belt.0),
+    };
+    match cached(key, || Table::Ext(fp_ntt_twiddles(n, root).into())) {
+        Table::Ext(table) => table,
+        Table::Base(_) => unreachable!("extension-field key holds a base-field table"),
+    }
+}
+
+pub fn twiddle_cache_stats() -> TwiddleCacheStats {
+    CACHE.lock().expect("twiddle cache poisoned").stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_key(n: usize) -> Key {
+        Key::Base { n, root: 7 }
+    }
+
+    fn base_table(len: usize) -> Table {
+        Table::Base(vec![Belt(1); len].into())
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let entry_bytes = 64 * std::mem::size_of::<Belt>();
+        let mut cache = TwiddleCache::new(2 * entry_bytes);
+        cache.insert(base_key(1), base_table(64));
+        cache.insert(base_key(2), base_table(64));
+        assert!(cache.get(&base_key(1)).is_some());
+        cache.insert(base_key(3), base_table(64));
+
+        assert!(cache.get(&base_key(2)).is_none());
+        assert!(cache.get(&base_key(1)).is_some());
+        assert!(cache.get(&base_key(3)).is_some());
+        assert_eq!(cache.stats.entries, 2);
+        assert_eq!(cache.stats.bytes, 2 * entry_bytes);
+        assert_eq!(cache.stats.evictions, 1);
+    }
+
+    #[test]
+    fn oversized_tables_are_not_cached() {
+        let mut cache = TwiddleCache::new(16);
+        cache.insert(base_key(1), base_table(64));
+        assert!(cache.get(&base_key(1)).is_none());
+        assert_eq!(cache.stats.bytes, 0);
+    }
+
+    #[test]
+    fn cached_tables_match_fresh_ones() {
+        let n = 1 << 6;
+        let root = Belt(n as u64).ordered_root().expect("root");
+        let first = bp_twiddles(n, &root);
+        let second = bp_twiddles(n, &root);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(&*first, bp_ntt_twiddles(n, &root).as_slice());
+
+        let root = Felt::ordered_root(n as u64).expect("root");
+        assert_eq!(
+            &*fp_twiddles(n, &root),
+            fp_ntt_twiddles(n, &root).as_slice()
+        );
+    }
+}
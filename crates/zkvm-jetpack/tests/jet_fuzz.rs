@@ -0,0 +1,44 @@
+//! Runs every fuzz target against the Hoon it replaces.
+//!
+//! Needs the gate library from `make assets/jet-fuzz.jam`, passed in `ZKVM_JET_FUZZ_JAM`; without
+//! it the test is skipped. `ZKVM_JET_FUZZ_CASES` sets the number of samples per target (default
+//! 200) and `ZKVM_JET_FUZZ_TARGETS` restricts the run to a comma-separated list of gate names.
+
+use quickcheck::Gen;
+use zkvm_jetpack::fuzz::{JetFuzzer, FUZZ_JAM_ENV, FUZZ_TARGETS};
+
+const DEFAULT_CASES: usize = 200;
+/// Upper bound on polynomial and list lengths in generated samples.
+const GEN_SIZE: usize = 32;
+
+#[test]
+fn jets_match_nock() {
+    let Ok(jam_path) = std::env::var(FUZZ_JAM_ENV) else {
+        eprintln!("{FUZZ_JAM_ENV} not set, skipping jet fuzzing");
+        return;
+    };
+    let jam = std::fs::read(&jam_path).unwrap_or_else(|err| panic!("reading {jam_path}: {err}"));
+    let cases = std::env::var("ZKVM_JET_FUZZ_CASES")
+        .ok()
+        .and_then(|cases| cases.parse().ok())
+        .unwrap_or(DEFAULT_CASES);
+    let only = std::env::var("ZKVM_JET_FUZZ_TARGETS").ok();
+
+    let mut gen = Gen::new(GEN_SIZE);
+    for target in FUZZ_TARGETS {
+        if let Some(only) = &only {
+            if !only.split(',').any(|name| name.trim() == target.name) {
+                continue;
+            }
+        }
+        // A fresh fuzzer per target keeps each one's stack usage bounded.
+        let mut fuzzer = JetFuzzer::boot(&jam).expect("boot jet-fuzz library");
+        match fuzzer.run(target, &mut gen, cases) {
+            Ok(report) => eprintln!(
+                "%{}: {} agreed, {} crashed in both, {} punted",
+                target.name, report.agreed, report.crashed, report.punted
+            ),
+            Err(err) => panic!("{err}"),
+        }
+    }
+}
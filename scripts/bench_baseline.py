@@ -0,0 +1,88 @@
+#!/usr/bin/env python3
+"""Save or check criterion results against a stored baseline.
+
+    bench_baseline.py save BASELINE      record the latest run in BASELINE
+    bench_baseline.py compare BASELINE   fail if any benchmark got slower than the threshold, or
+                                         if BASELINE or any benchmark in it is missing
+
+Results are read from criterion's output under target/criterion (or $CRITERION_DIR). The
+baseline maps each benchmark id to its mean time in nanoseconds.
+"""
+
+import argparse
+import json
+import os
+import sys
+from pathlib import Path
+
+
+def latest_results(criterion_dir):
+    results = {}
+    for benchmark in criterion_dir.glob("**/new/benchmark.json"):
+        estimates = benchmark.with_name("estimates.json")
+        if not estimates.exists():
+            continue
+        full_id = json.loads(benchmark.read_text())["full_id"]
+        mean = json.loads(estimates.read_text())["mean"]["point_estimate"]
+        results[full_id] = mean
+    return results
+
+
+def save(results, baseline):
+    baseline.write_text(json.dumps(dict(sorted(results.items())), indent=2) + "\n")
+    print(f"saved {len(results)} benchmarks to {baseline}")
+
+
+def compare(results, baseline, threshold):
+    stored = json.loads(baseline.read_text())
+    regressions = 0
+    missing = 0
+    for full_id, base in sorted(stored.items()):
+        if full_id not in results:
+            missing += 1
+            print(f"  MISSING   {full_id}")
+            continue
+        change = results[full_id] / base - 1
+        if change > threshold:
+            regressions += 1
+            status = "REGRESSED"
+        elif change < -threshold:
+            status = "improved"
+        else:
+            status = "ok"
+        print(f"  {status:<9} {full_id}: {base:.0f}ns -> {results[full_id]:.0f}ns ({change:+.1%})")
+    for full_id in sorted(results.keys() - stored.keys()):
+        print(f"  new       {full_id}")
+    if missing:
+        print(f"{missing} benchmark(s) in the baseline did not run; re-record it if they were removed")
+    if regressions:
+        print(f"{regressions} benchmark(s) regressed by more than {threshold:.0%}")
+    return 1 if missing or regressions else 0
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("command", choices=["save", "compare"])
+    parser.add_argument("baseline", type=Path)
+    parser.add_argument(
+        "--threshold",
+        type=float,
+        default=0.10,
+        help="fractional slowdown that counts as a regression (default 0.10)",
+    )
+    args = parser.parse_args()
+
+    if args.command == "compare" and not args.baseline.exists():
+        sys.exit(f"error: no baseline at {args.baseline}; record one with `save` and commit it")
+    criterion_dir = Path(os.environ.get("CRITERION_DIR", "target/criterion"))
+    results = latest_results(criterion_dir)
+    if not results:
+        sys.exit(f"error: no criterion results under {criterion_dir}; run the benchmarks first")
+    if args.command == "save":
+        save(results, args.baseline)
+        return 0
+    return compare(results, args.baseline, args.threshold)
+
+
+if __name__ == "__main__":
+    sys.exit(main())